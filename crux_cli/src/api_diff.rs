@@ -0,0 +1,254 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{diff, workspace};
+
+pub(crate) type Registry = BTreeMap<String, Value>;
+
+/// The names of the app's root types, which `TypeGen` writes next to the registry lockfile
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub(crate) struct Manifest {
+    pub event: Option<String>,
+    pub view_model: Option<String>,
+    pub effect: Option<String>,
+    pub capabilities: BTreeMap<String, String>,
+    pub deny_unknown_fields: BTreeSet<String>,
+}
+
+impl Default for Manifest {
+    /// The conventional names, for lockfiles written without a manifest
+    fn default() -> Self {
+        Self {
+            event: Some("Event".to_string()),
+            view_model: Some("ViewModel".to_string()),
+            effect: Some("Effect".to_string()),
+            capabilities: BTreeMap::new(),
+            deny_unknown_fields: BTreeSet::new(),
+        }
+    }
+}
+
+impl Manifest {
+    /// The root types, in the order event, view model, effect
+    pub(crate) fn roots(&self) -> impl Iterator<Item = &str> {
+        [&self.event, &self.view_model, &self.effect]
+            .into_iter()
+            .filter_map(|name| name.as_deref())
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Changes {
+    added: Vec<String>,
+    removed: Vec<String>,
+    changed: Vec<String>,
+}
+
+pub(crate) fn api_diff(base: &str, registry: Option<&Path>) -> Result<()> {
+    for (_, path) in &registries(registry)? {
+        let current = parse_registry(path, &read(path)?)?;
+        let previous = parse_registry(path, &git_show(base, path)?)?;
+
+        println!("{:-<80}\nBase:    {base}\nCurrent: {}", "", path.display());
+        show(&changes(&previous, &current), &previous, &current);
+    }
+
+    Ok(())
+}
+
+/// The registry lockfiles to work with, with the name of the core each belongs to.
+/// Uses the given lockfile if any (named after the file), otherwise the `registry`
+/// of each core in Crux.toml.
+pub(crate) fn registries(registry: Option<&Path>) -> Result<Vec<(String, PathBuf)>> {
+    if let Some(path) = registry {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "registry".to_string());
+        return Ok(vec![(name, path.to_path_buf())]);
+    }
+
+    let workspace = workspace::read_config()?;
+    let registries: Vec<_> = workspace
+        .cores
+        .values()
+        .filter_map(|core| Some((core.name.clone(), core.registry.clone()?)))
+        .collect();
+    if registries.is_empty() {
+        bail!("no registry lockfile given and no core in Crux.toml specifies a `registry`");
+    }
+    Ok(registries)
+}
+
+fn git_show(rev: &str, path: &Path) -> Result<String> {
+    // the `./` prefix makes git resolve the path relative to the current directory
+    let object = format!("{rev}:./{}", path.display());
+    let output = Command::new("git")
+        .arg("show")
+        .arg(&object)
+        .output()
+        .context("could not run git")?;
+    if !output.status.success() {
+        bail!(
+            "could not read {object} from git: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).with_context(|| format!("{object} is not UTF-8"))
+}
+
+/// The contents of the file at `path`, with the path in the error if it can't be read
+pub(crate) fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))
+}
+
+pub(crate) fn parse_registry(path: &Path, contents: &str) -> Result<Registry> {
+    match serde_json::from_str(contents) {
+        Ok(registry) => Ok(registry),
+        Err(e) => bail!("{} is not a valid registry lockfile: {e}", path.display()),
+    }
+}
+
+/// The manifest written next to the registry lockfile at `path`, e.g. `registry.manifest.json`
+/// for `registry.json`, or the conventional names if there is none
+pub(crate) fn read_manifest(path: &Path) -> Result<Manifest> {
+    let path = path.with_extension("manifest.json");
+    if !path.exists() {
+        return Ok(Manifest::default());
+    }
+    match serde_json::from_str(&read(&path)?) {
+        Ok(manifest) => Ok(manifest),
+        Err(e) => bail!("{} is not a valid registry manifest: {e}", path.display()),
+    }
+}
+
+fn changes(previous: &Registry, current: &Registry) -> Changes {
+    let mut changes = Changes::default();
+    for (name, format) in current {
+        match previous.get(name) {
+            None => changes.added.push(name.clone()),
+            Some(previous_format) if previous_format != format => {
+                changes.changed.push(name.clone());
+            }
+            Some(_) => {}
+        }
+    }
+    for name in previous.keys() {
+        if !current.contains_key(name) {
+            changes.removed.push(name.clone());
+        }
+    }
+    changes
+}
+
+fn show(changes: &Changes, previous: &Registry, current: &Registry) {
+    if changes == &Changes::default() {
+        println!("No changes to shared types");
+        return;
+    }
+    for (heading, names) in [("Added", &changes.added), ("Removed", &changes.removed)] {
+        if !names.is_empty() {
+            println!("{heading} types:");
+            for name in names {
+                println!("  {name}");
+            }
+            println!();
+        }
+    }
+    for name in &changes.changed {
+        let desired = pretty(&current[name]);
+        let actual = pretty(&previous[name]);
+        diff::show(Path::new(name), &desired, &actual);
+    }
+}
+
+fn pretty(format: &Value) -> String {
+    let mut s = serde_json::to_string_pretty(format).expect("JSON values should serialize");
+    s.push('\n');
+    s
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn registry(value: Value) -> Registry {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_no_changes() {
+        let previous = registry(json!({ "Event": { "ENUM": {} } }));
+        let current = previous.clone();
+
+        assert_eq!(changes(&previous, &current), Changes::default());
+    }
+
+    #[test]
+    fn test_added_removed_and_changed() {
+        let previous = registry(json!({
+            "Event": { "ENUM": { "0": { "Increment": "UNIT" } } },
+            "Old": "UNITSTRUCT",
+            "ViewModel": { "STRUCT": [{ "count": "STR" }] },
+        }));
+        let current = registry(json!({
+            "Event": { "ENUM": { "0": { "Increment": "UNIT" }, "1": { "Decrement": "UNIT" } } },
+            "New": "UNITSTRUCT",
+            "ViewModel": { "STRUCT": [{ "count": "STR" }] },
+        }));
+
+        assert_eq!(
+            changes(&previous, &current),
+            Changes {
+                added: vec!["New".to_string()],
+                removed: vec!["Old".to_string()],
+                changed: vec!["Event".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_registry() {
+        let result = parse_registry(Path::new("registry.json"), "not json");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_unreadable_registry() {
+        let error = read(Path::new("no/such/registry.json")).unwrap_err();
+
+        // the path is in the message, and the cause is kept in the chain below it
+        assert_eq!(error.to_string(), "could not read no/such/registry.json");
+        assert_eq!(error.chain().count(), 2);
+    }
+
+    #[test]
+    fn test_manifest_roots() {
+        let manifest: Manifest = serde_json::from_value(json!({
+            "event": "CounterEvent",
+            "view_model": null,
+            "capabilities": { "Render": "RenderOperation" },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            manifest.roots().collect::<Vec<_>>(),
+            ["CounterEvent", "Effect"]
+        );
+        assert_eq!(
+            read_manifest(Path::new("missing/registry.json")).unwrap(),
+            Manifest::default()
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::Path,
+};
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::{
+    api_diff::{parse_registry, read, read_manifest, registries, Manifest, Registry},
+    args::OutputArgs,
+    generated::Generated,
+};
+
+pub(crate) fn api_docs(
+    registry: Option<&Path>,
+    output: Option<&Path>,
+    args: &OutputArgs,
+) -> Result<()> {
+    if output.is_none() && (args.dry_run || args.clean) {
+        bail!("--dry-run and --clean need an --output directory");
+    }
+
+    let mut generated = output.map(|dir| Generated::new(dir, ".md"));
+    for (name, path) in &registries(registry)? {
+        let registry = parse_registry(path, &read(path)?)?;
+        let docs = render(name, &registry, &read_manifest(path)?);
+
+        match &mut generated {
+            Some(generated) => generated.add(name, "", docs),
+            None => print!("{docs}"),
+        }
+    }
+
+    match generated {
+        Some(generated) => generated.write(args),
+        None => Ok(()),
+    }
+}
+
+/// Render a markdown reference of all the types in the registry.
+///
+/// The registry doesn't include doc comments, so the reference only describes
+/// the shape of each type and which parts of the app use it.
+fn render(core: &str, registry: &Registry, manifest: &Manifest) -> String {
+    let users = users(registry, manifest);
+    let mut out = String::new();
+
+    writeln!(out, "# Shared types: {core}\n").unwrap();
+    writeln!(
+        out,
+        "The types the `{core}` core shares with its shells, as they are serialized across the FFI boundary.\n"
+    )
+    .unwrap();
+    for name in registry.keys() {
+        writeln!(out, "- [{name}](#{})", anchor(name)).unwrap();
+    }
+
+    for (name, format) in registry {
+        writeln!(out, "\n## {name}\n").unwrap();
+        if let Some(users) = users.get(name) {
+            let users: Vec<_> = users.iter().map(|user| format!("`{user}`")).collect();
+            writeln!(out, "Used by {}\n", users.join(", ")).unwrap();
+        }
+        container(&mut out, format);
+    }
+
+    out
+}
+
+fn container(out: &mut String, format: &Value) {
+    match format {
+        Value::String(unit) if unit == "UNITSTRUCT" => writeln!(out, "Unit struct").unwrap(),
+        Value::Object(_) => {
+            let Some((kind, body)) = entry(format) else {
+                return;
+            };
+            match kind.as_str() {
+                "NEWTYPESTRUCT" => writeln!(out, "Newtype struct of {}", type_ref(body)).unwrap(),
+                "TUPLESTRUCT" => writeln!(out, "Tuple struct of {}", tuple(body)).unwrap(),
+                "STRUCT" => {
+                    writeln!(out, "| Field | Type |\n| --- | --- |").unwrap();
+                    for (field, format) in named(body) {
+                        writeln!(out, "| `{field}` | {} |", type_ref(format)).unwrap();
+                    }
+                }
+                "ENUM" => {
+                    writeln!(out, "| Index | Variant | Data |\n| --- | --- | --- |").unwrap();
+                    let mut variants: Vec<_> = body.as_object().into_iter().flatten().collect();
+                    variants.sort_by_key(|(index, _)| index.parse::<u32>().unwrap_or(u32::MAX));
+                    for (index, variant) in variants {
+                        if let Some((name, format)) = entry(variant) {
+                            writeln!(out, "| {index} | `{name}` | {} |", variant_data(format))
+                                .unwrap();
+                        }
+                    }
+                }
+                _ => writeln!(out, "`{kind}`").unwrap(),
+            }
+        }
+        other => writeln!(out, "`{other}`").unwrap(),
+    }
+}
+
+fn variant_data(format: &Value) -> String {
+    match format {
+        Value::String(unit) if unit == "UNIT" => String::new(),
+        Value::Object(_) => match entry(format) {
+            Some((kind, body)) if kind == "NEWTYPE" => type_ref(body),
+            Some((kind, body)) if kind == "TUPLE" => tuple(body),
+            Some((kind, body)) if kind == "STRUCT" => {
+                let fields: Vec<_> = named(body)
+                    .map(|(field, format)| format!("`{field}`: {}", type_ref(format)))
+                    .collect();
+                format!("{{ {} }}", fields.join(", "))
+            }
+            _ => format!("`{format}`"),
+        },
+        other => format!("`{other}`"),
+    }
+}
+
+/// A reference to a type, e.g. a field's type, with links to other shared types.
+/// Generic brackets are escaped, so markdown doesn't mistake them for HTML.
+fn type_ref(format: &Value) -> String {
+    match format {
+        Value::String(primitive) => match primitive.as_str() {
+            "UNIT" => "()".to_string(),
+            "STR" => "String".to_string(),
+            "BYTES" => "Bytes".to_string(),
+            other => other.to_lowercase(),
+        },
+        Value::Object(_) => match entry(format) {
+            Some((kind, body)) => match kind.as_str() {
+                "TYPENAME" => {
+                    let name = body.as_str().unwrap_or_default();
+                    format!("[{name}](#{})", anchor(name))
+                }
+                "OPTION" => format!("Option\\<{}>", type_ref(body)),
+                "SEQ" => format!("Vec\\<{}>", type_ref(body)),
+                "MAP" => format!(
+                    "Map\\<{}, {}>",
+                    type_ref(&body["KEY"]),
+                    type_ref(&body["VALUE"])
+                ),
+                "TUPLE" => tuple(body),
+                "TUPLEARRAY" => format!("[{}; {}]", type_ref(&body["CONTENT"]), body["SIZE"]),
+                _ => format!("`{kind}`"),
+            },
+            None => String::new(),
+        },
+        other => format!("`{other}`"),
+    }
+}
+
+fn tuple(formats: &Value) -> String {
+    let formats: Vec<_> = formats
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(type_ref)
+        .collect();
+    format!("({})", formats.join(", "))
+}
+
+/// The named fields in a list of single-entry maps, the way the registry stores struct fields.
+fn named(fields: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    fields.as_array().into_iter().flatten().filter_map(entry)
+}
+
+/// The only entry of a single-entry map, e.g. a struct field or an enum variant
+fn entry(map: &Value) -> Option<(&String, &Value)> {
+    map.as_object()?.iter().next()
+}
+
+/// The headings' anchors, as generated by GitHub and mdBook
+fn anchor(name: &str) -> String {
+    name.to_lowercase()
+}
+
+/// The parts of the app which use each type: the event, the view model, and each of the
+/// effect's variants, named after the capability.
+fn users(registry: &Registry, manifest: &Manifest) -> BTreeMap<String, BTreeSet<String>> {
+    let mut roots = vec![];
+    for root in [&manifest.event, &manifest.view_model]
+        .into_iter()
+        .flatten()
+    {
+        if let Some(format) = registry.get(root) {
+            roots.push((root.clone(), format.clone()));
+        }
+    }
+    if let Some((effect, variants)) = manifest.effect.as_ref().and_then(|effect| {
+        let variants = registry.get(effect)?.get("ENUM")?.as_object()?;
+        Some((effect, variants))
+    }) {
+        for (name, format) in variants.values().filter_map(entry) {
+            roots.push((format!("{effect}::{name}"), format.clone()));
+        }
+    }
+
+    let mut users: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (root, format) in roots {
+        let mut seen = BTreeSet::new();
+        if registry.contains_key(&root) {
+            seen.insert(root.clone());
+        }
+        let mut pending = vec![format];
+        while let Some(format) = pending.pop() {
+            for name in type_names(&format) {
+                if seen.insert(name.clone()) {
+                    if let Some(format) = registry.get(&name) {
+                        pending.push(format.clone());
+                    }
+                }
+            }
+        }
+        for name in seen {
+            users.entry(name).or_default().insert(root.clone());
+        }
+    }
+
+    users
+}
+
+pub(crate) fn type_names(format: &Value) -> Vec<String> {
+    match format {
+        Value::Object(object) => object
+            .iter()
+            .flat_map(|(key, value)| match (key.as_str(), value) {
+                ("TYPENAME", Value::String(name)) => vec![name.clone()],
+                _ => type_names(value),
+            })
+            .collect(),
+        Value::Array(values) => values.iter().flat_map(type_names).collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn registry() -> Registry {
+        serde_json::from_value(json!({
+            "Effect": { "ENUM": {
+                "0": { "Http": { "NEWTYPE": { "TYPENAME": "HttpRequest" } } },
+                "1": { "Render": { "NEWTYPE": { "TYPENAME": "RenderOperation" } } },
+            } },
+            "Event": { "ENUM": {
+                "0": { "Increment": "UNIT" },
+                "1": { "Set": { "NEWTYPE": { "OPTION": "I64" } } },
+                "2": { "Move": { "STRUCT": [{ "x": "I32" }, { "y": "I32" }] } },
+            } },
+            "HttpHeader": { "STRUCT": [{ "name": "STR" }, { "value": "STR" }] },
+            "HttpRequest": { "STRUCT": [
+                { "method": "STR" },
+                { "headers": { "SEQ": { "TYPENAME": "HttpHeader" } } },
+                { "body": "BYTES" },
+            ] },
+            "RenderOperation": "UNITSTRUCT",
+            "ViewModel": { "STRUCT": [
+                { "count": "STR" },
+                { "totals": { "MAP": { "KEY": "STR", "VALUE": "U64" } } },
+            ] },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render_types() {
+        let docs = render("shared", &registry(), &Manifest::default());
+
+        assert!(docs.starts_with("# Shared types: shared\n"));
+        assert!(docs.contains("- [HttpHeader](#httpheader)\n"));
+        assert!(docs.contains("| `headers` | Vec\\<[HttpHeader](#httpheader)> |"));
+        assert!(docs.contains("| `totals` | Map\\<String, u64> |"));
+        assert!(docs.contains("| 0 | `Increment` |  |"));
+        assert!(docs.contains("| 1 | `Set` | Option\\<i64> |"));
+        assert!(docs.contains("| 2 | `Move` | { `x`: i32, `y`: i32 } |"));
+        assert!(docs.contains("## RenderOperation\n\nUsed by `Effect::Render`\n\nUnit struct"));
+    }
+
+    #[test]
+    fn test_users() {
+        let users = users(&registry(), &Manifest::default());
+
+        assert_eq!(
+            users["HttpHeader"],
+            BTreeSet::from(["Effect::Http".to_string()])
+        );
+        assert_eq!(users["Event"], BTreeSet::from(["Event".to_string()]));
+        assert_eq!(
+            users["ViewModel"],
+            BTreeSet::from(["ViewModel".to_string()])
+        );
+        assert!(!users.contains_key("Effect"));
+    }
+
+    #[test]
+    fn test_users_of_renamed_roots() {
+        let mut registry = registry();
+        let event = registry.remove("Event").unwrap();
+        registry.insert("CounterEvent".to_string(), event);
+        let manifest = Manifest {
+            event: Some("CounterEvent".to_string()),
+            ..Default::default()
+        };
+
+        let users = users(&registry, &manifest);
+
+        assert_eq!(
+            users["CounterEvent"],
+            BTreeSet::from(["CounterEvent".to_string()])
+        );
+        assert!(!users.contains_key("Event"));
+    }
+}
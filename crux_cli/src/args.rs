@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::{ArgAction, Args, Parser, Subcommand};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(
@@ -35,6 +35,36 @@ pub(crate) struct Cli {
 pub(crate) enum Commands {
     #[command(visible_alias = "doc")]
     Doctor(DoctorArgs),
+
+    /// Compare the shared types registry with the one committed at a git revision
+    Diff(DiffArgs),
+
+    /// Generate a markdown reference of the shared types in the registry
+    Docs(DocsArgs),
+
+    /// Generate schemas of the Event, ViewModel and Effect types in the registry
+    Schema(SchemaArgs),
+
+    /// Split the types of the cores in Crux.toml into a package per core and a shared package for the types they have in common, writing a `<core>.partition.json` to each core's output directory
+    Partition(PartitionArgs),
+
+    /// Check the generated Swift, Java and TypeScript code compiles, with the toolchains available
+    Verify(VerifyArgs),
+
+    /// Write build files (Bazel, Gradle, Swift package platforms) for the generated code, as configured in Crux.toml
+    BuildFiles,
+
+    /// Run the postprocessing commands configured in Crux.toml on the generated code, e.g. a formatter
+    Postprocess,
+
+    /// Copy the built-in typegen extensions (Swift package, request helpers, TypeScript runtime) to the `extensions` directory configured in Crux.toml, to override them
+    Extensions(ExtensionsArgs),
+
+    /// Replay a session recorded with `Bridge::with_recording` against the current code of a core, and show the steps whose view differs from the recording
+    Replay(ReplayArgs),
+
+    /// Check the CLI is compatible with the workspace's crux_core version, and install the latest CLI
+    Upgrade(UpgradeArgs),
 }
 
 #[derive(Args)]
@@ -43,6 +73,121 @@ pub(crate) struct DoctorArgs {
     pub(crate) fix: Option<PathBuf>,
 }
 
+#[derive(Args)]
+pub(crate) struct DiffArgs {
+    /// git revision to compare against (e.g. `main` or `HEAD~1`)
+    #[arg(long, short)]
+    pub(crate) base: String,
+
+    /// registry lockfile to compare, defaults to the `registry` of each core in Crux.toml
+    #[arg(long, short)]
+    pub(crate) registry: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub(crate) struct DocsArgs {
+    /// registry lockfile to document, defaults to the `registry` of each core in Crux.toml
+    #[arg(long, short)]
+    pub(crate) registry: Option<PathBuf>,
+
+    /// directory to write a `<core>.md` file for each core to, prints to stdout if not given
+    #[arg(long, short)]
+    pub(crate) output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub(crate) write: OutputArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct SchemaArgs {
+    /// registry lockfile to generate schemas from, defaults to the `registry` of each core in Crux.toml
+    #[arg(long, short)]
+    pub(crate) registry: Option<PathBuf>,
+
+    /// directory to write a `<core>.<type>.schema.json` file for each type to
+    #[arg(long, short)]
+    pub(crate) output: PathBuf,
+
+    /// schema language to generate
+    #[arg(long, short, value_enum, default_value = "jsonschema")]
+    pub(crate) language: Language,
+
+    #[command(flatten)]
+    pub(crate) write: OutputArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct PartitionArgs {
+    #[command(flatten)]
+    pub(crate) write: OutputArgs,
+}
+
+/// How to write the files generated into an output directory
+#[derive(Args)]
+pub(crate) struct OutputArgs {
+    /// list the files which would be written, with a diff of the ones which would change, without writing them
+    #[arg(long)]
+    pub(crate) dry_run: bool,
+
+    /// remove the files an earlier run wrote to the output directory which are no longer generated
+    #[arg(long)]
+    pub(crate) clean: bool,
+
+    /// with --clean, also remove the files of cores which are no longer in Crux.toml
+    #[arg(long, requires = "clean")]
+    pub(crate) force: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum Language {
+    /// JSON Schema (2020-12) of the types' JSON serialization
+    #[value(name = "jsonschema")]
+    JsonSchema,
+}
+
+#[derive(Args)]
+pub(crate) struct VerifyArgs {
+    /// directory of generated code, defaults to `generated` in the `type_gen` crate of each core in Crux.toml
+    #[arg(long, short)]
+    pub(crate) generated: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub(crate) struct ExtensionsArgs {
+    /// overwrite files which already exist in the directory
+    #[arg(long, short)]
+    pub(crate) force: bool,
+}
+
+#[derive(Args)]
+pub(crate) struct ReplayArgs {
+    /// session file written by `Session::export`
+    pub(crate) session: PathBuf,
+
+    /// core in Crux.toml to replay the session against, needed if there is more than one
+    #[arg(long, short)]
+    pub(crate) core: Option<String>,
+
+    /// path of the app type in the core's crate
+    #[arg(long, default_value = "App")]
+    pub(crate) app: String,
+
+    /// path of the effect type in the core's crate
+    #[arg(long, default_value = "Effect")]
+    pub(crate) effect: String,
+
+    /// seed of the core's id generator, if the recorded core was created `with_seed`
+    #[arg(long, short)]
+    pub(crate) seed: Option<u64>,
+}
+
+#[derive(Args)]
+pub(crate) struct UpgradeArgs {
+    /// only check the compatibility, failing if there are problems, without installing
+    #[arg(long, short)]
+    pub(crate) check: bool,
+}
+
 #[cfg(test)]
 mod cli_tests {
     use super::*;
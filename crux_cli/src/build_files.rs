@@ -0,0 +1,233 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{config::BuildFiles, workspace};
+
+pub(crate) fn build_files() -> Result<()> {
+    let workspace = workspace::read_config()?;
+
+    let mut written = 0;
+    for core in workspace.cores.values() {
+        let (Some(config), Some(type_gen)) = (&core.build_files, &core.type_gen) else {
+            continue;
+        };
+        let generated = type_gen.join("generated");
+        if !generated.exists() {
+            bail!(
+                "core ({}) has no generated code in {}, run the type generation first",
+                core.name,
+                generated.display()
+            );
+        }
+
+        let name = type_gen
+            .file_name()
+            .map_or("shared_types".into(), |name| name.to_string_lossy());
+        for path in write(config, &generated, &name)? {
+            println!("Wrote {}", path.display());
+            written += 1;
+        }
+    }
+
+    if written == 0 {
+        bail!("no core in Crux.toml has a `type_gen` crate and `build_files` to write");
+    }
+    Ok(())
+}
+
+/// Write the build files for the code in the `generated` directory, returning their paths
+fn write(config: &BuildFiles, generated: &Path, name: &str) -> Result<Vec<PathBuf>> {
+    let mut written = vec![];
+
+    let typescript = generated.join("typescript");
+    if config.bazel && typescript.exists() {
+        let package = fs::read_to_string(typescript.join("package.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            .and_then(|json| Some(json.get("name")?.as_str()?.to_string()))
+            .unwrap_or_else(|| name.to_string());
+
+        let path = typescript.join("BUILD.bazel");
+        fs::write(&path, bazel(&package))
+            .with_context(|| format!("could not write {}", path.display()))?;
+        written.push(path);
+    }
+
+    let java = generated.join("java");
+    if config.gradle && java.exists() {
+        let path = java.join("build.gradle.kts");
+        fs::write(&path, gradle(name))
+            .with_context(|| format!("could not write {}", path.display()))?;
+        written.push(path);
+    }
+
+    let swift = generated.join("swift");
+    if !config.swift_platforms.is_empty() && swift.exists() {
+        let entries =
+            fs::read_dir(&swift).with_context(|| format!("could not read {}", swift.display()))?;
+        for entry in entries {
+            let path = entry?.path().join("Package.swift");
+            if !path.exists() {
+                continue;
+            }
+            let manifest = fs::read_to_string(&path)
+                .with_context(|| format!("could not read {}", path.display()))?;
+            let manifest = with_platforms(&manifest, &config.swift_platforms)
+                .with_context(|| format!("{} has no products", path.display()))?;
+            fs::write(&path, manifest)
+                .with_context(|| format!("could not write {}", path.display()))?;
+            written.push(path);
+        }
+    }
+
+    Ok(written)
+}
+
+/// A `ts_project` building the TypeScript package with `rules_ts`
+fn bazel(package: &str) -> String {
+    format!(
+        r#"# Generated by `crux build-files`
+
+load("@aspect_rules_ts//ts:defs.bzl", "ts_project")
+
+ts_project(
+    name = "{package}",
+    srcs = glob(
+        ["**/*.ts"],
+        exclude = ["node_modules/**"],
+    ),
+    declaration = True,
+    tsconfig = "tsconfig.json",
+    visibility = ["//visibility:public"],
+)
+"#
+    )
+}
+
+/// A Java library module, with the generated code as its sources
+fn gradle(name: &str) -> String {
+    format!(
+        r#"// Generated by `crux build-files`. Include it in the shell's settings.gradle.kts with
+//
+//     include(":{name}")
+//     project(":{name}").projectDir = file("<path to this directory>")
+//
+// and depend on it with `implementation(project(":{name}"))`.
+
+plugins {{
+    `java-library`
+}}
+
+java {{
+    sourceCompatibility = JavaVersion.VERSION_1_8
+    targetCompatibility = JavaVersion.VERSION_1_8
+}}
+
+sourceSets {{
+    main {{
+        java {{
+            setSrcDirs(listOf("."))
+            exclude("build/**")
+        }}
+    }}
+}}
+"#
+    )
+}
+
+/// The Swift package `manifest`, declaring the `platforms`, in place of any it declared before
+fn with_platforms(manifest: &str, platforms: &[String]) -> Option<String> {
+    let declared = format!("    platforms: [{}],\n", platforms.join(", "));
+
+    let manifest: String = manifest
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("platforms:"))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let products = manifest.find("    products: [")?;
+
+    Some(format!(
+        "{}{declared}{}",
+        &manifest[..products],
+        &manifest[products..]
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MANIFEST: &str = r#"let package = Package(
+    name: "SharedTypes",
+    products: [
+        .library(name: "SharedTypes", targets: ["SharedTypes"]),
+    ],
+)
+"#;
+
+    #[test]
+    fn test_swift_platforms() {
+        let platforms = vec![".iOS(.v15)".to_string(), ".macOS(.v12)".to_string()];
+
+        let manifest = with_platforms(MANIFEST, &platforms).unwrap();
+        assert_eq!(
+            manifest,
+            r#"let package = Package(
+    name: "SharedTypes",
+    platforms: [.iOS(.v15), .macOS(.v12)],
+    products: [
+        .library(name: "SharedTypes", targets: ["SharedTypes"]),
+    ],
+)
+"#
+        );
+
+        // writing them again replaces them
+        let platforms = vec![".iOS(.v16)".to_string()];
+        let manifest = with_platforms(&manifest, &platforms).unwrap();
+        assert!(manifest.contains("    platforms: [.iOS(.v16)],\n    products: ["));
+        assert_eq!(manifest.matches("platforms:").count(), 1);
+
+        assert_eq!(with_platforms("let package = Package()", &platforms), None);
+    }
+
+    #[test]
+    fn test_write_build_files() {
+        let generated =
+            std::env::temp_dir().join(format!("crux_build_files_{}/generated", std::process::id()));
+        fs::create_dir_all(generated.join("typescript")).unwrap();
+        fs::create_dir_all(generated.join("java")).unwrap();
+        fs::create_dir_all(generated.join("swift/SharedTypes")).unwrap();
+        fs::write(
+            generated.join("typescript/package.json"),
+            r#"{ "name": "counter_types" }"#,
+        )
+        .unwrap();
+        fs::write(generated.join("swift/SharedTypes/Package.swift"), MANIFEST).unwrap();
+
+        let config = BuildFiles {
+            bazel: true,
+            gradle: true,
+            swift_platforms: vec![".iOS(.v15)".to_string()],
+        };
+        let written = write(&config, &generated, "shared_types").unwrap();
+        assert_eq!(written.len(), 3);
+
+        let bazel = fs::read_to_string(generated.join("typescript/BUILD.bazel")).unwrap();
+        assert!(bazel.contains(r#"name = "counter_types""#));
+        let gradle = fs::read_to_string(generated.join("java/build.gradle.kts")).unwrap();
+        assert!(gradle.contains(r#"include(":shared_types")"#));
+        let swift = fs::read_to_string(generated.join("swift/SharedTypes/Package.swift")).unwrap();
+        assert!(swift.contains("platforms: [.iOS(.v15)]"));
+
+        // nothing is written unless asked for
+        let written = write(&BuildFiles::default(), &generated, "shared_types").unwrap();
+        assert!(written.is_empty());
+
+        fs::remove_dir_all(generated.parent().unwrap()).unwrap();
+    }
+}
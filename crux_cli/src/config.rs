@@ -10,6 +10,9 @@ pub struct Workspace {
     pub repository: Option<String>,
     pub cores: BTreeMap<String, Core>,
     pub shells: Option<BTreeMap<String, Shell>>,
+    /// The package for the types more than one core has, which `crux partition` moves out
+    /// of the cores' packages. Without it, each core's package keeps its own copy
+    pub shared: Option<Output>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,7 +21,56 @@ pub struct Core {
     pub name: String,
     pub source: PathBuf,
     pub type_gen: Option<PathBuf>,
+    pub registry: Option<PathBuf>,
     pub crux_version: String,
+    pub build_files: Option<BuildFiles>,
+    pub postprocess: Option<Postprocess>,
+    /// Directory of files overriding the built-in typegen extensions, which the `type_gen`
+    /// crate passes to `TypeGen::extensions_dir`, and `crux extensions` copies them to
+    pub extensions: Option<PathBuf>,
+    /// The platforms each capability is available on, by the name of its variant of the effect
+    /// type, e.g. `Haptics = ["ios", "android"]`. Capabilities not listed are available on all
+    /// platforms. `crux doctor` warns when a shell's platform is missing for one the core uses
+    pub capabilities: Option<BTreeMap<String, Vec<String>>>,
+    /// Where `crux partition` writes the core's part of the types, and the names of its
+    /// package in each language
+    pub output: Option<Output>,
+}
+
+/// An output directory for generated types, and the package names for each language, e.g.
+/// `swift = "CounterTypes"`, `java = "com.example.counter"`, `typescript = "counter_types"`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Output {
+    /// Defaults to `generated` in the core's `type_gen` crate
+    pub dir: Option<PathBuf>,
+    pub swift: Option<String>,
+    pub java: Option<String>,
+    pub typescript: Option<String>,
+}
+
+/// Build files for the code generated in the `type_gen` crate, written by `crux build-files`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BuildFiles {
+    /// Write a `BUILD.bazel` for the TypeScript package
+    #[serde(default)]
+    pub bazel: bool,
+    /// Write a Gradle module for the Java code, to include in a Kotlin shell's build
+    #[serde(default)]
+    pub gradle: bool,
+    /// Platforms to declare in the Swift package manifests, e.g. `.iOS(.v15)`, which Xcode
+    /// needs to build them for anything but macOS
+    #[serde(default)]
+    pub swift_platforms: Vec<String>,
+}
+
+/// Commands `crux postprocess` runs on the code generated in the `type_gen` crate, for each
+/// language, e.g. `"swiftformat"`. The paths of the language's files are appended to the
+/// command's arguments.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Postprocess {
+    pub swift: Option<String>,
+    pub java: Option<String>,
+    pub typescript: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,4 +80,6 @@ pub struct Shell {
     pub template: Option<PathBuf>,
     pub source: PathBuf,
     pub cores: Vec<String>,
+    /// The platform the shell targets, e.g. `ios`, to check against the core's `capabilities`
+    pub platform: Option<String>,
 }
@@ -0,0 +1,254 @@
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+
+use crate::api_diff::Registry;
+
+/// Decode a value of the type `root` in the registry from `bytes` serialized by the bridge,
+/// i.e. with bincode's fixed size integers, into the JSON serde would serialize it to.
+pub(crate) fn decode(registry: &Registry, root: &str, bytes: &[u8]) -> Result<Value> {
+    let mut reader = Reader {
+        registry,
+        bytes,
+        position: 0,
+    };
+    reader.type_name(root)
+}
+
+struct Reader<'a> {
+    registry: &'a Registry,
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn type_name(&mut self, name: &str) -> Result<Value> {
+        let format = self
+            .registry
+            .get(name)
+            .with_context(|| format!("{name} is not in the registry"))?;
+        self.container(format)
+            .with_context(|| format!("could not decode {name}"))
+    }
+
+    fn container(&mut self, format: &Value) -> Result<Value> {
+        let Some((kind, body)) = entry(format) else {
+            // UNITSTRUCT
+            return Ok(Value::Null);
+        };
+        match kind.as_str() {
+            "NEWTYPESTRUCT" => self.format(body),
+            "TUPLESTRUCT" => self.tuple(body),
+            "STRUCT" => self.object(body),
+            "ENUM" => {
+                let index = u32::from_le_bytes(self.take()?).to_string();
+                let Some((name, variant)) = body.get(&index).and_then(entry) else {
+                    bail!("unknown variant {index}");
+                };
+                let data = match entry(variant) {
+                    Some((kind, body)) => match kind.as_str() {
+                        "NEWTYPE" => self.format(body)?,
+                        "TUPLE" => self.tuple(body)?,
+                        "STRUCT" => self.object(body)?,
+                        _ => bail!("unknown variant format {kind}"),
+                    },
+                    // unit variants are serialized as just their name
+                    None => return Ok(json!(name)),
+                };
+                Ok(json!({ name: data }))
+            }
+            _ => bail!("unknown container format {kind}"),
+        }
+    }
+
+    fn format(&mut self, format: &Value) -> Result<Value> {
+        if let Value::String(primitive) = format {
+            return self.primitive(primitive);
+        }
+        let Some((kind, body)) = entry(format) else {
+            bail!("unknown format {format}");
+        };
+        match kind.as_str() {
+            "TYPENAME" => self.type_name(body.as_str().unwrap_or_default()),
+            "OPTION" => match self.take::<1>()? {
+                [0] => Ok(Value::Null),
+                _ => self.format(body),
+            },
+            "SEQ" => {
+                let length = self.length()?;
+                (0..length).map(|_| self.format(body)).collect()
+            }
+            "MAP" => {
+                let length = self.length()?;
+                let mut map = Map::new();
+                for _ in 0..length {
+                    // JSON object keys are always strings
+                    let key = match self.format(&body["KEY"])? {
+                        Value::String(key) => key,
+                        key => key.to_string(),
+                    };
+                    map.insert(key, self.format(&body["VALUE"])?);
+                }
+                Ok(Value::Object(map))
+            }
+            "TUPLE" => self.tuple(body),
+            "TUPLEARRAY" => {
+                let size = body["SIZE"].as_u64().unwrap_or_default();
+                (0..size).map(|_| self.format(&body["CONTENT"])).collect()
+            }
+            _ => bail!("unknown format {kind}"),
+        }
+    }
+
+    fn primitive(&mut self, primitive: &str) -> Result<Value> {
+        Ok(match primitive {
+            "UNIT" => Value::Null,
+            "BOOL" => json!(self.take::<1>()? != [0]),
+            "I8" => json!(i8::from_le_bytes(self.take()?)),
+            "I16" => json!(i16::from_le_bytes(self.take()?)),
+            "I32" => json!(i32::from_le_bytes(self.take()?)),
+            "I64" => json!(i64::from_le_bytes(self.take()?)),
+            // too large for JSON numbers
+            "I128" => json!(i128::from_le_bytes(self.take()?).to_string()),
+            "U8" => json!(u8::from_le_bytes(self.take()?)),
+            "U16" => json!(u16::from_le_bytes(self.take()?)),
+            "U32" => json!(u32::from_le_bytes(self.take()?)),
+            "U64" => json!(u64::from_le_bytes(self.take()?)),
+            "U128" => json!(u128::from_le_bytes(self.take()?).to_string()),
+            "F32" => json!(f32::from_le_bytes(self.take()?)),
+            "F64" => json!(f64::from_le_bytes(self.take()?)),
+            "CHAR" => {
+                // the UTF-8 encoding, without a length
+                let first = self.take::<1>()?[0];
+                let length = match first.leading_ones() {
+                    0 => 1,
+                    ones => ones as usize,
+                };
+                let rest = self.slice(length - 1)?;
+                let text = [&[first], rest].concat();
+                json!(String::from_utf8(text).context("invalid char")?)
+            }
+            "STR" => {
+                let length = self.length()?;
+                let text = self.slice(length)?.to_vec();
+                json!(String::from_utf8(text).context("invalid string")?)
+            }
+            "BYTES" => {
+                let length = self.length()?;
+                json!(self.slice(length)?)
+            }
+            _ => bail!("unknown primitive {primitive}"),
+        })
+    }
+
+    fn tuple(&mut self, formats: &Value) -> Result<Value> {
+        formats
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|format| self.format(format))
+            .collect()
+    }
+
+    fn object(&mut self, fields: &Value) -> Result<Value> {
+        let mut object = Map::new();
+        for (name, format) in fields.as_array().into_iter().flatten().filter_map(entry) {
+            object.insert(name.clone(), self.format(format)?);
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn length(&mut self) -> Result<usize> {
+        let length = u64::from_le_bytes(self.take()?);
+        usize::try_from(length).context("length too large")
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let bytes = self.slice(N)?;
+        Ok(bytes.try_into().expect("slice has N bytes"))
+    }
+
+    fn slice(&mut self, length: usize) -> Result<&[u8]> {
+        let end = self.position.saturating_add(length);
+        let Some(bytes) = self.bytes.get(self.position..end) else {
+            bail!("unexpected end of input at byte {}", self.bytes.len());
+        };
+        self.position = end;
+        Ok(bytes)
+    }
+}
+
+fn entry(map: &Value) -> Option<(&String, &Value)> {
+    map.as_object()?.iter().next()
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn registry() -> Registry {
+        serde_json::from_value(json!({
+            "ViewModel": { "STRUCT": [
+                { "count": "U32" },
+                { "label": { "OPTION": "STR" } },
+                { "items": { "SEQ": { "TYPENAME": "Item" } } },
+            ] },
+            "Item": { "ENUM": {
+                "0": { "Empty": "UNIT" },
+                "1": { "Named": { "NEWTYPE": "STR" } },
+                "2": { "Point": { "TUPLE": ["I8", "CHAR"] } },
+            } },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn decodes_the_view_model() {
+        let mut bytes = vec![];
+        bytes.extend(7u32.to_le_bytes());
+        bytes.extend([1]);
+        bytes.extend(2u64.to_le_bytes());
+        bytes.extend(b"hi");
+        bytes.extend(3u64.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(1u64.to_le_bytes());
+        bytes.extend(b"a");
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend((-1i8).to_le_bytes());
+        bytes.extend("é".as_bytes());
+
+        assert_eq!(
+            decode(&registry(), "ViewModel", &bytes).unwrap(),
+            json!({
+                "count": 7,
+                "label": "hi",
+                "items": ["Empty", { "Named": "a" }, { "Point": [-1, "é"] }],
+            })
+        );
+    }
+
+    #[test]
+    fn fails_on_truncated_input() {
+        let mut bytes = vec![];
+        bytes.extend(7u32.to_le_bytes());
+        bytes.extend([0]);
+        bytes.extend(1u64.to_le_bytes());
+
+        let error = decode(&registry(), "ViewModel", &bytes).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "could not decode ViewModel: could not decode Item: unexpected end of input at byte 13"
+        );
+    }
+
+    #[test]
+    fn fails_on_unknown_variants() {
+        let error = decode(&registry(), "Item", &9u32.to_le_bytes()).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "could not decode Item: unknown variant 9"
+        );
+    }
+}
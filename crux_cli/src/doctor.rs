@@ -4,11 +4,13 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context as _, Result};
 use ignore::Walk;
 use ramhorns::Template;
 
 use crate::{
+    api_diff::{parse_registry, read, read_manifest, Manifest, Registry},
+    config::{Shell, Workspace},
     diff,
     template::{Context, CoreContext, ShellContext},
     workspace,
@@ -27,7 +29,10 @@ pub(crate) fn doctor(
 ) -> Result<()> {
     let workspace = workspace::read_config()?;
     let current_dir = &env::current_dir()?;
-    let template_root = current_dir.join(template_dir).canonicalize()?;
+    let template_root = current_dir
+        .join(template_dir)
+        .canonicalize()
+        .with_context(|| format!("template directory {} not found", template_dir.display()))?;
 
     for core in workspace.cores.values() {
         let (do_core, do_typegen) = match path {
@@ -69,6 +74,8 @@ pub(crate) fn doctor(
             };
 
             if do_shell {
+                check_capabilities(&workspace, shell)?;
+
                 // TODO support shell having multiple cores
                 if shell.cores.len() > 1 {
                     eprintln!("Warning: shell {name} has multiple cores, only checking first",);
@@ -95,6 +102,79 @@ pub(crate) fn doctor(
     workspace::write_config(&workspace)
 }
 
+/// Warn about the capabilities each of the shell's cores uses, which aren't available on
+/// the shell's platform, according to the core's `capabilities` in Crux.toml
+fn check_capabilities(workspace: &Workspace, shell: &Shell) -> Result<()> {
+    let Some(platform) = &shell.platform else {
+        return Ok(());
+    };
+
+    for core in shell
+        .cores
+        .iter()
+        .filter_map(|name| workspace.cores.get(name))
+    {
+        let (Some(capabilities), Some(registry)) = (&core.capabilities, &core.registry) else {
+            continue;
+        };
+        let effects = effects(
+            &parse_registry(registry, &read(registry)?)?,
+            &read_manifest(registry)?,
+        );
+
+        for effect in unavailable(&effects, capabilities, platform) {
+            eprintln!(
+                "Warning: core {core} uses the {effect} capability, which is not available on {platform} (shell {shell})",
+                core = core.name,
+                shell = shell.name
+            );
+        }
+        for capability in capabilities.keys().filter(|name| !effects.contains(name)) {
+            eprintln!(
+                "Warning: Crux.toml lists platforms for the {capability} capability, which core {core} does not use",
+                core = core.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The names of the effect type's variants, one for each capability, in index order
+fn effects(registry: &Registry, manifest: &Manifest) -> Vec<String> {
+    let mut variants: Vec<_> = manifest
+        .effect
+        .as_ref()
+        .and_then(|effect| registry.get(effect)?.get("ENUM")?.as_object())
+        .into_iter()
+        .flatten()
+        .collect();
+    variants.sort_by_key(|(index, _)| index.parse::<u32>().unwrap_or(u32::MAX));
+
+    variants
+        .into_iter()
+        .filter_map(|(_, variant)| variant.as_object()?.keys().next().cloned())
+        .collect()
+}
+
+/// The effects which aren't available on the platform. Effects without a list of platforms
+/// are available on all of them
+fn unavailable<'a>(
+    effects: &'a [String],
+    capabilities: &BTreeMap<String, Vec<String>>,
+    platform: &str,
+) -> Vec<&'a str> {
+    effects
+        .iter()
+        .filter(|effect| {
+            capabilities
+                .get(*effect)
+                .map_or(false, |platforms| !platforms.iter().any(|p| p == platform))
+        })
+        .map(String::as_str)
+        .collect()
+}
+
 fn compare(
     root: &Path,
     template_root: &Path,
@@ -169,7 +249,8 @@ fn read_files(
             println!("Reading: {path_display}");
         }
 
-        let template = fs::read_to_string(path)?;
+        let template = fs::read_to_string(path)
+            .with_context(|| format!("could not read template {path_display}"))?;
         let template = Template::new(template).unwrap();
 
         let rendered = match context {
@@ -294,6 +375,34 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_unavailable_capabilities() {
+        let registry: Registry = serde_json::from_value(serde_json::json!({
+            "Effect": { "ENUM": {
+                "0": { "Render": { "NEWTYPE": { "TYPENAME": "RenderOperation" } } },
+                "1": { "Haptics": { "NEWTYPE": { "TYPENAME": "HapticsOperation" } } },
+                "2": { "FileDialog": { "NEWTYPE": { "TYPENAME": "FileDialogOperation" } } },
+            } }
+        }))
+        .unwrap();
+        let effects = effects(&registry, &Manifest::default());
+        assert_eq!(effects, ["Render", "Haptics", "FileDialog"]);
+
+        let mut capabilities = BTreeMap::new();
+        capabilities.insert(
+            "Haptics".to_string(),
+            vec!["ios".to_string(), "android".to_string()],
+        );
+        capabilities.insert("FileDialog".to_string(), vec!["macos".to_string()]);
+
+        assert_eq!(unavailable(&effects, &capabilities, "ios"), ["FileDialog"]);
+        assert_eq!(
+            unavailable(&effects, &capabilities, "web"),
+            ["Haptics", "FileDialog"]
+        );
+        assert!(unavailable(&effects, &BTreeMap::new(), "web").is_empty());
+    }
+
     #[test]
     fn test_is_source_code() {
         assert!(is_source_code(Path::new("foo.rs")));
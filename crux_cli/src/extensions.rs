@@ -0,0 +1,92 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::workspace;
+
+pub(crate) fn extensions(force: bool) -> Result<()> {
+    let workspace = workspace::read_config()?;
+
+    let mut configured = 0;
+    for core in workspace.cores.values() {
+        let Some(extensions) = &core.extensions else {
+            continue;
+        };
+        configured += 1;
+
+        let (written, skipped) = write(crux_core::TYPEGEN_EXTENSIONS, extensions, force)?;
+        for path in written {
+            println!("Wrote {}", path.display());
+        }
+        if !skipped.is_empty() {
+            println!(
+                "core ({}): kept {} existing files in {}, use --force to overwrite them",
+                core.name,
+                skipped.len(),
+                extensions.display()
+            );
+        }
+    }
+
+    if configured == 0 {
+        bail!("no core in Crux.toml has an `extensions` directory to copy the templates to");
+    }
+    Ok(())
+}
+
+/// Write the `files`, paths and contents, to the same paths in `to`, overwriting existing
+/// files only if `force` is set, returning the paths written and the paths skipped
+fn write(files: &[(&str, &str)], to: &Path, force: bool) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut written = vec![];
+    let mut skipped = vec![];
+
+    for (name, data) in files {
+        let target = to.join(name);
+
+        if target.exists() && !force {
+            skipped.push(target);
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("could not create {}", parent.display()))?;
+            }
+            fs::write(&target, data)
+                .with_context(|| format!("could not write {}", target.display()))?;
+            written.push(target);
+        }
+    }
+
+    written.sort();
+    skipped.sort();
+    Ok((written, skipped))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_extensions() {
+        let to = std::env::temp_dir().join(format!("crux_extensions_{}", std::process::id()));
+        fs::create_dir_all(to.join("swift")).unwrap();
+        fs::write(to.join("swift/Package.swift"), "// custom").unwrap();
+
+        let (written, skipped) = write(crux_core::TYPEGEN_EXTENSIONS, &to, false).unwrap();
+        assert!(written.contains(&to.join("swift/requests.swift")));
+        assert!(written.contains(&to.join("typescript/bincode/mod.ts")));
+        assert_eq!(skipped, [to.join("swift/Package.swift")]);
+        assert_eq!(
+            fs::read_to_string(to.join("swift/Package.swift")).unwrap(),
+            "// custom"
+        );
+
+        let (written, skipped) = write(crux_core::TYPEGEN_EXTENSIONS, &to, true).unwrap();
+        assert!(written.contains(&to.join("swift/Package.swift")));
+        assert!(skipped.is_empty());
+
+        fs::remove_dir_all(to).unwrap();
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{args::OutputArgs, diff};
+
+/// The list of the files generated into a directory, the same as `crux_core`'s typegen keeps
+const LIST_FILE_NAME: &str = ".generated_files.json";
+
+#[derive(Serialize, Deserialize, Default)]
+struct List {
+    files: BTreeSet<String>,
+}
+
+/// The files a command generates into a directory, with a `<core>.` prefix and the same
+/// extension, written together once they are all known. The files written are listed in
+/// the directory, so that the files left over from an earlier run can be told apart from
+/// the ones written by hand.
+pub(crate) struct Generated {
+    dir: PathBuf,
+    extension: &'static str,
+    cores: BTreeSet<String>,
+    files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Status {
+    New,
+    Changed(String),
+    Unchanged,
+}
+
+impl Generated {
+    pub(crate) fn new(dir: &Path, extension: &'static str) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            extension,
+            cores: BTreeSet::new(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Add the file `<core>.<name><extension>`, or `<core><extension>` if `name` is empty
+    pub(crate) fn add(&mut self, core: &str, name: &str, contents: String) {
+        let file_name = if name.is_empty() {
+            format!("{core}{}", self.extension)
+        } else {
+            format!("{core}.{name}{}", self.extension)
+        };
+        self.cores.insert(core.to_string());
+        self.files.insert(file_name, contents);
+    }
+
+    /// Write the files, or with `--dry-run` list them with a diff of the ones which would
+    /// change. Files with the extension which an earlier run listed, but weren't generated
+    /// this time, are stale, and `--clean` removes them. A stale file of a core which wasn't
+    /// generated this time may still be wanted, so it is only removed with `--force`.
+    /// Files which were never listed, like ones written by hand, are left alone.
+    pub(crate) fn write(&self, args: &OutputArgs) -> Result<()> {
+        let files = self
+            .files
+            .iter()
+            .map(|(name, contents)| Ok((name, contents, self.status(name, contents)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let listed = self.listed()?;
+        let stale = self.stale(&listed);
+
+        if args.dry_run {
+            self.dry_run(&files, &stale, args);
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("could not create {}", self.dir.display()))?;
+        for (name, contents, status) in &files {
+            if *status == Status::Unchanged {
+                continue;
+            }
+            let file = self.dir.join(name);
+            fs::write(&file, contents)
+                .with_context(|| format!("could not write {}", file.display()))?;
+            println!("Wrote {}", file.display());
+        }
+
+        // files of other commands writing to the directory stay listed
+        let mut list = List {
+            files: listed
+                .into_iter()
+                .filter(|name| !name.ends_with(self.extension))
+                .chain(self.files.keys().cloned())
+                .collect(),
+        };
+        for (name, removable) in stale {
+            let file = self.dir.join(&name);
+            if args.clean && (removable || args.force) {
+                fs::remove_file(&file)
+                    .with_context(|| format!("could not remove {}", file.display()))?;
+                println!("Removed {}", file.display());
+            } else {
+                eprintln!(
+                    "Warning: {} is no longer generated, run with {} to remove it",
+                    file.display(),
+                    removal_flags(removable)
+                );
+                list.files.insert(name);
+            }
+        }
+
+        let list_path = self.dir.join(LIST_FILE_NAME);
+        let mut json = serde_json::to_string_pretty(&list).expect("file list should serialize");
+        json.push('\n');
+        fs::write(&list_path, json)
+            .with_context(|| format!("could not write {}", list_path.display()))
+    }
+
+    /// The files the earlier runs listed
+    fn listed(&self) -> Result<BTreeSet<String>> {
+        let list_path = self.dir.join(LIST_FILE_NAME);
+        let list: List = match fs::read(&list_path) {
+            Ok(list) => serde_json::from_slice(&list).unwrap_or_default(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => List::default(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("could not read {}", list_path.display()))
+            }
+        };
+        Ok(list.files)
+    }
+
+    fn status(&self, name: &str, contents: &str) -> Result<Status> {
+        let file = self.dir.join(name);
+        if !file.exists() {
+            return Ok(Status::New);
+        }
+        let existing = fs::read_to_string(&file)
+            .with_context(|| format!("could not read {}", file.display()))?;
+
+        Ok(if existing == contents {
+            Status::Unchanged
+        } else {
+            Status::Changed(existing)
+        })
+    }
+
+    /// The files with the extension which were `listed` but weren't generated this time,
+    /// and whether they belong to one of the cores which were
+    fn stale(&self, listed: &BTreeSet<String>) -> Vec<(String, bool)> {
+        listed
+            .iter()
+            .filter(|name| name.ends_with(self.extension) && !self.files.contains_key(*name))
+            .filter(|name| {
+                // only ever remove files in the directory itself
+                let mut components = Path::new(name.as_str()).components();
+                matches!(
+                    (components.next(), components.next()),
+                    (Some(Component::Normal(_)), None)
+                ) && self.dir.join(name).is_file()
+            })
+            .map(|name| {
+                let removable = self.cores.iter().any(|core| {
+                    *name == format!("{core}{}", self.extension)
+                        || name.starts_with(&format!("{core}."))
+                });
+                (name.clone(), removable)
+            })
+            .collect()
+    }
+
+    fn dry_run(
+        &self,
+        files: &[(&String, &String, Status)],
+        stale: &[(String, bool)],
+        args: &OutputArgs,
+    ) {
+        println!("{}", self.dir.display());
+        for (name, _, status) in files {
+            let (sign, note) = match status {
+                Status::New => ("+", "new"),
+                Status::Changed(_) => ("~", "changed"),
+                Status::Unchanged => (" ", "unchanged"),
+            };
+            println!("  {sign} {name} ({note})");
+        }
+        for (name, removable) in stale {
+            if args.clean && (*removable || args.force) {
+                println!("  - {name} (stale, would be removed)");
+            } else {
+                println!(
+                    "  - {name} (stale, kept, {} to remove)",
+                    removal_flags(*removable)
+                );
+            }
+        }
+
+        let count = |wanted: fn(&Status) -> bool| {
+            files.iter().filter(|(_, _, status)| wanted(status)).count()
+        };
+        println!(
+            "{} new, {} changed, {} unchanged, {} stale",
+            count(|status| *status == Status::New),
+            count(|status| matches!(status, Status::Changed(_))),
+            count(|status| *status == Status::Unchanged),
+            stale.len()
+        );
+
+        for (name, contents, status) in files {
+            if let Status::Changed(existing) = status {
+                println!();
+                diff::show(&self.dir.join(name), contents, existing);
+            }
+        }
+    }
+}
+
+fn removal_flags(removable: bool) -> &'static str {
+    if removable {
+        "--clean"
+    } else {
+        "--clean --force"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(clean: bool, force: bool) -> OutputArgs {
+        OutputArgs {
+            dry_run: false,
+            clean,
+            force,
+        }
+    }
+
+    #[test]
+    fn test_generated_files() {
+        let dir = std::env::temp_dir().join(format!("crux_generated_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("app.Event.schema.json"), "old").unwrap();
+        fs::write(dir.join("app.Removed.schema.json"), "old").unwrap();
+        fs::write(dir.join("gone.Event.schema.json"), "old").unwrap();
+        fs::write(dir.join("app.Handwritten.schema.json"), "kept").unwrap();
+        fs::write(dir.join("README.md"), "kept").unwrap();
+        fs::write(
+            dir.join(LIST_FILE_NAME),
+            r#"{ "files": [
+                "app.Event.schema.json",
+                "app.Removed.schema.json",
+                "gone.Event.schema.json",
+                "../app.Outside.schema.json",
+                "app.Event.md"
+            ] }"#,
+        )
+        .unwrap();
+
+        let mut generated = Generated::new(&dir, ".schema.json");
+        generated.add("app", "Event", "event".to_string());
+        generated.add("app", "ViewModel", "view model".to_string());
+
+        assert_eq!(
+            generated.status("app.Event.schema.json", "event").unwrap(),
+            Status::Changed("old".to_string())
+        );
+        assert_eq!(
+            generated.status("app.ViewModel.schema.json", "").unwrap(),
+            Status::New
+        );
+        assert_eq!(
+            generated.stale(&generated.listed().unwrap()),
+            [
+                ("app.Removed.schema.json".to_string(), true),
+                ("gone.Event.schema.json".to_string(), false)
+            ]
+        );
+
+        // a dry run writes nothing
+        generated
+            .write(&OutputArgs {
+                dry_run: true,
+                clean: true,
+                force: true,
+            })
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("app.Event.schema.json")).unwrap(),
+            "old"
+        );
+        assert!(!dir.join("app.ViewModel.schema.json").exists());
+
+        // stale files are kept unless asked to clean
+        generated.write(&args(false, false)).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("app.Event.schema.json")).unwrap(),
+            "event"
+        );
+        assert_eq!(
+            generated
+                .status("app.ViewModel.schema.json", "view model")
+                .unwrap(),
+            Status::Unchanged
+        );
+        assert!(dir.join("app.Removed.schema.json").exists());
+
+        // files of other cores need forcing
+        generated.write(&args(true, false)).unwrap();
+        assert!(!dir.join("app.Removed.schema.json").exists());
+        assert!(dir.join("gone.Event.schema.json").exists());
+
+        generated.write(&args(true, true)).unwrap();
+        assert!(!dir.join("gone.Event.schema.json").exists());
+        assert!(dir.join("app.Handwritten.schema.json").exists());
+        assert!(dir.join("README.md").exists());
+        assert_eq!(
+            generated.listed().unwrap(),
+            BTreeSet::from([
+                "app.Event.md".to_string(),
+                "app.Event.schema.json".to_string(),
+                "app.ViewModel.schema.json".to_string(),
+            ])
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,18 +1,37 @@
 use anyhow::Result;
-use args::{Commands, DoctorArgs};
+use args::{
+    Commands, DiffArgs, DocsArgs, DoctorArgs, ExtensionsArgs, PartitionArgs, ReplayArgs,
+    SchemaArgs, UpgradeArgs, VerifyArgs,
+};
 use clap::Parser;
 
 use args::Cli;
 
+mod api_diff;
+mod api_docs;
 mod args;
+mod build_files;
 mod config;
+mod decode;
 mod diff;
 mod doctor;
+mod extensions;
+mod generated;
+mod partition;
+mod postprocess;
+mod replay;
+mod schema;
 mod template;
+mod verify;
+mod version;
 mod workspace;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    if !matches!(cli.command, Some(Commands::Upgrade(_))) {
+        version::warn_if_incompatible();
+    }
+
     match &cli.command {
         Some(Commands::Doctor(DoctorArgs { .. })) => doctor::doctor(
             &cli.template_dir,
@@ -20,6 +39,33 @@ fn main() -> Result<()> {
             cli.verbose,
             cli.include_source_code,
         ),
+        Some(Commands::Diff(DiffArgs { base, registry })) => {
+            api_diff::api_diff(base, registry.as_deref())
+        }
+        Some(Commands::Docs(DocsArgs {
+            registry,
+            output,
+            write,
+        })) => api_docs::api_docs(registry.as_deref(), output.as_deref(), write),
+        Some(Commands::Schema(SchemaArgs {
+            registry,
+            output,
+            language,
+            write,
+        })) => schema::schema(registry.as_deref(), output, *language, write),
+        Some(Commands::Partition(PartitionArgs { write })) => partition::partition(write),
+        Some(Commands::Verify(VerifyArgs { generated })) => verify::verify(generated.as_deref()),
+        Some(Commands::BuildFiles) => build_files::build_files(),
+        Some(Commands::Postprocess) => postprocess::postprocess(),
+        Some(Commands::Extensions(ExtensionsArgs { force })) => extensions::extensions(*force),
+        Some(Commands::Replay(ReplayArgs {
+            session,
+            core,
+            app,
+            effect,
+            seed,
+        })) => replay::replay(session, core.as_deref(), app, effect, *seed),
+        Some(Commands::Upgrade(UpgradeArgs { check })) => version::upgrade(*check),
         None => Ok(()),
     }
 }
@@ -0,0 +1,333 @@
+//! Splitting the types of a workspace with several cores into a package per core, and a
+//! shared package for the types they have in common.
+//!
+//! Each core's registry holds every type its app uses, so two cores using the same
+//! capability both have its operation and output types. Generated into the cores' own
+//! packages, a shell using both cores ends up with two incompatible copies of each. The
+//! types which are the same in every core having them are moved to the shared package
+//! instead, and each core's package refers to them there.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use crate::{
+    api_diff::{parse_registry, read, read_manifest, Manifest, Registry},
+    api_docs::type_names,
+    args::OutputArgs,
+    config::{Core, Output},
+    generated::Generated,
+    workspace,
+};
+
+/// The name of the shared package's file in its output directory
+const SHARED: &str = "shared";
+
+/// What `crux partition` writes for a package, for its type generation to read
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct Partition {
+    /// The package's names in each language
+    packages: BTreeMap<&'static str, String>,
+    /// The types generated into the package
+    types: BTreeSet<String>,
+    /// The types the package refers to in other packages, by language, then package name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    external: BTreeMap<&'static str, BTreeMap<String, BTreeSet<String>>>,
+}
+
+pub(crate) fn partition(args: &OutputArgs) -> Result<()> {
+    let workspace = workspace::read_config()?;
+
+    let mut apps = BTreeMap::new();
+    for core in workspace.cores.values() {
+        let Some(path) = &core.registry else {
+            continue;
+        };
+        let registry = parse_registry(path, &read(path)?)?;
+        apps.insert(core.name.clone(), (registry, read_manifest(path)?));
+    }
+    if apps.is_empty() {
+        bail!("no core in Crux.toml specifies a `registry`");
+    }
+
+    let owners = owners(&apps);
+    let shared = match &workspace.shared {
+        Some(_) => shared(&apps, &owners),
+        None => BTreeSet::new(),
+    };
+
+    let mut outputs = BTreeMap::new();
+    for (name, (registry, _)) in &apps {
+        let core = &workspace.cores[name];
+        let output = core.output.as_ref();
+        let dir = output_dir(core)?;
+        let packages = packages(output);
+        let external = external(registry, &shared, workspace.shared.as_ref(), &packages)?;
+        let partition = Partition {
+            packages,
+            types: registry
+                .keys()
+                .filter(|name| !shared.contains(*name))
+                .cloned()
+                .collect(),
+            external,
+        };
+        outputs
+            .entry(dir)
+            .or_insert_with(Vec::new)
+            .push((name.as_str(), partition));
+    }
+
+    if let Some(output) = &workspace.shared {
+        let Some(dir) = &output.dir else {
+            bail!("the `shared` output in Crux.toml needs a `dir`");
+        };
+        let partition = Partition {
+            packages: packages(Some(output)),
+            types: shared,
+            external: BTreeMap::new(),
+        };
+        outputs
+            .entry(dir.clone())
+            .or_insert_with(Vec::new)
+            .push((SHARED, partition));
+    }
+
+    for (dir, partitions) in outputs {
+        let mut generated = Generated::new(&dir, ".partition.json");
+        for (name, partition) in partitions {
+            let mut json = serde_json::to_string_pretty(&partition)?;
+            json.push('\n');
+            generated.add(name, "", json);
+        }
+        generated.write(args)?;
+    }
+
+    Ok(())
+}
+
+/// The cores having each type in their registry
+fn owners(apps: &BTreeMap<String, (Registry, Manifest)>) -> BTreeMap<&str, BTreeSet<&str>> {
+    let mut owners: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (core, (registry, _)) in apps {
+        for name in registry.keys() {
+            owners
+                .entry(name.as_str())
+                .or_default()
+                .insert(core.as_str());
+        }
+    }
+    owners
+}
+
+/// The types to move to the shared package: those more than one core has, with the same
+/// format in each, which only use other shared types. The roots of each app stay in its
+/// own package, even where another app's are the same.
+fn shared(
+    apps: &BTreeMap<String, (Registry, Manifest)>,
+    owners: &BTreeMap<&str, BTreeSet<&str>>,
+) -> BTreeSet<String> {
+    let roots: BTreeSet<&str> = apps
+        .values()
+        .flat_map(|(_, manifest)| manifest.roots())
+        .collect();
+
+    let mut shared: BTreeSet<String> = owners
+        .iter()
+        .filter(|(name, cores)| cores.len() > 1 && !roots.contains(*name))
+        .filter(|(name, cores)| {
+            let mut formats = cores.iter().map(|core| apps[*core].0.get(**name));
+            let first = formats.next().flatten();
+            formats.all(|format| format == first)
+        })
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+    // a shared type can't refer back to a core's package, so drop the ones using a type
+    // which isn't shared, until none are left
+    loop {
+        let unshareable: Vec<String> = shared
+            .iter()
+            .filter(|name| {
+                let core = owners[name.as_str()]
+                    .iter()
+                    .next()
+                    .expect("owned by a core");
+                let format = &apps[*core].0[name.as_str()];
+                type_names(format).iter().any(|used| !shared.contains(used))
+            })
+            .cloned()
+            .collect();
+        if unshareable.is_empty() {
+            return shared;
+        }
+        for name in unshareable {
+            shared.remove(&name);
+        }
+    }
+}
+
+/// The shared types the core's `registry` has, by the shared package's name in each
+/// language
+fn external(
+    registry: &Registry,
+    shared: &BTreeSet<String>,
+    output: Option<&Output>,
+    core_packages: &BTreeMap<&'static str, String>,
+) -> Result<BTreeMap<&'static str, BTreeMap<String, BTreeSet<String>>>> {
+    let types: BTreeSet<String> = registry
+        .keys()
+        .filter(|name| shared.contains(*name))
+        .cloned()
+        .collect();
+    if types.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    let mut external = BTreeMap::new();
+    for (language, package) in packages(output) {
+        if core_packages.get(language) == Some(&package) {
+            bail!("the shared {language} package {package} is also the name of a core's package");
+        }
+        external.insert(language, BTreeMap::from([(package, types.clone())]));
+    }
+    Ok(external)
+}
+
+fn packages(output: Option<&Output>) -> BTreeMap<&'static str, String> {
+    let Some(output) = output else {
+        return BTreeMap::new();
+    };
+    [
+        ("swift", &output.swift),
+        ("java", &output.java),
+        ("typescript", &output.typescript),
+    ]
+    .into_iter()
+    .filter_map(|(language, package)| Some((language, package.clone()?)))
+    .collect()
+}
+
+fn output_dir(core: &Core) -> Result<PathBuf> {
+    if let Some(dir) = core.output.as_ref().and_then(|output| output.dir.as_ref()) {
+        return Ok(dir.clone());
+    }
+    match &core.type_gen {
+        Some(type_gen) => Ok(type_gen.join("generated")),
+        None => bail!(
+            "core ({}) needs an output `dir` or a `type_gen` crate to write its partition to",
+            core.name
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn app(registry: serde_json::Value) -> (Registry, Manifest) {
+        (
+            serde_json::from_value(registry).unwrap(),
+            Manifest::default(),
+        )
+    }
+
+    fn apps() -> BTreeMap<String, (Registry, Manifest)> {
+        BTreeMap::from([
+            (
+                "counter".to_string(),
+                app(json!({
+                    "Event": { "ENUM": { "0": { "Get": "UNIT" } } },
+                    "ViewModel": { "STRUCT": [{ "count": "STR" }] },
+                    "Effect": { "ENUM": {
+                        "0": { "Http": { "NEWTYPE": { "TYPENAME": "HttpRequest" } } },
+                    } },
+                    "HttpRequest": { "STRUCT": [
+                        { "url": "STR" },
+                        { "headers": { "SEQ": { "TYPENAME": "HttpHeader" } } },
+                    ] },
+                    "HttpHeader": { "STRUCT": [{ "name": "STR" }, { "value": "STR" }] },
+                    "Settings": { "STRUCT": [{ "theme": { "TYPENAME": "Theme" } }] },
+                    "Theme": { "ENUM": { "0": { "Light": "UNIT" } } },
+                })),
+            ),
+            (
+                "notes".to_string(),
+                app(json!({
+                    "Event": { "ENUM": { "0": { "Get": "UNIT" } } },
+                    "ViewModel": { "STRUCT": [{ "text": "STR" }] },
+                    "Effect": { "ENUM": {
+                        "0": { "Http": { "NEWTYPE": { "TYPENAME": "HttpRequest" } } },
+                    } },
+                    "HttpRequest": { "STRUCT": [
+                        { "url": "STR" },
+                        { "headers": { "SEQ": { "TYPENAME": "HttpHeader" } } },
+                    ] },
+                    "HttpHeader": { "STRUCT": [{ "name": "STR" }, { "value": "STR" }] },
+                    "Settings": { "STRUCT": [{ "theme": { "TYPENAME": "Theme" } }] },
+                    "Theme": { "ENUM": { "0": { "Dark": "UNIT" } } },
+                })),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_shared_types() {
+        let apps = apps();
+        let owners = owners(&apps);
+        assert_eq!(owners["HttpHeader"], BTreeSet::from(["counter", "notes"]));
+
+        // `Theme` differs, so `Settings` using it can't be shared either, and the roots
+        // stay with their apps even where they are the same
+        assert_eq!(
+            shared(&apps, &owners),
+            BTreeSet::from(["HttpHeader".to_string(), "HttpRequest".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_external_types() {
+        let apps = apps();
+        let shared = shared(&apps, &owners(&apps));
+        let output = Output {
+            swift: Some("SharedTypes".to_string()),
+            java: Some("com.example.shared".to_string()),
+            ..Default::default()
+        };
+        let counter = Output {
+            swift: Some("CounterTypes".to_string()),
+            ..Default::default()
+        };
+
+        let counter_external = external(
+            &apps["counter"].0,
+            &shared,
+            Some(&output),
+            &packages(Some(&counter)),
+        )
+        .unwrap();
+        let types = BTreeSet::from(["HttpHeader".to_string(), "HttpRequest".to_string()]);
+        assert_eq!(
+            counter_external,
+            BTreeMap::from([
+                (
+                    "java",
+                    BTreeMap::from([("com.example.shared".to_string(), types.clone())])
+                ),
+                (
+                    "swift",
+                    BTreeMap::from([("SharedTypes".to_string(), types)])
+                ),
+            ])
+        );
+
+        let clashing = packages(Some(&output));
+        assert!(external(&apps["counter"].0, &shared, Some(&output), &clashing).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    config::Postprocess,
+    verify::{files, Language},
+    workspace,
+};
+
+pub(crate) fn postprocess() -> Result<()> {
+    let workspace = workspace::read_config()?;
+
+    let mut ran = 0;
+    for core in workspace.cores.values() {
+        let (Some(config), Some(type_gen)) = (&core.postprocess, &core.type_gen) else {
+            continue;
+        };
+        let generated = type_gen.join("generated");
+        if !generated.exists() {
+            bail!(
+                "core ({}) has no generated code in {}, run the type generation first",
+                core.name,
+                generated.display()
+            );
+        }
+
+        for (language, files) in run(config, &generated)? {
+            println!("{language:?}: postprocessed {files} files");
+            ran += 1;
+        }
+    }
+
+    if ran == 0 {
+        bail!("no core in Crux.toml has a `type_gen` crate and `postprocess` commands to run");
+    }
+    Ok(())
+}
+
+/// Run the commands on the code in the `generated` directory, returning the number of
+/// files each language's command was run on
+fn run(config: &Postprocess, generated: &Path) -> Result<Vec<(Language, usize)>> {
+    let mut ran = vec![];
+
+    for language in Language::ALL {
+        let Some(command) = command(config, language) else {
+            continue;
+        };
+        let code = generated.join(language.dir());
+        let files = files(&code, language.extension());
+        if files.is_empty() {
+            continue;
+        }
+
+        let status = build(command, &files)
+            .with_context(|| format!("{language:?}: the postprocess command is empty"))?
+            .status()
+            .with_context(|| format!("{language:?}: could not run `{command}`"))?;
+        if !status.success() {
+            bail!("{language:?}: `{command}` failed with {status}");
+        }
+        ran.push((language, files.len()));
+    }
+
+    Ok(ran)
+}
+
+fn command(config: &Postprocess, language: Language) -> Option<&str> {
+    match language {
+        Language::Swift => config.swift.as_deref(),
+        Language::Java => config.java.as_deref(),
+        Language::TypeScript => config.typescript.as_deref(),
+    }
+}
+
+/// The `command`, split on whitespace, with the `files` appended to its arguments
+fn build(command: &str, files: &[PathBuf]) -> Option<Command> {
+    let mut words = command.split_whitespace();
+
+    let mut command = Command::new(words.next()?);
+    command.args(words).args(files);
+    Some(command)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_build_command() {
+        let files = vec![PathBuf::from("a.swift"), PathBuf::from("b.swift")];
+
+        let command = build("swiftformat --quiet", &files).unwrap();
+        assert_eq!(command.get_program(), "swiftformat");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            ["--quiet", "a.swift", "b.swift"]
+        );
+
+        assert!(build(" ", &files).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_postprocess() {
+        let generated =
+            std::env::temp_dir().join(format!("crux_postprocess_{}/generated", std::process::id()));
+        fs::create_dir_all(generated.join("swift/SharedTypes")).unwrap();
+        fs::create_dir_all(generated.join("typescript")).unwrap();
+        fs::write(generated.join("swift/SharedTypes/SharedTypes.swift"), "").unwrap();
+        fs::write(generated.join("typescript/index.ts"), "").unwrap();
+        fs::write(generated.join("typescript/package.json"), "{}").unwrap();
+
+        let config = Postprocess {
+            swift: Some("true".to_string()),
+            java: Some("false".to_string()),
+            typescript: Some("true --ts".to_string()),
+        };
+        // there is no Java code to run `false` on
+        let ran = run(&config, &generated).unwrap();
+        assert_eq!(ran, [(Language::Swift, 1), (Language::TypeScript, 1)]);
+
+        let config = Postprocess {
+            swift: Some("false".to_string()),
+            ..Default::default()
+        };
+        assert!(run(&config, &generated).is_err());
+
+        fs::remove_dir_all(generated.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,490 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use serde::Deserialize;
+use toml::Table;
+
+use crate::{
+    api_diff::{parse_registry, read, read_manifest, Registry},
+    config::Core,
+    decode::decode,
+    diff, workspace,
+};
+
+/// The steps of a replayed session whose view differs from the recorded one, as printed
+/// by the replay binary
+#[derive(Debug, Deserialize)]
+struct Report {
+    steps: usize,
+    divergences: Vec<Divergence>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Divergence {
+    step: usize,
+    message: String,
+    expected: Vec<u8>,
+    actual: Vec<u8>,
+}
+
+/// Replay a session file against the current code of a core, and show the steps whose view
+/// differs from the recorded one.
+///
+/// The CLI can't load the core itself, so it writes a small binary crate which depends on
+/// the core and on `crux_core` with the `devtools` feature, in the core's target directory,
+/// and runs it with cargo.
+pub(crate) fn replay(
+    session: &Path,
+    core: Option<&str>,
+    app: &str,
+    effect: &str,
+    seed: Option<u64>,
+) -> Result<()> {
+    let session = session
+        .canonicalize()
+        .with_context(|| format!("could not read {}", session.display()))?;
+    for path in [app, effect] {
+        if !is_rust_path(path) {
+            bail!("{path} is not a path to a type in the core's crate");
+        }
+    }
+
+    let workspace = workspace::read_config()?;
+    let core = match core {
+        Some(name) => workspace
+            .cores
+            .get(name)
+            .with_context(|| format!("there is no core named {name} in Crux.toml"))?,
+        None => match workspace.cores.values().collect::<Vec<_>>()[..] {
+            [core] => core,
+            [] => bail!("there are no cores in Crux.toml"),
+            _ => bail!("there is more than one core in Crux.toml, choose one with --core"),
+        },
+    };
+
+    let metadata = Metadata::read(&core.source)?;
+    let dir = metadata
+        .target_directory
+        .join("crux")
+        .join("replay")
+        .join(&core.name);
+    let package = metadata.core_package(&core.source)?;
+    let lib = package
+        .lib_name()
+        .with_context(|| format!("{} has no Rust library to replay against", package.name))?;
+
+    fs::create_dir_all(dir.join("src"))
+        .with_context(|| format!("could not create {}", dir.display()))?;
+    let manifest = harness_manifest(&package.name, &package.manifest_dir(), package.crux_core()?)?;
+    fs::write(dir.join("Cargo.toml"), manifest)?;
+    fs::write(
+        dir.join("src").join("main.rs"),
+        harness_main(lib, app, effect, seed),
+    )?;
+    // resolve the same versions of the dependencies as the core's workspace
+    let lockfile = metadata.workspace_root.join("Cargo.lock");
+    if lockfile.exists() {
+        fs::copy(&lockfile, dir.join("Cargo.lock"))?;
+    }
+
+    println!("Replaying {} against {}", session.display(), core.name);
+    let output = Command::new("cargo")
+        .args(["run", "--quiet", "--manifest-path"])
+        .arg(dir.join("Cargo.toml"))
+        .arg("--")
+        .arg(&session)
+        .env("CARGO_TARGET_DIR", &metadata.target_directory)
+        .stderr(Stdio::inherit())
+        .output()
+        .context("could not run cargo")?;
+    if !output.status.success() {
+        bail!("the session could not be replayed");
+    }
+    let report: Report =
+        serde_json::from_slice(&output.stdout).context("the replay's report is not valid")?;
+
+    let view = registry_view(core);
+    for divergence in &report.divergences {
+        show(divergence, view.as_ref());
+    }
+
+    if !report.divergences.is_empty() {
+        bail!(
+            "the view differs from the recording in {} of {} steps",
+            report.divergences.len(),
+            report.steps
+        );
+    }
+    println!(
+        "The view matches the recording in all {} steps",
+        report.steps
+    );
+    Ok(())
+}
+
+/// The core's registry, and the name of its view model, to decode the views with
+fn registry_view(core: &Core) -> Option<(Registry, String)> {
+    let path = core.registry.as_ref()?;
+    let registry = parse_registry(path, &read(path).ok()?).ok()?;
+    let view_model = read_manifest(path).ok()?.view_model?;
+    Some((registry, view_model))
+}
+
+/// Show the recorded and the replayed view, as a diff of their JSON if they can be decoded
+fn show(divergence: &Divergence, view: Option<&(Registry, String)>) {
+    let label = format!("step {} ({})", divergence.step, divergence.message);
+    let decoded = view.and_then(|(registry, root)| {
+        let json = |bytes: &[u8]| {
+            let value = decode(registry, root, bytes).ok()?;
+            let mut json = serde_json::to_string_pretty(&value).ok()?;
+            json.push('\n');
+            Some(json)
+        };
+        Some((json(&divergence.expected)?, json(&divergence.actual)?))
+    });
+
+    match decoded {
+        Some((expected, actual)) => diff::show(Path::new(&label), &actual, &expected),
+        None => {
+            let (expected, actual) = (&divergence.expected, &divergence.actual);
+            let first = expected
+                .iter()
+                .zip(actual)
+                .position(|(a, b)| a != b)
+                .unwrap_or_else(|| expected.len().min(actual.len()));
+            println!(
+                "{:-<80}\n{} recorded {} bytes, replayed {} bytes, first difference at byte {first}\n",
+                label,
+                style("view differs:").yellow(),
+                expected.len(),
+                actual.len(),
+            );
+        }
+    }
+}
+
+/// The parts of `cargo metadata` about the core's workspace the replay needs
+#[derive(Debug, Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    target_directory: PathBuf,
+    workspace_root: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct Package {
+    name: String,
+    manifest_path: PathBuf,
+    dependencies: Vec<Dependency>,
+    targets: Vec<Target>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Dependency {
+    name: String,
+    source: Option<String>,
+    req: String,
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Target {
+    name: String,
+    kind: Vec<String>,
+}
+
+impl Metadata {
+    fn read(source: &Path) -> Result<Self> {
+        let output = Command::new("cargo")
+            .args([
+                "metadata",
+                "--format-version",
+                "1",
+                "--no-deps",
+                "--manifest-path",
+            ])
+            .arg(source.join("Cargo.toml"))
+            .stderr(Stdio::inherit())
+            .output()
+            .context("could not run cargo")?;
+        if !output.status.success() {
+            bail!("could not read the metadata of {}", source.display());
+        }
+        serde_json::from_slice(&output.stdout).context("cargo metadata is not valid")
+    }
+
+    fn core_package(&self, source: &Path) -> Result<&Package> {
+        let manifest = source.join("Cargo.toml").canonicalize()?;
+        self.packages
+            .iter()
+            .find(|package| package.manifest_path == manifest)
+            .with_context(|| format!("{} is not a package", source.display()))
+    }
+}
+
+impl Package {
+    fn manifest_dir(&self) -> PathBuf {
+        self.manifest_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    }
+
+    /// The name to `use` the package's library by, if it has one other crates can use
+    fn lib_name(&self) -> Option<&str> {
+        self.targets
+            .iter()
+            .find(|target| {
+                target
+                    .kind
+                    .iter()
+                    .any(|kind| kind == "lib" || kind == "rlib")
+            })
+            .map(|target| target.name.as_str())
+    }
+
+    /// The package's `crux_core` dependency, as a dependency of the replay binary, so that
+    /// both get the same crate
+    fn crux_core(&self) -> Result<Table> {
+        let dependency = self
+            .dependencies
+            .iter()
+            .find(|dependency| dependency.name == "crux_core")
+            .with_context(|| format!("{} doesn't depend on crux_core", self.name))?;
+        Ok(dependency.as_toml())
+    }
+}
+
+impl Dependency {
+    fn as_toml(&self) -> Table {
+        let mut spec = Table::new();
+        match (&self.path, self.source.as_deref()) {
+            (Some(path), _) => {
+                spec.insert("path".into(), path.display().to_string().into());
+            }
+            (None, Some(source)) if source.starts_with("git+") => {
+                // e.g. `git+https://github.com/redbadger/crux?branch=master`
+                let url = &source["git+".len()..];
+                let (url, reference) = url.split_once('?').unwrap_or((url, ""));
+                spec.insert("git".into(), url.into());
+                if let Some((key, value)) = reference.split_once('=') {
+                    spec.insert(key.into(), value.into());
+                }
+            }
+            (None, source) => {
+                spec.insert("version".into(), self.req.clone().into());
+                if let Some(index) = source
+                    .filter(|source| !source.ends_with("github.com/rust-lang/crates.io-index"))
+                    .filter(|source| *source != "sparse+https://index.crates.io/")
+                {
+                    let index = index.strip_prefix("registry+").unwrap_or(index);
+                    spec.insert("registry-index".into(), index.into());
+                }
+            }
+        }
+        spec.insert("features".into(), vec!["devtools"].into());
+        spec
+    }
+}
+
+fn harness_manifest(package: &str, path: &Path, crux_core: Table) -> Result<String> {
+    let mut core = Table::new();
+    core.insert("path".into(), path.display().to_string().into());
+
+    let mut dependencies = Table::new();
+    dependencies.insert("crux_core".into(), crux_core.into());
+    dependencies.insert(package.into(), core.into());
+    dependencies.insert("serde_json".into(), "1".into());
+
+    let mut manifest = Table::new();
+    manifest.insert(
+        "package".into(),
+        toml::toml! {
+            name = "crux_replay"
+            version = "0.0.0"
+            edition = "2021"
+            publish = false
+        }
+        .into(),
+    );
+    manifest.insert("dependencies".into(), dependencies.into());
+    // not part of the core's workspace
+    manifest.insert("workspace".into(), Table::new().into());
+
+    Ok(format!(
+        "# written by `crux replay`\n{}",
+        toml::to_string(&manifest)?
+    ))
+}
+
+fn harness_main(lib: &str, app: &str, effect: &str, seed: Option<u64>) -> String {
+    let core = match seed {
+        Some(seed) => format!("Core::new().with_seed({seed})"),
+        None => "Core::new()".to_string(),
+    };
+
+    format!(
+        r#"// written by `crux replay`
+use crux_core::{{
+    bridge::{{
+        session::{{Message, Session}},
+        Bridge,
+    }},
+    Core,
+}};
+
+fn main() {{
+    let path = std::env::args().nth(1).expect("the path of the session");
+    let data = std::fs::read(&path).unwrap_or_else(|e| fail(format!("could not read {{path}}: {{e}}")));
+    let session = Session::import(&data).unwrap_or_else(|e| fail(e.to_string()));
+
+    let bridge = Bridge::<{lib}::{effect}, {lib}::{app}>::new({core});
+    let divergences = bridge
+        .replay(&session)
+        .unwrap_or_else(|e| fail(format!("the core rejected a message: {{e}}")));
+
+    let divergences: Vec<_> = divergences
+        .into_iter()
+        .map(|divergence| {{
+            let message = match &session.steps[divergence.step].message {{
+                Message::Init {{ .. }} => "init".to_string(),
+                Message::Event {{ .. }} => "event".to_string(),
+                Message::Events {{ .. }} => "events".to_string(),
+                Message::Response {{ id, .. }} => format!("response to {{id}}"),
+            }};
+            serde_json::json!({{
+                "step": divergence.step,
+                "message": message,
+                "expected": divergence.expected,
+                "actual": divergence.actual,
+            }})
+        }})
+        .collect();
+    let report = serde_json::json!({{
+        "steps": session.steps.len(),
+        "divergences": divergences,
+    }});
+    println!("{{report}}");
+}}
+
+fn fail(message: String) -> ! {{
+    eprintln!("{{message}}");
+    std::process::exit(1);
+}}
+"#
+    )
+}
+
+/// Whether `path` is a Rust path, e.g. `app::Counter`, so it can go in the generated code
+fn is_rust_path(path: &str) -> bool {
+    path.split("::").all(|segment| {
+        let mut chars = segment.chars();
+        chars
+            .next()
+            .map_or(false, |first| first.is_ascii_alphabetic() || first == '_')
+            && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use toml::Value;
+
+    use super::*;
+
+    fn dependency(source: Option<&str>, path: Option<&str>) -> Dependency {
+        Dependency {
+            name: "crux_core".to_string(),
+            source: source.map(str::to_string),
+            req: "^0.10.0".to_string(),
+            path: path.map(PathBuf::from),
+        }
+    }
+
+    #[test]
+    fn depends_on_the_same_crux_core() {
+        let toml = |dependency: Dependency| Value::from(dependency.as_toml());
+
+        assert_eq!(
+            toml(dependency(None, Some("/work/crux/crux_core"))),
+            toml::toml! {
+                path = "/work/crux/crux_core"
+                features = ["devtools"]
+            }
+            .into()
+        );
+        assert_eq!(
+            toml(dependency(
+                Some("registry+https://github.com/rust-lang/crates.io-index"),
+                None
+            )),
+            toml::toml! {
+                version = "^0.10.0"
+                features = ["devtools"]
+            }
+            .into()
+        );
+        assert_eq!(
+            toml(dependency(
+                Some("git+https://github.com/redbadger/crux?branch=master"),
+                None
+            )),
+            toml::toml! {
+                git = "https://github.com/redbadger/crux"
+                branch = "master"
+                features = ["devtools"]
+            }
+            .into()
+        );
+        assert_eq!(
+            toml(dependency(Some("registry+https://example.com/index"), None)),
+            toml::toml! {
+                version = "^0.10.0"
+                registry-index = "https://example.com/index"
+                features = ["devtools"]
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn manifest_depends_on_the_core() {
+        let crux_core = dependency(None, Some("/work/crux/crux_core")).as_toml();
+        let manifest =
+            harness_manifest("shared", Path::new("/work/app/shared"), crux_core).unwrap();
+        let manifest: Table = toml::from_str(&manifest).unwrap();
+
+        assert_eq!(
+            manifest["dependencies"]["crux_core"]["path"].as_str(),
+            Some("/work/crux/crux_core")
+        );
+        assert_eq!(
+            manifest["dependencies"]["shared"]["path"].as_str(),
+            Some("/work/app/shared")
+        );
+        assert!(manifest.contains_key("workspace"));
+    }
+
+    #[test]
+    fn main_uses_the_app_and_effect() {
+        let main = harness_main("shared", "App", "app::Effect", Some(42));
+
+        assert!(main.contains(
+            "Bridge::<shared::app::Effect, shared::App>::new(Core::new().with_seed(42))"
+        ));
+    }
+
+    #[test]
+    fn only_rust_paths_are_types() {
+        assert!(is_rust_path("App"));
+        assert!(is_rust_path("app::Counter"));
+        assert!(!is_rust_path("App; fn main() {}"));
+        assert!(!is_rust_path("app::"));
+        assert!(!is_rust_path(""));
+    }
+}
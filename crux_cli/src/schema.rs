@@ -0,0 +1,297 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    api_diff::{parse_registry, read, read_manifest, registries, Registry},
+    api_docs::type_names,
+    args::{Language, OutputArgs},
+    generated::Generated,
+};
+
+pub(crate) fn schema(
+    registry: Option<&Path>,
+    output: &Path,
+    language: Language,
+    args: &OutputArgs,
+) -> Result<()> {
+    let Language::JsonSchema = language;
+
+    let mut generated = Generated::new(output, ".schema.json");
+    for (name, path) in &registries(registry)? {
+        let registry = parse_registry(path, &read(path)?)?;
+
+        // the types at the edges of the core, which the shells and any backends exchange
+        let manifest = read_manifest(path)?;
+        for root in manifest.roots().filter(|root| registry.contains_key(*root)) {
+            let schema = json_schema(&registry, root, &manifest.deny_unknown_fields);
+            let mut schema = serde_json::to_string_pretty(&schema)?;
+            schema.push('\n');
+            generated.add(name, root, schema);
+        }
+    }
+
+    generated.write(args)
+}
+
+/// A JSON Schema document for `root` and all the types it uses, describing the types
+/// the way serde serializes them to JSON, e.g. enums are externally tagged. Objects may
+/// have fields the type doesn't know, as serde ignores them, unless the type is one of
+/// the ones in `deny_unknown_fields`.
+fn json_schema(registry: &Registry, root: &str, deny_unknown_fields: &BTreeSet<String>) -> Value {
+    let defs: Map<String, Value> = reachable(registry, root)
+        .into_iter()
+        .filter_map(|name| {
+            let format = registry.get(&name)?;
+            let container = container(format, deny_unknown_fields.contains(&name));
+            Some((name, container))
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": root,
+        "$ref": type_ref(root),
+        "$defs": defs,
+    })
+}
+
+fn reachable(registry: &Registry, root: &str) -> BTreeSet<String> {
+    let mut seen = BTreeSet::from([root.to_string()]);
+    let mut pending = vec![root.to_string()];
+    while let Some(name) = pending.pop() {
+        let Some(format) = registry.get(&name) else {
+            continue;
+        };
+        for name in type_names(format) {
+            if seen.insert(name.clone()) {
+                pending.push(name);
+            }
+        }
+    }
+    seen
+}
+
+fn container(format: &Value, deny_unknown_fields: bool) -> Value {
+    match entry(format) {
+        Some((kind, body)) => match kind.as_str() {
+            "NEWTYPESTRUCT" => schema_of(body),
+            "TUPLESTRUCT" => tuple(body),
+            "STRUCT" => object(body, deny_unknown_fields),
+            "ENUM" => {
+                let mut variants: Vec<_> = body.as_object().into_iter().flatten().collect();
+                variants.sort_by_key(|(index, _)| index.parse::<u32>().unwrap_or(u32::MAX));
+                let variants: Vec<_> = variants
+                    .into_iter()
+                    .filter_map(|(_, variant)| entry(variant))
+                    .map(|(name, format)| variant(name, format, deny_unknown_fields))
+                    .collect();
+                json!({ "oneOf": variants })
+            }
+            _ => json!({}),
+        },
+        // UNITSTRUCT
+        None => json!({ "type": "null" }),
+    }
+}
+
+fn variant(name: &str, format: &Value, deny_unknown_fields: bool) -> Value {
+    let data = match entry(format) {
+        Some((kind, body)) => match kind.as_str() {
+            "NEWTYPE" => schema_of(body),
+            "TUPLE" => tuple(body),
+            "STRUCT" => object(body, deny_unknown_fields),
+            _ => json!({}),
+        },
+        // unit variants are serialized as just their name
+        None => return json!({ "const": name }),
+    };
+
+    json!({
+        "type": "object",
+        "properties": { name: data },
+        "required": [name],
+        "additionalProperties": false,
+    })
+}
+
+/// The schema of a field's type, referring to other shared types by name
+fn schema_of(format: &Value) -> Value {
+    match format {
+        Value::String(primitive) => primitive_schema(primitive),
+        _ => match entry(format) {
+            Some((kind, body)) => match kind.as_str() {
+                "TYPENAME" => json!({ "$ref": type_ref(body.as_str().unwrap_or_default()) }),
+                "OPTION" => json!({ "anyOf": [schema_of(body), { "type": "null" }] }),
+                "SEQ" => json!({ "type": "array", "items": schema_of(body) }),
+                // JSON object keys are always strings
+                "MAP" => json!({
+                    "type": "object",
+                    "additionalProperties": schema_of(&body["VALUE"]),
+                }),
+                "TUPLE" => tuple(body),
+                "TUPLEARRAY" => json!({
+                    "type": "array",
+                    "items": schema_of(&body["CONTENT"]),
+                    "minItems": body["SIZE"],
+                    "maxItems": body["SIZE"],
+                }),
+                _ => json!({}),
+            },
+            None => json!({}),
+        },
+    }
+}
+
+fn primitive_schema(primitive: &str) -> Value {
+    match primitive {
+        "UNIT" => json!({ "type": "null" }),
+        "BOOL" => json!({ "type": "boolean" }),
+        "I8" | "I16" | "I32" | "I64" | "I128" => json!({ "type": "integer" }),
+        "U8" | "U16" | "U32" | "U64" | "U128" => json!({ "type": "integer", "minimum": 0 }),
+        "F32" | "F64" => json!({ "type": "number" }),
+        "CHAR" => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        "STR" => json!({ "type": "string" }),
+        // serde_json serializes bytes as an array of numbers
+        "BYTES" => json!({
+            "type": "array",
+            "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+        }),
+        _ => json!({}),
+    }
+}
+
+fn tuple(formats: &Value) -> Value {
+    let items: Vec<_> = formats
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(schema_of)
+        .collect();
+    json!({
+        "type": "array",
+        "prefixItems": items,
+        "minItems": items.len(),
+        "maxItems": items.len(),
+    })
+}
+
+fn object(fields: &Value, deny_unknown_fields: bool) -> Value {
+    let mut properties = BTreeMap::new();
+    let mut required = vec![];
+    for (name, format) in fields.as_array().into_iter().flatten().filter_map(entry) {
+        properties.insert(name.clone(), schema_of(format));
+        required.push(name.clone());
+    }
+    let mut object = json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    });
+    if deny_unknown_fields {
+        object["additionalProperties"] = false.into();
+    }
+    object
+}
+
+fn type_ref(name: &str) -> String {
+    format!("#/$defs/{name}")
+}
+
+fn entry(map: &Value) -> Option<(&String, &Value)> {
+    map.as_object()?.iter().next()
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn registry() -> Registry {
+        serde_json::from_value(json!({
+            "Effect": { "ENUM": {
+                "0": { "Render": { "NEWTYPE": { "TYPENAME": "RenderOperation" } } },
+            } },
+            "Event": { "ENUM": {
+                "0": { "Increment": "UNIT" },
+                "1": { "Set": { "NEWTYPE": { "OPTION": "I64" } } },
+                "2": { "Move": { "STRUCT": [{ "x": "I32" }, { "y": "I32" }] } },
+                "3": { "Tag": { "NEWTYPE": { "TYPENAME": "Tag" } } },
+            } },
+            "RenderOperation": "UNITSTRUCT",
+            "Tag": { "TUPLESTRUCT": ["STR", "U8"] },
+            "ViewModel": { "STRUCT": [
+                { "count": "STR" },
+                { "totals": { "MAP": { "KEY": "STR", "VALUE": "U64" } } },
+            ] },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_defs_include_only_reachable_types() {
+        let schema = json_schema(&registry(), "Event", &BTreeSet::new());
+
+        assert_eq!(schema["$ref"], "#/$defs/Event");
+        let defs: Vec<_> = schema["$defs"].as_object().unwrap().keys().collect();
+        assert_eq!(defs, ["Event", "Tag"]);
+    }
+
+    #[test]
+    fn test_enums_are_externally_tagged() {
+        let schema = json_schema(&registry(), "Event", &BTreeSet::new());
+        let variants = &schema["$defs"]["Event"]["oneOf"];
+
+        assert_eq!(variants[0], json!({ "const": "Increment" }));
+        assert_eq!(
+            variants[1]["properties"]["Set"],
+            json!({ "anyOf": [{ "type": "integer" }, { "type": "null" }] })
+        );
+        assert_eq!(variants[2]["required"], json!(["Move"]));
+        assert_eq!(
+            variants[2]["properties"]["Move"]["required"],
+            json!(["x", "y"])
+        );
+        assert_eq!(
+            variants[3]["properties"]["Tag"],
+            json!({ "$ref": "#/$defs/Tag" })
+        );
+        assert_eq!(schema["$defs"]["Tag"]["prefixItems"][1]["minimum"], 0);
+    }
+
+    #[test]
+    fn test_structs_and_maps() {
+        let schema = json_schema(&registry(), "ViewModel", &BTreeSet::new());
+        let view_model = &schema["$defs"]["ViewModel"];
+
+        // unknown fields are allowed, unless denied
+        assert_eq!(view_model.get("additionalProperties"), None);
+        assert_eq!(
+            view_model["properties"]["count"],
+            json!({ "type": "string" })
+        );
+        assert_eq!(
+            view_model["properties"]["totals"]["additionalProperties"],
+            json!({ "type": "integer", "minimum": 0 })
+        );
+
+        let denied = BTreeSet::from(["ViewModel".to_string()]);
+        let schema = json_schema(&registry(), "ViewModel", &denied);
+        assert_eq!(schema["$defs"]["ViewModel"]["additionalProperties"], false);
+    }
+
+    #[test]
+    fn test_unit_structs_are_null() {
+        let schema = json_schema(&registry(), "Effect", &BTreeSet::new());
+
+        assert_eq!(
+            schema["$defs"]["RenderOperation"],
+            json!({ "type": "null" })
+        );
+    }
+}
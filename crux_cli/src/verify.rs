@@ -0,0 +1,321 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+use ignore::Walk;
+
+use crate::workspace;
+
+/// A language the type generation writes code for, and how to compile it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Language {
+    Swift,
+    Java,
+    TypeScript,
+}
+
+impl Language {
+    pub(crate) const ALL: [Language; 3] = [Language::Swift, Language::Java, Language::TypeScript];
+
+    /// The directory `TypeGen` writes the language's code to, inside the generated directory
+    pub(crate) fn dir(self) -> &'static str {
+        match self {
+            Language::Swift => "swift",
+            Language::Java => "java",
+            Language::TypeScript => "typescript",
+        }
+    }
+
+    /// The extension of the language's source files
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Language::Swift => "swift",
+            Language::Java => "java",
+            Language::TypeScript => "ts",
+        }
+    }
+
+    fn tool(self) -> &'static str {
+        match self {
+            Language::Swift => "swift",
+            Language::Java => "javac",
+            Language::TypeScript => "pnpm",
+        }
+    }
+
+    /// The commands compiling the code in `dir`, each with the directory to run it in
+    fn commands(self, dir: &Path, scratch: &Path) -> Result<Vec<(Command, PathBuf)>> {
+        let mut commands = vec![];
+        match self {
+            // one package per module, named after it
+            Language::Swift => {
+                let entries = fs::read_dir(dir)
+                    .with_context(|| format!("could not read {}", dir.display()))?;
+                for entry in entries {
+                    let package = entry?.path();
+                    if package.join("Package.swift").exists() {
+                        let mut command = Command::new("swift");
+                        command.arg("build").arg("--scratch-path").arg(scratch);
+                        commands.push((command, package));
+                    }
+                }
+            }
+            Language::Java => {
+                let mut command = Command::new("javac");
+                command
+                    .arg("-d")
+                    .arg(scratch)
+                    .args(files(dir, self.extension()));
+                commands.push((command, dir.to_path_buf()));
+            }
+            Language::TypeScript => {
+                let mut command = Command::new("pnpm");
+                command.args(["exec", "tsc", "--noEmit", "--pretty", "false"]);
+                commands.push((command, dir.to_path_buf()));
+            }
+        }
+        Ok(commands)
+    }
+}
+
+pub(crate) fn verify(generated: Option<&Path>) -> Result<()> {
+    let mut failed = false;
+    for dir in generated_dirs(generated)? {
+        println!("{:-<80}\nVerifying {}", "", dir.display());
+
+        for language in Language::ALL {
+            let code = dir.join(language.dir());
+            if !code.exists() {
+                continue;
+            }
+            if !is_available(language.tool()) {
+                println!("{:?}: skipped, `{}` not found", language, language.tool());
+                continue;
+            }
+
+            let errors = compile(language, &code)?;
+            if errors.is_empty() {
+                println!("{language:?}: ok");
+            } else {
+                failed = true;
+                println!("{language:?}: failed to compile");
+                for error in errors {
+                    println!("  {error}");
+                }
+            }
+        }
+    }
+
+    if failed {
+        bail!("generated code failed to compile");
+    }
+    Ok(())
+}
+
+/// The generated directories to verify: the given one, or the `generated` directory
+/// of the `type_gen` crate of each core in Crux.toml
+fn generated_dirs(generated: Option<&Path>) -> Result<Vec<PathBuf>> {
+    if let Some(dir) = generated {
+        return Ok(vec![dir.to_path_buf()]);
+    }
+
+    let workspace = workspace::read_config()?;
+    let dirs: Vec<_> = workspace
+        .cores
+        .values()
+        .filter_map(|core| Some(core.type_gen.as_ref()?.join("generated")))
+        .filter(|dir| dir.exists())
+        .collect();
+    if dirs.is_empty() {
+        bail!("no generated code given, and no core in Crux.toml has a `type_gen` crate with a `generated` directory");
+    }
+    Ok(dirs)
+}
+
+fn is_available(tool: &str) -> bool {
+    env::var_os("PATH").map_or(false, |paths| {
+        env::split_paths(&paths)
+            .any(|dir| dir.join(tool).is_file() || dir.join(format!("{tool}.exe")).is_file())
+    })
+}
+
+pub(crate) fn files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    Walk::new(dir)
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == extension))
+        .collect()
+}
+
+/// Compile the code, returning the errors, if any, with the generated types they are in
+fn compile(language: Language, code: &Path) -> Result<Vec<String>> {
+    let code = &code.canonicalize()?;
+    let scratch = env::temp_dir().join(format!("crux_verify_{}", std::process::id()));
+
+    let mut errors = vec![];
+    for (mut command, dir) in language.commands(code, &scratch)? {
+        let output = command.current_dir(&dir).output()?;
+        if output.status.success() {
+            continue;
+        }
+
+        let output = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let mut located: Vec<_> = output
+            .lines()
+            .filter_map(|line| parse_error(language, line))
+            .map(|error| error.describe(&dir))
+            .collect();
+        if located.is_empty() {
+            // not in a format we know, so show it as it is
+            located.extend(output.lines().map(str::to_string));
+        }
+        errors.extend(located);
+    }
+
+    fs::remove_dir_all(&scratch).unwrap_or(());
+    Ok(errors)
+}
+
+#[derive(Debug, PartialEq)]
+struct CompileError {
+    file: PathBuf,
+    line: usize,
+    message: String,
+}
+
+impl CompileError {
+    /// The error, with the type it is in, which has the name of the Rust type it was
+    /// generated from
+    fn describe(&self, dir: &Path) -> String {
+        let path = dir.join(&self.file);
+        let name = fs::read_to_string(&path)
+            .ok()
+            .and_then(|source| enclosing_type(&source, self.line));
+
+        match name {
+            Some(name) => format!(
+                "{name} ({}:{}): {}",
+                self.file.display(),
+                self.line,
+                self.message
+            ),
+            None => format!("{}:{}: {}", self.file.display(), self.line, self.message),
+        }
+    }
+}
+
+/// Parse an error from the compiler's output, e.g.
+/// - swift: `/path/SharedTypes.swift:12:5: error: cannot find type 'Foo' in scope`
+/// - javac: `com/example/Event.java:12: error: cannot find symbol`
+/// - tsc: `types/shared_types.ts(12,5): error TS2304: Cannot find name 'Foo'.`
+fn parse_error(language: Language, line: &str) -> Option<CompileError> {
+    let (location, message) = match language {
+        Language::Swift | Language::Java => line.split_once(": error: ")?,
+        Language::TypeScript => line.split_once(": error ")?,
+    };
+
+    let (file, line) = match language {
+        Language::Swift | Language::Java => {
+            let mut parts = location.splitn(3, ':');
+            (parts.next()?, parts.next()?)
+        }
+        Language::TypeScript => {
+            let (file, position) = location.strip_suffix(')')?.rsplit_once('(')?;
+            (file, position.split(',').next()?)
+        }
+    };
+
+    Some(CompileError {
+        file: PathBuf::from(file),
+        line: line.parse().ok()?,
+        message: message.to_string(),
+    })
+}
+
+/// The name of the top level type declared closest above `line` (1-based) in the generated
+/// `source`, skipping nested ones like Java's builders and variant classes.
+fn enclosing_type(source: &str, line: usize) -> Option<String> {
+    const KEYWORDS: [&str; 4] = ["struct", "enum", "class", "interface"];
+
+    source
+        .lines()
+        .take(line)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .find_map(|line| {
+            let mut words = line.split_whitespace();
+            words.find(|word| KEYWORDS.contains(word))?;
+            let name: String = words
+                .next()?
+                .chars()
+                .take_while(|c| c.is_alphanumeric())
+                .collect();
+            (!name.is_empty()).then_some(name)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_error(
+                Language::Swift,
+                "/tmp/Sources/SharedTypes/SharedTypes.swift:12:5: error: cannot find type 'Foo' in scope"
+            ),
+            Some(CompileError {
+                file: PathBuf::from("/tmp/Sources/SharedTypes/SharedTypes.swift"),
+                line: 12,
+                message: "cannot find type 'Foo' in scope".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_error(
+                Language::Java,
+                "com/example/Event.java:3: error: cannot find symbol"
+            )
+            .map(|error| error.line),
+            Some(3)
+        );
+        assert_eq!(
+            parse_error(
+                Language::TypeScript,
+                "types/shared_types.ts(40,7): error TS2304: Cannot find name 'Foo'."
+            ),
+            Some(CompileError {
+                file: PathBuf::from("types/shared_types.ts"),
+                line: 40,
+                message: "TS2304: Cannot find name 'Foo'.".to_string(),
+            })
+        );
+        assert_eq!(parse_error(Language::Swift, "Compiling SharedTypes"), None);
+    }
+
+    #[test]
+    fn test_enclosing_type() {
+        let swift = "import Serde\n\npublic struct ViewModel: Hashable {\n    @Indirect public var count: Foo\n}\n\nindirect public enum Event: Hashable {\n    case increment\n}\n";
+        assert_eq!(enclosing_type(swift, 4).as_deref(), Some("ViewModel"));
+        assert_eq!(enclosing_type(swift, 8).as_deref(), Some("Event"));
+        assert_eq!(enclosing_type(swift, 1), None);
+
+        let java = "public final class User {\n    public final String name;\n\n    public static final class Builder {\n        public Strin name;\n";
+        assert_eq!(enclosing_type(java, 5).as_deref(), Some("User"));
+
+        let typescript = "export class EventVariantIncrement extends Event {\n  constructor () {\n";
+        assert_eq!(
+            enclosing_type(typescript, 2).as_deref(),
+            Some("EventVariantIncrement")
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::{fs, path::Path, process::Command};
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use serde::Deserialize;
+
+use crate::workspace;
+
+const LOCKFILE: &str = "Cargo.lock";
+
+/// The version of crux_core whose bridge and conventions this CLI generates code for.
+const CLI_CORE_VERSION: &str = crux_core::VERSION;
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    version: String,
+}
+
+pub(crate) fn upgrade(check: bool) -> Result<()> {
+    println!(
+        "crux CLI {} (crux_core {CLI_CORE_VERSION})",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let problems = problems()?;
+    for problem in &problems {
+        println!("{} {problem}", style("warning:").yellow());
+    }
+
+    if check {
+        if !problems.is_empty() {
+            bail!("the crux CLI is not compatible with this workspace");
+        }
+        println!("The crux CLI is compatible with this workspace");
+        return Ok(());
+    }
+
+    let status = Command::new("cargo")
+        .args(["install", "--locked", "crux_cli"])
+        .status()
+        .context("could not run cargo")?;
+    if !status.success() {
+        bail!("cargo install crux_cli failed");
+    }
+
+    Ok(())
+}
+
+/// Warn about any version mismatches before running another command, because code
+/// generated for a different version of crux_core may not work with the workspace's.
+pub(crate) fn warn_if_incompatible() {
+    let Ok(problems) = problems() else {
+        return;
+    };
+    for problem in problems {
+        eprintln!("{} {problem}", style("warning:").yellow());
+    }
+}
+
+fn problems() -> Result<Vec<String>> {
+    let workspace = workspace::read_config()?;
+    let path = Path::new(LOCKFILE);
+    let locked = if path.exists() {
+        let lockfile =
+            fs::read_to_string(path).with_context(|| format!("could not read {LOCKFILE}"))?;
+        locked_versions(&lockfile, "crux_core")?
+    } else {
+        Vec::new()
+    };
+
+    let mut problems = Vec::new();
+    for version in &locked {
+        if !compatible(CLI_CORE_VERSION, version) {
+            problems.push(format!(
+                "the workspace uses crux_core {version}, but this crux CLI is for crux_core {CLI_CORE_VERSION}, run `crux upgrade`"
+            ));
+        }
+    }
+    for core in workspace.cores.values() {
+        if !locked.is_empty()
+            && !locked
+                .iter()
+                .any(|version| compatible(&core.crux_version, version))
+        {
+            problems.push(format!(
+                "Crux.toml: core ({name}) has crux_version {declared}, but {LOCKFILE} has crux_core {locked}",
+                name = core.name,
+                declared = core.crux_version,
+                locked = locked.join(", ")
+            ));
+        }
+    }
+
+    Ok(problems)
+}
+
+/// The versions of the package called `name` in a Cargo.lock file.
+fn locked_versions(lockfile: &str, name: &str) -> Result<Vec<String>> {
+    let lockfile: Lockfile = toml::from_str(lockfile)?;
+
+    Ok(lockfile
+        .package
+        .into_iter()
+        .filter(|package| package.name == name)
+        .map(|package| package.version)
+        .collect())
+}
+
+/// Whether `version` is compatible with `requirement` by Cargo's default (caret) rules,
+/// e.g. 0.10.2 is compatible with 0.10.0, but 0.11.0 isn't.
+fn compatible(requirement: &str, version: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map_while(|part| part.parse().ok())
+            .collect()
+    }
+
+    let requirement = parts(requirement);
+    let mut version = parts(version);
+    version.resize(requirement.len().max(version.len()), 0);
+
+    // everything up to the first non-zero part (or the last part given) has to match,
+    // and the rest can't be lower
+    let significant = requirement
+        .iter()
+        .position(|part| *part != 0)
+        .unwrap_or(requirement.len().saturating_sub(1));
+    let (required, minimum) = requirement.split_at((significant + 1).min(requirement.len()));
+    let (actual, rest) = version.split_at(required.len());
+
+    actual == required && rest >= minimum
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compatible() {
+        assert!(compatible("0.10.0", "0.10.2"));
+        assert!(compatible("0.10", "0.10.2"));
+        assert!(compatible("1.2.0", "1.4.1"));
+        assert!(!compatible("0.10.0", "0.11.0"));
+        assert!(!compatible("0.10.2", "0.10.1"));
+        assert!(!compatible("1.2.0", "2.0.0"));
+        assert!(!compatible("1.2.0", "1.1.9"));
+        assert!(!compatible("0.0.3", "0.0.4"));
+    }
+
+    #[test]
+    fn test_locked_versions() {
+        let lockfile = r#"
+            version = 3
+
+            [[package]]
+            name = "crux_core"
+            version = "0.10.0"
+
+            [[package]]
+            name = "crux_http"
+            version = "0.10.3"
+        "#;
+
+        assert_eq!(
+            locked_versions(lockfile, "crux_core").unwrap(),
+            vec!["0.10.0".to_string()]
+        );
+    }
+}
@@ -1,14 +1,15 @@
 use std::{fs, path::PathBuf};
 
 use crate::config::Workspace;
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 
 const CONFIG_FILE: &str = "Crux.toml";
 
 pub fn read_config() -> Result<Workspace> {
     let path = PathBuf::from(CONFIG_FILE);
     if let Ok(file) = &fs::read_to_string(path) {
-        let mut workspace: Workspace = toml::from_str(file)?;
+        let mut workspace: Workspace =
+            toml::from_str(file).with_context(|| format!("{CONFIG_FILE} is not valid"))?;
 
         let all_cores = workspace.cores.keys().cloned().collect::<Vec<_>>();
         if all_cores.is_empty() {
@@ -49,6 +50,6 @@ pub fn read_config() -> Result<Workspace> {
 pub fn write_config(workspace: &Workspace) -> Result<()> {
     let path = PathBuf::from(CONFIG_FILE);
     let toml = toml::to_string(workspace)?;
-    fs::write(path, toml)?;
+    fs::write(path, toml).with_context(|| format!("could not write {CONFIG_FILE}"))?;
     Ok(())
 }
@@ -0,0 +1,111 @@
+//! Processing high frequency events, like text input or pointer moves, each of which spawns
+//! a task for its render, and every few of which also wait for a response from the shell.
+//!
+//! Run with `cargo bench -p crux_core --bench events`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use crux_core::Core;
+use crux_time::{Instant, TimeResponse};
+
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_time::{Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        TextChanged(String),
+        PointerMoved(f64, f64),
+        Save,
+
+        #[serde(skip)]
+        Saved(TimeResponse),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        text: String,
+        pointer: (f64, f64),
+        saved_at: Option<u64>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::TextChanged(text) => model.text = text,
+                Event::PointerMoved(x, y) => model.pointer = (x, y),
+                Event::Save => caps.time.now(Event::Saved),
+                Event::Saved(TimeResponse::Now(instant)) => model.saved_at = Some(instant.seconds),
+                Event::Saved(_) => {}
+            }
+            caps.render.render();
+        }
+
+        fn view(&self, _model: &Model) {}
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub render: Render<Event>,
+        pub time: Time<Event>,
+    }
+}
+
+use app::{App, Effect, Event};
+
+fn events(c: &mut Criterion) {
+    let mut group = c.benchmark_group("events");
+
+    group.bench_function("text_input", |b| {
+        let core = Core::<Effect, App>::new();
+        let mut text = String::new();
+        b.iter(|| {
+            if text.len() == 64 {
+                text.clear();
+            }
+            text.push('a');
+            core.process_event(Event::TextChanged(text.clone()))
+        });
+    });
+
+    group.bench_function("pointer_move", |b| {
+        let core = Core::<Effect, App>::new();
+        let mut x = 0.0;
+        b.iter(|| {
+            x += 1.0;
+            core.process_event(Event::PointerMoved(x, x))
+        });
+    });
+
+    // a task which waits for the shell, so it is polled again once the response arrives
+    group.bench_function("request_and_response", |b| {
+        let core = Core::<Effect, App>::new();
+        let now = TimeResponse::Now(Instant::new(1_700_000_000, 0).unwrap());
+        b.iter_batched(
+            || core.process_event(Event::Save),
+            |effects| {
+                for effect in effects {
+                    if let Effect::Time(mut request) = effect {
+                        core.resolve(&mut request, now.clone());
+                    }
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, events);
+criterion_main!(benches);
@@ -0,0 +1,97 @@
+//! Serializing a large view model with `Bridge::view`, which allocates a new buffer on
+//! every call, and with `Bridge::view_into`, which reuses the shell's buffer.
+//!
+//! Run with `cargo bench -p crux_core --bench view`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use crux_core::{bridge::Bridge, Core};
+
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use serde::{Deserialize, Serialize};
+
+    /// How many items the view model has, about 1MB serialized
+    const ITEMS: usize = 10_000;
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Noop,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Item {
+        pub id: u64,
+        pub title: String,
+        pub tags: Vec<String>,
+        pub score: f64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ViewModel {
+        pub items: Vec<Item>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = ();
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, _event: Event, _model: &mut (), caps: &Capabilities) {
+            caps.render.render();
+        }
+
+        fn view(&self, _model: &()) -> ViewModel {
+            let items = (0..ITEMS as u64)
+                .map(|id| Item {
+                    id,
+                    title: format!("Item number {id}, with a reasonably long title"),
+                    tags: vec!["one".to_string(), "two".to_string(), "three".to_string()],
+                    score: id as f64 / 3.0,
+                })
+                .collect();
+
+            ViewModel { items }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub render: Render<Event>,
+    }
+}
+
+fn view(c: &mut Criterion) {
+    let bridge = Bridge::<app::Effect, app::App>::new(Core::new());
+    let mut group = c.benchmark_group("view");
+
+    group.bench_function("view", |b| b.iter(|| bridge.view()));
+
+    group.bench_function("view_into", |b| {
+        let mut buffer = Vec::new();
+        b.iter(|| bridge.view_into(&mut buffer).unwrap());
+    });
+
+    // the serialization alone, without building the view model
+    let view_model = crux_core::App::view(&app::App, &());
+    group.bench_function("serialize_new_buffer", |b| {
+        b.iter(|| bincode::serialize(&view_model).unwrap());
+    });
+    group.bench_function("serialize_reused_buffer", |b| {
+        let mut buffer = Vec::new();
+        b.iter(|| {
+            buffer.clear();
+            bincode::serialize_into(&mut buffer, &view_model).unwrap();
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, view);
+criterion_main!(benches);
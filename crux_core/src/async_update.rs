@@ -0,0 +1,86 @@
+//! Support for handling events with async code, see [`App::async_update`](crate::App::async_update).
+//!
+//! The task returned by `async_update` runs on the core's executor, like the tasks capabilities
+//! spawn. Awaiting a capability's async API suspends it until the shell resolves the request,
+//! and it changes the model through a [`ModelHandle`]. The shell sees the same requests over
+//! the bridge as it would for an app written with callback events.
+
+use futures::{channel::oneshot, future::BoxFuture, Future, FutureExt};
+
+pub(crate) type ModelUpdate<Model> = Box<dyn FnOnce(&mut Model) + Send>;
+
+/// The model updates requested by tasks, waiting for the core to run them
+pub(crate) type ModelUpdates<Model> = crossbeam_channel::Receiver<ModelUpdate<Model>>;
+
+/// How the app handles an event, returned by [`App::async_update`](crate::App::async_update)
+pub enum AsyncUpdate<Ev> {
+    /// Run the task on the core's executor
+    Spawn(BoxFuture<'static, ()>),
+    /// Handle the event with [`App::update`](crate::App::update) instead
+    Update(Ev),
+}
+
+impl<Ev> AsyncUpdate<Ev> {
+    /// Handle the event with the `task`
+    pub fn spawn(task: impl Future<Output = ()> + Send + 'static) -> Self {
+        AsyncUpdate::Spawn(task.boxed())
+    }
+}
+
+/// Access to the app's model from an async update task.
+///
+/// The task can't hold on to the model while it waits for effects, because the core keeps
+/// handling other events in the meantime. Instead, it asks the core to run a closure with the
+/// model whenever it needs to read or change it.
+pub struct ModelHandle<Model> {
+    updates: crossbeam_channel::Sender<ModelUpdate<Model>>,
+}
+
+impl<Model> Clone for ModelHandle<Model> {
+    fn clone(&self) -> Self {
+        Self {
+            updates: self.updates.clone(),
+        }
+    }
+}
+
+impl<Model> ModelHandle<Model> {
+    /// A handle for the app's tasks, and the updates they request through it. Unlike the
+    /// capability channels, this doesn't need the model to be `'static`, which only tasks
+    /// using the handle require.
+    pub(crate) fn new() -> (Self, ModelUpdates<Model>) {
+        let (updates, receiver) = crossbeam_channel::unbounded();
+        (Self { updates }, receiver)
+    }
+}
+
+impl<Model: 'static> ModelHandle<Model> {
+    /// Run `f` with the model, returning its result. The task is suspended until the core
+    /// has run it, which it does before handling any further events.
+    pub async fn update<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut Model) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        // if the core is gone, the update is dropped, and so is the task
+        let _ = self.updates.send(Box::new(move |model| {
+            // the task may have been dropped, in which case nobody needs the result
+            let _ = sender.send(f(model));
+        }));
+
+        receiver
+            .await
+            .expect("the model update was dropped without being run")
+    }
+}
+
+/// Run the model updates requested by tasks, returning whether there were any
+pub(crate) fn apply_updates<Model>(updates: &ModelUpdates<Model>, model: &mut Model) -> bool {
+    let mut applied = false;
+    for update in updates.try_iter() {
+        update(model);
+        applied = true;
+    }
+    applied
+}
@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// A change in the number of effects waiting for a response from the shell, which the
+/// bridge sends to the app in an event, when set up with
+/// [`Bridge::with_backpressure`](super::Bridge::with_backpressure).
+///
+/// A shell which stops responding to effects, e.g. because the platform throttles it in
+/// the background, leaves them waiting in the bridge. The app can respond to
+/// `Congested` by making fewer requests, e.g. pausing a poll or a sync, until it's
+/// `Relieved`.
+///
+/// The effects waiting include those the bridge hasn't sent to the shell yet, past the
+/// maximum set with [`Bridge::with_max_pending`](super::Bridge::with_max_pending).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Backpressure {
+    /// More effects than the high-water mark are waiting for a response
+    Congested { pending: usize },
+    /// After being congested, no more than half of the high-water mark of effects are
+    /// waiting for a response
+    Relieved { pending: usize },
+}
+
+type MakeEvent<Ev> = Box<dyn Fn(Backpressure) -> Ev + Send + Sync>;
+
+pub(crate) struct Signal<Ev> {
+    high_water_mark: usize,
+    make_event: MakeEvent<Ev>,
+    congested: AtomicBool,
+}
+
+impl<Ev> Signal<Ev> {
+    pub(crate) fn new(
+        high_water_mark: usize,
+        make_event: impl Fn(Backpressure) -> Ev + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            high_water_mark,
+            make_event: Box::new(make_event),
+            congested: AtomicBool::new(false),
+        }
+    }
+
+    /// The event for the app, if the `pending` effects make it congested or relieved
+    pub(crate) fn event(&self, pending: usize) -> Option<Ev> {
+        let backpressure = if pending > self.high_water_mark {
+            let was_congested = self.congested.swap(true, Ordering::Relaxed);
+            (!was_congested).then_some(Backpressure::Congested { pending })
+        } else if pending <= self.high_water_mark / 2 {
+            let was_congested = self.congested.swap(false, Ordering::Relaxed);
+            was_congested.then_some(Backpressure::Relieved { pending })
+        } else {
+            None
+        };
+
+        backpressure.map(&self.make_event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signals_each_change_once() {
+        let signal = Signal::new(4, |backpressure| backpressure);
+
+        assert_eq!(signal.event(4), None);
+        assert_eq!(
+            signal.event(5),
+            Some(Backpressure::Congested { pending: 5 })
+        );
+        assert_eq!(signal.event(6), None);
+        // still congested until it falls to half the high-water mark
+        assert_eq!(signal.event(3), None);
+        assert_eq!(signal.event(2), Some(Backpressure::Relieved { pending: 2 }));
+        assert_eq!(signal.event(0), None);
+    }
+}
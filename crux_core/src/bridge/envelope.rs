@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+use crate::cause::Cause;
+
+/// A message crossing the bridge, with metadata for correlating the shell's and
+/// the core's logs, e.g. for distributed tracing.
+///
+/// The shell wraps each event or response in an envelope and passes it to
+/// [`Bridge::process_event_enveloped`](super::Bridge::process_event_enveloped) or
+/// [`Bridge::handle_response_enveloped`](super::Bridge::handle_response_enveloped). The
+/// requests the core returns come back in an envelope which refers to the shell's one
+/// and carries the same trace id. To follow a trace through a chain of effects, the shell
+/// should pass the trace id of each request's envelope back with the response to it.
+///
+/// Register the envelope with the type generator, e.g. `gen.register_type::<Envelope>()?`,
+/// to share it with the shell.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    /// Increases with every message the sender sends
+    pub sequence: u64,
+    /// The sequence number of the message this is a reply to
+    pub in_reply_to: Option<u64>,
+    /// The trace the message is part of, e.g. a user interaction
+    pub trace_id: Option<String>,
+    /// The serialized message
+    pub payload: Vec<u8>,
+    /// The events which caused the requests in a reply from the core, with the `devtools`
+    /// feature. Empty otherwise, and in messages from the shell.
+    pub causes: Vec<EffectCause>,
+}
+
+/// The [`Cause`] of the request with the id `effect`, in the payload of an [`Envelope`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EffectCause {
+    pub effect: u32,
+    pub cause: Cause,
+}
@@ -0,0 +1,23 @@
+use super::Request;
+
+/// What a fake does with a request, when the bridge is set up with
+/// [`Bridge::with_fakes`](super::Bridge::with_fakes).
+///
+/// Integration tests can run the core against a real shell, with some of the capabilities
+/// backed by in-process fakes, e.g. a virtual clock or an in-memory store, to isolate the
+/// subsystem under test. The fakes see every request first, and pass the ones they don't
+/// handle on to the shell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalReply {
+    /// Not handled by the fakes, send the request to the shell
+    ToShell,
+    /// Handled by the fakes, without a response for now. A fake which responds later,
+    /// e.g. a timer, passes its response to
+    /// [`Bridge::handle_response`](super::Bridge::handle_response) like a shell would.
+    Done,
+    /// Respond to the request straight away, with the output serialized with bincode like
+    /// the shell's responses
+    Respond(Vec<u8>),
+}
+
+pub(crate) type Fakes<Ffi> = Box<dyn Fn(&Request<Ffi>) -> LocalReply + Send + Sync>;
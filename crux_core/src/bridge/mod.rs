@@ -1,16 +1,42 @@
+mod backpressure;
+mod envelope;
+mod fakes;
+mod multi;
 mod registry;
 mod request_serde;
+#[cfg(feature = "devtools")]
+pub mod session;
+mod watchdog;
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 
 use bincode::{DefaultOptions, Options};
 use erased_serde::Serialize as _;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
+use crate::capability::{Introspect, Priority};
+use crate::init::Init;
+use crate::view_slice::ViewSlice;
 use crate::Effect;
 use crate::{App, Core};
+pub use backpressure::Backpressure;
+use backpressure::Signal;
+pub use envelope::{EffectCause, Envelope};
+use fakes::Fakes;
+pub use fakes::LocalReply;
+pub use multi::MultiBridge;
 use registry::{EffectId, ResolveRegistry};
 // ResolveByte is public to be accessible from crux_macros
 #[doc(hidden)]
 pub use request_serde::ResolveSerialized;
+#[cfg(feature = "devtools")]
+use session::{Divergence, Message, Session, Step};
+pub use watchdog::StuckEffect;
+use watchdog::Watchdog;
 
 /// Request for a side-effect passed from the Core to the Shell. The `EffectId` links
 /// the `Request` with the corresponding call to [`Core::resolve`] to pass the data back
@@ -24,17 +50,91 @@ where
 {
     pub id: EffectId,
     pub effect: Eff,
+    pub priority: Priority,
 }
 // ANCHOR_END: request
 
+/// An error handling a message from the shell. Apart from
+/// [`BridgeError::OutputTooLarge`], it leaves the core unchanged.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BridgeError {
+    /// The effect has already been resolved, and doesn't expect any more responses,
+    /// e.g. because the shell resolved it twice.
+    #[error("Effect {id} has already been resolved.")]
+    AlreadyResolved { id: u32 },
+    /// The effect does not expect a response, e.g. a notification.
+    #[error("Effect {id} does not expect a response.")]
+    NotExpected { id: u32 },
+    /// No effect with the id was sent to the shell.
+    #[error("Effect {id} not found.")]
+    NotFound { id: u32 },
+    /// The event could not be deserialized into the app's event type.
+    #[error("Event could not be deserialized.")]
+    InvalidEvent,
+    /// The startup configuration could not be deserialized into an [`Init`].
+    #[error("Init could not be deserialized.")]
+    InvalidInit,
+    /// The paths of a view slice could not be deserialized into a list of strings.
+    #[error("View slice paths could not be deserialized.")]
+    InvalidPaths,
+    /// The message could not be deserialized into an [`Envelope`].
+    #[error("Envelope could not be deserialized.")]
+    InvalidEnvelope,
+    /// The response could not be deserialized into the output of the effect's operation.
+    /// The effect is still waiting for a response.
+    #[error("Response to effect {id} could not be deserialized.")]
+    InvalidResponse { id: u32 },
+    /// The event or response is larger than the bridge's [`Limits::max_input_bytes`],
+    /// and was not deserialized.
+    #[error("Input of {size} bytes is over the limit of {limit} bytes.")]
+    InputTooLarge { size: usize, limit: usize },
+    /// The serialized requests or view are larger than the bridge's
+    /// [`Limits::max_output_bytes`]. When processing an event or a response, the core has
+    /// already updated the model, and the requests are dropped, so the effects they were
+    /// for will never be resolved.
+    #[error("Output of {size} bytes is over the limit of {limit} bytes.")]
+    OutputTooLarge { size: usize, limit: usize },
+}
+
+/// What the shell sent to the bridge
+#[derive(Clone, Copy)]
+enum Input {
+    /// An event
+    Event,
+    /// A list of events, processed together
+    Events,
+    /// A response to the effect with the id
+    Response(EffectId),
+}
+
+/// Maximum sizes of the serialized data passing through the [`Bridge`], to stop a
+/// malformed or malicious payload from the shell, or a runaway view model, from using
+/// up the memory on either side. There are no limits by default.
+///
+/// Payloads over 80% of a limit are counted in the core's
+/// [`Metrics`](crate::metrics::Metrics), to warn before the limit is reached.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The largest event or response the shell may send
+    pub max_input_bytes: Option<usize>,
+    /// The largest serialized requests or view the core may send back
+    pub max_output_bytes: Option<usize>,
+}
+
 /// Bridge is a core wrapper presenting the same interface as the [`Core`] but in a
 /// serialized form, using bincode as the serialization format.
+///
+/// Like the [`Core`], the bridge is `Send` and `Sync`, and can be called from multiple threads.
 pub struct Bridge<Eff, A>
 where
     Eff: Effect,
     A: App,
 {
     inner: BridgeWithSerializer<Eff, A>,
+    sequence: AtomicU64,
+    limits: Limits,
+    #[cfg(feature = "devtools")]
+    recording: Option<std::sync::Mutex<Session>>,
 }
 
 impl<Eff, A> Bridge<Eff, A>
@@ -44,11 +144,179 @@ where
 {
     /// Create a new Bridge using the provided `core`.
     pub fn new(core: Core<Eff, A>) -> Self {
+        Self::with_limits(core, Limits::default())
+    }
+
+    /// Create a new Bridge using the provided `core`, which checks the sizes of the data
+    /// passing through it against the `limits`.
+    pub fn with_limits(core: Core<Eff, A>, limits: Limits) -> Self {
         Self {
             inner: BridgeWithSerializer::new(core),
+            sequence: AtomicU64::new(0),
+            limits,
+            #[cfg(feature = "devtools")]
+            recording: None,
         }
     }
 
+    /// Send the app an event when more than `high_water_mark` effects are waiting for a
+    /// response from the shell, and again when no more than half of them are. See
+    /// [`Backpressure`].
+    pub fn with_backpressure<F>(mut self, high_water_mark: usize, make_event: F) -> Self
+    where
+        F: Fn(Backpressure) -> A::Event + Send + Sync + 'static,
+    {
+        self.inner = self.inner.with_backpressure(high_water_mark, make_event);
+        self
+    }
+
+    /// Send the shell at most `max_pending` effects to respond to at once. Further effects
+    /// wait in the bridge, and are sent in the order they were requested as the shell
+    /// responds to earlier ones.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.inner = self.inner.with_max_pending(max_pending);
+        self
+    }
+
+    /// Send the app an event about each effect still waiting for a response from the shell
+    /// more than `threshold` after it was sent, with the capability and the operation it
+    /// was for. See [`StuckEffect`].
+    pub fn with_watchdog<F>(mut self, threshold: Duration, make_event: F) -> Self
+    where
+        F: Fn(StuckEffect) -> A::Event + Send + Sync + 'static,
+    {
+        self.inner = self.inner.with_watchdog(threshold, make_event);
+        self
+    }
+
+    /// Pass every request to the `fakes` before the shell, e.g. to back some of the
+    /// capabilities with in-process fakes in an integration test, while the shell handles
+    /// the rest. The requests the fakes respond to, or handle with [`LocalReply::Done`],
+    /// are not returned to the shell. See [`LocalReply`].
+    pub fn with_fakes<F>(mut self, fakes: F) -> Self
+    where
+        F: Fn(&Request<Eff::Ffi>) -> LocalReply + Send + Sync + 'static,
+    {
+        self.inner.fakes = Some(Box::new(fakes));
+        self
+    }
+
+    /// Record the messages the shell sends, with the view after each of them, to export as
+    /// a [`Session`] and replay against a core later. See [`session`].
+    ///
+    /// Only available with the `devtools` feature, so that release builds don't include it.
+    #[cfg(feature = "devtools")]
+    pub fn with_recording(mut self) -> Self {
+        self.recording = Some(Default::default());
+        self
+    }
+
+    /// The messages recorded so far, if the bridge was created
+    /// [`with_recording`](Bridge::with_recording).
+    #[cfg(feature = "devtools")]
+    pub fn session(&self) -> Option<Session> {
+        self.recording
+            .as_ref()
+            .map(|session| session.lock().expect("Session Mutex poisoned.").clone())
+    }
+
+    /// Send the messages of a recorded `session` to this bridge, which should wrap a new
+    /// core, and return the steps whose view differs from the one recorded.
+    ///
+    /// Fails if a message is rejected, e.g. because the core didn't make the effect a
+    /// response is for.
+    #[cfg(feature = "devtools")]
+    pub fn replay(&self, session: &Session) -> Result<Vec<Divergence>, BridgeError>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        let mut divergences = Vec::new();
+
+        for (index, step) in session.steps.iter().enumerate() {
+            match &step.message {
+                Message::Init { config } => self.try_init(config)?,
+                Message::Event { event } => {
+                    self.try_process(Input::Event, event)?;
+                }
+                Message::Events { events } => {
+                    self.try_process(Input::Events, events)?;
+                }
+                Message::Response { id, output } => {
+                    self.try_process(Input::Response(EffectId(*id)), output)?;
+                }
+            }
+
+            let actual = self.recorded_view();
+            if actual != step.view {
+                divergences.push(Divergence {
+                    step: index,
+                    expected: step.view.clone(),
+                    actual,
+                });
+            }
+        }
+
+        Ok(divergences)
+    }
+
+    /// Add the `message` to the session being recorded, if any, with the current view
+    #[cfg(feature = "devtools")]
+    fn record(&self, message: impl FnOnce() -> Message) {
+        if let Some(session) = &self.recording {
+            let step = Step {
+                message: message(),
+                view: self.recorded_view(),
+            };
+            session
+                .lock()
+                .expect("Session Mutex poisoned.")
+                .steps
+                .push(step);
+        }
+    }
+
+    /// The view, serialized without counting it in the metrics or checking the limits
+    #[cfg(feature = "devtools")]
+    fn recorded_view(&self) -> Vec<u8> {
+        let mut buffer = vec![];
+        self.inner.view(&mut bincode::Serializer::new(
+            &mut buffer,
+            bincode_options(),
+        ));
+
+        buffer
+    }
+
+    /// Receive the startup configuration from the shell, before the first event.
+    ///
+    /// The `config` is a serialized [`Init`], which the core passes to [`App::init`]
+    /// to create the initial model.
+    pub fn init(&self, config: &[u8]) {
+        self.try_init(config)
+            .unwrap_or_else(|error| panic!("Init could not be processed. {error}"));
+    }
+
+    /// Receive the startup configuration from the shell, like [`Bridge::init`], but
+    /// returning an error instead of panicking if the `config` can't be deserialized, or is
+    /// over the bridge's [`Limits`]. The model is left unchanged in that case.
+    pub fn try_init(&self, config: &[u8]) -> Result<(), BridgeError> {
+        self.check_size(config.len(), self.limits.max_input_bytes, |size, limit| {
+            BridgeError::InputTooLarge { size, limit }
+        })?;
+
+        let options = bincode_options();
+
+        self.inner
+            .try_init(&mut bincode::Deserializer::from_slice(config, options))?;
+
+        #[cfg(feature = "devtools")]
+        self.record(|| Message::Init {
+            config: config.to_vec(),
+        });
+
+        Ok(())
+    }
+
     /// Receive an event from the shell.
     ///
     /// The `event` is serialized and will be deserialized by the core before it's passed
@@ -57,16 +325,43 @@ where
     where
         A::Event: for<'a> Deserialize<'a>,
     {
-        let options = Self::bincode_options();
-
-        let mut deser = bincode::Deserializer::from_slice(event, options);
+        self.try_process_event(event)
+            .unwrap_or_else(|error| panic!("Event could not be processed. {error}"))
+    }
 
-        let mut return_buffer = vec![];
-        let mut ser = bincode::Serializer::new(&mut return_buffer, options);
+    /// Receive an event from the shell, like [`Bridge::process_event`], but returning an
+    /// error instead of panicking if the `event` can't be deserialized, or is over the
+    /// bridge's [`Limits`].
+    pub fn try_process_event(&self, event: &[u8]) -> Result<Vec<u8>, BridgeError>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.try_process(Input::Event, event)
+            .map(|(requests, _)| requests)
+    }
 
-        self.inner.process_event(&mut deser, &mut ser);
+    /// Receive several events from the shell at once, e.g. interactions queued while the
+    /// app was offline, and process them in order with [`Core::process_events`].
+    ///
+    /// The `events` are a serialized list of events, and the requests of all of them are
+    /// returned together, with the renders merged into one.
+    pub fn process_events(&self, events: &[u8]) -> Vec<u8>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.try_process_events(events)
+            .unwrap_or_else(|error| panic!("Events could not be processed. {error}"))
+    }
 
-        return_buffer
+    /// Receive several events from the shell at once, like [`Bridge::process_events`], but
+    /// returning an error instead of panicking if any of the `events` can't be deserialized,
+    /// or they are over the bridge's [`Limits`]. In that case none of them are processed.
+    pub fn try_process_events(&self, events: &[u8]) -> Result<Vec<u8>, BridgeError>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.try_process(Input::Events, events)
+            .map(|(requests, _)| requests)
     }
 
     /// Receive a response to a capability request from the shell.
@@ -80,35 +375,287 @@ where
     where
         A::Event: for<'a> Deserialize<'a>,
     {
-        let options = Self::bincode_options();
+        self.try_handle_response(id, output)
+            .unwrap_or_else(|error| panic!("Response could not be handled. {error}"))
+    }
 
-        let mut deser = bincode::Deserializer::from_slice(output, options);
+    /// Receive a response to a capability request from the shell, like
+    /// [`Bridge::handle_response`], but returning an error instead of panicking if the
+    /// `id` doesn't match an effect awaiting a response, e.g. when the shell resolves an
+    /// effect a second time, or if the `output` can't be deserialized, or is over the
+    /// bridge's [`Limits`].
+    pub fn try_handle_response(&self, id: u32, output: &[u8]) -> Result<Vec<u8>, BridgeError>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.try_process(Input::Response(EffectId(id)), output)
+            .map(|(requests, _)| requests)
+    }
+
+    /// Process the `input`, returning the serialized requests and their causes
+    fn try_process(
+        &self,
+        kind: Input,
+        input: &[u8],
+    ) -> Result<(Vec<u8>, Vec<EffectCause>), BridgeError>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.check_size(input.len(), self.limits.max_input_bytes, |size, limit| {
+            BridgeError::InputTooLarge { size, limit }
+        })?;
+
+        let options = bincode_options();
+
+        let mut deser = bincode::Deserializer::from_slice(input, options);
 
         let mut return_buffer = vec![];
         let mut ser = bincode::Serializer::new(&mut return_buffer, options);
 
-        self.inner.handle_response(id, &mut deser, &mut ser);
+        #[cfg(feature = "devtools")]
+        let message = {
+            let input = input.to_vec();
+            move || match kind {
+                Input::Event => Message::Event { event: input },
+                Input::Events => Message::Events { events: input },
+                Input::Response(id) => Message::Response {
+                    id: id.0,
+                    output: input,
+                },
+            }
+        };
 
-        return_buffer
+        let causes = self.inner.process(
+            kind,
+            &mut <dyn erased_serde::Deserializer>::erase(&mut deser),
+            &mut <dyn erased_serde::Serializer>::erase(&mut ser),
+        )?;
+
+        // the core has processed the input, even if the requests turn out to be too large
+        #[cfg(feature = "devtools")]
+        self.record(message);
+
+        Ok((self.output(return_buffer)?, causes))
+    }
+
+    /// Receive an event from the shell, wrapped in an [`Envelope`].
+    ///
+    /// Works like [`Bridge::process_event`], with the resulting requests returned in an
+    /// envelope replying to the one received.
+    pub fn process_event_enveloped(&self, envelope: &[u8]) -> Vec<u8>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.try_process_event_enveloped(envelope)
+            .unwrap_or_else(|error| panic!("Event could not be processed. {error}"))
+    }
+
+    /// Receive an event from the shell, wrapped in an [`Envelope`], like
+    /// [`Bridge::process_event_enveloped`], but returning an error instead of panicking if
+    /// the `envelope` or the event in it can't be deserialized, or the event is over the
+    /// bridge's [`Limits`].
+    pub fn try_process_event_enveloped(&self, envelope: &[u8]) -> Result<Vec<u8>, BridgeError>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.enveloped(envelope, |event| self.try_process(Input::Event, event))
+    }
+
+    /// Receive a response to a capability request from the shell, wrapped in an [`Envelope`].
+    ///
+    /// Works like [`Bridge::handle_response`], with the resulting requests returned in an
+    /// envelope replying to the one received.
+    pub fn handle_response_enveloped(&self, id: u32, envelope: &[u8]) -> Vec<u8>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.try_handle_response_enveloped(id, envelope)
+            .unwrap_or_else(|error| panic!("Response could not be handled. {error}"))
+    }
+
+    /// Receive a response to a capability request from the shell, wrapped in an
+    /// [`Envelope`], like [`Bridge::handle_response_enveloped`], but returning an error
+    /// instead of panicking in the same cases as [`Bridge::try_handle_response`], or if the
+    /// `envelope` can't be deserialized.
+    pub fn try_handle_response_enveloped(
+        &self,
+        id: u32,
+        envelope: &[u8],
+    ) -> Result<Vec<u8>, BridgeError>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.enveloped(envelope, |output| {
+            self.try_process(Input::Response(EffectId(id)), output)
+        })
+    }
+
+    fn enveloped(
+        &self,
+        envelope: &[u8],
+        process: impl FnOnce(&[u8]) -> Result<(Vec<u8>, Vec<EffectCause>), BridgeError>,
+    ) -> Result<Vec<u8>, BridgeError> {
+        let options = bincode_options();
+
+        let received: Envelope = options
+            .deserialize(envelope)
+            .map_err(|_| BridgeError::InvalidEnvelope)?;
+
+        let (payload, causes) = process(&received.payload)?;
+        let reply = Envelope {
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed),
+            in_reply_to: Some(received.sequence),
+            payload,
+            trace_id: received.trace_id,
+            causes,
+        };
+
+        Ok(options
+            .serialize(&reply)
+            .expect("Envelope serialization failed."))
     }
 
     /// Get the current state of the app's view model (serialized).
     pub fn view(&self) -> Vec<u8> {
-        let options = Self::bincode_options();
+        self.try_view()
+            .unwrap_or_else(|error| panic!("View could not be returned. {error}"))
+    }
+
+    /// Get the current state of the app's view model (serialized), like [`Bridge::view`],
+    /// but returning an error instead of panicking if it's over the bridge's [`Limits`].
+    pub fn try_view(&self) -> Result<Vec<u8>, BridgeError> {
+        let mut return_buffer = vec![];
+        self.view_into(&mut return_buffer)?;
+
+        Ok(return_buffer)
+    }
+
+    /// Serialize the current state of the app's view model into `buffer`, replacing its
+    /// contents, like [`Bridge::try_view`]. Passing the same buffer on every call reuses its
+    /// allocation, which saves allocating and growing a new one for every view of a large
+    /// view model.
+    ///
+    /// If the view is over the bridge's [`Limits`], the buffer is left empty.
+    pub fn view_into(&self, buffer: &mut Vec<u8>) -> Result<(), BridgeError> {
+        let options = bincode_options();
+
+        buffer.clear();
+        self.inner
+            .view(&mut bincode::Serializer::new(&mut *buffer, options));
+
+        self.inner.core.record_bytes_out(buffer.len());
+        self.check_size(buffer.len(), self.limits.max_output_bytes, |size, limit| {
+            BridgeError::OutputTooLarge { size, limit }
+        })
+        .map_err(|error| {
+            buffer.clear();
+            error
+        })
+    }
+
+    /// Get some of the fields of the app's view model (serialized), for shells which
+    /// don't need all of a large view model.
+    ///
+    /// The `paths` are a serialized list of strings, and the result is a [`ViewSlice`] with
+    /// an optional value for each of them. See [`crate::view_slice`] for the path syntax.
+    pub fn view_slice(&self, paths: &[u8]) -> Vec<u8> {
+        self.try_view_slice(paths)
+            .unwrap_or_else(|error| panic!("View slice could not be returned. {error}"))
+    }
+
+    /// Get some of the fields of the app's view model (serialized), like
+    /// [`Bridge::view_slice`], but returning an error instead of panicking if the `paths`
+    /// can't be deserialized, or are over the bridge's [`Limits`], or so is the slice.
+    pub fn try_view_slice(&self, paths: &[u8]) -> Result<Vec<u8>, BridgeError> {
+        self.check_size(paths.len(), self.limits.max_input_bytes, |size, limit| {
+            BridgeError::InputTooLarge { size, limit }
+        })?;
+
+        let options = bincode_options();
+
+        let mut return_buffer = vec![];
+
+        self.inner.try_view_slice(
+            &mut bincode::Deserializer::from_slice(paths, options),
+            &mut bincode::Serializer::new(&mut return_buffer, options),
+        )?;
+
+        self.output(return_buffer)
+    }
+
+    /// Get the core's [`Metrics`](crate::metrics::Metrics) (serialized), e.g. to report
+    /// them to a monitoring service.
+    pub fn metrics(&self) -> Vec<u8> {
+        let options = bincode_options();
+
+        let mut return_buffer = vec![];
 
+        self.inner
+            .metrics(&mut bincode::Serializer::new(&mut return_buffer, options));
+
+        return_buffer
+    }
+
+    /// Get the current state of the model, as described by [`App::debug_model`], as
+    /// UTF-8 encoded JSON for display in development tools.
+    #[cfg(feature = "devtools")]
+    pub fn debug_model(&self) -> Vec<u8> {
         let mut return_buffer = vec![];
 
         self.inner
-            .view(&mut bincode::Serializer::new(&mut return_buffer, options));
+            .debug_model(&mut serde_json::Serializer::new(&mut return_buffer));
 
         return_buffer
     }
 
-    fn bincode_options() -> impl bincode::Options + Copy {
-        DefaultOptions::new()
-            .with_fixint_encoding()
-            .allow_trailing_bytes()
+    /// Get a description of the app's capabilities (serialized), e.g. for display in
+    /// development tools.
+    pub fn capabilities(&self) -> Vec<u8>
+    where
+        A::Capabilities: Introspect,
+    {
+        let options = bincode_options();
+
+        let mut return_buffer = vec![];
+
+        self.inner
+            .capabilities(&mut bincode::Serializer::new(&mut return_buffer, options));
+
+        return_buffer
     }
+
+    /// Count the output's bytes, and check them against the limit
+    fn output(&self, buffer: Vec<u8>) -> Result<Vec<u8>, BridgeError> {
+        self.inner.core.record_bytes_out(buffer.len());
+        self.check_size(buffer.len(), self.limits.max_output_bytes, |size, limit| {
+            BridgeError::OutputTooLarge { size, limit }
+        })?;
+
+        Ok(buffer)
+    }
+
+    fn check_size(
+        &self,
+        size: usize,
+        limit: Option<usize>,
+        error: impl FnOnce(usize, usize) -> BridgeError,
+    ) -> Result<(), BridgeError> {
+        let Some(limit) = limit else {
+            return Ok(());
+        };
+        self.inner.core.record_payload(size, limit);
+
+        if size > limit {
+            return Err(error(size, limit));
+        }
+        Ok(())
+    }
+}
+
+fn bincode_options() -> impl bincode::Options + Copy {
+    DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
 }
 
 /// A bridge with a user supplied serializer
@@ -129,6 +676,12 @@ where
 {
     core: Core<Eff, A>,
     registry: ResolveRegistry,
+    max_pending: Option<usize>,
+    /// The effects past `max_pending`, not yet sent to the shell
+    deferred: Mutex<VecDeque<Eff>>,
+    backpressure: Option<Signal<A::Event>>,
+    watchdog: Option<Watchdog<A::Event>>,
+    fakes: Option<Fakes<Eff::Ffi>>,
 }
 // ANCHOR_END: bridge_with_serializer
 
@@ -141,14 +694,91 @@ where
         Self {
             core,
             registry: Default::default(),
+            max_pending: None,
+            deferred: Default::default(),
+            backpressure: None,
+            watchdog: None,
+            fakes: None,
         }
     }
 
+    /// Send the app an event when more than `high_water_mark` effects are waiting for a
+    /// response from the shell, and again when no more than half of them are. See
+    /// [`Backpressure`].
+    pub fn with_backpressure<F>(mut self, high_water_mark: usize, make_event: F) -> Self
+    where
+        F: Fn(Backpressure) -> A::Event + Send + Sync + 'static,
+    {
+        self.backpressure = Some(Signal::new(high_water_mark, make_event));
+        self
+    }
+
+    /// Send the shell at most `max_pending` effects to respond to at once. Further effects
+    /// wait in the bridge, and are sent in the order they were requested as the shell
+    /// responds to earlier ones.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = Some(max_pending);
+        self
+    }
+
+    /// Send the app an event about each effect still waiting for a response from the shell
+    /// more than `threshold` after it was sent. See [`StuckEffect`].
+    ///
+    /// The effects are checked whenever the shell calls the bridge, so an app waiting on
+    /// the shell, e.g. showing a spinner, can keep them checked by starting a timer with
+    /// the `crux_time` capability.
+    pub fn with_watchdog<F>(mut self, threshold: Duration, make_event: F) -> Self
+    where
+        F: Fn(StuckEffect) -> A::Event + Send + Sync + 'static,
+    {
+        self.watchdog = Some(Watchdog::new(threshold, make_event));
+        self
+    }
+
+    /// Receive the startup configuration from the shell, before the first event.
+    ///
+    /// The `config` is a serialized [`Init`], which the core passes to [`App::init`]
+    /// to create the initial model.
+    pub fn init<'de, D>(&self, config: D)
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        self.try_init(config)
+            .unwrap_or_else(|error| panic!("Init could not be processed. {error}"));
+    }
+
+    /// Receive the startup configuration from the shell, like
+    /// [`BridgeWithSerializer::init`], but returning an error instead of panicking if the
+    /// `config` can't be deserialized. The model is left unchanged in that case.
+    pub fn try_init<'de, D>(&self, config: D) -> Result<(), BridgeError>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        let config = Init::deserialize(config).map_err(|_| BridgeError::InvalidInit)?;
+
+        self.core.init(config);
+
+        Ok(())
+    }
+
     /// Receive an event from the shell.
     ///
     /// The `event` is serialized and will be deserialized by the core before it's passed
     /// to your app.
     pub fn process_event<'de, D, S>(&self, event: D, requests_out: S)
+    where
+        for<'a> A::Event: Deserialize<'a>,
+        D: ::serde::de::Deserializer<'de> + 'de,
+        S: ::serde::ser::Serializer,
+    {
+        self.try_process_event(event, requests_out)
+            .unwrap_or_else(|error| panic!("Event could not be processed. {error}"));
+    }
+
+    /// Receive an event from the shell, like [`BridgeWithSerializer::process_event`], but
+    /// returning an error instead of panicking if the `event` can't be deserialized.
+    /// Nothing is written to `requests_out` in that case.
+    pub fn try_process_event<'de, D, S>(&self, event: D, requests_out: S) -> Result<(), BridgeError>
     where
         for<'a> A::Event: Deserialize<'a>,
         D: ::serde::de::Deserializer<'de> + 'de,
@@ -156,10 +786,49 @@ where
     {
         let mut erased_de = <dyn erased_serde::Deserializer>::erase(event);
         self.process(
-            None,
+            Input::Event,
+            &mut erased_de,
+            &mut <dyn erased_serde::Serializer>::erase(requests_out),
+        )
+        .map(|_| ())
+    }
+
+    /// Receive several events from the shell at once, and process them in order with
+    /// [`Core::process_events`].
+    ///
+    /// The `events` are a serialized list of events, and the requests of all of them are
+    /// written to `requests_out` together.
+    pub fn process_events<'de, D, S>(&self, events: D, requests_out: S)
+    where
+        for<'a> A::Event: Deserialize<'a>,
+        D: ::serde::de::Deserializer<'de> + 'de,
+        S: ::serde::ser::Serializer,
+    {
+        self.try_process_events(events, requests_out)
+            .unwrap_or_else(|error| panic!("Events could not be processed. {error}"));
+    }
+
+    /// Receive several events from the shell at once, like
+    /// [`BridgeWithSerializer::process_events`], but returning an error instead of panicking
+    /// if any of the `events` can't be deserialized. None of them are processed, and nothing
+    /// is written to `requests_out` in that case.
+    pub fn try_process_events<'de, D, S>(
+        &self,
+        events: D,
+        requests_out: S,
+    ) -> Result<(), BridgeError>
+    where
+        for<'a> A::Event: Deserialize<'a>,
+        D: ::serde::de::Deserializer<'de> + 'de,
+        S: ::serde::ser::Serializer,
+    {
+        let mut erased_de = <dyn erased_serde::Deserializer>::erase(events);
+        self.process(
+            Input::Events,
             &mut erased_de,
             &mut <dyn erased_serde::Serializer>::erase(requests_out),
-        );
+        )
+        .map(|_| ())
     }
 
     /// Receive a response to a capability request from the shell.
@@ -167,6 +836,25 @@ where
     /// The `output` is serialized capability output. It will be deserialized by the core.
     /// The `id` MUST match the `id` of the effect that triggered it, else the core will panic.
     pub fn handle_response<'de, D, S>(&self, id: u32, response: D, requests_out: S)
+    where
+        for<'a> A::Event: Deserialize<'a>,
+        D: ::serde::de::Deserializer<'de>,
+        S: ::serde::ser::Serializer,
+    {
+        self.try_handle_response(id, response, requests_out)
+            .unwrap_or_else(|error| panic!("Response could not be handled. {error}"));
+    }
+
+    /// Receive a response to a capability request from the shell, like
+    /// [`BridgeWithSerializer::handle_response`], but returning an error instead of
+    /// panicking if the `id` doesn't match an effect awaiting a response, or the
+    /// `response` can't be deserialized. Nothing is written to `requests_out` in that case.
+    pub fn try_handle_response<'de, D, S>(
+        &self,
+        id: u32,
+        response: D,
+        requests_out: S,
+    ) -> Result<(), BridgeError>
     where
         for<'a> A::Event: Deserialize<'a>,
         D: ::serde::de::Deserializer<'de>,
@@ -174,44 +862,146 @@ where
     {
         let mut erased_response = <dyn erased_serde::Deserializer>::erase(response);
         self.process(
-            Some(EffectId(id)),
+            Input::Response(EffectId(id)),
             &mut erased_response,
             &mut <dyn erased_serde::Serializer>::erase(requests_out),
-        );
+        )
+        .map(|_| ())
     }
 
+    /// Process the `data` the shell sent, writing the requests to `requests_out`, and
+    /// returning their causes
     fn process(
         &self,
-        id: Option<EffectId>,
+        input: Input,
         data: &mut dyn erased_serde::Deserializer,
         requests_out: &mut dyn erased_serde::Serializer,
-    ) where
+    ) -> Result<Vec<EffectCause>, BridgeError>
+    where
         A::Event: for<'a> Deserialize<'a>,
     {
-        let effects = match id {
-            None => {
+        // effects sent to the shell by this call are not yet stuck
+        let now = web_time::Instant::now();
+
+        self.core.start_message();
+        let effects = match input {
+            Input::Event => {
                 let shell_event =
-                    erased_serde::deserialize(data).expect("Message deserialization failed.");
+                    erased_serde::deserialize(data).map_err(|_| BridgeError::InvalidEvent)?;
+
+                self.core.handle_event(shell_event)
+            }
+            Input::Events => {
+                // all the events are deserialized first, so none are processed if one is invalid
+                let shell_events: Vec<A::Event> =
+                    erased_serde::deserialize(data).map_err(|_| BridgeError::InvalidEvent)?;
 
-                self.core.process_event(shell_event)
+                self.core.handle_events(shell_events)
             }
-            Some(id) => {
-                self.registry.resume(id, data).expect(
-                    "Response could not be handled. The request did not expect a response.",
-                );
+            Input::Response(id) => {
+                self.registry.resume(id, data)?;
 
                 self.core.process()
             }
         };
 
-        let requests: Vec<_> = effects
-            .into_iter()
-            .map(|eff| self.registry.register(eff))
-            .collect();
+        let mut causes = Vec::new();
+        let mut register = |eff: Eff| {
+            let cause = eff.cause().cloned();
+            let name = eff.name();
+            let request = self.registry.register(eff);
+            if let Some(watchdog) = &self.watchdog {
+                watchdog.watch(name, &request);
+            }
+            if let Some(cause) = cause {
+                causes.push(EffectCause {
+                    effect: request.id.0,
+                    cause,
+                });
+            }
+            request
+        };
+
+        let mut requests: Vec<_> = self.admit(effects).into_iter().map(&mut register).collect();
+
+        if let Some(event) = self
+            .backpressure
+            .as_ref()
+            .and_then(|signal| signal.event(self.waiting()))
+        {
+            let effects = self.core.handle_event(event);
+            requests.extend(self.admit(effects).into_iter().map(&mut register));
+        }
+
+        if let Some(watchdog) = &self.watchdog {
+            for event in watchdog.events(now, |id| self.registry.is_pending(id)) {
+                let effects = self.core.handle_event(event);
+                requests.extend(self.admit(effects).into_iter().map(&mut register));
+            }
+        }
+
+        if let Some(fakes) = &self.fakes {
+            let mut pending: VecDeque<_> = requests.into();
+            requests = Vec::new();
+
+            while let Some(request) = pending.pop_front() {
+                match fakes(&request) {
+                    LocalReply::ToShell => requests.push(request),
+                    LocalReply::Done => {}
+                    LocalReply::Respond(output) => {
+                        let mut deser =
+                            bincode::Deserializer::from_slice(&output, bincode_options());
+                        self.registry
+                            .resume(
+                                request.id,
+                                &mut <dyn erased_serde::Deserializer>::erase(&mut deser),
+                            )
+                            .unwrap_or_else(|error| {
+                                panic!("Fake response could not be handled. {error}")
+                            });
+
+                        let effects = self.admit(self.core.process());
+                        pending.extend(effects.into_iter().map(&mut register));
+                    }
+                }
+            }
+        }
+        self.core.record_pending_effects(self.registry.pending());
+        self.core.record_deferred_effects(self.deferred().len());
+
+        // a stable sort, so requests of the same priority stay in the order they were made
+        requests.sort_by_key(|request| request.priority);
 
         requests
             .erased_serialize(requests_out)
-            .expect("Request serialization failed.")
+            .expect("Request serialization failed.");
+
+        Ok(causes)
+    }
+
+    /// The `effects` to send to the shell now, after any deferred earlier, as long as no
+    /// more than the maximum are pending. The rest are deferred.
+    fn admit(&self, effects: Vec<Eff>) -> Vec<Eff> {
+        let Some(max_pending) = self.max_pending else {
+            return effects;
+        };
+
+        let mut deferred = self.deferred();
+        deferred.extend(effects);
+        let room = max_pending.saturating_sub(self.registry.pending());
+        let admitted = room.min(deferred.len());
+        deferred.drain(..admitted).collect()
+    }
+
+    /// The effects waiting for a response, whether sent to the shell or deferred
+    fn waiting(&self) -> usize {
+        self.registry.pending() + self.deferred().len()
+    }
+
+    fn deferred(&self) -> std::sync::MutexGuard<'_, VecDeque<Eff>> {
+        self.deferred
+            .lock()
+            .expect("Deferred effects Mutex was poisoned.")
     }
 
     /// Get the current state of the app's view model (serialized).
@@ -224,4 +1014,67 @@ where
             .erased_serialize(&mut <dyn erased_serde::Serializer>::erase(ser))
             .expect("View should serialize")
     }
+
+    /// Get some of the fields of the app's view model (serialized), as a [`ViewSlice`]
+    /// of the (serialized) list of `paths`.
+    pub fn view_slice<'de, D, S>(&self, paths: D, ser: S)
+    where
+        D: ::serde::de::Deserializer<'de>,
+        S: ::serde::ser::Serializer,
+    {
+        self.try_view_slice(paths, ser)
+            .unwrap_or_else(|error| panic!("View slice could not be returned. {error}"));
+    }
+
+    /// Get some of the fields of the app's view model (serialized), like
+    /// [`BridgeWithSerializer::view_slice`], but returning an error instead of panicking if
+    /// the `paths` can't be deserialized. Nothing is written to `ser` in that case.
+    pub fn try_view_slice<'de, D, S>(&self, paths: D, ser: S) -> Result<(), BridgeError>
+    where
+        D: ::serde::de::Deserializer<'de>,
+        S: ::serde::ser::Serializer,
+    {
+        let paths = Vec::<String>::deserialize(paths).map_err(|_| BridgeError::InvalidPaths)?;
+
+        ViewSlice::new(&self.core.view(), &paths)
+            .erased_serialize(&mut <dyn erased_serde::Serializer>::erase(ser))
+            .expect("View slice should serialize");
+
+        Ok(())
+    }
+
+    /// Get the core's [`Metrics`](crate::metrics::Metrics) (serialized).
+    pub fn metrics<S>(&self, ser: S)
+    where
+        S: ::serde::ser::Serializer,
+    {
+        self.core
+            .metrics()
+            .erased_serialize(&mut <dyn erased_serde::Serializer>::erase(ser))
+            .expect("Metrics should serialize")
+    }
+
+    /// Get the current state of the model, as described by [`App::debug_model`] (serialized).
+    #[cfg(feature = "devtools")]
+    pub fn debug_model<S>(&self, ser: S)
+    where
+        S: ::serde::ser::Serializer,
+    {
+        self.core
+            .debug_model()
+            .erased_serialize(&mut <dyn erased_serde::Serializer>::erase(ser))
+            .expect("Model description should serialize")
+    }
+
+    /// Get a description of the app's capabilities (serialized).
+    pub fn capabilities<S>(&self, ser: S)
+    where
+        S: ::serde::ser::Serializer,
+        A::Capabilities: Introspect,
+    {
+        self.core
+            .capabilities()
+            .erased_serialize(&mut <dyn erased_serde::Serializer>::erase(ser))
+            .expect("Capabilities should serialize")
+    }
 }
@@ -0,0 +1,121 @@
+use std::sync::RwLock;
+
+use serde::Deserialize;
+use slab::Slab;
+
+use super::Bridge;
+use crate::{App, Core, Effect, WithContext};
+
+/// A set of independent instances of the same app, each with its own model and
+/// pending effects, accessed through the same serialized interface as the [`Bridge`].
+///
+/// This lets a shell host several copies of an app at once (e.g. one per window in
+/// a desktop app). Each instance is identified by the id returned from
+/// [`MultiBridge::create_instance`], which the shell passes alongside every message.
+/// Effect ids in the returned requests are scoped to the instance that produced them,
+/// so responses must be sent back to the same instance.
+pub struct MultiBridge<Eff, A>
+where
+    Eff: Effect,
+    A: App,
+{
+    instances: RwLock<Slab<Bridge<Eff, A>>>,
+}
+
+impl<Eff, A> Default for MultiBridge<Eff, A>
+where
+    Eff: Effect + Send + 'static,
+    A: App,
+    A::Capabilities: WithContext<A::Event, Eff>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Eff, A> MultiBridge<Eff, A>
+where
+    Eff: Effect + Send + 'static,
+    A: App,
+    A::Capabilities: WithContext<A::Event, Eff>,
+{
+    /// Create a new MultiBridge with no instances.
+    pub fn new() -> Self {
+        Self {
+            instances: RwLock::new(Slab::new()),
+        }
+    }
+
+    /// Start a new instance of the app, returning its id.
+    ///
+    /// Ids of destroyed instances may be reused by later instances.
+    pub fn create_instance(&self) -> u32 {
+        let bridge = Bridge::new(Core::new());
+
+        let id = self
+            .instances
+            .write()
+            .expect("Instances RwLock poisoned.")
+            .insert(bridge);
+
+        id.try_into().expect("Instance id overflow")
+    }
+
+    /// Stop the instance with the given id, dropping its model and any pending effects.
+    ///
+    /// Returns `false` if there was no such instance.
+    pub fn destroy_instance(&self, instance: u32) -> bool {
+        self.instances
+            .write()
+            .expect("Instances RwLock poisoned.")
+            .try_remove(instance as usize)
+            .is_some()
+    }
+
+    /// Receive the startup configuration from the shell for the given instance, before
+    /// its first event.
+    ///
+    /// The `config` is a serialized [`Init`](crate::init::Init). The `instance` MUST be
+    /// the id of a live instance, else the core will panic.
+    pub fn init(&self, instance: u32, config: &[u8]) {
+        self.with_instance(instance, |bridge| bridge.init(config))
+    }
+
+    /// Receive an event from the shell for the given instance.
+    ///
+    /// The `event` is serialized and will be deserialized by the core before it's passed
+    /// to your app. The `instance` MUST be the id of a live instance, else the core will panic.
+    pub fn process_event(&self, instance: u32, event: &[u8]) -> Vec<u8>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.with_instance(instance, |bridge| bridge.process_event(event))
+    }
+
+    /// Receive a response to a capability request from the shell for the given instance.
+    ///
+    /// The `output` is serialized capability output. It will be deserialized by the core.
+    /// The `instance` and `id` MUST match the instance and the `id` of the effect that
+    /// triggered it, else the core will panic.
+    pub fn handle_response(&self, instance: u32, id: u32, output: &[u8]) -> Vec<u8>
+    where
+        A::Event: for<'a> Deserialize<'a>,
+    {
+        self.with_instance(instance, |bridge| bridge.handle_response(id, output))
+    }
+
+    /// Get the current state of the given instance's view model (serialized).
+    pub fn view(&self, instance: u32) -> Vec<u8> {
+        self.with_instance(instance, Bridge::view)
+    }
+
+    fn with_instance<T>(&self, instance: u32, f: impl FnOnce(&Bridge<Eff, A>) -> T) -> T {
+        let instances = self.instances.read().expect("Instances RwLock poisoned.");
+
+        let Some(bridge) = instances.get(instance as usize) else {
+            panic!("Instance {instance} not found.");
+        };
+
+        f(bridge)
+    }
+}
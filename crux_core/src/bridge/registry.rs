@@ -1,9 +1,8 @@
-use std::sync::Mutex;
+use std::{collections::HashMap, sync::Mutex};
 
 use serde::{Deserialize, Serialize};
-use slab::Slab;
 
-use super::Request;
+use super::{BridgeError, Request};
 use crate::bridge::request_serde::ResolveSerialized;
 use crate::core::ResolveError;
 use crate::Effect;
@@ -12,11 +11,25 @@ use crate::Effect;
 #[serde(transparent)]
 pub struct EffectId(pub u32);
 
-pub struct ResolveRegistry(Mutex<Slab<ResolveSerialized>>);
+/// Stores the resolves of effects sent to the shell until the shell responds.
+///
+/// Ids are handed out in sequence and never reused (until they wrap around), so an id
+/// lower than the next one, which is no longer stored, belongs to an effect which has
+/// already been resolved. A shell resolving an effect twice gets an error, instead of
+/// resolving a newer effect which was given the same id.
+pub struct ResolveRegistry(Mutex<Entries>);
+
+struct Entries {
+    resolves: HashMap<u32, ResolveSerialized>,
+    next_id: u32,
+}
 
 impl Default for ResolveRegistry {
     fn default() -> Self {
-        Self(Mutex::new(Slab::with_capacity(1024)))
+        Self(Mutex::new(Entries {
+            resolves: HashMap::with_capacity(1024),
+            next_id: 0,
+        }))
     }
 }
 
@@ -32,43 +45,77 @@ impl ResolveRegistry {
     where
         Eff: Effect,
     {
+        let priority = effect.priority();
         let (effect, resolve) = effect.serialize();
 
-        let id = self
-            .0
-            .lock()
-            .expect("Registry Mutex poisoned.")
-            .insert(resolve);
+        let mut entries = self.0.lock().expect("Registry Mutex poisoned.");
+        let id = entries.next_id;
+        entries.next_id = id.wrapping_add(1);
+        entries.resolves.insert(id, resolve);
 
         Request {
-            id: EffectId(id.try_into().expect("EffectId overflow")),
+            id: EffectId(id),
             effect,
+            priority,
         }
     }
     // ANCHOR_END: register
 
+    /// The number of effects waiting for a response from the shell
+    pub fn pending(&self) -> usize {
+        let entries = self.0.lock().expect("Registry Mutex poisoned.");
+
+        entries
+            .resolves
+            .values()
+            .filter(|resolve| !matches!(resolve, ResolveSerialized::Never))
+            .count()
+    }
+
+    /// Whether the effect with the `id` is waiting for a response from the shell
+    pub fn is_pending(&self, id: EffectId) -> bool {
+        let entries = self.0.lock().expect("Registry Mutex poisoned.");
+
+        matches!(
+            entries.resolves.get(&id.0),
+            Some(resolve) if !matches!(resolve, ResolveSerialized::Never)
+        )
+    }
+
     /// Resume a previously registered effect. This may fail, either because EffectId wasn't
-    /// found or because this effect was not expected to be resumed again.
+    /// found, or because this effect has already been resolved, or was not expected to be
+    /// resolved at all, or because the `body` doesn't deserialize to its output.
     pub fn resume(
         &self,
         id: EffectId,
         body: &mut dyn erased_serde::Deserializer,
-    ) -> Result<(), ResolveError> {
-        let mut registry_lock = self.0.lock().expect("Registry Mutex poisoned");
+    ) -> Result<(), BridgeError> {
+        let mut entries = self.0.lock().expect("Registry Mutex poisoned");
 
-        let entry = registry_lock.get_mut(id.0 as usize);
-
-        let Some(entry) = entry else {
-            // FIXME return an Err instead of panicking here.
-            panic!("Request with {id:?} not found.");
+        let Some(entry) = entries.resolves.get_mut(&id.0) else {
+            return Err(if id.0 < entries.next_id {
+                BridgeError::AlreadyResolved { id: id.0 }
+            } else {
+                BridgeError::NotFound { id: id.0 }
+            });
         };
 
         let resolved = entry.resolve(body);
 
-        if let ResolveSerialized::Never = entry {
-            registry_lock.remove(id.0 as usize);
+        // the shell can try again after sending output which doesn't deserialize
+        let finished = match resolved {
+            Ok(()) => matches!(entry, ResolveSerialized::Never),
+            Err(ResolveError::Deserialization) => false,
+            Err(_) => true,
+        };
+        if finished {
+            entries.resolves.remove(&id.0);
         }
 
-        resolved
+        resolved.map_err(|error| match error {
+            ResolveError::Never => BridgeError::NotExpected { id: id.0 },
+            ResolveError::FinishedMany => BridgeError::AlreadyResolved { id: id.0 },
+            ResolveError::Deserialization => BridgeError::InvalidResponse { id: id.0 },
+        })
     }
 }
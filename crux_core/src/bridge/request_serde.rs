@@ -6,9 +6,10 @@ use crate::{
 
 // used in docs/internals/bridge.md
 // ANCHOR: resolve_serialized
-type ResolveOnceSerialized = Box<dyn FnOnce(&mut dyn erased_serde::Deserializer) + Send>;
+type ResolveOnceSerialized =
+    Box<dyn FnMut(&mut dyn erased_serde::Deserializer) -> Result<(), ResolveError> + Send>;
 type ResolveManySerialized =
-    Box<dyn FnMut(&mut dyn erased_serde::Deserializer) -> Result<(), ()> + Send>;
+    Box<dyn FnMut(&mut dyn erased_serde::Deserializer) -> Result<(), ResolveError> + Send>;
 
 /// A deserializing version of Resolve
 ///
@@ -30,14 +31,12 @@ impl ResolveSerialized {
     ) -> Result<(), ResolveError> {
         match self {
             ResolveSerialized::Never => Err(ResolveError::Never),
-            ResolveSerialized::Many(f) => f(bytes).map_err(|_| ResolveError::FinishedMany),
-            ResolveSerialized::Once(_) => {
+            ResolveSerialized::Many(f) => f(bytes),
+            ResolveSerialized::Once(f) => {
+                f(bytes)?;
+
                 // The resolve has been used, turn it into a Never
-                if let ResolveSerialized::Once(f) =
-                    std::mem::replace(self, ResolveSerialized::Never)
-                {
-                    f(bytes);
-                }
+                *self = ResolveSerialized::Never;
 
                 Ok(())
             }
@@ -62,9 +61,7 @@ where
         // FIXME should Eff be bound as `Serializable`?
         let (operation, resolve) = (self.operation, self.resolve);
 
-        let resolve = resolve.deserializing(move |deserializer| {
-            erased_serde::deserialize(deserializer).expect("Deserialization failed")
-        });
+        let resolve = resolve.deserializing(|deserializer| erased_serde::deserialize(deserializer));
 
         (effect(operation), resolve)
     }
@@ -73,20 +70,32 @@ where
 impl<Out> Resolve<Out> {
     /// Convert this Resolve into a version which deserializes from bytes, consuming it.
     /// The `func` argument is a 'deserializer' converting from bytes into the `Out` type.
+    ///
+    /// Output which fails to deserialize doesn't resolve the request, so the shell can
+    /// respond again.
     fn deserializing<F>(self, mut func: F) -> ResolveSerialized
     where
-        F: (FnMut(&mut dyn erased_serde::Deserializer) -> Out) + Send + Sync + 'static,
+        F: (FnMut(&mut dyn erased_serde::Deserializer) -> Result<Out, erased_serde::Error>)
+            + Send
+            + Sync
+            + 'static,
         Out: 'static,
     {
         match self {
             Resolve::Never => ResolveSerialized::Never,
-            Resolve::Once(resolve) => ResolveSerialized::Once(Box::new(move |deser| {
-                let out = func(deser);
-                resolve(out)
-            })),
+            Resolve::Once(state) => {
+                let mut state = Some(state);
+                ResolveSerialized::Once(Box::new(move |deser| {
+                    let out = func(deser).map_err(|_| ResolveError::Deserialization)?;
+                    if let Some(state) = state.take().and_then(|state| state.upgrade()) {
+                        state.resolve(out);
+                    }
+                    Ok(())
+                }))
+            }
             Resolve::Many(resolve) => ResolveSerialized::Many(Box::new(move |deser| {
-                let out = func(deser);
-                resolve(out)
+                let out = func(deser).map_err(|_| ResolveError::Deserialization)?;
+                resolve(out).map_err(|()| ResolveError::FinishedMany)
             })),
         }
     }
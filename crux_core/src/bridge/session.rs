@@ -0,0 +1,122 @@
+//! Recording the messages a shell sends to the bridge, to replay them against a core later
+//!
+//! A [`Session`] is the list of messages the bridge received, in order, each with the view
+//! the core returned afterwards. It's written to a file as JSON lines: a header with the
+//! format's version, then a line for each [`Step`].
+//!
+//! Replaying the session on a new bridge sends it the same messages, and reports the steps
+//! whose view differs from the recorded one as a [`Divergence`]. Effect ids are handed out
+//! in sequence, so the responses in the session match the effects of the replay, as long
+//! as the core makes the same requests. A core which uses [`Ids`](crate::ids::Ids)
+//! needs to be created with the same [`Core::with_seed`](crate::Core::with_seed) for both.
+//!
+//! The `crux replay <file>` command of the CLI replays a session file against the current
+//! code of a core in the `Crux.toml` workspace, and shows the views which differ.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The version of the session file format written by [`Session::export`]
+pub const SESSION_VERSION: u32 = 1;
+
+/// The messages a shell sent to a [`Bridge`](super::Bridge) created
+/// [`with_recording`](super::Bridge::with_recording)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub steps: Vec<Step>,
+}
+
+/// A message the shell sent, with the view (serialized) once the core had processed it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Step {
+    pub message: Message,
+    pub view: Vec<u8>,
+}
+
+/// A message from the shell to the bridge, with its serialized payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Message {
+    /// The startup configuration, passed to [`Bridge::init`](super::Bridge::init)
+    Init { config: Vec<u8> },
+    /// An event, passed to [`Bridge::process_event`](super::Bridge::process_event)
+    Event { event: Vec<u8> },
+    /// A list of events, passed to [`Bridge::process_events`](super::Bridge::process_events)
+    Events { events: Vec<u8> },
+    /// A response to an effect, passed to
+    /// [`Bridge::handle_response`](super::Bridge::handle_response)
+    Response { id: u32, output: Vec<u8> },
+}
+
+/// A step of a replayed session whose view differs from the recorded one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The index of the step in the session
+    pub step: usize,
+    /// The view which was recorded (serialized)
+    pub expected: Vec<u8>,
+    /// The view after replaying the step (serialized)
+    pub actual: Vec<u8>,
+}
+
+/// An error reading a session file
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    /// The session was written in a version of the format this core can't read
+    #[error("Session format version {version} is not supported, expected {SESSION_VERSION}.")]
+    UnsupportedVersion { version: u32 },
+    /// A line of the session is not a valid header or step
+    #[error("Line {line} of the session is not valid: {message}")]
+    Invalid { line: usize, message: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+}
+
+impl Session {
+    /// Write the session as JSON lines, a header followed by a line for each step
+    pub fn export(&self) -> Vec<u8> {
+        let mut out = serde_json::to_vec(&Header {
+            version: SESSION_VERSION,
+        })
+        .expect("Session header should serialize");
+
+        for step in &self.steps {
+            out.push(b'\n');
+            serde_json::to_writer(&mut out, step).expect("Session step should serialize");
+        }
+        out.push(b'\n');
+
+        out
+    }
+
+    /// Read a session written by [`Session::export`]
+    pub fn import(data: &[u8]) -> Result<Self, SessionError> {
+        let invalid = |line: usize, error: serde_json::Error| SessionError::Invalid {
+            line: line + 1,
+            message: error.to_string(),
+        };
+        let mut lines = data
+            .split(|byte| *byte == b'\n')
+            .enumerate()
+            .filter(|(_, line)| !line.iter().all(u8::is_ascii_whitespace));
+
+        let Some((index, header)) = lines.next() else {
+            return Err(SessionError::Invalid {
+                line: 1,
+                message: "the session is empty".to_string(),
+            });
+        };
+        let Header { version } = serde_json::from_slice(header).map_err(|e| invalid(index, e))?;
+        if version != SESSION_VERSION {
+            return Err(SessionError::UnsupportedVersion { version });
+        }
+
+        let steps = lines
+            .map(|(index, line)| serde_json::from_slice(line).map_err(|e| invalid(index, e)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { steps })
+    }
+}
@@ -0,0 +1,170 @@
+use std::{collections::HashMap, io, sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use super::{EffectId, Request};
+
+/// The longest operation summary kept for a [`StuckEffect`], in bytes
+const MAX_OPERATION_LEN: usize = 256;
+
+/// An effect which has been waiting for a response from the shell for longer than the
+/// threshold, which the bridge sends to the app in an event, when set up with
+/// [`Bridge::with_watchdog`](super::Bridge::with_watchdog).
+///
+/// A shell which never responds to an effect leaves the app waiting forever, e.g. with a
+/// spinner which never stops. The app can log the stuck effect, or report it with the
+/// rest of its diagnostics, to tell which capability and which operation the shell
+/// dropped. Each effect is reported once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StuckEffect {
+    /// The id of the effect's request, as sent to the shell
+    pub id: u32,
+    /// The name of the variant of the effect, e.g. `"Http"`
+    pub name: String,
+    /// The effect's operation, serialized as JSON, and cut short if it's long
+    pub operation: String,
+    /// How long the effect had been waiting for a response when it was reported
+    pub pending_for: Duration,
+}
+
+type MakeEvent<Ev> = Box<dyn Fn(StuckEffect) -> Ev + Send + Sync>;
+
+struct Watched {
+    name: &'static str,
+    operation: String,
+    since: Instant,
+    reported: bool,
+}
+
+pub(crate) struct Watchdog<Ev> {
+    threshold: Duration,
+    make_event: MakeEvent<Ev>,
+    watched: Mutex<HashMap<u32, Watched>>,
+}
+
+impl<Ev> Watchdog<Ev> {
+    pub(crate) fn new(
+        threshold: Duration,
+        make_event: impl Fn(StuckEffect) -> Ev + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            threshold,
+            make_event: Box::new(make_event),
+            watched: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start the clock on a request sent to the shell
+    pub(crate) fn watch<Ffi>(&self, name: &'static str, request: &Request<Ffi>)
+    where
+        Ffi: Serialize,
+    {
+        let operation = preview(&request.effect, MAX_OPERATION_LEN);
+
+        self.lock().insert(
+            request.id.0,
+            Watched {
+                name,
+                operation,
+                since: Instant::now(),
+                reported: false,
+            },
+        );
+    }
+
+    /// The events for the app about the effects which were still `pending` past the
+    /// threshold at `now`, and haven't been reported yet
+    pub(crate) fn events(&self, now: Instant, pending: impl Fn(EffectId) -> bool) -> Vec<Ev> {
+        let mut watched = self.lock();
+        watched.retain(|id, _| pending(EffectId(*id)));
+
+        let mut stuck: Vec<_> = watched
+            .iter_mut()
+            .filter_map(|(id, watched)| {
+                let pending_for = now.saturating_duration_since(watched.since);
+                if watched.reported || pending_for <= self.threshold {
+                    return None;
+                }
+                watched.reported = true;
+
+                Some(StuckEffect {
+                    id: *id,
+                    name: watched.name.to_string(),
+                    operation: watched.operation.clone(),
+                    pending_for,
+                })
+            })
+            .collect();
+        drop(watched);
+
+        stuck.sort_by_key(|effect| effect.id);
+        stuck.into_iter().map(&self.make_event).collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u32, Watched>> {
+        self.watched.lock().expect("Watchdog Mutex was poisoned.")
+    }
+}
+
+/// The start of `value` serialized as JSON, at most `max_len` bytes of it on a character
+/// boundary, without serializing the rest
+fn preview(value: &impl Serialize, max_len: usize) -> String {
+    let mut preview = Preview {
+        bytes: Vec::new(),
+        max_len,
+        cut: false,
+    };
+    if serde_json::to_writer(&mut preview, value).is_err() && !preview.cut {
+        return String::new();
+    }
+
+    let Preview { mut bytes, cut, .. } = preview;
+    if let Err(error) = std::str::from_utf8(&bytes) {
+        bytes.truncate(error.valid_up_to());
+    }
+    let mut text = String::from_utf8(bytes).expect("bytes should be valid UTF-8");
+    if cut {
+        text.push('…');
+    }
+    text
+}
+
+/// A writer which keeps the first `max_len` bytes, and fails once it has them, so the
+/// serializer stops early
+struct Preview {
+    bytes: Vec<u8>,
+    max_len: usize,
+    cut: bool,
+}
+
+impl io::Write for Preview {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let room = self.max_len - self.bytes.len();
+        if buf.len() > room {
+            self.bytes.extend_from_slice(&buf[..room]);
+            self.cut = true;
+            return Err(io::Error::new(io::ErrorKind::Other, "preview is full"));
+        }
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_operations_are_cut_short() {
+        assert_eq!(preview(&"short", 8), "\"short\"");
+        assert_eq!(preview(&"a longer one", 8), "\"a longe…");
+        // 'é' is two bytes, and isn't split
+        assert_eq!(preview(&"ééééé", 6), "\"éé…");
+        assert_eq!(preview(&vec!["item"; 1000], 16), r#"["item","item","…"#);
+    }
+}
@@ -0,0 +1,77 @@
+//! Built-in capability used to send follow-up events to the app from `update`.
+
+use crate::capability::{CapabilityContext, Never};
+use crate::Capability;
+
+/// Use an instance of `Dispatch` to process further events after the current one, e.g.
+/// a `Refresh` after a `SetFilter`, without a round trip to the Shell.
+///
+/// Dispatched events are queued, and processed in the order they were sent once the
+/// current call to [`App::update`](crate::App::update) returns, before the core returns
+/// to the Shell. If a single message from the Shell results in more than 10,000 events, which
+/// most likely means the app keeps dispatching events to itself in a loop, the core drops the
+/// rest. See [`Core::with_max_events`](crate::Core::with_max_events).
+///
+/// Like [`Compose`](crate::compose::Compose), `Dispatch` doesn't send any operations to the
+/// shell, so use `#[effect(skip)]` to skip generating an effect variant for it:
+///
+/// ```rust
+/// # use crux_core::macros::Effect;
+/// # use crux_core::{dispatch::Dispatch, render::Render};
+/// # enum Event { Nothing }
+/// #[derive(Effect)]
+/// pub struct Capabilities {
+///     pub render: Render<Event>,
+///     #[effect(skip)]
+///     pub dispatch: Dispatch<Event>,
+/// }
+/// ```
+pub struct Dispatch<Ev> {
+    context: CapabilityContext<Never, Ev>,
+}
+
+impl<Ev> Clone for Dispatch<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+        }
+    }
+}
+
+impl<Ev> Dispatch<Ev>
+where
+    Ev: 'static,
+{
+    pub fn new(context: CapabilityContext<Never, Ev>) -> Self {
+        Self { context }
+    }
+
+    /// Queue an `event` to be processed by the app after the current one.
+    pub fn send(&self, event: Ev) {
+        self.context.update_app(event);
+    }
+}
+
+impl<Ev> Capability<Ev> for Dispatch<Ev> {
+    type Operation = Never;
+    type MappedSelf<MappedEv> = Dispatch<MappedEv>;
+
+    fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+    where
+        F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+        Ev: 'static,
+        NewEv: 'static,
+    {
+        Dispatch::new(self.context.map_event(f))
+    }
+
+    #[cfg(feature = "typegen")]
+    fn register_types(_generator: &mut crate::typegen::TypeGen) -> crate::typegen::Result {
+        panic!(
+            r#"
+            The Dispatch Capability should not be registered for type generation.
+            Instead, use #[effect(skip)] to skip the generation of an effect variant for the Dispatch Capability.
+            "#
+        )
+    }
+}
@@ -0,0 +1,178 @@
+//! Built-in capability used to generate unique ids, e.g. for new entities, from `update`.
+
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::capability::{CapabilityContext, Never};
+use crate::Capability;
+
+/// A unique id, made by the core's id generator, see [`Ids`].
+///
+/// Ids sort in the order they were made. The upper 64 bits are a sequence number, counting
+/// from 1 for each core, and the lower 64 bits are derived from the generator's seed and
+/// the sequence number, so that ids made by different cores are very unlikely to collide.
+/// Like a ULID, an id is displayed as 26 characters of Crockford's base32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Id(pub u128);
+
+impl Id {
+    /// The position of the id in the sequence of ids made by the core, starting at 1
+    pub fn sequence(self) -> u64 {
+        (self.0 >> 64) as u64
+    }
+
+    /// The lower 64 bits of the id, for ids which need to fit in a `u64`, e.g. timer ids.
+    /// They're unique among the ids made by one core, and derived from its seed, so unlike
+    /// the [`sequence`](Id::sequence) they're very unlikely to repeat the ids of another
+    /// core in the same shell, e.g. another instance of a
+    /// [`MultiBridge`](crate::bridge::MultiBridge).
+    pub fn to_u64(self) -> u64 {
+        self.0 as u64
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+        // 26 characters of 5 bits hold 130 bits, the first character holds the top 3
+        let text: String = (0..26)
+            .rev()
+            .map(|i| ALPHABET[((self.0 >> (i * 5)) & 0x1f) as usize] as char)
+            .collect();
+        f.write_str(&text)
+    }
+}
+
+/// The id generator shared by all the capabilities of a core, through their contexts.
+///
+/// `Core` seeds it randomly, `AppTester` with a fixed seed, so that tests, and replays of
+/// recorded sessions which use the same seed, get the same ids every time.
+#[derive(Clone)]
+pub(crate) struct IdSource(Arc<Mutex<Generator>>);
+
+struct Generator {
+    seed: u64,
+    sequence: u64,
+}
+
+impl IdSource {
+    pub(crate) fn seeded(seed: u64) -> Self {
+        Self(Arc::new(Mutex::new(Generator { seed, sequence: 0 })))
+    }
+
+    /// Start the sequence again from a new `seed`
+    pub(crate) fn reseed(&self, seed: u64) {
+        *self.0.lock().expect("IdSource Mutex was poisoned.") = Generator { seed, sequence: 0 };
+    }
+
+    pub(crate) fn next(&self) -> Id {
+        let mut generator = self.0.lock().expect("IdSource Mutex was poisoned.");
+        generator.sequence += 1;
+
+        let sequence = generator.sequence;
+        // mixing the seed first keeps nearby seeds from making the same suffixes
+        let suffix = mix(mix(generator.seed) ^ sequence);
+
+        Id((u128::from(sequence) << 64) | u128::from(suffix))
+    }
+}
+
+impl Default for IdSource {
+    fn default() -> Self {
+        let mut hasher = RandomState::new().build_hasher();
+        web_time::SystemTime::now()
+            .duration_since(web_time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+
+        Self::seeded(hasher.finish())
+    }
+}
+
+/// The SplitMix64 finaliser, which spreads consecutive inputs over the whole range
+fn mix(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+/// Use an instance of `Ids` to make unique ids in `update`, e.g. for a new todo item.
+///
+/// The ids come from the core, instead of a random number generator or a counter kept by
+/// the app, so they're predictable in tests: an [`AppTester`](crate::testing::AppTester)
+/// makes the same ids every run, and [`AppTester::with_seed`](crate::testing::AppTester::with_seed)
+/// and [`Core::with_seed`](crate::Core::with_seed) reproduce the ids of a recorded session.
+/// Capabilities can make ids for their own use with [`CapabilityContext::next_id`].
+///
+/// `Ids` doesn't send any operations to the shell, so use `#[effect(skip)]` to skip
+/// generating an effect variant for it:
+///
+/// ```rust
+/// # use crux_core::macros::Effect;
+/// # use crux_core::{ids::Ids, render::Render};
+/// # enum Event { Nothing }
+/// #[derive(Effect)]
+/// pub struct Capabilities {
+///     pub render: Render<Event>,
+///     #[effect(skip)]
+///     pub ids: Ids<Event>,
+/// }
+/// ```
+pub struct Ids<Ev> {
+    context: CapabilityContext<Never, Ev>,
+}
+
+impl<Ev> Clone for Ids<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+        }
+    }
+}
+
+impl<Ev> Ids<Ev>
+where
+    Ev: 'static,
+{
+    pub fn new(context: CapabilityContext<Never, Ev>) -> Self {
+        Self { context }
+    }
+
+    /// Make a new id, never made before by this core (or since it was last reseeded).
+    pub fn next(&self) -> Id {
+        self.context.next_id()
+    }
+}
+
+impl<Ev> Capability<Ev> for Ids<Ev> {
+    type Operation = Never;
+    type MappedSelf<MappedEv> = Ids<MappedEv>;
+
+    fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+    where
+        F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+        Ev: 'static,
+        NewEv: 'static,
+    {
+        Ids::new(self.context.map_event(f))
+    }
+
+    #[cfg(feature = "typegen")]
+    fn register_types(_generator: &mut crate::typegen::TypeGen) -> crate::typegen::Result {
+        panic!(
+            r#"
+            The Ids Capability should not be registered for type generation.
+            Instead, use #[effect(skip)] to skip the generation of an effect variant for the Ids Capability.
+            "#
+        )
+    }
+}
@@ -1,2 +1,4 @@
 pub mod compose;
+pub mod dispatch;
+pub mod ids;
 pub mod render;
@@ -50,6 +50,7 @@ impl<T> Receiver<T> {
         }
     }
 
+    #[allow(mismatched_lifetime_syntaxes)]
     pub fn drain(&self) -> Drain<T> {
         Drain { receiver: self }
     }
@@ -130,7 +131,8 @@ mod tests {
 
     use super::*;
 
-    assert_impl_all!(Sender<i32>: Send);
+    assert_impl_all!(Sender<i32>: Send, Sync);
+    assert_impl_all!(Receiver<i32>: Send, Sync);
 
     #[test]
     fn test_channels() {
@@ -145,4 +147,31 @@ mod tests {
 
         assert_eq!(recv.receive(), None);
     }
+
+    #[test]
+    fn test_channels_across_threads() {
+        let (send, recv) = channel();
+
+        let handles = (0..8)
+            .map(|thread| {
+                let send = send.map_input(move |n| (thread, n));
+                std::thread::spawn(move || {
+                    for n in 0..1000 {
+                        send.send(n);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // messages from each sender arrive in the order they were sent
+        let mut next = [0; 8];
+        for (thread, n) in recv.drain() {
+            assert_eq!(n, next[thread]);
+            next[thread] += 1;
+        }
+        assert_eq!(next, [1000; 8]);
+    }
 }
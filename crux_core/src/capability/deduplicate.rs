@@ -0,0 +1,217 @@
+//! Sharing the output of idempotent requests between the tasks making them
+//!
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use futures::channel::oneshot;
+
+use crate::capability::{CapabilityContext, Operation};
+
+/// The tasks waiting for the output of each pending deduplicated request, by key
+pub(crate) type Pending<T> = Arc<Mutex<HashMap<String, Vec<oneshot::Sender<T>>>>>;
+
+/// Removes the key of a request from the pending ones when the task which sent it finishes,
+/// or is dropped before the request is resolved. In that case the waiting tasks are woken
+/// up to send the request themselves.
+struct Sent<'a, T> {
+    pending: &'a Pending<T>,
+    key: &'a str,
+    finished: bool,
+}
+
+impl<T> Sent<'_, T> {
+    /// The tasks waiting for the output
+    fn finish(mut self) -> Vec<oneshot::Sender<T>> {
+        self.finished = true;
+        lock(self.pending).remove(self.key).unwrap_or_default()
+    }
+}
+
+impl<T> Drop for Sent<'_, T> {
+    fn drop(&mut self) {
+        if !self.finished {
+            lock(self.pending).remove(self.key);
+        }
+    }
+}
+
+fn lock<T>(
+    pending: &Pending<T>,
+) -> std::sync::MutexGuard<'_, HashMap<String, Vec<oneshot::Sender<T>>>> {
+    pending.lock().expect("Pending requests Mutex poisoned.")
+}
+
+impl<Op, Ev> CapabilityContext<Op, Ev>
+where
+    Op: Operation,
+    Op::Output: Clone,
+    Ev: 'static,
+{
+    /// Send an effect request to the shell, like
+    /// [`request_from_shell`](CapabilityContext::request_from_shell), unless a request with
+    /// the same `key` is already waiting for the shell's output, in which case no request is
+    /// sent, and the output of the pending one is returned to both callers.
+    ///
+    /// Use it for idempotent operations, which are safe to share, with a key which identifies
+    /// them, e.g. `"user/42"` for fetching a user. Once the output arrives, the next request
+    /// with the key is sent to the shell again. Requests are only shared with the other
+    /// contexts of the same capability, cloned or mapped with
+    /// [`map_event`](CapabilityContext::map_event).
+    ///
+    /// ```rust,ignore
+    /// let ctx = self.context.clone();
+    /// self.context.spawn(async move {
+    ///     let key = format!("user/{id}");
+    ///     let response = ctx
+    ///         .request_from_shell_deduplicated(key, HttpRequest::get(url))
+    ///         .await;
+    ///
+    ///     ctx.update_app(callback(response));
+    /// });
+    /// ```
+    pub async fn request_from_shell_deduplicated(
+        &self,
+        key: impl Into<String>,
+        operation: Op,
+    ) -> Op::Output {
+        let key = key.into();
+        let pending = &self.inner.pending;
+
+        loop {
+            let waiting = {
+                let mut pending = lock(pending);
+                match pending.get_mut(&key) {
+                    Some(waiting) => {
+                        let (sender, receiver) = oneshot::channel();
+                        waiting.push(sender);
+                        Some(receiver)
+                    }
+                    None => {
+                        pending.insert(key.clone(), Vec::new());
+                        None
+                    }
+                }
+            };
+
+            let Some(receiver) = waiting else {
+                break;
+            };
+            if let Ok(output) = receiver.await {
+                return output;
+            }
+            // the task which sent the request was dropped, try again
+        }
+
+        let sent = Sent {
+            pending,
+            key: &key,
+            finished: false,
+        };
+        let output = self.request_from_shell(operation).await;
+
+        for waiting in sent.finish() {
+            let _ = waiting.send(output.clone());
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use futures::Future;
+
+    use crate::capability::{channel, executor_and_spawner, CapabilityContext, Operation};
+
+    #[derive(serde::Serialize, Clone, PartialEq, Eq, Debug)]
+    struct Fetch(u32);
+
+    impl Operation for Fetch {
+        type Output = String;
+    }
+
+    #[test]
+    fn pending_requests_are_shared() {
+        let (request_sender, requests) = channel();
+        let (event_sender, events) = channel::<String>();
+        let (executor, spawner) = executor_and_spawner();
+        let context =
+            CapabilityContext::new(request_sender, event_sender, spawner, Default::default());
+
+        let fetch = |id: u32| {
+            let context = context.clone();
+            async move {
+                let output = context
+                    .request_from_shell_deduplicated(format!("user/{id}"), Fetch(id))
+                    .await;
+                context.update_app(output);
+            }
+        };
+
+        context.spawn(fetch(42));
+        context.spawn(fetch(42));
+        context.spawn(fetch(7));
+        executor.run_all();
+
+        let mut first = requests.receive().expect("a request for user 42");
+        assert_eq!(first.operation, Fetch(42));
+        let mut second = requests.receive().expect("a request for user 7");
+        assert_eq!(second.operation, Fetch(7));
+        assert_matches!(requests.receive(), None);
+
+        first.resolve("ferris".to_string()).unwrap();
+        second.resolve("crab".to_string()).unwrap();
+        executor.run_all();
+
+        let mut outputs: Vec<_> = events.drain().collect();
+        outputs.sort();
+        assert_eq!(outputs, ["crab", "ferris", "ferris"]);
+
+        // once resolved, the request is sent again
+        context.spawn(fetch(42));
+        executor.run_all();
+        assert_matches!(requests.receive(), Some(request) if request.operation == Fetch(42));
+    }
+
+    #[test]
+    fn waiting_tasks_send_the_request_if_the_first_is_dropped() {
+        let (request_sender, requests) = channel();
+        let (event_sender, events) = channel::<String>();
+        let (executor, spawner) = executor_and_spawner();
+        let context =
+            CapabilityContext::new(request_sender, event_sender, spawner, Default::default());
+
+        let fetch = context.request_from_shell_deduplicated("user/42", Fetch(42));
+        context.spawn({
+            let context = context.clone();
+            async move {
+                let output = context
+                    .request_from_shell_deduplicated("user/42", Fetch(42))
+                    .await;
+                context.update_app(output);
+            }
+        });
+
+        // the first request is sent by polling it, then dropped before it's resolved
+        let mut fetch = Box::pin(fetch);
+        let waker = futures::task::noop_waker();
+        let mut cx = std::task::Context::from_waker(&waker);
+        assert!(fetch.as_mut().poll(&mut cx).is_pending());
+        executor.run_all();
+
+        let dropped = requests.receive().expect("the first request");
+        assert_matches!(requests.receive(), None);
+        drop(fetch);
+        drop(dropped);
+        executor.run_all();
+
+        let mut request = requests.receive().expect("the request sent again");
+        request.resolve("ferris".to_string()).unwrap();
+        executor.run_all();
+
+        assert_eq!(events.drain().collect::<Vec<_>>(), ["ferris"]);
+    }
+}
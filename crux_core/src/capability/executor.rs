@@ -1,29 +1,139 @@
 use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    pin::Pin,
     sync::{Arc, Mutex},
-    task::{Context, Wake},
+    task::{Context, Poll, Wake, Waker},
 };
 
 use crossbeam_channel::{Receiver, Sender};
-use futures::{future, Future, FutureExt};
+use futures::Future;
 use slab::Slab;
 
-type BoxFuture = future::BoxFuture<'static, ()>;
-
 // used in docs/internals/runtime.md
 // ANCHOR: executor
 pub(crate) struct QueuingExecutor {
-    spawn_queue: Receiver<BoxFuture>,
+    spawn_queue: Receiver<PooledTask>,
     ready_queue: Receiver<TaskId>,
     ready_sender: Sender<TaskId>,
-    tasks: Mutex<Slab<Option<BoxFuture>>>,
+    tasks: Mutex<Tasks>,
+    pool: TaskPool,
 }
 // ANCHOR_END: executor
 
+// used in docs/internals/runtime.md
+// ANCHOR: task
+/// A spawned future, in a slot of its own type. Once the future completes, the slot is
+/// emptied and goes back to the [`TaskPool`], to hold the next future of the same type.
+trait Task: Send {
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()>;
+
+    /// Move the future out of `future`, an `Option` of the slot's type, into the empty slot
+    fn refill(self: Pin<&mut Self>, future: &mut dyn Any);
+
+    fn future_type(&self) -> TypeId;
+}
+
+type PooledTask = Pin<Box<dyn Task>>;
+// ANCHOR_END: task
+
+impl<F> Task for Option<F>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let Some(future) = self.as_mut().as_pin_mut() else {
+            return Poll::Ready(());
+        };
+        let poll = future.poll(cx);
+        if poll.is_ready() {
+            // drop what the future holds now, rather than when the slot is reused
+            self.set(None);
+        }
+        poll
+    }
+
+    fn refill(mut self: Pin<&mut Self>, future: &mut dyn Any) {
+        if let Some(future) = future.downcast_mut::<Option<F>>() {
+            self.set(future.take());
+        }
+    }
+
+    fn future_type(&self) -> TypeId {
+        TypeId::of::<F>()
+    }
+}
+
+/// The empty slots of completed tasks, by the type of future they hold, so that spawning a
+/// task of a type which ran before doesn't allocate. Events which arrive at a high rate, e.g.
+/// text input, tend to spawn the same few types of task over and over.
+#[derive(Clone, Default)]
+struct TaskPool(Arc<Mutex<HashMap<TypeId, Vec<PooledTask>>>>);
+
+impl TaskPool {
+    /// Slots kept for each type of future, beyond which completed ones are freed
+    const SLOTS_PER_TYPE: usize = 32;
+
+    fn task<F>(&self, future: F) -> PooledTask
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let slot = self
+            .0
+            .lock()
+            .expect("Task pool poisoned")
+            .get_mut(&TypeId::of::<F>())
+            .and_then(Vec::pop);
+
+        match slot {
+            Some(mut slot) => {
+                slot.as_mut().refill(&mut Some(future));
+                slot
+            }
+            None => Box::pin(Some(future)),
+        }
+    }
+
+    fn recycle(&self, task: PooledTask) {
+        let mut pool = self.0.lock().expect("Task pool poisoned");
+        let slots = pool.entry(task.future_type()).or_default();
+        if slots.len() < Self::SLOTS_PER_TYPE {
+            slots.push(task);
+        }
+    }
+}
+
+/// The tasks being run, with a waker for each slot of the slab. Slots are reused once their
+/// task completes, and so are their wakers, so polling a task doesn't allocate a new one.
+#[derive(Default)]
+struct Tasks {
+    slab: Slab<Option<PooledTask>>,
+    wakers: Vec<Waker>,
+}
+
+impl Tasks {
+    fn waker(&mut self, task_id: TaskId, sender: &Sender<TaskId>) -> Waker {
+        let index = *task_id as usize;
+        while self.wakers.len() <= index {
+            let task_id = TaskId(self.wakers.len() as u32);
+            self.wakers.push(
+                Arc::new(TaskWaker {
+                    task_id,
+                    sender: sender.clone(),
+                })
+                .into(),
+            );
+        }
+        self.wakers[index].clone()
+    }
+}
+
 // used in docs/internals/runtime.md
 // ANCHOR: spawner
 #[derive(Clone)]
 pub struct Spawner {
-    future_sender: Sender<BoxFuture>,
+    future_sender: Sender<PooledTask>,
+    pool: TaskPool,
 }
 // ANCHOR_END: spawner
 
@@ -41,15 +151,20 @@ impl std::ops::Deref for TaskId {
 pub(crate) fn executor_and_spawner() -> (QueuingExecutor, Spawner) {
     let (future_sender, spawn_queue) = crossbeam_channel::unbounded();
     let (ready_sender, ready_queue) = crossbeam_channel::unbounded();
+    let pool = TaskPool::default();
 
     (
         QueuingExecutor {
             ready_queue,
             spawn_queue,
             ready_sender,
-            tasks: Mutex::new(Slab::new()),
+            tasks: Mutex::new(Tasks::default()),
+            pool: pool.clone(),
+        },
+        Spawner {
+            future_sender,
+            pool,
         },
-        Spawner { future_sender },
     )
 }
 
@@ -57,9 +172,11 @@ pub(crate) fn executor_and_spawner() -> (QueuingExecutor, Spawner) {
 // ANCHOR: spawning
 impl Spawner {
     pub fn spawn(&self, future: impl Future<Output = ()> + 'static + Send) {
-        let future = future.boxed();
+        // the requests the task makes are caused by the event being handled when it's spawned
+        #[cfg(feature = "devtools")]
+        let future = crate::cause::caused(crate::cause::current(), future);
         self.future_sender
-            .send(future)
+            .send(self.pool.task(future))
             .expect("unable to spawn an async task, task sender channel is disconnected.")
     }
 }
@@ -103,6 +220,7 @@ impl QueuingExecutor {
                     .tasks
                     .lock()
                     .expect("Task slab poisoned")
+                    .slab
                     .insert(Some(task));
                 self.run_task(TaskId(task_id.try_into().expect("TaskId overflow")));
                 did_some_work = true;
@@ -131,7 +249,7 @@ impl QueuingExecutor {
 
     fn run_task(&self, task_id: TaskId) -> RunTask {
         let mut lock = self.tasks.lock().expect("Task slab poisoned");
-        let Some(task) = lock.get_mut(*task_id as usize) else {
+        let Some(task) = lock.slab.get_mut(*task_id as usize) else {
             return RunTask::Missing;
         };
         let Some(mut task) = task.take() else {
@@ -140,14 +258,11 @@ impl QueuingExecutor {
             return RunTask::Unavailable;
         };
 
+        let waker = lock.waker(task_id, &self.ready_sender);
+
         // free the mutex so other threads can make progress
         drop(lock);
 
-        let waker = Arc::new(TaskWaker {
-            task_id,
-            sender: self.ready_sender.clone(),
-        })
-        .into();
         let context = &mut Context::from_waker(&waker);
 
         // poll the task
@@ -156,13 +271,16 @@ impl QueuingExecutor {
             self.tasks
                 .lock()
                 .expect("Task slab poisoned")
+                .slab
                 .get_mut(*task_id as usize)
                 .expect("Task slot is missing")
                 .replace(task);
             RunTask::Suspended
         } else {
-            // otherwise the future is completed and we can free the slot
-            self.tasks.lock().unwrap().remove(*task_id as usize);
+            // otherwise the future is completed and we can free the slot, and keep the
+            // task's own slot for the next task like it
+            self.tasks.lock().unwrap().slab.remove(*task_id as usize);
+            self.pool.recycle(task);
             RunTask::Completed
         }
     }
@@ -180,6 +298,7 @@ enum RunTask {
 #[cfg(test)]
 mod tests {
 
+    use futures::FutureExt;
     use rand::Rng;
     use std::{
         sync::atomic::{AtomicI32, Ordering},
@@ -240,6 +359,7 @@ mod tests {
         impl Future for Chaotic {
             type Output = ();
 
+            #[allow(clippy::needless_return, clippy::redundant_pattern_matching)]
             fn poll(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
                 // once we're done, we're done
                 if self.ready_once {
@@ -296,4 +416,80 @@ mod tests {
         assert_eq!(executor.ready_queue.len(), 0);
         assert_eq!(CHAOS_COUNT.load(Ordering::SeqCst), 0);
     }
+
+    #[test]
+    fn test_wakers_are_reused() {
+        // Suspends once, waking itself, and keeps the waker of each poll
+        struct Yield {
+            wakers: Arc<Mutex<Vec<Waker>>>,
+        }
+
+        impl Future for Yield {
+            type Output = ();
+
+            fn poll(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let mut wakers = self.wakers.lock().unwrap();
+                wakers.push(cx.waker().clone());
+                if wakers.len() == 1 {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            }
+        }
+
+        let (executor, spawner) = executor_and_spawner();
+        let first = Arc::new(Mutex::new(Vec::new()));
+        let second = Arc::new(Mutex::new(Vec::new()));
+
+        spawner.spawn(Yield {
+            wakers: first.clone(),
+        });
+        executor.run_all();
+        // the second task takes the slot the first one completed in
+        spawner.spawn(Yield {
+            wakers: second.clone(),
+        });
+        executor.run_all();
+
+        let first = first.lock().unwrap();
+        let second = second.lock().unwrap();
+        assert_eq!(first.len(), 2);
+        assert!(first[0].will_wake(&first[1]));
+        assert!(first[0].will_wake(&second[0]));
+    }
+
+    #[test]
+    fn test_task_slots_are_reused() {
+        async fn task(counter: Arc<()>) {
+            drop(counter);
+        }
+        fn address(task: &PooledTask) -> *const u8 {
+            &**task as *const dyn Task as *const u8
+        }
+        let run = |task: &mut PooledTask| {
+            let context = &mut Context::from_waker(futures::task::noop_waker_ref());
+            assert!(task.as_mut().poll(context).is_ready());
+        };
+
+        let pool = TaskPool::default();
+        let counter = Arc::new(());
+
+        let mut first = pool.task(task(counter.clone()));
+        let slot = address(&first);
+        run(&mut first);
+        // the completed future is dropped before the slot goes back to the pool
+        assert_eq!(Arc::strong_count(&counter), 1);
+        pool.recycle(first);
+
+        // a future of another type gets a new slot
+        let other = pool.task(async {});
+        assert_ne!(address(&other), slot);
+
+        let mut second = pool.task(task(counter.clone()));
+        assert_eq!(address(&second), slot);
+        run(&mut second);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
 }
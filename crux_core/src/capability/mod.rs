@@ -188,6 +188,7 @@
 
 pub(crate) mod channel;
 
+mod deduplicate;
 mod executor;
 mod shell_request;
 mod shell_stream;
@@ -197,8 +198,9 @@ use serde::de::DeserializeOwned;
 use std::sync::Arc;
 
 pub(crate) use channel::channel;
-pub(crate) use executor::{executor_and_spawner, QueuingExecutor};
+pub(crate) use executor::{executor_and_spawner, QueuingExecutor, Spawner};
 
+use crate::ids::{Id, IdSource};
 use crate::Request;
 use channel::Sender;
 
@@ -364,6 +366,59 @@ pub trait WithContext<Ev, Ef> {
     fn new_with_context(context: ProtoContext<Ef, Ev>) -> Self;
 }
 
+/// Describes one of an app's capabilities, so that development tools can display
+/// the effects the app may request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct CapabilityInfo {
+    /// The name of the capability's variant of the app's `Effect` type, e.g. "Http"
+    pub effect: &'static str,
+    /// The name of the capability's field of the app's `Capabilities` type, e.g. "http"
+    pub field: &'static str,
+    /// The doc comment of the capability's field, or an empty string if it has none
+    pub doc: &'static str,
+    /// The fully qualified name of the capability's [`Operation`] type. The format of the
+    /// operation can be found in the shared types registry under the last segment of the name.
+    pub operation: &'static str,
+}
+
+/// Runtime introspection of an app's capabilities.
+///
+/// This is implemented automatically by `#[derive(Effect)]` for the `Capabilities` struct,
+/// skipping the capabilities marked with `#[effect(skip)]`.
+pub trait Introspect {
+    fn capabilities() -> Vec<CapabilityInfo>;
+}
+
+/// A hint to the shell about how urgently to carry out a request, e.g. a request
+/// affecting what the user sees now is more urgent than prefetching data in the background.
+///
+/// The bridge returns requests in priority order, highest first. Requests of the same
+/// priority stay in the order they were made. Use [`CapabilityContext::with_priority`]
+/// to set the priority of a capability's requests.
+///
+/// The priority only orders the requests returned together. The core doesn't hold any
+/// back, so however many more urgent requests there are, a low priority request reaches
+/// the shell in the same call as the event or response which led to it.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
 /// An interface for capabilities to interact with the app and the shell.
 ///
 /// To use [`update_app`](CapabilityContext::update_app), [`notify_shell`](CapabilityContext::notify_shell)
@@ -406,6 +461,8 @@ where
     Op: Operation,
 {
     inner: std::sync::Arc<ContextInner<Op, Event>>,
+    priority: Priority,
+    source: Option<&'static str>,
 }
 
 struct ContextInner<Op, Event>
@@ -415,6 +472,8 @@ where
     shell_channel: Sender<Request<Op>>,
     app_channel: Sender<Event>,
     spawner: executor::Spawner,
+    pending: deduplicate::Pending<Op::Output>,
+    ids: IdSource,
 }
 // ANCHOR_END: capability_context
 
@@ -423,6 +482,7 @@ pub struct ProtoContext<Eff, Event> {
     shell_channel: Sender<Eff>,
     app_channel: Sender<Event>,
     spawner: executor::Spawner,
+    ids: IdSource,
 }
 
 impl<Op, Ev> Clone for CapabilityContext<Op, Ev>
@@ -432,6 +492,8 @@ where
     fn clone(&self) -> Self {
         Self {
             inner: Arc::clone(&self.inner),
+            priority: self.priority,
+            source: self.source,
         }
     }
 }
@@ -450,9 +512,15 @@ where
             shell_channel,
             app_channel,
             spawner,
+            ids: IdSource::default(),
         }
     }
 
+    /// The id generator shared by the capabilities made from this context
+    pub(crate) fn ids(&self) -> IdSource {
+        self.ids.clone()
+    }
+
     /// Specialize the CapabilityContext to a specific capability, wrapping its operations into
     /// an Effect `Ef`. The `func` argument will typically be an Effect variant constructor, but
     /// can be any function taking the capability's operation type and returning
@@ -469,6 +537,7 @@ where
             self.shell_channel.map_input(func),
             self.app_channel.clone(),
             self.spawner.clone(),
+            self.ids.clone(),
         )
     }
 }
@@ -482,14 +551,52 @@ where
         shell_channel: Sender<Request<Op>>,
         app_channel: Sender<Ev>,
         spawner: executor::Spawner,
+        ids: IdSource,
     ) -> Self {
         let inner = Arc::new(ContextInner {
             shell_channel,
             app_channel,
             spawner,
+            pending: Default::default(),
+            ids,
         });
 
-        CapabilityContext { inner }
+        CapabilityContext {
+            inner,
+            priority: Priority::default(),
+            source: None,
+        }
+    }
+
+    /// A copy of the context, which sends all its requests to the shell with the given
+    /// [`Priority`].
+    ///
+    /// ```rust,ignore
+    /// let context = self.context.with_priority(Priority::Low);
+    /// self.context.spawn(async move {
+    ///     context.request_from_shell(HttpRequest::get(url)).await;
+    /// });
+    /// ```
+    pub fn with_priority(&self, priority: Priority) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            priority,
+            source: self.source,
+        }
+    }
+
+    /// A copy of the context, whose requests are attributed to the derived capability
+    /// named `source`, which makes them through this context's capability.
+    /// The name is available from [`Request::source`] when debugging.
+    ///
+    /// This is called by `#[derive(Effect)]` for capabilities marked with `#[effect(uses(...))]`.
+    /// You should not need to call this function directly.
+    pub fn derived_from(&self, source: &'static str) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            priority: self.priority,
+            source: Some(source),
+        }
     }
 
     /// Spawn a task to do the asynchronous work. Within the task, async code
@@ -498,6 +605,13 @@ where
         self.inner.spawner.spawn(f);
     }
 
+    /// Make a new unique id from the core's id generator, e.g. to tell the shell which
+    /// timer to cancel. Unlike a counter kept by the capability, the ids are the same each
+    /// time a test runs, see [`Ids`](crate::ids::Ids).
+    pub fn next_id(&self) -> Id {
+        self.inner.ids.next()
+    }
+
     /// Send an effect request to the shell in a fire and forget fashion. The
     /// provided `operation` does not expect anything to be returned back.
     pub async fn notify_shell(&self, operation: Op) {
@@ -505,9 +619,7 @@ where
         // it's important that it is.  It forces all capabilities to
         // spawn onto the executor which keeps the ordering of effects
         // consistent with their function calls.
-        self.inner
-            .shell_channel
-            .send(Request::resolves_never(operation));
+        self.send_request(Request::resolves_never(operation));
     }
 
     /// Send an event to the app. The event will be processed on the next
@@ -600,14 +712,25 @@ where
         F: Fn(NewEv) -> Ev + Sync + Send + 'static,
         NewEv: 'static,
     {
-        CapabilityContext::new(
-            self.inner.shell_channel.clone(),
-            self.inner.app_channel.map_input(func),
-            self.inner.spawner.clone(),
-        )
+        // requests are deduplicated with the other contexts of the capability
+        let inner = Arc::new(ContextInner {
+            shell_channel: self.inner.shell_channel.clone(),
+            app_channel: self.inner.app_channel.map_input(func),
+            spawner: self.inner.spawner.clone(),
+            pending: Arc::clone(&self.inner.pending),
+            ids: self.inner.ids.clone(),
+        });
+
+        CapabilityContext {
+            inner,
+            priority: self.priority,
+            source: self.source,
+        }
     }
 
-    pub(crate) fn send_request(&self, request: Request<Op>) {
+    pub(crate) fn send_request(&self, mut request: Request<Op>) {
+        request.priority = self.priority;
+        request.source = self.source;
         self.inner.shell_channel.send(request);
     }
 }
@@ -1,49 +1,67 @@
 //! Async support for implementing capabilities
 //!
 use std::{
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, Weak},
     task::{Poll, Waker},
 };
 
 use futures::Future;
 
-use crate::Request;
+use crate::{
+    capability::{CapabilityContext, Operation},
+    core::SharedResolve,
+    Request,
+};
 
 pub struct ShellRequest<T> {
-    shared_state: Arc<Mutex<SharedState<T>>>,
+    shared_state: Arc<dyn PollShared<T>>,
 }
 
 #[cfg(test)]
 impl ShellRequest<()> {
     pub(crate) fn new() -> Self {
+        struct Never;
+
+        impl PollShared<()> for Never {
+            fn poll(&self, _waker: &Waker) -> Poll<()> {
+                Poll::Pending
+            }
+        }
+
         Self {
-            shared_state: Arc::new(Mutex::new(SharedState {
-                result: None,
-                waker: None,
-                send_request: None,
-            })),
+            shared_state: Arc::new(Never),
         }
     }
 }
 
-struct SharedState<T> {
-    result: Option<T>,
+/// The state shared between the future and the request it sends. The request resolves it
+/// through a weak reference, and is itself kept in it until the future is first polled, so
+/// neither the callback nor the deferred send needs a separate allocation.
+struct SharedState<Op, Ev>
+where
+    Op: Operation,
+{
+    result: Option<Op::Output>,
     waker: Option<Waker>,
-    send_request: Option<Box<dyn FnOnce() + Send + 'static>>,
+    unsent: Option<(CapabilityContext<Op, Ev>, Request<Op>)>,
 }
 
-impl<T> Future for ShellRequest<T> {
-    type Output = T;
+/// The future's side of the shared state, without the operation's and event's types
+trait PollShared<T>: Send + Sync {
+    fn poll(&self, waker: &Waker) -> Poll<T>;
+}
 
-    fn poll(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<Self::Output> {
-        let mut shared_state = self.shared_state.lock().unwrap();
+impl<Op, Ev> PollShared<Op::Output> for Mutex<SharedState<Op, Ev>>
+where
+    Op: Operation,
+    Ev: 'static,
+{
+    fn poll(&self, waker: &Waker) -> Poll<Op::Output> {
+        let mut shared_state = self.lock().unwrap();
 
         // If there's still a request to send, take it and send it
-        if let Some(send_request) = shared_state.send_request.take() {
-            send_request();
+        if let Some((context, request)) = shared_state.unsent.take() {
+            context.send_request(request);
         }
 
         // If a result has been delivered, we're ready to continue
@@ -51,17 +69,50 @@ impl<T> Future for ShellRequest<T> {
         match shared_state.result.take() {
             Some(result) => Poll::Ready(result),
             None => {
-                let cloned_waker = cx.waker().clone();
-                shared_state.waker = Some(cloned_waker);
+                match &mut shared_state.waker {
+                    Some(current) if current.will_wake(waker) => {}
+                    current => *current = Some(waker.clone()),
+                }
                 Poll::Pending
             }
         }
     }
 }
 
-impl<Op, Ev> crate::capability::CapabilityContext<Op, Ev>
+// used in docs/internals/runtime.md
+// ANCHOR: resolve
+impl<Op, Ev> SharedResolve<Op::Output> for Mutex<SharedState<Op, Ev>>
 where
-    Op: crate::capability::Operation,
+    Op: Operation,
+    Ev: 'static,
+{
+    fn resolve(&self, result: Op::Output) {
+        let mut shared_state = self.lock().unwrap();
+
+        // Attach the result to the shared state of the future
+        shared_state.result = Some(result);
+        // Signal the executor to wake the task holding this future
+        if let Some(waker) = shared_state.waker.take() {
+            waker.wake()
+        }
+    }
+}
+// ANCHOR_END: resolve
+
+impl<T> Future for ShellRequest<T> {
+    type Output = T;
+
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Self::Output> {
+        self.shared_state.poll(cx.waker())
+    }
+}
+
+impl<Op, Ev> CapabilityContext<Op, Ev>
+where
+    Op: Operation,
     Ev: 'static,
 {
     /// Send an effect request to the shell, expecting an output. The
@@ -73,41 +124,18 @@ where
     /// `await`ed. You should only call this method inside an async task
     /// created with [`CapabilityContext::spawn`](crate::capability::CapabilityContext::spawn).
     pub fn request_from_shell(&self, operation: Op) -> ShellRequest<Op::Output> {
-        let shared_state = Arc::new(Mutex::new(SharedState {
-            result: None,
-            waker: None,
-            send_request: None,
-        }));
-
-        // Our callback holds a weak pointer to avoid circular references
-        // from shared_state -> send_request -> request -> shared_state
-        let callback_shared_state = Arc::downgrade(&shared_state);
-
-        // used in docs/internals/runtime.md
-        // ANCHOR: resolve
-        let request = Request::resolves_once(operation, move |result| {
-            let Some(shared_state) = callback_shared_state.upgrade() else {
-                // The ShellRequest was dropped before we were called, so just
-                // do nothing.
-                return;
-            };
-
-            let mut shared_state = shared_state.lock().unwrap();
-
-            // Attach the result to the shared state of the future
-            shared_state.result = Some(result);
-            // Signal the executor to wake the task holding this future
-            if let Some(waker) = shared_state.waker.take() {
-                waker.wake()
-            }
-        });
-        // ANCHOR_END: resolve
-
-        // Send the request on the next poll of the ShellRequest future
-        let send_req_context = self.clone();
-        let send_request = move || send_req_context.send_request(request);
+        // The request only holds a weak pointer to avoid circular references
+        // from shared_state -> request -> shared_state
+        let shared_state = Arc::new_cyclic(|shared_state: &Weak<Mutex<SharedState<Op, Ev>>>| {
+            let resolve: Weak<dyn SharedResolve<Op::Output>> = shared_state.clone();
 
-        shared_state.lock().unwrap().send_request = Some(Box::new(send_request));
+            // Send the request on the next poll of the ShellRequest future
+            Mutex::new(SharedState {
+                result: None,
+                waker: None,
+                unsent: Some((self.clone(), Request::resolves_once(operation, resolve))),
+            })
+        });
 
         ShellRequest { shared_state }
     }
@@ -131,8 +159,12 @@ mod tests {
         let (request_sender, requests) = channel();
         let (event_sender, events) = channel::<()>();
         let (executor, spawner) = executor_and_spawner();
-        let capability_context =
-            CapabilityContext::new(request_sender, event_sender.clone(), spawner.clone());
+        let capability_context = CapabilityContext::new(
+            request_sender,
+            event_sender.clone(),
+            spawner.clone(),
+            Default::default(),
+        );
 
         let future = capability_context.request_from_shell(TestOperation);
 
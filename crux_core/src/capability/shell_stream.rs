@@ -107,8 +107,12 @@ mod tests {
         let (request_sender, requests) = channel();
         let (event_sender, events) = channel::<()>();
         let (executor, spawner) = executor_and_spawner();
-        let capability_context =
-            CapabilityContext::new(request_sender, event_sender.clone(), spawner.clone());
+        let capability_context = CapabilityContext::new(
+            request_sender,
+            event_sender.clone(),
+            spawner.clone(),
+            Default::default(),
+        );
 
         let mut stream = capability_context.stream_from_shell(TestOperation);
 
@@ -0,0 +1,61 @@
+//! Tagging effect requests with the event which caused them, for development tools
+//!
+//! With the `devtools` feature, the core numbers the events it passes to the app's `update`
+//! function, and every request made while handling an event, or by a task spawned while
+//! handling it, carries a [`Cause`] naming that event. Shells can read it from
+//! [`Request::cause`](crate::Request::cause), or, through the bridge, from the `causes` of the
+//! [`Envelope`](crate::bridge::Envelope) the requests come back in, and show which user action
+//! led to an effect when they log it.
+//!
+//! Without the feature, requests have no cause, and none of this is compiled in.
+
+use serde::{Deserialize, Serialize};
+
+/// The event whose `update` made a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cause {
+    /// The number of the event, counting the events the core has processed, from 1
+    pub event_id: u64,
+    /// The name of the event, as described by [`App::debug_event`](crate::App::debug_event)
+    pub event: Option<String>,
+}
+
+#[cfg(feature = "devtools")]
+pub(crate) use tagging::{caused, current, with};
+
+#[cfg(feature = "devtools")]
+mod tagging {
+    use std::cell::RefCell;
+
+    use futures::{future::poll_fn, Future};
+
+    use super::Cause;
+
+    thread_local! {
+        /// The cause of the work the core is doing on this thread
+        static CURRENT: RefCell<Option<Cause>> = const { RefCell::new(None) };
+    }
+
+    /// The cause of the work being done on this thread, if any
+    pub(crate) fn current() -> Option<Cause> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Run `f` with `cause` as the current cause, restoring the previous one afterwards
+    pub(crate) fn with<T>(cause: Option<Cause>, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT.with(|current| current.replace(cause));
+        let result = f();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+
+        result
+    }
+
+    /// A task which keeps the cause it was spawned with, whenever it's polled
+    pub(crate) async fn caused<F>(cause: Option<Cause>, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        futures::pin_mut!(future);
+        poll_fn(|cx| with(cause.clone(), || future.as_mut().poll(cx))).await
+    }
+}
@@ -1,6 +1,8 @@
 use serde::Serialize;
 
 use crate::bridge::ResolveSerialized;
+use crate::capability::Priority;
+use crate::cause::Cause;
 
 /// Implemented automatically with the Effect macro from `crux_macros`.
 /// This is used by the [`Bridge`](crate::bridge::Bridge) to serialize effects going across the
@@ -19,5 +21,29 @@ pub trait Effect: Send + 'static {
     /// You should not need to call this method directly. It is called by
     /// the [`Bridge`](crate::bridge::Bridge)
     fn serialize(self) -> (Self::Ffi, ResolveSerialized);
+
+    /// The [`Priority`] of the request the effect is carrying.
+    fn priority(&self) -> Priority {
+        Priority::default()
+    }
+
+    /// The [`Cause`] of the request the effect is carrying, with the `devtools` feature.
+    fn cause(&self) -> Option<&Cause> {
+        None
+    }
+
+    /// Whether the effect is a request to render, from the [`Render`](crate::render::Render)
+    /// capability. [`Core::process_events`](crate::Core::process_events) merges the renders
+    /// of the events it processes into one.
+    fn is_render(&self) -> bool {
+        false
+    }
+
+    /// The name of the variant of the effect, and so of the capability which requested it,
+    /// e.g. `"Http"`. The core counts the effects requested with each name in its
+    /// [`Metrics`](crate::metrics::Metrics).
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
 }
 // ANCHOR_END: effect
@@ -2,16 +2,27 @@ mod effect;
 mod request;
 mod resolve;
 
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::RwLock;
 
 pub use effect::Effect;
 pub use request::Request;
 pub use resolve::ResolveError;
 
-pub(crate) use resolve::Resolve;
+pub(crate) use resolve::{Resolve, SharedResolve};
 
-use crate::capability::{self, channel::Receiver, Operation, ProtoContext, QueuingExecutor};
-use crate::{App, WithContext};
+use crate::async_update::{self, AsyncUpdate, ModelHandle, ModelUpdates};
+use crate::capability::{
+    self, channel::Receiver, CapabilityInfo, Introspect, Operation, ProtoContext, QueuingExecutor,
+    Spawner,
+};
+use crate::ids::IdSource;
+use crate::metrics::{Metrics, Recorder};
+use crate::{init::Init, App, WithContext};
+
+/// The most events the capabilities can send to the app for a single message from the shell,
+/// unless set with [`Core::with_max_events`]
+const DEFAULT_MAX_EVENTS: usize = 10_000;
 
 /// The Crux core. Create an instance of this type with your effect type, and your app type as type parameters
 ///
@@ -21,6 +32,11 @@ use crate::{App, WithContext};
 ///
 /// The result of the capability's work can then be sent back to the core using [`Core::resolve`], passing
 /// in the request and the corresponding capability output type.
+///
+/// The core is `Send` and `Sync` as long as the app, its model and capabilities are, so it can be
+/// shared between threads (e.g. in an `Arc`) and called from a shell's background threads.
+/// Effects are returned from whichever call drains them first, so when several threads call into
+/// the core concurrently, an effect caused by one thread's event may be returned to another thread.
 // used in docs/internals/runtime.md
 // ANCHOR: core
 pub struct Core<Ef, A>
@@ -40,7 +56,17 @@ where
     // internals
     requests: Receiver<Ef>,
     capability_events: Receiver<A::Event>,
+    model_updates: ModelUpdates<A::Model>,
+    model_handle: ModelHandle<A::Model>,
     executor: QueuingExecutor,
+    spawner: Spawner,
+    ids: IdSource,
+    metrics: Recorder,
+    max_events: usize,
+    /// The events sent by capabilities since the current message from the shell
+    message_events: AtomicUsize,
+    #[cfg(feature = "devtools")]
+    events: std::sync::atomic::AtomicU64,
 }
 // ANCHOR_END: core
 
@@ -61,34 +87,116 @@ where
     {
         let (request_sender, request_receiver) = capability::channel();
         let (event_sender, event_receiver) = capability::channel();
+        let (model_handle, model_updates) = ModelHandle::new();
         let (executor, spawner) = capability::executor_and_spawner();
-        let capability_context = ProtoContext::new(request_sender, event_sender, spawner);
+        let capability_context = ProtoContext::new(request_sender, event_sender, spawner.clone());
+        let ids = capability_context.ids();
 
         Self {
             model: Default::default(),
             executor,
+            spawner,
             app: Default::default(),
             capabilities: <<A as App>::Capabilities>::new_with_context(capability_context),
             requests: request_receiver,
             capability_events: event_receiver,
+            model_updates,
+            model_handle,
+            ids,
+            metrics: Recorder::default(),
+            max_events: DEFAULT_MAX_EVENTS,
+            message_events: AtomicUsize::new(0),
+            #[cfg(feature = "devtools")]
+            events: Default::default(),
         }
     }
 
+    /// Seed the core's id generator, which is seeded randomly otherwise, so that the app and
+    /// its capabilities make the same ids as in an earlier session with the same `seed`,
+    /// e.g. to replay its events. See [`Ids`](crate::ids::Ids).
+    #[must_use]
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.ids.reseed(seed);
+        self
+    }
+
+    /// Process at most `max_events` events sent to the app by its capabilities, e.g. with
+    /// [`Dispatch`](crate::dispatch::Dispatch), for each message from the shell, 10,000 by default.
+    ///
+    /// This guards against apps dispatching events to themselves in an endless loop. Once the
+    /// limit is reached, the core drops the events sent until the next message from the shell,
+    /// and counts them in [`Metrics::dropped_events`].
+    #[must_use]
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.max_events = max_events;
+        self
+    }
+
+    /// Replace the model with the one created by the app's `init` function from the
+    /// startup `config`. Shells should call this once, before the first event, as any
+    /// state the app built up so far is discarded.
+    pub fn init(&self, config: Init) {
+        let model = self.app.init(config);
+
+        *self.model.write().expect("Model RwLock was poisoned.") = model;
+    }
+
     /// Run the app's `update` function with a given `event`, returning a vector of
     /// effect requests.
     // used in docs/internals/runtime.md
     // ANCHOR: process_event
     pub fn process_event(&self, event: A::Event) -> Vec<Ef> {
-        let mut model = self.model.write().expect("Model RwLock was poisoned.");
+        self.start_message();
 
-        self.app.update(event, &mut model, &self.capabilities);
+        self.handle_event(event)
+    }
+    // ANCHOR_END: process_event
 
-        // drop the model here, we don't want to hold the lock for the process() call
-        drop(model);
+    /// Start counting the events sent by capabilities afresh, for a new message from the shell
+    pub(crate) fn start_message(&self) {
+        self.message_events.store(0, Ordering::Relaxed);
+    }
+
+    /// [`Core::process_event`], as part of the current message from the shell
+    pub(crate) fn handle_event(&self, event: A::Event) -> Vec<Ef> {
+        self.update(event);
 
         self.process()
     }
-    // ANCHOR_END: process_event
+
+    /// Run the app's `update` function with each of the `events` in turn, returning the
+    /// effect requests of all of them together, e.g. when the shell replays interactions
+    /// queued while offline, or coalesces rapid text input.
+    ///
+    /// The events are processed in order: each event's `update`, and any events the
+    /// capabilities send back to the app while handling it, run before the next event's.
+    /// The effects are returned in the order they were requested, except that renders are
+    /// merged into the last of them, so the shell updates its view once for the batch.
+    pub fn process_events(&self, events: impl IntoIterator<Item = A::Event>) -> Vec<Ef> {
+        self.start_message();
+
+        self.handle_events(events)
+    }
+
+    /// [`Core::process_events`], as part of the current message from the shell
+    pub(crate) fn handle_events(&self, events: impl IntoIterator<Item = A::Event>) -> Vec<Ef> {
+        for event in events {
+            self.update(event);
+            self.settle();
+        }
+
+        let mut effects: Vec<Ef> = self.requests.drain().collect();
+        if let Some(last) = effects.iter().rposition(Ef::is_render) {
+            let mut index = 0;
+            effects.retain(|effect| {
+                let keep = index == last || !effect.is_render();
+                index += 1;
+                keep
+            });
+        }
+
+        self.record_effects(effects)
+    }
 
     /// Resolve an effect `request` for operation `Op` with the corresponding result.
     ///
@@ -106,6 +214,7 @@ where
         let resolve_result = request.resolve(result);
         debug_assert!(resolve_result.is_ok());
 
+        self.start_message();
         self.process()
     }
     // ANCHOR_END: resolve
@@ -113,26 +222,130 @@ where
     // used in docs/internals/runtime.md
     // ANCHOR: process
     pub(crate) fn process(&self) -> Vec<Ef> {
+        self.settle();
+
+        self.record_effects(self.requests.drain().collect())
+    }
+
+    /// Run the spawned tasks, and the app's `update` with the events they send, until
+    /// there's no more work to do, or the capabilities sent too many events for the current
+    /// message from the shell
+    fn settle(&self) {
         self.executor.run_all();
 
-        while let Some(capability_event) = self.capability_events.receive() {
+        loop {
+            // tasks waiting for the model go first, so they see it as it was when they asked
             let mut model = self.model.write().expect("Model RwLock was poisoned.");
-            self.app
-                .update(capability_event, &mut model, &self.capabilities);
+            let updated = async_update::apply_updates(&self.model_updates, &mut model);
             drop(model);
+            if updated {
+                self.executor.run_all();
+                continue;
+            }
+
+            let Some(capability_event) = self.capability_events.receive() else {
+                break;
+            };
+            if self.message_events.fetch_add(1, Ordering::Relaxed) >= self.max_events {
+                // most likely the app is dispatching events in a loop
+                let dropped = 1 + self.capability_events.drain().count();
+                self.metrics.dropped_events(dropped);
+                break;
+            }
+
+            self.update(capability_event);
             self.executor.run_all();
         }
-
-        self.requests.drain().collect()
     }
     // ANCHOR_END: process
 
+    fn record_effects(&self, effects: Vec<Ef>) -> Vec<Ef> {
+        for effect in &effects {
+            self.metrics.effect(effect.name());
+        }
+
+        effects
+    }
+
+    /// Run the app's `async_update` function with the event, then its `update` function if
+    /// `async_update` doesn't handle it. Tasks are spawned, to run in the next `process()`.
+    fn update(&self, event: A::Event) {
+        #[cfg(feature = "devtools")]
+        {
+            let cause = crate::cause::Cause {
+                event_id: self
+                    .events
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+                    + 1,
+                event: self.app.debug_event(&event),
+            };
+            crate::cause::with(Some(cause), || self.run_update(event));
+        }
+        #[cfg(not(feature = "devtools"))]
+        self.run_update(event);
+    }
+
+    fn run_update(&self, event: A::Event) {
+        self.metrics.update(|| {
+            let event =
+                match self
+                    .app
+                    .async_update(event, self.model_handle.clone(), &self.capabilities)
+                {
+                    AsyncUpdate::Spawn(task) => return self.spawner.spawn(task),
+                    AsyncUpdate::Update(event) => event,
+                };
+
+            let mut model = self.model.write().expect("Model RwLock was poisoned.");
+            self.app.update(event, &mut model, &self.capabilities);
+        });
+    }
+
     /// Get the current state of the app's view model.
     pub fn view(&self) -> A::ViewModel {
         let model = self.model.read().expect("Model RwLock was poisoned.");
 
         self.app.view(&model)
     }
+
+    /// Get the current state of the model, as described by [`App::debug_model`], for
+    /// display in development tools.
+    #[cfg(feature = "devtools")]
+    pub fn debug_model(&self) -> serde_json::Value {
+        let model = self.model.read().expect("Model RwLock was poisoned.");
+
+        self.app.debug_model(&model)
+    }
+
+    /// The counters of the work the core has done since it was created, for monitoring.
+    /// See [`Metrics`].
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.metrics()
+    }
+
+    pub(crate) fn record_bytes_out(&self, bytes: usize) {
+        self.metrics.bytes_out(bytes);
+    }
+
+    pub(crate) fn record_payload(&self, bytes: usize, limit: usize) {
+        self.metrics.payload(bytes, limit);
+    }
+
+    pub(crate) fn record_pending_effects(&self, pending: usize) {
+        self.metrics.pending_effects(pending);
+    }
+
+    pub(crate) fn record_deferred_effects(&self, deferred: usize) {
+        self.metrics.deferred_effects(deferred);
+    }
+
+    /// Describe the capabilities of the app, e.g. for display in development tools.
+    pub fn capabilities(&self) -> Vec<CapabilityInfo>
+    where
+        A::Capabilities: Introspect,
+    {
+        A::Capabilities::capabilities()
+    }
 }
 
 impl<Ef, A> Default for Core<Ef, A>
@@ -1,8 +1,12 @@
-use std::fmt::{self, Debug};
+use std::{
+    fmt::{self, Debug},
+    sync::Weak,
+};
 
 use crate::{
-    capability::Operation,
-    core::resolve::{Resolve, ResolveError},
+    capability::{Operation, Priority},
+    cause::Cause,
+    core::resolve::{Resolve, ResolveError, SharedResolve},
 };
 
 /// Request represents an effect request from the core to the shell.
@@ -18,6 +22,10 @@ where
 {
     pub operation: Op,
     pub(crate) resolve: Resolve<Op::Output>,
+    pub(crate) priority: Priority,
+    pub(crate) source: Option<&'static str>,
+    #[cfg(feature = "devtools")]
+    pub(crate) cause: Option<Cause>,
 }
 
 impl<Op> Request<Op>
@@ -28,16 +36,21 @@ where
         Self {
             operation,
             resolve: Resolve::Never,
+            priority: Priority::default(),
+            source: None,
+            #[cfg(feature = "devtools")]
+            cause: crate::cause::current(),
         }
     }
 
-    pub(crate) fn resolves_once<F>(operation: Op, resolve: F) -> Self
-    where
-        F: FnOnce(Op::Output) + Send + 'static,
-    {
+    pub(crate) fn resolves_once(operation: Op, state: Weak<dyn SharedResolve<Op::Output>>) -> Self {
         Self {
             operation,
-            resolve: Resolve::Once(Box::new(resolve)),
+            resolve: Resolve::Once(state),
+            priority: Priority::default(),
+            source: None,
+            #[cfg(feature = "devtools")]
+            cause: crate::cause::current(),
         }
     }
 
@@ -48,9 +61,39 @@ where
         Self {
             operation,
             resolve: Resolve::Many(Box::new(resolve)),
+            priority: Priority::default(),
+            source: None,
+            #[cfg(feature = "devtools")]
+            cause: crate::cause::current(),
         }
     }
 
+    /// The priority the capability requested the shell carries out the request with.
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Whether the request is a [`RenderOperation`](crate::render::RenderOperation), from the
+    /// [`Render`](crate::render::Render) capability.
+    pub fn is_render(&self) -> bool {
+        std::any::TypeId::of::<Op>() == std::any::TypeId::of::<crate::render::RenderOperation>()
+    }
+
+    /// The name of the derived capability which made the request through the capability
+    /// it belongs to, if any. See [`CapabilityContext::derived_from`](crate::capability::CapabilityContext::derived_from).
+    pub fn source(&self) -> Option<&'static str> {
+        self.source
+    }
+
+    /// The event whose `update` made the request, with the `devtools` feature. See
+    /// [`cause`](crate::cause).
+    pub fn cause(&self) -> Option<&Cause> {
+        #[cfg(feature = "devtools")]
+        return self.cause.as_ref();
+        #[cfg(not(feature = "devtools"))]
+        None
+    }
+
     pub(crate) fn resolve(&mut self, output: Op::Output) -> Result<(), ResolveError> {
         self.resolve.resolve(output)
     }
@@ -61,6 +104,13 @@ where
     Op: Operation + Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("Request").field(&self.operation).finish()
+        match self.source {
+            Some(source) => f
+                .debug_struct("Request")
+                .field("operation", &self.operation)
+                .field("source", &source)
+                .finish(),
+            None => f.debug_tuple("Request").field(&self.operation).finish(),
+        }
     }
 }
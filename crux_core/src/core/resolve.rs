@@ -1,8 +1,10 @@
+use std::sync::Weak;
+
 use thiserror::Error;
 
 // used in docs/internals/runtime.md
 // ANCHOR: resolve
-type ResolveOnce<Out> = Box<dyn FnOnce(Out) + Send>;
+type ResolveOnce<Out> = Weak<dyn SharedResolve<Out>>;
 type ResolveMany<Out> = Box<dyn Fn(Out) -> Result<(), ()> + Send>;
 
 /// Resolve is a callback used to resolve an effect request and continue
@@ -14,6 +16,15 @@ pub(crate) enum Resolve<Out> {
 }
 // ANCHOR_END: resolve
 
+/// The state of a future waiting for the output of a request, which a request resolving
+/// once resolves directly, rather than through a boxed callback, since requests are made
+/// at a high rate, e.g. for a timer set again on every frame. The request only holds a
+/// weak reference, so a future which was dropped isn't kept alive, and its output is
+/// discarded.
+pub(crate) trait SharedResolve<Out>: Send + Sync {
+    fn resolve(&self, output: Out);
+}
+
 impl<Out> Resolve<Out> {
     pub fn resolve(&mut self, output: Out) -> Result<(), ResolveError> {
         match self {
@@ -21,8 +32,10 @@ impl<Out> Resolve<Out> {
             Resolve::Many(f) => f(output).map_err(|_| ResolveError::FinishedMany),
             Resolve::Once(_) => {
                 // The resolve has been used, turn it into a Never
-                if let Resolve::Once(f) = std::mem::replace(self, Resolve::Never) {
-                    f(output);
+                if let Resolve::Once(state) = std::mem::replace(self, Resolve::Never) {
+                    if let Some(state) = state.upgrade() {
+                        state.resolve(output);
+                    }
                 }
 
                 Ok(())
@@ -37,4 +50,6 @@ pub enum ResolveError {
     Never,
     #[error("Attempted to resolve a request that has concluded.")]
     FinishedMany,
+    #[error("Attempted to resolve a request with output that could not be deserialized.")]
+    Deserialization,
 }
@@ -0,0 +1,38 @@
+//! Startup configuration passed from the shell
+//!
+//! Before sending the first event, a shell can pass an [`Init`] to
+//! [`Bridge::init`](crate::bridge::Bridge::init) (or [`Core::init`](crate::Core::init)),
+//! which the core hands to [`App::init`](crate::App::init) to create the initial model.
+//! Shells using it generate the type with `TypeGen::register_init`, so every shell sends the
+//! same structure.
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration the shell knows at startup, and the app needs to set up its model
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Init {
+    /// The base URL of the API the app talks to, if the shell decides it, e.g. per build flavour
+    pub api_base_url: Option<String>,
+    /// The names of the enabled feature flags
+    pub feature_flags: Vec<String>,
+    /// The user's locale as a BCP 47 language tag, e.g. `en-GB`
+    pub locale: Option<String>,
+    /// The platform the shell is running on
+    pub platform: PlatformInfo,
+}
+
+impl Init {
+    /// Whether the feature flag called `flag` is enabled
+    pub fn has_feature(&self, flag: &str) -> bool {
+        self.feature_flags.iter().any(|enabled| enabled == flag)
+    }
+}
+
+/// The platform a shell is running on
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlatformInfo {
+    /// The name of the platform, e.g. `iOS`, `Android` or `Web`
+    pub name: String,
+    /// The version of the platform, e.g. `17.4`
+    pub version: String,
+}
@@ -149,17 +149,25 @@
 //! See [typegen] for details.
 //!
 
+pub mod async_update;
 pub mod bridge;
 pub mod capability;
+pub mod cause;
+pub mod init;
+pub mod metrics;
 pub mod testing;
 #[cfg(feature = "typegen")]
 pub mod typegen;
+pub mod view_slice;
+pub mod viewmodel;
 
 mod capabilities;
 mod core;
 
 use serde::Serialize;
 
+use async_update::{AsyncUpdate, ModelHandle};
+
 pub use self::{
     capabilities::*,
     capability::{Capability, WithContext},
@@ -167,6 +175,37 @@ pub use self::{
 };
 pub use crux_macros as macros;
 
+/// The version of this crate. Tools such as the `crux` CLI compare it with the version of
+/// crux_core the app uses, because the bridge and the generated shell code have to match.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+macro_rules! typegen_extensions {
+    ($($path:literal,)*) => {
+        &[$(($path, include_str!(concat!("../typegen_extensions/", $path))),)*]
+    };
+}
+
+/// The built-in extensions the type generation adds to the generated code, as their paths
+/// relative to the extensions directory and their contents, e.g. for the `crux` CLI to write
+/// them out as a starting point for overrides. See `TypeGen::extensions_dir`.
+pub const TYPEGEN_EXTENSIONS: &[(&str, &str)] = typegen_extensions!(
+    "java/Requests.java",
+    "swift/Package.swift",
+    "swift/requests.swift",
+    "typescript/bincode/bincodeDeserializer.ts",
+    "typescript/bincode/bincodeSerializer.ts",
+    "typescript/bincode/mod.ts",
+    "typescript/package.json",
+    "typescript/pnpm-lock.yaml",
+    "typescript/serde/binaryDeserializer.ts",
+    "typescript/serde/binarySerializer.ts",
+    "typescript/serde/deserializer.ts",
+    "typescript/serde/mod.ts",
+    "typescript/serde/serializer.ts",
+    "typescript/serde/types.ts",
+    "typescript/tsconfig.json",
+);
+
 /// Implement [`App`] on your type to make it into a Crux app. Use your type implementing [`App`]
 /// as the type argument to [`Core`] or [`Bridge`](bridge::Bridge).
 pub trait App: Default {
@@ -192,6 +231,74 @@ pub trait App: Default {
     /// Typically, `update` should call at least [`Render::render`](crate::render::Render::render).
     fn update(&self, event: Self::Event, model: &mut Self::Model, caps: &Self::Capabilities);
 
+    /// Asynchronous alternative to [`update`](App::update), for events which are easier to handle
+    /// by awaiting the results of capability calls than with callback events.
+    ///
+    /// Return [`AsyncUpdate::spawn`] with a task for the events handled this way, and
+    /// [`AsyncUpdate::Update`] with the event for the others, which are then passed to `update`.
+    /// The task runs on the core's executor, so awaiting a capability's async API suspends it
+    /// until the shell resolves the request, and it reads and changes the model with the given
+    /// [`ModelHandle`]. The default passes every event on to `update`.
+    ///
+    /// ```rust,ignore
+    /// fn async_update(
+    ///     &self,
+    ///     event: Event,
+    ///     model: ModelHandle<Model>,
+    ///     caps: &Capabilities,
+    /// ) -> AsyncUpdate<Event> {
+    ///     match event {
+    ///         Event::Refresh => {
+    ///             let (http, render) = (caps.http.clone(), caps.render.clone());
+    ///             AsyncUpdate::spawn(async move {
+    ///                 let response = http.get(API_URL).send_async().await;
+    ///                 model.update(move |model| model.response = response).await;
+    ///                 render.render();
+    ///             })
+    ///         }
+    ///         event => AsyncUpdate::Update(event),
+    ///     }
+    /// }
+    /// ```
+    fn async_update(
+        &self,
+        event: Self::Event,
+        model: ModelHandle<Self::Model>,
+        caps: &Self::Capabilities,
+    ) -> AsyncUpdate<Self::Event> {
+        let _ = (model, caps);
+        AsyncUpdate::Update(event)
+    }
+
     /// View method is used by the Shell to request the current state of the user interface
     fn view(&self, model: &Self::Model) -> Self::ViewModel;
+
+    /// Create the initial model from the startup configuration passed by the Shell with
+    /// [`Core::init`], before the first event. The default ignores the configuration and
+    /// uses the default model.
+    fn init(&self, config: init::Init) -> Self::Model {
+        let _ = config;
+        Self::Model::default()
+    }
+
+    /// Describe the `model` for display in development tools, e.g. with `serde_json::to_value`
+    /// if the model is serializable. The default shows nothing.
+    ///
+    /// Only available with the `devtools` feature, so that release builds don't include it.
+    #[cfg(feature = "devtools")]
+    fn debug_model(&self, model: &Self::Model) -> serde_json::Value {
+        let _ = model;
+        serde_json::Value::Null
+    }
+
+    /// Name the `event` for display in development tools, e.g. with `format!("{event:?}")`.
+    /// The name is part of the [`Cause`](cause::Cause) of the requests made while handling
+    /// the event. The default doesn't name it.
+    ///
+    /// Only available with the `devtools` feature, so that release builds don't include it.
+    #[cfg(feature = "devtools")]
+    fn debug_event(&self, event: &Self::Event) -> Option<String> {
+        let _ = event;
+        None
+    }
 }
@@ -0,0 +1,189 @@
+//! Counters describing the work the core has done, for monitoring its health in production
+//!
+//! The core counts the events it processes, how long the app's `update` function takes and the
+//! effects requested from each capability, and the [`Bridge`](crate::bridge::Bridge) adds up the
+//! bytes it serializes for the shell, and the payloads close to or over its
+//! [`Limits`](crate::bridge::Limits), and how many effects are waiting for a response, or to
+//! be sent. Shells can read the counters with `Bridge::metrics` and
+//! report them to their monitoring tools, without any instrumentation in the app. Shells can
+//! generate the [`Metrics`] type with `TypeGen::register_metrics`.
+
+use std::{collections::BTreeMap, sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+/// The core's counters, since it was created
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metrics {
+    /// The events the app's `update` function was called with, from the shell and from capabilities
+    pub events_processed: u64,
+    /// The effects requested from each capability, by the name of their `Effect` variant
+    pub effects_emitted: BTreeMap<String, u64>,
+    /// The average time the app's `update` function took, in microseconds
+    pub average_update_micros: u64,
+    /// The bytes of serialized requests and views the bridge returned to the shell
+    pub bytes_out: u64,
+    /// The payloads in either direction within the bridge's limits, but over 80% of them
+    pub near_limit_payloads: u64,
+    /// The payloads in either direction the bridge refused for being over its limits
+    pub oversized_payloads: u64,
+    /// The effects the bridge has sent to the shell, which are waiting for a response
+    pub pending_effects: u64,
+    /// The most effects which have been waiting for a response at once
+    pub peak_pending_effects: u64,
+    /// The effects waiting in the bridge to be sent to the shell, past its maximum of
+    /// pending effects, see [`Bridge::with_max_pending`](crate::bridge::Bridge::with_max_pending)
+    pub deferred_effects: u64,
+    /// The events sent to the app by capabilities which the core dropped, past its limit
+    /// for a single message from the shell, see [`Core::with_max_events`](crate::Core::with_max_events)
+    pub dropped_events: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    events: u64,
+    update_time: Duration,
+    effects: BTreeMap<&'static str, u64>,
+    bytes_out: u64,
+    near_limit: u64,
+    oversized: u64,
+    pending_effects: u64,
+    peak_pending_effects: u64,
+    deferred_effects: u64,
+    dropped_events: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct Recorder {
+    counters: Mutex<Counters>,
+}
+
+impl Recorder {
+    /// Run the app's `update` function, counting the event and timing it
+    pub(crate) fn update<T>(&self, update: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = update();
+        let elapsed = start.elapsed();
+
+        let mut counters = self.lock();
+        counters.events += 1;
+        counters.update_time += elapsed;
+
+        result
+    }
+
+    pub(crate) fn effect(&self, name: &'static str) {
+        *self.lock().effects.entry(name).or_default() += 1;
+    }
+
+    pub(crate) fn bytes_out(&self, bytes: usize) {
+        self.lock().bytes_out += bytes as u64;
+    }
+
+    /// Count a payload of `bytes` checked against a `limit`, if it's close to or over it
+    pub(crate) fn payload(&self, bytes: usize, limit: usize) {
+        if bytes > limit {
+            self.lock().oversized += 1;
+        } else if bytes as u128 * 5 > limit as u128 * 4 {
+            self.lock().near_limit += 1;
+        }
+    }
+
+    pub(crate) fn pending_effects(&self, pending: usize) {
+        let mut counters = self.lock();
+        counters.pending_effects = pending as u64;
+        counters.peak_pending_effects = counters.peak_pending_effects.max(pending as u64);
+    }
+
+    pub(crate) fn deferred_effects(&self, deferred: usize) {
+        self.lock().deferred_effects = deferred as u64;
+    }
+
+    pub(crate) fn dropped_events(&self, dropped: usize) {
+        self.lock().dropped_events += dropped as u64;
+    }
+
+    pub(crate) fn metrics(&self) -> Metrics {
+        let counters = self.lock();
+
+        let average_update = match u32::try_from(counters.events) {
+            Ok(0) => Duration::ZERO,
+            Ok(events) => counters.update_time / events,
+            Err(_) => {
+                Duration::from_secs_f64(counters.update_time.as_secs_f64() / counters.events as f64)
+            }
+        };
+
+        Metrics {
+            events_processed: counters.events,
+            effects_emitted: counters
+                .effects
+                .iter()
+                .map(|(name, count)| (name.to_string(), *count))
+                .collect(),
+            average_update_micros: u64::try_from(average_update.as_micros()).unwrap_or(u64::MAX),
+            bytes_out: counters.bytes_out,
+            near_limit_payloads: counters.near_limit,
+            oversized_payloads: counters.oversized,
+            pending_effects: counters.pending_effects,
+            peak_pending_effects: counters.peak_pending_effects,
+            deferred_effects: counters.deferred_effects,
+            dropped_events: counters.dropped_events,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Counters> {
+        self.counters.lock().expect("Metrics Mutex was poisoned.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_update_time() {
+        let recorder = Recorder::default();
+        assert_eq!(recorder.metrics(), Metrics::default());
+
+        recorder.update(|| std::thread::sleep(Duration::from_millis(2)));
+        recorder.update(|| ());
+
+        let metrics = recorder.metrics();
+        assert_eq!(metrics.events_processed, 2);
+        assert!(metrics.average_update_micros >= 1000);
+    }
+
+    #[test]
+    fn effects_by_capability() {
+        let recorder = Recorder::default();
+
+        recorder.effect("Http");
+        recorder.effect("Render");
+        recorder.effect("Http");
+        recorder.bytes_out(10);
+        recorder.bytes_out(5);
+
+        let metrics = recorder.metrics();
+        assert_eq!(
+            metrics.effects_emitted,
+            BTreeMap::from([("Http".to_string(), 2), ("Render".to_string(), 1)])
+        );
+        assert_eq!(metrics.bytes_out, 15);
+    }
+
+    #[test]
+    fn payloads_near_and_over_the_limit() {
+        let recorder = Recorder::default();
+
+        recorder.payload(80, 100);
+        recorder.payload(81, 100);
+        recorder.payload(100, 100);
+        recorder.payload(101, 100);
+
+        let metrics = recorder.metrics();
+        assert_eq!(metrics.near_limit_payloads, 2);
+        assert_eq!(metrics.oversized_payloads, 1);
+    }
+}
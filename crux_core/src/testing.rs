@@ -1,11 +1,18 @@
 //! Testing support for unit testing Crux apps.
 use anyhow::Result;
-use std::{collections::VecDeque, sync::Arc};
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    fmt::Write as _,
+    sync::{Arc, Mutex},
+};
 
 use crate::{
+    async_update::{self, AsyncUpdate, ModelHandle, ModelUpdates},
     capability::{
-        channel::Receiver, executor_and_spawner, Operation, ProtoContext, QueuingExecutor,
+        channel::Receiver, executor_and_spawner, Operation, ProtoContext, QueuingExecutor, Spawner,
     },
+    ids::IdSource,
     Request, WithContext,
 };
 
@@ -26,13 +33,25 @@ where
 {
     app: App,
     capabilities: App::Capabilities,
-    context: Arc<AppContext<Ef, App::Event>>,
+    context: Arc<AppContext<Ef, App::Event, App::Model>>,
+    ids: IdSource,
+    model_handle: ModelHandle<App::Model>,
+    history: Option<History<App::Model>>,
+}
+
+/// The model before and after each event, kept by an [`AppTester`] created
+/// [`with_history`](AppTester::with_history)
+struct History<Model> {
+    snapshot: fn(&Model) -> Model,
+    transitions: Mutex<Vec<(Model, Model)>>,
 }
 
-struct AppContext<Ef, Ev> {
+struct AppContext<Ef, Ev, Model> {
     commands: Receiver<Ef>,
     events: Receiver<Ev>,
+    model_updates: ModelUpdates<Model>,
     executor: QueuingExecutor,
+    spawner: Spawner,
 }
 
 impl<App, Ef> AppTester<App, Ef>
@@ -53,13 +72,143 @@ where
         }
     }
 
+    /// Keep a snapshot of the model before and after every event passed to
+    /// [`update`](AppTester::update), and every request resolved with
+    /// [`resolve_with_model`](AppTester::resolve_with_model), so that tests can check how the
+    /// state evolves with [`history`](AppTester::history) and
+    /// [`assert_transition`](AppTester::assert_transition).
+    #[must_use]
+    pub fn with_history(mut self) -> Self
+    where
+        App::Model: Clone,
+    {
+        self.history = Some(History {
+            snapshot: App::Model::clone,
+            transitions: Mutex::new(Vec::new()),
+        });
+        self
+    }
+
+    /// Seed the id generator of the capabilities with `seed` instead of `0`, e.g. to make the
+    /// same ids as a [`Core::with_seed`](crate::Core::with_seed) in a recorded session.
+    #[must_use]
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.ids.reseed(seed);
+        self
+    }
+
+    /// The model before the first recorded event, followed by the model after each one
+    ///
+    /// # Panics
+    ///
+    /// If the tester wasn't created [`with_history`](AppTester::with_history).
+    pub fn history(&self) -> Vec<App::Model>
+    where
+        App::Model: Clone,
+    {
+        let transitions = self.transitions();
+        let first = transitions.first().map(|(before, _)| before.clone());
+
+        first
+            .into_iter()
+            .chain(transitions.iter().map(|(_, after)| after.clone()))
+            .collect()
+    }
+
+    /// Assert that `predicate` holds for the model before and after the last recorded event,
+    /// e.g.
+    ///
+    /// ```rust,ignore
+    /// app.assert_transition(|before, after| after.count == before.count + 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the predicate doesn't hold, there were no events, or the tester wasn't created
+    /// [`with_history`](AppTester::with_history).
+    pub fn assert_transition<P>(&self, predicate: P)
+    where
+        P: FnOnce(&App::Model, &App::Model) -> bool,
+    {
+        let transitions = self.transitions();
+        let Some((before, after)) = transitions.last() else {
+            panic!("Expected a transition but no events have been recorded");
+        };
+        assert!(
+            predicate(before, after),
+            "Transition {} doesn't match",
+            transitions.len() - 1
+        );
+    }
+
+    /// Assert that `predicate` holds for the model before and after every recorded event,
+    /// e.g. that a counter never goes down.
+    ///
+    /// # Panics
+    ///
+    /// If the predicate doesn't hold for one of the transitions, or the tester wasn't created
+    /// [`with_history`](AppTester::with_history).
+    pub fn assert_every_transition<P>(&self, mut predicate: P)
+    where
+        P: FnMut(&App::Model, &App::Model) -> bool,
+    {
+        for (index, (before, after)) in self.transitions().iter().enumerate() {
+            assert!(predicate(before, after), "Transition {index} doesn't match");
+        }
+    }
+
+    fn transitions(&self) -> std::sync::MutexGuard<'_, Vec<(App::Model, App::Model)>> {
+        self.history
+            .as_ref()
+            .expect("History isn't recorded, create the AppTester with `with_history`")
+            .transitions
+            .lock()
+            .expect("History Mutex poisoned.")
+    }
+
+    fn snapshot(&self, model: &App::Model) -> Option<App::Model> {
+        self.history
+            .as_ref()
+            .map(|history| (history.snapshot)(model))
+    }
+
+    fn record(&self, before: Option<App::Model>, model: &App::Model) {
+        if let (Some(history), Some(before)) = (&self.history, before) {
+            let after = (history.snapshot)(model);
+            history
+                .transitions
+                .lock()
+                .expect("History Mutex poisoned.")
+                .push((before, after));
+        }
+    }
+
+    /// Create the initial model with the app's `init` function, as the core does when the
+    /// shell passes its startup configuration
+    pub fn init(&self, config: crate::init::Init) -> App::Model {
+        self.app.init(config)
+    }
+
     /// Run the app's `update` function with an event and a model state
     ///
     /// You can use the resulting [`Update`] to inspect the effects which were requested
     /// and potential further events dispatched by capabilities.
+    ///
+    /// Events the app handles with [`async_update`](crate::App::async_update) spawn a task,
+    /// which runs until it waits for an effect, changing the `model` as it goes.
     pub fn update(&self, event: App::Event, model: &mut App::Model) -> Update<Ef, App::Event> {
-        self.app.update(event, model, &self.capabilities);
-        self.context.updates()
+        let before = self.snapshot(model);
+        match self
+            .app
+            .async_update(event, self.model_handle.clone(), &self.capabilities)
+        {
+            AsyncUpdate::Spawn(task) => self.context.spawner.spawn(task),
+            AsyncUpdate::Update(event) => self.app.update(event, model, &self.capabilities),
+        }
+        let update = self.context.updates_with_model(model);
+        self.record(before, model);
+
+        update
     }
 
     /// Resolve an effect `request` from previous update with an operation output.
@@ -76,6 +225,24 @@ where
         Ok(self.context.updates())
     }
 
+    /// Resolve an effect `request` from previous update with an operation output, for
+    /// requests made by an [`async_update`](crate::App::async_update) task.
+    ///
+    /// The task continues until it waits for another effect, changing the `model` as it goes.
+    pub fn resolve_with_model<Op: Operation>(
+        &self,
+        request: &mut Request<Op>,
+        value: Op::Output,
+        model: &mut App::Model,
+    ) -> Result<Update<Ef, App::Event>> {
+        let before = self.snapshot(model);
+        request.resolve(value)?;
+        let update = self.context.updates_with_model(model);
+        self.record(before, model);
+
+        Ok(update)
+    }
+
     /// Resolve an effect `request` from previous update, then run the resulting event
     ///
     /// This helper is useful for the common case where  one expects the effect to resolve
@@ -106,8 +273,11 @@ where
     fn default() -> Self {
         let (command_sender, commands) = crate::capability::channel();
         let (event_sender, events) = crate::capability::channel();
+        let (model_handle, model_updates) = ModelHandle::new();
         let (executor, spawner) = executor_and_spawner();
-        let capability_context = ProtoContext::new(command_sender, event_sender, spawner);
+        let capability_context = ProtoContext::new(command_sender, event_sender, spawner.clone());
+        let ids = capability_context.ids();
+        ids.reseed(0);
 
         Self {
             app: App::default(),
@@ -115,8 +285,13 @@ where
             context: Arc::new(AppContext {
                 commands,
                 events,
+                model_updates,
                 executor,
+                spawner,
             }),
+            ids,
+            model_handle,
+            history: None,
         }
     }
 }
@@ -130,7 +305,7 @@ where
     }
 }
 
-impl<Ef, Ev> AppContext<Ef, Ev> {
+impl<Ef, Ev, Model> AppContext<Ef, Ev, Model> {
     pub fn updates(self: &Arc<Self>) -> Update<Ef, Ev> {
         self.executor.run_all();
         let effects = self.commands.drain().collect();
@@ -138,6 +313,16 @@ impl<Ef, Ev> AppContext<Ef, Ev> {
 
         Update { effects, events }
     }
+
+    /// Like `updates`, running the model updates async update tasks wait for
+    pub fn updates_with_model(self: &Arc<Self>, model: &mut Model) -> Update<Ef, Ev> {
+        self.executor.run_all();
+        while async_update::apply_updates(&self.model_updates, model) {
+            self.executor.run_all();
+        }
+
+        self.updates()
+    }
 }
 
 /// Update test helper holds the result of running an app update using [`AppTester::update`]
@@ -164,6 +349,26 @@ impl<Ef, Ev> Update<Ef, Ev> {
         self.effects.iter_mut()
     }
 
+    /// Describe the effects in a stable, readable form for snapshot tests, numbered in the
+    /// order they were requested, e.g.
+    ///
+    /// ```rust,ignore
+    /// insta::assert_snapshot!(update.effects_snapshot());
+    /// ```
+    ///
+    /// This is easier to review than destructuring a long sequence of effects one by one.
+    pub fn effects_snapshot(&self) -> String
+    where
+        Ef: Debug,
+    {
+        let mut snapshot = String::new();
+        for (index, effect) in self.effects.iter().enumerate() {
+            writeln!(snapshot, "{index}: {effect:#?}").expect("writing to a String can't fail");
+        }
+
+        snapshot
+    }
+
     /// Assert that the update contains exactly one effect and zero events,
     /// and return the effect
     pub fn expect_one_effect(mut self) -> Ef {
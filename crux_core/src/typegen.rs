@@ -78,7 +78,10 @@
 //! - `generated/java/Requests.java`
 //!
 //! Then create the `typegen_extensions/{target}/{target-file}`
-//! with the desired content next to your `build.rs` file.
+//! with the desired content next to your `build.rs` file, or in another directory passed
+//! to [`TypeGen::extensions_dir`]. Each file overrides the built-in one at the same path,
+//! the others are used as they are. `crux extensions` copies the built-in files into the
+//! directory set as `extensions` for the core in `Crux.toml`, as a starting point.
 //!
 //! For example `typegen_extensions/swift/Package.swift`:
 //!
@@ -113,13 +116,14 @@
 //! )
 //! ```
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_generate::{java, swift, typescript, Encoding, SourceInstaller};
-use serde_reflection::{Registry, Tracer, TracerConfig};
+use serde_reflection::{
+    ContainerFormat, Format, FormatHolder, Named, Registry, Tracer, TracerConfig, VariantFormat,
+};
 use std::{
-    fs::{self, File},
-    io::Write,
-    mem,
+    collections::{BTreeMap, BTreeSet},
+    fs, mem,
     path::{Path, PathBuf},
 };
 use thiserror::Error;
@@ -127,10 +131,26 @@ use thiserror::Error;
 // Expose from `serde_reflection` for `register_type_with_samples()`
 use serde_reflection::Samples;
 
-use crate::App;
+use crate::{
+    capability::{CapabilityInfo, Introspect},
+    App,
+};
+
+use generated::GeneratedFiles;
+
+mod accessors;
+mod capabilities;
+mod generated;
+#[cfg(feature = "proptest")]
+mod strategy;
+mod validators;
 
 pub type Result = std::result::Result<(), TypeGenError>;
 
+static RESULT_ERROR_HINT: &str = r#"
+HINT: A `Result` has only been traced partially. Register it explicitly with the types it is used with,
+    e.g. gen.register_type::<Result<MyValue, MyError>>()"#;
+
 static DESERIALIZATION_ERROR_HINT: &str = r#"
 This might be because you attempted to pass types with custom serialization across the FFI boundary. Make sure that:
 1. Types you use in Event, ViewModel and Capabilities serialize as a container, otherwise wrap them in a new type struct,
@@ -151,6 +171,8 @@ pub enum TypeGenError {
     Generation(String),
     #[error("error writing generated types")]
     Io(#[from] std::io::Error),
+    #[error("{0} contain nested options (e.g. `Option<Option<T>>`), which TypeScript can't tell apart from a single option.\nUse TypeGen::collapse_nested_options to generate them anyway, treating `Some(None)` as `None`.")]
+    NestedOptions(String),
     #[error("`pnpm` is needed for TypeScript type generation, but it could not be found in PATH.\nPlease install it from https://pnpm.io/installation")]
     PnpmNotFound(#[source] std::io::Error),
 }
@@ -165,16 +187,138 @@ pub trait Export {
     fn register_types(generator: &mut TypeGen) -> Result;
 }
 
+/// The roles of the types in the registry, as registered by [`TypeGen::register_app`],
+/// for tools reading the registry lockfile.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub event: Option<String>,
+    pub view_model: Option<String>,
+    pub effect: Option<String>,
+    /// The operation type of each capability, by the name of its variant of the effect type
+    pub capabilities: BTreeMap<String, String>,
+    /// The types which reject unknown fields, registered with [`TypeGen::deny_unknown_fields`]
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub deny_unknown_fields: BTreeSet<String>,
+}
+
+impl Manifest {
+    fn rename(&mut self, names: &BTreeMap<String, String>) {
+        for name in [&mut self.event, &mut self.view_model, &mut self.effect]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(new_name) = names.get(name) {
+                *name = new_name.clone();
+            }
+        }
+        self.deny_unknown_fields = mem::take(&mut self.deny_unknown_fields)
+            .into_iter()
+            .map(|name| names.get(&name).cloned().unwrap_or(name))
+            .collect();
+    }
+}
+
+/// The value of a constant shared with the Shell, registered with [`TypeGen::register_constant`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+macro_rules! integer_constant {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Constant {
+            fn from(value: $t) -> Self {
+                Constant::Integer(value.into())
+            }
+        })*
+    };
+}
+
+integer_constant!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<bool> for Constant {
+    fn from(value: bool) -> Self {
+        Constant::Bool(value)
+    }
+}
+
+impl From<f32> for Constant {
+    fn from(value: f32) -> Self {
+        Constant::Float(value.into())
+    }
+}
+
+impl From<f64> for Constant {
+    fn from(value: f64) -> Self {
+        Constant::Float(value)
+    }
+}
+
+impl From<&str> for Constant {
+    fn from(value: &str) -> Self {
+        Constant::String(value.to_string())
+    }
+}
+
+impl From<String> for Constant {
+    fn from(value: String) -> Self {
+        Constant::String(value)
+    }
+}
+
+impl TryFrom<u64> for Constant {
+    type Error = TypeGenError;
+
+    fn try_from(value: u64) -> std::result::Result<Self, Self::Error> {
+        i64::try_from(value).map(Constant::Integer).map_err(|_| {
+            TypeGenError::Generation(format!("{value} is too large for a shared constant"))
+        })
+    }
+}
+
+impl TryFrom<usize> for Constant {
+    type Error = TypeGenError;
+
+    fn try_from(value: usize) -> std::result::Result<Self, Self::Error> {
+        Constant::try_from(value as u64)
+    }
+}
+
 /// The `TypeGen` struct stores the registered types so that they can be generated for foreign languages
 /// use `TypeGen::new()` to create an instance
 pub struct TypeGen {
     pub state: State,
+    collapse_nested_options: bool,
+    typescript_validators: bool,
+    enum_accessors: bool,
+    renames: BTreeMap<String, String>,
+    prefix: String,
+    suffix: String,
+    manifest: Manifest,
+    view_paths: Vec<String>,
+    constants: Vec<(String, Constant)>,
+    capabilities: Vec<CapabilityInfo>,
+    extensions_dir: PathBuf,
 }
 
 impl Default for TypeGen {
     fn default() -> Self {
         TypeGen {
             state: State::Registering(Tracer::new(TracerConfig::default()), Samples::new()),
+            collapse_nested_options: false,
+            typescript_validators: false,
+            enum_accessors: false,
+            renames: BTreeMap::new(),
+            prefix: String::new(),
+            suffix: String::new(),
+            manifest: Manifest::default(),
+            view_paths: Vec::new(),
+            constants: Vec::new(),
+            capabilities: Vec::new(),
+            extensions_dir: PathBuf::from("./typegen_extensions"),
         }
     }
 }
@@ -202,11 +346,29 @@ impl TypeGen {
         self.register_type::<A::Event>()?;
         self.register_type::<A::ViewModel>()?;
 
+        self.manifest.event = self.traced_name::<A::Event>();
+        self.manifest.view_model = self.traced_name::<A::ViewModel>();
+
         A::Capabilities::register_types(self)?;
 
         Ok(())
     }
 
+    /// Register the [`Init`](crate::init::Init) startup configuration, for a Shell which
+    /// passes it to [`Bridge::init`](crate::bridge::Bridge::init). It's generated as `Init`,
+    /// along with its `PlatformInfo`. If the app has types with the same names, rename these
+    /// with [`rename_type`](TypeGen::rename_type).
+    pub fn register_init(&mut self) -> Result {
+        self.register_type::<crate::init::Init>()
+    }
+
+    /// Register the core's [`Metrics`](crate::metrics::Metrics), for a Shell which reads them
+    /// with [`Bridge::metrics`](crate::bridge::Bridge::metrics). If the app has a type with
+    /// the same name, rename this one with [`rename_type`](TypeGen::rename_type).
+    pub fn register_metrics(&mut self) -> Result {
+        self.register_type::<crate::metrics::Metrics>()
+    }
+
     /// Register sample values for types with custom serialization. This is necessary
     /// because the type registration relies on Serde to understand the structure of the types,
     /// and as part of the process runs a faux deserialization on each of them, with a best
@@ -253,6 +415,25 @@ impl TypeGen {
     ///   Ok(())
     /// }
     /// ```
+    ///
+    /// `Result<T, E>` fields are shared as an enum called `Result`, with an `Ok` and an `Err`
+    /// variant, in the same externally tagged format serde uses. Like other nested enums,
+    /// the `Result` needs registering explicitly, so that both of its variants are traced:
+    /// ```rust
+    /// # use crux_core::typegen::TypeGen;
+    /// # use serde::{Serialize, Deserialize};
+    /// # use anyhow::Error;
+    /// #[derive(Serialize, Deserialize)]
+    /// struct ViewModel { items: Result<Vec<String>, String> }
+    /// fn register() -> Result<(), Error> {
+    ///   let mut gen = TypeGen::new();
+    ///   gen.register_type::<ViewModel>()?;
+    ///   gen.register_type::<Result<Vec<String>, String>>()?;
+    ///   Ok(())
+    /// }
+    /// ```
+    /// Serde gives every `Result` the same name, so all the `Result` fields of the shared types
+    /// must have the same `T` and `E`.
     pub fn register_type<'de, T>(&mut self) -> Result
     where
         T: serde::Deserialize<'de>,
@@ -279,6 +460,32 @@ The 2 common cases are:
         }
     }
 
+    /// Register the app's effect type, i.e. the serializable version of the type generated by
+    /// the `Effect` derive macro. The `Export` derive macro does this for you.
+    pub fn register_effect<'de, T>(&mut self) -> Result
+    where
+        T: serde::Deserialize<'de>,
+    {
+        self.register_type::<T>()?;
+        self.manifest.effect = self.traced_name::<T>();
+
+        Ok(())
+    }
+
+    /// The name of a registered type in the registry
+    fn traced_name<'de, T>(&mut self) -> Option<String>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        let State::Registering(tracer, _) = &mut self.state else {
+            return None;
+        };
+        match tracer.trace_simple_type::<T>() {
+            Ok((Format::TypeName(name), _)) => Some(name),
+            _ => None,
+        }
+    }
+
     /// Usually, the simple `register_type()` method can generate the types you need.
     /// Sometimes, though, you need to provide samples of your type. The `Uuid` type,
     /// for example, requires a sample struct to help the typegen system understand
@@ -345,6 +552,196 @@ The 2 common cases are:
         }
     }
 
+    /// Share a constant with the Shell, e.g. a limit the Shell's UI should enforce too.
+    /// The constants are generated in a `Constants` type, with their names in the
+    /// language's style, e.g. `Constants.maxItems` in Swift and TypeScript, and
+    /// `Constants.MAX_ITEMS` in Java, for a constant registered as `MAX_ITEMS`.
+    ///
+    /// C-like enums which aren't used by the app's types can be shared with
+    /// [`TypeGen::register_type`], like any other type.
+    /// e.g.
+    /// ```rust
+    /// # use crux_core::typegen::{Constant, TypeGen};
+    /// const MAX_ITEMS: usize = 100;
+    /// # let mut gen = TypeGen::new();
+    /// gen.register_constant("MAX_ITEMS", Constant::try_from(MAX_ITEMS)?)?;
+    /// gen.register_constant("API_VERSION", "v2")?;
+    /// # Ok::<(), crux_core::typegen::TypeGenError>(())
+    /// ```
+    ///
+    /// Integers are generated as 64 bit integers in Swift and Java, and as numbers in
+    /// TypeScript, which can only represent integers up to 2^53 exactly.
+    pub fn register_constant(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Into<Constant>,
+    ) -> Result {
+        let State::Registering(..) = self.state else {
+            return Err(TypeGenError::LateRegistration);
+        };
+        let name = name.into();
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(TypeGenError::Generation(format!(
+                "{name:?} is not a valid name for a shared constant"
+            )));
+        }
+
+        let value = value.into();
+        if matches!(value, Constant::Float(value) if !value.is_finite()) {
+            return Err(TypeGenError::Generation(format!(
+                "shared constant {name} is not a finite number"
+            )));
+        }
+
+        self.constants.retain(|(existing, _)| *existing != name);
+        self.constants.push((name, value));
+        Ok(())
+    }
+
+    /// Generate a table of the app's capabilities, mapping each variant of the effect type
+    /// to the name of the capability's field, its operation type and the field's doc comment,
+    /// so that shells can build their dispatch on it and devtools can label the effects.
+    /// The table is `CapabilityTable.swift` in Swift, `CapabilityTable.java` in Java and
+    /// `types/capabilities.ts` in TypeScript, each with a lookup by the effect's variant.
+    /// e.g.
+    /// ```rust
+    /// # use crux_core::{macros::Effect, render::Render, typegen::TypeGen};
+    /// # enum Event {}
+    /// #[derive(Effect)]
+    /// struct Capabilities {
+    ///     /// Redraws the UI
+    ///     render: Render<Event>,
+    /// }
+    ///
+    /// # let mut gen = TypeGen::new();
+    /// gen.register_capabilities::<Capabilities>()?;
+    /// # Ok::<(), crux_core::typegen::TypeGenError>(())
+    /// ```
+    pub fn register_capabilities<C: Introspect>(&mut self) -> Result {
+        let State::Registering(..) = self.state else {
+            return Err(TypeGenError::LateRegistration);
+        };
+        self.capabilities = C::capabilities();
+        Ok(())
+    }
+
+    /// Nested options (e.g. `Option<Option<T>>`) are preserved by the bincode serialization,
+    /// and generated as such for Swift (`T??`) and Java (`Optional<Optional<T>>`), but TypeScript
+    /// represents every option as `T | null`, so `Some(None)` and `None` can't be told apart.
+    ///
+    /// By default, `TypeGen::typescript` fails if any of the registered types contain a nested
+    /// option. Call this method with `true` to generate them anyway, in which case a `Some(None)`
+    /// sent from the core is received by the shell as `null`, and sent back as `None`.
+    pub fn collapse_nested_options(&mut self, collapse: bool) {
+        self.collapse_nested_options = collapse;
+    }
+
+    /// Call this method with `true` to generate runtime validators with the TypeScript types,
+    /// in `types/validators.ts`. For each type `T`, `validateT(value)` checks a value parsed
+    /// from JSON against the representation `serde_json` gives `T`, returning a description of
+    /// each mismatch, e.g. `"User.name: expected a string"`, and `isT(value)` returns whether
+    /// there are none. Web shells receiving the shared types as JSON, e.g. from a server,
+    /// can use them to reject a payload which doesn't match the contract straight away.
+    pub fn typescript_validators(&mut self, generate: bool) {
+        self.typescript_validators = generate;
+    }
+
+    /// Register a type marked `#[serde(deny_unknown_fields)]`, which the serde reflection
+    /// can't see. Its fields, or the fields of its struct variants if it's an enum, are then
+    /// checked for unknown ones by the TypeScript validators, and by the tools reading the
+    /// registry lockfile, e.g. the JSON schemas of `crux schema`. Other types tolerate
+    /// unknown fields, so older shells can read JSON from newer cores.
+    /// The type is registered too, if it wasn't already.
+    ///
+    /// The bincode serialization used across the bridge doesn't name the fields, so the
+    /// Swift and Java decoders are not affected.
+    /// e.g.
+    /// ```rust
+    /// # use crux_core::typegen::TypeGen;
+    /// # use serde::{Deserialize, Serialize};
+    /// #[derive(Serialize, Deserialize)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct Settings {
+    ///     dark_mode: bool,
+    /// }
+    ///
+    /// # let mut gen = TypeGen::new();
+    /// gen.deny_unknown_fields::<Settings>()?;
+    /// # Ok::<(), crux_core::typegen::TypeGenError>(())
+    /// ```
+    pub fn deny_unknown_fields<'de, T>(&mut self) -> Result
+    where
+        T: Deserialize<'de>,
+    {
+        self.register_type::<T>()?;
+        let name = self.traced_name::<T>().ok_or_else(|| {
+            TypeGenError::Generation(format!(
+                "{} has no fields to deny unknown ones in",
+                std::any::type_name::<T>()
+            ))
+        })?;
+        self.manifest.deny_unknown_fields.insert(name);
+        Ok(())
+    }
+
+    /// Call this method with `true` to generate accessors for the variants of the registered
+    /// enums, like `Event` and `Effect`, with the types for each language. For a variant `V`,
+    /// Swift gets `isV` and `asV` properties in `EnumAccessors.swift`, Kotlin gets the same
+    /// extension properties on the Java classes in `EnumAccessors.kt`, and TypeScript gets
+    /// `isEV(value)` and `asEV(value)` functions for an enum `E` in `types/accessors.ts`.
+    /// `asV` returns the value of a newtype variant, or the variant's fields otherwise, and
+    /// `nil` or `null` for the other variants.
+    pub fn enum_accessors(&mut self, generate: bool) {
+        self.enum_accessors = generate;
+    }
+
+    /// Rename a registered type in the generated code, e.g. to avoid a clash with
+    /// an existing `Event` type in the Shell. References to the type from other types are
+    /// renamed too, the serialization format is not affected.
+    /// e.g.
+    /// ```rust
+    /// # use crux_core::typegen::TypeGen;
+    /// # let mut gen = TypeGen::new();
+    /// gen.rename_type("Event", "CoreEvent")?;
+    /// # Ok::<(), crux_core::typegen::TypeGenError>(())
+    /// ```
+    pub fn rename_type(&mut self, from: impl Into<String>, to: impl Into<String>) -> Result {
+        let State::Registering(..) = self.state else {
+            return Err(TypeGenError::LateRegistration);
+        };
+        self.renames.insert(from.into(), to.into());
+        Ok(())
+    }
+
+    /// Add a prefix to the names of all the generated types which haven't been renamed with
+    /// [`TypeGen::rename_type`], except `Request`, which the generated helpers depend on.
+    pub fn type_prefix(&mut self, prefix: impl Into<String>) -> Result {
+        let State::Registering(..) = self.state else {
+            return Err(TypeGenError::LateRegistration);
+        };
+        self.prefix = prefix.into();
+        Ok(())
+    }
+
+    /// Add a suffix to the names of all the generated types which haven't been renamed with
+    /// [`TypeGen::rename_type`], except `Request`, which the generated helpers depend on.
+    pub fn type_suffix(&mut self, suffix: impl Into<String>) -> Result {
+        let State::Registering(..) = self.state else {
+            return Err(TypeGenError::LateRegistration);
+        };
+        self.suffix = suffix.into();
+        Ok(())
+    }
+
+    /// Look for files overriding the built-in extensions (e.g. `swift/Package.swift`) in
+    /// `dir`, instead of in `typegen_extensions` in the current directory. See
+    /// [custom extensions](crate::typegen#custom-extensions).
+    pub fn extensions_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.extensions_dir = dir.into();
+    }
+
     /// Generates types for Swift
     /// e.g.
     /// ```rust
@@ -382,31 +779,54 @@ The 2 common cases are:
             .install_module(&config, registry)
             .map_err(|e| TypeGenError::Generation(e.to_string()))?;
 
+        let sources = Path::new("Sources").join(module_name);
+        let mut files = GeneratedFiles::new(&path);
+        files.add(sources.join(format!("{}.swift", upper_camel_case(module_name))));
+
         // add bincode deserialization for Vec<Request>
-        let mut output = File::create(
-            path.join("Sources")
-                .join(module_name)
-                .join("Requests.swift"),
-        )?;
+        let requests_data = self.extension("swift/requests.swift")?;
+
+        files.write(sources.join("Requests.swift"), requests_data)?;
 
-        let requests_path = self.extensions_path("swift/requests.swift");
+        if !self.view_paths.is_empty() {
+            files.write(
+                sources.join("ViewPath.swift"),
+                swift_view_paths(&self.view_paths),
+            )?;
+        }
 
-        let requests_data = fs::read_to_string(requests_path)?;
+        if !self.constants.is_empty() {
+            files.write(
+                sources.join("Constants.swift"),
+                swift_constants(&self.constants),
+            )?;
+        }
 
-        write!(output, "{}", requests_data)?;
+        if !self.capabilities.is_empty() {
+            files.write(
+                sources.join("CapabilityTable.swift"),
+                capabilities::swift(&self.capability_rows()),
+            )?;
+        }
+
+        if self.enum_accessors {
+            files.write(
+                sources.join("EnumAccessors.swift"),
+                accessors::swift(registry),
+            )?;
+        }
 
         // wrap it all up in a swift package
-        let mut output = File::create(path.join("Package.swift"))?;
+        let package_data = self.extension("swift/Package.swift")?;
 
-        let package_path = self.extensions_path("swift/Package.swift");
+        files.write(
+            "Package.swift",
+            package_data.replace("SharedTypes", module_name),
+        )?;
 
-        let package_data = fs::read_to_string(package_path)?;
+        files.finish()?;
 
-        write!(
-            output,
-            "{}",
-            package_data.replace("SharedTypes", module_name)
-        )?;
+        tidy_files(&path, "swift")?;
 
         Ok(())
     }
@@ -431,8 +851,11 @@ The 2 common cases are:
 
         let package_path = package_name.replace('.', "/");
 
-        // remove any existing generated shared types, this ensures that we remove no longer used types
-        fs::remove_dir_all(path.as_ref().join(&package_path)).unwrap_or(());
+        // remove the generated types of an earlier run which didn't list its files, this
+        // ensures that we remove no longer used types
+        if !GeneratedFiles::exists(path.as_ref()) {
+            fs::remove_dir_all(path.as_ref().join(&package_path)).unwrap_or(());
+        }
 
         let config = serde_generate::CodeGeneratorConfig::new(package_name.to_string())
             .with_encodings(vec![Encoding::Bincode]);
@@ -454,19 +877,49 @@ The 2 common cases are:
             .install_module(&config, registry)
             .map_err(|e| TypeGenError::Generation(e.to_string()))?;
 
-        let requests_path = self.extensions_path("java/Requests.java");
+        let package_path = Path::new(&package_path);
+        let mut files = GeneratedFiles::new(path.as_ref());
+        for name in registry.keys().map(String::as_str).chain(["TraitHelpers"]) {
+            files.add(package_path.join(format!("{name}.java")));
+        }
 
-        let requests_data = fs::read_to_string(requests_path)?;
+        let requests_data = self.extension("java/Requests.java")?;
 
         let requests = format!("package {package_name};\n\n{}", requests_data);
 
-        fs::write(
-            path.as_ref()
-                .to_path_buf()
-                .join(package_path)
-                .join("Requests.java"),
-            requests,
-        )?;
+        files.write(package_path.join("Requests.java"), requests)?;
+
+        if !self.view_paths.is_empty() {
+            files.write(
+                package_path.join("ViewPath.java"),
+                java_view_paths(package_name, &self.view_paths),
+            )?;
+        }
+
+        if !self.constants.is_empty() {
+            files.write(
+                package_path.join("Constants.java"),
+                java_constants(package_name, &self.constants),
+            )?;
+        }
+
+        if !self.capabilities.is_empty() {
+            files.write(
+                package_path.join("CapabilityTable.java"),
+                capabilities::java(package_name, &self.capability_rows()),
+            )?;
+        }
+
+        if self.enum_accessors {
+            files.write(
+                package_path.join("EnumAccessors.kt"),
+                accessors::kotlin(package_name, registry),
+            )?;
+        }
+
+        files.finish()?;
+
+        tidy_files(path.as_ref(), "java")?;
 
         Ok(())
     }
@@ -484,6 +937,15 @@ The 2 common cases are:
     pub fn typescript(&mut self, module_name: &str, path: impl AsRef<Path>) -> Result {
         self.ensure_registry()?;
 
+        if !self.collapse_nested_options {
+            if let State::Generating(registry) = &self.state {
+                let names = containers_with_nested_options(registry);
+                if !names.is_empty() {
+                    return Err(TypeGenError::NestedOptions(names.join(", ")));
+                }
+            }
+        }
+
         fs::create_dir_all(&path)?;
         let output_dir = path.as_ref().to_path_buf();
 
@@ -495,8 +957,19 @@ The 2 common cases are:
             .install_bincode_runtime()
             .map_err(|e| TypeGenError::Generation(e.to_string()))?;
 
-        let extensions_dir = self.extensions_path("typescript");
-        copy(extensions_dir, path)?;
+        for (name, data) in crate::TYPEGEN_EXTENSIONS {
+            if let Some(name) = name.strip_prefix("typescript/") {
+                let to = path.as_ref().join(name);
+                if let Some(parent) = to.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(to, data)?;
+            }
+        }
+        let overrides = self.extensions_dir.join("typescript");
+        if overrides.is_dir() {
+            copy(overrides, &path)?;
+        }
 
         let registry = match &self.state {
             State::Generating(registry) => registry,
@@ -518,11 +991,48 @@ The 2 common cases are:
             )
             .replace(".ts'", "'");
 
-        let types_dir = output_dir.join("types");
-        fs::create_dir_all(&types_dir)?;
+        let types_dir = Path::new("types");
+        fs::create_dir_all(output_dir.join(types_dir))?;
+
+        let mut files = GeneratedFiles::new(&output_dir);
+        files.write(types_dir.join(format!("{module_name}.ts")), tidy(&out))?;
 
-        let mut output = File::create(types_dir.join(format!("{module_name}.ts")))?;
-        write!(output, "{out}")?;
+        if !self.view_paths.is_empty() {
+            files.write(
+                types_dir.join("view_path.ts"),
+                typescript_view_paths(&self.view_paths),
+            )?;
+        }
+
+        if !self.constants.is_empty() {
+            files.write(
+                types_dir.join("constants.ts"),
+                typescript_constants(&self.constants),
+            )?;
+        }
+
+        if !self.capabilities.is_empty() {
+            files.write(
+                types_dir.join("capabilities.ts"),
+                capabilities::typescript(&self.capability_rows()),
+            )?;
+        }
+
+        if self.typescript_validators {
+            files.write(
+                types_dir.join("validators.ts"),
+                validators::typescript(registry, &self.manifest.deny_unknown_fields),
+            )?;
+        }
+
+        if self.enum_accessors {
+            files.write(
+                types_dir.join("accessors.ts"),
+                accessors::typescript(module_name, registry),
+            )?;
+        }
+
+        files.finish()?;
 
         // Install dependencies
         std::process::Command::new("pnpm")
@@ -546,6 +1056,171 @@ The 2 common cases are:
         Ok(())
     }
 
+    /// Generates types for Rust, as a standalone crate named `crate_name`, for shells written
+    /// in Rust (e.g. a terminal UI, or a desktop app with egui) which exchange the shared types
+    /// with the core over the bridge, without depending on the crate of the app itself.
+    /// The types derive `Serialize` and `Deserialize`, so the shell can (de)serialize them with
+    /// bincode like the other shells.
+    /// e.g.
+    /// ```rust
+    /// # use crux_core::typegen::TypeGen;
+    /// # use std::env::temp_dir;
+    /// # let mut gen = TypeGen::new();
+    /// # let output_root = temp_dir().join("crux_core_typegen_doctest");
+    /// gen.rust("shared_types", output_root.join("rust"))?;
+    /// # Ok::<(), crux_core::typegen::TypeGenError>(())
+    /// ```
+    pub fn rust(&mut self, crate_name: &str, path: impl AsRef<Path>) -> Result {
+        self.ensure_registry()?;
+
+        let path = path.as_ref().join(crate_name);
+
+        fs::create_dir_all(path.join("src"))?;
+
+        let registry = match &self.state {
+            State::Generating(registry) => registry,
+            _ => panic!("registry creation failed"),
+        };
+
+        fs::write(
+            path.join("Cargo.toml"),
+            format!(
+                r#"[package]
+name = "{crate_name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+serde = {{ version = "1.0", features = ["derive"] }}
+serde_bytes = "0.11"
+"#
+            ),
+        )?;
+
+        let config = serde_generate::CodeGeneratorConfig::new(crate_name.to_string())
+            .with_encodings(vec![Encoding::Bincode]);
+
+        let generator = serde_generate::rust::CodeGenerator::new(&config);
+        let mut source = Vec::new();
+        generator
+            .output(&mut source, registry)
+            .map_err(|e| TypeGenError::Generation(e.to_string()))?;
+
+        fs::write(
+            path.join("src").join("lib.rs"),
+            tidy(&String::from_utf8_lossy(&source)),
+        )?;
+
+        Ok(())
+    }
+
+    /// Writes the registry of shared types as a JSON "lockfile".
+    ///
+    /// Committing this file alongside the generated code allows the `crux diff`
+    /// command to compare the shell-facing API across git revisions.
+    ///
+    /// A [`Manifest`] naming the app's event, view model and effect types is written
+    /// next to it, e.g. `registry.manifest.json` for `registry.json`, so tools don't
+    /// need to guess which types they are.
+    /// e.g.
+    /// ```rust
+    /// # use crux_core::typegen::TypeGen;
+    /// # use std::env::temp_dir;
+    /// # let mut gen = TypeGen::new();
+    /// # let output_root = temp_dir().join("crux_core_typegen_doctest");
+    /// gen.registry_lockfile(output_root.join("registry.json"))?;
+    /// # Ok::<(), crux_core::typegen::TypeGenError>(())
+    /// ```
+    pub fn registry_lockfile(&mut self, path: impl AsRef<Path>) -> Result {
+        self.ensure_registry()?;
+
+        let registry = match &self.state {
+            State::Generating(registry) => registry,
+            _ => panic!("registry creation failed"),
+        };
+
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(registry)
+            .map_err(|e| TypeGenError::Generation(e.to_string()))?;
+        fs::write(&path, json + "\n")?;
+
+        let manifest = serde_json::to_string_pretty(&self.manifest)
+            .map_err(|e| TypeGenError::Generation(e.to_string()))?;
+        fs::write(
+            path.as_ref().with_extension("manifest.json"),
+            manifest + "\n",
+        )?;
+
+        Ok(())
+    }
+
+    /// A [proptest](https://docs.rs/proptest) strategy generating values of `T`, the type
+    /// registered as `name` (after any renames), for property based tests of the core.
+    ///
+    /// The values are generated from the type's serde format and deserialized, so they are
+    /// all values the shell could send. Values the type's `Deserialize` implementation
+    /// rejects are skipped, and recursive types are kept to a few levels deep.
+    /// e.g.
+    /// ```rust,ignore
+    /// let mut gen = TypeGen::new();
+    /// gen.register_app::<App>()?;
+    /// let sessions = proptest::collection::vec(gen.event_strategy::<App>()?, 1..20);
+    /// ```
+    #[cfg(feature = "proptest")]
+    pub fn strategy<T>(
+        &mut self,
+        name: &str,
+    ) -> std::result::Result<proptest::strategy::BoxedStrategy<T>, TypeGenError>
+    where
+        T: serde::de::DeserializeOwned + std::fmt::Debug + 'static,
+    {
+        self.ensure_registry()?;
+
+        let State::Generating(registry) = &self.state else {
+            panic!("registry creation failed");
+        };
+        strategy::strategy(registry, name)
+    }
+
+    /// A proptest strategy generating the app's events, see [`TypeGen::strategy`].
+    /// The app must be registered with [`TypeGen::register_app`] first.
+    #[cfg(feature = "proptest")]
+    pub fn event_strategy<A>(
+        &mut self,
+    ) -> std::result::Result<proptest::strategy::BoxedStrategy<A::Event>, TypeGenError>
+    where
+        A: App,
+        A::Event: serde::de::DeserializeOwned + std::fmt::Debug,
+    {
+        self.ensure_registry()?;
+
+        let name = self.manifest.event.clone().ok_or_else(unregistered_app)?;
+        self.strategy(&name)
+    }
+
+    /// A proptest strategy generating the app's view models, see [`TypeGen::strategy`].
+    /// The app must be registered with [`TypeGen::register_app`] first.
+    #[cfg(feature = "proptest")]
+    pub fn view_model_strategy<A>(
+        &mut self,
+    ) -> std::result::Result<proptest::strategy::BoxedStrategy<A::ViewModel>, TypeGenError>
+    where
+        A: App,
+        A::ViewModel: serde::de::DeserializeOwned + std::fmt::Debug + 'static,
+    {
+        self.ensure_registry()?;
+
+        let name = self
+            .manifest
+            .view_model
+            .clone()
+            .ok_or_else(unregistered_app)?;
+        self.strategy(&name)
+    }
+
     fn ensure_registry(&mut self) -> Result {
         if let State::Registering(_, _) = self.state {
             // replace the current state with a dummy tracer
@@ -557,31 +1232,488 @@ The 2 common cases are:
             // convert tracer to registry
             if let State::Registering(tracer, _) = old_state {
                 // replace dummy with registry
-                self.state = State::Generating(
-                    tracer
-                        .registry()
-                        .map_err(|e| TypeGenError::Generation(e.explanation()))?,
-                );
+                self.state = State::Generating(tracer.registry().map_err(|e| match e {
+                    serde_reflection::Error::MissingVariants(ref names)
+                        if names.iter().any(|name| name == "Result") =>
+                    {
+                        TypeGenError::Generation(format!("{}{RESULT_ERROR_HINT}", e.explanation()))
+                    }
+                    e => TypeGenError::Generation(e.explanation()),
+                })?);
+            }
+
+            if let State::Generating(registry) = &mut self.state {
+                skip_phantom_data(registry);
+                // field names don't change when types are renamed
+                if let Some(view_model) = &self.manifest.view_model {
+                    self.view_paths = view_paths(registry, view_model);
+                }
+                let names = new_names(registry, &self.renames, &self.prefix, &self.suffix)?;
+                rename_types(registry, &names);
+                self.manifest.rename(&names);
+                self.manifest.capabilities =
+                    capabilities(registry, self.manifest.effect.as_deref());
             }
         }
         Ok(())
     }
 
-    fn extensions_path(&self, path: &str) -> PathBuf {
-        let custom = PathBuf::from("./typegen_extensions").join(path);
-        let default = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join("typegen_extensions")
-            .join(path);
+    fn capability_rows(&self) -> Vec<capabilities::Capability> {
+        capabilities::rows(&self.capabilities, &self.manifest.capabilities)
+    }
+
+    /// The contents of the extension at `path`, from the extensions directory if it's
+    /// overridden there, or else the built-in one
+    fn extension(&self, path: &str) -> std::result::Result<String, TypeGenError> {
+        let custom = self.extensions_dir.join(path);
 
         match custom.try_exists() {
-            Ok(true) => custom,
-            Ok(false) => default,
-            Err(e) => {
-                println!("cant check typegen extensions override: {}", e);
-                default
+            Ok(true) => return Ok(fs::read_to_string(custom)?),
+            Ok(false) => {}
+            Err(e) => println!("cant check typegen extensions override: {}", e),
+        }
+
+        let (_, data) = crate::TYPEGEN_EXTENSIONS
+            .iter()
+            .find(|(name, _)| *name == path)
+            .expect("a built-in extension");
+        Ok(data.to_string())
+    }
+}
+
+#[cfg(feature = "proptest")]
+fn unregistered_app() -> TypeGenError {
+    TypeGenError::Generation("the app must be registered with register_app first".to_string())
+}
+
+/// The operation type of each variant of the effect type
+fn capabilities(registry: &Registry, effect: Option<&str>) -> BTreeMap<String, String> {
+    let Some(ContainerFormat::Enum(variants)) = effect.and_then(|effect| registry.get(effect))
+    else {
+        return BTreeMap::new();
+    };
+
+    variants
+        .values()
+        .filter_map(|variant| match &variant.value {
+            VariantFormat::NewType(format) => match format.as_ref() {
+                Format::TypeName(operation) => Some((variant.name.clone(), operation.clone())),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn new_names(
+    registry: &Registry,
+    renames: &BTreeMap<String, String>,
+    prefix: &str,
+    suffix: &str,
+) -> std::result::Result<BTreeMap<String, String>, TypeGenError> {
+    if let Some(name) = renames.keys().find(|name| !registry.contains_key(*name)) {
+        return Err(TypeGenError::Generation(format!(
+            "cannot rename `{name}`, no such type has been registered"
+        )));
+    }
+
+    let mut names = BTreeMap::new();
+    let mut taken = BTreeMap::new();
+    for name in registry.keys() {
+        let new_name = match renames.get(name) {
+            Some(new_name) => new_name.clone(),
+            None if name == "Request" => name.clone(),
+            None => format!("{prefix}{name}{suffix}"),
+        };
+        if let Some(other) = taken.insert(new_name.clone(), name) {
+            return Err(TypeGenError::Generation(format!(
+                "renaming `{name}` and `{other}` both result in a type called `{new_name}`"
+            )));
+        }
+        if &new_name != name {
+            names.insert(name.clone(), new_name);
+        }
+    }
+    Ok(names)
+}
+
+fn rename_types(registry: &mut Registry, names: &BTreeMap<String, String>) {
+    if names.is_empty() {
+        return;
+    }
+
+    *registry = mem::take(registry)
+        .into_iter()
+        .map(|(name, mut container)| {
+            container
+                .visit_mut(&mut |format| {
+                    if let Format::TypeName(name) = format {
+                        if let Some(new_name) = names.get(name) {
+                            new_name.clone_into(name);
+                        }
+                    }
+                    Ok(())
+                })
+                .expect("registry formats should not contain variables");
+            (names.get(&name).cloned().unwrap_or(name), container)
+        })
+        .collect();
+}
+
+const PHANTOM_DATA: &str = "PhantomData";
+
+/// Remove `PhantomData` fields, which serde traces as a unit struct called `PhantomData`,
+/// so that they don't show up in the generated types. They take up no space in bincode,
+/// so the generated types stay compatible with the core. Tuple structs and variants
+/// left with one field become newtypes, and with none, unit structs and variants.
+fn skip_phantom_data(registry: &mut Registry) {
+    if !matches!(
+        registry.get(PHANTOM_DATA),
+        Some(ContainerFormat::UnitStruct)
+    ) {
+        return;
+    }
+
+    fn is_phantom(format: &Format) -> bool {
+        matches!(format, Format::TypeName(name) if name == PHANTOM_DATA)
+    }
+
+    for container in registry.values_mut() {
+        *container = match mem::replace(container, ContainerFormat::UnitStruct) {
+            ContainerFormat::NewTypeStruct(format) if is_phantom(&format) => {
+                ContainerFormat::UnitStruct
+            }
+            ContainerFormat::TupleStruct(mut fields) => {
+                fields.retain(|format| !is_phantom(format));
+                match fields.len() {
+                    0 => ContainerFormat::UnitStruct,
+                    1 => ContainerFormat::NewTypeStruct(Box::new(fields.remove(0))),
+                    _ => ContainerFormat::TupleStruct(fields),
+                }
+            }
+            ContainerFormat::Struct(mut fields) => {
+                fields.retain(|field| !is_phantom(&field.value));
+                ContainerFormat::Struct(fields)
+            }
+            ContainerFormat::Enum(mut variants) => {
+                for variant in variants.values_mut() {
+                    variant.value = match mem::replace(&mut variant.value, VariantFormat::Unit) {
+                        VariantFormat::NewType(format) if is_phantom(&format) => {
+                            VariantFormat::Unit
+                        }
+                        VariantFormat::Tuple(mut fields) => {
+                            fields.retain(|format| !is_phantom(format));
+                            match fields.len() {
+                                0 => VariantFormat::Unit,
+                                1 => VariantFormat::NewType(Box::new(fields.remove(0))),
+                                _ => VariantFormat::Tuple(fields),
+                            }
+                        }
+                        VariantFormat::Struct(mut fields) => {
+                            fields.retain(|field| !is_phantom(&field.value));
+                            VariantFormat::Struct(fields)
+                        }
+                        other => other,
+                    };
+                }
+                ContainerFormat::Enum(variants)
+            }
+            other => other,
+        };
+    }
+
+    // keep the type if it's still used somewhere else, e.g. in an `Option`
+    let mut used = false;
+    for container in registry.values() {
+        container
+            .visit(&mut |format| {
+                used |= is_phantom(format);
+                Ok(())
+            })
+            .expect("registry formats should not contain variables");
+    }
+    if !used {
+        registry.remove(PHANTOM_DATA);
+    }
+}
+
+/// The paths of the fields of the view model, and of the structs nested in it, which a shell
+/// can ask for with [`Bridge::view_slice`](crate::bridge::Bridge::view_slice).
+fn view_paths(registry: &Registry, view_model: &str) -> Vec<String> {
+    fn struct_fields<'a>(
+        registry: &'a Registry,
+        format: &'a Format,
+    ) -> Option<(&'a str, &'a [Named<Format>])> {
+        match format {
+            Format::Option(inner) => struct_fields(registry, inner),
+            Format::TypeName(name) => match registry.get(name)? {
+                ContainerFormat::Struct(fields) => Some((name, fields)),
+                ContainerFormat::NewTypeStruct(inner) => struct_fields(registry, inner),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn collect<'a>(
+        registry: &'a Registry,
+        prefix: &str,
+        fields: &'a [Named<Format>],
+        visiting: &mut Vec<&'a str>,
+        paths: &mut Vec<String>,
+    ) {
+        for field in fields {
+            let path = format!("{prefix}{}", field.name);
+            paths.push(path.clone());
+
+            if let Some((name, fields)) = struct_fields(registry, &field.value) {
+                if !visiting.contains(&name) {
+                    visiting.push(name);
+                    collect(registry, &format!("{path}."), fields, visiting, paths);
+                    visiting.pop();
+                }
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    if let Some(ContainerFormat::Struct(fields)) = registry.get(view_model) {
+        collect(registry, "", fields, &mut vec![view_model], &mut paths);
+    }
+    paths
+}
+
+/// The name of the constant for a path, in camel case, e.g. `userName` for `user.name`
+fn view_path_constant(path: &str) -> String {
+    let mut name = String::new();
+    for part in path.split(['.', '_']).filter(|part| !part.is_empty()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            if name.is_empty() {
+                name.extend(first.to_lowercase());
+            } else {
+                name.extend(first.to_uppercase());
+            }
+            name.push_str(chars.as_str());
+        }
+    }
+    name
+}
+
+/// The name in upper camel case, e.g. `SharedTypes` for `shared_types`, as the Swift
+/// generator names the module's source file
+fn upper_camel_case(name: &str) -> String {
+    name.split(['_', '-'])
+        .flat_map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect()
+}
+
+fn swift_view_paths(paths: &[String]) -> String {
+    let mut out = String::from("public enum ViewPath {\n");
+    for path in paths {
+        out.push_str(&format!(
+            "    public static let {} = \"{path}\"\n",
+            view_path_constant(path)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn java_view_paths(package_name: &str, paths: &[String]) -> String {
+    let mut out = format!(
+        "package {package_name};\n\npublic final class ViewPath {{\n    private ViewPath() {{}}\n\n"
+    );
+    for path in paths {
+        out.push_str(&format!(
+            "    public static final String {} = \"{path}\";\n",
+            path.replace('.', "_").to_uppercase()
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn typescript_view_paths(paths: &[String]) -> String {
+    let mut out = String::from("export const ViewPath = {\n");
+    for path in paths {
+        out.push_str(&format!("  {}: \"{path}\",\n", view_path_constant(path)));
+    }
+    out.push_str("} as const;\n");
+    out
+}
+
+/// The name of a constant in camel case, e.g. `maxItems` for `MAX_ITEMS`
+fn constant_name(name: &str) -> String {
+    let mut camel = String::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let part = part.to_lowercase();
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            if camel.is_empty() {
+                camel.push(first);
+            } else {
+                camel.extend(first.to_uppercase());
             }
+            camel.push_str(chars.as_str());
         }
     }
+    camel
+}
+
+/// The constant's value as a literal, which is the same in Swift, Java and TypeScript,
+/// apart from Java's long integers
+fn constant_literal(value: &Constant) -> String {
+    match value {
+        Constant::Bool(value) => value.to_string(),
+        Constant::Integer(value) => value.to_string(),
+        Constant::Float(value) => format!("{value:?}"),
+        Constant::String(value) => format!("{value:?}"),
+    }
+}
+
+fn swift_constants(constants: &[(String, Constant)]) -> String {
+    let mut out = String::from("public enum Constants {\n");
+    for (name, value) in constants {
+        let ty = match value {
+            Constant::Bool(_) => "Bool",
+            Constant::Integer(_) => "Int64",
+            Constant::Float(_) => "Double",
+            Constant::String(_) => "String",
+        };
+        out.push_str(&format!(
+            "    public static let {}: {ty} = {}\n",
+            constant_name(name),
+            constant_literal(value)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn java_constants(package_name: &str, constants: &[(String, Constant)]) -> String {
+    let mut out = format!(
+        "package {package_name};\n\npublic final class Constants {{\n    private Constants() {{}}\n\n"
+    );
+    for (name, value) in constants {
+        let (ty, suffix) = match value {
+            Constant::Bool(_) => ("boolean", ""),
+            Constant::Integer(_) => ("long", "L"),
+            Constant::Float(_) => ("double", ""),
+            Constant::String(_) => ("String", ""),
+        };
+        out.push_str(&format!(
+            "    public static final {ty} {} = {}{suffix};\n",
+            name.to_uppercase(),
+            constant_literal(value)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn typescript_constants(constants: &[(String, Constant)]) -> String {
+    let mut out = String::from("export const Constants = {\n");
+    for (name, value) in constants {
+        out.push_str(&format!(
+            "  {}: {},\n",
+            constant_name(name),
+            constant_literal(value)
+        ));
+    }
+    out.push_str("} as const;\n");
+    out
+}
+
+fn containers_with_nested_options(registry: &Registry) -> Vec<&str> {
+    registry
+        .iter()
+        .filter(|(_, container)| {
+            let mut formats: Vec<&Format> = Vec::new();
+            match container {
+                ContainerFormat::UnitStruct => {}
+                ContainerFormat::NewTypeStruct(format) => formats.push(format),
+                ContainerFormat::TupleStruct(fields) => formats.extend(fields),
+                ContainerFormat::Struct(fields) => formats.extend(fields.iter().map(|f| &f.value)),
+                ContainerFormat::Enum(variants) => {
+                    for variant in variants.values() {
+                        match &variant.value {
+                            VariantFormat::NewType(format) => formats.push(format),
+                            VariantFormat::Tuple(fields) => formats.extend(fields),
+                            VariantFormat::Struct(fields) => {
+                                formats.extend(fields.iter().map(|f| &f.value));
+                            }
+                            VariantFormat::Unit | VariantFormat::Variable(_) => {}
+                        }
+                    }
+                }
+            }
+            formats.into_iter().any(has_nested_option)
+        })
+        .map(|(name, _)| name.as_str())
+        .collect()
+}
+
+fn has_nested_option(format: &Format) -> bool {
+    match format {
+        Format::Option(inner) => {
+            matches!(inner.as_ref(), Format::Option(_)) || has_nested_option(inner)
+        }
+        Format::Seq(inner) | Format::TupleArray { content: inner, .. } => has_nested_option(inner),
+        Format::Map { key, value } => has_nested_option(key) || has_nested_option(value),
+        Format::Tuple(formats) => formats.iter().any(has_nested_option),
+        _ => false,
+    }
+}
+
+/// Normalize the whitespace in generated code the way formatters (swift-format, ktlint,
+/// prettier) leave it, so that it passes their checks when committed: no trailing
+/// whitespace, no leading or consecutive blank lines, and a single newline at the end.
+fn tidy(source: &str) -> String {
+    let mut tidy = String::with_capacity(source.len());
+    let mut blank = true;
+    for line in source.lines().map(str::trim_end) {
+        if line.is_empty() {
+            if !blank {
+                tidy.push('\n');
+            }
+            blank = true;
+        } else {
+            tidy.push_str(line);
+            tidy.push('\n');
+            blank = false;
+        }
+    }
+
+    let end = tidy.trim_end().len();
+    tidy.truncate(end);
+    tidy.push('\n');
+    tidy
+}
+
+/// [`tidy`] all the files with the `extension` in `dir` and its subdirectories.
+fn tidy_files(dir: &Path, extension: &str) -> Result {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            tidy_files(&path, extension)?;
+        } else if path.extension().map_or(false, |ext| ext == extension) {
+            let source = fs::read_to_string(&path)?;
+            let tidy = tidy(&source);
+            if tidy != source {
+                fs::write(&path, tidy)?;
+            }
+        }
+    }
+
+    Ok(())
 }
 
 fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result {
@@ -605,7 +1737,12 @@ fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result {
 #[cfg(feature = "typegen")]
 #[cfg(test)]
 mod tests {
-    use crate::typegen::TypeGen;
+    use std::collections::BTreeSet;
+
+    use crate::typegen::{
+        java_constants, swift_constants, tidy, typescript_constants, typescript_view_paths,
+        upper_camel_case, validators, view_paths, Constant, State, TypeGen,
+    };
     use serde::{Deserialize, Serialize};
     use uuid::Uuid;
 
@@ -635,4 +1772,140 @@ mod tests {
         let result = gen.register_type_with_samples(sample_data);
         assert!(result.is_ok(), "typegen failed with second sample data set");
     }
+
+    #[test]
+    fn test_tidy() {
+        let source = "\nimport Serde\n\n\n\nstruct A {  \n\tlet a: Int\t\n}\n\n\n";
+
+        assert_eq!(
+            tidy(source),
+            "import Serde\n\nstruct A {\n\tlet a: Int\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_view_paths() {
+        #[derive(Serialize, Deserialize)]
+        struct ViewModel {
+            title: String,
+            signed_in_user: Option<User>,
+            items: Vec<User>,
+        }
+
+        #[derive(Serialize, Deserialize)]
+        struct User {
+            name: String,
+            manager: Option<Box<User>>,
+        }
+
+        let mut gen = TypeGen::new();
+        gen.register_type::<ViewModel>().unwrap();
+        gen.ensure_registry().unwrap();
+        let State::Generating(registry) = &gen.state else {
+            panic!("expected a registry");
+        };
+
+        let paths = view_paths(registry, "ViewModel");
+        assert_eq!(
+            paths,
+            [
+                "title",
+                "signed_in_user",
+                "signed_in_user.name",
+                "signed_in_user.manager",
+                "items",
+            ]
+        );
+
+        assert_eq!(
+            typescript_view_paths(&paths[..3]),
+            "export const ViewPath = {\n  title: \"title\",\n  signedInUser: \"signed_in_user\",\n  signedInUserName: \"signed_in_user.name\",\n} as const;\n"
+        );
+    }
+
+    #[test]
+    fn test_typescript_validators() {
+        #[derive(Serialize, Deserialize)]
+        struct User {
+            name: String,
+            age: Option<u8>,
+            tags: Vec<String>,
+        }
+
+        #[derive(Serialize, Deserialize)]
+        enum Event {
+            SignOut,
+            SignIn(User),
+            Move { x: f64, y: f64 },
+        }
+
+        let mut gen = TypeGen::new();
+        gen.register_type::<Event>().unwrap();
+        gen.ensure_registry().unwrap();
+        let State::Generating(registry) = &gen.state else {
+            panic!("expected a registry");
+        };
+
+        let validators = validators::typescript(registry, &BTreeSet::new());
+
+        assert!(validators.contains(
+            "export function validateEvent(value: unknown, path = \"Event\"): string[] {"
+        ));
+        assert!(validators.contains("if (!([\"SignOut\"] as string[]).includes(value)) {"));
+        assert!(validators.contains("errors.push(...validateUser(content, `${path}.SignIn`));"));
+        assert!(validators.contains(
+            "          if (typeof content[\"x\"] !== \"number\") {\n            errors.push(`${path}.Move.x: expected a number`);"
+        ));
+        assert!(validators.contains(
+            "    if (value[\"age\"] !== null && value[\"age\"] !== undefined) {\n      if (!Number.isInteger(value[\"age\"])) {"
+        ));
+        assert!(validators.contains(
+            "      value[\"tags\"].forEach((item2: unknown, i2: number) => {\n        if (typeof item2 !== \"string\") {\n          errors.push(`${path}.tags[${i2}]: expected a string`);"
+        ));
+        assert!(validators.contains("export function isUser(value: unknown): boolean {"));
+        // unknown fields are tolerated
+        assert!(!validators.contains("unknown field"));
+
+        let validators = validators::typescript(registry, &BTreeSet::from(["Event".to_string()]));
+
+        assert!(validators.contains(
+            "          for (const key4 of Object.keys(content)) {\n            if (!([\"x\", \"y\"] as string[]).includes(key4)) {\n              errors.push(`${path}.Move.${key4}: unknown field`);"
+        ));
+        assert_eq!(validators.matches("unknown field").count(), 1);
+    }
+
+    #[test]
+    fn test_constants() {
+        let mut gen = TypeGen::new();
+        gen.register_constant("MAX_ITEMS", Constant::try_from(100_usize).unwrap())
+            .unwrap();
+        gen.register_constant("API_VERSION", "v\"2\"").unwrap();
+        gen.register_constant("ratio", 0.5).unwrap();
+        gen.register_constant("BETA", true).unwrap();
+        gen.register_constant("MAX_ITEMS", 200).unwrap();
+
+        assert!(gen.register_constant("max-items", 1).is_err());
+        assert!(gen.register_constant("LIMIT", f64::NAN).is_err());
+        assert!(Constant::try_from(u64::MAX).is_err());
+
+        assert_eq!(
+            swift_constants(&gen.constants),
+            "public enum Constants {\n    public static let apiVersion: String = \"v\\\"2\\\"\"\n    public static let ratio: Double = 0.5\n    public static let beta: Bool = true\n    public static let maxItems: Int64 = 200\n}\n"
+        );
+        assert_eq!(
+            java_constants("com.example", &gen.constants[3..]),
+            "package com.example;\n\npublic final class Constants {\n    private Constants() {}\n\n    public static final long MAX_ITEMS = 200L;\n}\n"
+        );
+        assert_eq!(
+            typescript_constants(&gen.constants[1..3]),
+            "export const Constants = {\n  ratio: 0.5,\n  beta: true,\n} as const;\n"
+        );
+    }
+
+    #[test]
+    fn test_upper_camel_case() {
+        assert_eq!(upper_camel_case("shared_types"), "SharedTypes");
+        assert_eq!(upper_camel_case("SharedTypes"), "SharedTypes");
+        assert_eq!(upper_camel_case("shared"), "Shared");
+    }
 }
@@ -0,0 +1,241 @@
+//! Accessors for the variants of the registered enums, e.g. `Event` and `Effect`
+//!
+//! The generated enums can only be taken apart with a `switch` or a type check, which every
+//! shell ends up wrapping in the same extension boilerplate. For each variant `V` of an enum
+//! `E`, the accessors say whether a value is a `V`, and return its payload if it is: the value
+//! of a newtype variant, or all the fields of a tuple or struct variant.
+
+use std::fmt::Write;
+
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+
+const HEADER: &str = "// Accessors for the variants of the shared enums\n";
+
+/// The enums in the `registry`, with their variants
+fn enums(registry: &Registry) -> impl Iterator<Item = (&String, Vec<&Named<VariantFormat>>)> {
+    registry
+        .iter()
+        .filter_map(|(name, container)| match container {
+            ContainerFormat::Enum(variants) => Some((name, variants.values().collect())),
+            _ => None,
+        })
+}
+
+/// Whether the `variant` carries any data
+fn has_payload(variant: &VariantFormat) -> bool {
+    match variant {
+        VariantFormat::Unit | VariantFormat::Variable(_) => false,
+        VariantFormat::NewType(_) => true,
+        VariantFormat::Tuple(formats) => !formats.is_empty(),
+        VariantFormat::Struct(fields) => !fields.is_empty(),
+    }
+}
+
+/// Swift computed properties in an extension of each enum, `isV` and, for variants with a
+/// payload, `asV`, which is `nil` for the other variants
+pub(super) fn swift(registry: &Registry) -> String {
+    let mut out = String::from(HEADER);
+
+    for (name, variants) in enums(registry) {
+        write!(out, "\nextension {name} {{").expect("writing to a String");
+
+        for variant in variants {
+            let case = lowercase_first_letter(&variant.name);
+
+            write!(
+                out,
+                r#"
+    public var is{0}: Bool {{
+        if case .{case} = self {{ return true }}
+        return false
+    }}
+"#,
+                variant.name
+            )
+            .expect("writing to a String");
+
+            if !has_payload(&variant.value) {
+                continue;
+            }
+
+            let (bindings, payload, payload_type) = match &variant.value {
+                VariantFormat::NewType(format) => {
+                    ("value".to_string(), "value".to_string(), swift_type(format))
+                }
+                VariantFormat::Tuple(formats) => {
+                    let names: Vec<_> = (0..formats.len()).map(|i| format!("field{i}")).collect();
+                    let types: Vec<_> = formats.iter().map(swift_type).collect();
+                    (
+                        names.join(", "),
+                        format!("({})", names.join(", ")),
+                        format!("({})", types.join(", ")),
+                    )
+                }
+                VariantFormat::Struct(fields) => {
+                    let names: Vec<_> = fields.iter().map(|field| field.name.as_str()).collect();
+                    let labelled: Vec<_> =
+                        names.iter().map(|name| format!("{name}: {name}")).collect();
+                    let types: Vec<_> = fields
+                        .iter()
+                        .map(|field| format!("{}: {}", field.name, swift_type(&field.value)))
+                        .collect();
+                    (
+                        names.join(", "),
+                        format!("({})", labelled.join(", ")),
+                        format!("({})", types.join(", ")),
+                    )
+                }
+                VariantFormat::Unit | VariantFormat::Variable(_) => unreachable!(),
+            };
+
+            write!(
+                out,
+                r#"
+    public var as{0}: {payload_type}? {{
+        if case let .{case}({bindings}) = self {{ return {payload} }}
+        return nil
+    }}
+"#,
+                variant.name
+            )
+            .expect("writing to a String");
+        }
+
+        out.push_str("}\n");
+    }
+
+    out
+}
+
+/// Kotlin extension properties on each of the generated Java classes, `isV` and, for
+/// variants with a payload, `asV`, which is `null` for the other variants. A newtype
+/// variant's payload is its value, other variants are returned whole, with their fields.
+pub(super) fn kotlin(package_name: &str, registry: &Registry) -> String {
+    let mut out = format!("{HEADER}package {package_name}\n");
+
+    for (name, variants) in enums(registry) {
+        for variant in variants {
+            let class = format!("{name}.{}", variant.name);
+
+            write!(
+                out,
+                "\nval {name}.is{}: Boolean\n    get() = this is {class}\n",
+                variant.name
+            )
+            .expect("writing to a String");
+
+            let payload = match &variant.value {
+                VariantFormat::NewType(_) => format!("(this as? {class})?.value"),
+                variant if has_payload(variant) => format!("this as? {class}"),
+                _ => continue,
+            };
+            write!(
+                out,
+                "\nval {name}.as{}\n    get() = {payload}\n",
+                variant.name
+            )
+            .expect("writing to a String");
+        }
+    }
+
+    out
+}
+
+/// TypeScript functions for each variant, `isEV(value)`, narrowing the type of the value,
+/// and, for variants with a payload, `asEV(value)`, which is `null` for the other variants.
+/// A newtype variant's payload is its value, other variants are returned whole.
+pub(super) fn typescript(module_name: &str, registry: &Registry) -> String {
+    let mut imports = Vec::new();
+    let mut functions = String::new();
+
+    for (name, variants) in enums(registry) {
+        imports.push(name.clone());
+
+        for variant in variants {
+            let class = format!("{name}Variant{}", variant.name);
+            imports.push(class.clone());
+
+            write!(
+                functions,
+                r#"
+export function is{name}{0}(value: {name}): value is {class} {{
+  return value instanceof {class};
+}}
+"#,
+                variant.name
+            )
+            .expect("writing to a String");
+
+            let payload = match &variant.value {
+                VariantFormat::NewType(_) => "value.value",
+                variant if has_payload(variant) => "value",
+                _ => continue,
+            };
+            write!(
+                functions,
+                r#"
+export function as{name}{0}(value: {name}) {{
+  return value instanceof {class} ? {payload} : null;
+}}
+"#,
+                variant.name
+            )
+            .expect("writing to a String");
+        }
+    }
+
+    format!(
+        "{HEADER}\nimport {{ {} }} from \"./{module_name}\";\n{functions}",
+        imports.join(", ")
+    )
+}
+
+/// The Swift type serde-generate gives the `format`
+fn swift_type(format: &Format) -> String {
+    match format {
+        Format::TypeName(name) => name.clone(),
+        Format::Unit => "Unit".into(),
+        Format::Bool => "Bool".into(),
+        Format::I8 => "Int8".into(),
+        Format::I16 => "Int16".into(),
+        Format::I32 => "Int32".into(),
+        Format::I64 => "Int64".into(),
+        Format::I128 => "Int128".into(),
+        Format::U8 => "UInt8".into(),
+        Format::U16 => "UInt16".into(),
+        Format::U32 => "UInt32".into(),
+        Format::U64 => "UInt64".into(),
+        Format::U128 => "UInt128".into(),
+        Format::F32 => "Float".into(),
+        Format::F64 => "Double".into(),
+        Format::Char => "Character".into(),
+        Format::Str => "String".into(),
+        Format::Bytes => "[UInt8]".into(),
+        Format::Option(format) => format!("{}?", swift_type(format)),
+        Format::Seq(format)
+        | Format::TupleArray {
+            content: format, ..
+        } => {
+            format!("[{}]", swift_type(format))
+        }
+        Format::Map { key, value } => format!("[{}: {}]", swift_type(key), swift_type(value)),
+        Format::Tuple(formats) => format!(
+            "Tuple{}<{}>",
+            formats.len(),
+            formats
+                .iter()
+                .map(swift_type)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Format::Variable(_) => panic!("unexpected variable format"),
+    }
+}
+
+fn lowercase_first_letter(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
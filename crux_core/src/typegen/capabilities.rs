@@ -0,0 +1,170 @@
+//! A table of the app's capabilities, by the variant of the effect type which requests them
+//!
+//! Shells dispatch the effects they receive on the variant of `Effect`, and devtools label
+//! the traffic between the core and the shell with it. The table maps each variant to the
+//! name of the capability's field of the app's `Capabilities`, the name of its operation
+//! type, and the doc comment of the field, so neither has to keep its own list in sync.
+
+use std::collections::BTreeMap;
+
+use crate::capability::CapabilityInfo;
+
+const HEADER: &str =
+    "// The capabilities of the app, by the variant of the effect type requesting them\n";
+
+/// A row of the table
+pub(super) struct Capability {
+    effect: String,
+    capability: String,
+    operation: String,
+    doc: String,
+}
+
+/// The rows for the registered `capabilities`. The operation is the name of its type in the
+/// generated code, found in the `operations` for the variant, which accounts for renamed
+/// types. Capabilities without one use the last segment of the type's Rust name.
+pub(super) fn rows(
+    capabilities: &[CapabilityInfo],
+    operations: &BTreeMap<String, String>,
+) -> Vec<Capability> {
+    capabilities
+        .iter()
+        .map(|info| Capability {
+            effect: info.effect.to_string(),
+            capability: info.field.to_string(),
+            operation: operations.get(info.effect).cloned().unwrap_or_else(|| {
+                let path = info.operation.split('<').next().unwrap_or(info.operation);
+                path.rsplit("::").next().unwrap_or(path).to_string()
+            }),
+            doc: info.doc.to_string(),
+        })
+        .collect()
+}
+
+/// A `CapabilityInfo` struct, and a `CapabilityTable` enum with `all` the rows and a
+/// `forEffect(_:)` lookup
+pub(super) fn swift(rows: &[Capability]) -> String {
+    let mut out = String::from(HEADER);
+    out.push_str(
+        r#"
+public struct CapabilityInfo {
+    public let effect: String
+    public let capability: String
+    public let operation: String
+    public let doc: String
+}
+
+public enum CapabilityTable {
+    public static let all: [CapabilityInfo] = [
+"#,
+    );
+    for row in rows {
+        out.push_str(&format!(
+            "        CapabilityInfo(effect: {:?}, capability: {:?}, operation: {:?}, doc: {:?}),\n",
+            row.effect, row.capability, row.operation, row.doc
+        ));
+    }
+    out.push_str(
+        r#"    ]
+
+    public static func forEffect(_ effect: String) -> CapabilityInfo? {
+        all.first { $0.effect == effect }
+    }
+}
+"#,
+    );
+    out
+}
+
+/// A `CapabilityTable` class with a nested `Info` class, the `ALL` rows and a `forEffect`
+/// lookup, which returns `null` for an unknown variant
+pub(super) fn java(package_name: &str, rows: &[Capability]) -> String {
+    let mut out = format!("package {package_name};\n\n{HEADER}");
+    out.push_str(
+        r#"
+import java.util.Arrays;
+import java.util.Collections;
+import java.util.List;
+
+public final class CapabilityTable {
+    private CapabilityTable() {}
+
+    public static final class Info {
+        public final String effect;
+        public final String capability;
+        public final String operation;
+        public final String doc;
+
+        Info(String effect, String capability, String operation, String doc) {
+            this.effect = effect;
+            this.capability = capability;
+            this.operation = operation;
+            this.doc = doc;
+        }
+    }
+
+    public static final List<Info> ALL = Collections.unmodifiableList(Arrays.asList(
+"#,
+    );
+    let infos: Vec<_> = rows
+        .iter()
+        .map(|row| {
+            format!(
+                "        new Info({:?}, {:?}, {:?}, {:?})",
+                row.effect, row.capability, row.operation, row.doc
+            )
+        })
+        .collect();
+    out.push_str(&infos.join(",\n"));
+    out.push_str(
+        r#"
+    ));
+
+    public static Info forEffect(String effect) {
+        for (Info info : ALL) {
+            if (info.effect.equals(effect)) {
+                return info;
+            }
+        }
+        return null;
+    }
+}
+"#,
+    );
+    out
+}
+
+/// A `CapabilityInfo` interface, the `CapabilityTable` rows and a `capabilityForEffect`
+/// lookup
+pub(super) fn typescript(rows: &[Capability]) -> String {
+    let mut out = String::from(HEADER);
+    out.push_str(
+        r#"
+export interface CapabilityInfo {
+  effect: string;
+  capability: string;
+  operation: string;
+  doc: string;
+}
+
+export const CapabilityTable: readonly CapabilityInfo[] = [
+"#,
+    );
+    for row in rows {
+        out.push_str(&format!(
+            "  {{ effect: {:?}, capability: {:?}, operation: {:?}, doc: {:?} }},\n",
+            row.effect, row.capability, row.operation, row.doc
+        ));
+    }
+    out.push_str(
+        r#"];
+
+export function capabilityForEffect(
+  effect: string,
+): CapabilityInfo | undefined {
+  return CapabilityTable.find((info) => info.effect === effect);
+}
+"#,
+    );
+    out
+}
@@ -0,0 +1,95 @@
+//! The list of the files generated for a language, kept next to them for the next run
+//!
+//! When a type is removed from the core, or an option like enum accessors is turned off,
+//! the next run doesn't write its file, but the file from the earlier run stays, and keeps
+//! the old type compiling in the shell. Each run lists the files it wrote, and deletes the
+//! ones the earlier run listed but it didn't write. Files the generator didn't write, like
+//! the shell's own extensions, are never listed, so they are left alone.
+
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The name of the list, in the language's output directory
+pub(super) const FILE_NAME: &str = ".generated_files.json";
+
+#[derive(Serialize, Deserialize, Default)]
+struct List {
+    files: BTreeSet<String>,
+}
+
+pub(super) struct GeneratedFiles {
+    root: PathBuf,
+    files: BTreeSet<String>,
+}
+
+impl GeneratedFiles {
+    pub(super) fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            files: BTreeSet::new(),
+        }
+    }
+
+    /// Whether an earlier run left a list in `root`
+    pub(super) fn exists(root: &Path) -> bool {
+        root.join(FILE_NAME).is_file()
+    }
+
+    /// Write the file at `path`, relative to the root, and list it
+    pub(super) fn write(
+        &mut self,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        fs::write(self.root.join(path.as_ref()), contents)?;
+        self.add(path);
+        Ok(())
+    }
+
+    /// List the file at `path`, relative to the root, written by the code generator
+    pub(super) fn add(&mut self, path: impl AsRef<Path>) {
+        let path = path
+            .as_ref()
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.files.insert(path);
+    }
+
+    /// Delete the files the earlier run listed which weren't written this time, and replace
+    /// the list
+    pub(super) fn finish(self) -> io::Result<()> {
+        let list_path = self.root.join(FILE_NAME);
+        let previous: List = match fs::read(&list_path) {
+            Ok(list) => serde_json::from_slice(&list).unwrap_or_default(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => List::default(),
+            Err(e) => return Err(e),
+        };
+
+        for stale in previous.files.difference(&self.files) {
+            let path = Path::new(stale);
+            // only ever delete inside the output directory
+            if !path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                continue;
+            }
+            match fs::remove_file(self.root.join(path)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+
+        let list = List { files: self.files };
+        let mut json = serde_json::to_string_pretty(&list).expect("file list should serialize");
+        json.push('\n');
+        fs::write(list_path, json)
+    }
+}
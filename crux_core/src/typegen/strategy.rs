@@ -0,0 +1,215 @@
+//! Proptest strategies for the registered types, built from their serde formats
+//!
+//! The strategies generate values encoded with bincode, as the shell sends them across the
+//! bridge, following the format, and deserialize them into the Rust type. So every value is
+//! one the shell could have sent.
+
+use std::fmt::Debug;
+
+use proptest::{
+    collection::vec,
+    prelude::*,
+    strategy::{BoxedStrategy, Just, Union},
+};
+use serde::de::DeserializeOwned;
+use serde_reflection::{ContainerFormat, Format, Registry, VariantFormat};
+
+use super::TypeGenError;
+
+/// How deep values get before options, sequences and maps are left empty, and enums only
+/// use variants which don't contain other types, so that recursive types stay finite.
+const MAX_DEPTH: usize = 4;
+/// The most elements in generated sequences and maps
+const MAX_LEN: usize = 4;
+
+type Bytes = Vec<u8>;
+
+pub(super) fn strategy<T>(registry: &Registry, name: &str) -> Result<BoxedStrategy<T>, TypeGenError>
+where
+    T: DeserializeOwned + Debug + 'static,
+{
+    let Some(container) = registry.get(name) else {
+        return Err(TypeGenError::Generation(format!(
+            "{name} is not a registered type"
+        )));
+    };
+
+    Ok(container_strategy(registry, container, 0)
+        // e.g. a `Uuid` is sent as bytes, but not all bytes are a valid `Uuid`
+        .prop_filter_map(
+            "rejected by the type's Deserialize implementation",
+            |bytes| bincode::deserialize(&bytes).ok(),
+        )
+        .boxed())
+}
+
+fn container_strategy(
+    registry: &Registry,
+    container: &ContainerFormat,
+    depth: usize,
+) -> BoxedStrategy<Bytes> {
+    match container {
+        ContainerFormat::UnitStruct => Just(vec![]).boxed(),
+        ContainerFormat::NewTypeStruct(format) => format_strategy(registry, format, depth),
+        ContainerFormat::TupleStruct(formats) => fields_strategy(registry, formats.iter(), depth),
+        ContainerFormat::Struct(fields) => {
+            fields_strategy(registry, fields.iter().map(|field| &field.value), depth)
+        }
+        ContainerFormat::Enum(variants) => {
+            let shallow: Vec<_> = variants
+                .iter()
+                .filter(|(_, variant)| depth < MAX_DEPTH || is_shallow(&variant.value))
+                .collect();
+            let variants = if shallow.is_empty() {
+                variants.iter().collect()
+            } else {
+                shallow
+            };
+            if variants.is_empty() || depth > 4 * MAX_DEPTH {
+                // no value can be generated, and deserializing nothing will fail
+                return Just(vec![]).boxed();
+            }
+
+            Union::new(variants.into_iter().map(|(index, variant)| {
+                let index = *index;
+                variant_strategy(registry, &variant.value, depth)
+                    .prop_map(move |value| [&index.to_le_bytes()[..], &value].concat())
+                    .boxed()
+            }))
+            .boxed()
+        }
+    }
+}
+
+fn variant_strategy(
+    registry: &Registry,
+    variant: &VariantFormat,
+    depth: usize,
+) -> BoxedStrategy<Bytes> {
+    match variant {
+        VariantFormat::Unit | VariantFormat::Variable(_) => Just(vec![]).boxed(),
+        VariantFormat::NewType(format) => format_strategy(registry, format, depth),
+        VariantFormat::Tuple(formats) => fields_strategy(registry, formats.iter(), depth),
+        VariantFormat::Struct(fields) => {
+            fields_strategy(registry, fields.iter().map(|field| &field.value), depth)
+        }
+    }
+}
+
+/// Struct fields and tuple elements, which are encoded one after the other
+fn fields_strategy<'a>(
+    registry: &Registry,
+    formats: impl Iterator<Item = &'a Format>,
+    depth: usize,
+) -> BoxedStrategy<Bytes> {
+    let fields: Vec<_> = formats
+        .map(|format| format_strategy(registry, format, depth))
+        .collect();
+
+    fields.prop_map(|fields| fields.concat()).boxed()
+}
+
+fn format_strategy(registry: &Registry, format: &Format, depth: usize) -> BoxedStrategy<Bytes> {
+    let len = if depth < MAX_DEPTH {
+        0..=MAX_LEN
+    } else {
+        0..=0
+    };
+
+    match format {
+        Format::TypeName(name) => match registry.get(name) {
+            Some(container) => container_strategy(registry, container, depth + 1),
+            None => Just(vec![]).boxed(),
+        },
+        Format::Variable(_) | Format::Unit => Just(vec![]).boxed(),
+        Format::Bool => any::<bool>().prop_map(|b| vec![u8::from(b)]).boxed(),
+        Format::I8 => primitive::<i8>(),
+        Format::I16 => primitive::<i16>(),
+        Format::I32 => primitive::<i32>(),
+        Format::I64 => primitive::<i64>(),
+        Format::I128 => primitive::<i128>(),
+        Format::U8 => primitive::<u8>(),
+        Format::U16 => primitive::<u16>(),
+        Format::U32 => primitive::<u32>(),
+        Format::U64 => primitive::<u64>(),
+        Format::U128 => primitive::<u128>(),
+        Format::F32 => primitive::<f32>(),
+        Format::F64 => primitive::<f64>(),
+        Format::Char => primitive::<char>(),
+        Format::Str => primitive::<String>(),
+        Format::Bytes => vec(any::<u8>(), 0..=4 * MAX_LEN)
+            .prop_map(|bytes| with_len(bytes.len(), vec![bytes]))
+            .boxed(),
+        Format::Option(format) if depth < MAX_DEPTH => prop_oneof![
+            Just(vec![0]),
+            format_strategy(registry, format, depth).prop_map(|value| [&[1], &value[..]].concat()),
+        ]
+        .boxed(),
+        Format::Option(_) => Just(vec![0]).boxed(),
+        Format::Seq(format) => vec(format_strategy(registry, format, depth), len)
+            .prop_map(|items| with_len(items.len(), items))
+            .boxed(),
+        Format::Map { key, value } => vec(
+            (
+                format_strategy(registry, key, depth),
+                format_strategy(registry, value, depth),
+            ),
+            len,
+        )
+        .prop_map(|entries| {
+            with_len(
+                entries.len(),
+                entries
+                    .into_iter()
+                    .flat_map(|(key, value)| [key, value])
+                    .collect(),
+            )
+        })
+        .boxed(),
+        Format::Tuple(formats) => fields_strategy(registry, formats.iter(), depth),
+        // arrays have no length prefix
+        Format::TupleArray { content, size } => {
+            vec(format_strategy(registry, content, depth), *size..=*size)
+                .prop_map(|items| items.concat())
+                .boxed()
+        }
+    }
+}
+
+/// A primitive value, encoded by bincode
+fn primitive<T>() -> BoxedStrategy<Bytes>
+where
+    T: Arbitrary + serde::Serialize + 'static,
+{
+    any::<T>()
+        .prop_map(|value| bincode::serialize(&value).expect("primitives can be serialized"))
+        .boxed()
+}
+
+/// The items of a sequence, after its length
+fn with_len(len: usize, items: Vec<Bytes>) -> Bytes {
+    let mut bytes = (len as u64).to_le_bytes().to_vec();
+    bytes.extend(items.concat());
+    bytes
+}
+
+/// Whether the variant's data doesn't refer to other registered types
+fn is_shallow(variant: &VariantFormat) -> bool {
+    fn shallow(format: &Format) -> bool {
+        match format {
+            Format::TypeName(_) => false,
+            Format::Option(format) | Format::Seq(format) => shallow(format),
+            Format::Map { key, value } => shallow(key) && shallow(value),
+            Format::Tuple(formats) => formats.iter().all(shallow),
+            Format::TupleArray { content, .. } => shallow(content),
+            _ => true,
+        }
+    }
+
+    match variant {
+        VariantFormat::Unit | VariantFormat::Variable(_) => true,
+        VariantFormat::NewType(format) => shallow(format),
+        VariantFormat::Tuple(formats) => formats.iter().all(shallow),
+        VariantFormat::Struct(fields) => fields.iter().all(|field| shallow(&field.value)),
+    }
+}
@@ -0,0 +1,321 @@
+//! Runtime validators for the JSON representation of the registered types, for TypeScript
+//!
+//! The generated TypeScript classes are (de)serialized with bincode across the bridge, but
+//! web shells also receive the same types as JSON, e.g. from a server using `serde_json`.
+//! For each type `T`, `validateT(value)` returns the places where a parsed JSON value
+//! doesn't match the serde JSON representation of `T`, and `isT(value)` whether it does.
+//!
+//! Objects may have fields the type doesn't know, as serde ignores them by default, unless
+//! the type was registered as denying unknown fields.
+
+use std::{collections::BTreeSet, fmt::Write};
+
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+
+const HEADER: &str = r#"// Validators for the JSON representation of the shared types, as serialized by serde_json
+
+function isObject(value: unknown): value is Record<string, unknown> {
+  return typeof value === "object" && value !== null && !Array.isArray(value);
+}
+"#;
+
+pub(super) fn typescript(registry: &Registry, deny_unknown_fields: &BTreeSet<String>) -> String {
+    let mut out = String::from(HEADER);
+
+    for (name, container) in registry {
+        let mut body = String::new();
+        container_checks(&mut body, container, deny_unknown_fields.contains(name));
+
+        write!(
+            out,
+            r#"
+export function validate{name}(value: unknown, path = "{name}"): string[] {{
+  const errors: string[] = [];
+{body}  return errors;
+}}
+
+export function is{name}(value: unknown): boolean {{
+  return validate{name}(value).length === 0;
+}}
+"#
+        )
+        .expect("writing to a String");
+    }
+
+    out
+}
+
+fn container_checks(out: &mut String, container: &ContainerFormat, deny_unknown_fields: bool) {
+    match container {
+        ContainerFormat::UnitStruct => checks(out, &Format::Unit, "value", "${path}", 1),
+        ContainerFormat::NewTypeStruct(format) => checks(out, format, "value", "${path}", 1),
+        ContainerFormat::TupleStruct(formats) => {
+            checks(out, &Format::Tuple(formats.clone()), "value", "${path}", 1)
+        }
+        ContainerFormat::Struct(fields) => {
+            struct_checks(out, fields, "value", "${path}", 1, deny_unknown_fields)
+        }
+        ContainerFormat::Enum(variants) => {
+            let units: Vec<_> = variants
+                .values()
+                .filter(|variant| matches!(variant.value, VariantFormat::Unit))
+                .map(|variant| format!("{:?}", variant.name))
+                .collect();
+
+            line(out, 1, "if (typeof value === \"string\") {");
+            line(
+                out,
+                2,
+                &format!(
+                    "if (!([{}] as string[]).includes(value)) {{",
+                    units.join(", ")
+                ),
+            );
+            push(out, 3, "${path}", "unknown variant ${value}");
+            line(out, 2, "}");
+            line(
+                out,
+                1,
+                "} else if (isObject(value) && Object.keys(value).length === 1) {",
+            );
+            line(
+                out,
+                2,
+                "const [variant, content] = Object.entries(value)[0];",
+            );
+            line(out, 2, "switch (variant) {");
+            for variant in variants.values() {
+                let path = format!("${{path}}.{}", variant.name);
+                let format = match &variant.value {
+                    VariantFormat::Unit => continue,
+                    VariantFormat::NewType(format) => (**format).clone(),
+                    VariantFormat::Tuple(formats) => Format::Tuple(formats.clone()),
+                    VariantFormat::Struct(fields) => {
+                        line(out, 3, &format!("case {:?}:", variant.name));
+                        struct_checks(out, fields, "content", &path, 4, deny_unknown_fields);
+                        line(out, 4, "break;");
+                        continue;
+                    }
+                    VariantFormat::Variable(_) => continue,
+                };
+                line(out, 3, &format!("case {:?}:", variant.name));
+                checks(out, &format, "content", &path, 4);
+                line(out, 4, "break;");
+            }
+            line(out, 3, "default:");
+            push(out, 4, "${path}", "unknown variant ${variant}");
+            line(out, 2, "}");
+            line(out, 1, "} else {");
+            push(out, 2, "${path}", "expected a variant");
+            line(out, 1, "}");
+        }
+    }
+}
+
+fn struct_checks(
+    out: &mut String,
+    fields: &[Named<Format>],
+    value: &str,
+    path: &str,
+    indent: usize,
+    deny_unknown_fields: bool,
+) {
+    line(out, indent, &format!("if (!isObject({value})) {{"));
+    push(out, indent + 1, path, "expected an object");
+    line(out, indent, "} else {");
+    if deny_unknown_fields {
+        let names: Vec<_> = fields
+            .iter()
+            .map(|field| format!("{:?}", field.name))
+            .collect();
+        let key = format!("key{indent}");
+        line(
+            out,
+            indent + 1,
+            &format!("for (const {key} of Object.keys({value})) {{"),
+        );
+        line(
+            out,
+            indent + 2,
+            &format!(
+                "if (!([{}] as string[]).includes({key})) {{",
+                names.join(", ")
+            ),
+        );
+        push(
+            out,
+            indent + 3,
+            &format!("{path}.${{{key}}}"),
+            "unknown field",
+        );
+        line(out, indent + 2, "}");
+        line(out, indent + 1, "}");
+    }
+    for field in fields {
+        checks(
+            out,
+            &field.value,
+            &format!("{value}[{:?}]", field.name),
+            &format!("{path}.{}", field.name),
+            indent + 1,
+        );
+    }
+    line(out, indent, "}");
+}
+
+/// Statements checking the JSON `value` against the `format`. The `value` is a TypeScript
+/// expression, and the `path` the errors are reported at is the body of a template literal.
+fn checks(out: &mut String, format: &Format, value: &str, path: &str, indent: usize) {
+    match format {
+        Format::Unit => {
+            line(out, indent, &format!("if ({value} !== null) {{"));
+            push(out, indent + 1, path, "expected null");
+            line(out, indent, "}");
+        }
+        Format::Bool => type_check(out, value, path, "boolean", indent),
+        Format::Str | Format::Char => type_check(out, value, path, "string", indent),
+        Format::F32 | Format::F64 => type_check(out, value, path, "number", indent),
+        Format::I8
+        | Format::I16
+        | Format::I32
+        | Format::I64
+        | Format::I128
+        | Format::U8
+        | Format::U16
+        | Format::U32
+        | Format::U64
+        | Format::U128 => {
+            line(out, indent, &format!("if (!Number.isInteger({value})) {{"));
+            push(out, indent + 1, path, "expected an integer");
+            line(out, indent, "}");
+        }
+        Format::Bytes => checks(out, &Format::Seq(Box::new(Format::U8)), value, path, indent),
+        Format::Option(format) => {
+            line(
+                out,
+                indent,
+                &format!("if ({value} !== null && {value} !== undefined) {{"),
+            );
+            checks(out, format, value, path, indent + 1);
+            line(out, indent, "}");
+        }
+        Format::Seq(format) => array_checks(out, format, None, value, path, indent),
+        Format::TupleArray { content, size } => {
+            array_checks(out, content, Some(*size), value, path, indent)
+        }
+        Format::Map { value: format, .. } => {
+            let (key, item) = (format!("key{indent}"), format!("item{indent}"));
+            line(out, indent, &format!("if (!isObject({value})) {{"));
+            push(out, indent + 1, path, "expected an object");
+            line(out, indent, "} else {");
+            line(
+                out,
+                indent + 1,
+                &format!("for (const [{key}, {item}] of Object.entries({value})) {{"),
+            );
+            checks(
+                out,
+                format,
+                &item,
+                &format!("{path}.${{{key}}}"),
+                indent + 2,
+            );
+            line(out, indent + 1, "}");
+            line(out, indent, "}");
+        }
+        Format::Tuple(formats) => {
+            line(
+                out,
+                indent,
+                &format!(
+                    "if (!Array.isArray({value}) || {value}.length !== {}) {{",
+                    formats.len()
+                ),
+            );
+            push(
+                out,
+                indent + 1,
+                path,
+                &format!("expected an array of {}", formats.len()),
+            );
+            line(out, indent, "} else {");
+            for (position, format) in formats.iter().enumerate() {
+                checks(
+                    out,
+                    format,
+                    &format!("{value}[{position}]"),
+                    &format!("{path}[{position}]"),
+                    indent + 1,
+                );
+            }
+            line(out, indent, "}");
+        }
+        Format::TypeName(name) => line(
+            out,
+            indent,
+            &format!("errors.push(...validate{name}({value}, `{path}`));"),
+        ),
+        // not left in a finished registry
+        Format::Variable(_) => {}
+    }
+}
+
+/// Statements checking the JSON `value` is an array of `size` elements, if it's fixed, which
+/// each match the `format`
+fn array_checks(
+    out: &mut String,
+    format: &Format,
+    size: Option<usize>,
+    value: &str,
+    path: &str,
+    indent: usize,
+) {
+    let item = format!("item{indent}");
+    let index = format!("i{indent}");
+    let length = match size {
+        Some(size) => format!(" || {value}.length !== {size}"),
+        None => String::new(),
+    };
+
+    line(
+        out,
+        indent,
+        &format!("if (!Array.isArray({value}){length}) {{"),
+    );
+    push(out, indent + 1, path, "expected an array");
+    line(out, indent, "} else {");
+    line(
+        out,
+        indent + 1,
+        &format!("{value}.forEach(({item}: unknown, {index}: number) => {{"),
+    );
+    checks(
+        out,
+        format,
+        &item,
+        &format!("{path}[${{{index}}}]"),
+        indent + 2,
+    );
+    line(out, indent + 1, "});");
+    line(out, indent, "}");
+}
+
+fn type_check(out: &mut String, value: &str, path: &str, expected: &str, indent: usize) {
+    line(
+        out,
+        indent,
+        &format!("if (typeof {value} !== \"{expected}\") {{"),
+    );
+    push(out, indent + 1, path, &format!("expected a {expected}"));
+    line(out, indent, "}");
+}
+
+fn push(out: &mut String, indent: usize, path: &str, message: &str) {
+    line(out, indent, &format!("errors.push(`{path}: {message}`);"));
+}
+
+fn line(out: &mut String, indent: usize, text: &str) {
+    out.push_str(&"  ".repeat(indent));
+    out.push_str(text);
+    out.push('\n');
+}
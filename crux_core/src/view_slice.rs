@@ -0,0 +1,681 @@
+//! Partial views, for shells which only need a few fields of a large view model
+//!
+//! Instead of the whole view model, a shell can ask the bridge for a [`ViewSlice`], listing the
+//! fields it needs by their paths, e.g. `"user.name"` or `"items.0.title"`. Each path is a
+//! list of struct field names, map keys, sequence indices and enum variant names, separated by
+//! dots. Newtypes and `Some` are looked through, so `"user.name"` works for an `Option<User>`
+//! field too, and the empty path `""` stands for the whole view model.
+//!
+//! The slice is serialized as a sequence with an `Option` for each path, in the order they were
+//! requested, holding the field's value in its own type, or `None` when the path doesn't lead
+//! anywhere, e.g. because an optional field is empty or a variant doesn't match. The type
+//! generation writes the paths of the view model's structs as constants for each language,
+//! named `ViewPath`.
+
+use std::fmt;
+
+use serde::ser::{self, Impossible, Serialize, SerializeSeq, Serializer};
+
+/// The fields of a view model at the given `paths`, serialized as a sequence of options.
+/// See the [module documentation](self) for the path syntax.
+pub struct ViewSlice<'a, T: ?Sized> {
+    value: &'a T,
+    paths: &'a [String],
+}
+
+impl<'a, T> ViewSlice<'a, T>
+where
+    T: Serialize + ?Sized,
+{
+    pub fn new(value: &'a T, paths: &'a [String]) -> Self {
+        Self { value, paths }
+    }
+}
+
+impl<T> Serialize for ViewSlice<'_, T>
+where
+    T: Serialize + ?Sized,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.paths.len()))?;
+        for path in self.paths {
+            seq.serialize_element(&Field {
+                value: self.value,
+                path,
+            })?;
+        }
+        seq.end()
+    }
+}
+
+/// The value at `path`, serialized as `Some(value)`, or `None` if there is nothing there
+struct Field<'a, T: ?Sized> {
+    value: &'a T,
+    path: &'a str,
+}
+
+impl<T> Serialize for Field<'_, T>
+where
+    T: Serialize + ?Sized,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let path: Vec<&str> = if self.path.is_empty() {
+            vec![]
+        } else {
+            self.path.split('.').collect()
+        };
+
+        let mut slot = Some(serializer);
+        match descend(self.value, &path, &mut slot)? {
+            Some(ok) => Ok(ok),
+            None => slot
+                .take()
+                .expect("serializer is only used once the field is found")
+                .serialize_none(),
+        }
+    }
+}
+
+/// Serialize the value at `path` inside `value` into the serializer in `slot`,
+/// or return `None` without touching it if there's no such value
+fn descend<T, S>(value: &T, path: &[&str], slot: &mut Option<S>) -> Result<Option<S::Ok>, S::Error>
+where
+    T: Serialize + ?Sized,
+    S: Serializer,
+{
+    if path.is_empty() {
+        let serializer = slot.take().expect("a field is only found once");
+        return serializer.serialize_some(value).map(Some);
+    }
+
+    value.serialize(Find { path, slot })
+}
+
+/// A serializer which walks the serde data model of a value looking for a path,
+/// and ignores everything else
+struct Find<'a, 'p, S> {
+    path: &'p [&'p str],
+    slot: &'a mut Option<S>,
+}
+
+/// Looks for the next segment of a path in the fields, elements or entries of a value
+struct FindIn<'a, 'p, S: Serializer> {
+    find: Find<'a, 'p, S>,
+    index: usize,
+    key: Option<String>,
+    found: Option<S::Ok>,
+}
+
+impl<'a, 'p, S: Serializer> FindIn<'a, 'p, S> {
+    fn new(find: Find<'a, 'p, S>) -> Self {
+        Self {
+            find,
+            index: 0,
+            key: None,
+            found: None,
+        }
+    }
+
+    /// Nothing can match, e.g. in a variant other than the one the path leads to
+    fn nowhere(slot: &'a mut Option<S>) -> Self {
+        Self::new(Find { path: &[], slot })
+    }
+
+    fn field<T>(&mut self, name: &str, value: &T) -> Result<(), S::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        if self.found.is_none() && self.find.path.first() == Some(&name) {
+            self.found = descend(value, &self.find.path[1..], self.find.slot)?;
+        }
+        Ok(())
+    }
+
+    fn element<T>(&mut self, value: &T) -> Result<(), S::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let index = self.index.to_string();
+        self.index += 1;
+        self.field(&index, value)
+    }
+
+    fn end(self) -> Result<Option<S::Ok>, S::Error> {
+        Ok(self.found)
+    }
+}
+
+impl<'a, 'p, S> Find<'a, 'p, S>
+where
+    S: Serializer,
+{
+    fn matches(&self, name: &str) -> bool {
+        self.path.first() == Some(&name)
+    }
+
+    /// Continue inside a variant, if the path leads to it
+    fn variant(self, variant: &str) -> FindIn<'a, 'p, S> {
+        if self.matches(variant) {
+            FindIn::new(Find {
+                path: &self.path[1..],
+                slot: self.slot,
+            })
+        } else {
+            FindIn::nowhere(self.slot)
+        }
+    }
+}
+
+macro_rules! not_found {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, _value: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(None)
+            }
+        )*
+    };
+}
+
+impl<'a, 'p, S> Serializer for Find<'a, 'p, S>
+where
+    S: Serializer,
+{
+    type Ok = Option<S::Ok>;
+    type Error = S::Error;
+
+    type SerializeSeq = FindIn<'a, 'p, S>;
+    type SerializeTuple = FindIn<'a, 'p, S>;
+    type SerializeTupleStruct = FindIn<'a, 'p, S>;
+    type SerializeTupleVariant = FindIn<'a, 'p, S>;
+    type SerializeMap = FindIn<'a, 'p, S>;
+    type SerializeStruct = FindIn<'a, 'p, S>;
+    type SerializeStructVariant = FindIn<'a, 'p, S>;
+
+    not_found!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        if !self.matches(variant) {
+            return Ok(None);
+        }
+        descend(value, &self.path[1..], self.slot)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(FindIn::new(self))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(FindIn::new(self))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(FindIn::new(self))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(self.variant(variant))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(FindIn::new(self))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(FindIn::new(self))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(self.variant(variant))
+    }
+}
+
+macro_rules! find_elements {
+    ($($trait:ident::$method:ident),* $(,)?) => {
+        $(
+            impl<S: Serializer> ser::$trait for FindIn<'_, '_, S> {
+                type Ok = Option<S::Ok>;
+                type Error = S::Error;
+
+                fn $method<T>(&mut self, value: &T) -> Result<(), Self::Error>
+                where
+                    T: Serialize + ?Sized,
+                {
+                    self.element(value)
+                }
+
+                fn end(self) -> Result<Self::Ok, Self::Error> {
+                    FindIn::end(self)
+                }
+            }
+        )*
+    };
+}
+
+find_elements!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field,
+);
+
+macro_rules! find_fields {
+    ($($trait:ident),* $(,)?) => {
+        $(
+            impl<S: Serializer> ser::$trait for FindIn<'_, '_, S> {
+                type Ok = Option<S::Ok>;
+                type Error = S::Error;
+
+                fn serialize_field<T>(
+                    &mut self,
+                    key: &'static str,
+                    value: &T,
+                ) -> Result<(), Self::Error>
+                where
+                    T: Serialize + ?Sized,
+                {
+                    self.field(key, value)
+                }
+
+                fn end(self) -> Result<Self::Ok, Self::Error> {
+                    FindIn::end(self)
+                }
+            }
+        )*
+    };
+}
+
+find_fields!(SerializeStruct, SerializeStructVariant);
+
+impl<S: Serializer> ser::SerializeMap for FindIn<'_, '_, S> {
+    type Ok = Option<S::Ok>;
+    type Error = S::Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        // keys which can't be written in a path can't be found
+        self.key = key.serialize(MapKey).ok();
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        match self.key.take() {
+            Some(key) => self.field(&key, value),
+            None => Ok(()),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        FindIn::end(self)
+    }
+}
+
+/// Turns map keys into path segments, for keys which are strings, numbers, chars or unit variants
+struct MapKey;
+
+#[derive(Debug)]
+struct NotAPathSegment;
+
+impl fmt::Display for NotAPathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("map key can't be used in a path")
+    }
+}
+
+impl std::error::Error for NotAPathSegment {}
+
+impl ser::Error for NotAPathSegment {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        NotAPathSegment
+    }
+}
+
+macro_rules! display_key {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(value.to_string())
+            }
+        )*
+    };
+}
+
+impl Serializer for MapKey {
+    type Ok = String;
+    type Error = NotAPathSegment;
+
+    type SerializeSeq = Impossible<String, NotAPathSegment>;
+    type SerializeTuple = Impossible<String, NotAPathSegment>;
+    type SerializeTupleStruct = Impossible<String, NotAPathSegment>;
+    type SerializeTupleVariant = Impossible<String, NotAPathSegment>;
+    type SerializeMap = Impossible<String, NotAPathSegment>;
+    type SerializeStruct = Impossible<String, NotAPathSegment>;
+    type SerializeStructVariant = Impossible<String, NotAPathSegment>;
+
+    display_key!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_char(char),
+        serialize_str(&str),
+    );
+
+    fn serialize_f32(self, _value: f32) -> Result<Self::Ok, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<Self::Ok, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(NotAPathSegment)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(NotAPathSegment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+    use serde_json::json;
+
+    use super::ViewSlice;
+
+    #[derive(Serialize)]
+    struct ViewModel {
+        title: String,
+        user: Option<User>,
+        items: Vec<Item>,
+        totals: BTreeMap<String, u32>,
+        status: Status,
+    }
+
+    #[derive(Serialize)]
+    struct User {
+        name: String,
+        id: UserId,
+    }
+
+    #[derive(Serialize)]
+    struct UserId(u64);
+
+    #[derive(Serialize)]
+    struct Item {
+        title: String,
+        done: bool,
+    }
+
+    #[derive(Serialize)]
+    enum Status {
+        Syncing { progress: u8 },
+    }
+
+    fn view_model() -> ViewModel {
+        ViewModel {
+            title: "Todos".to_string(),
+            user: Some(User {
+                name: "Ada".to_string(),
+                id: UserId(7),
+            }),
+            items: vec![
+                Item {
+                    title: "one".to_string(),
+                    done: true,
+                },
+                Item {
+                    title: "two".to_string(),
+                    done: false,
+                },
+            ],
+            totals: BTreeMap::from([("done".to_string(), 1)]),
+            status: Status::Syncing { progress: 50 },
+        }
+    }
+
+    fn slice(paths: &[&str]) -> serde_json::Value {
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string()).collect();
+        serde_json::to_value(ViewSlice::new(&view_model(), &paths)).unwrap()
+    }
+
+    #[test]
+    fn fields_in_order() {
+        assert_eq!(
+            slice(&["user.name", "title", "user.id"]),
+            json!(["Ada", "Todos", 7])
+        );
+    }
+
+    #[test]
+    fn elements_entries_and_variants() {
+        assert_eq!(
+            slice(&["items.1.title", "totals.done", "status.Syncing.progress"]),
+            json!(["two", 1, 50])
+        );
+    }
+
+    #[test]
+    fn nested_values() {
+        assert_eq!(
+            slice(&["items.0"]),
+            json!([{ "title": "one", "done": true }])
+        );
+        assert_eq!(slice(&[""])[0]["title"], "Todos");
+    }
+
+    #[test]
+    fn missing_values_are_none() {
+        assert_eq!(
+            slice(&[
+                "subtitle",
+                "items.2.title",
+                "user.name.first",
+                "status.Idle"
+            ]),
+            json!([null, null, null, null])
+        );
+    }
+
+    #[test]
+    fn bincode_values_keep_their_types() {
+        let paths = vec!["user.id".to_string(), "items.0.done".to_string()];
+        let bytes = bincode::serialize(&ViewSlice::new(&view_model(), &paths)).unwrap();
+
+        let slice: (u64, Option<u64>, Option<bool>) = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(slice, (2, Some(7), Some(true)));
+    }
+}
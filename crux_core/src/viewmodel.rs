@@ -0,0 +1,576 @@
+//! Common building blocks for view models
+//!
+//! Most apps need to show lists which are loaded a page at a time, and data which is
+//! still loading or failed to load. This module provides [`Paginated`] and [`Loadable`]
+//! for those cases, so they don't need reinventing in every app.
+//!
+//! Both types are generic, which the type generation doesn't normally support, because serde
+//! gives every instance of a generic type the same name. These types name each instance after
+//! its type parameters instead, e.g. `Paginated<Item>` is shared as `PaginatedItem` and
+//! `Loadable<User, String>` as `LoadableUserString`, so an app can use several instances
+//! side by side. As with other enums nested in the view model, each instance of [`Loadable`]
+//! needs registering with the type generator, e.g. `gen.register_type::<Loadable<User, String>>()?`.
+//!
+//! Fields of the view model which are expensive to compute from the model, like formatted
+//! text or totals over a long list, can be remembered between calls to `view` with a [`Memo`],
+//! and only computed again when the fields of the model they are computed from change. The
+//! [`ViewModelBuilder`](crate::macros::ViewModelBuilder) derive macro declares them on the
+//! view model itself.
+
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::BTreeMap,
+    fmt,
+    marker::PhantomData,
+    sync::{Mutex, RwLock},
+};
+
+use serde::{
+    de::{self, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// A page of items from a longer list
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Paginated<T> {
+    /// The items on this page
+    pub items: Vec<T>,
+    /// The zero-based index of this page
+    pub page: u32,
+    /// The maximum number of items on a page
+    pub page_size: u32,
+    /// The number of items on all the pages, if known
+    pub total_items: Option<u64>,
+}
+
+impl<T> Default for Paginated<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            page: 0,
+            page_size: 0,
+            total_items: None,
+        }
+    }
+}
+
+impl<T> Paginated<T> {
+    /// The number of pages, if the total number of items is known
+    pub fn page_count(&self) -> Option<u64> {
+        match (self.total_items, self.page_size) {
+            (Some(_), 0) => Some(0),
+            (Some(total), size) => {
+                let size = u64::from(size);
+                Some(total / size + u64::from(total % size != 0))
+            }
+            (None, _) => None,
+        }
+    }
+
+    /// Whether there are more items after this page. When the total number of items isn't
+    /// known, a full page is taken to mean there may be more.
+    pub fn has_next_page(&self) -> bool {
+        match self.page_count() {
+            Some(count) => u64::from(self.page) + 1 < count,
+            None => self.page_size > 0 && self.items.len() == self.page_size as usize,
+        }
+    }
+}
+
+/// Data which is loaded asynchronously, e.g. from an API
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub enum Loadable<T, E> {
+    /// Loading hasn't started
+    #[default]
+    NotLoaded,
+    /// Loading is in progress
+    Loading,
+    /// The data has loaded
+    Loaded(T),
+    /// Loading failed
+    Failed(E),
+}
+
+impl<T, E> Loadable<T, E> {
+    pub fn is_loading(&self) -> bool {
+        matches!(self, Loadable::Loading)
+    }
+
+    /// The loaded data, if any
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Loadable::Loaded(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The error loading the data failed with, if any
+    pub fn error(&self) -> Option<&E> {
+        match self {
+            Loadable::Failed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl<T, E> From<Result<T, E>> for Loadable<T, E> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Loadable::Loaded(value),
+            Err(error) => Loadable::Failed(error),
+        }
+    }
+}
+
+const PAGINATED_FIELDS: &[&str] = &["items", "page", "page_size", "total_items"];
+const LOADABLE_VARIANTS: &[&str] = &["NotLoaded", "Loading", "Loaded", "Failed"];
+
+impl<T> Serialize for Paginated<T>
+where
+    T: Serialize + 'static,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = instance_name::<Self>("Paginated", &[type_name::<T>()]);
+        let mut state = serializer.serialize_struct(name, PAGINATED_FIELDS.len())?;
+        state.serialize_field("items", &self.items)?;
+        state.serialize_field("page", &self.page)?;
+        state.serialize_field("page_size", &self.page_size)?;
+        state.serialize_field("total_items", &self.total_items)?;
+        state.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Paginated<T>
+where
+    T: Deserialize<'de> + 'static,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PaginatedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for PaginatedVisitor<T> {
+            type Value = Paginated<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a page of items")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let items = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let page = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let page_size = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let total_items = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+
+                Ok(Paginated {
+                    items,
+                    page,
+                    page_size,
+                    total_items,
+                })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut page = Paginated::default();
+                let (mut items, mut page_number, mut page_size) = (None, None, None);
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "items" => items = Some(map.next_value()?),
+                        "page" => page_number = Some(map.next_value()?),
+                        "page_size" => page_size = Some(map.next_value()?),
+                        "total_items" => page.total_items = map.next_value()?,
+                        _ => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+                page.items = items.ok_or_else(|| de::Error::missing_field("items"))?;
+                page.page = page_number.ok_or_else(|| de::Error::missing_field("page"))?;
+                page.page_size = page_size.ok_or_else(|| de::Error::missing_field("page_size"))?;
+                Ok(page)
+            }
+        }
+
+        let name = instance_name::<Self>("Paginated", &[type_name::<T>()]);
+        deserializer.deserialize_struct(name, PAGINATED_FIELDS, PaginatedVisitor(PhantomData))
+    }
+}
+
+impl<T, E> Serialize for Loadable<T, E>
+where
+    T: Serialize + 'static,
+    E: Serialize + 'static,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = instance_name::<Self>("Loadable", &[type_name::<T>(), type_name::<E>()]);
+        match self {
+            Loadable::NotLoaded => serializer.serialize_unit_variant(name, 0, "NotLoaded"),
+            Loadable::Loading => serializer.serialize_unit_variant(name, 1, "Loading"),
+            Loadable::Loaded(value) => {
+                serializer.serialize_newtype_variant(name, 2, "Loaded", value)
+            }
+            Loadable::Failed(error) => {
+                serializer.serialize_newtype_variant(name, 3, "Failed", error)
+            }
+        }
+    }
+}
+
+impl<'de, T, E> Deserialize<'de> for Loadable<T, E>
+where
+    T: Deserialize<'de> + 'static,
+    E: Deserialize<'de> + 'static,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        enum Variant {
+            NotLoaded,
+            Loading,
+            Loaded,
+            Failed,
+        }
+
+        impl<'de> Deserialize<'de> for Variant {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct VariantVisitor;
+
+                impl Visitor<'_> for VariantVisitor {
+                    type Value = Variant;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("a Loadable variant")
+                    }
+
+                    fn visit_u64<E: de::Error>(self, index: u64) -> Result<Variant, E> {
+                        match index {
+                            0 => Ok(Variant::NotLoaded),
+                            1 => Ok(Variant::Loading),
+                            2 => Ok(Variant::Loaded),
+                            3 => Ok(Variant::Failed),
+                            _ => Err(de::Error::invalid_value(
+                                de::Unexpected::Unsigned(index),
+                                &self,
+                            )),
+                        }
+                    }
+
+                    fn visit_str<E: de::Error>(self, name: &str) -> Result<Variant, E> {
+                        match name {
+                            "NotLoaded" => Ok(Variant::NotLoaded),
+                            "Loading" => Ok(Variant::Loading),
+                            "Loaded" => Ok(Variant::Loaded),
+                            "Failed" => Ok(Variant::Failed),
+                            _ => Err(de::Error::unknown_variant(name, LOADABLE_VARIANTS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(VariantVisitor)
+            }
+        }
+
+        struct LoadableVisitor<T, E>(PhantomData<(T, E)>);
+
+        impl<'de, T: Deserialize<'de>, E: Deserialize<'de>> Visitor<'de> for LoadableVisitor<T, E> {
+            type Value = Loadable<T, E>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a Loadable")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                match data.variant()? {
+                    (Variant::NotLoaded, variant) => {
+                        variant.unit_variant()?;
+                        Ok(Loadable::NotLoaded)
+                    }
+                    (Variant::Loading, variant) => {
+                        variant.unit_variant()?;
+                        Ok(Loadable::Loading)
+                    }
+                    (Variant::Loaded, variant) => variant.newtype_variant().map(Loadable::Loaded),
+                    (Variant::Failed, variant) => variant.newtype_variant().map(Loadable::Failed),
+                }
+            }
+        }
+
+        let name = instance_name::<Self>("Loadable", &[type_name::<T>(), type_name::<E>()]);
+        deserializer.deserialize_enum(name, LOADABLE_VARIANTS, LoadableVisitor(PhantomData))
+    }
+}
+
+/// The name of an instance of a generic type, e.g. `PaginatedItem` for `Paginated<app::Item>`.
+///
+/// Serde needs a `&'static str`, so each name is leaked once and cached by the type's id.
+fn instance_name<Instance: 'static>(base: &str, parameters: &[&str]) -> &'static str {
+    static NAMES: RwLock<BTreeMap<TypeId, &'static str>> = RwLock::new(BTreeMap::new());
+
+    let id = TypeId::of::<Instance>();
+
+    if let Some(name) = NAMES.read().expect("Names RwLock poisoned.").get(&id) {
+        return name;
+    }
+
+    let mut name = base.to_string();
+    for parameter in parameters {
+        name.push_str(&type_name_without_paths(parameter));
+    }
+
+    NAMES
+        .write()
+        .expect("Names RwLock poisoned.")
+        .entry(id)
+        .or_insert_with(|| Box::leak(name.into_boxed_str()))
+}
+
+/// Strip the module paths and punctuation from a type name, capitalising each
+/// remaining identifier, e.g. `Vec<app::Item>` becomes `VecItem` and `(u8, bool)` becomes `U8Bool`.
+fn type_name_without_paths(type_name: &str) -> String {
+    let mut name = String::new();
+    let mut rest = type_name;
+
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let (identifier, after) = rest.split_at(end);
+
+        if !after.starts_with("::") {
+            for part in identifier.split('_') {
+                let mut chars = part.chars();
+                if let Some(first) = chars.next() {
+                    name.extend(first.to_uppercase());
+                    name.push_str(chars.as_str());
+                }
+            }
+        }
+
+        rest = after.trim_start_matches(|c: char| !(c.is_alphanumeric() || c == '_'));
+    }
+
+    name
+}
+
+/// A value computed from some fields of the model, which is only computed again when they
+/// change, e.g. in `App::view`.
+///
+/// ```rust
+/// # use crux_core::viewmodel::Memo;
+/// # #[derive(Default)]
+/// # struct Model { items: Vec<u32> }
+/// # let model = Model::default();
+/// let total = Memo::default();
+///
+/// let value: u32 = total.get(&[&model.items], || model.items.iter().sum());
+/// ```
+///
+/// The keys are compared with the ones the value was last computed from, and a copy of them
+/// is kept for the next time, so they should be cheaper to compare and clone than the value
+/// is to compute.
+#[derive(Default)]
+pub struct Memo {
+    computed: Mutex<Option<Computed>>,
+}
+
+struct Computed {
+    keys: Vec<Box<dyn Any + Send>>,
+    value: Box<dyn Any + Send>,
+}
+
+impl Memo {
+    /// The value computed from the `keys` by `compute`, which is only called if the keys differ
+    /// from the last call, or the value is of a different type.
+    pub fn get<T>(&self, keys: &[&dyn MemoKey], compute: impl FnOnce() -> T) -> T
+    where
+        T: Clone + Send + 'static,
+    {
+        let mut computed = self.computed.lock().expect("Memo Mutex was poisoned.");
+
+        if let Some(Computed {
+            keys: previous,
+            value,
+        }) = &*computed
+        {
+            let unchanged = previous.len() == keys.len()
+                && keys
+                    .iter()
+                    .zip(previous)
+                    .all(|(key, previous)| key.matches(previous.as_ref()));
+            if let (true, Some(value)) = (unchanged, value.downcast_ref::<T>()) {
+                return value.clone();
+            }
+        }
+
+        let value = compute();
+        *computed = Some(Computed {
+            keys: keys.iter().map(|key| key.to_stored()).collect(),
+            value: Box::new(value.clone()),
+        });
+        value
+    }
+}
+
+/// A field of the model a [`Memo`] is keyed on, implemented for all types which can be
+/// compared and cloned
+pub trait MemoKey {
+    /// Whether the key is equal to a `stored` copy of a key
+    fn matches(&self, stored: &(dyn Any + Send)) -> bool;
+    /// A copy of the key, to compare with later
+    fn to_stored(&self) -> Box<dyn Any + Send>;
+}
+
+impl<T> MemoKey for T
+where
+    T: PartialEq + Clone + Send + 'static,
+{
+    fn matches(&self, stored: &(dyn Any + Send)) -> bool {
+        stored.downcast_ref::<T>() == Some(self)
+    }
+
+    fn to_stored(&self) -> Box<dyn Any + Send> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_names_without_paths() {
+        assert_eq!(type_name_without_paths("u32"), "U32");
+        assert_eq!(type_name_without_paths("alloc::string::String"), "String");
+        assert_eq!(
+            type_name_without_paths("alloc::vec::Vec<my_app::model::Item>"),
+            "VecItem"
+        );
+        assert_eq!(type_name_without_paths("(u8, bool)"), "U8Bool");
+        assert_eq!(type_name_without_paths("my_app::http_error"), "HttpError");
+    }
+
+    #[test]
+    fn instance_names() {
+        assert_eq!(
+            instance_name::<Paginated<String>>("Paginated", &[type_name::<String>()]),
+            "PaginatedString"
+        );
+        assert_eq!(
+            instance_name::<Loadable<u32, String>>(
+                "Loadable",
+                &[type_name::<u32>(), type_name::<String>()]
+            ),
+            "LoadableU32String"
+        );
+    }
+
+    #[test]
+    fn paginated_json_roundtrip() {
+        let page = Paginated {
+            items: vec!["a".to_string(), "b".to_string()],
+            page: 1,
+            page_size: 2,
+            total_items: Some(5),
+        };
+
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "items": ["a", "b"], "page": 1, "page_size": 2, "total_items": 5 })
+        );
+        assert_eq!(
+            serde_json::from_value::<Paginated<String>>(json).unwrap(),
+            page
+        );
+    }
+
+    #[test]
+    fn paginated_bincode_roundtrip() {
+        let page = Paginated {
+            items: vec![1u8, 2, 3],
+            page: 0,
+            page_size: 3,
+            total_items: None,
+        };
+
+        let bytes = bincode::serialize(&page).unwrap();
+        assert_eq!(bincode::deserialize::<Paginated<u8>>(&bytes).unwrap(), page);
+    }
+
+    #[test]
+    fn loadable_roundtrip() {
+        let values: Vec<Loadable<u32, String>> = vec![
+            Loadable::NotLoaded,
+            Loadable::Loading,
+            Loadable::Loaded(3),
+            Loadable::Failed("oops".to_string()),
+        ];
+
+        for value in values {
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Loadable<u32, String>>(&json).unwrap(),
+                value
+            );
+
+            let bytes = bincode::serialize(&value).unwrap();
+            assert_eq!(
+                bincode::deserialize::<Loadable<u32, String>>(&bytes).unwrap(),
+                value
+            );
+        }
+
+        assert_eq!(
+            serde_json::to_string(&Loadable::<u32, String>::Loaded(3)).unwrap(),
+            r#"{"Loaded":3}"#
+        );
+    }
+
+    #[test]
+    fn pages() {
+        let mut page = Paginated {
+            items: vec![1, 2],
+            page: 0,
+            page_size: 2,
+            total_items: Some(3),
+        };
+        assert_eq!(page.page_count(), Some(2));
+        assert!(page.has_next_page());
+
+        page.page = 1;
+        assert!(!page.has_next_page());
+
+        page.total_items = None;
+        assert!(page.has_next_page());
+
+        page.items.pop();
+        assert!(!page.has_next_page());
+    }
+
+    #[test]
+    fn memo_computes_again_when_the_keys_change() {
+        let memo = Memo::default();
+        let mut computed = 0;
+        let mut items = vec![1, 2];
+        let currency = "GBP".to_string();
+
+        let mut total = |items: &Vec<u32>, currency: &String| {
+            memo.get(&[items, currency], || {
+                computed += 1;
+                format!("{} {currency}", items.iter().sum::<u32>())
+            })
+        };
+
+        assert_eq!(total(&items, &currency), "3 GBP");
+        assert_eq!(total(&items, &currency), "3 GBP");
+
+        items.push(3);
+        assert_eq!(total(&items, &currency), "6 GBP");
+        assert_eq!(computed, 2);
+    }
+}
@@ -0,0 +1,235 @@
+mod app {
+    use crux_core::{
+        async_update::{AsyncUpdate, ModelHandle},
+        macros::Effect,
+        render::Render,
+    };
+    use doctest_support::compose::capabilities::{
+        capability_one::CapabilityOne, capability_two::CapabilityTwo,
+    };
+    use futures::future::join;
+    use serde::Serialize;
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Debug, PartialEq)]
+    pub enum Event {
+        Add(usize),
+        AddBoth(usize, usize),
+        Reset,
+    }
+
+    #[derive(Default, Serialize, Debug, PartialEq)]
+    pub struct Model {
+        pub total: usize,
+        pub additions: usize,
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        one: CapabilityOne<Event>,
+        two: CapabilityTwo<Event>,
+        render: Render<Event>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = usize;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Reset => {
+                    *model = Model::default();
+                    caps.render.render();
+                }
+                _ => unreachable!("handled by async_update"),
+            }
+        }
+
+        fn async_update(
+            &self,
+            event: Event,
+            model: ModelHandle<Model>,
+            caps: &Capabilities,
+        ) -> AsyncUpdate<Event> {
+            let (one, two, render) = (caps.one.clone(), caps.two.clone(), caps.render.clone());
+
+            match event {
+                Event::Add(number) => AsyncUpdate::spawn(async move {
+                    let additions = model.update(|model| model.additions).await;
+                    let result = one.one_async(number + additions).await;
+
+                    model
+                        .update(move |model| {
+                            model.total += result;
+                            model.additions += 1;
+                        })
+                        .await;
+                    render.render();
+                }),
+                Event::AddBoth(a, b) => AsyncUpdate::spawn(async move {
+                    let (a, b) = join(one.one_async(a), two.two_async(b)).await;
+
+                    model.update(move |model| model.total += a + b).await;
+                    render.render();
+                }),
+                event => AsyncUpdate::Update(event),
+            }
+        }
+
+        fn view(&self, model: &Model) -> usize {
+            model.total
+        }
+    }
+}
+
+/// An app whose model borrows, which doesn't use `async_update`
+mod borrowing {
+    use std::marker::PhantomData;
+
+    use crux_core::{macros::Effect, render::Render};
+
+    #[derive(Default)]
+    pub struct App<'a>(PhantomData<&'a ()>);
+
+    pub enum Event {
+        Greet,
+    }
+
+    #[derive(Default)]
+    pub struct Model<'a> {
+        pub greeting: &'a str,
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        render: Render<Event>,
+    }
+
+    impl<'a> crux_core::App for App<'a> {
+        type Event = Event;
+        type Model = Model<'a>;
+        type ViewModel = String;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model<'a>, caps: &Capabilities) {
+            match event {
+                Event::Greet => model.greeting = "hello",
+            }
+            caps.render.render();
+        }
+
+        fn view(&self, model: &Model<'a>) -> String {
+            model.greeting.to_string()
+        }
+    }
+}
+
+mod tests {
+    use crux_core::{testing::AppTester, Core};
+
+    use crate::app::{App, Effect, Event, Model};
+    use crate::borrowing;
+
+    #[test]
+    fn awaits_effects_in_update() {
+        let core: Core<Effect, App> = Core::default();
+
+        let mut effects = core.process_event(Event::Add(3));
+        let Some(Effect::CapabilityOne(mut request)) = effects.pop() else {
+            panic!("expected a request for capability one");
+        };
+        assert!(effects.is_empty());
+        assert_eq!(core.view(), 0);
+
+        let effects = core.resolve(&mut request, 10);
+
+        assert!(matches!(effects[..], [Effect::Render(_)]));
+        assert_eq!(core.view(), 10);
+    }
+
+    #[test]
+    fn tasks_read_the_model_when_they_run() {
+        let core: Core<Effect, App> = Core::default();
+
+        let Some(Effect::CapabilityOne(mut first)) = core.process_event(Event::Add(1)).pop() else {
+            panic!("expected a request for capability one");
+        };
+        core.resolve(&mut first, 1);
+
+        let Some(Effect::CapabilityOne(second)) = core.process_event(Event::Add(1)).pop() else {
+            panic!("expected a request for capability one");
+        };
+        // the second addition sees the first
+        assert_eq!(format!("{:?}", second.operation), "OpOne { number: 2 }");
+    }
+
+    #[test]
+    fn other_events_are_passed_to_update() {
+        let core: Core<Effect, App> = Core::default();
+
+        let mut effects = core.process_event(Event::AddBoth(1, 2));
+        for effect in &mut effects {
+            match effect {
+                Effect::CapabilityOne(request) => {
+                    core.resolve(request, 1);
+                }
+                Effect::CapabilityTwo(request) => {
+                    core.resolve(request, 2);
+                }
+                Effect::Render(_) => panic!("rendered before the effects were resolved"),
+            }
+        }
+        assert_eq!(core.view(), 3);
+
+        let effects = core.process_event(Event::Reset);
+
+        assert!(matches!(effects[..], [Effect::Render(_)]));
+        assert_eq!(core.view(), 0);
+    }
+
+    #[test]
+    fn app_tester_runs_tasks() {
+        let app: AppTester<App, Effect> = AppTester::default();
+        let mut model = Model::default();
+
+        let update = app.update(Event::Add(5), &mut model);
+        let Effect::CapabilityOne(mut request) = update.expect_one_effect() else {
+            panic!("expected a request for capability one");
+        };
+
+        let update = app
+            .resolve_with_model(&mut request, 5, &mut model)
+            .expect("should resolve");
+
+        assert!(update.expect_one_effect().is_render());
+        assert_eq!(
+            model,
+            Model {
+                total: 5,
+                additions: 1
+            }
+        );
+    }
+
+    #[test]
+    fn models_need_not_be_static() {
+        fn greet<'a>(model: &mut borrowing::Model<'a>) -> String {
+            let core: Core<borrowing::Effect, borrowing::App<'a>> = Core::default();
+            core.process_event(borrowing::Event::Greet);
+
+            let app: AppTester<borrowing::App<'a>, borrowing::Effect> = AppTester::default();
+            let update = app.update(borrowing::Event::Greet, model);
+            assert!(update.expect_one_effect().is_render());
+
+            core.view()
+        }
+
+        let mut model = borrowing::Model::default();
+        assert_eq!(greet(&mut model), "hello");
+        assert_eq!(model.greeting, "hello");
+    }
+}
@@ -0,0 +1,179 @@
+mod app {
+    use crux_core::bridge::Backpressure;
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_time::{Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Poll,
+        #[serde(skip)]
+        Polled(TimeResponse),
+        #[serde(skip)]
+        Backpressure(Backpressure),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub paused: bool,
+        pub polled: usize,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = bool;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Poll => {
+                    if !model.paused {
+                        caps.time.now(Event::Polled);
+                    }
+                }
+                Event::Polled(TimeResponse::Now(_)) => model.polled += 1,
+                Event::Polled(_) => {}
+                Event::Backpressure(backpressure) => {
+                    model.paused = matches!(backpressure, Backpressure::Congested { .. });
+                    caps.render.render();
+                }
+            }
+        }
+
+        fn view(&self, model: &Model) -> bool {
+            model.paused
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::{bridge::Bridge, bridge::Request, metrics::Metrics, Core};
+    use crux_time::{Instant, TimeResponse};
+
+    use crate::app::{App, Effect, EffectFfi, Event};
+
+    fn poll(bridge: &Bridge<Effect, App>) -> Vec<Request<EffectFfi>> {
+        bincode::deserialize(&bridge.process_event(&bincode::serialize(&Event::Poll).unwrap()))
+            .unwrap()
+    }
+
+    fn paused(bridge: &Bridge<Effect, App>) -> bool {
+        bincode::deserialize(&bridge.view()).unwrap()
+    }
+
+    #[test]
+    fn app_is_told_when_effects_pile_up() {
+        let bridge =
+            Bridge::<Effect, App>::new(Core::new()).with_backpressure(2, Event::Backpressure);
+
+        let mut ids = vec![];
+        for _ in 0..2 {
+            let requests = poll(&bridge);
+            assert_eq!(requests.len(), 1);
+            ids.push(requests[0].id.0);
+        }
+        assert!(!paused(&bridge));
+
+        // the third time request is over the high-water mark, and the app renders paused
+        let requests = poll(&bridge);
+        assert!(matches!(
+            requests[..],
+            [
+                Request {
+                    effect: EffectFfi::Time(_),
+                    ..
+                },
+                Request {
+                    effect: EffectFfi::Render(_),
+                    ..
+                }
+            ]
+        ));
+        ids.push(requests[0].id.0);
+        assert!(paused(&bridge));
+        assert!(poll(&bridge).is_empty());
+
+        let now = bincode::serialize(&TimeResponse::Now(Instant::new(1, 0).unwrap())).unwrap();
+        let requests: Vec<Request<EffectFfi>> =
+            bincode::deserialize(&bridge.handle_response(ids[0], &now)).unwrap();
+        assert!(requests.is_empty());
+        assert!(paused(&bridge));
+
+        // down to half the high-water mark
+        let requests: Vec<Request<EffectFfi>> =
+            bincode::deserialize(&bridge.handle_response(ids[1], &now)).unwrap();
+        assert!(matches!(
+            requests[..],
+            [Request {
+                effect: EffectFfi::Render(_),
+                ..
+            }]
+        ));
+        assert!(!paused(&bridge));
+
+        let metrics: Metrics = bincode::deserialize(&bridge.metrics()).unwrap();
+        assert_eq!(metrics.pending_effects, 1);
+        assert_eq!(metrics.peak_pending_effects, 3);
+    }
+
+    #[test]
+    fn effects_past_the_maximum_wait_in_the_bridge() {
+        let bridge = Bridge::<Effect, App>::new(Core::new())
+            .with_max_pending(2)
+            .with_backpressure(2, Event::Backpressure);
+
+        let ids: Vec<u32> = (0..2).map(|_| poll(&bridge)[0].id.0).collect();
+
+        // the third time request waits in the bridge, but counts as waiting for the app,
+        // as does the render of its backpressure event
+        assert!(poll(&bridge).is_empty());
+        assert!(paused(&bridge));
+        let metrics: Metrics = bincode::deserialize(&bridge.metrics()).unwrap();
+        assert_eq!(metrics.pending_effects, 2);
+        assert_eq!(metrics.deferred_effects, 2);
+
+        // they are sent in order, as the shell responds to earlier effects
+        let now = bincode::serialize(&TimeResponse::Now(Instant::new(1, 0).unwrap())).unwrap();
+        let requests: Vec<Request<EffectFfi>> =
+            bincode::deserialize(&bridge.handle_response(ids[0], &now)).unwrap();
+        assert!(matches!(
+            requests[..],
+            [Request {
+                effect: EffectFfi::Time(_),
+                ..
+            }]
+        ));
+        let requests: Vec<Request<EffectFfi>> =
+            bincode::deserialize(&bridge.handle_response(ids[1], &now)).unwrap();
+        // the render doesn't wait for a response, so the app is relieved, and renders again
+        assert!(matches!(
+            requests[..],
+            [
+                Request {
+                    effect: EffectFfi::Render(_),
+                    ..
+                },
+                Request {
+                    effect: EffectFfi::Render(_),
+                    ..
+                }
+            ]
+        ));
+        assert!(!paused(&bridge));
+
+        let metrics: Metrics = bincode::deserialize(&bridge.metrics()).unwrap();
+        assert_eq!(metrics.deferred_effects, 0);
+        assert_eq!(metrics.peak_pending_effects, 2);
+    }
+}
@@ -0,0 +1,209 @@
+mod app {
+    use crux_core::compose::Compose;
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_time::{Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Type(char),
+        Submit,
+        GetTime,
+        #[serde(skip)]
+        Submitted(String),
+        #[serde(skip)]
+        SetTime(TimeResponse),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub text: String,
+        pub log: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub text: String,
+        pub log: Vec<String>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Type(character) => {
+                    model.text.push(character);
+                    model.log.push(format!("typed {character}"));
+                }
+                Event::Submit => {
+                    let text = std::mem::take(&mut model.text);
+                    model.log.push("submit".to_string());
+
+                    // the event comes back through a capability, after this update
+                    caps.compose.spawn(|context| async move {
+                        context.update_app(Event::Submitted(text));
+                    });
+                }
+                Event::Submitted(text) => model.log.push(format!("submitted {text}")),
+                Event::GetTime => caps.time.now(Event::SetTime),
+                Event::SetTime(time) => model.log.push(format!("time {time:?}")),
+            }
+
+            caps.render.render();
+        }
+
+        fn view(&self, model: &Model) -> ViewModel {
+            ViewModel {
+                text: model.text.clone(),
+                log: model.log.clone(),
+            }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+        #[effect(skip)]
+        pub compose: Compose<Event>,
+    }
+}
+
+mod drawing {
+    // the render capability under another name, so its effect variant is not called "Render"
+    use crux_core::macros::Effect;
+    use crux_core::render::Render as Draw;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Event;
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = ();
+        type ViewModel = ();
+        type Capabilities = Capabilities;
+
+        fn update(&self, _event: Event, _model: &mut (), caps: &Capabilities) {
+            caps.draw.render();
+        }
+
+        fn view(&self, _model: &()) {}
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub draw: Draw<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::{
+        bridge::{Bridge, BridgeError, Request},
+        Core,
+    };
+
+    use crate::app::{App, Effect, EffectFfi, Event, ViewModel};
+    use crate::drawing;
+
+    #[test]
+    fn events_are_processed_in_order() {
+        let core: Core<Effect, App> = Core::new();
+
+        core.process_events([
+            Event::Type('a'),
+            Event::Submit,
+            Event::Type('b'),
+            Event::Type('c'),
+        ]);
+
+        assert_eq!(
+            core.view(),
+            ViewModel {
+                text: "bc".to_string(),
+                log: vec![
+                    "typed a".to_string(),
+                    "submit".to_string(),
+                    "submitted a".to_string(),
+                    "typed b".to_string(),
+                    "typed c".to_string(),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn renders_are_merged_into_the_last_one() {
+        let core: Core<Effect, App> = Core::new();
+
+        let effects = core.process_events([Event::Type('a'), Event::GetTime, Event::Type('b')]);
+
+        let [Effect::Time(_), Effect::Render(_)] = &effects[..] else {
+            panic!("Expected a time request and a single render");
+        };
+    }
+
+    #[test]
+    fn renders_are_merged_whatever_their_variant_is_called() {
+        let core: Core<drawing::Effect, drawing::App> = Core::new();
+
+        let effects = core.process_events([drawing::Event, drawing::Event]);
+
+        let [drawing::Effect::Draw(_)] = &effects[..] else {
+            panic!("Expected a single render");
+        };
+    }
+
+    #[test]
+    fn bridge_processes_a_list_of_events() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        let events = vec![Event::Type('a'), Event::Type('b'), Event::GetTime];
+
+        let requests: Vec<Request<EffectFfi>> =
+            bincode::deserialize(&bridge.process_events(&bincode::serialize(&events).unwrap()))
+                .unwrap();
+
+        let [Request {
+            effect: EffectFfi::Time(_),
+            ..
+        }, Request {
+            effect: EffectFfi::Render(_),
+            ..
+        }] = &requests[..]
+        else {
+            panic!("Expected a time request and a single render");
+        };
+
+        let view: ViewModel = bincode::deserialize(&bridge.view()).unwrap();
+        assert_eq!(view.text, "ab");
+    }
+
+    #[test]
+    fn no_events_are_processed_if_one_is_invalid() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        // a list of two events, the second of which is skipped in serialization
+        let mut events = bincode::serialize(&vec![Event::Type('a')]).unwrap();
+        events[0] = 2;
+        events.extend(3u32.to_le_bytes());
+
+        assert_eq!(
+            bridge.try_process_events(&events),
+            Err(BridgeError::InvalidEvent)
+        );
+
+        let view: ViewModel = bincode::deserialize(&bridge.view()).unwrap();
+        assert_eq!(view.log, Vec::<String>::new());
+    }
+}
@@ -0,0 +1,280 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_time::{Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        GetTime,
+        #[serde(skip)]
+        SetTime(TimeResponse),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub time: Option<u64>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Option<u64>;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Self::Model, caps: &Capabilities) {
+            match event {
+                Event::GetTime => caps.time.now(Event::SetTime),
+                Event::SetTime(TimeResponse::Now(instant)) => {
+                    *model = Some(instant.seconds);
+                    caps.render.render();
+                }
+                Event::SetTime(_) => {}
+            }
+        }
+
+        fn view(&self, model: &Self::Model) -> Self::ViewModel {
+            ViewModel { time: *model }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::{
+        bridge::{Bridge, BridgeError, Limits, Request},
+        metrics::Metrics,
+        Core,
+    };
+    use crux_time::{Instant, TimeResponse};
+
+    use crate::app::{App, Effect, EffectFfi, Event, ViewModel};
+
+    fn now(seconds: u64) -> Vec<u8> {
+        bincode::serialize(&TimeResponse::Now(Instant::new(seconds, 0).unwrap())).unwrap()
+    }
+
+    fn get_time(bridge: &Bridge<Effect, App>) -> u32 {
+        let requests: Vec<Request<EffectFfi>> = bincode::deserialize(
+            &bridge.process_event(&bincode::serialize(&Event::GetTime).unwrap()),
+        )
+        .unwrap();
+
+        let [Request {
+            id,
+            effect: EffectFfi::Time(_),
+            ..
+        }] = &requests[..]
+        else {
+            panic!("Expected a time request");
+        };
+
+        id.0
+    }
+
+    #[test]
+    fn resolving_an_effect_twice_is_an_error() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        let id = get_time(&bridge);
+
+        let requests: Vec<Request<EffectFfi>> =
+            bincode::deserialize(&bridge.try_handle_response(id, &now(1)).unwrap()).unwrap();
+        assert!(matches!(requests[0].effect, EffectFfi::Render(_)));
+
+        assert_eq!(
+            bridge.try_handle_response(id, &now(2)),
+            Err(BridgeError::AlreadyResolved { id })
+        );
+
+        let view: ViewModel = bincode::deserialize(&bridge.view()).unwrap();
+        assert_eq!(view, ViewModel { time: Some(1) });
+    }
+
+    #[test]
+    fn ids_of_resolved_effects_are_not_reused() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        let first = get_time(&bridge);
+        bridge.try_handle_response(first, &now(1)).unwrap();
+        let second = get_time(&bridge);
+        assert_ne!(first, second);
+
+        // a late duplicate response for the first effect doesn't resolve the second
+        assert_eq!(
+            bridge.try_handle_response(first, &now(2)),
+            Err(BridgeError::AlreadyResolved { id: first })
+        );
+        bridge.try_handle_response(second, &now(3)).unwrap();
+
+        let view: ViewModel = bincode::deserialize(&bridge.view()).unwrap();
+        assert_eq!(view, ViewModel { time: Some(3) });
+    }
+
+    #[test]
+    fn notifications_do_not_expect_a_response() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        let id = get_time(&bridge);
+
+        let requests: Vec<Request<EffectFfi>> =
+            bincode::deserialize(&bridge.try_handle_response(id, &now(1)).unwrap()).unwrap();
+        let render = requests[0].id.0;
+
+        assert_eq!(
+            bridge.try_handle_response(render, &[]),
+            Err(BridgeError::NotExpected { id: render })
+        );
+    }
+
+    #[test]
+    fn unknown_effects_are_not_found() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        assert_eq!(
+            bridge.try_handle_response(42, &now(1)),
+            Err(BridgeError::NotFound { id: 42 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Effect 0 has already been resolved.")]
+    fn handle_response_panics_when_resolving_twice() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        let id = get_time(&bridge);
+
+        bridge.handle_response(id, &now(1));
+        bridge.handle_response(id, &now(2));
+    }
+
+    #[test]
+    fn malformed_events_are_an_error() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        // an unknown variant, and a variant which is skipped
+        assert_eq!(
+            bridge.try_process_event(&[7, 0, 0, 0]),
+            Err(BridgeError::InvalidEvent)
+        );
+        assert_eq!(
+            bridge.try_process_event(&[1, 0, 0, 0]),
+            Err(BridgeError::InvalidEvent)
+        );
+        assert_eq!(
+            bridge.try_process_event(&[]),
+            Err(BridgeError::InvalidEvent)
+        );
+    }
+
+    #[test]
+    fn malformed_responses_leave_the_effect_waiting() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        let id = get_time(&bridge);
+
+        assert_eq!(
+            bridge.try_handle_response(id, &[0xff; 3]),
+            Err(BridgeError::InvalidResponse { id })
+        );
+        bridge.try_handle_response(id, &now(1)).unwrap();
+
+        let view: ViewModel = bincode::deserialize(&bridge.view()).unwrap();
+        assert_eq!(view, ViewModel { time: Some(1) });
+    }
+
+    #[test]
+    fn inputs_over_the_limit_are_refused() {
+        let bridge = Bridge::<Effect, App>::with_limits(
+            Core::new(),
+            Limits {
+                max_input_bytes: Some(4),
+                max_output_bytes: None,
+            },
+        );
+        let id = get_time(&bridge);
+
+        assert_eq!(
+            bridge.try_process_event(&[0; 5]),
+            Err(BridgeError::InputTooLarge { size: 5, limit: 4 })
+        );
+        assert_eq!(
+            bridge.try_handle_response(id, &now(1)),
+            Err(BridgeError::InputTooLarge { size: 16, limit: 4 })
+        );
+
+        // the event is right at the limit
+        let metrics: Metrics = bincode::deserialize(&bridge.metrics()).unwrap();
+        assert_eq!(metrics.near_limit_payloads, 1);
+        assert_eq!(metrics.oversized_payloads, 2);
+
+        let view: ViewModel = bincode::deserialize(&bridge.view()).unwrap();
+        assert_eq!(view, ViewModel { time: None });
+    }
+
+    #[test]
+    fn outputs_over_the_limit_are_an_error() {
+        let bridge = Bridge::<Effect, App>::with_limits(
+            Core::new(),
+            Limits {
+                max_input_bytes: None,
+                max_output_bytes: Some(8),
+            },
+        );
+
+        let event = bincode::serialize(&Event::GetTime).unwrap();
+        let Err(BridgeError::OutputTooLarge { size, limit: 8 }) = bridge.try_process_event(&event)
+        else {
+            panic!("Expected the requests to be over the limit");
+        };
+        assert!(size > 8);
+
+        let view: ViewModel = bincode::deserialize(&bridge.try_view().unwrap()).unwrap();
+        assert_eq!(view, ViewModel { time: None });
+
+        let metrics: Metrics = bincode::deserialize(&bridge.metrics()).unwrap();
+        assert_eq!(metrics.near_limit_payloads, 0);
+        assert_eq!(metrics.oversized_payloads, 1);
+    }
+
+    #[test]
+    fn view_into_reuses_the_buffer() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        let mut buffer = Vec::with_capacity(64);
+        let allocation = buffer.as_ptr();
+
+        let id = get_time(&bridge);
+        bridge.handle_response(id, &now(1));
+
+        bridge.view_into(&mut buffer).unwrap();
+        assert_eq!(buffer, bridge.view());
+        assert_eq!(buffer.as_ptr(), allocation);
+
+        let view: ViewModel = bincode::deserialize(&buffer).unwrap();
+        assert_eq!(view, ViewModel { time: Some(1) });
+    }
+
+    #[test]
+    fn view_into_leaves_the_buffer_empty_over_the_limit() {
+        let bridge = Bridge::<Effect, App>::with_limits(
+            Core::new(),
+            Limits {
+                max_input_bytes: None,
+                max_output_bytes: Some(0),
+            },
+        );
+
+        let mut buffer = vec![1, 2, 3];
+
+        assert_eq!(
+            bridge.view_into(&mut buffer),
+            Err(BridgeError::OutputTooLarge { size: 1, limit: 0 })
+        );
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+mod capability {
+    use std::sync::Arc;
+
+    use crux_core::capability::{Capability, CapabilityContext, Never};
+    use crux_http::Http;
+    use crux_time::{Duration, Time, TimeResponse};
+
+    /// A capability implemented in terms of `Http` and `Time`
+    pub struct Session<Ev> {
+        context: CapabilityContext<Never, Ev>,
+        http: Http<Ev>,
+        time: Time<Ev>,
+    }
+
+    impl<Ev> Session<Ev>
+    where
+        Ev: 'static,
+    {
+        pub fn new(context: CapabilityContext<Never, Ev>, http: Http<Ev>, time: Time<Ev>) -> Self {
+            Self {
+                context,
+                http,
+                time,
+            }
+        }
+
+        /// Sign in, then expire the session after an hour
+        pub fn sign_in<F>(&self, user: &str, callback: F)
+        where
+            F: FnOnce(bool) -> Ev + Send + Sync + 'static,
+        {
+            let context = self.context.clone();
+            let http = self.http.clone();
+            let time = self.time.clone();
+            let url = format!("https://example.com/sign_in/{user}");
+
+            self.context.spawn(async move {
+                let signed_in = http
+                    .post(url)
+                    .send_async()
+                    .await
+                    .map_or(false, |response| response.status().is_success());
+
+                if signed_in {
+                    let hour = Duration::from_secs(60 * 60).unwrap();
+                    let _: TimeResponse =
+                        time.notify_after_async(crux_time::TimerId(1), hour).await;
+                }
+
+                context.update_app(callback(signed_in));
+            });
+        }
+    }
+
+    impl<Ev> Capability<Ev> for Session<Ev> {
+        type Operation = Never;
+        type MappedSelf<MappedEv> = Session<MappedEv>;
+
+        fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+        where
+            F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+            Ev: 'static,
+            NewEv: 'static + Send,
+        {
+            let f = Arc::new(f);
+
+            Session {
+                context: self.context.map_event({
+                    let f = Arc::clone(&f);
+                    move |event| f(event)
+                }),
+                http: self.http.map_event({
+                    let f = Arc::clone(&f);
+                    move |event| f(event)
+                }),
+                time: self.time.map_event(move |event| f(event)),
+            }
+        }
+    }
+}
+
+mod app {
+    use crux_core::macros::Effect;
+    use crux_http::Http;
+    use crux_time::Time;
+    use serde::{Deserialize, Serialize};
+
+    use crate::capability::Session;
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        SignIn,
+        Fetch,
+
+        #[serde(skip)]
+        SignedIn(bool),
+        #[serde(skip)]
+        Fetched(crux_http::Result<crux_http::Response<Vec<u8>>>),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub signed_in: bool,
+        pub fetched: bool,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::SignIn => caps.session.sign_in("ferris", Event::SignedIn),
+                Event::Fetch => caps
+                    .http
+                    .get("https://example.com/data")
+                    .send(Event::Fetched),
+                Event::SignedIn(signed_in) => model.signed_in = signed_in,
+                Event::Fetched(result) => model.fetched = result.is_ok(),
+            }
+        }
+
+        fn view(&self, _model: &Model) -> Self::ViewModel {}
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub http: Http<Event>,
+        // only used through the session
+        #[allow(dead_code)]
+        pub time: Time<Event>,
+        #[effect(uses(http, time))]
+        pub session: Session<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::testing::AppTester;
+    use crux_http::protocol::{HttpResponse, HttpResult};
+    use crux_time::TimeRequest;
+
+    use crate::app::{App, Effect, Event, Model};
+
+    #[test]
+    fn requests_are_attributed_to_the_derived_capability() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let mut update = app.update(Event::SignIn, &mut model);
+        let mut request = update.effects.remove(0).expect_http();
+        assert_eq!(request.source(), Some("Session"));
+        assert_eq!(request.operation.url, "https://example.com/sign_in/ferris");
+
+        let update = app
+            .resolve(&mut request, HttpResult::Ok(HttpResponse::ok().build()))
+            .unwrap();
+        let request = update.into_effects().next().unwrap().expect_time();
+        assert_eq!(request.source(), Some("Session"));
+        assert!(matches!(request.operation, TimeRequest::NotifyAfter { .. }));
+    }
+
+    #[test]
+    fn requests_made_directly_are_not_attributed() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let update = app.update(Event::Fetch, &mut model);
+        let request = update.into_effects().next().unwrap().expect_http();
+
+        assert_eq!(request.source(), None);
+    }
+
+    #[test]
+    fn attribution_shows_in_debug_output() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let update = app.update(Event::SignIn, &mut model);
+
+        assert!(format!("{:?}", update.effects[0]).contains("source: \"Session\""));
+    }
+}
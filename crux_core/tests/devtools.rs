@@ -0,0 +1,147 @@
+#[cfg(feature = "devtools")]
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Increment,
+    }
+
+    #[derive(Default, Serialize)]
+    pub struct Model {
+        count: u32,
+        // not something the user sees, but useful when debugging
+        last_event: Option<String>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = String;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            model.last_event = Some(format!("{event:?}"));
+            match event {
+                Event::Increment => model.count += 1,
+            }
+            caps.render.render();
+        }
+
+        fn view(&self, model: &Model) -> Self::ViewModel {
+            model.count.to_string()
+        }
+
+        fn debug_model(&self, model: &Model) -> serde_json::Value {
+            serde_json::to_value(model).unwrap()
+        }
+
+        fn debug_event(&self, event: &Event) -> Option<String> {
+            Some(format!("{event:?}"))
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub render: Render<Event>,
+    }
+}
+
+#[cfg(feature = "devtools")]
+mod tests {
+    use bincode::{DefaultOptions, Options};
+    use crux_core::{
+        bridge::{Bridge, EffectCause, Envelope, Request},
+        cause::Cause,
+        Core,
+    };
+    use serde_json::json;
+
+    use crate::app::{App, Effect, EffectFfi, Event};
+
+    #[test]
+    fn core_describes_the_model() {
+        let core: Core<Effect, App> = Core::new();
+        assert_eq!(
+            core.debug_model(),
+            json!({ "count": 0, "last_event": null })
+        );
+
+        core.process_event(Event::Increment);
+
+        assert_eq!(
+            core.debug_model(),
+            json!({ "count": 1, "last_event": "Increment" })
+        );
+    }
+
+    #[test]
+    fn bridge_returns_the_model_as_json() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        let event = DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .serialize(&Event::Increment)
+            .unwrap();
+        bridge.process_event(&event);
+
+        let model: serde_json::Value = serde_json::from_slice(&bridge.debug_model()).unwrap();
+
+        assert_eq!(model, json!({ "count": 1, "last_event": "Increment" }));
+    }
+
+    fn cause(event_id: u64) -> Cause {
+        Cause {
+            event_id,
+            event: Some("Increment".to_string()),
+        }
+    }
+
+    #[test]
+    fn requests_carry_the_event_which_caused_them() {
+        let core: Core<Effect, App> = Core::new();
+
+        let effects = core.process_event(Event::Increment);
+        let Effect::Render(request) = &effects[0];
+        assert_eq!(request.cause(), Some(&cause(1)));
+
+        let effects = core.process_event(Event::Increment);
+        let Effect::Render(request) = &effects[0];
+        assert_eq!(request.cause(), Some(&cause(2)));
+    }
+
+    #[test]
+    fn bridge_returns_the_causes_in_the_envelope() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        let options = DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes();
+        let envelope = options
+            .serialize(&Envelope {
+                sequence: 0,
+                in_reply_to: None,
+                trace_id: None,
+                payload: options.serialize(&Event::Increment).unwrap(),
+                causes: vec![],
+            })
+            .unwrap();
+
+        let reply: Envelope = options
+            .deserialize(&bridge.process_event_enveloped(&envelope))
+            .unwrap();
+        let requests: Vec<Request<EffectFfi>> = options.deserialize(&reply.payload).unwrap();
+
+        assert_eq!(
+            reply.causes,
+            [EffectCause {
+                effect: requests[0].id.0,
+                cause: cause(1),
+            }]
+        );
+    }
+}
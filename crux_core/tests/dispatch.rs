@@ -0,0 +1,111 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::{dispatch::Dispatch, render::Render};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub enum Event {
+        SetFilter(String),
+        Refresh,
+        Loop,
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub filter: String,
+        pub log: Vec<String>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = Vec<String>;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Self::Model, caps: &Capabilities) {
+            match event {
+                Event::SetFilter(filter) => {
+                    model.log.push(format!("filter {filter}"));
+                    model.filter = filter;
+                    caps.dispatch.send(Event::Refresh);
+                    model.log.push("filter done".to_string());
+                }
+                Event::Refresh => {
+                    model.log.push(format!("refresh {}", model.filter));
+                    caps.render.render();
+                }
+                Event::Loop => caps.dispatch.send(Event::Loop),
+            }
+        }
+
+        fn view(&self, model: &Self::Model) -> Self::ViewModel {
+            model.log.clone()
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub render: Render<Event>,
+        #[effect(skip)]
+        pub dispatch: Dispatch<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::{testing::AppTester, Core};
+
+    use crate::app::{App, Effect, Event, Model};
+
+    #[test]
+    fn dispatched_events_are_processed_in_the_same_call() {
+        let core: Core<Effect, App> = Core::new();
+
+        let effects = core.process_event(Event::SetFilter("active".to_string()));
+
+        assert!(matches!(effects[..], [Effect::Render(_)]));
+        assert_eq!(
+            core.view(),
+            vec!["filter active", "filter done", "refresh active"]
+        );
+    }
+
+    #[test]
+    fn dispatched_events_are_visible_in_tests() {
+        let app = AppTester::<App, _>::default();
+        let mut model = Model::default();
+
+        let update = app.update(Event::SetFilter("all".to_string()), &mut model);
+
+        assert_eq!(update.events, vec![Event::Refresh]);
+    }
+
+    #[test]
+    fn dispatching_in_a_loop_drops_the_events() {
+        let core: Core<Effect, App> = Core::new();
+
+        let effects = core.process_event(Event::Loop);
+
+        assert!(effects.is_empty());
+        assert_eq!(core.metrics().events_processed, 10_001);
+        assert_eq!(core.metrics().dropped_events, 1);
+    }
+
+    #[test]
+    fn events_are_limited_for_the_whole_message() {
+        let core: Core<Effect, App> = Core::new().with_max_events(3);
+
+        core.process_events(["a", "b", "c", "d"].map(|filter| Event::SetFilter(filter.into())));
+
+        assert_eq!(core.metrics().dropped_events, 1);
+        assert_eq!(core.view().last().unwrap(), "filter done");
+
+        // the next message starts counting afresh
+        core.process_event(Event::SetFilter("e".to_string()));
+
+        assert_eq!(core.view().last().unwrap(), "refresh e");
+        assert_eq!(core.metrics().dropped_events, 1);
+    }
+}
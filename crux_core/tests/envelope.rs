@@ -0,0 +1,165 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_time::{Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        GetTime,
+        #[serde(skip)]
+        SetTime(TimeResponse),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub time: Option<u64>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Option<u64>;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Self::Model, caps: &Capabilities) {
+            match event {
+                Event::GetTime => caps.time.now(Event::SetTime),
+                Event::SetTime(TimeResponse::Now(instant)) => {
+                    *model = Some(instant.seconds);
+                    caps.render.render();
+                }
+                Event::SetTime(_) => {}
+            }
+        }
+
+        fn view(&self, model: &Self::Model) -> Self::ViewModel {
+            ViewModel { time: *model }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use bincode::{DefaultOptions, Options};
+    use crux_core::{
+        bridge::{Bridge, BridgeError, Envelope, Request},
+        Core,
+    };
+    use crux_time::{Instant, TimeResponse};
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use crate::app::{App, Effect, EffectFfi, Event, ViewModel};
+
+    fn options() -> impl Options + Copy {
+        DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+    }
+
+    fn serialize(value: &impl Serialize) -> Vec<u8> {
+        options().serialize(value).unwrap()
+    }
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> T {
+        options().deserialize(bytes).unwrap()
+    }
+
+    fn envelope(sequence: u64, trace_id: Option<&str>, payload: &impl Serialize) -> Vec<u8> {
+        serialize(&Envelope {
+            sequence,
+            in_reply_to: None,
+            trace_id: trace_id.map(ToString::to_string),
+            payload: serialize(payload),
+            causes: vec![],
+        })
+    }
+
+    #[test]
+    fn replies_carry_the_trace() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        let reply: Envelope = deserialize(&bridge.process_event_enveloped(&envelope(
+            7,
+            Some("tap"),
+            &Event::GetTime,
+        )));
+        assert_eq!(reply.sequence, 0);
+        assert_eq!(reply.in_reply_to, Some(7));
+        assert_eq!(reply.trace_id.as_deref(), Some("tap"));
+
+        let requests: Vec<Request<EffectFfi>> = deserialize(&reply.payload);
+        let Request {
+            id,
+            effect: EffectFfi::Time(_),
+            ..
+        } = &requests[0]
+        else {
+            panic!("Expected a time request");
+        };
+
+        let now = TimeResponse::Now(Instant::new(1, 0).unwrap());
+        let reply: Envelope =
+            deserialize(&bridge.handle_response_enveloped(id.0, &envelope(8, Some("tap"), &now)));
+        assert_eq!(reply.sequence, 1);
+        assert_eq!(reply.in_reply_to, Some(8));
+        assert_eq!(reply.trace_id.as_deref(), Some("tap"));
+
+        let requests: Vec<Request<EffectFfi>> = deserialize(&reply.payload);
+        assert!(matches!(requests[0].effect, EffectFfi::Render(_)));
+
+        let view: ViewModel = deserialize(&bridge.view());
+        assert_eq!(view, ViewModel { time: Some(1) });
+    }
+
+    #[test]
+    fn trace_id_is_optional() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        let reply: Envelope =
+            deserialize(&bridge.process_event_enveloped(&envelope(0, None, &Event::GetTime)));
+        assert_eq!(reply.trace_id, None);
+        assert_eq!(reply.in_reply_to, Some(0));
+    }
+
+    #[cfg(not(feature = "devtools"))]
+    #[test]
+    fn no_causes_without_devtools() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        let reply: Envelope =
+            deserialize(&bridge.process_event_enveloped(&envelope(0, None, &Event::GetTime)));
+        assert!(reply.causes.is_empty());
+    }
+
+    #[test]
+    fn malformed_messages_are_an_error() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        assert_eq!(
+            bridge.try_process_event_enveloped(&[0xff; 3]),
+            Err(BridgeError::InvalidEnvelope)
+        );
+        assert_eq!(
+            bridge.try_process_event_enveloped(&envelope(0, None, &u32::MAX)),
+            Err(BridgeError::InvalidEvent)
+        );
+        assert_eq!(
+            bridge.try_handle_response_enveloped(42, &envelope(1, None, &())),
+            Err(BridgeError::NotFound { id: 42 })
+        );
+
+        // rejected messages don't take a sequence number
+        let reply: Envelope =
+            deserialize(&bridge.process_event_enveloped(&envelope(2, None, &Event::GetTime)));
+        assert_eq!(reply.sequence, 0);
+    }
+}
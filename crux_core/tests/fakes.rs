@@ -0,0 +1,146 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_time::{Duration, Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Start,
+        #[serde(skip)]
+        Started(TimeResponse),
+        #[serde(skip)]
+        Tick(TimeResponse),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        started_at: Option<u64>,
+        ticks: u32,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub started_at: Option<u64>,
+        pub ticks: u32,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Start => caps.time.now(Event::Started),
+                Event::Started(TimeResponse::Now(instant)) => {
+                    model.started_at = Some(instant.seconds);
+                    caps.time
+                        .notify_after(Duration::from_secs(1).unwrap(), Event::Tick);
+                    caps.render.render();
+                }
+                Event::Tick(TimeResponse::DurationElapsed { .. }) => {
+                    model.ticks += 1;
+                    caps.render.render();
+                }
+                Event::Started(_) | Event::Tick(_) => {}
+            }
+        }
+
+        fn view(&self, model: &Model) -> ViewModel {
+            ViewModel {
+                started_at: model.started_at,
+                ticks: model.ticks,
+            }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crux_core::{
+        bridge::{Bridge, LocalReply, Request},
+        Core,
+    };
+    use crux_time::{Instant, TimeRequest, TimeResponse};
+
+    use crate::app::{App, Effect, EffectFfi, Event, ViewModel};
+
+    /// A bridge with the time capability backed by a fake clock, which answers `Now`
+    /// straight away, and keeps the ids of the timers to fire them later
+    fn bridge(timers: Arc<Mutex<Vec<u32>>>) -> Bridge<Effect, App> {
+        Bridge::new(Core::new()).with_fakes(move |request: &Request<EffectFfi>| {
+            match &request.effect {
+                EffectFfi::Time(TimeRequest::Now) => {
+                    let now = TimeResponse::Now(Instant::new(1_700_000_000, 0).unwrap());
+                    LocalReply::Respond(bincode::serialize(&now).unwrap())
+                }
+                EffectFfi::Time(TimeRequest::NotifyAfter { .. }) => {
+                    timers.lock().unwrap().push(request.id.0);
+                    LocalReply::Done
+                }
+                _ => LocalReply::ToShell,
+            }
+        })
+    }
+
+    fn to_shell(requests: &[u8]) -> Vec<Request<EffectFfi>> {
+        bincode::deserialize(requests).unwrap()
+    }
+
+    #[test]
+    fn faked_capabilities_are_handled_in_process() {
+        let timers = Arc::default();
+        let bridge = bridge(Arc::clone(&timers));
+
+        let requests = to_shell(&bridge.process_event(&bincode::serialize(&Event::Start).unwrap()));
+
+        // the time request was answered by the fake, only the render reaches the shell
+        let [Request {
+            effect: EffectFfi::Render(_),
+            ..
+        }] = &requests[..]
+        else {
+            panic!("Expected a single render");
+        };
+
+        let view: ViewModel = bincode::deserialize(&bridge.view()).unwrap();
+        assert_eq!(
+            view,
+            ViewModel {
+                started_at: Some(1_700_000_000),
+                ticks: 0
+            }
+        );
+    }
+
+    #[test]
+    fn fakes_can_respond_later() {
+        let timers = Arc::new(Mutex::new(Vec::new()));
+        let bridge = bridge(Arc::clone(&timers));
+
+        bridge.process_event(&bincode::serialize(&Event::Start).unwrap());
+
+        let timer = timers.lock().unwrap().pop().expect("a timer");
+        let elapsed = TimeResponse::DurationElapsed {
+            id: crux_time::TimerId(0),
+        };
+        let requests =
+            to_shell(&bridge.handle_response(timer, &bincode::serialize(&elapsed).unwrap()));
+        assert_eq!(requests.len(), 1);
+
+        let view: ViewModel = bincode::deserialize(&bridge.view()).unwrap();
+        assert_eq!(view.ticks, 1);
+    }
+}
@@ -0,0 +1,130 @@
+mod app {
+    use crux_core::ids::{Id, Ids};
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Add(String),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub items: Vec<(Id, String)>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub items: Vec<String>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Add(name) => {
+                    model.items.push((caps.ids.next(), name));
+                    caps.render.render();
+                }
+            }
+        }
+
+        fn view(&self, model: &Model) -> ViewModel {
+            ViewModel {
+                items: model
+                    .items
+                    .iter()
+                    .map(|(id, name)| format!("{id} {name}"))
+                    .collect(),
+            }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub render: Render<Event>,
+        #[effect(skip)]
+        pub ids: Ids<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::{ids::Id, testing::AppTester, Core};
+
+    use crate::app::{App, Effect, Event, Model};
+
+    fn ids(app: &AppTester<App, Effect>) -> Vec<Id> {
+        let mut model = Model::default();
+        for name in ["milk", "eggs", "bread"] {
+            app.update(Event::Add(name.to_string()), &mut model)
+                .expect_one_effect()
+                .expect_render();
+        }
+
+        model.items.into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn ids_are_unique_and_in_order() {
+        let ids = ids(&AppTester::default());
+
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            ids.iter().map(|id| id.sequence()).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn testers_make_the_same_ids_every_time() {
+        assert_eq!(ids(&AppTester::default()), ids(&AppTester::default()));
+
+        let seeded = ids(&AppTester::default().with_seed(42));
+        assert_eq!(seeded, ids(&AppTester::default().with_seed(42)));
+        assert_ne!(seeded, ids(&AppTester::default()));
+    }
+
+    #[test]
+    fn a_seeded_core_makes_the_same_ids_as_a_tester() {
+        let core: Core<Effect, App> = Core::new().with_seed(42);
+        for name in ["milk", "eggs", "bread"] {
+            core.process_event(Event::Add(name.to_string()));
+        }
+
+        let expected: Vec<_> = ids(&AppTester::default().with_seed(42))
+            .iter()
+            .zip(["milk", "eggs", "bread"])
+            .map(|(id, name)| format!("{id} {name}"))
+            .collect();
+        assert_eq!(core.view().items, expected);
+    }
+
+    #[test]
+    fn ids_are_displayed_like_ulids() {
+        assert_eq!(Id(0).to_string(), "00000000000000000000000000");
+        assert_eq!(Id(32).to_string(), "00000000000000000000000010");
+        assert_eq!(Id(u128::MAX).to_string(), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+    }
+
+    #[test]
+    fn short_ids_are_unique_across_cores() {
+        let short = |seed| -> Vec<u64> {
+            ids(&AppTester::default().with_seed(seed))
+                .into_iter()
+                .map(Id::to_u64)
+                .collect()
+        };
+        let (first, second) = (short(1), short(2));
+
+        assert!(first.iter().all(|id| !second.contains(id)));
+        assert_eq!(first, short(1));
+    }
+}
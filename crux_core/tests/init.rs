@@ -0,0 +1,133 @@
+mod app {
+    use crux_core::{init::Init, macros::Effect, render::Render};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Refresh,
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub api: String,
+        pub locale: String,
+        pub dark_mode: bool,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    pub struct ViewModel {
+        pub url: String,
+        pub locale: String,
+        pub dark_mode: bool,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn init(&self, config: Init) -> Model {
+            Model {
+                api: config
+                    .api_base_url
+                    .clone()
+                    .unwrap_or_else(|| "https://example.com".to_string()),
+                locale: config.locale.clone().unwrap_or_else(|| "en".to_string()),
+                dark_mode: config.has_feature("dark_mode"),
+            }
+        }
+
+        fn update(&self, _event: Event, _model: &mut Model, caps: &Capabilities) {
+            caps.render.render();
+        }
+
+        fn view(&self, model: &Model) -> ViewModel {
+            ViewModel {
+                url: format!("{}/items", model.api),
+                locale: model.locale.clone(),
+                dark_mode: model.dark_mode,
+            }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::{
+        bridge::{Bridge, BridgeError},
+        init::{Init, PlatformInfo},
+        testing::AppTester,
+        Core,
+    };
+
+    use crate::app::{App, Effect, ViewModel};
+
+    fn config() -> Init {
+        Init {
+            api_base_url: Some("https://staging.example.com".to_string()),
+            feature_flags: vec!["dark_mode".to_string()],
+            locale: Some("en-GB".to_string()),
+            platform: PlatformInfo {
+                name: "iOS".to_string(),
+                version: "17.4".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn core_creates_the_model_from_the_config() {
+        let core: Core<Effect, App> = Core::new();
+
+        core.init(config());
+
+        assert_eq!(
+            core.view(),
+            ViewModel {
+                url: "https://staging.example.com/items".to_string(),
+                locale: "en-GB".to_string(),
+                dark_mode: true,
+            }
+        );
+    }
+
+    #[test]
+    fn bridge_receives_the_config_serialized() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        bridge.init(&bincode::serialize(&config()).unwrap());
+
+        let view: ViewModel = bincode::deserialize(&bridge.view()).unwrap();
+        assert_eq!(view.url, "https://staging.example.com/items");
+        assert!(view.dark_mode);
+    }
+
+    #[test]
+    fn app_tester_creates_the_model_from_the_config() {
+        let app = AppTester::<App, Effect>::default();
+
+        let model = app.init(Init::default());
+
+        assert_eq!(model.api, "https://example.com");
+        assert_eq!(model.locale, "en");
+        assert!(!model.dark_mode);
+    }
+
+    #[test]
+    fn malformed_config_is_an_error() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        let before = bridge.view();
+
+        assert_eq!(bridge.try_init(&[0xff; 3]), Err(BridgeError::InvalidInit));
+
+        // the model is left as it was
+        assert_eq!(bridge.view(), before);
+    }
+}
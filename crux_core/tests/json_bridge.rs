@@ -42,6 +42,7 @@ mod core {
     pub type Bridge = BridgeWithSerializer<Effect, App>;
 }
 
+#[allow(clippy::useless_borrows_in_formatting)]
 mod tests {
 
     use crate::core::Bridge;
@@ -87,4 +88,22 @@ mod tests {
             )
         };
     }
+
+    #[test]
+    fn capabilities() {
+        let bridge = Bridge::new(Core::default());
+
+        let mut bytes = vec![];
+        bridge.capabilities(&mut serde_json::Serializer::new(&mut bytes));
+
+        let actual: Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(actual[0]["effect"], json!("Render"));
+        assert_eq!(actual[0]["field"], json!("render"));
+
+        let Value::String(operation) = &actual[0]["operation"] else {
+            panic!("Expected operation to be a string")
+        };
+        assert!(operation.ends_with("::RenderOperation"));
+    }
 }
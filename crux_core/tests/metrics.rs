@@ -0,0 +1,83 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_http::Http;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Refresh,
+        Increment,
+
+        #[serde(skip)]
+        Fetched(crux_http::Result<crux_http::Response<Vec<u8>>>),
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = u32;
+        type ViewModel = u32;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut u32, caps: &Capabilities) {
+            match event {
+                Event::Refresh => caps
+                    .http
+                    .get("https://example.com/count")
+                    .send(Event::Fetched),
+                Event::Increment => {
+                    *model += 1;
+                    caps.render.render();
+                }
+                Event::Fetched(result) => {
+                    if result.is_ok() {
+                        caps.render.render();
+                    }
+                }
+            }
+        }
+
+        fn view(&self, model: &u32) -> u32 {
+            *model
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub http: Http<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crux_core::{bridge::Bridge, metrics::Metrics, Core};
+
+    use crate::app::{App, Effect, Event};
+
+    #[test]
+    fn bridge_counts_events_effects_and_bytes() {
+        let bridge = Bridge::<Effect, App>::new(Core::default());
+
+        let mut bytes_out = 0;
+        for event in [Event::Increment, Event::Increment, Event::Refresh] {
+            bytes_out += bridge
+                .process_event(&bincode::serialize(&event).unwrap())
+                .len();
+        }
+        bytes_out += bridge.view().len();
+
+        let metrics: Metrics = bincode::deserialize(&bridge.metrics()).unwrap();
+
+        assert_eq!(metrics.events_processed, 3);
+        assert_eq!(
+            metrics.effects_emitted,
+            BTreeMap::from([("Http".to_string(), 1), ("Render".to_string(), 2)])
+        );
+        assert_eq!(metrics.bytes_out, bytes_out as u64);
+    }
+}
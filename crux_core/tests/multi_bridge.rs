@@ -0,0 +1,111 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Increment,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub count: u64,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = u64;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, _event: Event, model: &mut Self::Model, caps: &Capabilities) {
+            *model += 1;
+            caps.render.render();
+        }
+
+        fn view(&self, model: &Self::Model) -> Self::ViewModel {
+            ViewModel { count: *model }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use bincode::{DefaultOptions, Options};
+    use crux_core::bridge::{MultiBridge, Request};
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use crate::app::{App, Effect, EffectFfi, Event, ViewModel};
+
+    fn options() -> impl Options + Copy {
+        DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+    }
+
+    fn serialize(value: &impl Serialize) -> Vec<u8> {
+        options().serialize(value).unwrap()
+    }
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> T {
+        options().deserialize(bytes).unwrap()
+    }
+
+    #[test]
+    fn instances_are_independent() {
+        let bridge = MultiBridge::<Effect, App>::new();
+        let first = bridge.create_instance();
+        let second = bridge.create_instance();
+        assert_ne!(first, second);
+
+        bridge.process_event(first, &serialize(&Event::Increment));
+        bridge.process_event(first, &serialize(&Event::Increment));
+        bridge.process_event(second, &serialize(&Event::Increment));
+
+        let view: ViewModel = deserialize(&bridge.view(first));
+        assert_eq!(view, ViewModel { count: 2 });
+
+        let view: ViewModel = deserialize(&bridge.view(second));
+        assert_eq!(view, ViewModel { count: 1 });
+    }
+
+    #[test]
+    fn effect_ids_are_scoped_to_instances() {
+        let bridge = MultiBridge::<Effect, App>::new();
+        let first = bridge.create_instance();
+        let second = bridge.create_instance();
+
+        let requests: Vec<Request<EffectFfi>> =
+            deserialize(&bridge.process_event(first, &serialize(&Event::Increment)));
+        assert_eq!(requests[0].id.0, 0);
+
+        let requests: Vec<Request<EffectFfi>> =
+            deserialize(&bridge.process_event(second, &serialize(&Event::Increment)));
+        assert_eq!(requests[0].id.0, 0);
+    }
+
+    #[test]
+    fn destroyed_instance_is_removed() {
+        let bridge = MultiBridge::<Effect, App>::new();
+        let instance = bridge.create_instance();
+
+        assert!(bridge.destroy_instance(instance));
+        assert!(!bridge.destroy_instance(instance));
+    }
+
+    #[test]
+    #[should_panic(expected = "Instance 0 not found.")]
+    fn unknown_instance_panics() {
+        let bridge = MultiBridge::<Effect, App>::new();
+
+        bridge.view(0);
+    }
+}
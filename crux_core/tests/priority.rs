@@ -0,0 +1,201 @@
+mod capability {
+    use crux_core::capability::{CapabilityContext, Operation, Priority};
+    use crux_core::macros::Capability;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct Prefetch {
+        pub url: String,
+    }
+
+    impl Operation for Prefetch {
+        type Output = ();
+    }
+
+    #[derive(Capability)]
+    pub struct Prefetcher<Ev> {
+        context: CapabilityContext<Prefetch, Ev>,
+    }
+
+    impl<Ev> Prefetcher<Ev>
+    where
+        Ev: 'static,
+    {
+        pub fn new(context: CapabilityContext<Prefetch, Ev>) -> Self {
+            Self { context }
+        }
+
+        /// Prefetch in the background, with a low priority
+        pub fn prefetch(&self, url: &str) {
+            self.prefetch_with_priority(url, Priority::Low);
+        }
+
+        pub fn prefetch_with_priority(&self, url: &str, priority: Priority) {
+            let context = self.context.with_priority(priority);
+            let url = url.to_string();
+            self.context.spawn(async move {
+                context.notify_shell(Prefetch { url }).await;
+            });
+        }
+    }
+}
+
+mod app {
+    use crux_core::capability::Priority;
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use serde::{Deserialize, Serialize};
+
+    use crate::capability::Prefetcher;
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Open,
+        /// Prefetch `count` urls, cycling through the priorities, starting with low
+        Flood {
+            count: usize,
+        },
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = ();
+        type ViewModel = ();
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, _model: &mut Self::Model, caps: &Capabilities) {
+            match event {
+                Event::Open => {
+                    caps.prefetcher.prefetch("/next");
+                    caps.render.render();
+                    caps.prefetcher.prefetch("/previous");
+                }
+                Event::Flood { count } => {
+                    let priorities = [Priority::Low, Priority::High, Priority::Normal];
+                    for (index, priority) in priorities.iter().cycle().take(count).enumerate() {
+                        caps.prefetcher
+                            .prefetch_with_priority(&format!("/{index}"), *priority);
+                    }
+                }
+            }
+        }
+
+        fn view(&self, _model: &Self::Model) -> Self::ViewModel {}
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub prefetcher: Prefetcher<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use bincode::{DefaultOptions, Options};
+    use crux_core::{
+        bridge::{Bridge, Request},
+        capability::Priority,
+        testing::AppTester,
+        Effect as _,
+    };
+
+    use crate::app::{App, Effect, EffectFfi, Event};
+
+    #[test]
+    fn requests_carry_their_priority() {
+        let app = AppTester::<App, _>::default();
+
+        let update = app.update(Event::Open, &mut ());
+        let priorities: Vec<_> = update.effects.iter().map(Effect::priority).collect();
+
+        assert_eq!(
+            priorities,
+            vec![Priority::Low, Priority::Normal, Priority::Low]
+        );
+    }
+
+    #[test]
+    fn bridge_returns_requests_in_priority_order() {
+        let bridge = Bridge::<Effect, App>::new(Default::default());
+        let options = DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes();
+
+        let event = options.serialize(&Event::Open).unwrap();
+        let requests: Vec<Request<EffectFfi>> =
+            options.deserialize(&bridge.process_event(&event)).unwrap();
+
+        let effects: Vec<_> = requests
+            .iter()
+            .map(|request| match &request.effect {
+                EffectFfi::Prefetcher(prefetch) => (request.priority, prefetch.url.as_str()),
+                EffectFfi::Render(_) => (request.priority, "render"),
+            })
+            .collect();
+
+        assert_eq!(
+            effects,
+            vec![
+                (Priority::Normal, "render"),
+                (Priority::Low, "/next"),
+                (Priority::Low, "/previous"),
+            ]
+        );
+    }
+
+    fn flood(bridge: &Bridge<Effect, App>, count: usize) -> Vec<(Priority, usize)> {
+        let options = DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes();
+
+        let event = options.serialize(&Event::Flood { count }).unwrap();
+        let requests: Vec<Request<EffectFfi>> =
+            options.deserialize(&bridge.process_event(&event)).unwrap();
+
+        requests
+            .iter()
+            .map(|request| match &request.effect {
+                EffectFfi::Prefetcher(prefetch) => {
+                    (request.priority, prefetch.url[1..].parse().unwrap())
+                }
+                EffectFfi::Render(_) => panic!("Unexpected render"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn low_priority_requests_are_not_held_back() {
+        let bridge = Bridge::<Effect, App>::new(Default::default());
+
+        // however many more urgent requests there are, every low priority request
+        // reaches the shell in the call which made it
+        for _ in 0..3 {
+            let requests = flood(&bridge, 3000);
+            assert_eq!(requests.len(), 3000);
+
+            let low: Vec<_> = requests
+                .iter()
+                .filter(|(priority, _)| *priority == Priority::Low)
+                .map(|(_, index)| *index)
+                .collect();
+            let expected: Vec<_> = (0..3000).step_by(3).collect();
+            assert_eq!(low, expected);
+        }
+    }
+
+    #[test]
+    fn requests_are_ordered_by_priority_then_as_made() {
+        let bridge = Bridge::<Effect, App>::new(Default::default());
+
+        let requests = flood(&bridge, 3000);
+
+        let mut expected = requests.clone();
+        expected.sort_by_key(|&(priority, index)| (priority, index));
+        assert_eq!(requests, expected);
+        assert_eq!(requests[0], (Priority::High, 1));
+        assert_eq!(requests[2999], (Priority::Low, 2997));
+    }
+}
@@ -0,0 +1,182 @@
+#[cfg(feature = "devtools")]
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_time::{Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Start,
+        Increment,
+        #[serde(skip)]
+        Started(TimeResponse),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        started_at: Option<u64>,
+        count: u32,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub started_at: Option<u64>,
+        pub count: u32,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Start => caps.time.now(Event::Started),
+                Event::Increment => model.count += 1,
+                Event::Started(TimeResponse::Now(instant)) => {
+                    model.started_at = Some(instant.seconds);
+                }
+                Event::Started(_) => {}
+            }
+            caps.render.render();
+        }
+
+        fn view(&self, model: &Model) -> ViewModel {
+            ViewModel {
+                started_at: model.started_at,
+                count: model.count,
+            }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+#[cfg(feature = "devtools")]
+mod tests {
+    use crux_core::{
+        bridge::{
+            session::{Message, Session, SessionError},
+            Bridge, Request,
+        },
+        Core,
+    };
+    use crux_time::{Instant, TimeRequest, TimeResponse};
+
+    use crate::app::{App, Effect, EffectFfi, Event, ViewModel};
+
+    /// Record a session which starts the app, answers its time request, and increments
+    fn record() -> Session {
+        let bridge = Bridge::<Effect, App>::new(Core::new()).with_recording();
+
+        let requests: Vec<Request<EffectFfi>> = bincode::deserialize(
+            &bridge.process_event(&bincode::serialize(&Event::Start).unwrap()),
+        )
+        .unwrap();
+        let Some(Request { id, .. }) = requests
+            .iter()
+            .find(|request| matches!(request.effect, EffectFfi::Time(TimeRequest::Now)))
+        else {
+            panic!("Expected a time request");
+        };
+        let now = TimeResponse::Now(Instant::new(1_700_000_000, 0).unwrap());
+        bridge.handle_response(id.0, &bincode::serialize(&now).unwrap());
+        bridge.process_event(&bincode::serialize(&Event::Increment).unwrap());
+
+        bridge.session().expect("the bridge is recording")
+    }
+
+    #[test]
+    fn bridge_records_the_messages_and_views() {
+        let session = record();
+
+        assert_eq!(session.steps.len(), 3);
+        assert!(matches!(session.steps[0].message, Message::Event { .. }));
+        assert!(matches!(session.steps[1].message, Message::Response { .. }));
+
+        let view: ViewModel = bincode::deserialize(&session.steps[2].view).unwrap();
+        assert_eq!(
+            view,
+            ViewModel {
+                started_at: Some(1_700_000_000),
+                count: 1
+            }
+        );
+    }
+
+    #[test]
+    fn bridge_without_recording_has_no_session() {
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        bridge.process_event(&bincode::serialize(&Event::Increment).unwrap());
+
+        assert_eq!(bridge.session(), None);
+    }
+
+    #[test]
+    fn session_survives_export_and_import() {
+        let session = record();
+
+        let exported = session.export();
+        assert!(exported.starts_with(b"{\"version\":1}\n"));
+
+        assert_eq!(Session::import(&exported), Ok(session));
+    }
+
+    #[test]
+    fn replay_matches_the_recording() {
+        let session = record();
+
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+
+        assert_eq!(bridge.replay(&session), Ok(vec![]));
+    }
+
+    #[test]
+    fn replay_reports_diverging_views() {
+        let mut session = record();
+        let expected = bincode::serialize(&ViewModel {
+            started_at: Some(1_700_000_000),
+            count: 2,
+        })
+        .unwrap();
+        session.steps[2].view = expected.clone();
+
+        let bridge = Bridge::<Effect, App>::new(Core::new());
+        let divergences = bridge.replay(&session).unwrap();
+
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].step, 2);
+        assert_eq!(divergences[0].expected, expected);
+        let actual: ViewModel = bincode::deserialize(&divergences[0].actual).unwrap();
+        assert_eq!(actual.count, 1);
+    }
+
+    #[test]
+    fn import_rejects_other_versions_and_invalid_lines() {
+        assert_eq!(
+            Session::import(b"{\"version\":2}\n"),
+            Err(SessionError::UnsupportedVersion { version: 2 })
+        );
+
+        let Err(SessionError::Invalid { line, .. }) =
+            Session::import(b"{\"version\":1}\n{\"message\":\"nope\"}\n")
+        else {
+            panic!("Expected an invalid line");
+        };
+        assert_eq!(line, 2);
+
+        assert!(matches!(
+            Session::import(b""),
+            Err(SessionError::Invalid { line: 1, .. })
+        ));
+    }
+}
@@ -10,6 +10,8 @@ mod app {
     #[derive(Debug, Serialize, Deserialize)]
     pub enum Event {
         Hello,
+        Twice,
+        Append(String),
     }
 
     #[derive(Effect)]
@@ -26,8 +28,18 @@ mod app {
         type ViewModel = String;
         type Capabilities = Capabilities;
 
-        fn update(&self, _event: Self::Event, _model: &mut Self::Model, caps: &Self::Capabilities) {
-            caps.render.render()
+        fn update(&self, event: Self::Event, model: &mut Self::Model, caps: &Self::Capabilities) {
+            match event {
+                Event::Hello => caps.render.render(),
+                Event::Twice => {
+                    caps.render.render();
+                    caps.render.render();
+                }
+                Event::Append(text) => {
+                    model.push_str(&text);
+                    caps.render.render();
+                }
+            }
         }
 
         fn view(&self, model: &Self::Model) -> Self::ViewModel {
@@ -49,3 +61,60 @@ fn app_tester_new() {
 
     assert_eq!(effects.count(), 1);
 }
+
+#[test]
+fn effects_snapshot() {
+    let tester = AppTester::new(app::MyApp);
+
+    let mut model = "Hello".to_string();
+
+    let update = tester.update(app::Event::Twice, &mut model);
+
+    assert_eq!(
+        update.effects_snapshot(),
+        "\
+0: Render(
+    Request(
+        RenderOperation,
+    ),
+)
+1: Render(
+    Request(
+        RenderOperation,
+    ),
+)
+"
+    );
+}
+
+#[test]
+fn history_of_model_states() {
+    let tester = AppTester::new(app::MyApp).with_history();
+
+    let mut model = "Hello".to_string();
+
+    let _ = tester.update(app::Event::Append(",".to_string()), &mut model);
+    tester.assert_transition(|before, after| after.len() == before.len() + 1);
+
+    let _ = tester.update(app::Event::Hello, &mut model);
+    tester.assert_transition(|before, after| before == after);
+
+    let _ = tester.update(app::Event::Append(" world".to_string()), &mut model);
+    tester.assert_every_transition(|before, after| after.starts_with(before.as_str()));
+
+    assert_eq!(
+        tester.history(),
+        ["Hello", "Hello,", "Hello,", "Hello, world"]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Transition 0 doesn't match")]
+fn failing_transition() {
+    let tester = AppTester::new(app::MyApp).with_history();
+
+    let mut model = "Hello".to_string();
+
+    let _ = tester.update(app::Event::Hello, &mut model);
+    tester.assert_transition(|before, after| before != after);
+}
@@ -0,0 +1,128 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Increment,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub count: u64,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = u64;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, _event: Event, model: &mut Self::Model, caps: &Capabilities) {
+            *model += 1;
+            caps.render.render();
+        }
+
+        fn view(&self, model: &Self::Model) -> Self::ViewModel {
+            ViewModel { count: *model }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use std::{collections::HashSet, sync::Arc, thread};
+
+    use bincode::{DefaultOptions, Options};
+    use crux_core::{
+        bridge::{Bridge, MultiBridge, Request},
+        Core,
+    };
+    use static_assertions::assert_impl_all;
+
+    use crate::app::{App, Effect, EffectFfi, Event, ViewModel};
+
+    assert_impl_all!(Core<Effect, App>: Send, Sync);
+    assert_impl_all!(Bridge<Effect, App>: Send, Sync);
+    assert_impl_all!(MultiBridge<Effect, App>: Send, Sync);
+
+    const THREADS: usize = 8;
+    const EVENTS_PER_THREAD: usize = 500;
+
+    fn options() -> impl Options + Copy {
+        DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+    }
+
+    #[test]
+    fn core_process_event_from_many_threads() {
+        let core: Arc<Core<Effect, App>> = Arc::new(Core::new());
+
+        let handles = (0..THREADS)
+            .map(|_| {
+                let core = core.clone();
+                thread::spawn(move || {
+                    (0..EVENTS_PER_THREAD)
+                        .map(|_| core.process_event(Event::Increment).len())
+                        .sum::<usize>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let effects: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+        // every effect is returned exactly once, though not necessarily to the
+        // thread which processed the event causing it
+        assert_eq!(effects, THREADS * EVENTS_PER_THREAD);
+        assert_eq!(
+            core.view(),
+            ViewModel {
+                count: (THREADS * EVENTS_PER_THREAD) as u64
+            }
+        );
+    }
+
+    #[test]
+    fn bridge_process_event_from_many_threads() {
+        let bridge: Arc<Bridge<Effect, App>> = Arc::new(Bridge::new(Core::new()));
+        let event = options().serialize(&Event::Increment).unwrap();
+
+        let handles = (0..THREADS)
+            .map(|_| {
+                let bridge = bridge.clone();
+                let event = event.clone();
+                thread::spawn(move || {
+                    let mut ids = Vec::new();
+                    for _ in 0..EVENTS_PER_THREAD {
+                        let requests: Vec<Request<EffectFfi>> = options()
+                            .deserialize(&bridge.process_event(&event))
+                            .unwrap();
+                        ids.extend(requests.into_iter().map(|r| r.id.0));
+                    }
+                    ids
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let ids: Vec<u32> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        let unique: HashSet<u32> = ids.iter().copied().collect();
+
+        assert_eq!(ids.len(), THREADS * EVENTS_PER_THREAD);
+        assert_eq!(unique.len(), ids.len(), "effect ids must not be reused");
+
+        let view: ViewModel = options().deserialize(&bridge.view()).unwrap();
+        assert_eq!(view.count, (THREADS * EVENTS_PER_THREAD) as u64);
+    }
+}
@@ -28,6 +28,7 @@ mod shared {
 
     #[derive(Effect, Export)]
     pub struct Capabilities {
+        /// Redraws the UI
         #[allow(dead_code)]
         pub render: Render<Event>,
     }
@@ -35,8 +36,9 @@ mod shared {
 
 #[cfg(feature = "typegen")]
 mod test {
-    use super::shared::{App, Event};
-    use crux_core::typegen::TypeGen;
+    use super::shared::{App, Capabilities, Event};
+    use crux_core::typegen::{TypeGen, TypeGenError};
+    use serde::{Deserialize, Serialize};
     use uuid::Uuid;
 
     // FIXME this test is quite slow
@@ -62,6 +64,275 @@ mod test {
             .expect("typescript type gen failed");
     }
 
+    #[test]
+    fn generated_code_is_whitespace_clean() {
+        let mut gen = TypeGen::new();
+
+        let sample_events = vec![Event::SendUuid(Uuid::new_v4())];
+        gen.register_type_with_samples(sample_events).unwrap();
+
+        gen.register_app::<App>().unwrap();
+        gen.register_constant("MAX_ITEMS", 100).unwrap();
+        gen.register_capabilities::<Capabilities>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+
+        gen.swift("SharedTypes", temp.join("swift"))
+            .expect("swift type gen failed");
+        gen.java("com.example.counter.shared_types", temp.join("java"))
+            .expect("java type gen failed");
+
+        let mut problems = Vec::new();
+        for path in files(temp.path()) {
+            if !matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("swift" | "java")
+            ) {
+                continue;
+            }
+
+            let source = std::fs::read_to_string(&path).unwrap();
+            let name = path
+                .strip_prefix(temp.path())
+                .unwrap()
+                .display()
+                .to_string();
+            if source.lines().any(|line| line.ends_with([' ', '\t'])) {
+                problems.push(format!("{name}: trailing whitespace"));
+            }
+            if source.lines().any(|line| line.starts_with('\t')) {
+                problems.push(format!("{name}: indented with tabs"));
+            }
+            if !source.ends_with('\n') || source.ends_with("\n\n") {
+                problems.push(format!("{name}: doesn't end with a single newline"));
+            }
+            if source.contains("\n\n\n") {
+                problems.push(format!("{name}: consecutive blank lines"));
+            }
+            if source.starts_with('\n') {
+                problems.push(format!("{name}: starts with a blank line"));
+            }
+        }
+
+        assert_eq!(problems, Vec::<String>::new());
+    }
+
+    #[test]
+    fn extensions_are_overridden_from_a_directory() {
+        let mut gen = TypeGen::new();
+
+        let sample_events = vec![Event::SendUuid(Uuid::new_v4())];
+        gen.register_type_with_samples(sample_events).unwrap();
+        gen.register_app::<App>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let extensions = temp.join("templates");
+        std::fs::create_dir_all(extensions.join("swift")).unwrap();
+        std::fs::write(
+            extensions.join("swift").join("Package.swift"),
+            "// custom package for SharedTypes\n",
+        )
+        .unwrap();
+        gen.extensions_dir(&extensions);
+
+        gen.swift("Shared", temp.join("swift"))
+            .expect("swift type gen failed");
+
+        let package = std::fs::read_to_string(temp.join("swift/Shared/Package.swift")).unwrap();
+        assert_eq!(package, "// custom package for Shared\n");
+
+        // the files which aren't overridden are the built-in ones
+        let requests =
+            std::fs::read_to_string(temp.join("swift/Shared/Sources/Shared/Requests.swift"))
+                .unwrap();
+        assert!(requests.contains("public extension [Request]"));
+    }
+
+    #[test]
+    fn enum_accessors_are_generated_on_request() {
+        let mut gen = TypeGen::new();
+
+        let sample_events = vec![Event::SendUuid(Uuid::new_v4())];
+        gen.register_type_with_samples(sample_events).unwrap();
+        gen.register_app::<App>().unwrap();
+        gen.enum_accessors(true);
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        gen.swift("SharedTypes", temp.join("swift"))
+            .expect("swift type gen failed");
+        gen.java("com.example.shared_types", temp.join("java"))
+            .expect("java type gen failed");
+
+        let swift = std::fs::read_to_string(
+            temp.join("swift/SharedTypes/Sources/SharedTypes/EnumAccessors.swift"),
+        )
+        .unwrap();
+        assert!(swift.contains("extension Event {"));
+        assert!(swift.contains("public var isNone: Bool {"));
+        assert!(swift.contains("public var asSendUuid: [UInt8]? {"));
+        assert!(swift.contains("if case let .sendUuid(value) = self { return value }"));
+        // unit variants have no payload to return
+        assert!(!swift.contains("asNone"));
+
+        let kotlin =
+            std::fs::read_to_string(temp.join("java/com/example/shared_types/EnumAccessors.kt"))
+                .unwrap();
+        assert!(kotlin.contains("package com.example.shared_types\n"));
+        assert!(kotlin.contains("val Event.isNone: Boolean\n    get() = this is Event.None\n"));
+        assert!(
+            kotlin.contains("val Event.asSendUuid\n    get() = (this as? Event.SendUuid)?.value\n")
+        );
+    }
+
+    #[test]
+    fn capability_table_is_generated_on_request() {
+        let mut gen = TypeGen::new();
+
+        let sample_events = vec![Event::SendUuid(Uuid::new_v4())];
+        gen.register_type_with_samples(sample_events).unwrap();
+        gen.register_app::<App>().unwrap();
+        gen.register_capabilities::<Capabilities>().unwrap();
+        gen.rename_type("RenderOperation", "Render").unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        gen.swift("SharedTypes", temp.join("swift"))
+            .expect("swift type gen failed");
+        gen.java("com.example.shared_types", temp.join("java"))
+            .expect("java type gen failed");
+
+        let swift = std::fs::read_to_string(
+            temp.join("swift/SharedTypes/Sources/SharedTypes/CapabilityTable.swift"),
+        )
+        .unwrap();
+        assert!(swift.contains(
+            "CapabilityInfo(effect: \"Render\", capability: \"render\", operation: \"Render\", doc: \"Redraws the UI\"),\n"
+        ));
+        assert!(
+            swift.contains("public static func forEffect(_ effect: String) -> CapabilityInfo? {")
+        );
+
+        let java = std::fs::read_to_string(
+            temp.join("java/com/example/shared_types/CapabilityTable.java"),
+        )
+        .unwrap();
+        assert!(java.starts_with("package com.example.shared_types;\n"));
+        assert!(java.contains("new Info(\"Render\", \"render\", \"Render\", \"Redraws the UI\")\n"));
+    }
+
+    #[test]
+    fn files_no_longer_generated_are_removed() {
+        #[derive(Serialize, Deserialize)]
+        struct Removed {
+            value: u32,
+        }
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let swift = temp.join("swift/SharedTypes");
+        let java = temp.join("java/com/example/shared_types");
+
+        let mut gen = TypeGen::new();
+        gen.register_type_with_samples(vec![Event::SendUuid(Uuid::new_v4())])
+            .unwrap();
+        gen.register_app::<App>().unwrap();
+        gen.register_type::<Removed>().unwrap();
+        gen.register_constant("MAX_ITEMS", 100).unwrap();
+        gen.swift("SharedTypes", temp.join("swift"))
+            .expect("swift type gen failed");
+        gen.java("com.example.shared_types", temp.join("java"))
+            .expect("java type gen failed");
+
+        assert!(swift.join("Sources/SharedTypes/Constants.swift").exists());
+        assert!(java.join("Removed.java").exists());
+        // files the generator didn't write are kept
+        std::fs::write(java.join("Extensions.kt"), "").unwrap();
+
+        let mut gen = TypeGen::new();
+        gen.register_type_with_samples(vec![Event::SendUuid(Uuid::new_v4())])
+            .unwrap();
+        gen.register_app::<App>().unwrap();
+        gen.swift("SharedTypes", temp.join("swift"))
+            .expect("swift type gen failed");
+        gen.java("com.example.shared_types", temp.join("java"))
+            .expect("java type gen failed");
+
+        assert!(!swift.join("Sources/SharedTypes/Constants.swift").exists());
+        assert!(swift.join("Sources/SharedTypes/SharedTypes.swift").exists());
+        assert!(swift.join("Package.swift").exists());
+        assert!(!java.join("Removed.java").exists());
+        assert!(!java.join("Constants.java").exists());
+        assert!(java.join("Event.java").exists());
+        assert!(java.join("TraitHelpers.java").exists());
+        assert!(java.join("Extensions.kt").exists());
+
+        let list = std::fs::read_to_string(swift.join(".generated_files.json")).unwrap();
+        assert!(list.contains("\"Sources/SharedTypes/Requests.swift\""));
+    }
+
+    #[test]
+    fn enum_accessors_are_not_generated_by_default() {
+        let mut gen = TypeGen::new();
+
+        let sample_events = vec![Event::SendUuid(Uuid::new_v4())];
+        gen.register_type_with_samples(sample_events).unwrap();
+        gen.register_app::<App>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        gen.swift("SharedTypes", temp.join("swift"))
+            .expect("swift type gen failed");
+
+        assert!(!temp
+            .join("swift/SharedTypes/Sources/SharedTypes/EnumAccessors.swift")
+            .exists());
+        assert!(!temp
+            .join("swift/SharedTypes/Sources/SharedTypes/CapabilityTable.swift")
+            .exists());
+    }
+
+    #[test]
+    fn rust_types_are_a_standalone_crate() {
+        let mut gen = TypeGen::new();
+
+        let sample_events = vec![Event::SendUuid(Uuid::new_v4())];
+        gen.register_type_with_samples(sample_events).unwrap();
+        gen.register_app::<App>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        gen.rust("shared_types", temp.join("rust"))
+            .expect("rust type gen failed");
+        // generating again over the previous output is fine
+        gen.rust("shared_types", temp.join("rust"))
+            .expect("rust type gen failed");
+
+        let manifest = std::fs::read_to_string(temp.join("rust/shared_types/Cargo.toml")).unwrap();
+        assert!(manifest.contains("name = \"shared_types\""));
+        assert!(manifest.contains("serde = { version = \"1.0\", features = [\"derive\"] }"));
+        assert!(!manifest.contains("crux_core"));
+
+        let lib = std::fs::read_to_string(temp.join("rust/shared_types/src/lib.rs")).unwrap();
+        assert!(lib.contains("pub enum Event {"));
+        assert!(lib.contains("SendUuid(Bytes),"));
+        assert!(lib.contains("pub struct ViewModel;"));
+        assert!(lib.contains("pub enum Effect {"));
+        assert!(lib.contains("Render(RenderOperation),"));
+        assert!(
+            lib.contains("#[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]")
+        );
+    }
+
+    fn files(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .flat_map(|entry| {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    files(&path)
+                } else {
+                    vec![path]
+                }
+            })
+            .collect()
+    }
+
     // TODO: instead of using the Render capability here, it would be better to also test against a custom
     // capability that has an output type
     #[test]
@@ -73,6 +344,8 @@ mod test {
 
         gen.register_app::<App>()
             .expect("Should register types in App");
+        gen.register_init().expect("Should register Init");
+        gen.register_metrics().expect("Should register Metrics");
 
         let registry = match gen.state {
             crux_core::typegen::State::Registering(tracer, _) => {
@@ -87,8 +360,490 @@ mod test {
 
         assert!(registry.contains_key("Event"));
         assert!(registry.contains_key("ViewModel"));
+        assert!(registry.contains_key("Init"));
+        assert!(registry.contains_key("PlatformInfo"));
+        assert!(registry.contains_key("Metrics"));
 
         assert!(registry.contains_key("Effect"));
         assert!(registry.contains_key("RenderOperation"));
     }
+
+    #[test]
+    fn test_registry_lockfile() {
+        let mut gen = TypeGen::new();
+
+        gen.register_samples(vec![Event::SendUuid(Uuid::new_v4())])
+            .unwrap();
+        gen.register_app::<App>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.join("generated").join("registry.json");
+
+        gen.registry_lockfile(&path)
+            .expect("registry lockfile generation failed");
+
+        let lockfile: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert!(lockfile.get("Event").is_some());
+        assert!(lockfile.get("ViewModel").is_some());
+        assert!(lockfile.get("Effect").is_some());
+        assert!(lockfile.get("Init").is_none());
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct LoadState {
+        items: Result<Vec<String>, String>,
+        cached: Option<Result<Vec<String>, String>>,
+    }
+
+    #[test]
+    fn test_result_fields() {
+        let mut gen = TypeGen::new();
+
+        gen.register_type::<LoadState>().unwrap();
+        gen.register_type::<Result<Vec<String>, String>>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.join("registry.json");
+        gen.registry_lockfile(&path).unwrap();
+
+        let lockfile: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(
+            lockfile["Result"],
+            serde_json::json!({ "ENUM": {
+                "0": { "Ok": { "NEWTYPE": { "SEQ": "STR" } } },
+                "1": { "Err": { "NEWTYPE": "STR" } },
+            }})
+        );
+        assert_eq!(
+            lockfile["LoadState"]["STRUCT"][0]["items"],
+            serde_json::json!({ "TYPENAME": "Result" })
+        );
+    }
+
+    #[test]
+    fn test_unregistered_result_fields() {
+        let mut gen = TypeGen::new();
+
+        gen.register_type::<LoadState>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let error = gen
+            .registry_lockfile(temp.join("registry.json"))
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("gen.register_type::<Result<MyValue, MyError>>()"));
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Patch {
+        name: Option<Option<String>>,
+    }
+
+    #[test]
+    fn test_nested_options() {
+        let mut gen = TypeGen::new();
+
+        gen.register_type::<Patch>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+
+        gen.swift("SharedTypes", temp.join("swift"))
+            .expect("swift type gen failed");
+
+        let result = gen.typescript("shared_types", temp.join("typescript"));
+        assert!(matches!(result, Err(TypeGenError::NestedOptions(names)) if names == "Patch"));
+    }
+
+    #[test]
+    fn test_rename_types() {
+        let mut gen = TypeGen::new();
+
+        gen.register_samples(vec![Event::SendUuid(Uuid::new_v4())])
+            .unwrap();
+        gen.register_app::<App>().unwrap();
+        gen.rename_type("Event", "CoreEvent").unwrap();
+        gen.type_prefix("Shared").unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.join("registry.json");
+        gen.registry_lockfile(&path).unwrap();
+
+        let lockfile: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert!(lockfile.get("Event").is_none());
+        assert!(lockfile.get("CoreEvent").is_some());
+        assert!(lockfile.get("SharedViewModel").is_some());
+        assert!(lockfile.get("SharedEffect").is_some());
+        // the generated helpers depend on `Request`, so it keeps its name
+        assert_eq!(
+            lockfile["Request"]["STRUCT"][1]["effect"],
+            serde_json::json!({ "TYPENAME": "SharedEffect" })
+        );
+
+        assert!(matches!(
+            gen.rename_type("ViewModel", "View"),
+            Err(TypeGenError::LateRegistration)
+        ));
+    }
+
+    #[test]
+    fn test_manifest() {
+        use crux_core::typegen::Manifest;
+
+        let mut gen = TypeGen::new();
+
+        gen.register_samples(vec![Event::SendUuid(Uuid::new_v4())])
+            .unwrap();
+        gen.register_app::<App>().unwrap();
+        gen.deny_unknown_fields::<Event>().unwrap();
+        gen.rename_type("Event", "CoreEvent").unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        gen.registry_lockfile(temp.join("registry.json")).unwrap();
+
+        let manifest: Manifest = serde_json::from_str(
+            &std::fs::read_to_string(temp.join("registry.manifest.json")).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest,
+            Manifest {
+                event: Some("CoreEvent".to_string()),
+                view_model: Some("ViewModel".to_string()),
+                effect: Some("Effect".to_string()),
+                capabilities: [("Render".to_string(), "RenderOperation".to_string())].into(),
+                deny_unknown_fields: ["CoreEvent".to_string()].into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rename_type_clash() {
+        let mut gen = TypeGen::new();
+
+        gen.register_samples(vec![Event::SendUuid(Uuid::new_v4())])
+            .unwrap();
+        gen.register_app::<App>().unwrap();
+        gen.rename_type("Event", "ViewModel").unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let result = gen.registry_lockfile(temp.join("registry.json"));
+
+        assert!(matches!(result, Err(TypeGenError::Generation(_))));
+    }
+
+    #[test]
+    fn test_view_model_instances() {
+        use crux_core::viewmodel::{Loadable, Paginated};
+
+        #[derive(Serialize, Deserialize)]
+        struct Item {
+            name: String,
+        }
+
+        #[derive(Serialize, Deserialize)]
+        struct Screen {
+            items: Paginated<Item>,
+            tags: Paginated<String>,
+            user: Loadable<String, u32>,
+        }
+
+        let mut gen = TypeGen::new();
+        gen.register_type::<Loadable<String, u32>>().unwrap();
+        gen.register_type::<Screen>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.join("registry.json");
+        gen.registry_lockfile(&path).unwrap();
+
+        let lockfile: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(
+            lockfile["Screen"]["STRUCT"][0]["items"],
+            serde_json::json!({ "TYPENAME": "PaginatedItem" })
+        );
+        assert_eq!(
+            lockfile["PaginatedString"]["STRUCT"][0]["items"],
+            serde_json::json!({ "SEQ": "STR" })
+        );
+        assert_eq!(
+            lockfile["LoadableStringU32"]["ENUM"]["3"]["Failed"],
+            serde_json::json!({ "NEWTYPE": "U32" })
+        );
+    }
+}
+
+#[cfg(feature = "typegen")]
+mod phantom_data {
+    use std::marker::PhantomData;
+
+    use crux_core::typegen::TypeGen;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Tagged<T> {
+        value: u32,
+        unit: (),
+        marker: PhantomData<T>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Id<T>(u32, PhantomData<T>);
+
+    #[derive(Serialize, Deserialize)]
+    struct Marker<T>(PhantomData<T>);
+
+    #[derive(Serialize, Deserialize)]
+    enum Key<T> {
+        Named(String, PhantomData<T>),
+        Anonymous(PhantomData<T>),
+    }
+
+    fn lockfile(gen: &mut TypeGen) -> serde_json::Value {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.join("registry.json");
+        gen.registry_lockfile(&path).unwrap();
+
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn phantom_data_fields_are_skipped() {
+        let mut gen = TypeGen::new();
+
+        gen.register_type::<Tagged<String>>().unwrap();
+        gen.register_type::<Id<String>>().unwrap();
+        gen.register_type::<Marker<String>>().unwrap();
+        gen.register_type::<Key<String>>().unwrap();
+
+        let lockfile = lockfile(&mut gen);
+
+        assert!(lockfile.get("PhantomData").is_none());
+        assert_eq!(
+            lockfile["Tagged"],
+            serde_json::json!({ "STRUCT": [{ "value": "U32" }, { "unit": "UNIT" }] })
+        );
+        assert_eq!(
+            lockfile["Id"],
+            serde_json::json!({ "NEWTYPESTRUCT": "U32" })
+        );
+        assert_eq!(lockfile["Marker"], serde_json::json!("UNITSTRUCT"));
+        assert_eq!(
+            lockfile["Key"]["ENUM"],
+            serde_json::json!({
+                "0": { "Named": { "NEWTYPE": "STR" } },
+                "1": { "Anonymous": "UNIT" },
+            })
+        );
+    }
+
+    #[test]
+    fn skipped_fields_take_no_space() {
+        // which is why the generated types can leave them out
+        assert_eq!(
+            bincode::serialize(&Id::<String>(7, PhantomData)).unwrap(),
+            bincode::serialize(&7u32).unwrap()
+        );
+        assert_eq!(
+            bincode::serialize(&Marker::<String>(PhantomData)).unwrap(),
+            Vec::<u8>::new()
+        );
+    }
+
+    #[test]
+    fn phantom_data_used_elsewhere_is_kept() {
+        #[derive(Serialize, Deserialize)]
+        struct Maybe {
+            marker: Option<PhantomData<String>>,
+        }
+
+        let mut gen = TypeGen::new();
+
+        gen.register_type::<Maybe>().unwrap();
+
+        let lockfile = lockfile(&mut gen);
+
+        assert_eq!(lockfile["PhantomData"], serde_json::json!("UNITSTRUCT"));
+    }
+}
+
+#[cfg(feature = "typegen")]
+mod borrowed_types {
+    use crux_core::typegen::TypeGen;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct ViewModel {
+        title: &'static str,
+        data: &'static [u8],
+    }
+
+    #[test]
+    fn borrowed_types_are_shared_as_owned() {
+        let mut gen = TypeGen::new();
+        gen.register_type::<ViewModel>().unwrap();
+
+        let temp = assert_fs::TempDir::new().unwrap();
+        let path = temp.join("registry.json");
+        gen.registry_lockfile(&path).unwrap();
+        let lockfile: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(
+            lockfile["ViewModel"],
+            serde_json::json!({ "STRUCT": [{ "title": "STR" }, { "data": "BYTES" }] })
+        );
+
+        // a byte slice serializes as a sequence, which bincode encodes the same way as bytes,
+        // with the length followed by the bytes themselves
+        let view = ViewModel {
+            title: "hi",
+            data: &[1, 2],
+        };
+        assert_eq!(
+            bincode::serialize(&view).unwrap(),
+            [
+                &[2, 0, 0, 0, 0, 0, 0, 0, b'h', b'i'][..],
+                &[2, 0, 0, 0, 0, 0, 0, 0, 1, 2]
+            ]
+            .concat()
+        );
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod strategies {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use crux_core::{
+        macros::{Effect, Export},
+        render::Render,
+        typegen::TypeGen,
+    };
+    use proptest::{
+        strategy::{Strategy, ValueTree},
+        test_runner::TestRunner,
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    struct App;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Event {
+        Reset,
+        Rename { name: String, tags: Vec<String> },
+        Move(i32, i32),
+        Edit(Tree),
+        SetId(uuid::Uuid),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Tree {
+        Leaf(u8),
+        Node(Box<Tree>, Box<Tree>),
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct ViewModel {
+        totals: BTreeMap<String, u64>,
+        selected: Option<u32>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = ();
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, _event: Event, _model: &mut (), _caps: &Capabilities) {}
+
+        fn view(&self, _model: &()) -> ViewModel {
+            unimplemented!()
+        }
+    }
+
+    #[derive(Effect, Export)]
+    struct Capabilities {
+        #[allow(dead_code)]
+        render: Render<Event>,
+    }
+
+    fn samples<T: std::fmt::Debug>(strategy: impl Strategy<Value = T>) -> Vec<T> {
+        let mut runner = TestRunner::deterministic();
+        (0..200)
+            .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+            .collect()
+    }
+
+    fn generator() -> TypeGen {
+        let mut gen = TypeGen::new();
+        gen.register_samples(vec![Event::SetId(uuid::Uuid::new_v4())])
+            .unwrap();
+        gen.register_type::<Tree>().unwrap();
+        gen.register_app::<App>().unwrap();
+        gen
+    }
+
+    fn depth(tree: &Tree) -> usize {
+        match tree {
+            Tree::Leaf(_) => 1,
+            Tree::Node(left, right) => 1 + depth(left).max(depth(right)),
+        }
+    }
+
+    #[test]
+    fn events_cover_every_variant() {
+        let mut gen = generator();
+
+        let events = samples(gen.event_strategy::<App>().unwrap());
+
+        let variants: BTreeSet<_> = events
+            .iter()
+            .map(|event| {
+                format!("{event:?}")
+                    .split(['(', ' '])
+                    .next()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            variants,
+            BTreeSet::from(["Edit", "Move", "Rename", "Reset", "SetId"].map(String::from))
+        );
+    }
+
+    #[test]
+    fn recursive_types_stay_finite() {
+        let mut gen = generator();
+
+        for event in samples(gen.event_strategy::<App>().unwrap()) {
+            if let Event::Edit(tree) = event {
+                assert!(depth(&tree) <= 6, "{tree:?} is too deep");
+            }
+        }
+    }
+
+    #[test]
+    fn view_models_and_renamed_types() {
+        let mut gen = generator();
+        gen.rename_type("ViewModel", "CounterView").unwrap();
+
+        let view_models = samples(gen.view_model_strategy::<App>().unwrap());
+        assert!(view_models.iter().any(|view| view.selected.is_some()));
+        assert!(view_models.iter().any(|view| view.totals.len() > 1));
+
+        assert!(gen.strategy::<Tree>("Tree").is_ok());
+        assert!(gen.strategy::<ViewModel>("ViewModel").is_err());
+    }
 }
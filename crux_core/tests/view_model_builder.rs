@@ -0,0 +1,99 @@
+mod app {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crux_core::{
+        macros::{Effect, ViewModelBuilder},
+        render::Render,
+    };
+    use serde::Serialize;
+
+    pub static TOTALS_COMPUTED: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Default)]
+    pub struct App {
+        view_model: ViewModelBuilder,
+    }
+
+    pub enum Event {
+        Add(u32),
+        Select(usize),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        prices: Vec<u32>,
+        currency: String,
+        selected: usize,
+    }
+
+    #[derive(ViewModelBuilder, Serialize, Debug, PartialEq)]
+    #[view_model(model = Model)]
+    pub struct ViewModel {
+        pub selected: usize,
+        #[view_model(computed = format_total, reads(prices, currency))]
+        pub total: String,
+    }
+
+    fn format_total(prices: &[u32], currency: &str) -> String {
+        TOTALS_COMPUTED.fetch_add(1, Ordering::SeqCst);
+        format!("{}{}", currency, prices.iter().sum::<u32>())
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        render: Render<Event>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            model.currency = "£".to_string();
+            match event {
+                Event::Add(price) => model.prices.push(price),
+                Event::Select(index) => model.selected = index,
+            }
+            caps.render.render();
+        }
+
+        fn view(&self, model: &Model) -> ViewModel {
+            self.view_model.build(model)
+        }
+    }
+}
+
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use crux_core::Core;
+
+    use crate::app::{App, Effect, Event, ViewModel, TOTALS_COMPUTED};
+
+    #[test]
+    fn computed_fields_are_remembered_until_what_they_read_changes() {
+        let core: Core<Effect, App> = Core::default();
+
+        core.process_event(Event::Add(3));
+        assert_eq!(
+            core.view(),
+            ViewModel {
+                selected: 0,
+                total: "£3".to_string()
+            }
+        );
+        assert_eq!(core.view().total, "£3");
+        assert_eq!(TOTALS_COMPUTED.load(Ordering::SeqCst), 1);
+
+        // the total doesn't read the selection
+        core.process_event(Event::Select(1));
+        assert_eq!(core.view().selected, 1);
+        assert_eq!(TOTALS_COMPUTED.load(Ordering::SeqCst), 1);
+
+        core.process_event(Event::Add(4));
+        assert_eq!(core.view().total, "£7");
+        assert_eq!(TOTALS_COMPUTED.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,104 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        SignIn(String),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        user: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct ViewModel {
+        pub title: String,
+        pub user: Option<User>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct User {
+        pub name: String,
+        pub avatar: Vec<u8>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::SignIn(name) => model.user = Some(name),
+            }
+            caps.render.render();
+        }
+
+        fn view(&self, model: &Model) -> ViewModel {
+            ViewModel {
+                title: "Inbox".to_string(),
+                user: model.user.as_ref().map(|name| User {
+                    name: name.clone(),
+                    avatar: vec![0; 1024],
+                }),
+            }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::{
+        bridge::{Bridge, BridgeError},
+        Core,
+    };
+
+    use crate::app::{App, Effect, Event};
+
+    fn paths(paths: &[&str]) -> Vec<u8> {
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string()).collect();
+        bincode::serialize(&paths).unwrap()
+    }
+
+    #[test]
+    fn view_slice_only_includes_requested_fields() {
+        let bridge = Bridge::<Effect, App>::new(Core::default());
+
+        let slice = bridge.view_slice(&paths(&["user.name", "title"]));
+        let (_, name, title): (u64, Option<String>, Option<String>) =
+            bincode::deserialize(&slice).unwrap();
+        assert_eq!(name, None);
+        assert_eq!(title.as_deref(), Some("Inbox"));
+
+        bridge.process_event(&bincode::serialize(&Event::SignIn("Ada".to_string())).unwrap());
+
+        let slice = bridge.view_slice(&paths(&["user.name"]));
+        let (_, name): (u64, Option<String>) = bincode::deserialize(&slice).unwrap();
+        assert_eq!(name.as_deref(), Some("Ada"));
+
+        // the avatar is left out
+        assert!(slice.len() < bridge.view().len() / 10);
+    }
+
+    #[test]
+    fn malformed_paths_are_an_error() {
+        let bridge = Bridge::<Effect, App>::new(Core::default());
+
+        // a list claiming more paths than there are bytes
+        assert_eq!(
+            bridge.try_view_slice(&[0xff; 8]),
+            Err(BridgeError::InvalidPaths)
+        );
+    }
+}
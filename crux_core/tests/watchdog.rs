@@ -0,0 +1,136 @@
+mod app {
+    use crux_core::bridge::StuckEffect;
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_time::{Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        GetTime,
+        Nothing,
+        #[serde(skip)]
+        SetTime(TimeResponse),
+        #[serde(skip)]
+        Stuck(StuckEffect),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        time: Option<u64>,
+        stuck: Vec<StuckEffect>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub time: Option<u64>,
+        pub stuck: Vec<StuckEffect>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::GetTime => caps.time.now(Event::SetTime),
+                Event::Nothing => {}
+                Event::SetTime(TimeResponse::Now(instant)) => {
+                    model.time = Some(instant.seconds);
+                    caps.render.render();
+                }
+                Event::SetTime(_) => {}
+                Event::Stuck(effect) => model.stuck.push(effect),
+            }
+        }
+
+        fn view(&self, model: &Model) -> ViewModel {
+            ViewModel {
+                time: model.time,
+                stuck: model.stuck.clone(),
+            }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use std::time::Duration;
+
+    use crux_core::{
+        bridge::{Bridge, Request},
+        Core,
+    };
+    use crux_time::{Instant, TimeResponse};
+
+    use crate::app::{App, Effect, EffectFfi, Event, ViewModel};
+
+    fn bridge(threshold: Duration) -> Bridge<Effect, App> {
+        Bridge::new(Core::new()).with_watchdog(threshold, Event::Stuck)
+    }
+
+    fn send(bridge: &Bridge<Effect, App>, event: &Event) -> Vec<Request<EffectFfi>> {
+        bincode::deserialize(&bridge.process_event(&bincode::serialize(event).unwrap())).unwrap()
+    }
+
+    fn view(bridge: &Bridge<Effect, App>) -> ViewModel {
+        bincode::deserialize(&bridge.view()).unwrap()
+    }
+
+    #[test]
+    fn effects_waiting_past_the_threshold_are_reported_once() {
+        let bridge = bridge(Duration::ZERO);
+
+        let requests = send(&bridge, &Event::GetTime);
+        let id = requests[0].id.0;
+        // an effect isn't stuck in the call which sent it
+        assert_eq!(view(&bridge).stuck, vec![]);
+
+        send(&bridge, &Event::Nothing);
+        send(&bridge, &Event::Nothing);
+
+        let stuck = view(&bridge).stuck;
+        let [effect] = &stuck[..] else {
+            panic!("Expected one stuck effect, got {stuck:?}");
+        };
+        assert_eq!(effect.id, id);
+        assert_eq!(effect.name, "Time");
+        assert_eq!(effect.operation, r#"{"Time":"now"}"#);
+        assert!(effect.pending_for > Duration::ZERO);
+    }
+
+    #[test]
+    fn resolved_effects_are_not_reported() {
+        let bridge = bridge(Duration::ZERO);
+
+        let requests = send(&bridge, &Event::GetTime);
+        let now = TimeResponse::Now(Instant::new(1_700_000_000, 0).unwrap());
+        bridge.handle_response(requests[0].id.0, &bincode::serialize(&now).unwrap());
+
+        send(&bridge, &Event::Nothing);
+
+        let view = view(&bridge);
+        assert_eq!(view.time, Some(1_700_000_000));
+        assert_eq!(view.stuck, vec![]);
+    }
+
+    #[test]
+    fn effects_within_the_threshold_are_not_reported() {
+        let bridge = bridge(Duration::from_secs(60));
+
+        send(&bridge, &Event::GetTime);
+        send(&bridge, &Event::Nothing);
+
+        assert_eq!(view(&bridge).stuck, vec![]);
+    }
+}
@@ -0,0 +1,318 @@
+//! Dialogs for Crux apps
+//!
+//! `crux_dialog` allows Crux apps to ask the user a question, by asking the Shell to show
+//! an alert, a confirmation or a choice with the platform's own dialogs, and to hear back
+//! which action the user took. Flows which need the user's say, like confirming a
+//! destructive action, stay in the Core instead of being written again in every Shell.
+
+use serde::{Deserialize, Serialize};
+
+use crux_core::capability::{CapabilityContext, Operation};
+
+/// How the Shell should present a [`DialogAction`]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum DialogActionStyle {
+    #[default]
+    Default,
+    /// The action which backs out of the dialog, e.g. "Cancel"
+    Cancel,
+    /// An action which destroys data, which platforms usually show in red
+    Destructive,
+}
+
+/// A button of a dialog
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct DialogAction {
+    pub label: String,
+    pub style: DialogActionStyle,
+}
+
+impl DialogAction {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            style: DialogActionStyle::Default,
+        }
+    }
+
+    /// An action which backs out of the dialog
+    pub fn cancel(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            style: DialogActionStyle::Cancel,
+        }
+    }
+
+    /// An action which destroys data
+    pub fn destructive(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            style: DialogActionStyle::Destructive,
+        }
+    }
+}
+
+/// Supported operations
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum DialogOperation {
+    /// Tell the user something, with a single button to dismiss the dialog
+    Alert { title: String, message: String },
+    /// Ask the user to confirm an action, or cancel it
+    Confirm {
+        title: String,
+        message: String,
+        confirm: DialogAction,
+        cancel: DialogAction,
+    },
+    /// Ask the user to pick one of the options, or cancel
+    Choose {
+        title: String,
+        message: String,
+        options: Vec<DialogAction>,
+        cancel: DialogAction,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum DialogResponse {
+    /// Response to a `DialogOperation::Alert` once the user has dismissed it, or to
+    /// any dialog the user closed without picking an action, e.g. by tapping outside it
+    Dismissed,
+    /// Response to a `DialogOperation::Confirm` when the user confirmed
+    Confirmed,
+    /// Response to a `DialogOperation::Confirm` or `DialogOperation::Choose` when the user
+    /// picked the cancel action
+    Cancelled,
+    /// Response to a `DialogOperation::Choose`, with the index of the option the user picked
+    Chosen { index: u32 },
+}
+
+impl Operation for DialogOperation {
+    type Output = DialogResponse;
+}
+
+pub struct Dialog<Ev> {
+    context: CapabilityContext<DialogOperation, Ev>,
+}
+
+impl<Ev> crux_core::Capability<Ev> for Dialog<Ev> {
+    type Operation = DialogOperation;
+
+    type MappedSelf<MappedEv> = Dialog<MappedEv>;
+
+    fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+    where
+        F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+        Ev: 'static,
+        NewEv: 'static + Send,
+    {
+        Dialog::new(self.context.map_event(f))
+    }
+
+    #[cfg(feature = "typegen")]
+    fn register_types(generator: &mut crux_core::typegen::TypeGen) -> crux_core::typegen::Result {
+        generator.register_type::<DialogActionStyle>()?;
+        generator.register_type::<DialogAction>()?;
+        generator.register_type::<Self::Operation>()?;
+        generator.register_type::<<Self::Operation as Operation>::Output>()?;
+        Ok(())
+    }
+}
+
+impl<Ev> Clone for Dialog<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+        }
+    }
+}
+
+impl<Ev> Dialog<Ev>
+where
+    Ev: 'static,
+{
+    pub fn new(context: CapabilityContext<DialogOperation, Ev>) -> Self {
+        Self { context }
+    }
+
+    /// Show an alert with the `title` and `message`.
+    /// Will dispatch the event once the user has dismissed it.
+    pub fn alert<F>(&self, title: impl Into<String>, message: impl Into<String>, make_event: F)
+    where
+        F: FnOnce() -> Ev + Send + 'static,
+    {
+        let operation = DialogOperation::Alert {
+            title: title.into(),
+            message: message.into(),
+        };
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                context.request_from_shell(operation).await;
+                context.update_app(make_event());
+            }
+        });
+    }
+
+    /// Show an alert, while in an async context. This is used together with
+    /// [`crux_core::compose::Compose`].
+    pub async fn alert_async(&self, title: impl Into<String>, message: impl Into<String>) {
+        let operation = DialogOperation::Alert {
+            title: title.into(),
+            message: message.into(),
+        };
+        self.context.request_from_shell(operation).await;
+    }
+
+    /// Ask the user to confirm with the `confirm` action, or back out with the `cancel` one.
+    /// Will dispatch the event with `true` if the user confirmed, and `false` if they
+    /// cancelled or dismissed the dialog.
+    ///
+    /// ```rust
+    /// # use crux_dialog::{Dialog, DialogAction};
+    /// # enum Event { Delete(bool) }
+    /// # fn update(dialog: &Dialog<Event>) {
+    /// dialog.confirm(
+    ///     "Delete note?",
+    ///     "This can't be undone.",
+    ///     DialogAction::destructive("Delete"),
+    ///     DialogAction::cancel("Keep"),
+    ///     Event::Delete,
+    /// );
+    /// # }
+    /// ```
+    pub fn confirm<F>(
+        &self,
+        title: impl Into<String>,
+        message: impl Into<String>,
+        confirm: DialogAction,
+        cancel: DialogAction,
+        make_event: F,
+    ) where
+        F: FnOnce(bool) -> Ev + Send + 'static,
+    {
+        let operation = DialogOperation::Confirm {
+            title: title.into(),
+            message: message.into(),
+            confirm,
+            cancel,
+        };
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = context.request_from_shell(operation).await;
+                context.update_app(make_event(response == DialogResponse::Confirmed));
+            }
+        });
+    }
+
+    /// Ask the user to confirm, while in an async context. This is used together with
+    /// [`crux_core::compose::Compose`].
+    ///
+    /// Returns `true` if the user confirmed.
+    pub async fn confirm_async(
+        &self,
+        title: impl Into<String>,
+        message: impl Into<String>,
+        confirm: DialogAction,
+        cancel: DialogAction,
+    ) -> bool {
+        let operation = DialogOperation::Confirm {
+            title: title.into(),
+            message: message.into(),
+            confirm,
+            cancel,
+        };
+        self.context.request_from_shell(operation).await == DialogResponse::Confirmed
+    }
+
+    /// Ask the user to pick one of the `options`, each a value of the app's choosing with
+    /// the action showing it, or back out with the `cancel` action.
+    /// Will dispatch the event with the value of the option the user picked, or `None` if
+    /// they cancelled or dismissed the dialog.
+    ///
+    /// ```rust
+    /// # use crux_dialog::{Dialog, DialogAction};
+    /// # #[derive(Clone, Copy)] enum Sort { Newest, Oldest }
+    /// # enum Event { SortBy(Option<Sort>) }
+    /// # fn update(dialog: &Dialog<Event>) {
+    /// dialog.choose(
+    ///     "Sort by",
+    ///     "",
+    ///     vec![
+    ///         (Sort::Newest, DialogAction::new("Newest first")),
+    ///         (Sort::Oldest, DialogAction::new("Oldest first")),
+    ///     ],
+    ///     DialogAction::cancel("Cancel"),
+    ///     Event::SortBy,
+    /// );
+    /// # }
+    /// ```
+    pub fn choose<T, F>(
+        &self,
+        title: impl Into<String>,
+        message: impl Into<String>,
+        options: Vec<(T, DialogAction)>,
+        cancel: DialogAction,
+        make_event: F,
+    ) where
+        T: Send + 'static,
+        F: FnOnce(Option<T>) -> Ev + Send + 'static,
+    {
+        let (values, operation) = choose(title.into(), message.into(), options, cancel);
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = context.request_from_shell(operation).await;
+                context.update_app(make_event(chosen(values, &response)));
+            }
+        });
+    }
+
+    /// Ask the user to pick one of the `options`, while in an async context. This is used
+    /// together with [`crux_core::compose::Compose`].
+    ///
+    /// Returns the value of the option the user picked, or `None` if they didn't.
+    pub async fn choose_async<T>(
+        &self,
+        title: impl Into<String>,
+        message: impl Into<String>,
+        options: Vec<(T, DialogAction)>,
+        cancel: DialogAction,
+    ) -> Option<T> {
+        let (values, operation) = choose(title.into(), message.into(), options, cancel);
+        let response = self.context.request_from_shell(operation).await;
+
+        chosen(values, &response)
+    }
+}
+
+/// Split the `options` into the app's values, and the operation showing their actions
+fn choose<T>(
+    title: String,
+    message: String,
+    options: Vec<(T, DialogAction)>,
+    cancel: DialogAction,
+) -> (Vec<T>, DialogOperation) {
+    let (values, options) = options.into_iter().unzip();
+    let operation = DialogOperation::Choose {
+        title,
+        message,
+        options,
+        cancel,
+    };
+
+    (values, operation)
+}
+
+/// The value of the option picked in the `response`, if any. An index the dialog didn't
+/// have counts as no choice.
+fn chosen<T>(values: Vec<T>, response: &DialogResponse) -> Option<T> {
+    match response {
+        DialogResponse::Chosen { index } => values.into_iter().nth(*index as usize),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,190 @@
+use crux_core::{macros::Effect, testing::AppTester};
+use serde::{Deserialize, Serialize};
+
+use crate::{Dialog, DialogAction, DialogActionStyle, DialogOperation, DialogResponse};
+
+#[derive(Default)]
+pub struct App;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Colour {
+    Red,
+    Green,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Event {
+    Delete,
+    PickColour,
+    Saved,
+
+    DeleteConfirmed(bool),
+    ColourPicked(Option<Colour>),
+    Acknowledged,
+}
+
+#[derive(Debug, Default)]
+pub struct Model {
+    pub deleted: bool,
+    pub colour: Option<Colour>,
+    pub acknowledged: bool,
+}
+
+impl crux_core::App for App {
+    type Event = Event;
+    type Model = Model;
+    type ViewModel = ();
+
+    type Capabilities = Capabilities;
+
+    fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+        match event {
+            Event::Delete => caps.dialog.confirm(
+                "Delete note?",
+                "This can't be undone.",
+                DialogAction::destructive("Delete"),
+                DialogAction::cancel("Keep"),
+                Event::DeleteConfirmed,
+            ),
+            Event::PickColour => caps.dialog.choose(
+                "Colour",
+                "Pick a colour for the note",
+                vec![
+                    (Colour::Red, DialogAction::new("Red")),
+                    (Colour::Green, DialogAction::new("Green")),
+                ],
+                DialogAction::cancel("Cancel"),
+                Event::ColourPicked,
+            ),
+            Event::Saved => caps
+                .dialog
+                .alert("Saved", "Your note was saved.", || Event::Acknowledged),
+
+            Event::DeleteConfirmed(confirmed) => model.deleted = confirmed,
+            Event::ColourPicked(colour) => model.colour = colour,
+            Event::Acknowledged => model.acknowledged = true,
+        }
+    }
+
+    fn view(&self, _model: &Self::Model) -> Self::ViewModel {}
+}
+
+#[derive(Effect)]
+pub struct Capabilities {
+    pub dialog: Dialog<Event>,
+}
+
+#[test]
+fn test_confirm() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Delete, &mut model)
+        .expect_one_effect()
+        .expect_dialog();
+
+    assert_eq!(
+        request.operation,
+        DialogOperation::Confirm {
+            title: "Delete note?".to_string(),
+            message: "This can't be undone.".to_string(),
+            confirm: DialogAction {
+                label: "Delete".to_string(),
+                style: DialogActionStyle::Destructive,
+            },
+            cancel: DialogAction {
+                label: "Keep".to_string(),
+                style: DialogActionStyle::Cancel,
+            },
+        }
+    );
+
+    let _updated = app.resolve_to_event_then_update(request, DialogResponse::Confirmed, &mut model);
+
+    assert!(model.deleted);
+}
+
+#[test]
+fn test_confirm_dismissed() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Delete, &mut model)
+        .expect_one_effect()
+        .expect_dialog();
+
+    let _updated = app.resolve_to_event_then_update(request, DialogResponse::Dismissed, &mut model);
+
+    assert!(!model.deleted);
+}
+
+#[test]
+fn test_choose() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::PickColour, &mut model)
+        .expect_one_effect()
+        .expect_dialog();
+
+    let DialogOperation::Choose { options, .. } = &request.operation else {
+        panic!("Expected a choice");
+    };
+    let labels: Vec<_> = options.iter().map(|option| option.label.as_str()).collect();
+    assert_eq!(labels, ["Red", "Green"]);
+
+    let _updated =
+        app.resolve_to_event_then_update(request, DialogResponse::Chosen { index: 1 }, &mut model);
+
+    assert_eq!(model.colour, Some(Colour::Green));
+}
+
+#[test]
+fn test_choose_cancelled_or_out_of_range() {
+    let app = AppTester::<App, _>::default();
+
+    for response in [
+        DialogResponse::Cancelled,
+        DialogResponse::Chosen { index: 2 },
+    ] {
+        let mut model = Model {
+            colour: Some(Colour::Red),
+            ..Default::default()
+        };
+
+        let request = &mut app
+            .update(Event::PickColour, &mut model)
+            .expect_one_effect()
+            .expect_dialog();
+
+        let _updated = app.resolve_to_event_then_update(request, response, &mut model);
+
+        assert_eq!(model.colour, None);
+    }
+}
+
+#[test]
+fn test_alert() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Saved, &mut model)
+        .expect_one_effect()
+        .expect_dialog();
+
+    assert_eq!(
+        request.operation,
+        DialogOperation::Alert {
+            title: "Saved".to_string(),
+            message: "Your note was saved.".to_string(),
+        }
+    );
+
+    let _updated = app.resolve_to_event_then_update(request, DialogResponse::Dismissed, &mut model);
+
+    assert!(model.acknowledged);
+}
@@ -1,10 +1,16 @@
+use std::collections::HashMap;
 use std::fmt;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+
+use futures_util::future::{BoxFuture, Shared};
+use futures_util::FutureExt;
 
 use crate::http::{Method, Url};
 use crate::middleware::{Middleware, Next};
-use crate::protocol::{EffectSender, HttpResult, ProtocolRequestBuilder};
-use crate::{Config, Request, RequestBuilder, ResponseAsync, Result};
+use crate::protocol::{
+    Coalesce, EffectSender, HttpRequest, HttpResult, OnProgress, ProtocolRequestBuilder,
+};
+use crate::{Config, HttpError, Request, RequestBuilder, ResponseAsync, Result};
 
 /// An HTTP client, capable of sending `Request`s
 ///
@@ -35,6 +41,8 @@ use crate::{Config, Request, RequestBuilder, ResponseAsync, Result};
 pub struct Client {
     config: Config,
     effect_sender: Arc<dyn EffectSender + Send + Sync>,
+    /// Coalesced requests waiting for the shell's result, keyed by the request
+    in_flight: Arc<Mutex<HashMap<String, Shared<BoxFuture<'static, HttpResult>>>>>,
     /// Holds the middleware stack.
     ///
     /// Note(Fishrock123): We do actually want this structure.
@@ -57,6 +65,7 @@ impl Clone for Client {
         Self {
             config: self.config.clone(),
             effect_sender: Arc::clone(&self.effect_sender),
+            in_flight: Arc::clone(&self.in_flight),
             middleware: Arc::new(self.middleware.iter().cloned().collect()),
         }
     }
@@ -76,6 +85,7 @@ impl Client {
         Self {
             config: Config::default(),
             effect_sender: Arc::new(sender),
+            in_flight: Arc::default(),
             middleware: Arc::new(vec![]),
         }
     }
@@ -112,10 +122,29 @@ impl Client {
 
         let next = Next::new(&mw_stack, &|req, client| {
             Box::pin(async move {
-                let req = req.into_protocol_request().await.unwrap();
-                match client.effect_sender.send(req).await {
+                let on_progress = req.ext::<OnProgress>().cloned();
+                let coalesce = req.ext::<Coalesce>().is_some()
+                    && matches!(req.method(), Method::Get | Method::Head);
+                let mut req = req.into_protocol_request().await.unwrap();
+
+                let result = match on_progress {
+                    Some(on_progress) => {
+                        req.report_progress = true;
+                        client
+                            .effect_sender
+                            .send_with_progress(req, on_progress)
+                            .await
+                    }
+                    None if coalesce => client.send_coalesced(req).await,
+                    None => client.effect_sender.send(req).await,
+                };
+                match result {
                     HttpResult::Ok(res) => Ok(res.into()),
                     HttpResult::Err(e) => Err(e),
+                    HttpResult::Progress(_) => Err(HttpError::Io(
+                        "the shell reported progress for a request which didn't ask for it"
+                            .to_string(),
+                    )),
                 }
             })
         });
@@ -123,6 +152,7 @@ impl Client {
         let client = Self {
             config: self.config.clone(),
             effect_sender: Arc::clone(&self.effect_sender),
+            in_flight: Arc::clone(&self.in_flight),
             // Erase the middleware stack for the Client accessible from within middleware.
             // This avoids gratuitous circular borrow & logic issues.
             middleware: Arc::new(vec![]),
@@ -132,6 +162,38 @@ impl Client {
         Ok(ResponseAsync::new(res.into()))
     }
 
+    /// Send the request, unless an identical one is already waiting for the shell's
+    /// result, in which case return that one's result instead
+    async fn send_coalesced(&self, req: HttpRequest) -> HttpResult {
+        // the key covers everything the shell sees, so only identical requests share
+        let key = serde_json::to_string(&req).expect("HttpRequest should serialize");
+        let pending = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let effect_sender = Arc::clone(&self.effect_sender);
+                async move { effect_sender.send(req).await }
+                    .boxed()
+                    .shared()
+            })
+            .clone();
+
+        let result = pending.clone().await;
+
+        // the first request to finish makes way for the next identical one
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .get(&key)
+            .map_or(false, |current| current.ptr_eq(&pending))
+        {
+            in_flight.remove(&key);
+        }
+
+        result
+    }
+
     /// Submit a `Request` and get the response body as bytes.
     pub async fn recv_bytes(&self, req: impl Into<Request>) -> Result<Vec<u8>> {
         let mut res = self.send(req.into()).await?;
@@ -39,6 +39,8 @@ pub type Result<T> = std::result::Result<T, HttpError>;
 pub struct Http<Ev> {
     context: CapabilityContext<protocol::HttpRequest, Ev>,
     client: Client,
+    /// Whether GET and HEAD requests made with this instance are coalesced
+    coalesce: bool,
 }
 
 impl<Ev> crux_core::Capability<Ev> for Http<Ev> {
@@ -52,7 +54,10 @@ impl<Ev> crux_core::Capability<Ev> for Http<Ev> {
         Ev: 'static,
         NewEv: 'static + Send,
     {
-        Http::new(self.context.map_event(f))
+        Http {
+            coalesce: self.coalesce,
+            ..Http::new(self.context.map_event(f))
+        }
     }
 
     #[cfg(feature = "typegen")]
@@ -70,6 +75,7 @@ impl<Ev> Clone for Http<Ev> {
         Self {
             context: self.context.clone(),
             client: self.client.clone(),
+            coalesce: self.coalesce,
         }
     }
 }
@@ -82,6 +88,35 @@ where
         Self {
             client: Client::new(context.clone()),
             context,
+            coalesce: false,
+        }
+    }
+
+    /// A copy of the capability whose GET and HEAD requests are
+    /// [coalesced](RequestBuilder::coalesce): while one is waiting for the shell's response,
+    /// identical requests made with it, or its clones, don't reach the shell, and are
+    /// resolved with the same response.
+    ///
+    /// Useful when independent parts of the app may ask for the same resource at the
+    /// same time, e.g. in the same update.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # enum Event { ReceiveResponse(crux_http::Result<crux_http::Response<Vec<u8>>>) }
+    /// # struct Capabilities { http: crux_http::Http<Event> }
+    /// # fn update(caps: &Capabilities) {
+    /// let http = caps.http.coalescing();
+    /// http.get("https://httpbin.org/get").send(Event::ReceiveResponse);
+    /// // sent to the shell only once
+    /// http.get("https://httpbin.org/get").send(Event::ReceiveResponse);
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn coalescing(&self) -> Self {
+        Self {
+            coalesce: true,
+            ..self.clone()
         }
     }
 
@@ -52,14 +52,24 @@ use std::sync::Arc;
 
 use crate::{Client, Request, ResponseAsync, Result};
 
+#[cfg(feature = "cache")]
+mod cache;
 mod redirect;
+#[cfg(feature = "retry")]
+mod retry;
 
+#[cfg(feature = "cache")]
+pub use cache::Cache;
 pub use redirect::Redirect;
+#[cfg(feature = "retry")]
+pub use retry::{is_transient, Attempts, Backoff, Retry};
 
 use async_trait::async_trait;
 use futures_util::future::BoxFuture;
 
 /// Middleware that wraps around remaining middleware chain.
+// async_trait marks the boxed future #[must_use], which it already is
+#[allow(clippy::double_must_use)]
 #[async_trait]
 pub trait Middleware: 'static + Send + Sync {
     /// Asynchronously handle the request, and return a response.
@@ -0,0 +1,602 @@
+//! HTTP cache middleware, storing responses with the [`KeyValue`] capability.
+//!
+//! Responses to GET requests are cached following their `Cache-Control` header, and served
+//! from the store while they are fresh, without making a request. Once they are stale, they
+//! are revalidated with the server using their `ETag` or `Last-Modified` header, and a
+//! `304 Not Modified` response is answered from the store.
+//!
+//! Requests with credentials, i.e. an `Authorization` or `Cookie` header, aren't cached.
+//! Responses with a `Vary` header are stored once for each value of the headers they vary by.
+//!
+//! The store is limited to a maximum size, evicting the least recently used responses
+//! to make room for new ones.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use crux_kv::KeyValue;
+//! # use crux_time::Time;
+//! # enum Event { ReceiveResponse(crux_http::Result<crux_http::Response<Vec<u8>>>) }
+//! # struct Capabilities { http: crux_http::Http<Event>, key_value: KeyValue<Event>, time: Time<Event> }
+//! # fn update(caps: &Capabilities) {
+//! use crux_http::middleware::Cache;
+//!
+//! caps.http
+//!     .get("https://httpbin.org/cache/60")
+//!     .middleware(Cache::new(caps.key_value.clone(), caps.time.clone()))
+//!     .send(Event::ReceiveResponse)
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+
+use crux_kv::{error::KeyValueError, KeyValue, Write};
+use crux_time::{Time, TimeResponse};
+use futures_util::future::{join, join_all};
+use serde::{Deserialize, Serialize};
+
+use crate::http::{headers, Method, StatusCode};
+use crate::middleware::{Middleware, Next, Request};
+use crate::protocol::{HttpHeader, HttpResponse};
+use crate::{Client, ResponseAsync, Result};
+
+const DEFAULT_PREFIX: &str = "crux_http:cache:";
+const DEFAULT_MAX_SIZE: usize = 10 * 1024 * 1024;
+/// How many times to try updating the index when other requests keep changing it
+const INDEX_ATTEMPTS: usize = 8;
+
+/// A middleware which caches responses to GET requests in the key-value store.
+pub struct Cache<Ev> {
+    key_value: KeyValue<Ev>,
+    time: Time<Ev>,
+    prefix: String,
+    max_size: usize,
+}
+
+impl<Ev> Cache<Ev>
+where
+    Ev: 'static,
+{
+    /// Create a new instance of the Cache middleware, which stores responses with `key_value`,
+    /// and uses `time` to tell whether they are fresh.
+    ///
+    /// The cache uses keys starting with `crux_http:cache:` and up to 10MB of the store,
+    /// use [`Cache::prefix`] and [`Cache::max_size`] to change that.
+    pub fn new(key_value: KeyValue<Ev>, time: Time<Ev>) -> Self {
+        Self {
+            key_value,
+            time,
+            prefix: DEFAULT_PREFIX.to_string(),
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+
+    /// Use keys starting with `prefix` in the store, e.g. to keep separate caches.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Store at most `max_size` bytes of responses, evicting the least recently used
+    /// ones when a new response doesn't fit. Responses larger than this aren't stored.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    fn index_key(&self) -> String {
+        format!("{}index", self.prefix)
+    }
+
+    fn entry_key(&self, url: &str) -> String {
+        format!("{}entry:{url}", self.prefix)
+    }
+
+    /// The key of the response to `url` for a request with `request_headers`, when the
+    /// response varies by the headers named in `vary`
+    fn variant_key(&self, url: &str, vary: &[String], request_headers: &[HttpHeader]) -> String {
+        let mut key = self.entry_key(url);
+        for name in vary {
+            let values: Vec<&str> = request_headers
+                .iter()
+                .filter(|header| header.name.eq_ignore_ascii_case(name))
+                .map(|header| header.value.as_str())
+                .collect();
+            key.push_str(&format!("\n{name}: {}", values.join(", ")));
+        }
+        key
+    }
+
+    async fn now(&self) -> Option<u64> {
+        match self.time.now_async().await {
+            TimeResponse::Now(instant) => Some(instant.seconds),
+            _ => None,
+        }
+    }
+
+    async fn read<T: for<'de> Deserialize<'de>>(&self, key: String) -> Option<T> {
+        let bytes = self.key_value.get_async(key).await.ok()??;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// The cached response for a request with `request_headers`, and its key, given what's
+    /// stored under the request's URL
+    async fn lookup(
+        &self,
+        url: &str,
+        request_headers: &[HttpHeader],
+        stored: Option<Stored>,
+    ) -> Option<(String, Entry)> {
+        match stored? {
+            Stored::Response(entry) => Some((self.entry_key(url), entry)),
+            Stored::Vary(vary) => {
+                let key = self.variant_key(url, &vary, request_headers);
+                match self.read(key.clone()).await? {
+                    Stored::Response(entry) => Some((key, entry)),
+                    Stored::Vary(_) => None,
+                }
+            }
+        }
+    }
+
+    /// Mark the entry under `key` as used at `now`. Only the entry is written, the index
+    /// only changes when entries are added or removed.
+    async fn touch(&self, key: String, mut entry: Entry, now: u64) {
+        if entry.last_used >= now {
+            return;
+        }
+        entry.last_used = now;
+        if let Ok(value) = serde_json::to_vec(&Stored::Response(entry)) {
+            let _ = self.key_value.set_async(key, value).await;
+        }
+    }
+
+    /// Store `values` under their keys, evicting the least recently used entries to make
+    /// room for them.
+    ///
+    /// Errors from the store are ignored, failing to cache a response shouldn't fail the request.
+    async fn write(&self, values: Vec<(String, Stored)>) {
+        let mut sizes = Vec::new();
+        let mut writes = Vec::new();
+        for (key, stored) in values {
+            let Ok(value) = serde_json::to_vec(&stored) else {
+                return;
+            };
+            sizes.push((key.clone(), value.len()));
+            writes.push(Write::Set { key, value });
+        }
+        if sizes.iter().map(|(_, size)| size).sum::<usize>() > self.max_size {
+            return;
+        }
+
+        let Some(evicted) = self.update_index(&sizes).await else {
+            return;
+        };
+        let deletes = evicted.into_iter().map(|key| Write::Delete { key });
+
+        let _ = self
+            .key_value
+            .batch_async(deletes.chain(writes).collect())
+            .await;
+    }
+
+    /// Add the entries of `sizes` to the index, and remove the least recently used ones
+    /// until they fit, returning the keys of those.
+    ///
+    /// Other requests may be writing to the cache at the same time, so the index is only
+    /// replaced if it hasn't changed since it was read, and updated again if it has.
+    async fn update_index(&self, sizes: &[(String, usize)]) -> Option<Vec<String>> {
+        let mut current = self.key_value.get_async(self.index_key()).await.ok()?;
+
+        for _ in 0..INDEX_ATTEMPTS {
+            let mut index: Index = current
+                .as_deref()
+                .and_then(|bytes| serde_json::from_slice(bytes).ok())
+                .unwrap_or_default();
+            for (key, size) in sizes {
+                index.0.insert(key.clone(), *size);
+            }
+            let evicted = self.evict(&mut index, sizes).await;
+            let new = serde_json::to_vec(&index).ok()?;
+
+            match self
+                .key_value
+                .compare_and_swap_async(self.index_key(), current.clone(), Some(new))
+                .await
+            {
+                Ok(()) => return Some(evicted),
+                Err(KeyValueError::Conflict { current: value }) => current = value.into(),
+                Err(_) => return None,
+            }
+        }
+
+        None
+    }
+
+    /// Remove the least recently used entries from the index, other than those in `kept`,
+    /// until it is within the maximum size, returning their keys
+    async fn evict(&self, index: &mut Index, kept: &[(String, usize)]) -> Vec<String> {
+        if index.size() <= self.max_size {
+            return Vec::new();
+        }
+
+        // the recency of each entry is only kept in the entry, so read them all
+        let keys: Vec<String> = index
+            .0
+            .keys()
+            .filter(|key| !kept.iter().any(|(kept, _)| kept == *key))
+            .cloned()
+            .collect();
+        let stored = join_all(keys.iter().map(|key| self.read::<Stored>(key.clone()))).await;
+        let last_used: BTreeMap<&String, u64> = keys
+            .iter()
+            .zip(&stored)
+            .filter_map(|(key, stored)| match stored {
+                Some(Stored::Response(entry)) => Some((key, entry.last_used)),
+                _ => None,
+            })
+            .collect();
+
+        // the responses varying by a header are used as recently as their latest variant,
+        // and anything unreadable is the first to go
+        let mut candidates: Vec<(u64, &String)> = keys
+            .iter()
+            .zip(&stored)
+            .map(|(key, stored)| match stored {
+                Some(Stored::Response(entry)) => (entry.last_used, key),
+                Some(Stored::Vary(_)) => {
+                    let variants = format!("{key}\n");
+                    let latest = last_used
+                        .iter()
+                        .filter(|(variant, _)| variant.starts_with(&variants))
+                        .map(|(_, last_used)| *last_used)
+                        .max();
+                    (latest.unwrap_or_default(), key)
+                }
+                None => (0, key),
+            })
+            .collect();
+        candidates.sort();
+
+        let mut evicted = Vec::new();
+        for (_, key) in candidates {
+            if index.size() <= self.max_size {
+                break;
+            }
+            index.0.remove(key);
+            evicted.push(key.clone());
+        }
+        evicted
+    }
+}
+
+impl<Ev> Clone for Cache<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            key_value: self.key_value.clone(),
+            time: self.time.clone(),
+            prefix: self.prefix.clone(),
+            max_size: self.max_size,
+        }
+    }
+}
+
+impl<Ev> std::fmt::Debug for Cache<Ev> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cache")
+            .field("prefix", &self.prefix)
+            .field("max_size", &self.max_size)
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl<Ev> Middleware for Cache<Ev>
+where
+    Ev: Send + 'static,
+{
+    async fn handle(
+        &self,
+        mut req: Request,
+        client: Client,
+        next: Next<'_>,
+    ) -> Result<ResponseAsync> {
+        let request_directives = Directives::parse(
+            req.header(headers::CACHE_CONTROL)
+                .map(|values| values.as_str()),
+        );
+        let has_credentials =
+            req.header(headers::AUTHORIZATION).is_some() || req.header(headers::COOKIE).is_some();
+        if req.method() != Method::Get || request_directives.no_store || has_credentials {
+            return next.run(req, client).await;
+        }
+
+        let url = req.url().to_string();
+        let request_headers = headers_of(req.iter());
+        let (now, stored) = join(self.now(), self.read(self.entry_key(&url))).await;
+        let Some(now) = now else {
+            return next.run(req, client).await;
+        };
+        let cached = self.lookup(&url, &request_headers, stored).await;
+
+        if let Some((key, entry)) = &cached {
+            if entry.is_fresh(now) && !request_directives.no_cache {
+                let response = entry.response.clone();
+                self.touch(key.clone(), entry.clone(), now).await;
+                return Ok(response.into());
+            }
+
+            if let Some(etag) = entry.header(headers::ETAG.as_str()) {
+                req.insert_header(headers::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = entry.header(headers::LAST_MODIFIED.as_str()) {
+                req.insert_header(headers::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let mut res = next.run(req, client).await?;
+
+        match (res.status(), cached) {
+            (StatusCode::NotModified, Some((key, mut entry))) => {
+                entry.revalidated(&res, now);
+                let response = entry.response.clone();
+                self.write(vec![(key, Stored::Response(entry))]).await;
+                Ok(response.into())
+            }
+            (StatusCode::Ok, _) => {
+                let body = res.body_bytes().await?;
+                let response = HttpResponse {
+                    status: res.status().into(),
+                    headers: headers_of(res.iter()),
+                    body: body.clone(),
+                };
+                res.set_body(body);
+
+                let vary = Vary::parse(res.header(headers::VARY).map(|values| values.as_str()));
+                let entry = Entry::new(response, now);
+                match (entry, vary) {
+                    (Some(entry), Vary::Nothing) => {
+                        self.write(vec![(self.entry_key(&url), Stored::Response(entry))])
+                            .await;
+                    }
+                    (Some(entry), Vary::Headers(vary)) => {
+                        let key = self.variant_key(&url, &vary, &request_headers);
+                        self.write(vec![
+                            (self.entry_key(&url), Stored::Vary(vary)),
+                            (key, Stored::Response(entry)),
+                        ])
+                        .await;
+                    }
+                    _ => {}
+                }
+                Ok(res)
+            }
+            _ => Ok(res),
+        }
+    }
+}
+
+/// What is stored under the URL of a request, or of a request and the headers the
+/// response varies by
+#[derive(Serialize, Deserialize)]
+enum Stored {
+    Response(Entry),
+    /// The names of the headers the response varies by
+    Vary(Vec<String>),
+}
+
+/// A cached response, until when it can be used without revalidating it, and when it
+/// was last used
+#[derive(Serialize, Deserialize, Clone)]
+struct Entry {
+    response: HttpResponse,
+    fresh_until: u64,
+    last_used: u64,
+}
+
+impl Entry {
+    /// The entry for a response received at `now`, if it can be cached
+    fn new(response: HttpResponse, now: u64) -> Option<Self> {
+        let mut entry = Entry {
+            response,
+            fresh_until: now,
+            last_used: now,
+        };
+        let directives = Directives::parse(entry.header(headers::CACHE_CONTROL.as_str()));
+        if directives.no_store {
+            return None;
+        }
+
+        let can_revalidate = entry.header(headers::ETAG.as_str()).is_some()
+            || entry.header(headers::LAST_MODIFIED.as_str()).is_some();
+        match directives.max_age {
+            Some(max_age) if max_age > 0 && !directives.no_cache => {
+                entry.fresh_until = now.saturating_add(max_age);
+            }
+            _ if !can_revalidate => return None,
+            _ => {}
+        }
+
+        Some(entry)
+    }
+
+    fn is_fresh(&self, now: u64) -> bool {
+        now < self.fresh_until
+    }
+
+    /// Update the entry with the headers of a `304 Not Modified` response received at `now`
+    fn revalidated(&mut self, not_modified: &ResponseAsync, now: u64) {
+        for header in headers_of(not_modified.iter()) {
+            if header
+                .name
+                .eq_ignore_ascii_case(headers::CONTENT_LENGTH.as_str())
+            {
+                continue;
+            }
+            self.response
+                .headers
+                .retain(|existing| !existing.name.eq_ignore_ascii_case(&header.name));
+            self.response.headers.push(header);
+        }
+
+        let directives = Directives::parse(self.header(headers::CACHE_CONTROL.as_str()));
+        self.fresh_until = match directives.max_age {
+            Some(max_age) if !directives.no_cache => now.saturating_add(max_age),
+            _ => now,
+        };
+        self.last_used = now;
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.response
+            .headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .map(|header| header.value.as_str())
+    }
+}
+
+fn headers_of(headers: crate::http::headers::Iter<'_>) -> Vec<HttpHeader> {
+    headers
+        .flat_map(|(name, values)| {
+            values.iter().map(|value| HttpHeader {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The keys of the cached responses and their sizes
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+struct Index(BTreeMap<String, usize>);
+
+impl Index {
+    fn size(&self) -> usize {
+        self.0.values().sum()
+    }
+}
+
+/// The headers named by a `Vary` header
+#[derive(Debug, PartialEq, Eq)]
+enum Vary {
+    Nothing,
+    /// The lowercase names of the headers, sorted
+    Headers(Vec<String>),
+    /// The response varies by something other than headers, so it can't be reused
+    Anything,
+}
+
+impl Vary {
+    fn parse(header: Option<&str>) -> Self {
+        let mut names: Vec<String> = header
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect();
+        names.sort();
+        names.dedup();
+
+        if names.iter().any(|name| name == "*") {
+            Vary::Anything
+        } else if names.is_empty() {
+            Vary::Nothing
+        } else {
+            Vary::Headers(names)
+        }
+    }
+}
+
+/// The `Cache-Control` directives the cache understands
+#[derive(Default, Debug, PartialEq, Eq)]
+struct Directives {
+    no_store: bool,
+    no_cache: bool,
+    max_age: Option<u64>,
+}
+
+impl Directives {
+    fn parse(header: Option<&str>) -> Self {
+        let mut directives = Self::default();
+
+        for directive in header.unwrap_or_default().split(',') {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+            match name.to_ascii_lowercase().as_str() {
+                "no-store" => directives.no_store = true,
+                "no-cache" => directives.no_cache = true,
+                "max-age" => directives.max_age = value.and_then(|value| value.parse().ok()),
+                _ => {}
+            }
+        }
+
+        directives
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cache_control() {
+        assert_eq!(
+            Directives::parse(Some("public, max-age=60")),
+            Directives {
+                max_age: Some(60),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            Directives::parse(Some("No-Cache, max-age=\"0\"")),
+            Directives {
+                no_cache: true,
+                max_age: Some(0),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            Directives::parse(Some("no-store")),
+            Directives {
+                no_store: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(Directives::parse(None), Directives::default());
+    }
+
+    #[test]
+    fn parses_vary() {
+        assert_eq!(Vary::parse(None), Vary::Nothing);
+        assert_eq!(
+            Vary::parse(Some("Accept-Language, accept, Accept")),
+            Vary::Headers(vec!["accept".to_string(), "accept-language".to_string()])
+        );
+        assert_eq!(Vary::parse(Some("Accept, *")), Vary::Anything);
+    }
+
+    #[test]
+    fn only_cacheable_responses_are_stored() {
+        let response = |cache_control: &str| {
+            HttpResponse::ok()
+                .header("Cache-Control", cache_control)
+                .build()
+        };
+
+        let entry = Entry::new(response("max-age=60"), 100).unwrap();
+        assert!(entry.is_fresh(159));
+        assert!(!entry.is_fresh(160));
+
+        assert!(Entry::new(response("no-store, max-age=60"), 100).is_none());
+        assert!(Entry::new(response("no-cache"), 100).is_none());
+
+        let revalidate = HttpResponse::ok()
+            .header("Cache-Control", "no-cache")
+            .header("ETag", "\"v1\"")
+            .build();
+        assert!(!Entry::new(revalidate, 100).unwrap().is_fresh(100));
+    }
+}
@@ -0,0 +1,344 @@
+//! HTTP retry middleware, waiting between attempts with the [`Time`] capability.
+//!
+//! Requests which fail with a transient error, e.g. a timeout or a `503 Service Unavailable`
+//! response, are sent again after a delay, which grows with every attempt. The delays are
+//! jittered, so that many apps failing at the same time don't all retry at the same time.
+//! A `Retry-After` header with a number of seconds overrides the delay.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use crux_time::Time;
+//! # enum Event { ReceiveResponse(crux_http::Result<crux_http::Response<Vec<u8>>>) }
+//! # struct Capabilities { http: crux_http::Http<Event>, time: Time<Event> }
+//! # fn update(caps: &Capabilities) {
+//! use crux_http::middleware::{Backoff, Retry};
+//! use crux_time::Duration;
+//!
+//! caps.http
+//!     .get("https://httpbin.org/status/503")
+//!     .middleware(
+//!         Retry::new(caps.time.clone())
+//!             .max_attempts(5)
+//!             .backoff(Backoff::Constant(Duration::from_secs(2).unwrap())),
+//!     )
+//!     .send(Event::ReceiveResponse)
+//! # }
+//! ```
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+};
+
+use crux_time::{Duration, Time, TimeResponse};
+
+use crate::http::{headers, StatusCode};
+use crate::middleware::{Middleware, Next, Request};
+use crate::{Client, HttpError, ResponseAsync, Result};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const NANOS_PER_MILLI: u64 = 1_000_000;
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+type Predicate = dyn Fn(&Result<ResponseAsync>) -> bool + Send + Sync;
+
+/// A middleware which sends a request again when it fails with a transient error.
+///
+/// Only use it for requests which are safe to repeat, e.g. GET requests, or requests the
+/// server deduplicates with an idempotency key, as a request which timed out may still have
+/// been processed.
+pub struct Retry<Ev> {
+    time: Time<Ev>,
+    max_attempts: u32,
+    backoff: Backoff,
+    jitter: bool,
+    retry_on: Arc<Predicate>,
+}
+
+/// How long to wait before each retry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Backoff {
+    /// The same delay before every retry
+    Constant(Duration),
+    /// `initial` before the first retry, doubling before each following one, up to `max`
+    Exponential { initial: Duration, max: Duration },
+    /// The delays before each retry in turn, repeating the last one if there are more retries
+    Schedule(Vec<Duration>),
+}
+
+/// The attempts the [`Retry`] middleware made to get a response, available from
+/// [`Response::attempts`](crate::Response::attempts).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attempts {
+    /// How many times the request was sent, including the first time
+    pub count: u32,
+    /// How long the middleware waited before each retry
+    pub delays: Vec<Duration>,
+}
+
+impl<Ev> Retry<Ev>
+where
+    Ev: 'static,
+{
+    /// Create a new instance of the Retry middleware, which waits between attempts with `time`.
+    ///
+    /// By default, a request is sent up to 3 times, retrying the errors and responses
+    /// [`is_transient`] accepts, with an exponential backoff from 500ms up to 30s. Use
+    /// [`Retry::max_attempts`], [`Retry::retry_on`] and [`Retry::backoff`] to change that.
+    pub fn new(time: Time<Ev>) -> Self {
+        Self {
+            time,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            backoff: Backoff::Exponential {
+                initial: Duration::new(500 * NANOS_PER_MILLI),
+                max: Duration::new(30 * NANOS_PER_SEC),
+            },
+            jitter: true,
+            retry_on: Arc::new(is_transient),
+        }
+    }
+
+    /// Send the request at most `max_attempts` times, including the first time.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Wait between attempts following `backoff`.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Whether to randomize the delays, waiting between half and all of each delay. This is
+    /// on by default, turning it off makes the delays predictable, e.g. in tests.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Retry the errors and responses `predicate` returns `true` for, instead of the
+    /// ones [`is_transient`] accepts.
+    pub fn retry_on<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Result<ResponseAsync>) -> bool + Send + Sync + 'static,
+    {
+        self.retry_on = Arc::new(predicate);
+        self
+    }
+
+    /// The delay before the retry following attempt number `attempt`, starting from 1
+    fn delay(&self, attempt: u32, result: &Result<ResponseAsync>) -> Duration {
+        if let Some(retry_after) = retry_after(result) {
+            return retry_after;
+        }
+
+        let delay = self.backoff.delay(attempt);
+        if self.jitter {
+            jittered(delay, random())
+        } else {
+            delay
+        }
+    }
+}
+
+impl Backoff {
+    /// The delay before the retry following attempt number `attempt`, starting from 1
+    fn delay(&self, attempt: u32) -> Duration {
+        let retry = attempt.saturating_sub(1);
+
+        match self {
+            Backoff::Constant(delay) => *delay,
+            Backoff::Exponential { initial, max } => {
+                let factor = 1u64.checked_shl(retry).unwrap_or(u64::MAX);
+                let delay = initial.as_nanos().saturating_mul(factor);
+                Duration::new(delay.min(max.as_nanos()))
+            }
+            Backoff::Schedule(delays) => {
+                let index = usize::try_from(retry).unwrap_or(usize::MAX);
+                delays
+                    .get(index)
+                    .or(delays.last())
+                    .copied()
+                    .unwrap_or(Duration::new(0))
+            }
+        }
+    }
+}
+
+/// Whether the outcome of a request is worth retrying: an I/O error or a timeout, or a
+/// `408 Request Timeout`, `429 Too Many Requests`, `500 Internal Server Error`,
+/// `502 Bad Gateway`, `503 Service Unavailable` or `504 Gateway Timeout` response.
+pub fn is_transient(result: &Result<ResponseAsync>) -> bool {
+    match result {
+        Ok(response) => matches!(
+            response.status(),
+            StatusCode::RequestTimeout
+                | StatusCode::TooManyRequests
+                | StatusCode::InternalServerError
+                | StatusCode::BadGateway
+                | StatusCode::ServiceUnavailable
+                | StatusCode::GatewayTimeout
+        ),
+        Err(error) => matches!(error, HttpError::Io(_) | HttpError::Timeout),
+    }
+}
+
+/// The delay in a `Retry-After` header, if it's a number of seconds
+fn retry_after(result: &Result<ResponseAsync>) -> Option<Duration> {
+    let seconds: u64 = result
+        .as_ref()
+        .ok()?
+        .header(headers::RETRY_AFTER)?
+        .last()
+        .as_str()
+        .trim()
+        .parse()
+        .ok()?;
+
+    Some(Duration::new(seconds.saturating_mul(NANOS_PER_SEC)))
+}
+
+/// Between half and all of `delay`, picked by `random`
+fn jittered(delay: Duration, random: u64) -> Duration {
+    let half = delay.as_nanos() / 2;
+    let jitter = if half == 0 { 0 } else { random % (half + 1) };
+
+    Duration::new(delay.as_nanos() - half + jitter)
+}
+
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+impl<Ev> Clone for Retry<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            time: self.time.clone(),
+            max_attempts: self.max_attempts,
+            backoff: self.backoff.clone(),
+            jitter: self.jitter,
+            retry_on: self.retry_on.clone(),
+        }
+    }
+}
+
+impl<Ev> std::fmt::Debug for Retry<Ev> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Retry")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl<Ev> Middleware for Retry<Ev>
+where
+    Ev: Send + 'static,
+{
+    async fn handle(
+        &self,
+        mut req: Request,
+        client: Client,
+        next: Next<'_>,
+    ) -> Result<ResponseAsync> {
+        // cloning a request drops its body, so keep it to send with every attempt
+        let body = req.take_body().into_bytes().await?;
+        let mut attempts = Attempts {
+            count: 0,
+            delays: Vec::new(),
+        };
+
+        loop {
+            let mut attempt = req.clone();
+            if !body.is_empty() {
+                attempt.body_bytes(&body);
+            }
+            let result = next.run(attempt, client.clone()).await;
+            attempts.count += 1;
+
+            if attempts.count >= self.max_attempts || !(self.retry_on)(&result) {
+                return result.map(|mut res| {
+                    res.insert_ext(attempts);
+                    res
+                });
+            }
+
+            let delay = self.delay(attempts.count, &result);
+            if !matches!(
+                self.time.sleep_async(delay).await,
+                TimeResponse::DurationElapsed { .. }
+            ) {
+                return result;
+            }
+            attempts.delays.push(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis).unwrap()
+    }
+
+    #[test]
+    fn exponential_backoff_doubles_up_to_the_maximum() {
+        let backoff = Backoff::Exponential {
+            initial: millis(100),
+            max: millis(500),
+        };
+
+        let delays: Vec<_> = (1..=5).map(|attempt| backoff.delay(attempt)).collect();
+        assert_eq!(
+            delays,
+            [
+                millis(100),
+                millis(200),
+                millis(400),
+                millis(500),
+                millis(500)
+            ]
+        );
+        assert_eq!(backoff.delay(u32::MAX), millis(500));
+    }
+
+    #[test]
+    fn schedule_repeats_the_last_delay() {
+        let backoff = Backoff::Schedule(vec![millis(10), millis(50)]);
+
+        let delays: Vec<_> = (1..=3).map(|attempt| backoff.delay(attempt)).collect();
+        assert_eq!(delays, [millis(10), millis(50), millis(50)]);
+        assert_eq!(Backoff::Schedule(vec![]).delay(1), Duration::new(0));
+    }
+
+    #[test]
+    fn jitter_keeps_at_least_half_the_delay() {
+        assert_eq!(jittered(millis(100), 0), millis(50));
+        assert_eq!(jittered(millis(100), 50 * NANOS_PER_MILLI), millis(100));
+        assert_eq!(jittered(Duration::new(1), u64::MAX), Duration::new(1));
+
+        for _ in 0..100 {
+            let delay = jittered(millis(100), random()).as_nanos();
+            assert!((50 * NANOS_PER_MILLI..=100 * NANOS_PER_MILLI).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn transient_failures() {
+        let response = |status| Ok(ResponseAsync::new(crate::http::Response::new(status)));
+
+        assert!(is_transient(&response(StatusCode::ServiceUnavailable)));
+        assert!(is_transient(&response(StatusCode::TooManyRequests)));
+        assert!(!is_transient(&response(StatusCode::NotFound)));
+        assert!(!is_transient(&response(StatusCode::Ok)));
+        assert!(is_transient(&Err(HttpError::Timeout)));
+        assert!(!is_transient(&Err(HttpError::Url("nope".to_string()))));
+    }
+}
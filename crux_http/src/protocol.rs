@@ -4,8 +4,11 @@
 //! out all their operations by exchanging messages with the platform specific shell.
 //! This module defines the protocol for crux_http to communicate with the shell.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use derive_builder::Builder;
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 
 use crate::HttpError;
@@ -29,6 +32,10 @@ pub struct HttpRequest {
     pub headers: Vec<HttpHeader>,
     #[serde(with = "serde_bytes")]
     pub body: Vec<u8>,
+    /// Whether the app wants to hear how the transfer is going. If it does, the shell can
+    /// resolve the request with any number of [`HttpResult::Progress`] before the final
+    /// [`HttpResult::Ok`] or [`HttpResult::Err`].
+    pub report_progress: bool,
 }
 
 impl std::fmt::Debug for HttpRequest {
@@ -49,9 +56,11 @@ impl std::fmt::Debug for HttpRequest {
         if !self.headers.is_empty() {
             builder.field("headers", &self.headers);
         };
-        builder
-            .field("body", &format_args!("{}", body_repr))
-            .finish()
+        builder.field("body", &format_args!("{}", body_repr));
+        if self.report_progress {
+            builder.field("report_progress", &self.report_progress);
+        };
+        builder.finish()
     }
 }
 
@@ -63,6 +72,7 @@ macro_rules! http_method {
                 url: Some(url.into()),
                 headers: Some(vec![]),
                 body: Some(vec![]),
+                report_progress: Some(false),
             }
         }
     };
@@ -145,10 +155,33 @@ impl HttpResponseBuilder {
     }
 }
 
+/// Which way the bytes of an [`HttpProgress`] are going
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpTransfer {
+    /// Sending the request body
+    Upload,
+    /// Receiving the response body
+    Download,
+}
+
+/// How far along a transfer is, which the shell sends for requests which ask to
+/// [`report_progress`](HttpRequest::report_progress)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HttpProgress {
+    pub transfer: HttpTransfer,
+    /// The number of bytes transferred so far
+    pub bytes: u64,
+    /// The number of bytes to transfer in total, if the shell knows, e.g. from the
+    /// `Content-Length` of the response
+    pub total: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub enum HttpResult {
     Ok(HttpResponse),
     Err(HttpError),
+    /// An update on the transfer, before the final result
+    Progress(HttpProgress),
 }
 
 impl From<crate::Result<HttpResponse>> for HttpResult {
@@ -164,9 +197,24 @@ impl crux_core::capability::Operation for HttpRequest {
     type Output = HttpResult;
 }
 
+/// Where the progress of a request goes, kept in the request's extensions until it's
+/// sent to the shell
+#[derive(Clone)]
+pub(crate) struct OnProgress(pub(crate) Arc<dyn Fn(HttpProgress) + Send + Sync>);
+
+/// Marks a request to be coalesced with identical ones while they are in flight, kept in
+/// the request's extensions until it's sent to the shell
+#[derive(Clone, Copy)]
+pub(crate) struct Coalesce;
+
+#[allow(clippy::double_must_use)]
 #[async_trait]
 pub(crate) trait EffectSender {
     async fn send(&self, effect: HttpRequest) -> HttpResult;
+
+    /// Send the request, passing any progress the shell reports to `on_progress`,
+    /// and return the final result
+    async fn send_with_progress(&self, effect: HttpRequest, on_progress: OnProgress) -> HttpResult;
 }
 
 #[async_trait]
@@ -177,8 +225,24 @@ where
     async fn send(&self, effect: HttpRequest) -> HttpResult {
         crux_core::capability::CapabilityContext::request_from_shell(self, effect).await
     }
+
+    async fn send_with_progress(&self, effect: HttpRequest, on_progress: OnProgress) -> HttpResult {
+        let mut results = self.stream_from_shell(effect);
+
+        while let Some(result) = results.next().await {
+            match result {
+                HttpResult::Progress(progress) => (on_progress.0)(progress),
+                result => return result,
+            }
+        }
+
+        HttpResult::Err(HttpError::Io(
+            "the shell stopped responding before the request finished".to_string(),
+        ))
+    }
 }
 
+#[allow(clippy::double_must_use)]
 #[async_trait]
 pub(crate) trait ProtocolRequestBuilder {
     async fn into_protocol_request(mut self) -> crate::Result<HttpRequest>;
@@ -206,6 +270,7 @@ impl ProtocolRequestBuilder for crate::Request {
                 })
                 .collect(),
             body,
+            report_progress: false,
         })
     }
 }
@@ -257,6 +322,7 @@ mod tests {
                     value: "bar".to_string(),
                 }],
                 body: "123".as_bytes().to_vec(),
+                report_progress: false,
             }
         );
     }
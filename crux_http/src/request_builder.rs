@@ -1,5 +1,6 @@
 use crate::expect::{ExpectBytes, ExpectJson, ExpectString};
 use crate::middleware::Middleware;
+use crate::protocol::{Coalesce, HttpProgress, OnProgress};
 use crate::{
     expect::ResponseExpectation,
     http::{
@@ -13,7 +14,7 @@ use futures_util::future::BoxFuture;
 use http_types::convert::DeserializeOwned;
 use serde::Serialize;
 
-use std::{fmt, marker::PhantomData};
+use std::{fmt, marker::PhantomData, sync::Arc};
 
 /// Request Builder
 ///
@@ -57,8 +58,13 @@ enum CapOrClient<Event> {
 
 impl<Event> RequestBuilder<Event, Vec<u8>> {
     pub(crate) fn new(method: Method, url: Url, capability: crate::Http<Event>) -> Self {
+        let mut req = Request::new(method, url);
+        if capability.coalesce {
+            req.set_ext(Coalesce);
+        }
+
         Self {
-            req: Some(Request::new(method, url)),
+            req: Some(req),
             cap_or_client: CapOrClient::Capability(capability),
             phantom: PhantomData,
             expectation: Box::new(ExpectBytes),
@@ -285,6 +291,74 @@ where
         self
     }
 
+    /// Dispatch an event made by `make_event` to the app's `update` function every time the
+    /// shell reports progress on the transfer, e.g. to show a progress bar for a large
+    /// download. The response is still dispatched once it's complete, by the callback
+    /// passed to [`send`](RequestBuilder::send), or returned by the future.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called in a middleware context, which can't dispatch events.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use crux_http::protocol::HttpProgress;
+    /// # enum Event {
+    /// #     Progress(HttpProgress),
+    /// #     ReceiveResponse(crux_http::Result<crux_http::Response<Vec<u8>>>),
+    /// # }
+    /// # struct Capabilities { http: crux_http::Http<Event> }
+    /// # fn update(caps: &Capabilities) {
+    /// caps.http
+    ///     .get("https://example.com/large.zip")
+    ///     .on_progress(Event::Progress)
+    ///     .send(Event::ReceiveResponse)
+    /// # }
+    /// ```
+    pub fn on_progress<F>(mut self, make_event: F) -> Self
+    where
+        F: Fn(HttpProgress) -> Event + Send + Sync + 'static,
+    {
+        let CapOrClient::Capability(capability) = &self.cap_or_client else {
+            panic!("Called RequestBuilder::on_progress in a middleware context");
+        };
+        let context = capability.context.clone();
+
+        self.req
+            .as_mut()
+            .unwrap()
+            .set_ext(OnProgress(Arc::new(move |progress| {
+                context.update_app(make_event(progress));
+            })));
+        self
+    }
+
+    /// Coalesce the request with identical ones: while one is waiting for the shell's
+    /// response, the others aren't sent to the shell, and each is resolved with the same
+    /// response once it arrives. The next identical request after that is sent again.
+    ///
+    /// Requests are identical when their method, URL, headers and body are the same, after
+    /// any middleware ran. Only GET and HEAD requests are coalesced, and not those reporting
+    /// [progress](RequestBuilder::on_progress).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # enum Event { ReceiveResponse(crux_http::Result<crux_http::Response<Vec<u8>>>) }
+    /// # struct Capabilities { http: crux_http::Http<Event> }
+    /// # fn update(caps: &Capabilities) {
+    /// caps.http
+    ///     .get("https://httpbin.org/get")
+    ///     .coalesce()
+    ///     .send(Event::ReceiveResponse)
+    /// # }
+    /// ```
+    pub fn coalesce(mut self) -> Self {
+        self.req.as_mut().unwrap().set_ext(Coalesce);
+        self
+    }
+
     /// Return the constructed `Request`.
     pub fn build(self) -> Request {
         self.req.unwrap()
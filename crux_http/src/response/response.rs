@@ -19,6 +19,9 @@ pub struct Response<Body> {
     #[serde(with = "header_serde")]
     headers: Headers,
     body: Option<Body>,
+    #[cfg(feature = "retry")]
+    #[serde(skip)]
+    attempts: Option<crate::middleware::Attempts>,
 }
 
 impl<Body> Response<Body> {
@@ -43,6 +46,8 @@ impl<Body> Response<Body> {
             headers,
             version: res.version(),
             body: Some(body),
+            #[cfg(feature = "retry")]
+            attempts: res.ext().cloned(),
         })
     }
 
@@ -167,8 +172,17 @@ impl<Body> Response<Body> {
             headers: self.headers,
             status: self.status,
             version: self.version,
+            #[cfg(feature = "retry")]
+            attempts: self.attempts,
         }
     }
+
+    /// How many attempts the [`Retry`](crate::middleware::Retry) middleware made to get
+    /// this response, and how long it waited between them, if it was used.
+    #[cfg(feature = "retry")]
+    pub fn attempts(&self) -> Option<&crate::middleware::Attempts> {
+        self.attempts.as_ref()
+    }
 }
 
 impl Response<Vec<u8>> {
@@ -180,6 +194,8 @@ impl Response<Vec<u8>> {
             headers,
             version: None,
             body: None,
+            #[cfg(feature = "retry")]
+            attempts: None,
         }
     }
 
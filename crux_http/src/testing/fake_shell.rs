@@ -5,7 +5,7 @@ use std::{
 
 use async_trait::async_trait;
 
-use crate::protocol::{EffectSender, HttpRequest, HttpResponse, HttpResult};
+use crate::protocol::{EffectSender, HttpRequest, HttpResponse, HttpResult, OnProgress};
 
 /// FakeShell implements EffectSender for use in our internal tests.
 #[derive(Clone, Default)]
@@ -47,4 +47,8 @@ impl EffectSender for FakeShell {
                 .expect("test tried to send an unexpected HttpRequest"),
         )
     }
+
+    async fn send_with_progress(&self, effect: HttpRequest, _: OnProgress) -> HttpResult {
+        self.send(effect).await
+    }
 }
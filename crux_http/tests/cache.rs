@@ -0,0 +1,437 @@
+#![cfg(feature = "cache")]
+
+mod shared {
+    use crux_core::macros::Effect;
+    use crux_http::{middleware::Cache, Http};
+    use crux_kv::KeyValue;
+    use crux_time::Time;
+    use serde::{Deserialize, Serialize};
+
+    pub const MAX_SIZE: usize = 2000;
+
+    #[derive(Default)]
+    pub(crate) struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Fetch(String),
+        FetchWithHeader(String, String, String),
+
+        // events local to the core
+        #[serde(skip)]
+        Fetched(crux_http::Result<crux_http::Response<String>>),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub body: String,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Fetch(url) => caps
+                    .http
+                    .get(url)
+                    .middleware(
+                        Cache::new(caps.key_value.clone(), caps.time.clone()).max_size(MAX_SIZE),
+                    )
+                    .expect_string()
+                    .send(Event::Fetched),
+                Event::FetchWithHeader(url, name, value) => caps
+                    .http
+                    .get(url)
+                    .header(name.as_str(), value)
+                    .middleware(
+                        Cache::new(caps.key_value.clone(), caps.time.clone()).max_size(MAX_SIZE),
+                    )
+                    .expect_string()
+                    .send(Event::Fetched),
+                Event::Fetched(Ok(mut response)) => {
+                    model.body = response.take_body().unwrap();
+                }
+                Event::Fetched(Err(error)) => panic!("error: {error:?}"),
+            }
+        }
+
+        fn view(&self, _model: &Self::Model) -> Self::ViewModel {}
+    }
+
+    #[derive(Effect)]
+    pub(crate) struct Capabilities {
+        pub http: Http<Event>,
+        pub key_value: KeyValue<Event>,
+        pub time: Time<Event>,
+    }
+}
+
+mod tests {
+    use std::collections::{BTreeMap, VecDeque};
+
+    use crux_core::testing::AppTester;
+    use crux_http::protocol::{HttpRequest, HttpResponse, HttpResult};
+    use crux_kv::{
+        error::KeyValueError, value::Value, KeyValueOperation, KeyValueResponse, KeyValueResult,
+        Write,
+    };
+    use crux_time::{Instant, TimeResponse};
+
+    use crate::shared::{App, Effect, Event, Model};
+
+    /// A shell with an in-memory store, a clock, and canned HTTP responses
+    #[derive(Default)]
+    struct Shell {
+        store: BTreeMap<String, Vec<u8>>,
+        now: u64,
+        responses: VecDeque<HttpResponse>,
+        requests: Vec<HttpRequest>,
+        operations: Vec<KeyValueOperation>,
+    }
+
+    impl Shell {
+        fn fetch(&mut self, app: &AppTester<App, Effect>, model: &mut Model, url: &str) {
+            self.handle(app, model, vec![Event::Fetch(url.to_string())]);
+        }
+
+        /// Handle the events together, resolving the effects of all of them in turn
+        fn handle(&mut self, app: &AppTester<App, Effect>, model: &mut Model, events: Vec<Event>) {
+            model.body.clear();
+
+            let mut effects: VecDeque<Effect> = events
+                .into_iter()
+                .flat_map(|event| app.update(event, model).effects)
+                .collect();
+
+            while let Some(effect) = effects.pop_front() {
+                let update = match effect {
+                    Effect::Time(mut request) => {
+                        let now = TimeResponse::Now(Instant::new(self.now, 0).unwrap());
+                        app.resolve(&mut request, now)
+                    }
+                    Effect::KeyValue(mut request) => {
+                        let result = self.key_value(&request.operation);
+                        app.resolve(&mut request, result)
+                    }
+                    Effect::Http(mut request) => {
+                        self.requests.push(request.operation.clone());
+                        let response = self.responses.pop_front().expect("a response");
+                        app.resolve(&mut request, HttpResult::Ok(response))
+                    }
+                }
+                .unwrap();
+
+                effects.extend(update.effects);
+                for event in update.events {
+                    effects.extend(app.update(event, model).effects);
+                }
+            }
+        }
+
+        fn key_value(&mut self, operation: &KeyValueOperation) -> KeyValueResult {
+            self.operations.push(operation.clone());
+
+            let response = match operation {
+                KeyValueOperation::Get { key } => KeyValueResponse::Get {
+                    value: self.store.get(key).cloned().into(),
+                },
+                KeyValueOperation::Set { key, value } => KeyValueResponse::Set {
+                    previous: self.store.insert(key.clone(), value.clone()).into(),
+                },
+                KeyValueOperation::CompareAndSwap { key, expected, new } => {
+                    let current: Value = self.store.get(key).cloned().into();
+                    if &current != expected {
+                        let error = KeyValueError::Conflict { current };
+                        return KeyValueResult::Err { error };
+                    }
+                    match new {
+                        Value::Bytes(new) => self.store.insert(key.clone(), new.clone()),
+                        Value::None => self.store.remove(key),
+                    };
+                    KeyValueResponse::CompareAndSwap
+                }
+                KeyValueOperation::Batch { writes } => {
+                    for write in writes {
+                        match write {
+                            Write::Set { key, value } => {
+                                self.store.insert(key.clone(), value.clone());
+                            }
+                            Write::Delete { key } => {
+                                self.store.remove(key);
+                            }
+                        }
+                    }
+                    KeyValueResponse::Batch
+                }
+                operation => panic!("unexpected operation {operation:?}"),
+            };
+
+            KeyValueResult::Ok { response }
+        }
+
+        fn is_cached(&self, url: &str) -> bool {
+            self.store
+                .contains_key(&format!("crux_http:cache:entry:{url}"))
+        }
+    }
+
+    #[test]
+    fn fresh_responses_are_served_from_the_store() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell {
+            now: 1000,
+            ..Default::default()
+        };
+        shell.responses.push_back(
+            HttpResponse::ok()
+                .header("Cache-Control", "max-age=60")
+                .body("hello")
+                .build(),
+        );
+
+        shell.fetch(&app, &mut model, "https://example.com/data");
+        assert_eq!(model.body, "hello");
+        assert_eq!(shell.requests.len(), 1);
+
+        shell.now = 1059;
+        shell.fetch(&app, &mut model, "https://example.com/data");
+        assert_eq!(model.body, "hello");
+        assert_eq!(shell.requests.len(), 1);
+    }
+
+    #[test]
+    fn stale_responses_are_revalidated() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell {
+            now: 1000,
+            ..Default::default()
+        };
+        shell.responses.push_back(
+            HttpResponse::ok()
+                .header("Cache-Control", "max-age=60")
+                .header("ETag", "\"v1\"")
+                .body("hello")
+                .build(),
+        );
+        shell.responses.push_back(HttpResponse::status(304).build());
+
+        shell.fetch(&app, &mut model, "https://example.com/data");
+
+        shell.now = 1100;
+        shell.fetch(&app, &mut model, "https://example.com/data");
+        assert_eq!(model.body, "hello");
+        assert_eq!(shell.requests.len(), 2);
+        assert!(shell.requests[1]
+            .headers
+            .iter()
+            .any(|header| header.name == "if-none-match" && header.value == "\"v1\""));
+
+        // fresh again after revalidating
+        shell.now = 1150;
+        shell.fetch(&app, &mut model, "https://example.com/data");
+        assert_eq!(model.body, "hello");
+        assert_eq!(shell.requests.len(), 2);
+    }
+
+    #[test]
+    fn responses_marked_no_store_are_not_cached() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        for body in ["one", "two"] {
+            shell.responses.push_back(
+                HttpResponse::ok()
+                    .header("Cache-Control", "no-store, max-age=60")
+                    .body(body)
+                    .build(),
+            );
+        }
+
+        shell.fetch(&app, &mut model, "https://example.com/data");
+        shell.fetch(&app, &mut model, "https://example.com/data");
+
+        assert_eq!(model.body, "two");
+        assert_eq!(shell.requests.len(), 2);
+        assert!(!shell.is_cached("https://example.com/data"));
+    }
+
+    #[test]
+    fn least_recently_used_responses_are_evicted() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        for _ in 0..3 {
+            shell.responses.push_back(
+                HttpResponse::ok()
+                    .header("Cache-Control", "max-age=60")
+                    .body("x".repeat(200))
+                    .build(),
+            );
+        }
+
+        for (now, url) in ["a", "b", "a"].into_iter().enumerate() {
+            shell.now = now as u64;
+            shell.fetch(&app, &mut model, &format!("https://example.com/{url}"));
+        }
+        assert_eq!(shell.requests.len(), 2);
+
+        // only two responses fit in the cache
+        shell.now = 3;
+        shell.fetch(&app, &mut model, "https://example.com/c");
+
+        assert!(shell.is_cached("https://example.com/a"));
+        assert!(!shell.is_cached("https://example.com/b"));
+        assert!(shell.is_cached("https://example.com/c"));
+        assert_eq!(
+            shell.store["crux_http:cache:index"],
+            br#"{"crux_http:cache:entry:https://example.com/a":997,"crux_http:cache:entry:https://example.com/c":997}"#
+        );
+    }
+
+    #[test]
+    fn a_failed_lookup_falls_back_to_the_network() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        shell.store.insert(
+            "crux_http:cache:entry:https://example.com/data".to_string(),
+            b"not json".to_vec(),
+        );
+        shell
+            .responses
+            .push_back(HttpResponse::ok().body("hello").build());
+
+        shell.fetch(&app, &mut model, "https://example.com/data");
+
+        assert_eq!(model.body, "hello");
+        assert_eq!(shell.requests.len(), 1);
+    }
+
+    #[test]
+    fn fresh_responses_only_touch_their_entry() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        shell.responses.push_back(
+            HttpResponse::ok()
+                .header("Cache-Control", "max-age=60")
+                .body("hello")
+                .build(),
+        );
+
+        shell.fetch(&app, &mut model, "https://example.com/data");
+        shell.operations.clear();
+
+        shell.now = 10;
+        shell.fetch(&app, &mut model, "https://example.com/data");
+
+        let key = "crux_http:cache:entry:https://example.com/data".to_string();
+        assert!(matches!(
+            &shell.operations[..],
+            [KeyValueOperation::Get { key: get }, KeyValueOperation::Set { key: set, .. }]
+                if *get == key && *set == key
+        ));
+    }
+
+    #[test]
+    fn concurrent_responses_are_all_indexed() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        for _ in 0..3 {
+            shell.responses.push_back(
+                HttpResponse::ok()
+                    .header("Cache-Control", "max-age=60")
+                    .body("x".repeat(200))
+                    .build(),
+            );
+        }
+
+        let events = ["a", "b", "c"]
+            .map(|url| Event::Fetch(format!("https://example.com/{url}")))
+            .into();
+        shell.handle(&app, &mut model, events);
+
+        // only two responses fit in the cache, and the index knows which
+        let index: BTreeMap<String, usize> =
+            serde_json::from_slice(&shell.store["crux_http:cache:index"]).unwrap();
+        let entries: Vec<&String> = shell
+            .store
+            .keys()
+            .filter(|key| key.starts_with("crux_http:cache:entry:"))
+            .collect();
+        assert_eq!(index.keys().collect::<Vec<_>>(), entries);
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn responses_are_stored_for_each_value_of_the_headers_they_vary_by() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        for body in ["hello", "bonjour"] {
+            shell.responses.push_back(
+                HttpResponse::ok()
+                    .header("Cache-Control", "max-age=60")
+                    .header("Vary", "Accept-Language")
+                    .body(body)
+                    .build(),
+            );
+        }
+        let fetch = |language: &str| {
+            vec![Event::FetchWithHeader(
+                "https://example.com/greeting".to_string(),
+                "Accept-Language".to_string(),
+                language.to_string(),
+            )]
+        };
+
+        shell.handle(&app, &mut model, fetch("en"));
+        assert_eq!(model.body, "hello");
+        shell.handle(&app, &mut model, fetch("fr"));
+        assert_eq!(model.body, "bonjour");
+        assert_eq!(shell.requests.len(), 2);
+
+        shell.handle(&app, &mut model, fetch("en"));
+        assert_eq!(model.body, "hello");
+        shell.handle(&app, &mut model, fetch("fr"));
+        assert_eq!(model.body, "bonjour");
+        assert_eq!(shell.requests.len(), 2);
+    }
+
+    #[test]
+    fn requests_with_credentials_are_not_cached() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        for body in ["alice", "bob"] {
+            shell.responses.push_back(
+                HttpResponse::ok()
+                    .header("Cache-Control", "max-age=60")
+                    .body(body)
+                    .build(),
+            );
+        }
+        let fetch = |token: &str| {
+            vec![Event::FetchWithHeader(
+                "https://example.com/me".to_string(),
+                "Authorization".to_string(),
+                format!("Bearer {token}"),
+            )]
+        };
+
+        shell.handle(&app, &mut model, fetch("alice"));
+        shell.handle(&app, &mut model, fetch("bob"));
+
+        assert_eq!(model.body, "bob");
+        assert_eq!(shell.requests.len(), 2);
+        assert!(shell.store.is_empty());
+    }
+}
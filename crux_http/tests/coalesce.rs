@@ -0,0 +1,158 @@
+mod shared {
+    use crux_core::macros::Effect;
+    use crux_http::Http;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub(crate) struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        /// Two parts of the app asking for the same resource
+        GetTwice,
+        GetBoth,
+        PostTwice,
+        GetUncoalesced,
+
+        // events local to the core
+        #[serde(skip)]
+        Got(&'static str, crux_http::Result<crux_http::Response<String>>),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub bodies: Vec<(&'static str, String)>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            let get = |part: &'static str, url: &str| {
+                caps.http
+                    .get(url)
+                    .coalesce()
+                    .expect_string()
+                    .send(move |result| Event::Got(part, result));
+            };
+
+            match event {
+                Event::GetTwice => {
+                    get("header", "http://example.com/user");
+                    get("sidebar", "http://example.com/user");
+                }
+                Event::GetBoth => {
+                    get("header", "http://example.com/user");
+                    get("sidebar", "http://example.com/settings");
+                }
+                Event::PostTwice => {
+                    let http = caps.http.coalescing();
+                    for part in ["header", "sidebar"] {
+                        http.post("http://example.com/visits")
+                            .expect_string()
+                            .send(move |result| Event::Got(part, result));
+                    }
+                }
+                Event::GetUncoalesced => {
+                    for part in ["header", "sidebar"] {
+                        caps.http
+                            .get("http://example.com/user")
+                            .expect_string()
+                            .send(move |result| Event::Got(part, result));
+                    }
+                }
+                Event::Got(part, Ok(mut response)) => {
+                    model.bodies.push((part, response.take_body().unwrap()));
+                }
+                Event::Got(_, Err(error)) => panic!("error: {error:?}"),
+            }
+        }
+
+        fn view(&self, _model: &Self::Model) -> Self::ViewModel {}
+    }
+
+    #[derive(Effect)]
+    pub(crate) struct Capabilities {
+        pub http: Http<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::testing::AppTester;
+    use crux_http::protocol::{HttpResponse, HttpResult};
+
+    use crate::shared::{App, Effect, Event, Model};
+
+    fn ok(body: &str) -> HttpResult {
+        HttpResult::Ok(HttpResponse::ok().body(body).build())
+    }
+
+    #[test]
+    fn identical_gets_are_sent_once() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let mut request = app
+            .update(Event::GetTwice, &mut model)
+            .expect_one_effect()
+            .expect_http();
+
+        let update = app.resolve(&mut request, ok("alice")).unwrap();
+        for event in update.events {
+            let _ = app.update(event, &mut model);
+        }
+
+        assert_eq!(
+            model.bodies,
+            [
+                ("header", "alice".to_string()),
+                ("sidebar", "alice".to_string())
+            ]
+        );
+
+        // the response arrived, so the next request is sent again
+        let effects = app.update(Event::GetTwice, &mut model).effects;
+        assert_eq!(effects.len(), 1);
+    }
+
+    #[test]
+    fn different_gets_are_both_sent() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let requests: Vec<_> = app
+            .update(Event::GetBoth, &mut model)
+            .effects
+            .into_iter()
+            .map(Effect::expect_http)
+            .collect();
+
+        let urls: Vec<_> = requests.iter().map(|r| r.operation.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            ["http://example.com/user", "http://example.com/settings"]
+        );
+    }
+
+    #[test]
+    fn only_gets_and_heads_are_coalesced() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let effects = app.update(Event::PostTwice, &mut model).effects;
+        assert_eq!(effects.len(), 2);
+    }
+
+    #[test]
+    fn requests_are_not_coalesced_by_default() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let effects = app.update(Event::GetUncoalesced, &mut model).effects;
+        assert_eq!(effects.len(), 2);
+    }
+}
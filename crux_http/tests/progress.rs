@@ -0,0 +1,129 @@
+mod shared {
+    use crux_core::macros::Effect;
+    use crux_http::{protocol::HttpProgress, Http};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub(crate) struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Download,
+        Get,
+
+        // events local to the core
+        #[serde(skip)]
+        Progress(HttpProgress),
+        #[serde(skip)]
+        Downloaded(crux_http::Result<crux_http::Response<String>>),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub progress: Vec<(u64, Option<u64>)>,
+        pub body: Option<String>,
+        pub error: Option<crux_http::HttpError>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Download => caps
+                    .http
+                    .get("http://example.com/large.txt")
+                    .on_progress(Event::Progress)
+                    .expect_string()
+                    .send(Event::Downloaded),
+                Event::Get => caps
+                    .http
+                    .get("http://example.com/small.txt")
+                    .expect_string()
+                    .send(Event::Downloaded),
+                Event::Progress(progress) => model.progress.push((progress.bytes, progress.total)),
+                Event::Downloaded(Ok(mut response)) => model.body = response.take_body(),
+                Event::Downloaded(Err(error)) => model.error = Some(error),
+            }
+        }
+
+        fn view(&self, _model: &Self::Model) -> Self::ViewModel {}
+    }
+
+    #[derive(Effect)]
+    pub(crate) struct Capabilities {
+        pub http: Http<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::testing::AppTester;
+    use crux_http::protocol::{HttpProgress, HttpResponse, HttpResult, HttpTransfer};
+
+    use crate::shared::{App, Effect, Event, Model};
+
+    fn progress(bytes: u64) -> HttpResult {
+        HttpResult::Progress(HttpProgress {
+            transfer: HttpTransfer::Download,
+            bytes,
+            total: Some(10),
+        })
+    }
+
+    #[test]
+    fn progress_is_reported_before_the_response() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let mut request = app
+            .update(Event::Download, &mut model)
+            .expect_one_effect()
+            .expect_http();
+        assert!(request.operation.report_progress);
+
+        for bytes in [4, 8] {
+            let event = app
+                .resolve(&mut request, progress(bytes))
+                .unwrap()
+                .expect_one_event();
+            let _ = app.update(event, &mut model);
+        }
+        assert_eq!(model.progress, [(4, Some(10)), (8, Some(10))]);
+        assert_eq!(model.body, None);
+
+        let response = HttpResult::Ok(HttpResponse::ok().body("0123456789").build());
+        let event = app
+            .resolve(&mut request, response)
+            .unwrap()
+            .expect_one_event();
+        let _ = app.update(event, &mut model);
+
+        assert_eq!(model.body.as_deref(), Some("0123456789"));
+        assert_eq!(model.progress.len(), 2);
+    }
+
+    #[test]
+    fn requests_without_a_progress_callback_dont_ask_for_it() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let mut request = app
+            .update(Event::Get, &mut model)
+            .expect_one_effect()
+            .expect_http();
+        assert!(!request.operation.report_progress);
+
+        let event = app
+            .resolve(&mut request, progress(4))
+            .unwrap()
+            .expect_one_event();
+        let _ = app.update(event, &mut model);
+
+        assert!(model.progress.is_empty());
+        assert!(model.error.is_some());
+    }
+}
@@ -0,0 +1,202 @@
+#![cfg(feature = "retry")]
+
+mod shared {
+    use crux_core::macros::Effect;
+    use crux_http::{
+        middleware::{Attempts, Backoff, Retry},
+        Http,
+    };
+    use crux_time::{Duration, Time};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub(crate) struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Post(String),
+
+        // events local to the core
+        #[serde(skip)]
+        Posted(crux_http::Result<crux_http::Response<String>>),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub body: Option<String>,
+        pub attempts: Option<Attempts>,
+        pub error: Option<crux_http::HttpError>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Post(body) => caps
+                    .http
+                    .post("https://example.com/jobs")
+                    .body_string(body)
+                    .middleware(
+                        Retry::new(caps.time.clone())
+                            .backoff(Backoff::Schedule(vec![
+                                Duration::from_secs(1).unwrap(),
+                                Duration::from_secs(5).unwrap(),
+                            ]))
+                            .jitter(false),
+                    )
+                    .expect_string()
+                    .send(Event::Posted),
+                Event::Posted(Ok(mut response)) => {
+                    model.attempts = response.attempts().cloned();
+                    model.body = response.take_body();
+                }
+                Event::Posted(Err(error)) => model.error = Some(error),
+            }
+        }
+
+        fn view(&self, _model: &Self::Model) -> Self::ViewModel {}
+    }
+
+    #[derive(Effect)]
+    pub(crate) struct Capabilities {
+        pub http: Http<Event>,
+        pub time: Time<Event>,
+    }
+}
+
+mod tests {
+    use std::collections::VecDeque;
+
+    use crux_core::testing::AppTester;
+    use crux_http::{
+        protocol::{HttpRequest, HttpResponse, HttpResult},
+        HttpError,
+    };
+    use crux_time::{Duration, TimeRequest, TimeResponse};
+
+    use crate::shared::{App, Effect, Event, Model};
+
+    /// A shell with canned HTTP results, which fires timers straight away
+    #[derive(Default)]
+    struct Shell {
+        results: VecDeque<HttpResult>,
+        requests: Vec<HttpRequest>,
+        waited: Vec<Duration>,
+    }
+
+    impl Shell {
+        fn post(&mut self, app: &AppTester<App, Effect>, model: &mut Model, body: &str) {
+            let update = app.update(Event::Post(body.to_string()), model);
+            let mut effects: VecDeque<Effect> = update.effects.into();
+
+            while let Some(effect) = effects.pop_front() {
+                let update = match effect {
+                    Effect::Time(mut request) => {
+                        let TimeRequest::NotifyAfter { id, duration } = request.operation else {
+                            panic!("unexpected operation {:?}", request.operation);
+                        };
+                        self.waited.push(duration);
+                        app.resolve(&mut request, TimeResponse::DurationElapsed { id })
+                    }
+                    Effect::Http(mut request) => {
+                        self.requests.push(request.operation.clone());
+                        let result = self.results.pop_front().expect("a result");
+                        app.resolve(&mut request, result)
+                    }
+                }
+                .unwrap();
+
+                effects.extend(update.effects);
+                for event in update.events {
+                    effects.extend(app.update(event, model).effects);
+                }
+            }
+        }
+    }
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds).unwrap()
+    }
+
+    #[test]
+    fn transient_failures_are_retried() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        shell.results.extend([
+            HttpResult::Err(HttpError::Timeout),
+            HttpResult::Ok(HttpResponse::status(503).build()),
+            HttpResult::Ok(HttpResponse::ok().body("done").build()),
+        ]);
+
+        shell.post(&app, &mut model, "job");
+
+        assert_eq!(model.body.as_deref(), Some("done"));
+        assert_eq!(shell.waited, [secs(1), secs(5)]);
+
+        let attempts = model.attempts.unwrap();
+        assert_eq!(attempts.count, 3);
+        assert_eq!(attempts.delays, [secs(1), secs(5)]);
+
+        // the body is sent with every attempt
+        assert!(shell.requests.iter().all(|request| request.body == b"job"));
+    }
+
+    #[test]
+    fn gives_up_after_the_last_attempt() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        shell.results.extend([
+            HttpResult::Err(HttpError::Io("reset".to_string())),
+            HttpResult::Err(HttpError::Io("reset".to_string())),
+            HttpResult::Err(HttpError::Io("reset".to_string())),
+        ]);
+
+        shell.post(&app, &mut model, "job");
+
+        assert_eq!(model.error, Some(HttpError::Io("reset".to_string())));
+        assert_eq!(shell.requests.len(), 3);
+    }
+
+    #[test]
+    fn retry_after_overrides_the_backoff() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        shell.results.extend([
+            HttpResult::Ok(
+                HttpResponse::status(429)
+                    .header("Retry-After", "30")
+                    .build(),
+            ),
+            HttpResult::Ok(HttpResponse::ok().body("done").build()),
+        ]);
+
+        shell.post(&app, &mut model, "job");
+
+        assert_eq!(model.body.as_deref(), Some("done"));
+        assert_eq!(shell.waited, [secs(30)]);
+    }
+
+    #[test]
+    fn other_failures_are_not_retried() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+        let mut shell = Shell::default();
+        shell
+            .results
+            .push_back(HttpResult::Ok(HttpResponse::status(404).build()));
+
+        shell.post(&app, &mut model, "job");
+
+        assert!(matches!(model.error, Some(HttpError::Http { .. })));
+        assert_eq!(shell.requests.len(), 1);
+        assert!(shell.waited.is_empty());
+    }
+}
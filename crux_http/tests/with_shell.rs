@@ -1,3 +1,4 @@
+#[allow(clippy::useless_borrows_in_formatting)]
 mod shared {
     use crux_core::macros::Effect;
     use crux_core::render::Render;
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Error type for I18n operations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Error)]
+#[serde(rename_all = "camelCase")]
+pub enum I18nError {
+    #[error("missing translation for key: {key}")]
+    MissingKey { key: String },
+    #[error("invalid argument: {message}")]
+    InvalidArgument { message: String },
+    #[error("other error: {message}")]
+    Other { message: String },
+}
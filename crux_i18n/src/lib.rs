@@ -0,0 +1,330 @@
+//! Localization for Crux apps
+//!
+//! `crux_i18n` allows Crux apps to produce localized text for their view models by asking the
+//! Shell to look up translations, select plural forms and format numbers using the platform's
+//! locale rules. The Core only deals with message keys and arguments and never needs to embed
+//! locale data.
+
+pub mod error;
+
+use serde::{Deserialize, Serialize};
+
+use crux_core::capability::{CapabilityContext, Operation};
+
+use error::I18nError;
+
+/// A named argument to be interpolated into a localized message
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct I18nArgument {
+    pub name: String,
+    pub value: I18nValue,
+}
+
+impl I18nArgument {
+    pub fn new(name: impl Into<String>, value: impl Into<I18nValue>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// The value of an [`I18nArgument`]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum I18nValue {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+}
+
+impl From<String> for I18nValue {
+    fn from(value: String) -> Self {
+        I18nValue::Text(value)
+    }
+}
+
+impl From<&str> for I18nValue {
+    fn from(value: &str) -> Self {
+        I18nValue::Text(value.to_string())
+    }
+}
+
+impl From<i64> for I18nValue {
+    fn from(value: i64) -> Self {
+        I18nValue::Integer(value)
+    }
+}
+
+impl From<f64> for I18nValue {
+    fn from(value: f64) -> Self {
+        I18nValue::Float(value)
+    }
+}
+
+/// How a number should be formatted by [`I18nOperation::FormatNumber`]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum NumberStyle {
+    /// Plain decimal number with locale grouping and decimal separators
+    Decimal,
+    /// A fraction formatted as a percentage, e.g. 0.25 as "25 %"
+    Percent,
+    /// A monetary amount in the currency with the given ISO 4217 code
+    Currency { code: String },
+}
+
+/// Supported operations
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum I18nOperation {
+    /// Look up the message for a key, interpolating the arguments
+    Translate {
+        key: String,
+        arguments: Vec<I18nArgument>,
+    },
+    /// Look up the plural form of the message for a key matching `count`,
+    /// interpolating the arguments
+    Plural {
+        key: String,
+        count: i64,
+        arguments: Vec<I18nArgument>,
+    },
+    /// Format a number according to the current locale
+    FormatNumber { value: f64, style: NumberStyle },
+    /// Get the identifier of the current locale (e.g. "en-GB")
+    Locale,
+}
+
+/// The result of an operation on the shell's localization facilities.
+///
+/// Note: we can't use `Result` here because generics are not currently
+/// supported across the FFI boundary, when using the builtin typegen.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum I18nResult {
+    Ok { response: I18nResponse },
+    Err { error: I18nError },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum I18nResponse {
+    /// Response to `I18nOperation::Translate`, `I18nOperation::Plural` and
+    /// `I18nOperation::FormatNumber`, carrying the localized text
+    Text { text: String },
+    /// Response to `I18nOperation::Locale`, carrying the locale identifier
+    Locale { locale: String },
+}
+
+impl Operation for I18nOperation {
+    type Output = I18nResult;
+}
+
+pub struct I18n<Ev> {
+    context: CapabilityContext<I18nOperation, Ev>,
+}
+
+impl<Ev> crux_core::Capability<Ev> for I18n<Ev> {
+    type Operation = I18nOperation;
+
+    type MappedSelf<MappedEv> = I18n<MappedEv>;
+
+    fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+    where
+        F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+        Ev: 'static,
+        NewEv: 'static + Send,
+    {
+        I18n::new(self.context.map_event(f))
+    }
+
+    #[cfg(feature = "typegen")]
+    fn register_types(generator: &mut crux_core::typegen::TypeGen) -> crux_core::typegen::Result {
+        generator.register_type::<I18nValue>()?;
+        generator.register_type::<NumberStyle>()?;
+        generator.register_type::<I18nResponse>()?;
+        generator.register_type::<I18nError>()?;
+        generator.register_type::<Self::Operation>()?;
+        generator.register_type::<<Self::Operation as Operation>::Output>()?;
+        Ok(())
+    }
+}
+
+impl<Ev> Clone for I18n<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+        }
+    }
+}
+
+impl<Ev> I18n<Ev>
+where
+    Ev: 'static,
+{
+    pub fn new(context: CapabilityContext<I18nOperation, Ev>) -> Self {
+        Self { context }
+    }
+
+    /// Ask the shell for the message stored under `key`, with the `arguments` interpolated.
+    /// Will dispatch the event with the localized text as payload.
+    pub fn translate<F>(&self, key: impl Into<String>, arguments: Vec<I18nArgument>, make_event: F)
+    where
+        F: FnOnce(Result<String, I18nError>) -> Ev + Send + Sync + 'static,
+    {
+        let operation = I18nOperation::Translate {
+            key: key.into(),
+            arguments,
+        };
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = text(&context, operation).await;
+                context.update_app(make_event(response));
+            }
+        });
+    }
+
+    /// Ask the shell for the message stored under `key`, while in an async context.
+    /// This is used together with [`crux_core::compose::Compose`].
+    pub async fn translate_async(
+        &self,
+        key: impl Into<String>,
+        arguments: Vec<I18nArgument>,
+    ) -> Result<String, I18nError> {
+        let operation = I18nOperation::Translate {
+            key: key.into(),
+            arguments,
+        };
+        text(&self.context, operation).await
+    }
+
+    /// Ask the shell for the plural form of the message stored under `key` which matches
+    /// `count` in the current locale, with the `arguments` interpolated.
+    /// Will dispatch the event with the localized text as payload.
+    pub fn plural<F>(
+        &self,
+        key: impl Into<String>,
+        count: i64,
+        arguments: Vec<I18nArgument>,
+        make_event: F,
+    ) where
+        F: FnOnce(Result<String, I18nError>) -> Ev + Send + Sync + 'static,
+    {
+        let operation = I18nOperation::Plural {
+            key: key.into(),
+            count,
+            arguments,
+        };
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = text(&context, operation).await;
+                context.update_app(make_event(response));
+            }
+        });
+    }
+
+    /// Ask the shell for the plural form of the message stored under `key`, while in an
+    /// async context. This is used together with [`crux_core::compose::Compose`].
+    pub async fn plural_async(
+        &self,
+        key: impl Into<String>,
+        count: i64,
+        arguments: Vec<I18nArgument>,
+    ) -> Result<String, I18nError> {
+        let operation = I18nOperation::Plural {
+            key: key.into(),
+            count,
+            arguments,
+        };
+        text(&self.context, operation).await
+    }
+
+    /// Ask the shell to format `value` in the given `style` using the current locale.
+    /// Will dispatch the event with the formatted number as payload.
+    pub fn format_number<F>(&self, value: f64, style: NumberStyle, make_event: F)
+    where
+        F: FnOnce(Result<String, I18nError>) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = text(&context, I18nOperation::FormatNumber { value, style }).await;
+                context.update_app(make_event(response));
+            }
+        });
+    }
+
+    /// Ask the shell to format `value` in the given `style`, while in an async context.
+    /// This is used together with [`crux_core::compose::Compose`].
+    pub async fn format_number_async(
+        &self,
+        value: f64,
+        style: NumberStyle,
+    ) -> Result<String, I18nError> {
+        text(&self.context, I18nOperation::FormatNumber { value, style }).await
+    }
+
+    /// Ask the shell for the identifier of the current locale.
+    /// Will dispatch the event with the locale identifier (e.g. "en-GB") as payload.
+    pub fn locale<F>(&self, make_event: F)
+    where
+        F: FnOnce(Result<String, I18nError>) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = locale(&context).await;
+                context.update_app(make_event(response));
+            }
+        });
+    }
+
+    /// Ask the shell for the identifier of the current locale, while in an async context.
+    /// This is used together with [`crux_core::compose::Compose`].
+    pub async fn locale_async(&self) -> Result<String, I18nError> {
+        locale(&self.context).await
+    }
+}
+
+async fn text<Ev: 'static>(
+    context: &CapabilityContext<I18nOperation, Ev>,
+    operation: I18nOperation,
+) -> Result<String, I18nError> {
+    context.request_from_shell(operation).await.unwrap_text()
+}
+
+async fn locale<Ev: 'static>(
+    context: &CapabilityContext<I18nOperation, Ev>,
+) -> Result<String, I18nError> {
+    context
+        .request_from_shell(I18nOperation::Locale)
+        .await
+        .unwrap_locale()
+}
+
+impl I18nResult {
+    fn unwrap_text(self) -> Result<String, I18nError> {
+        match self {
+            I18nResult::Ok { response } => match response {
+                I18nResponse::Text { text } => Ok(text),
+                I18nResponse::Locale { .. } => {
+                    panic!("attempt to convert I18nResponse other than Text to String")
+                }
+            },
+            I18nResult::Err { error } => Err(error),
+        }
+    }
+
+    fn unwrap_locale(self) -> Result<String, I18nError> {
+        match self {
+            I18nResult::Ok { response } => match response {
+                I18nResponse::Locale { locale } => Ok(locale),
+                I18nResponse::Text { .. } => {
+                    panic!("attempt to convert I18nResponse other than Locale to String")
+                }
+            },
+            I18nResult::Err { error } => Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,231 @@
+use crux_core::{macros::Effect, render::Render, testing::AppTester};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::I18nError, I18n, I18nArgument, I18nOperation, I18nResponse, I18nResult, I18nValue,
+    NumberStyle,
+};
+
+#[derive(Default)]
+pub struct App;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Event {
+    Greet,
+    CountItems(i64),
+    FormatTotal(f64),
+    GetLocale,
+
+    Text(Result<String, I18nError>),
+    Locale(Result<String, I18nError>),
+}
+
+#[derive(Debug, Default)]
+pub struct Model {
+    pub text: String,
+    pub locale: String,
+    pub error: Option<I18nError>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ViewModel {
+    pub text: String,
+}
+
+impl crux_core::App for App {
+    type Event = Event;
+    type Model = Model;
+    type ViewModel = ViewModel;
+
+    type Capabilities = Capabilities;
+
+    fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+        match event {
+            Event::Greet => caps.i18n.translate(
+                "greeting",
+                vec![I18nArgument::new("name", "Ferris")],
+                Event::Text,
+            ),
+            Event::CountItems(count) => caps.i18n.plural("items", count, vec![], Event::Text),
+            Event::FormatTotal(total) => caps.i18n.format_number(
+                total,
+                NumberStyle::Currency {
+                    code: "GBP".to_string(),
+                },
+                Event::Text,
+            ),
+            Event::GetLocale => caps.i18n.locale(Event::Locale),
+            Event::Text(Ok(text)) => {
+                model.text = text;
+                caps.render.render();
+            }
+            Event::Locale(Ok(locale)) => model.locale = locale,
+            Event::Text(Err(error)) | Event::Locale(Err(error)) => model.error = Some(error),
+        }
+    }
+
+    fn view(&self, model: &Self::Model) -> Self::ViewModel {
+        ViewModel {
+            text: model.text.clone(),
+        }
+    }
+}
+
+#[derive(Effect)]
+pub struct Capabilities {
+    pub i18n: I18n<Event>,
+    pub render: Render<Event>,
+}
+
+#[test]
+fn test_translate() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Greet, &mut model)
+        .expect_one_effect()
+        .expect_i18n();
+
+    assert_eq!(
+        request.operation,
+        I18nOperation::Translate {
+            key: "greeting".to_string(),
+            arguments: vec![I18nArgument {
+                name: "name".to_string(),
+                value: I18nValue::Text("Ferris".to_string()),
+            }],
+        }
+    );
+
+    let mut update = app.resolve_to_event_then_update(
+        request,
+        I18nResult::Ok {
+            response: I18nResponse::Text {
+                text: "Hello, Ferris!".to_string(),
+            },
+        },
+        &mut model,
+    );
+
+    assert!(update.effects_mut().next().unwrap().is_render());
+    assert_eq!(app.view(&model).text, "Hello, Ferris!");
+}
+
+#[test]
+fn test_plural() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::CountItems(3), &mut model)
+        .expect_one_effect()
+        .expect_i18n();
+
+    assert_eq!(
+        request.operation,
+        I18nOperation::Plural {
+            key: "items".to_string(),
+            count: 3,
+            arguments: vec![],
+        }
+    );
+
+    let _update = app.resolve_to_event_then_update(
+        request,
+        I18nResult::Ok {
+            response: I18nResponse::Text {
+                text: "3 items".to_string(),
+            },
+        },
+        &mut model,
+    );
+
+    assert_eq!(model.text, "3 items");
+}
+
+#[test]
+fn test_format_number() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::FormatTotal(1234.5), &mut model)
+        .expect_one_effect()
+        .expect_i18n();
+
+    assert_eq!(
+        request.operation,
+        I18nOperation::FormatNumber {
+            value: 1234.5,
+            style: NumberStyle::Currency {
+                code: "GBP".to_string()
+            },
+        }
+    );
+
+    let _update = app.resolve_to_event_then_update(
+        request,
+        I18nResult::Ok {
+            response: I18nResponse::Text {
+                text: "£1,234.50".to_string(),
+            },
+        },
+        &mut model,
+    );
+
+    assert_eq!(model.text, "£1,234.50");
+}
+
+#[test]
+fn test_locale() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::GetLocale, &mut model)
+        .expect_one_effect()
+        .expect_i18n();
+
+    assert_eq!(request.operation, I18nOperation::Locale);
+
+    let _update = app.resolve_to_event_then_update(
+        request,
+        I18nResult::Ok {
+            response: I18nResponse::Locale {
+                locale: "en-GB".to_string(),
+            },
+        },
+        &mut model,
+    );
+
+    assert_eq!(model.locale, "en-GB");
+}
+
+#[test]
+fn test_missing_key() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Greet, &mut model)
+        .expect_one_effect()
+        .expect_i18n();
+
+    let _update = app.resolve_to_event_then_update(
+        request,
+        I18nResult::Err {
+            error: I18nError::MissingKey {
+                key: "greeting".to_string(),
+            },
+        },
+        &mut model,
+    );
+
+    assert_eq!(
+        model.error,
+        Some(I18nError::MissingKey {
+            key: "greeting".to_string()
+        })
+    );
+}
@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::value::Value;
+
 /// Error type for KeyValue operations
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Error)]
 #[serde(rename_all = "camelCase")]
 pub enum KeyValueError {
+    /// The store could not be read or written. For a batch, none of the writes were applied.
     #[error("IO error: {message}")]
     Io { message: String },
     #[error("timeout")]
@@ -13,4 +16,7 @@ pub enum KeyValueError {
     CursorNotFound,
     #[error("other error: {message}")]
     Other { message: String },
+    /// A compare-and-swap found a different value under the key than the expected one
+    #[error("conflict: the key does not hold the expected value")]
+    Conflict { current: Value },
 }
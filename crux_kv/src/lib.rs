@@ -6,6 +6,7 @@
 pub mod error;
 pub mod value;
 
+use futures::{future, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 
 use crux_core::capability::{CapabilityContext, Operation};
@@ -41,27 +42,82 @@ pub enum KeyValueOperation {
         /// a `KeyValueError::CursorNotFound` error.
         cursor: u64,
     },
+    /// Apply all the writes together, or none of them if any fails
+    Batch { writes: Vec<Write> },
+    /// Write `new` under a key, but only if the key currently holds `expected`.
+    /// A `Value::None` for `expected` means the key must not exist, and for `new`
+    /// that the key should be removed.
+    CompareAndSwap {
+        key: String,
+        expected: Value,
+        new: Value,
+    },
+    /// Watch the keys starting with a prefix for changes made outside the app, e.g. by the
+    /// Shell or another process like a widget or an extension, until the watch is stopped
+    /// with `Unwatch`. The Shell responds with a `KeyValueResponse::Changed` for every
+    /// change, and a final `KeyValueResponse::Unwatched`. The app's own writes aren't
+    /// reported.
+    Watch {
+        id: WatchId,
+        /// The prefix of the keys to watch, or an empty string to watch all keys
+        prefix: String,
+    },
+    /// Stop the watch with the id, which the Shell confirms with a
+    /// `KeyValueResponse::Unwatched` in response to the `Watch`
+    Unwatch { id: WatchId },
+}
+
+/// The id of a [`KeyValueOperation::Watch`], to stop it with
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchId(pub u64);
+
+/// A single write in a `KeyValueOperation::Batch`
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Write {
+    /// Write bytes under a key
+    Set {
+        key: String,
+        #[serde(with = "serde_bytes")]
+        value: Vec<u8>,
+    },
+    /// Remove a key and its value
+    Delete { key: String },
+}
+
+impl std::fmt::Debug for Write {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Write::Set { key, value } => f
+                .debug_struct("Set")
+                .field("key", key)
+                .field("value", &format_args!("{}", bytes_repr(value)))
+                .finish(),
+            Write::Delete { key } => f.debug_struct("Delete").field("key", key).finish(),
+        }
+    }
+}
+
+fn bytes_repr(value: &[u8]) -> String {
+    if let Ok(s) = std::str::from_utf8(value) {
+        if s.len() < 50 {
+            format!("\"{s}\"")
+        } else {
+            format!("\"{}\"...", s.chars().take(50).collect::<String>())
+        }
+    } else {
+        format!("<binary data - {} bytes>", value.len())
+    }
 }
 
 impl std::fmt::Debug for KeyValueOperation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             KeyValueOperation::Get { key } => f.debug_struct("Get").field("key", key).finish(),
-            KeyValueOperation::Set { key, value } => {
-                let body_repr = if let Ok(s) = std::str::from_utf8(value) {
-                    if s.len() < 50 {
-                        format!("\"{s}\"")
-                    } else {
-                        format!("\"{}\"...", s.chars().take(50).collect::<String>())
-                    }
-                } else {
-                    format!("<binary data - {} bytes>", value.len())
-                };
-                f.debug_struct("Set")
-                    .field("key", key)
-                    .field("value", &format_args!("{}", body_repr))
-                    .finish()
-            }
+            KeyValueOperation::Set { key, value } => f
+                .debug_struct("Set")
+                .field("key", key)
+                .field("value", &format_args!("{}", bytes_repr(value)))
+                .finish(),
             KeyValueOperation::Delete { key } => {
                 f.debug_struct("Delete").field("key", key).finish()
             }
@@ -73,6 +129,21 @@ impl std::fmt::Debug for KeyValueOperation {
                 .field("prefix", prefix)
                 .field("cursor", cursor)
                 .finish(),
+            KeyValueOperation::Batch { writes } => {
+                f.debug_struct("Batch").field("writes", writes).finish()
+            }
+            KeyValueOperation::CompareAndSwap { key, expected, new } => f
+                .debug_struct("CompareAndSwap")
+                .field("key", key)
+                .field("expected", expected)
+                .field("new", new)
+                .finish(),
+            KeyValueOperation::Watch { id, prefix } => f
+                .debug_struct("Watch")
+                .field("id", id)
+                .field("prefix", prefix)
+                .finish(),
+            KeyValueOperation::Unwatch { id } => f.debug_struct("Unwatch").field("id", id).finish(),
         }
     }
 }
@@ -113,6 +184,22 @@ pub enum KeyValueResponse {
         /// include a `KeyValueError::CursorNotFound` error.
         next_cursor: u64,
     },
+    /// Response to a `KeyValueOperation::Batch`, once all the writes have been applied.
+    /// If any of them failed, none should have been applied, and the result should instead
+    /// be an error.
+    Batch,
+    /// Response to a `KeyValueOperation::CompareAndSwap`, once the new value has been written.
+    /// If the key didn't hold the expected value, the result should instead be a
+    /// `KeyValueError::Conflict` error.
+    CompareAndSwap,
+    /// Response to a `KeyValueOperation::Watch`, for every change to a key under its prefix,
+    /// returning the key and its new value, which is empty if the key was removed.
+    /// If the Shell can't watch the keys, it should respond with an error instead, which
+    /// ends the watch.
+    Changed { key: String, value: Value },
+    /// The last response to a `KeyValueOperation::Watch`, once it has been stopped with
+    /// `KeyValueOperation::Unwatch`
+    Unwatched,
 }
 
 impl Operation for KeyValueOperation {
@@ -142,6 +229,8 @@ impl<Ev> crux_core::Capability<Ev> for KeyValue<Ev> {
         generator.register_type::<KeyValueResponse>()?;
         generator.register_type::<KeyValueError>()?;
         generator.register_type::<Value>()?;
+        generator.register_type::<Write>()?;
+        generator.register_type::<WatchId>()?;
         generator.register_type::<Self::Operation>()?;
         generator.register_type::<<Self::Operation as Operation>::Output>()?;
         Ok(())
@@ -303,6 +392,130 @@ where
     ) -> Result<(Vec<String>, u64), KeyValueError> {
         list_keys(&self.context, prefix, cursor).await
     }
+
+    /// Apply all the `writes` together, will dispatch the event with `Ok(())` once they
+    /// have all been applied. If any of them fails, none are applied.
+    pub fn batch<F>(&self, writes: Vec<Write>, make_event: F)
+    where
+        F: FnOnce(Result<(), KeyValueError>) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = batch(&context, writes).await;
+                context.update_app(make_event(response))
+            }
+        });
+    }
+
+    /// Apply all the `writes` together, while in an async context. This is used together
+    /// with [`crux_core::compose::Compose`].
+    ///
+    /// If any of the writes fails, none are applied.
+    pub async fn batch_async(&self, writes: Vec<Write>) -> Result<(), KeyValueError> {
+        batch(&self.context, writes).await
+    }
+
+    /// Write `new` under `key` if it currently holds `expected`, will dispatch the event
+    /// with `Ok(())` if the value was written.
+    ///
+    /// `None` as `expected` means the key must not exist, and as `new` that the key
+    /// should be removed. If the key holds something else, the result is a
+    /// `KeyValueError::Conflict` error with the current value.
+    pub fn compare_and_swap<F>(
+        &self,
+        key: String,
+        expected: Option<Vec<u8>>,
+        new: Option<Vec<u8>>,
+        make_event: F,
+    ) where
+        F: FnOnce(Result<(), KeyValueError>) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = compare_and_swap(&context, key, expected, new).await;
+                context.update_app(make_event(response))
+            }
+        });
+    }
+
+    /// Write `new` under `key` if it currently holds `expected`, while in an async context.
+    /// This is used together with [`crux_core::compose::Compose`].
+    ///
+    /// `None` as `expected` means the key must not exist, and as `new` that the key
+    /// should be removed. If the key holds something else, the result is a
+    /// `KeyValueError::Conflict` error with the current value.
+    pub async fn compare_and_swap_async(
+        &self,
+        key: String,
+        expected: Option<Vec<u8>>,
+        new: Option<Vec<u8>>,
+    ) -> Result<(), KeyValueError> {
+        compare_and_swap(&self.context, key, expected, new).await
+    }
+
+    /// Watch the keys starting with `prefix` for changes made outside the app, e.g. by
+    /// another process like a widget or an extension, so the app can stay in sync with
+    /// them. Every change is passed to the app as the key and its new value, which is
+    /// `None` if the key was removed, wrapped in the event produced by `make_event`.
+    ///
+    /// Passing the returned [`WatchId`] to [`KeyValue::unwatch`] stops the watch, after
+    /// which no more events are dispatched. If the Shell can't watch the keys, the error
+    /// is dispatched instead, and the watch ends.
+    pub fn watch<F>(&self, prefix: String, make_event: F) -> WatchId
+    where
+        F: Fn(Result<(String, Option<Vec<u8>>), KeyValueError>) -> Ev + Send + Sync + 'static,
+    {
+        let id = WatchId(self.context.next_id().to_u64());
+        self.context.spawn({
+            let context = self.context.clone();
+            let mut changes = self.watch_async(id, prefix);
+
+            async move {
+                while let Some(change) = changes.next().await {
+                    context.update_app(make_event(change));
+                }
+            }
+        });
+
+        id
+    }
+
+    /// Watch the keys starting with `prefix` for changes made outside the app, see
+    /// [`KeyValue::watch`]. This is an async call to use with [`crux_core::compose::Compose`].
+    ///
+    /// The stream ends once the watch is stopped with [`KeyValue::unwatch`], or after an error.
+    pub fn watch_async(
+        &self,
+        id: WatchId,
+        prefix: String,
+    ) -> impl Stream<Item = Result<(String, Option<Vec<u8>>), KeyValueError>> {
+        self.context
+            .stream_from_shell(KeyValueOperation::Watch { id, prefix })
+            .scan(false, |failed, result| {
+                if *failed {
+                    return future::ready(None);
+                }
+
+                let change = result.unwrap_changed();
+                *failed = matches!(change, Some(Err(_)));
+                future::ready(change)
+            })
+    }
+
+    /// Stop the watch with the `id`, returned by [`KeyValue::watch`].
+    pub fn unwatch(&self, id: WatchId) {
+        self.context.spawn({
+            let context = self.context.clone();
+
+            async move {
+                context
+                    .notify_shell(KeyValueOperation::Unwatch { id })
+                    .await;
+            }
+        });
+    }
 }
 
 async fn get<Ev: 'static>(
@@ -357,6 +570,32 @@ async fn list_keys<Ev: 'static>(
         .unwrap_list_keys()
 }
 
+async fn batch<Ev: 'static>(
+    context: &CapabilityContext<KeyValueOperation, Ev>,
+    writes: Vec<Write>,
+) -> Result<(), KeyValueError> {
+    context
+        .request_from_shell(KeyValueOperation::Batch { writes })
+        .await
+        .unwrap_batch()
+}
+
+async fn compare_and_swap<Ev: 'static>(
+    context: &CapabilityContext<KeyValueOperation, Ev>,
+    key: String,
+    expected: Option<Vec<u8>>,
+    new: Option<Vec<u8>>,
+) -> Result<(), KeyValueError> {
+    context
+        .request_from_shell(KeyValueOperation::CompareAndSwap {
+            key,
+            expected: expected.into(),
+            new: new.into(),
+        })
+        .await
+        .unwrap_compare_and_swap()
+}
+
 impl KeyValueResult {
     fn unwrap_get(self) -> Result<Option<Vec<u8>>, KeyValueError> {
         match self {
@@ -418,6 +657,41 @@ impl KeyValueResult {
             KeyValueResult::Err { error } => Err(error.clone()),
         }
     }
+
+    fn unwrap_batch(self) -> Result<(), KeyValueError> {
+        match self {
+            KeyValueResult::Ok { response } => match response {
+                KeyValueResponse::Batch => Ok(()),
+                _ => panic!("attempt to convert KeyValueResponse other than Batch to ()"),
+            },
+            KeyValueResult::Err { error } => Err(error.clone()),
+        }
+    }
+
+    fn unwrap_compare_and_swap(self) -> Result<(), KeyValueError> {
+        match self {
+            KeyValueResult::Ok { response } => match response {
+                KeyValueResponse::CompareAndSwap => Ok(()),
+                _ => panic!("attempt to convert KeyValueResponse other than CompareAndSwap to ()"),
+            },
+            KeyValueResult::Err { error } => Err(error.clone()),
+        }
+    }
+
+    /// The change, or `None` once the watch has been stopped
+    #[allow(clippy::type_complexity)]
+    fn unwrap_changed(self) -> Option<Result<(String, Option<Vec<u8>>), KeyValueError>> {
+        match self {
+            KeyValueResult::Ok { response } => match response {
+                KeyValueResponse::Changed { key, value } => Some(Ok((key, value.into()))),
+                KeyValueResponse::Unwatched => None,
+                _ => panic!(
+                    "attempt to convert KeyValueResponse other than Changed or Unwatched to a change"
+                ),
+            },
+            KeyValueResult::Err { error } => Some(Err(error)),
+        }
+    }
 }
 
 #[cfg(test)]
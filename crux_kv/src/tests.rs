@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     error::KeyValueError, value::Value, KeyValue, KeyValueOperation, KeyValueResponse,
-    KeyValueResult,
+    KeyValueResult, WatchId, Write,
 };
 
 #[derive(Default)]
@@ -18,11 +18,17 @@ pub enum Event {
     Exists,
     ListKeys,
     GetThenSet,
+    Batch,
+    CompareAndSwap,
+    Watch,
+    Unwatch,
 
     GetResponse(Result<Option<Vec<u8>>, KeyValueError>),
     SetResponse(Result<Option<Vec<u8>>, KeyValueError>),
     ExistsResponse(Result<bool, KeyValueError>),
     ListKeysResponse(Result<(Vec<String>, u64), KeyValueError>),
+    WriteResponse(Result<(), KeyValueError>),
+    Changed(Result<(String, Option<Vec<u8>>), KeyValueError>),
 }
 
 #[derive(Debug, Default)]
@@ -31,6 +37,9 @@ pub struct Model {
     pub keys: Vec<String>,
     pub cursor: u64,
     pub successful: bool,
+    pub error: Option<KeyValueError>,
+    pub watch: Option<WatchId>,
+    pub changes: Vec<(String, Option<Vec<u8>>)>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -77,6 +86,34 @@ impl crux_core::App for App {
                 }
             }),
 
+            Event::Batch => caps.key_value.batch(
+                vec![
+                    Write::Set {
+                        key: "test:1".to_string(),
+                        value: 42i32.to_ne_bytes().to_vec(),
+                    },
+                    Write::Delete {
+                        key: "test:2".to_string(),
+                    },
+                ],
+                Event::WriteResponse,
+            ),
+            Event::CompareAndSwap => caps.key_value.compare_and_swap(
+                key,
+                Some(41i32.to_ne_bytes().to_vec()),
+                Some(42i32.to_ne_bytes().to_vec()),
+                Event::WriteResponse,
+            ),
+
+            Event::Watch => {
+                model.watch = Some(caps.key_value.watch("test:".to_string(), Event::Changed));
+            }
+            Event::Unwatch => {
+                if let Some(id) = model.watch.take() {
+                    caps.key_value.unwatch(id);
+                }
+            }
+
             Event::GetResponse(Ok(Some(value))) => {
                 let (int_bytes, _rest) = value.split_at(std::mem::size_of::<i32>());
                 model.value = i32::from_ne_bytes(int_bytes.try_into().unwrap());
@@ -102,6 +139,11 @@ impl crux_core::App for App {
                 caps.render.render()
             }
 
+            Event::WriteResponse(Ok(())) => {
+                model.successful = true;
+                caps.render.render()
+            }
+
             Event::GetResponse(Err(error)) => {
                 panic!("error: {:?}", error);
             }
@@ -114,6 +156,14 @@ impl crux_core::App for App {
             Event::ListKeysResponse(Err(error)) => {
                 panic!("Error: {:?}", error);
             }
+            Event::WriteResponse(Err(error)) => {
+                model.error = Some(error);
+            }
+            Event::Changed(Ok(change)) => model.changes.push(change),
+            Event::Changed(Err(error)) => {
+                model.watch = None;
+                model.error = Some(error);
+            }
         }
     }
 
@@ -163,6 +213,7 @@ fn test_get() {
 }
 
 #[test]
+#[allow(clippy::useless_conversion)]
 fn test_set() {
     let app = AppTester::<App, _>::default();
     let mut model = Model::default();
@@ -285,6 +336,209 @@ fn test_list_keys() {
 }
 
 #[test]
+fn test_batch() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Batch, &mut model)
+        .expect_one_effect()
+        .expect_key_value();
+
+    assert_eq!(
+        request.operation,
+        KeyValueOperation::Batch {
+            writes: vec![
+                Write::Set {
+                    key: "test:1".to_string(),
+                    value: 42i32.to_ne_bytes().to_vec(),
+                },
+                Write::Delete {
+                    key: "test:2".to_string(),
+                },
+            ]
+        }
+    );
+
+    let _updated = app.resolve_to_event_then_update(
+        request,
+        KeyValueResult::Ok {
+            response: KeyValueResponse::Batch,
+        },
+        &mut model,
+    );
+
+    assert!(model.successful);
+}
+
+#[test]
+fn test_batch_failed() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Batch, &mut model)
+        .expect_one_effect()
+        .expect_key_value();
+
+    let error = KeyValueError::Io {
+        message: "disk full".to_string(),
+    };
+    let _updated = app.resolve_to_event_then_update(
+        request,
+        KeyValueResult::Err {
+            error: error.clone(),
+        },
+        &mut model,
+    );
+
+    assert!(!model.successful);
+    assert_eq!(model.error, Some(error));
+}
+
+#[test]
+fn test_compare_and_swap() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::CompareAndSwap, &mut model)
+        .expect_one_effect()
+        .expect_key_value();
+
+    assert_eq!(
+        request.operation,
+        KeyValueOperation::CompareAndSwap {
+            key: "test".to_string(),
+            expected: 41i32.to_ne_bytes().to_vec().into(),
+            new: 42i32.to_ne_bytes().to_vec().into(),
+        }
+    );
+
+    let _updated = app.resolve_to_event_then_update(
+        request,
+        KeyValueResult::Ok {
+            response: KeyValueResponse::CompareAndSwap,
+        },
+        &mut model,
+    );
+
+    assert!(model.successful);
+}
+
+#[test]
+fn test_compare_and_swap_conflict() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::CompareAndSwap, &mut model)
+        .expect_one_effect()
+        .expect_key_value();
+
+    let _updated = app.resolve_to_event_then_update(
+        request,
+        KeyValueResult::Err {
+            error: KeyValueError::Conflict {
+                current: 40i32.to_ne_bytes().to_vec().into(),
+            },
+        },
+        &mut model,
+    );
+
+    assert!(!model.successful);
+    assert_eq!(
+        model.error,
+        Some(KeyValueError::Conflict {
+            current: 40i32.to_ne_bytes().to_vec().into(),
+        })
+    );
+}
+
+#[test]
+fn test_watch() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Watch, &mut model)
+        .expect_one_effect()
+        .expect_key_value();
+
+    let id = model.watch.unwrap();
+    assert_eq!(
+        request.operation,
+        KeyValueOperation::Watch {
+            id,
+            prefix: "test:".to_string(),
+        }
+    );
+
+    for (key, value) in [("test:1", Value::Bytes(vec![1])), ("test:2", Value::None)] {
+        let changed = KeyValueResult::Ok {
+            response: KeyValueResponse::Changed {
+                key: key.to_string(),
+                value,
+            },
+        };
+        let event = app.resolve(request, changed).unwrap().expect_one_event();
+        app.update(event, &mut model).assert_empty();
+    }
+
+    assert_eq!(
+        model.changes,
+        [
+            ("test:1".to_string(), Some(vec![1])),
+            ("test:2".to_string(), None)
+        ]
+    );
+
+    let unwatch = app
+        .update(Event::Unwatch, &mut model)
+        .expect_one_effect()
+        .expect_key_value();
+    assert_eq!(unwatch.operation, KeyValueOperation::Unwatch { id });
+
+    let unwatched = KeyValueResult::Ok {
+        response: KeyValueResponse::Unwatched,
+    };
+    app.resolve(request, unwatched).unwrap().assert_empty();
+}
+
+#[test]
+fn test_watch_error_ends_the_watch() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Watch, &mut model)
+        .expect_one_effect()
+        .expect_key_value();
+
+    let error = KeyValueError::Other {
+        message: "watching is not supported".to_string(),
+    };
+    let failed = KeyValueResult::Err {
+        error: error.clone(),
+    };
+    let event = app.resolve(request, failed).unwrap().expect_one_event();
+    app.update(event, &mut model).assert_empty();
+
+    assert_eq!(model.watch, None);
+    assert_eq!(model.error, Some(error));
+
+    // no more changes are dispatched
+    let changed = KeyValueResult::Ok {
+        response: KeyValueResponse::Changed {
+            key: "test:1".to_string(),
+            value: Value::None,
+        },
+    };
+    app.resolve(request, changed).unwrap().assert_empty();
+}
+
+#[test]
+#[allow(clippy::useless_conversion)]
 pub fn test_kv_async() -> Result<()> {
     let app = AppTester::<App, _>::default();
     let mut model = Model::default();
@@ -385,4 +639,24 @@ fn test_kv_operation_debug_repr() {
             r#"Set { key: "my key", value: <binary data - 2 bytes> }"#
         );
     }
+
+    {
+        // batch
+        let op = KeyValueOperation::Batch {
+            writes: vec![
+                Write::Set {
+                    key: "my key".into(),
+                    value: b"my value".to_vec(),
+                },
+                Write::Delete {
+                    key: "other key".into(),
+                },
+            ],
+        };
+        let repr = format!("{op:?}");
+        assert_eq!(
+            repr,
+            r#"Batch { writes: [Set { key: "my key", value: "my value" }, Delete { key: "other key" }] }"#
+        );
+    }
 }
@@ -0,0 +1,112 @@
+//! App lifecycle transitions for Crux apps
+//!
+//! The Shell knows when the app comes to the foreground, goes to the background or is about to
+//! be suspended, through each platform's own callbacks. `crux_lifecycle` lets the app subscribe
+//! to these transitions, described as a [`LifecycleState`], so that it can pause its timers,
+//! save its state before it's suspended or refresh its data when the user comes back, without
+//! any platform specific code.
+//!
+//! The Shell responds to the subscription straight away with the current state, and then with
+//! every transition, for as long as the app is running.
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crux_core::capability::{CapabilityContext, Operation};
+
+/// Supported operations
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum LifecycleOperation {
+    /// Respond with the current state, and then with every transition
+    Subscribe,
+}
+
+/// The state the app is in, reported by the shell
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum LifecycleState {
+    /// The app is visible and the user can interact with it
+    Foreground,
+    /// The app is not visible, but still running, e.g. when the user has switched to another
+    /// app, or the browser tab is hidden
+    Background,
+    /// The app is about to be suspended or terminated by the operating system, and this is
+    /// the last chance to save its state. It may be resumed, coming to the foreground again.
+    Suspended,
+}
+
+impl Operation for LifecycleOperation {
+    type Output = LifecycleState;
+}
+
+pub struct Lifecycle<Ev> {
+    context: CapabilityContext<LifecycleOperation, Ev>,
+}
+
+impl<Ev> crux_core::Capability<Ev> for Lifecycle<Ev> {
+    type Operation = LifecycleOperation;
+
+    type MappedSelf<MappedEv> = Lifecycle<MappedEv>;
+
+    fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+    where
+        F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+        Ev: 'static,
+        NewEv: 'static + Send,
+    {
+        Lifecycle::new(self.context.map_event(f))
+    }
+
+    #[cfg(feature = "typegen")]
+    fn register_types(generator: &mut crux_core::typegen::TypeGen) -> crux_core::typegen::Result {
+        generator.register_type::<Self::Operation>()?;
+        generator.register_type::<<Self::Operation as Operation>::Output>()?;
+        Ok(())
+    }
+}
+
+impl<Ev> Clone for Lifecycle<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+        }
+    }
+}
+
+impl<Ev> Lifecycle<Ev>
+where
+    Ev: 'static,
+{
+    pub fn new(context: CapabilityContext<LifecycleOperation, Ev>) -> Self {
+        Self { context }
+    }
+
+    /// Subscribe to the app's lifecycle. Will dispatch the event with the current
+    /// [`LifecycleState`] as payload, and again after every transition.
+    pub fn subscribe<F>(&self, make_event: F)
+    where
+        F: Fn(LifecycleState) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            let mut states = self.subscribe_async();
+
+            async move {
+                while let Some(state) = states.next().await {
+                    context.update_app(make_event(state));
+                }
+            }
+        });
+    }
+
+    /// Subscribe to the app's lifecycle, while in an async context. This is used together with
+    /// [`crux_core::compose::Compose`].
+    ///
+    /// Returns a stream of the current [`LifecycleState`], followed by every transition.
+    pub fn subscribe_async(&self) -> impl Stream<Item = LifecycleState> {
+        self.context
+            .stream_from_shell(LifecycleOperation::Subscribe)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,125 @@
+use crux_core::{macros::Effect, render::Render, testing::AppTester};
+use serde::{Deserialize, Serialize};
+
+use crate::{Lifecycle, LifecycleOperation, LifecycleState};
+
+#[derive(Default)]
+pub struct App;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Event {
+    Start,
+
+    Transitioned(LifecycleState),
+}
+
+#[derive(Debug, Default)]
+pub struct Model {
+    pub state: Option<LifecycleState>,
+    pub refreshes: usize,
+    pub saves: usize,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ViewModel {
+    pub refreshes: usize,
+}
+
+impl crux_core::App for App {
+    type Event = Event;
+    type Model = Model;
+    type ViewModel = ViewModel;
+
+    type Capabilities = Capabilities;
+
+    fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+        match event {
+            Event::Start => caps.lifecycle.subscribe(Event::Transitioned),
+            Event::Transitioned(state) => {
+                let previous = model.state.replace(state);
+                match state {
+                    // refresh when the user comes back, but not on start up
+                    LifecycleState::Foreground if previous.is_some() => {
+                        model.refreshes += 1;
+                        caps.render.render();
+                    }
+                    LifecycleState::Suspended => model.saves += 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn view(&self, model: &Self::Model) -> Self::ViewModel {
+        ViewModel {
+            refreshes: model.refreshes,
+        }
+    }
+}
+
+#[derive(Effect)]
+#[cfg_attr(feature = "typegen", derive(crux_core::macros::Export))]
+pub struct Capabilities {
+    pub lifecycle: Lifecycle<Event>,
+    pub render: Render<Event>,
+}
+
+#[test]
+fn delivers_every_transition() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Start, &mut model)
+        .expect_one_effect()
+        .expect_lifecycle();
+
+    assert_eq!(request.operation, LifecycleOperation::Subscribe);
+
+    for state in [
+        LifecycleState::Foreground,
+        LifecycleState::Background,
+        LifecycleState::Suspended,
+        LifecycleState::Foreground,
+    ] {
+        let _update = app.resolve_to_event_then_update(request, state, &mut model);
+        assert_eq!(model.state, Some(state));
+    }
+
+    assert_eq!(model.saves, 1);
+    assert_eq!(model.refreshes, 1);
+}
+
+#[test]
+fn current_state_is_delivered_first() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Start, &mut model)
+        .expect_one_effect()
+        .expect_lifecycle();
+
+    app.resolve_to_event_then_update(request, LifecycleState::Foreground, &mut model)
+        .assert_empty();
+
+    assert_eq!(model.state, Some(LifecycleState::Foreground));
+    assert_eq!(model.refreshes, 0);
+}
+
+#[cfg(feature = "typegen")]
+#[test]
+fn lifecycle_types_are_generated() {
+    use crux_core::typegen::{State, TypeGen};
+
+    let mut gen = TypeGen::new();
+    gen.register_app::<App>().expect("to register the app");
+
+    let State::Registering(tracer, _) = gen.state else {
+        panic!("expected to still be registering");
+    };
+    let registry = tracer.registry().expect("to get the registry");
+
+    assert!(registry.contains_key("LifecycleOperation"));
+    assert!(registry.contains_key("LifecycleState"));
+}
@@ -0,0 +1,280 @@
+//! Logging and analytics for Crux apps
+//!
+//! `crux_log` allows Crux apps to emit diagnostic log records and analytics events through the
+//! Shell's logging infrastructure, so that they end up alongside the rest of the platform's logs
+//! (e.g. OSLog, Logcat or the browser console) and analytics pipelines.
+//!
+//! Records are fire-and-forget, the Shell doesn't respond to them. Which records are sent to the
+//! Shell is controlled by a [`LogConfig`], which the Shell passes to the Core at startup (typically
+//! as the payload of an event), and the app hands to [`Log::configure`].
+
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crux_core::capability::{CapabilityContext, Operation};
+
+/// Severity of a log record, ordered from the most verbose to the most severe
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// A structured key-value field attached to a log record or analytics event
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct LogField {
+    pub key: String,
+    pub value: LogValue,
+}
+
+impl LogField {
+    pub fn new(key: impl Into<String>, value: impl Into<LogValue>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// The value of a [`LogField`]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum LogValue {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<String> for LogValue {
+    fn from(value: String) -> Self {
+        LogValue::Text(value)
+    }
+}
+
+impl From<&str> for LogValue {
+    fn from(value: &str) -> Self {
+        LogValue::Text(value.to_string())
+    }
+}
+
+impl From<i64> for LogValue {
+    fn from(value: i64) -> Self {
+        LogValue::Integer(value)
+    }
+}
+
+impl From<f64> for LogValue {
+    fn from(value: f64) -> Self {
+        LogValue::Float(value)
+    }
+}
+
+impl From<bool> for LogValue {
+    fn from(value: bool) -> Self {
+        LogValue::Bool(value)
+    }
+}
+
+/// Configuration passed from the Shell to the Core at startup.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct LogConfig {
+    /// Log records below this level are not sent to the Shell
+    pub min_level: LogLevel,
+    /// Only one in every `analytics_sampling` analytics events is sent to the Shell.
+    /// `1` sends every event, `0` disables analytics altogether.
+    pub analytics_sampling: u32,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            min_level: LogLevel::Info,
+            analytics_sampling: 1,
+        }
+    }
+}
+
+/// Supported operations
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum LogOperation {
+    /// A diagnostic log record
+    Log {
+        level: LogLevel,
+        message: String,
+        fields: Vec<LogField>,
+    },
+    /// An analytics event
+    Analytics { name: String, fields: Vec<LogField> },
+}
+
+impl Operation for LogOperation {
+    type Output = ();
+}
+
+#[derive(Default)]
+struct LogState {
+    config: LogConfig,
+    analytics_count: u64,
+}
+
+impl LogState {
+    fn sample_analytics(&mut self) -> bool {
+        let sampling = u64::from(self.config.analytics_sampling);
+        if sampling == 0 {
+            return false;
+        }
+        let sampled = self.analytics_count % sampling == 0;
+        self.analytics_count = self.analytics_count.wrapping_add(1);
+        sampled
+    }
+}
+
+/// The Log capability API
+///
+/// The configuration is shared between all clones of the capability (including those
+/// created with `map_event`), so configuring it once at startup applies app-wide.
+pub struct Log<Ev> {
+    context: CapabilityContext<LogOperation, Ev>,
+    state: Arc<Mutex<LogState>>,
+}
+
+impl<Ev> crux_core::Capability<Ev> for Log<Ev> {
+    type Operation = LogOperation;
+
+    type MappedSelf<MappedEv> = Log<MappedEv>;
+
+    fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+    where
+        F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+        Ev: 'static,
+        NewEv: 'static + Send,
+    {
+        Log {
+            context: self.context.map_event(f),
+            state: self.state.clone(),
+        }
+    }
+
+    #[cfg(feature = "typegen")]
+    fn register_types(generator: &mut crux_core::typegen::TypeGen) -> crux_core::typegen::Result {
+        generator.register_type::<LogLevel>()?;
+        generator.register_type::<LogValue>()?;
+        generator.register_type::<LogConfig>()?;
+        generator.register_type::<Self::Operation>()?;
+        Ok(())
+    }
+}
+
+impl<Ev> Clone for Log<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<Ev> Log<Ev>
+where
+    Ev: 'static,
+{
+    pub fn new(context: CapabilityContext<LogOperation, Ev>) -> Self {
+        Self {
+            context,
+            state: Arc::default(),
+        }
+    }
+
+    /// Apply the configuration received from the Shell.
+    pub fn configure(&self, config: LogConfig) {
+        let mut state = self.state.lock().expect("Log state Mutex poisoned.");
+        state.config = config;
+        state.analytics_count = 0;
+    }
+
+    /// The configuration currently in effect.
+    pub fn config(&self) -> LogConfig {
+        self.state
+            .lock()
+            .expect("Log state Mutex poisoned.")
+            .config
+            .clone()
+    }
+
+    /// Emit a log record with the given `level`, unless it is below the configured minimum level.
+    pub fn log(&self, level: LogLevel, message: impl Into<String>, fields: Vec<LogField>) {
+        let min_level = self
+            .state
+            .lock()
+            .expect("Log state Mutex poisoned.")
+            .config
+            .min_level;
+        if level < min_level {
+            return;
+        }
+
+        self.notify(LogOperation::Log {
+            level,
+            message: message.into(),
+            fields,
+        });
+    }
+
+    /// Emit a log record at [`LogLevel::Trace`]
+    pub fn trace(&self, message: impl Into<String>, fields: Vec<LogField>) {
+        self.log(LogLevel::Trace, message, fields);
+    }
+
+    /// Emit a log record at [`LogLevel::Debug`]
+    pub fn debug(&self, message: impl Into<String>, fields: Vec<LogField>) {
+        self.log(LogLevel::Debug, message, fields);
+    }
+
+    /// Emit a log record at [`LogLevel::Info`]
+    pub fn info(&self, message: impl Into<String>, fields: Vec<LogField>) {
+        self.log(LogLevel::Info, message, fields);
+    }
+
+    /// Emit a log record at [`LogLevel::Warn`]
+    pub fn warn(&self, message: impl Into<String>, fields: Vec<LogField>) {
+        self.log(LogLevel::Warn, message, fields);
+    }
+
+    /// Emit a log record at [`LogLevel::Error`]
+    pub fn error(&self, message: impl Into<String>, fields: Vec<LogField>) {
+        self.log(LogLevel::Error, message, fields);
+    }
+
+    /// Emit an analytics event, subject to the configured sampling.
+    pub fn analytics(&self, name: impl Into<String>, fields: Vec<LogField>) {
+        if !self
+            .state
+            .lock()
+            .expect("Log state Mutex poisoned.")
+            .sample_analytics()
+        {
+            return;
+        }
+
+        self.notify(LogOperation::Analytics {
+            name: name.into(),
+            fields,
+        });
+    }
+
+    fn notify(&self, operation: LogOperation) {
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                context.notify_shell(operation).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests;
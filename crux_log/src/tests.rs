@@ -0,0 +1,154 @@
+use crux_core::{macros::Effect, testing::AppTester};
+use serde::{Deserialize, Serialize};
+
+use crate::{Log, LogConfig, LogField, LogLevel, LogOperation, LogValue};
+
+#[derive(Default)]
+pub struct App;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Event {
+    Startup(LogConfig),
+    Debug,
+    Info,
+    Tapped,
+}
+
+impl crux_core::App for App {
+    type Event = Event;
+    type Model = ();
+    type ViewModel = ();
+
+    type Capabilities = Capabilities;
+
+    fn update(&self, event: Event, _model: &mut (), caps: &Capabilities) {
+        match event {
+            Event::Startup(config) => caps.log.configure(config),
+            Event::Debug => caps.log.debug("debugging", vec![]),
+            Event::Info => caps.log.info(
+                "loaded",
+                vec![LogField::new("items", 3), LogField::new("cached", true)],
+            ),
+            Event::Tapped => caps
+                .log
+                .analytics("button_tapped", vec![LogField::new("button", "save")]),
+        }
+    }
+
+    fn view(&self, _model: &()) {}
+}
+
+#[derive(Effect)]
+pub struct Capabilities {
+    pub log: Log<Event>,
+}
+
+#[test]
+fn test_log_with_fields() {
+    let app = AppTester::<App, _>::default();
+
+    let request = app
+        .update(Event::Info, &mut ())
+        .expect_one_effect()
+        .expect_log();
+
+    assert_eq!(
+        request.operation,
+        LogOperation::Log {
+            level: LogLevel::Info,
+            message: "loaded".to_string(),
+            fields: vec![
+                LogField {
+                    key: "items".to_string(),
+                    value: LogValue::Integer(3)
+                },
+                LogField {
+                    key: "cached".to_string(),
+                    value: LogValue::Bool(true)
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+fn test_min_level() {
+    let app = AppTester::<App, _>::default();
+
+    // the default minimum level is Info
+    app.update(Event::Debug, &mut ()).assert_empty();
+
+    app.update(
+        Event::Startup(LogConfig {
+            min_level: LogLevel::Debug,
+            ..Default::default()
+        }),
+        &mut (),
+    )
+    .assert_empty();
+
+    let request = app
+        .update(Event::Debug, &mut ())
+        .expect_one_effect()
+        .expect_log();
+    assert!(matches!(
+        request.operation,
+        LogOperation::Log {
+            level: LogLevel::Debug,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_analytics_sampling() {
+    let app = AppTester::<App, _>::default();
+
+    app.update(
+        Event::Startup(LogConfig {
+            analytics_sampling: 3,
+            ..Default::default()
+        }),
+        &mut (),
+    )
+    .assert_empty();
+
+    let sent = (0..9)
+        .filter(|_| !app.update(Event::Tapped, &mut ()).effects.is_empty())
+        .count();
+    assert_eq!(sent, 3);
+}
+
+#[test]
+fn test_analytics_disabled() {
+    let app = AppTester::<App, _>::default();
+
+    app.update(
+        Event::Startup(LogConfig {
+            analytics_sampling: 0,
+            ..Default::default()
+        }),
+        &mut (),
+    )
+    .assert_empty();
+
+    app.update(Event::Tapped, &mut ()).assert_empty();
+}
+
+#[test]
+fn test_analytics_event() {
+    let app = AppTester::<App, _>::default();
+
+    let request = app
+        .update(Event::Tapped, &mut ())
+        .expect_one_effect()
+        .expect_log();
+
+    assert_eq!(
+        request.operation,
+        LogOperation::Analytics {
+            name: "button_tapped".to_string(),
+            fields: vec![LogField::new("button", "save")],
+        }
+    );
+}
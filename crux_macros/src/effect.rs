@@ -3,7 +3,7 @@ use proc_macro2::{Literal, TokenStream};
 use proc_macro_error::{abort_call_site, OptionExt};
 use quote::{format_ident, quote};
 use std::collections::BTreeMap;
-use syn::{DeriveInput, GenericArgument, Ident, PathArguments, Type};
+use syn::{Attribute, DeriveInput, Expr, GenericArgument, Ident, Lit, Meta, PathArguments, Type};
 
 #[derive(FromDeriveInput, Debug)]
 #[darling(attributes(effect), supports(struct_named))]
@@ -14,12 +14,15 @@ struct EffectStructReceiver {
 }
 
 #[derive(FromField, Debug)]
-#[darling(attributes(effect))]
+#[darling(attributes(effect), forward_attrs(doc))]
 pub struct EffectFieldReceiver {
     ident: Option<Ident>,
     ty: Type,
+    attrs: Vec<Attribute>,
     #[darling(default)]
     skip: bool,
+    #[darling(default)]
+    uses: util::PathList,
 }
 
 struct Field {
@@ -27,6 +30,8 @@ struct Field {
     variant: Ident,
     event: Type,
     skip: bool,
+    uses: Vec<Ident>,
+    doc: String,
 }
 
 impl From<&EffectFieldReceiver> for Field {
@@ -37,10 +42,40 @@ impl From<&EffectFieldReceiver> for Field {
             variant,
             event,
             skip: f.skip,
+            uses: f
+                .uses
+                .iter()
+                .map(|path| {
+                    path.get_ident()
+                        .cloned()
+                        .expect_or_abort("uses should list the names of fields")
+                })
+                .collect(),
+            doc: doc_comment(&f.attrs),
         }
     }
 }
 
+/// The text of the doc comment in the `attrs`, one line per attribute, without the
+/// space which follows `///`
+fn doc_comment(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(doc) => match &doc.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(line) => Some(line.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_string).unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 impl ToTokens for EffectStructReceiver {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let ident = &self.ident;
@@ -82,7 +117,12 @@ impl ToTokens for EffectStructReceiver {
         let mut with_context_fields = Vec::new();
         let mut ffi_variants = Vec::new();
         let mut match_arms = Vec::new();
+        let mut priority_arms = Vec::new();
+        let mut cause_arms = Vec::new();
+        let mut is_render_arms = Vec::new();
+        let mut name_arms = Vec::new();
         let mut filters = Vec::new();
+        let mut infos = Vec::new();
 
         for (
             field_name,
@@ -91,10 +131,47 @@ impl ToTokens for EffectStructReceiver {
                 variant,
                 event,
                 skip,
+                uses,
+                doc,
             },
         ) in fields.iter()
         {
-            if *skip {
+            if !uses.is_empty() {
+                // a derived capability makes its requests through the capabilities it uses,
+                // which are constructed for it with requests attributed to it
+                let source = variant.to_string();
+                let mut used = Vec::new();
+                for name in uses {
+                    let Some(Field {
+                        capability,
+                        variant,
+                        skip: false,
+                        uses,
+                        ..
+                    }) = fields.get(name)
+                    else {
+                        abort_call_site!(
+                            "\"{}\" can only use capabilities which are not skipped",
+                            field_name
+                        );
+                    };
+                    if !uses.is_empty() {
+                        abort_call_site!(
+                            "\"{}\" can't use \"{}\", which is derived from other capabilities",
+                            field_name,
+                            name
+                        );
+                    }
+                    used.push(quote! {
+                        #capability::new(context.specialize(#effect_name::#variant).derived_from(#source))
+                    });
+                }
+
+                let msg = format!("Requesting effects from capability \"{variant}\" is impossible because it is derived from other capabilities");
+                with_context_fields.push(quote! {
+                    #field_name: #capability::new(context.specialize(|_| unreachable!(#msg)), #(#used),*)
+                });
+            } else if *skip {
                 let msg = format!("Requesting effects from capability \"{variant}\" is impossible because it was skipped",);
                 with_context_fields.push(quote! {
                     #field_name: #capability::new(context.specialize(|_| unreachable!(#msg)))
@@ -112,6 +189,25 @@ impl ToTokens for EffectStructReceiver {
 
                 match_arms.push(quote! { #effect_name::#variant(request) => request.serialize(#ffi_effect_name::#variant) });
 
+                priority_arms
+                    .push(quote! { #effect_name::#variant(ref request) => request.priority() });
+                cause_arms.push(quote! { #effect_name::#variant(ref request) => request.cause() });
+                is_render_arms
+                    .push(quote! { #effect_name::#variant(ref request) => request.is_render() });
+
+                let variant_as_str = variant.to_string();
+                name_arms.push(quote! { #effect_name::#variant(_) => #variant_as_str });
+
+                let field_as_str = field_name.to_string();
+                infos.push(quote! {
+                    ::crux_core::capability::CapabilityInfo {
+                        effect: #variant_as_str,
+                        field: #field_as_str,
+                        doc: #doc,
+                        operation: ::std::any::type_name::<<#capability<#event> as ::crux_core::capability::Capability<#event>>::Operation>(),
+                    }
+                });
+
                 let filter_fn = format_ident!("is_{}", field_name);
                 let map_fn = format_ident!("into_{}", field_name);
                 let expect_fn = format_ident!("expect_{}", field_name);
@@ -164,6 +260,30 @@ impl ToTokens for EffectStructReceiver {
                         #(#match_arms ,)*
                     }
                 }
+
+                fn priority(&self) -> ::crux_core::capability::Priority {
+                    match *self {
+                        #(#priority_arms ,)*
+                    }
+                }
+
+                fn cause(&self) -> Option<&::crux_core::cause::Cause> {
+                    match *self {
+                        #(#cause_arms ,)*
+                    }
+                }
+
+                fn is_render(&self) -> bool {
+                    match *self {
+                        #(#is_render_arms ,)*
+                    }
+                }
+
+                fn name(&self) -> &'static str {
+                    match *self {
+                        #(#name_arms ,)*
+                    }
+                }
             }
 
             impl ::crux_core::WithContext<#event, #effect_name> for #ident {
@@ -174,6 +294,12 @@ impl ToTokens for EffectStructReceiver {
                 }
             }
 
+            impl ::crux_core::capability::Introspect for #ident {
+                fn capabilities() -> Vec<::crux_core::capability::CapabilityInfo> {
+                    vec![#(#infos ,)*]
+                }
+            }
+
             #(#filters)*
         })
     }
@@ -261,6 +387,26 @@ mod tests {
                     Effect::Render(request) => request.serialize(EffectFfi::Render),
                 }
             }
+            fn priority(&self) -> ::crux_core::capability::Priority {
+                match *self {
+                    Effect::Render(ref request) => request.priority(),
+                }
+            }
+            fn cause(&self) -> Option<&::crux_core::cause::Cause> {
+                match *self {
+                    Effect::Render(ref request) => request.cause(),
+                }
+            }
+            fn is_render(&self) -> bool {
+                match *self {
+                    Effect::Render(ref request) => request.is_render(),
+                }
+            }
+            fn name(&self) -> &'static str {
+                match *self {
+                    Effect::Render(_) => "Render",
+                }
+            }
         }
         impl ::crux_core::WithContext<Event, Effect> for Capabilities {
             fn new_with_context(
@@ -271,6 +417,15 @@ mod tests {
                 }
             }
         }
+        impl ::crux_core::capability::Introspect for Capabilities {
+            fn capabilities() -> Vec<::crux_core::capability::CapabilityInfo> {
+                vec![
+                    ::crux_core::capability::CapabilityInfo { effect : "Render", field :
+                    "render", doc : "", operation : ::std::any::type_name:: << Render < Event >
+                    as ::crux_core::capability::Capability < Event >> ::Operation > (), },
+                ]
+            }
+        }
         impl Effect {
             pub fn is_render(&self) -> bool {
                 if let Effect::Render(_) = self { true } else { false }
@@ -296,6 +451,7 @@ mod tests {
                 }
             }
         }
+
         "###);
     }
 
@@ -335,6 +491,26 @@ mod tests {
                     Effect::Render(request) => request.serialize(EffectFfi::Render),
                 }
             }
+            fn priority(&self) -> ::crux_core::capability::Priority {
+                match *self {
+                    Effect::Render(ref request) => request.priority(),
+                }
+            }
+            fn cause(&self) -> Option<&::crux_core::cause::Cause> {
+                match *self {
+                    Effect::Render(ref request) => request.cause(),
+                }
+            }
+            fn is_render(&self) -> bool {
+                match *self {
+                    Effect::Render(ref request) => request.is_render(),
+                }
+            }
+            fn name(&self) -> &'static str {
+                match *self {
+                    Effect::Render(_) => "Render",
+                }
+            }
         }
         impl ::crux_core::WithContext<Event, Effect> for Capabilities {
             fn new_with_context(
@@ -353,6 +529,15 @@ mod tests {
                 }
             }
         }
+        impl ::crux_core::capability::Introspect for Capabilities {
+            fn capabilities() -> Vec<::crux_core::capability::CapabilityInfo> {
+                vec![
+                    ::crux_core::capability::CapabilityInfo { effect : "Render", field :
+                    "render", doc : "", operation : ::std::any::type_name:: << Render < Event >
+                    as ::crux_core::capability::Capability < Event >> ::Operation > (), },
+                ]
+            }
+        }
         impl Effect {
             pub fn is_render(&self) -> bool {
                 if let Effect::Render(_) = self { true } else { false }
@@ -378,6 +563,163 @@ mod tests {
                 }
             }
         }
+
+        "###);
+    }
+
+    #[test]
+    fn effect_uses() {
+        let input = r#"
+            #[derive(Effect)]
+            pub struct Capabilities {
+                pub http: Http<Event>,
+                pub key_value: KeyValue<Event>,
+                #[effect(uses(http, key_value))]
+                pub auth: Auth<Event>,
+            }
+        "#;
+        let input = parse_str(input).unwrap();
+        let input = EffectStructReceiver::from_derive_input(&input).unwrap();
+
+        let actual = quote!(#input);
+
+        insta::assert_snapshot!(pretty_print(&actual), @r###"
+        #[derive(Debug)]
+        pub enum Effect {
+            Http(
+                ::crux_core::Request<
+                    <Http<Event> as ::crux_core::capability::Capability<Event>>::Operation,
+                >,
+            ),
+            KeyValue(
+                ::crux_core::Request<
+                    <KeyValue<Event> as ::crux_core::capability::Capability<Event>>::Operation,
+                >,
+            ),
+        }
+        #[derive(::serde::Serialize, ::serde::Deserialize)]
+        #[serde(rename = "Effect")]
+        pub enum EffectFfi {
+            Http(<Http<Event> as ::crux_core::capability::Capability<Event>>::Operation),
+            KeyValue(<KeyValue<Event> as ::crux_core::capability::Capability<Event>>::Operation),
+        }
+        impl ::crux_core::Effect for Effect {
+            type Ffi = EffectFfi;
+            fn serialize(self) -> (Self::Ffi, ::crux_core::bridge::ResolveSerialized) {
+                match self {
+                    Effect::Http(request) => request.serialize(EffectFfi::Http),
+                    Effect::KeyValue(request) => request.serialize(EffectFfi::KeyValue),
+                }
+            }
+            fn priority(&self) -> ::crux_core::capability::Priority {
+                match *self {
+                    Effect::Http(ref request) => request.priority(),
+                    Effect::KeyValue(ref request) => request.priority(),
+                }
+            }
+            fn cause(&self) -> Option<&::crux_core::cause::Cause> {
+                match *self {
+                    Effect::Http(ref request) => request.cause(),
+                    Effect::KeyValue(ref request) => request.cause(),
+                }
+            }
+            fn is_render(&self) -> bool {
+                match *self {
+                    Effect::Http(ref request) => request.is_render(),
+                    Effect::KeyValue(ref request) => request.is_render(),
+                }
+            }
+            fn name(&self) -> &'static str {
+                match *self {
+                    Effect::Http(_) => "Http",
+                    Effect::KeyValue(_) => "KeyValue",
+                }
+            }
+        }
+        impl ::crux_core::WithContext<Event, Effect> for Capabilities {
+            fn new_with_context(
+                context: ::crux_core::capability::ProtoContext<Effect, Event>,
+            ) -> Capabilities {
+                Capabilities {
+                    auth: Auth::new(
+                        context
+                            .specialize(|_| {
+                                unreachable!(
+                                    "Requesting effects from capability \"Auth\" is impossible because it is derived from other capabilities"
+                                )
+                            }),
+                        Http::new(context.specialize(Effect::Http).derived_from("Auth")),
+                        KeyValue::new(context.specialize(Effect::KeyValue).derived_from("Auth")),
+                    ),
+                    http: Http::new(context.specialize(Effect::Http)),
+                    key_value: KeyValue::new(context.specialize(Effect::KeyValue)),
+                }
+            }
+        }
+        impl ::crux_core::capability::Introspect for Capabilities {
+            fn capabilities() -> Vec<::crux_core::capability::CapabilityInfo> {
+                vec![
+                    ::crux_core::capability::CapabilityInfo { effect : "Http", field : "http",
+                    doc : "", operation : ::std::any::type_name:: << Http < Event > as
+                    ::crux_core::capability::Capability < Event >> ::Operation > (), },
+                    ::crux_core::capability::CapabilityInfo { effect : "KeyValue", field :
+                    "key_value", doc : "", operation : ::std::any::type_name:: << KeyValue <
+                    Event > as ::crux_core::capability::Capability < Event >> ::Operation > (),
+                    },
+                ]
+            }
+        }
+        impl Effect {
+            pub fn is_http(&self) -> bool {
+                if let Effect::Http(_) = self { true } else { false }
+            }
+            pub fn into_http(
+                self,
+            ) -> Option<
+                crux_core::Request<
+                    <Http<Event> as ::crux_core::capability::Capability<Event>>::Operation,
+                >,
+            > {
+                if let Effect::Http(request) = self { Some(request) } else { None }
+            }
+            pub fn expect_http(
+                self,
+            ) -> crux_core::Request<
+                <Http<Event> as ::crux_core::capability::Capability<Event>>::Operation,
+            > {
+                if let Effect::Http(request) = self {
+                    request
+                } else {
+                    panic!("not a {} effect", "http")
+                }
+            }
+        }
+        impl Effect {
+            pub fn is_key_value(&self) -> bool {
+                if let Effect::KeyValue(_) = self { true } else { false }
+            }
+            pub fn into_key_value(
+                self,
+            ) -> Option<
+                crux_core::Request<
+                    <KeyValue<Event> as ::crux_core::capability::Capability<Event>>::Operation,
+                >,
+            > {
+                if let Effect::KeyValue(request) = self { Some(request) } else { None }
+            }
+            pub fn expect_key_value(
+                self,
+            ) -> crux_core::Request<
+                <KeyValue<Event> as ::crux_core::capability::Capability<Event>>::Operation,
+            > {
+                if let Effect::KeyValue(request) = self {
+                    request
+                } else {
+                    panic!("not a {} effect", "key_value")
+                }
+            }
+        }
+
         "###);
     }
 
@@ -462,6 +804,42 @@ mod tests {
                     MyEffect::Time(request) => request.serialize(MyEffectFfi::Time),
                 }
             }
+            fn priority(&self) -> ::crux_core::capability::Priority {
+                match *self {
+                    MyEffect::Http(ref request) => request.priority(),
+                    MyEffect::KeyValue(ref request) => request.priority(),
+                    MyEffect::Platform(ref request) => request.priority(),
+                    MyEffect::Render(ref request) => request.priority(),
+                    MyEffect::Time(ref request) => request.priority(),
+                }
+            }
+            fn cause(&self) -> Option<&::crux_core::cause::Cause> {
+                match *self {
+                    MyEffect::Http(ref request) => request.cause(),
+                    MyEffect::KeyValue(ref request) => request.cause(),
+                    MyEffect::Platform(ref request) => request.cause(),
+                    MyEffect::Render(ref request) => request.cause(),
+                    MyEffect::Time(ref request) => request.cause(),
+                }
+            }
+            fn is_render(&self) -> bool {
+                match *self {
+                    MyEffect::Http(ref request) => request.is_render(),
+                    MyEffect::KeyValue(ref request) => request.is_render(),
+                    MyEffect::Platform(ref request) => request.is_render(),
+                    MyEffect::Render(ref request) => request.is_render(),
+                    MyEffect::Time(ref request) => request.is_render(),
+                }
+            }
+            fn name(&self) -> &'static str {
+                match *self {
+                    MyEffect::Http(_) => "Http",
+                    MyEffect::KeyValue(_) => "KeyValue",
+                    MyEffect::Platform(_) => "Platform",
+                    MyEffect::Render(_) => "Render",
+                    MyEffect::Time(_) => "Time",
+                }
+            }
         }
         impl ::crux_core::WithContext<MyEvent, MyEffect> for MyCapabilities {
             fn new_with_context(
@@ -476,6 +854,27 @@ mod tests {
                 }
             }
         }
+        impl ::crux_core::capability::Introspect for MyCapabilities {
+            fn capabilities() -> Vec<::crux_core::capability::CapabilityInfo> {
+                vec![
+                    ::crux_core::capability::CapabilityInfo { effect : "Http", field : "http",
+                    doc : "", operation : ::std::any::type_name:: << crux_http::Http < MyEvent >
+                    as ::crux_core::capability::Capability < MyEvent >> ::Operation > (), },
+                    ::crux_core::capability::CapabilityInfo { effect : "KeyValue", field :
+                    "key_value", doc : "", operation : ::std::any::type_name:: << KeyValue <
+                    MyEvent > as ::crux_core::capability::Capability < MyEvent >> ::Operation >
+                    (), }, ::crux_core::capability::CapabilityInfo { effect : "Platform", field :
+                    "platform", doc : "", operation : ::std::any::type_name:: << Platform <
+                    MyEvent > as ::crux_core::capability::Capability < MyEvent >> ::Operation >
+                    (), }, ::crux_core::capability::CapabilityInfo { effect : "Render", field :
+                    "render", doc : "", operation : ::std::any::type_name:: << Render < MyEvent >
+                    as ::crux_core::capability::Capability < MyEvent >> ::Operation > (), },
+                    ::crux_core::capability::CapabilityInfo { effect : "Time", field : "time",
+                    doc : "", operation : ::std::any::type_name:: << Time < MyEvent > as
+                    ::crux_core::capability::Capability < MyEvent >> ::Operation > (), },
+                ]
+            }
+        }
         impl MyEffect {
             pub fn is_http(&self) -> bool {
                 if let MyEffect::Http(_) = self { true } else { false }
@@ -609,6 +1008,7 @@ mod tests {
                 }
             }
         }
+
         "###);
     }
 
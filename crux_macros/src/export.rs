@@ -58,7 +58,8 @@ impl ToTokens for ExportStructReceiver {
                 fn register_types(generator: &mut ::crux_core::typegen::TypeGen) -> ::crux_core::typegen::Result {
                     use ::crux_core::capability::Capability;
                     #(#output_type_exports)*
-                    generator.register_type::<#ffi_export_name>()?;
+                    generator.register_effect::<#ffi_export_name>()?;
+                    generator.register_type::<::crux_core::capability::Priority>()?;
                     generator.register_type::<::crux_core::bridge::Request<#ffi_export_name>>()?;
 
                     Ok(())
@@ -136,7 +137,8 @@ mod tests {
             ) -> ::crux_core::typegen::Result {
                 use ::crux_core::capability::Capability;
                 Render::<Event>::register_types(generator)?;
-                generator.register_type::<EffectFfi>()?;
+                generator.register_effect::<EffectFfi>()?;
+                generator.register_type::<::crux_core::capability::Priority>()?;
                 generator.register_type::<::crux_core::bridge::Request<EffectFfi>>()?;
                 Ok(())
             }
@@ -187,7 +189,8 @@ mod tests {
                 KeyValue::<MyEvent>::register_types(generator)?;
                 Platform::<MyEvent>::register_types(generator)?;
                 Render::<MyEvent>::register_types(generator)?;
-                generator.register_type::<EffectFfi>()?;
+                generator.register_effect::<EffectFfi>()?;
+                generator.register_type::<::crux_core::capability::Priority>()?;
                 generator.register_type::<::crux_core::bridge::Request<EffectFfi>>()?;
                 Ok(())
             }
@@ -224,7 +227,8 @@ mod tests {
                 Platform::<MyEvent>::register_types(generator)?;
                 Render::<MyEvent>::register_types(generator)?;
                 Time::<MyEvent>::register_types(generator)?;
-                generator.register_type::<EffectFfi>()?;
+                generator.register_effect::<EffectFfi>()?;
+                generator.register_type::<::crux_core::capability::Priority>()?;
                 generator.register_type::<::crux_core::bridge::Request<EffectFfi>>()?;
                 Ok(())
             }
@@ -255,7 +259,8 @@ mod tests {
             ) -> ::crux_core::typegen::Result {
                 use ::crux_core::capability::Capability;
                 Render::<Event>::register_types(generator)?;
-                generator.register_type::<MyEffectFfi>()?;
+                generator.register_effect::<MyEffectFfi>()?;
+                generator.register_type::<::crux_core::capability::Priority>()?;
                 generator.register_type::<::crux_core::bridge::Request<MyEffectFfi>>()?;
                 Ok(())
             }
@@ -1,13 +1,17 @@
 mod capability;
 mod effect;
 mod export;
+mod sanitized_debug;
+mod view_model;
 
 use capability::capability_impl;
 use effect::effect_impl;
 use export::export_impl;
 use proc_macro::TokenStream;
 use proc_macro_error::proc_macro_error;
+use sanitized_debug::sanitized_debug_impl;
 use syn::parse_macro_input;
+use view_model::view_model_impl;
 
 /// Procedural macro to derive an Effect enum, with a variant for
 /// each non-skipped capability.
@@ -21,6 +25,13 @@ use syn::parse_macro_input;
 /// No Effect variant will be generated for fields annotated with
 /// `#[effect(skip)]`.
 ///
+/// A capability implemented in terms of others, e.g. one which signs in
+/// using the `http` and `key_value` capabilities, is annotated with
+/// `#[effect(uses(http, key_value))]`. It gets no Effect variant either, and
+/// is constructed with its own context followed by instances of the listed
+/// capabilities, in order. Their requests are attributed to it, see
+/// `Request::source`.
+///
 /// e.g.
 /// ```rust
 /// # use crux_core::{Capability, render::Render, compose::Compose};
@@ -56,6 +67,7 @@ use syn::parse_macro_input;
 
 #[proc_macro_derive(Effect, attributes(effect))]
 #[proc_macro_error]
+#[allow(clippy::empty_line_after_outer_attr)]
 pub fn effect(input: TokenStream) -> TokenStream {
     effect_impl(&parse_macro_input!(input)).into()
 }
@@ -71,3 +83,95 @@ pub fn export(input: TokenStream) -> TokenStream {
 pub fn capability(input: TokenStream) -> TokenStream {
     capability_impl(&parse_macro_input!(input)).into()
 }
+
+/// Procedural macro to derive a builder for a view model, which copies its fields from the
+/// fields of the model with the same names, and computes the fields annotated with
+/// `#[view_model(computed = function, reads(fields))]` by calling the function with
+/// references to the listed fields of the model.
+///
+/// The builder for a `ViewModel` is called `ViewModelBuilder`, and it remembers the
+/// computed fields, so that `build` only calls the functions again when the fields they
+/// read have changed. Keep it in the app, and call it from `App::view`. The model is named
+/// with the `model` attribute.
+///
+/// e.g.
+/// ```rust
+/// # use crux_core::macros::ViewModelBuilder;
+/// #[derive(Default)]
+/// pub struct Model {
+///     count: usize,
+///     prices: Vec<u32>,
+///     currency: String,
+/// }
+///
+/// #[derive(ViewModelBuilder)]
+/// #[view_model(model = Model)]
+/// pub struct ViewModel {
+///     pub count: usize,
+///     #[view_model(computed = format_total, reads(prices, currency))]
+///     pub total: String,
+/// }
+///
+/// fn format_total(prices: &[u32], currency: &str) -> String {
+///     format!("{} {currency}", prices.iter().sum::<u32>())
+/// }
+///
+/// #[derive(Default)]
+/// pub struct App {
+///     view_model: ViewModelBuilder,
+/// }
+/// # impl crux_core::App for App {
+/// #     type Event = ();
+/// #     type Model = Model;
+/// #     type ViewModel = ();
+/// #     type Capabilities = ();
+/// #     fn update(&self, _event: (), _model: &mut Model, _caps: &()) {}
+/// #     fn view(&self, _model: &Model) {}
+/// # }
+///
+/// // in `App::view`
+/// # let app = App::default();
+/// # let model = Model::default();
+/// let view_model = app.view_model.build(&model);
+/// # assert_eq!(view_model.total, "0 ");
+/// ```
+#[proc_macro_derive(ViewModelBuilder, attributes(view_model))]
+#[proc_macro_error]
+pub fn view_model(input: TokenStream) -> TokenStream {
+    view_model_impl(&parse_macro_input!(input)).into()
+}
+
+/// Procedural macro to derive `Debug` for a struct or enum, with the fields annotated with
+/// `#[sensitive]` shown as `<redacted>`, e.g. for events carrying passwords or tokens, which
+/// must not end up in logs. Everything which formats the type with `{:?}`, e.g. a shell
+/// logging the events it sends, or `App::debug_event`, sees the redacted version.
+///
+/// e.g.
+/// ```rust
+/// # use crux_core::macros::SanitizedDebug;
+/// #[derive(SanitizedDebug)]
+/// pub enum Event {
+///     SignIn {
+///         user: String,
+///         #[sensitive]
+///         password: String,
+///     },
+///     TokenReceived(#[sensitive] String),
+/// }
+///
+/// let event = Event::SignIn {
+///     user: "ferris".to_string(),
+///     password: "hunter2".to_string(),
+/// };
+/// assert_eq!(
+///     format!("{event:?}"),
+///     r#"SignIn { user: "ferris", password: <redacted> }"#
+/// );
+/// ```
+///
+/// Only the Rust side is redacted, the types generated for the shell are unchanged.
+#[proc_macro_derive(SanitizedDebug, attributes(sensitive))]
+#[proc_macro_error]
+pub fn sanitized_debug(input: TokenStream) -> TokenStream {
+    sanitized_debug_impl(&parse_macro_input!(input)).into()
+}
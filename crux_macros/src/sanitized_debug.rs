@@ -0,0 +1,232 @@
+use darling::{ast, FromDeriveInput, FromField, FromVariant, ToTokens};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Attribute, DeriveInput, Generics, Ident, LitStr};
+
+#[derive(FromDeriveInput, Debug)]
+#[darling(supports(struct_any, enum_any))]
+struct SanitizedDebugReceiver {
+    ident: Ident,
+    generics: Generics,
+    data: ast::Data<SanitizedDebugVariant, SanitizedDebugField>,
+}
+
+#[derive(FromVariant, Debug)]
+struct SanitizedDebugVariant {
+    ident: Ident,
+    fields: ast::Fields<SanitizedDebugField>,
+}
+
+#[derive(FromField, Debug)]
+#[darling(forward_attrs(sensitive))]
+struct SanitizedDebugField {
+    ident: Option<Ident>,
+    attrs: Vec<Attribute>,
+}
+
+impl SanitizedDebugField {
+    fn is_sensitive(&self) -> bool {
+        !self.attrs.is_empty()
+    }
+}
+
+/// The body formatting the fields of a struct or variant called `name`, which are bound to
+/// variables of the same names (named fields) or `_0`, `_1`, ... (tuple fields)
+fn format_fields(name: &Ident, fields: &ast::Fields<SanitizedDebugField>) -> TokenStream {
+    let name = LitStr::new(&name.to_string(), name.span());
+    let value = |field: &SanitizedDebugField, binding: &Ident| {
+        if field.is_sensitive() {
+            quote!(&::std::format_args!("<redacted>"))
+        } else {
+            quote!(#binding)
+        }
+    };
+
+    match fields.style {
+        ast::Style::Struct => {
+            let fields = fields.iter().map(|field| {
+                let ident = field.ident.as_ref().expect("named fields have names");
+                let label = LitStr::new(&ident.to_string(), ident.span());
+                let value = value(field, ident);
+                quote!(.field(#label, #value))
+            });
+            quote!(f.debug_struct(#name) #(#fields)* .finish())
+        }
+        ast::Style::Tuple => {
+            let fields = fields.iter().enumerate().map(|(index, field)| {
+                let value = value(field, &format_ident!("_{}", index));
+                quote!(.field(#value))
+            });
+            quote!(f.debug_tuple(#name) #(#fields)* .finish())
+        }
+        ast::Style::Unit => quote!(f.write_str(#name)),
+    }
+}
+
+/// The pattern binding the fields of `path`, skipping the sensitive ones
+fn pattern(path: TokenStream, fields: &ast::Fields<SanitizedDebugField>) -> TokenStream {
+    match fields.style {
+        ast::Style::Struct => {
+            let fields = fields.iter().map(|field| {
+                let ident = field.ident.as_ref().expect("named fields have names");
+                if field.is_sensitive() {
+                    quote!(#ident: _)
+                } else {
+                    quote!(#ident)
+                }
+            });
+            quote!(#path { #(#fields),* })
+        }
+        ast::Style::Tuple => {
+            let fields = fields.iter().enumerate().map(|(index, field)| {
+                if field.is_sensitive() {
+                    quote!(_)
+                } else {
+                    format_ident!("_{}", index).into_token_stream()
+                }
+            });
+            quote!(#path(#(#fields),*))
+        }
+        ast::Style::Unit => path,
+    }
+}
+
+impl ToTokens for SanitizedDebugReceiver {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = &self.ident;
+
+        let body = match &self.data {
+            ast::Data::Struct(fields) => {
+                let pattern = pattern(quote!(Self), fields);
+                let format = format_fields(ident, fields);
+                quote! {
+                    let #pattern = self;
+                    #format
+                }
+            }
+            ast::Data::Enum(variants) => {
+                let arms = variants.iter().map(|variant| {
+                    let name = &variant.ident;
+                    let pattern = pattern(quote!(Self::#name), &variant.fields);
+                    let format = format_fields(name, &variant.fields);
+                    quote!(#pattern => #format)
+                });
+                quote! {
+                    match self {
+                        #(#arms,)*
+                    }
+                }
+            }
+        };
+
+        // like the standard derive, require the type parameters to be Debug
+        let mut generics = self.generics.clone();
+        for param in generics.type_params_mut() {
+            param.bounds.push(parse_quote!(::std::fmt::Debug));
+        }
+        let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+        tokens.extend(quote! {
+            impl #impl_generics ::std::fmt::Debug for #ident #type_generics #where_clause {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    #body
+                }
+            }
+        });
+    }
+}
+
+pub(crate) fn sanitized_debug_impl(input: &DeriveInput) -> TokenStream {
+    let input = match SanitizedDebugReceiver::from_derive_input(input) {
+        Ok(v) => v,
+        Err(e) => {
+            return e.write_errors();
+        }
+    };
+
+    quote!(#input)
+}
+
+#[cfg(test)]
+mod tests {
+    use darling::FromDeriveInput;
+    use quote::quote;
+    use syn::parse_str;
+
+    use super::SanitizedDebugReceiver;
+
+    #[test]
+    fn sensitive_fields_are_redacted() {
+        let input = r#"
+            #[derive(SanitizedDebug)]
+            pub enum Event {
+                SignIn {
+                    user: String,
+                    #[sensitive]
+                    password: String,
+                },
+                Token(#[sensitive] String, u64),
+                SignOut,
+            }
+        "#;
+        let input = parse_str(input).unwrap();
+        let input = SanitizedDebugReceiver::from_derive_input(&input).unwrap();
+
+        let actual = quote!(#input);
+
+        insta::assert_snapshot!(pretty_print(&actual), @r###"
+        impl ::std::fmt::Debug for Event {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    Self::SignIn { user, password: _ } => {
+                        f.debug_struct("SignIn")
+                            .field("user", user)
+                            .field("password", &::std::format_args!("<redacted>"))
+                            .finish()
+                    }
+                    Self::Token(_, _1) => {
+                        f.debug_tuple("Token")
+                            .field(&::std::format_args!("<redacted>"))
+                            .field(_1)
+                            .finish()
+                    }
+                    Self::SignOut => f.write_str("SignOut"),
+                }
+            }
+        }
+        "###);
+    }
+
+    #[test]
+    fn structs() {
+        let input = r#"
+            #[derive(SanitizedDebug)]
+            pub struct Session<T> {
+                pub user: T,
+                #[sensitive]
+                pub token: String,
+            }
+        "#;
+        let input = parse_str(input).unwrap();
+        let input = SanitizedDebugReceiver::from_derive_input(&input).unwrap();
+
+        let actual = quote!(#input);
+
+        insta::assert_snapshot!(pretty_print(&actual), @r###"
+        impl<T: ::std::fmt::Debug> ::std::fmt::Debug for Session<T> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let Self { user, token: _ } = self;
+                f.debug_struct("Session")
+                    .field("user", user)
+                    .field("token", &::std::format_args!("<redacted>"))
+                    .finish()
+            }
+        }
+        "###);
+    }
+
+    fn pretty_print(ts: &proc_macro2::TokenStream) -> String {
+        let file = syn::parse_file(&ts.to_string()).unwrap();
+        prettyplease::unparse(&file)
+    }
+}
@@ -0,0 +1,169 @@
+use darling::{ast, util, FromDeriveInput, FromField, ToTokens};
+use proc_macro2::TokenStream;
+use proc_macro_error::{abort, OptionExt};
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Ident, Path, Type, Visibility};
+
+#[derive(FromDeriveInput, Debug)]
+#[darling(attributes(view_model), supports(struct_named))]
+struct ViewModelStructReceiver {
+    ident: Ident,
+    vis: Visibility,
+    model: Path,
+    data: ast::Data<util::Ignored, ViewModelFieldReceiver>,
+}
+
+#[derive(FromField, Debug)]
+#[darling(attributes(view_model))]
+struct ViewModelFieldReceiver {
+    ident: Option<Ident>,
+    ty: Type,
+    computed: Option<Path>,
+    #[darling(default)]
+    reads: util::PathList,
+}
+
+impl ToTokens for ViewModelStructReceiver {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ident = &self.ident;
+        let vis = &self.vis;
+        let model = &self.model;
+        let builder = format_ident!("{}Builder", ident);
+
+        let fields = self
+            .data
+            .as_ref()
+            .take_struct()
+            .expect_or_abort("should be a struct")
+            .fields;
+
+        let mut memos = Vec::new();
+        let mut values = Vec::new();
+        for field in fields {
+            let name = field
+                .ident
+                .as_ref()
+                .expect_or_abort("fields should be named");
+
+            let Some(compute) = &field.computed else {
+                if !field.reads.is_empty() {
+                    abort!(name, "`reads` is only used by `computed` fields");
+                }
+                values.push(quote!(#name: ::std::clone::Clone::clone(&model.#name)));
+                continue;
+            };
+
+            if field.reads.is_empty() {
+                abort!(
+                    name,
+                    "computed fields should list the fields of the model they are computed from, e.g. `reads(items)`"
+                );
+            }
+            let reads: Vec<_> = field
+                .reads
+                .iter()
+                .map(|path| {
+                    path.get_ident()
+                        .cloned()
+                        .expect_or_abort("reads should list the names of fields of the model")
+                })
+                .collect();
+
+            let ty = &field.ty;
+            memos.push(quote!(#name: ::crux_core::viewmodel::Memo));
+            values.push(quote! {
+                #name: self.#name.get::<#ty>(
+                    &[#(&model.#reads),*],
+                    || #compute(#(&model.#reads),*),
+                )
+            });
+        }
+
+        let doc = format!(
+            "Builds [`{ident}`]s from the model, computing the computed fields again only when the fields of the model they read have changed"
+        );
+
+        tokens.extend(quote! {
+            #[doc = #doc]
+            #[derive(Default)]
+            #vis struct #builder {
+                #(#memos,)*
+            }
+
+            impl #builder {
+                #[doc = "The view model for the `model`"]
+                #vis fn build(&self, model: &#model) -> #ident {
+                    #ident {
+                        #(#values,)*
+                    }
+                }
+            }
+        });
+    }
+}
+
+pub(crate) fn view_model_impl(input: &DeriveInput) -> TokenStream {
+    let input = match ViewModelStructReceiver::from_derive_input(input) {
+        Ok(v) => v,
+        Err(e) => {
+            return e.write_errors();
+        }
+    };
+
+    quote!(#input)
+}
+
+#[cfg(test)]
+mod tests {
+    use darling::FromDeriveInput;
+    use quote::quote;
+    use syn::parse_str;
+
+    use super::ViewModelStructReceiver;
+
+    #[test]
+    fn computed_fields() {
+        let input = r#"
+            #[derive(ViewModelBuilder)]
+            #[view_model(model = Model)]
+            pub struct ViewModel {
+                pub count: usize,
+                #[view_model(computed = format_total, reads(items, currency))]
+                pub total: String,
+            }
+        "#;
+        let input = parse_str(input).unwrap();
+        let input = ViewModelStructReceiver::from_derive_input(&input).unwrap();
+
+        let actual = quote!(#input);
+
+        insta::assert_snapshot!(pretty_print(&actual), @r###"
+        ///Builds [`ViewModel`]s from the model, computing the computed fields again only when the fields of the model they read have changed
+        #[derive(Default)]
+        pub struct ViewModelBuilder {
+            total: ::crux_core::viewmodel::Memo,
+        }
+        impl ViewModelBuilder {
+            ///The view model for the `model`
+            pub fn build(&self, model: &Model) -> ViewModel {
+                ViewModel {
+                    count: ::std::clone::Clone::clone(&model.count),
+                    total: self
+                        .total
+                        .get::<
+                            String,
+                        >(
+                            &[&model.items, &model.currency],
+                            || format_total(&model.items, &model.currency),
+                        ),
+                }
+            }
+        }
+        "###);
+    }
+
+    fn pretty_print(ts: &proc_macro2::TokenStream) -> String {
+        let file = syn::parse_file(&ts.to_string()).unwrap();
+        prettyplease::unparse(&file)
+    }
+}
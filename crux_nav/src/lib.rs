@@ -0,0 +1,205 @@
+//! Typed navigation for Crux apps
+//!
+//! `crux_nav` allows Crux apps to ask the Shell to push, pop or replace screens. The screens
+//! are described by a route type defined by the app, typically an enum, which is included in
+//! the generated types along with the rest of the capability's types, so both sides agree on
+//! the routes and their parameters.
+//!
+//! The route type is a type parameter of [`Navigation`], but Crux capabilities have to be
+//! generic over just the event type, so declare a type alias fixing the route type, and use it
+//! in the capabilities:
+//!
+//! ```rust
+//! # use crux_core::macros::Effect;
+//! # use serde::{Deserialize, Serialize};
+//! # #[derive(Serialize, Deserialize, Debug)]
+//! # pub enum Event {}
+//! #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+//! pub enum Route {
+//!     Home,
+//!     Item { id: u32 },
+//! }
+//!
+//! pub type Navigation<Ev> = crux_nav::Navigation<Ev, Route>;
+//!
+//! #[derive(Effect)]
+//! pub struct Capabilities {
+//!     pub navigation: Navigation<Event>,
+//! }
+//! ```
+//!
+//! Deep links arrive in the Shell, which can parse them into a route and send it to the app
+//! in an event, so the app decides what to do with them, like with any other user input.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crux_core::capability::{CapabilityContext, Operation};
+
+/// A type describing the screens of an app, typically an enum
+pub trait Route: Serialize + DeserializeOwned + Clone + PartialEq + Send + 'static {}
+
+impl<T> Route for T where T: Serialize + DeserializeOwned + Clone + PartialEq + Send + 'static {}
+
+/// Supported operations
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum NavigationOperation<R> {
+    /// Show the screen for `route` on top of the current one
+    Push { route: R },
+    /// Go back to the previous screen
+    Pop,
+    /// Show the screen for `route` instead of the current one
+    Replace { route: R },
+}
+
+/// The outcome of a navigation, as decided by the shell
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum NavigationResponse<R> {
+    /// The shell navigated as requested, and is now showing `current`,
+    /// or nothing if the last screen was popped
+    Navigated { current: Option<R> },
+    /// The shell intercepted the navigation and showed `current` instead,
+    /// e.g. a sign in screen
+    Redirected { current: R },
+    /// The shell intercepted the navigation and stayed on the current screen,
+    /// e.g. because the user chose to keep editing
+    Cancelled,
+}
+
+impl<R> NavigationResponse<R> {
+    /// The screen the shell is showing after the navigation, if it changed
+    pub fn current(&self) -> Option<&R> {
+        match self {
+            NavigationResponse::Navigated { current } => current.as_ref(),
+            NavigationResponse::Redirected { current } => Some(current),
+            NavigationResponse::Cancelled => None,
+        }
+    }
+}
+
+impl<R> Operation for NavigationOperation<R>
+where
+    R: Route,
+{
+    type Output = NavigationResponse<R>;
+}
+
+pub struct Navigation<Ev, R>
+where
+    R: Route,
+{
+    context: CapabilityContext<NavigationOperation<R>, Ev>,
+}
+
+impl<Ev, R> crux_core::Capability<Ev> for Navigation<Ev, R>
+where
+    R: Route,
+{
+    type Operation = NavigationOperation<R>;
+
+    type MappedSelf<MappedEv> = Navigation<MappedEv, R>;
+
+    fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+    where
+        F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+        Ev: 'static,
+        NewEv: 'static + Send,
+    {
+        Navigation::new(self.context.map_event(f))
+    }
+
+    #[cfg(feature = "typegen")]
+    fn register_types(generator: &mut crux_core::typegen::TypeGen) -> crux_core::typegen::Result {
+        generator.register_type::<R>()?;
+        generator.register_type::<Self::Operation>()?;
+        generator.register_type::<<Self::Operation as Operation>::Output>()?;
+        Ok(())
+    }
+}
+
+impl<Ev, R> Clone for Navigation<Ev, R>
+where
+    R: Route,
+{
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+        }
+    }
+}
+
+impl<Ev, R> Navigation<Ev, R>
+where
+    Ev: 'static,
+    R: Route,
+{
+    pub fn new(context: CapabilityContext<NavigationOperation<R>, Ev>) -> Self {
+        Self { context }
+    }
+
+    /// Ask the shell to show the screen for `route` on top of the current one.
+    /// Will dispatch the event with the shell's [`NavigationResponse`] as payload.
+    pub fn push<F>(&self, route: R, make_event: F)
+    where
+        F: FnOnce(NavigationResponse<R>) -> Ev + Send + Sync + 'static,
+    {
+        self.navigate(NavigationOperation::Push { route }, make_event);
+    }
+
+    /// Ask the shell to show the screen for `route` on top of the current one, while in an
+    /// async context. This is used together with [`crux_core::compose::Compose`].
+    pub async fn push_async(&self, route: R) -> NavigationResponse<R> {
+        self.context
+            .request_from_shell(NavigationOperation::Push { route })
+            .await
+    }
+
+    /// Ask the shell to go back to the previous screen.
+    /// Will dispatch the event with the shell's [`NavigationResponse`] as payload.
+    pub fn pop<F>(&self, make_event: F)
+    where
+        F: FnOnce(NavigationResponse<R>) -> Ev + Send + Sync + 'static,
+    {
+        self.navigate(NavigationOperation::Pop, make_event);
+    }
+
+    /// Ask the shell to go back to the previous screen, while in an async context.
+    /// This is used together with [`crux_core::compose::Compose`].
+    pub async fn pop_async(&self) -> NavigationResponse<R> {
+        self.context
+            .request_from_shell(NavigationOperation::Pop)
+            .await
+    }
+
+    /// Ask the shell to show the screen for `route` instead of the current one.
+    /// Will dispatch the event with the shell's [`NavigationResponse`] as payload.
+    pub fn replace<F>(&self, route: R, make_event: F)
+    where
+        F: FnOnce(NavigationResponse<R>) -> Ev + Send + Sync + 'static,
+    {
+        self.navigate(NavigationOperation::Replace { route }, make_event);
+    }
+
+    /// Ask the shell to show the screen for `route` instead of the current one, while in an
+    /// async context. This is used together with [`crux_core::compose::Compose`].
+    pub async fn replace_async(&self, route: R) -> NavigationResponse<R> {
+        self.context
+            .request_from_shell(NavigationOperation::Replace { route })
+            .await
+    }
+
+    fn navigate<F>(&self, operation: NavigationOperation<R>, make_event: F)
+    where
+        F: FnOnce(NavigationResponse<R>) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = context.request_from_shell(operation).await;
+                context.update_app(make_event(response));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,196 @@
+use crux_core::{macros::Effect, render::Render, testing::AppTester};
+use serde::{Deserialize, Serialize};
+
+use crate::{NavigationOperation, NavigationResponse};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Route {
+    Home,
+    Item { id: u32 },
+    SignIn,
+}
+
+pub type Navigation<Ev> = crate::Navigation<Ev, Route>;
+
+#[derive(Default)]
+pub struct App;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Event {
+    OpenItem(u32),
+    Back,
+    GoHome,
+
+    Navigated(NavigationResponse<Route>),
+}
+
+#[derive(Debug, Default)]
+pub struct Model {
+    pub current: Option<Route>,
+    pub cancelled: bool,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ViewModel {
+    pub current: Option<Route>,
+}
+
+impl crux_core::App for App {
+    type Event = Event;
+    type Model = Model;
+    type ViewModel = ViewModel;
+
+    type Capabilities = Capabilities;
+
+    fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+        match event {
+            Event::OpenItem(id) => caps.navigation.push(Route::Item { id }, Event::Navigated),
+            Event::Back => caps.navigation.pop(Event::Navigated),
+            Event::GoHome => caps.navigation.replace(Route::Home, Event::Navigated),
+            Event::Navigated(NavigationResponse::Cancelled) => model.cancelled = true,
+            Event::Navigated(response) => {
+                model.current = response.current().cloned();
+                caps.render.render();
+            }
+        }
+    }
+
+    fn view(&self, model: &Self::Model) -> Self::ViewModel {
+        ViewModel {
+            current: model.current.clone(),
+        }
+    }
+}
+
+#[derive(Effect)]
+#[cfg_attr(feature = "typegen", derive(crux_core::macros::Export))]
+pub struct Capabilities {
+    pub navigation: Navigation<Event>,
+    pub render: Render<Event>,
+}
+
+#[test]
+fn push() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::OpenItem(7), &mut model)
+        .expect_one_effect()
+        .expect_navigation();
+
+    assert_eq!(
+        request.operation,
+        NavigationOperation::Push {
+            route: Route::Item { id: 7 }
+        }
+    );
+
+    let _update = app.resolve_to_event_then_update(
+        request,
+        NavigationResponse::Navigated {
+            current: Some(Route::Item { id: 7 }),
+        },
+        &mut model,
+    );
+
+    assert_eq!(model.current, Some(Route::Item { id: 7 }));
+}
+
+#[test]
+fn pop() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Back, &mut model)
+        .expect_one_effect()
+        .expect_navigation();
+
+    assert_eq!(request.operation, NavigationOperation::Pop);
+
+    let _update = app.resolve_to_event_then_update(
+        request,
+        NavigationResponse::Navigated {
+            current: Some(Route::Home),
+        },
+        &mut model,
+    );
+
+    assert_eq!(model.current, Some(Route::Home));
+}
+
+#[test]
+fn replace() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::GoHome, &mut model)
+        .expect_one_effect()
+        .expect_navigation();
+
+    assert_eq!(
+        request.operation,
+        NavigationOperation::Replace { route: Route::Home }
+    );
+}
+
+#[test]
+fn redirected() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::OpenItem(7), &mut model)
+        .expect_one_effect()
+        .expect_navigation();
+
+    let _update = app.resolve_to_event_then_update(
+        request,
+        NavigationResponse::Redirected {
+            current: Route::SignIn,
+        },
+        &mut model,
+    );
+
+    assert_eq!(model.current, Some(Route::SignIn));
+}
+
+#[test]
+fn cancelled() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model {
+        current: Some(Route::Home),
+        ..Default::default()
+    };
+
+    let request = &mut app
+        .update(Event::OpenItem(7), &mut model)
+        .expect_one_effect()
+        .expect_navigation();
+
+    let _update =
+        app.resolve_to_event_then_update(request, NavigationResponse::Cancelled, &mut model);
+
+    assert!(model.cancelled);
+    assert_eq!(model.current, Some(Route::Home));
+}
+
+#[cfg(feature = "typegen")]
+#[test]
+fn route_type_is_generated() {
+    use crux_core::typegen::{State, TypeGen};
+
+    let mut gen = TypeGen::new();
+    gen.register_app::<App>().expect("to register the app");
+
+    let State::Registering(tracer, _) = gen.state else {
+        panic!("expected to still be registering");
+    };
+    let registry = tracer.registry().expect("to get the registry");
+
+    assert!(registry.contains_key("Route"));
+    assert!(registry.contains_key("NavigationOperation"));
+    assert!(registry.contains_key("NavigationResponse"));
+}
@@ -0,0 +1,155 @@
+//! Network connectivity for Crux apps
+//!
+//! Whether the device is online, and over which kind of connection, is only known to the
+//! Shell. `crux_net_status` lets the app ask for the current [`NetStatusResponse`], or
+//! subscribe to every change, so that an offline-first app can sync when it comes back
+//! online, or avoid large transfers on a metered connection, without shell specific code.
+//!
+//! The Shell responds to a subscription straight away with the current status, and then
+//! whenever it changes, for as long as the app is running.
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crux_core::capability::{CapabilityContext, Operation};
+
+/// Supported operations
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum NetStatusOperation {
+    /// Respond with the current status
+    Get,
+    /// Respond with the current status, and then again whenever it changes
+    Subscribe,
+}
+
+/// The kind of connection the device is using
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Connection {
+    /// No connection
+    Offline,
+    Wifi,
+    Cellular,
+    /// A wired connection
+    Ethernet,
+    /// A connection the shell can't classify, e.g. a VPN, or any connection on platforms
+    /// which don't tell, like the web
+    Other,
+}
+
+/// The device's connectivity, reported by the shell
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct NetStatusResponse {
+    pub connection: Connection,
+    /// Whether the user may be charged for the data, e.g. on a cellular network or a
+    /// personal hotspot. Always `false` when offline.
+    pub metered: bool,
+}
+
+impl NetStatusResponse {
+    /// Whether the device has a connection, though it may not reach the internet
+    pub fn is_online(&self) -> bool {
+        self.connection != Connection::Offline
+    }
+}
+
+impl Operation for NetStatusOperation {
+    type Output = NetStatusResponse;
+}
+
+pub struct NetStatus<Ev> {
+    context: CapabilityContext<NetStatusOperation, Ev>,
+}
+
+impl<Ev> crux_core::Capability<Ev> for NetStatus<Ev> {
+    type Operation = NetStatusOperation;
+
+    type MappedSelf<MappedEv> = NetStatus<MappedEv>;
+
+    fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+    where
+        F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+        Ev: 'static,
+        NewEv: 'static + Send,
+    {
+        NetStatus::new(self.context.map_event(f))
+    }
+
+    #[cfg(feature = "typegen")]
+    fn register_types(generator: &mut crux_core::typegen::TypeGen) -> crux_core::typegen::Result {
+        generator.register_type::<Connection>()?;
+        generator.register_type::<Self::Operation>()?;
+        generator.register_type::<<Self::Operation as Operation>::Output>()?;
+        Ok(())
+    }
+}
+
+impl<Ev> Clone for NetStatus<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+        }
+    }
+}
+
+impl<Ev> NetStatus<Ev>
+where
+    Ev: 'static,
+{
+    pub fn new(context: CapabilityContext<NetStatusOperation, Ev>) -> Self {
+        Self { context }
+    }
+
+    /// Ask the shell for the current status.
+    /// Will dispatch the event with the [`NetStatusResponse`] as payload.
+    pub fn get<F>(&self, make_event: F)
+    where
+        F: FnOnce(NetStatusResponse) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            let this = self.clone();
+
+            async move {
+                context.update_app(make_event(this.get_async().await));
+            }
+        });
+    }
+
+    /// Ask the shell for the current status, while in an async context. This is used
+    /// together with [`crux_core::compose::Compose`].
+    pub async fn get_async(&self) -> NetStatusResponse {
+        self.context
+            .request_from_shell(NetStatusOperation::Get)
+            .await
+    }
+
+    /// Subscribe to the status. Will dispatch the event with the current
+    /// [`NetStatusResponse`] as payload, and again whenever it changes.
+    pub fn subscribe<F>(&self, make_event: F)
+    where
+        F: Fn(NetStatusResponse) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            let mut statuses = self.subscribe_async();
+
+            async move {
+                while let Some(status) = statuses.next().await {
+                    context.update_app(make_event(status));
+                }
+            }
+        });
+    }
+
+    /// Subscribe to the status, while in an async context. This is used together with
+    /// [`crux_core::compose::Compose`].
+    ///
+    /// Returns a stream of the current [`NetStatusResponse`], followed by every change.
+    pub fn subscribe_async(&self) -> impl Stream<Item = NetStatusResponse> {
+        self.context
+            .stream_from_shell(NetStatusOperation::Subscribe)
+    }
+}
+
+#[cfg(test)]
+mod tests;
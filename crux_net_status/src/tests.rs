@@ -0,0 +1,141 @@
+use crux_core::{macros::Effect, render::Render, testing::AppTester};
+use serde::{Deserialize, Serialize};
+
+use crate::{Connection, NetStatus, NetStatusOperation, NetStatusResponse};
+
+#[derive(Default)]
+pub struct App;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Event {
+    Check,
+    Watch,
+
+    StatusChanged(NetStatusResponse),
+}
+
+#[derive(Debug, Default)]
+pub struct Model {
+    pub status: Option<NetStatusResponse>,
+    pub pending_changes: usize,
+    pub syncs: usize,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ViewModel {
+    pub online: bool,
+}
+
+impl crux_core::App for App {
+    type Event = Event;
+    type Model = Model;
+    type ViewModel = ViewModel;
+
+    type Capabilities = Capabilities;
+
+    fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+        match event {
+            Event::Check => caps.net_status.get(Event::StatusChanged),
+            Event::Watch => caps.net_status.subscribe(Event::StatusChanged),
+            Event::StatusChanged(status) => {
+                // sync the offline changes, but not over a metered connection
+                if status.is_online() && !status.metered && model.pending_changes > 0 {
+                    model.pending_changes = 0;
+                    model.syncs += 1;
+                }
+                model.status = Some(status);
+                caps.render.render();
+            }
+        }
+    }
+
+    fn view(&self, model: &Self::Model) -> Self::ViewModel {
+        ViewModel {
+            online: model.status.map_or(false, |status| status.is_online()),
+        }
+    }
+}
+
+#[derive(Effect)]
+#[cfg_attr(feature = "typegen", derive(crux_core::macros::Export))]
+pub struct Capabilities {
+    pub net_status: NetStatus<Event>,
+    pub render: Render<Event>,
+}
+
+const OFFLINE: NetStatusResponse = NetStatusResponse {
+    connection: Connection::Offline,
+    metered: false,
+};
+
+const CELLULAR: NetStatusResponse = NetStatusResponse {
+    connection: Connection::Cellular,
+    metered: true,
+};
+
+const WIFI: NetStatusResponse = NetStatusResponse {
+    connection: Connection::Wifi,
+    metered: false,
+};
+
+#[test]
+fn get() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Check, &mut model)
+        .expect_one_effect()
+        .expect_net_status();
+
+    assert_eq!(request.operation, NetStatusOperation::Get);
+
+    let _update = app.resolve_to_event_then_update(request, WIFI, &mut model);
+
+    assert!(app.view(&model).online);
+}
+
+#[test]
+fn subscribe() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model {
+        pending_changes: 3,
+        ..Default::default()
+    };
+
+    let request = &mut app
+        .update(Event::Watch, &mut model)
+        .expect_one_effect()
+        .expect_net_status();
+
+    assert_eq!(request.operation, NetStatusOperation::Subscribe);
+
+    let _update = app.resolve_to_event_then_update(request, OFFLINE, &mut model);
+    assert!(!app.view(&model).online);
+
+    let _update = app.resolve_to_event_then_update(request, CELLULAR, &mut model);
+    assert!(app.view(&model).online);
+    assert_eq!(model.syncs, 0);
+
+    let _update = app.resolve_to_event_then_update(request, WIFI, &mut model);
+    assert_eq!(model.syncs, 1);
+    assert_eq!(model.pending_changes, 0);
+}
+
+#[cfg(feature = "typegen")]
+#[test]
+fn net_status_types_are_generated() {
+    use crux_core::typegen::{State, TypeGen};
+
+    let mut gen = TypeGen::new();
+    gen.register_app::<App>().expect("to register the app");
+
+    let State::Registering(tracer, _) = gen.state else {
+        panic!("expected to still be registering");
+    };
+    let registry = tracer.registry().expect("to get the registry");
+
+    assert!(registry.contains_key("NetStatusOperation"));
+    assert!(registry.contains_key("NetStatusResponse"));
+    assert!(registry.contains_key("Connection"));
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Error type for SecureStore operations
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Error)]
+#[serde(rename_all = "camelCase")]
+pub enum SecureStoreError {
+    /// The user dismissed the authentication prompt
+    #[error("the user cancelled authentication")]
+    UserCancelled,
+    /// The user tried to authenticate, but wasn't recognised
+    #[error("authentication failed")]
+    AuthenticationFailed,
+    /// The device can't protect the item as asked, e.g. biometrics are required but none
+    /// are enrolled, or a passcode is required but none is set
+    #[error("not available: {message}")]
+    NotAvailable { message: String },
+    /// The item can't be read until the device is unlocked
+    #[error("the device is locked")]
+    Locked,
+    #[error("other error: {message}")]
+    Other { message: String },
+}
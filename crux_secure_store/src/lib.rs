@@ -0,0 +1,362 @@
+//! Secure storage for Crux apps
+//!
+//! `crux_secure_store` allows Crux apps to keep secrets, like auth tokens, in the platform's
+//! secure storage (the Keychain on Apple platforms, the Keystore on Android) by asking the
+//! Shell to store them, rather than in a generic key-value store. Each item can require the
+//! device to be unlocked, or the user to authenticate, before it can be read.
+
+pub mod error;
+
+use serde::{Deserialize, Serialize};
+
+use crux_core::capability::{CapabilityContext, Operation};
+
+use error::SecureStoreError;
+
+/// When an item can be read, which maps to the Keychain's `kSecAttrAccessible` values, or
+/// the Keystore's unlocked device requirement
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum Accessibility {
+    /// Only while the device is unlocked
+    #[default]
+    WhenUnlocked,
+    /// After the device has been unlocked once since it started, e.g. for background work
+    AfterFirstUnlock,
+    /// Only while the device is unlocked, and only if it has a passcode set. The item is
+    /// removed if the passcode is
+    WhenPasscodeSet,
+}
+
+/// Whether the user has to authenticate every time an item is read
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum Authentication {
+    /// No authentication beyond the `Accessibility` of the item
+    #[default]
+    None,
+    /// The user must authenticate with biometrics (e.g. Face ID or a fingerprint).
+    /// The item is invalidated if the enrolled biometrics change
+    Biometric,
+    /// The user must authenticate with biometrics, or the device passcode
+    BiometricOrPasscode,
+}
+
+/// How the Shell should protect an item it stores
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct SecureStoreOptions {
+    pub accessibility: Accessibility,
+    pub authentication: Authentication,
+    /// Keep the item on this device, out of backups and keychain sync
+    pub this_device_only: bool,
+}
+
+/// Supported operations
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SecureStoreOperation {
+    /// Read the secret stored under a key. If the item requires authentication, the Shell
+    /// shows the `prompt`, e.g. "Sign in to your account", or the platform's default prompt
+    /// if it's empty
+    Get { key: String, prompt: String },
+    /// Write a secret under a key, protected as the options say, replacing any previous one
+    Set {
+        key: String,
+        #[serde(with = "serde_bytes")]
+        value: Vec<u8>,
+        options: SecureStoreOptions,
+    },
+    /// Remove a key and its secret
+    Delete { key: String },
+}
+
+// secrets are left out of the debug output, which ends up in logs and test snapshots
+impl std::fmt::Debug for SecureStoreOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecureStoreOperation::Get { key, prompt } => f
+                .debug_struct("Get")
+                .field("key", key)
+                .field("prompt", prompt)
+                .finish(),
+            SecureStoreOperation::Set {
+                key,
+                value,
+                options,
+            } => f
+                .debug_struct("Set")
+                .field("key", key)
+                .field("value", &format_args!("<{} bytes>", value.len()))
+                .field("options", options)
+                .finish(),
+            SecureStoreOperation::Delete { key } => {
+                f.debug_struct("Delete").field("key", key).finish()
+            }
+        }
+    }
+}
+
+/// The secret stored under a key.
+///
+/// `Secret::None` is used to represent the absence of a secret.
+///
+/// Note: we can't use `Option` here because generics are not currently
+/// supported across the FFI boundary, when using the builtin typegen.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Secret {
+    None,
+    Bytes(#[serde(with = "serde_bytes")] Vec<u8>),
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Secret::None => f.write_str("None"),
+            Secret::Bytes(bytes) => write!(f, "Bytes(<{} bytes>)", bytes.len()),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Secret {
+    fn from(bytes: Vec<u8>) -> Self {
+        Secret::Bytes(bytes)
+    }
+}
+
+impl From<Secret> for Option<Vec<u8>> {
+    fn from(secret: Secret) -> Option<Vec<u8>> {
+        match secret {
+            Secret::None => None,
+            Secret::Bytes(bytes) => Some(bytes),
+        }
+    }
+}
+
+impl From<Option<Vec<u8>>> for Secret {
+    fn from(bytes: Option<Vec<u8>>) -> Self {
+        match bytes {
+            None => Secret::None,
+            Some(bytes) => Secret::Bytes(bytes),
+        }
+    }
+}
+
+/// The result of an operation on the secure store.
+///
+/// Note: we can't use `Result` here because generics are not currently
+/// supported across the FFI boundary, when using the builtin typegen.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum SecureStoreResult {
+    Ok { response: SecureStoreResponse },
+    Err { error: SecureStoreError },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum SecureStoreResponse {
+    /// Response to a `SecureStoreOperation::Get`,
+    /// returning the secret stored under the key, which may be empty
+    Get { secret: Secret },
+    /// Response to a `SecureStoreOperation::Set`, once the secret has been stored
+    Set,
+    /// Response to a `SecureStoreOperation::Delete`, once the secret has been removed,
+    /// or if there was none
+    Delete,
+}
+
+impl Operation for SecureStoreOperation {
+    type Output = SecureStoreResult;
+}
+
+pub struct SecureStore<Ev> {
+    context: CapabilityContext<SecureStoreOperation, Ev>,
+}
+
+impl<Ev> crux_core::Capability<Ev> for SecureStore<Ev> {
+    type Operation = SecureStoreOperation;
+
+    type MappedSelf<MappedEv> = SecureStore<MappedEv>;
+
+    fn map_event<F, NewEv>(&self, f: F) -> Self::MappedSelf<NewEv>
+    where
+        F: Fn(NewEv) -> Ev + Send + Sync + 'static,
+        Ev: 'static,
+        NewEv: 'static + Send,
+    {
+        SecureStore::new(self.context.map_event(f))
+    }
+
+    #[cfg(feature = "typegen")]
+    fn register_types(generator: &mut crux_core::typegen::TypeGen) -> crux_core::typegen::Result {
+        generator.register_type::<Accessibility>()?;
+        generator.register_type::<Authentication>()?;
+        generator.register_type::<SecureStoreOptions>()?;
+        generator.register_type::<Secret>()?;
+        generator.register_type::<SecureStoreResponse>()?;
+        generator.register_type::<SecureStoreError>()?;
+        generator.register_type::<Self::Operation>()?;
+        generator.register_type::<<Self::Operation as Operation>::Output>()?;
+        Ok(())
+    }
+}
+
+impl<Ev> Clone for SecureStore<Ev> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+        }
+    }
+}
+
+impl<Ev> SecureStore<Ev>
+where
+    Ev: 'static,
+{
+    pub fn new(context: CapabilityContext<SecureStoreOperation, Ev>) -> Self {
+        Self { context }
+    }
+
+    /// Read the secret under `key`, showing the `prompt` if the item requires the user to
+    /// authenticate. Will dispatch the event with the secret, or `None` if there isn't one.
+    /// If the user cancels the prompt, the result is a [`SecureStoreError::UserCancelled`].
+    pub fn get<F>(&self, key: String, prompt: String, make_event: F)
+    where
+        F: FnOnce(Result<Option<Vec<u8>>, SecureStoreError>) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = get(&context, key, prompt).await;
+                context.update_app(make_event(response));
+            }
+        });
+    }
+
+    /// Read the secret under `key`, while in an async context. This is used together with
+    /// [`crux_core::compose::Compose`].
+    ///
+    /// Returns the secret stored under the key, or `None` if the key is not present.
+    pub async fn get_async(
+        &self,
+        key: String,
+        prompt: String,
+    ) -> Result<Option<Vec<u8>>, SecureStoreError> {
+        get(&self.context, key, prompt).await
+    }
+
+    /// Store `value` under `key`, protected as the `options` say, will dispatch the event
+    /// with `Ok(())` once it has been stored
+    pub fn set<F>(&self, key: String, value: Vec<u8>, options: SecureStoreOptions, make_event: F)
+    where
+        F: FnOnce(Result<(), SecureStoreError>) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = set(&context, key, value, options).await;
+                context.update_app(make_event(response));
+            }
+        });
+    }
+
+    /// Store `value` under `key`, while in an async context. This is used together with
+    /// [`crux_core::compose::Compose`].
+    pub async fn set_async(
+        &self,
+        key: String,
+        value: Vec<u8>,
+        options: SecureStoreOptions,
+    ) -> Result<(), SecureStoreError> {
+        set(&self.context, key, value, options).await
+    }
+
+    /// Remove a `key` and its secret, will dispatch the event with `Ok(())` once it's gone
+    pub fn delete<F>(&self, key: String, make_event: F)
+    where
+        F: FnOnce(Result<(), SecureStoreError>) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            async move {
+                let response = delete(&context, key).await;
+                context.update_app(make_event(response));
+            }
+        });
+    }
+
+    /// Remove a `key` and its secret, while in an async context. This is used together with
+    /// [`crux_core::compose::Compose`].
+    pub async fn delete_async(&self, key: String) -> Result<(), SecureStoreError> {
+        delete(&self.context, key).await
+    }
+}
+
+async fn get<Ev: 'static>(
+    context: &CapabilityContext<SecureStoreOperation, Ev>,
+    key: String,
+    prompt: String,
+) -> Result<Option<Vec<u8>>, SecureStoreError> {
+    context
+        .request_from_shell(SecureStoreOperation::Get { key, prompt })
+        .await
+        .unwrap_get()
+}
+
+async fn set<Ev: 'static>(
+    context: &CapabilityContext<SecureStoreOperation, Ev>,
+    key: String,
+    value: Vec<u8>,
+    options: SecureStoreOptions,
+) -> Result<(), SecureStoreError> {
+    context
+        .request_from_shell(SecureStoreOperation::Set {
+            key,
+            value,
+            options,
+        })
+        .await
+        .unwrap_set()
+}
+
+async fn delete<Ev: 'static>(
+    context: &CapabilityContext<SecureStoreOperation, Ev>,
+    key: String,
+) -> Result<(), SecureStoreError> {
+    context
+        .request_from_shell(SecureStoreOperation::Delete { key })
+        .await
+        .unwrap_delete()
+}
+
+impl SecureStoreResult {
+    fn unwrap_get(self) -> Result<Option<Vec<u8>>, SecureStoreError> {
+        match self {
+            SecureStoreResult::Ok { response } => match response {
+                SecureStoreResponse::Get { secret } => Ok(secret.into()),
+                _ => panic!(
+                    "attempt to convert SecureStoreResponse other than Get to Option<Vec<u8>>"
+                ),
+            },
+            SecureStoreResult::Err { error } => Err(error),
+        }
+    }
+
+    fn unwrap_set(self) -> Result<(), SecureStoreError> {
+        match self {
+            SecureStoreResult::Ok { response } => match response {
+                SecureStoreResponse::Set => Ok(()),
+                _ => panic!("attempt to convert SecureStoreResponse other than Set to ()"),
+            },
+            SecureStoreResult::Err { error } => Err(error),
+        }
+    }
+
+    fn unwrap_delete(self) -> Result<(), SecureStoreError> {
+        match self {
+            SecureStoreResult::Ok { response } => match response {
+                SecureStoreResponse::Delete => Ok(()),
+                _ => panic!("attempt to convert SecureStoreResponse other than Delete to ()"),
+            },
+            SecureStoreResult::Err { error } => Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
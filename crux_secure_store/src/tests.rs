@@ -0,0 +1,263 @@
+use crux_core::{macros::Effect, render::Render, testing::AppTester};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::SecureStoreError, Accessibility, Authentication, Secret, SecureStore,
+    SecureStoreOperation, SecureStoreOptions, SecureStoreResponse, SecureStoreResult,
+};
+
+#[derive(Default)]
+pub struct App;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Event {
+    SignIn(String),
+    Restore,
+    SignOut,
+
+    Stored(Result<(), SecureStoreError>),
+    Restored(Result<Option<Vec<u8>>, SecureStoreError>),
+}
+
+#[derive(Debug, Default)]
+pub struct Model {
+    pub token: Option<String>,
+    pub cancelled: bool,
+    pub error: Option<SecureStoreError>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ViewModel {
+    pub signed_in: bool,
+}
+
+const TOKEN_KEY: &str = "auth_token";
+
+impl crux_core::App for App {
+    type Event = Event;
+    type Model = Model;
+    type ViewModel = ViewModel;
+
+    type Capabilities = Capabilities;
+
+    fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+        match event {
+            Event::SignIn(token) => {
+                let options = SecureStoreOptions {
+                    authentication: Authentication::Biometric,
+                    this_device_only: true,
+                    ..Default::default()
+                };
+                caps.secure_store.set(
+                    TOKEN_KEY.to_string(),
+                    token.clone().into_bytes(),
+                    options,
+                    Event::Stored,
+                );
+                model.token = Some(token);
+            }
+            Event::Restore => caps.secure_store.get(
+                TOKEN_KEY.to_string(),
+                "Sign in to your account".to_string(),
+                Event::Restored,
+            ),
+            Event::SignOut => {
+                model.token = None;
+                caps.secure_store
+                    .delete(TOKEN_KEY.to_string(), Event::Stored);
+            }
+
+            Event::Stored(Ok(())) => caps.render.render(),
+            Event::Restored(Ok(token)) => {
+                model.token = token.map(|bytes| String::from_utf8(bytes).unwrap());
+                caps.render.render();
+            }
+            Event::Restored(Err(SecureStoreError::UserCancelled)) => {
+                model.cancelled = true;
+                caps.render.render();
+            }
+            Event::Stored(Err(error)) | Event::Restored(Err(error)) => {
+                model.error = Some(error);
+                caps.render.render();
+            }
+        }
+    }
+
+    fn view(&self, model: &Self::Model) -> Self::ViewModel {
+        ViewModel {
+            signed_in: model.token.is_some(),
+        }
+    }
+}
+
+#[derive(Effect)]
+pub struct Capabilities {
+    pub secure_store: SecureStore<Event>,
+    pub render: Render<Event>,
+}
+
+#[test]
+fn test_set() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::SignIn("secret".to_string()), &mut model)
+        .expect_one_effect()
+        .expect_secure_store();
+
+    assert_eq!(
+        request.operation,
+        SecureStoreOperation::Set {
+            key: TOKEN_KEY.to_string(),
+            value: b"secret".to_vec(),
+            options: SecureStoreOptions {
+                accessibility: Accessibility::WhenUnlocked,
+                authentication: Authentication::Biometric,
+                this_device_only: true,
+            },
+        }
+    );
+
+    let update = app
+        .resolve(
+            request,
+            SecureStoreResult::Ok {
+                response: SecureStoreResponse::Set,
+            },
+        )
+        .unwrap();
+    let event = update.expect_one_event();
+    let _ = app
+        .update(event, &mut model)
+        .expect_one_effect()
+        .expect_render();
+
+    assert_eq!(model.token.as_deref(), Some("secret"));
+    assert_eq!(model.error, None);
+}
+
+#[test]
+fn test_get() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Restore, &mut model)
+        .expect_one_effect()
+        .expect_secure_store();
+
+    assert_eq!(
+        request.operation,
+        SecureStoreOperation::Get {
+            key: TOKEN_KEY.to_string(),
+            prompt: "Sign in to your account".to_string(),
+        }
+    );
+
+    let _updated = app.resolve_to_event_then_update(
+        request,
+        SecureStoreResult::Ok {
+            response: SecureStoreResponse::Get {
+                secret: b"secret".to_vec().into(),
+            },
+        },
+        &mut model,
+    );
+
+    assert_eq!(model.token.as_deref(), Some("secret"));
+}
+
+#[test]
+fn test_get_missing() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model {
+        token: Some("stale".to_string()),
+        ..Default::default()
+    };
+
+    let request = &mut app
+        .update(Event::Restore, &mut model)
+        .expect_one_effect()
+        .expect_secure_store();
+
+    let _updated = app.resolve_to_event_then_update(
+        request,
+        SecureStoreResult::Ok {
+            response: SecureStoreResponse::Get {
+                secret: Secret::None,
+            },
+        },
+        &mut model,
+    );
+
+    assert_eq!(model.token, None);
+}
+
+#[test]
+fn test_get_cancelled() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model::default();
+
+    let request = &mut app
+        .update(Event::Restore, &mut model)
+        .expect_one_effect()
+        .expect_secure_store();
+
+    let _updated = app.resolve_to_event_then_update(
+        request,
+        SecureStoreResult::Err {
+            error: SecureStoreError::UserCancelled,
+        },
+        &mut model,
+    );
+
+    assert!(model.cancelled);
+    assert_eq!(model.error, None);
+}
+
+#[test]
+fn test_delete() {
+    let app = AppTester::<App, _>::default();
+    let mut model = Model {
+        token: Some("secret".to_string()),
+        ..Default::default()
+    };
+
+    let request = &mut app
+        .update(Event::SignOut, &mut model)
+        .expect_one_effect()
+        .expect_secure_store();
+
+    assert_eq!(
+        request.operation,
+        SecureStoreOperation::Delete {
+            key: TOKEN_KEY.to_string()
+        }
+    );
+
+    let _updated = app.resolve_to_event_then_update(
+        request,
+        SecureStoreResult::Err {
+            error: SecureStoreError::Locked,
+        },
+        &mut model,
+    );
+
+    assert_eq!(model.token, None);
+    assert_eq!(model.error, Some(SecureStoreError::Locked));
+}
+
+#[test]
+fn test_secrets_are_not_debug_printed() {
+    let operation = SecureStoreOperation::Set {
+        key: TOKEN_KEY.to_string(),
+        value: b"secret".to_vec(),
+        options: SecureStoreOptions::default(),
+    };
+    let secret: Secret = b"secret".to_vec().into();
+
+    assert!(!format!("{operation:?}").contains("secret"));
+    assert!(format!("{operation:?}").contains("<6 bytes>"));
+    assert_eq!(format!("{secret:?}"), "Bytes(<6 bytes>)");
+}
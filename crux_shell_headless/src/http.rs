@@ -0,0 +1,78 @@
+//! The shell side of the [`Http`](crux_http::Http) capability, making real requests.
+
+use std::io::{self, Read};
+
+use crux_http::{
+    protocol::{HttpRequest, HttpResponse, HttpResult},
+    HttpError,
+};
+use crux_simulator::Reply;
+
+/// Makes the app's HTTP requests over the network, blocking until the response arrives.
+///
+/// Responses with error statuses (4xx and 5xx) are passed to the app like any other
+/// response, as a browser or a platform HTTP client would. Clones share the same
+/// connection pool.
+#[derive(Clone)]
+pub struct HttpClient {
+    agent: ureq::Agent,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpClient {
+    pub fn new() -> Self {
+        Self {
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    /// Handle an HTTP request from the app.
+    pub fn handle(&self, request: &HttpRequest) -> Reply {
+        Reply::respond(&self.send(request))
+    }
+
+    fn send(&self, request: &HttpRequest) -> HttpResult {
+        let mut outgoing = self.agent.request(&request.method, &request.url);
+        for header in &request.headers {
+            outgoing = outgoing.set(&header.name, &header.value);
+        }
+
+        let response = match outgoing.send_bytes(&request.body) {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(error)) => return HttpResult::Err(http_error(&error)),
+        };
+
+        let mut builder = HttpResponse::status(response.status());
+        for name in response.headers_names() {
+            if let Some(value) = response.header(&name) {
+                builder.header(name.clone(), value);
+            }
+        }
+
+        let mut body = vec![];
+        if let Err(error) = response.into_reader().read_to_end(&mut body) {
+            return HttpResult::Err(HttpError::Io(error.to_string()));
+        }
+
+        HttpResult::Ok(builder.body(body).build())
+    }
+}
+
+fn http_error(error: &ureq::Transport) -> HttpError {
+    let timed_out = std::error::Error::source(error)
+        .and_then(|source| source.downcast_ref::<io::Error>())
+        .map_or(false, |error| error.kind() == io::ErrorKind::TimedOut);
+
+    match error.kind() {
+        _ if timed_out => HttpError::Timeout,
+        ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme => {
+            HttpError::Url(error.to_string())
+        }
+        _ => HttpError::Io(error.to_string()),
+    }
+}
@@ -0,0 +1,89 @@
+//! The shell side of the [`KeyValue`](crux_kv::KeyValue) capability, stored in a file.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crux_kv::KeyValueOperation;
+use crux_simulator::{kv::MemoryKv, Reply};
+
+/// A key-value store kept in memory, and saved to a JSON file after every write, so the
+/// app finds its data again the next time the shell runs. Without a file, the store
+/// starts empty every time. Clones share the same store.
+#[derive(Clone, Default)]
+pub struct FileKv {
+    store: MemoryKv,
+    path: Option<PathBuf>,
+}
+
+impl FileKv {
+    /// A store which is only kept in memory.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// A store saved to the file at `path`, starting with its contents if it exists.
+    ///
+    /// # Errors
+    ///
+    /// If the file exists but can't be read, or isn't a store saved by a `FileKv`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let store = MemoryKv::new();
+
+        match fs::read(&path) {
+            Ok(saved) => {
+                let entries: BTreeMap<String, Vec<u8>> = serde_json::from_slice(&saved)?;
+                for (key, value) in entries {
+                    store.insert(&key, value);
+                }
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error),
+        }
+
+        Ok(Self {
+            store,
+            path: Some(path),
+        })
+    }
+
+    /// The value stored under `key`, if any.
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.store.get(key)
+    }
+
+    /// Handle a key-value operation from the app, with the `id` of the request it came in.
+    /// Nothing else writes to the store, so watches never see a change.
+    ///
+    /// # Panics
+    ///
+    /// If the store can't be saved to its file.
+    pub fn handle(&self, id: u32, operation: &KeyValueOperation) -> Reply {
+        let reply = self.store.handle(id, operation);
+
+        let read_only = matches!(
+            operation,
+            KeyValueOperation::Get { .. }
+                | KeyValueOperation::Exists { .. }
+                | KeyValueOperation::ListKeys { .. }
+                | KeyValueOperation::Watch { .. }
+                | KeyValueOperation::Unwatch { .. }
+        );
+        if !read_only {
+            self.save().expect("Key-value store could not be saved.");
+        }
+
+        reply
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        fs::write(path, serde_json::to_vec(&self.store.entries())?)
+    }
+}
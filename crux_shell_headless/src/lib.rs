@@ -0,0 +1,249 @@
+//! A reference headless shell, running a Crux app from a terminal, natively or under WASI.
+//!
+//! The [`HeadlessShell`] reads commands a line at a time, e.g. from standard input: an
+//! event as JSON sends it to the app, after which the view model is printed as JSON. It
+//! drives the core through the serialized bridge, with a
+//! [`Simulator`](crux_simulator::Simulator), and handles the requests with a function you
+//! provide, which can use real implementations of the shell side of the built-in
+//! capabilities: [`HttpClient`](http::HttpClient) makes HTTP requests over the network
+//! (feature `http`), [`FileKv`](kv::FileKv) keeps a key-value store in a file (feature
+//! `kv`), and [`SystemClock`](time::SystemClock) fires timers as real time passes
+//! (feature `time`).
+//!
+//! The shell doesn't use threads or an async runtime, so it also runs under WASI, e.g.
+//! built for the `wasm32-wasip1` target without the `http` feature. HTTP requests block
+//! until the response arrives, and timers fire between commands, or while waiting.
+//!
+//! ```rust,ignore
+//! fn main() -> std::io::Result<()> {
+//!     let http = HttpClient::new();
+//!     let kv = FileKv::open("store.json")?;
+//!     let clock = SystemClock::new();
+//!
+//!     let mut shell = HeadlessShell::<Effect, App, _>::new({
+//!         let clock = clock.clone();
+//!         move |request: &Request<EffectFfi>| match &request.effect {
+//!             EffectFfi::Http(operation) => http.handle(operation),
+//!             EffectFfi::KeyValue(operation) => kv.handle(request.id.0, operation),
+//!             EffectFfi::Time(operation) => clock.handle(request.id.0, operation),
+//!             EffectFfi::Render(_) => Reply::Done,
+//!         }
+//!     })
+//!     .with_clock(clock);
+//!
+//!     shell.run(std::io::stdin().lock(), std::io::stdout())
+//! }
+//! ```
+//!
+//! Besides events, the shell understands these commands:
+//!
+//! - `:view` prints the view model
+//! - `:wait <seconds>` waits, e.g. `:wait 1.5`, delivering timers and animation frames,
+//!   then prints the view model
+//! - `:help` lists the commands
+//! - `:quit` stops the shell, like the end of the input does
+
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "kv")]
+pub mod kv;
+#[cfg(feature = "time")]
+pub mod time;
+
+use std::io::{self, BufRead, Write};
+
+use crux_core::{bridge::Request, App, Effect, WithContext};
+use crux_simulator::Simulator;
+pub use crux_simulator::{Reply, Response};
+use serde::de::DeserializeOwned;
+
+const HELP: &str = r#"Send an event as JSON, e.g. "Increment" or {"SetCount":3}, or a command:
+  :view            print the view model
+  :wait <seconds>  wait, delivering timers and animation frames
+  :help            show this help
+  :quit            stop the shell"#;
+
+/// A headless shell, running the app `A` with effect type `Eff`, and handling its
+/// requests with `H`.
+pub struct HeadlessShell<Eff, A, H>
+where
+    Eff: Effect,
+    A: App,
+{
+    simulator: Simulator<Eff, A, H>,
+    #[cfg(feature = "time")]
+    clock: Option<time::SystemClock>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command<'a> {
+    Event(&'a str),
+    View,
+    Wait(std::time::Duration),
+    Help,
+    Quit,
+}
+
+impl<Eff, A, H> HeadlessShell<Eff, A, H>
+where
+    Eff: Effect + Send + 'static,
+    Eff::Ffi: DeserializeOwned,
+    A: App,
+    A::Capabilities: WithContext<A::Event, Eff>,
+    A::Event: serde::Serialize + DeserializeOwned,
+    A::ViewModel: DeserializeOwned,
+    H: FnMut(&Request<Eff::Ffi>) -> Reply,
+{
+    /// Start a new instance of the app, with requests handled by `handler`.
+    pub fn new(handler: H) -> Self {
+        Self {
+            simulator: Simulator::new(handler),
+            #[cfg(feature = "time")]
+            clock: None,
+        }
+    }
+
+    /// Deliver the timers and animation frames of the `clock`, which should be the
+    /// one handling the app's time requests.
+    #[cfg(feature = "time")]
+    #[must_use]
+    pub fn with_clock(mut self, clock: time::SystemClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Send an event to the app, returning once its requests have been handled, and
+    /// any timers which are due have fired.
+    pub fn send(&mut self, event: &A::Event) {
+        self.simulator.send(event);
+        self.deliver_due();
+    }
+
+    /// The current view model.
+    pub fn view(&self) -> A::ViewModel {
+        self.simulator.view()
+    }
+
+    /// Wait for `duration`, delivering timers as they fire, and animation frames.
+    pub fn wait(&mut self, duration: std::time::Duration) {
+        let end = std::time::Instant::now() + duration;
+
+        loop {
+            self.deliver_due();
+            #[cfg(feature = "time")]
+            if let Some(clock) = &self.clock {
+                self.simulator.respond_all(clock.frames());
+            }
+
+            let now = std::time::Instant::now();
+            if now >= end {
+                break;
+            }
+            let next = self.next_due().map_or(end, |due| due.min(end));
+            std::thread::sleep(next.saturating_duration_since(now));
+        }
+    }
+
+    /// Read commands from `input` until it ends, or the `:quit` command, writing the
+    /// view models and any errors to `output`.
+    ///
+    /// # Errors
+    ///
+    /// If reading the input or writing the output fails.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+
+            match parse(line.trim()) {
+                Ok(None) => continue,
+                Ok(Some(Command::Quit)) => break,
+                Ok(Some(Command::Help)) => writeln!(output, "{HELP}")?,
+                Ok(Some(Command::View)) => self.print_view(&mut output)?,
+                Ok(Some(Command::Wait(duration))) => {
+                    self.wait(duration);
+                    self.print_view(&mut output)?;
+                }
+                Ok(Some(Command::Event(json))) => match serde_json::from_str(json) {
+                    Ok(event) => {
+                        self.send(&event);
+                        self.print_view(&mut output)?;
+                    }
+                    Err(error) => writeln!(output, "error: not an event, {error}")?,
+                },
+                Err(error) => writeln!(output, "error: {error}")?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_view(&self, output: &mut impl Write) -> io::Result<()> {
+        let view = serde_json::to_string(&self.view())?;
+        writeln!(output, "{view}")
+    }
+
+    fn deliver_due(&mut self) {
+        #[cfg(feature = "time")]
+        if let Some(clock) = &self.clock {
+            self.simulator.respond_all(clock.due());
+        }
+    }
+
+    fn next_due(&self) -> Option<std::time::Instant> {
+        #[cfg(feature = "time")]
+        if let Some(clock) = &self.clock {
+            return clock.next_due();
+        }
+        None
+    }
+}
+
+/// Parse a line of input, which is ignored if it's empty
+fn parse(line: &str) -> Result<Option<Command<'_>>, String> {
+    let Some(command) = line.strip_prefix(':') else {
+        return Ok((!line.is_empty()).then_some(Command::Event(line)));
+    };
+
+    let mut words = command.split_whitespace();
+    let command = match (words.next(), words.next()) {
+        (Some("view"), None) => Command::View,
+        (Some("help"), None) => Command::Help,
+        (Some("quit"), None) => Command::Quit,
+        (Some("wait"), Some(seconds)) => {
+            let duration = seconds
+                .parse()
+                .ok()
+                .and_then(|seconds| std::time::Duration::try_from_secs_f64(seconds).ok())
+                .ok_or_else(|| format!("{seconds} is not a number of seconds"))?;
+            Command::Wait(duration)
+        }
+        _ => return Err(format!("unknown command `{line}`, try :help")),
+    };
+    if words.next().is_some() {
+        return Err(format!("unknown command `{line}`, try :help"));
+    }
+    Ok(Some(command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(parse(""), Ok(None));
+        assert_eq!(
+            parse(r#"{"SetCount":3}"#),
+            Ok(Some(Command::Event(r#"{"SetCount":3}"#)))
+        );
+        assert_eq!(parse(":view"), Ok(Some(Command::View)));
+        assert_eq!(
+            parse(":wait 1.5"),
+            Ok(Some(Command::Wait(std::time::Duration::from_millis(1500))))
+        );
+        assert!(parse(":wait soon").is_err());
+        assert!(parse(":wait -1").is_err());
+        assert!(parse(":view all").is_err());
+        assert!(parse(":launch").is_err());
+    }
+}
@@ -0,0 +1,322 @@
+//! The shell side of the [`Time`](crux_time::Time) capability, with the system clock.
+
+use std::{
+    env,
+    sync::{Arc, Mutex},
+    time::{self, SystemTime, UNIX_EPOCH},
+};
+
+use crux_simulator::{Reply, Response};
+use crux_time::{
+    calendar, Duration, FormatStyle, Instant, MissedTicks, TimeRequest, TimeResponse, TimerId,
+};
+
+/// How often animation frames are delivered
+const FRAME: time::Duration = time::Duration::from_micros(16_667);
+
+/// The system clock, with timers which fire as real time passes.
+///
+/// The shell asks the clock for the timers which are [`due`](SystemClock::due) between
+/// commands, and while waiting. Animation frame subscriptions get a frame every 1/60th of a
+/// second while the shell waits, as there is no display to drive them.
+///
+/// The time zone is read from the `TZ` environment variable, falling back to UTC, and offsets
+/// of other time zones come from the database bundled with `chrono-tz`. There is
+/// no locale, so instants are formatted relative to now in whole seconds (e.g. "90 seconds
+/// ago"), and as seconds since the Unix epoch in the other styles (e.g. "ShortDate
+/// 1700000000"), which is also the only text parsed back, whatever the locale asked for.
+/// Paused timers and animations are held back until they are resumed. Clones share the same
+/// timers.
+#[derive(Clone)]
+pub struct SystemClock {
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    started: time::Instant,
+    timers: Vec<Timer>,
+    animations: Vec<Animation>,
+    /// responses confirming cleared animations, delivered with the next due timers
+    cleared: Vec<Response>,
+}
+
+struct Timer {
+    request: u32,
+    id: TimerId,
+    due: time::Instant,
+    response: TimeResponse,
+    /// paused timers don't fire, even when they are due
+    paused: bool,
+}
+
+struct Animation {
+    request: u32,
+    id: TimerId,
+    paused: bool,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                started: time::Instant::now(),
+                timers: Vec::new(),
+                animations: Vec::new(),
+                cleared: Vec::new(),
+            })),
+        }
+    }
+
+    /// The responses to the timers which are due, in the order they were due.
+    pub fn due(&self) -> Vec<Response> {
+        let mut state = self.lock();
+        let now = time::Instant::now();
+
+        let (mut fired, pending): (Vec<_>, Vec<_>) = state
+            .timers
+            .drain(..)
+            .partition(|timer| timer.due <= now && !timer.paused);
+        state.timers = pending;
+
+        fired.sort_by_key(|timer| timer.due);
+        let mut responses = std::mem::take(&mut state.cleared);
+        responses.extend(
+            fired
+                .into_iter()
+                .map(|timer| Response::new(timer.request, &timer.response)),
+        );
+        responses
+    }
+
+    /// A frame for each animation.
+    pub fn frames(&self) -> Vec<Response> {
+        let state = self.lock();
+        let elapsed = u64::try_from(state.started.elapsed().as_nanos()).unwrap_or(u64::MAX);
+
+        state
+            .animations
+            .iter()
+            .filter(|animation| !animation.paused)
+            .map(|animation| {
+                Response::new(
+                    animation.request,
+                    &TimeResponse::AnimationFrame {
+                        id: animation.id,
+                        timestamp: Duration::new(elapsed),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// When the shell should next ask for the due timers or frames, if there are any.
+    pub fn next_due(&self) -> Option<time::Instant> {
+        let state = self.lock();
+
+        let frame = state
+            .animations
+            .iter()
+            .any(|animation| !animation.paused)
+            .then(|| time::Instant::now() + FRAME);
+        state
+            .timers
+            .iter()
+            .filter(|timer| !timer.paused)
+            .map(|timer| timer.due)
+            .chain(frame)
+            .min()
+    }
+
+    /// Handle a time request from the app, with the `id` of the request it came in.
+    pub fn handle(&self, id: u32, request: &TimeRequest) -> Reply {
+        let mut state = self.lock();
+
+        match request {
+            TimeRequest::Now => Reply::respond(&TimeResponse::Now(now())),
+            TimeRequest::NowQuantized { resolution } => {
+                Reply::respond(&TimeResponse::Now(now().quantized(*resolution)))
+            }
+            TimeRequest::TimeZone => Reply::respond(&TimeResponse::TimeZone {
+                name: env::var("TZ").unwrap_or_else(|_| "UTC".to_string()),
+            }),
+            TimeRequest::NotifyAt { id: timer, instant } => {
+                let from_now = nanos(*instant).saturating_sub(nanos(now()));
+                state.timers.push(Timer {
+                    request: id,
+                    id: *timer,
+                    due: time::Instant::now() + duration(from_now),
+                    response: TimeResponse::InstantArrived { id: *timer },
+                    paused: false,
+                });
+                Reply::Later
+            }
+            TimeRequest::NotifyAfter {
+                id: timer,
+                duration: after,
+            } => {
+                state.timers.push(Timer {
+                    request: id,
+                    id: *timer,
+                    due: time::Instant::now() + duration(u128::from(after.as_nanos())),
+                    response: TimeResponse::DurationElapsed { id: *timer },
+                    paused: false,
+                });
+                Reply::Later
+            }
+            TimeRequest::Clear { id: timer } => {
+                state.timers.retain(|pending| pending.id != *timer);
+
+                let (cleared, animations): (Vec<_>, Vec<_>) = state
+                    .animations
+                    .drain(..)
+                    .partition(|animation| animation.id == *timer);
+                state.animations = animations;
+                state.cleared.extend(cleared.into_iter().map(|animation| {
+                    Response::new(animation.request, &TimeResponse::Cleared { id: *timer })
+                }));
+                Reply::Done
+            }
+            TimeRequest::Pause { id: timer } => {
+                state.pause(*timer, true);
+                Reply::Done
+            }
+            TimeRequest::Resume { id: timer, missed } => {
+                state.pause(*timer, false);
+                if *missed == MissedTicks::Skip {
+                    let now = time::Instant::now();
+                    for pending in state.timers.iter_mut() {
+                        if pending.id == *timer && pending.due <= now {
+                            pending.response = TimeResponse::Missed { id: *timer };
+                        }
+                    }
+                }
+                Reply::Done
+            }
+            TimeRequest::AnimationFrames { id: timer } => {
+                state.animations.push(Animation {
+                    request: id,
+                    id: *timer,
+                    paused: false,
+                });
+                Reply::Later
+            }
+            TimeRequest::Format { instant, style } => Reply::respond(&TimeResponse::Formatted {
+                text: format(nanos(now()), nanos(*instant), *style),
+            }),
+            TimeRequest::Parse { text, style, .. } => {
+                Reply::respond(&match parse(nanos(now()), text, *style) {
+                    Ok(parsed) => TimeResponse::Parsed {
+                        instant: instant(parsed),
+                    },
+                    Err(reason) => TimeResponse::ParseFailed { reason },
+                })
+            }
+            TimeRequest::UtcOffset { zone, instant } => {
+                let offset = calendar::time_zone(zone)
+                    .and_then(|time_zone| calendar::utc_offset(*instant, time_zone));
+                Reply::respond(&offset.map_or_else(
+                    |_| TimeResponse::UnknownTimeZone { name: zone.clone() },
+                    |seconds| TimeResponse::UtcOffset { seconds },
+                ))
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Clock Mutex poisoned.")
+    }
+}
+
+impl State {
+    /// Pause or resume the timers and animations with the `id`
+    fn pause(&mut self, id: TimerId, paused: bool) {
+        for timer in self.timers.iter_mut().filter(|timer| timer.id == id) {
+            timer.paused = paused;
+        }
+        for animation in self
+            .animations
+            .iter_mut()
+            .filter(|animation| animation.id == id)
+        {
+            animation.paused = paused;
+        }
+    }
+}
+
+fn now() -> Instant {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System clock is before the Unix epoch.");
+
+    Instant::new(since_epoch.as_secs(), since_epoch.subsec_nanos())
+        .expect("System time should be a valid instant.")
+}
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+fn format(now: u128, instant: u128, style: FormatStyle) -> String {
+    match style {
+        FormatStyle::Relative if instant > now => {
+            format!("in {} seconds", (instant - now) / NANOS_PER_SEC)
+        }
+        FormatStyle::Relative => format!("{} seconds ago", (now - instant) / NANOS_PER_SEC),
+        style => format!("{style:?} {}", instant / NANOS_PER_SEC),
+    }
+}
+
+/// Parse `text` formatted by [`format`] in the `style`, back into nanoseconds since the epoch
+fn parse(now: u128, text: &str, style: FormatStyle) -> Result<u128, String> {
+    let seconds = |digits: &str| {
+        digits
+            .parse::<u64>()
+            .map(|seconds| u128::from(seconds) * NANOS_PER_SEC)
+            .map_err(|_| format!("{text:?} is not a {style:?} time"))
+    };
+
+    let parsed = match style {
+        FormatStyle::Relative => {
+            if let Some(ahead) = text
+                .strip_prefix("in ")
+                .and_then(|text| text.strip_suffix(" seconds"))
+            {
+                Ok(now + seconds(ahead)?)
+            } else if let Some(ago) = text.strip_suffix(" seconds ago") {
+                now.checked_sub(seconds(ago)?)
+                    .ok_or_else(|| format!("{text:?} is before the Unix epoch"))
+            } else {
+                Err(format!("{text:?} is not a Relative time"))
+            }
+        }
+        style => match text.strip_prefix(&format!("{style:?} ")) {
+            Some(digits) => seconds(digits),
+            None => Err(format!("{text:?} is not a {style:?} time")),
+        },
+    }?;
+
+    if parsed / NANOS_PER_SEC > u128::from(u64::MAX) {
+        return Err(format!("{text:?} is too far in the future"));
+    }
+    Ok(parsed)
+}
+
+fn nanos(instant: Instant) -> u128 {
+    u128::from(instant.seconds) * NANOS_PER_SEC + u128::from(instant.nanos)
+}
+
+fn instant(nanos: u128) -> Instant {
+    let seconds = u64::try_from(nanos / NANOS_PER_SEC).expect("Parsed time should fit an instant.");
+    #[allow(clippy::cast_possible_truncation)]
+    let nanos = (nanos % NANOS_PER_SEC) as u32;
+
+    Instant::new(seconds, nanos).expect("Parsed time should be a valid instant.")
+}
+
+fn duration(nanos: u128) -> time::Duration {
+    time::Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
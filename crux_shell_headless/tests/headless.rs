@@ -0,0 +1,139 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_kv::KeyValue;
+    use crux_time::{Duration, Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Load,
+        Increment,
+        IncrementLater {
+            millis: u64,
+        },
+
+        #[serde(skip)]
+        Loaded(Result<Option<Vec<u8>>, crux_kv::error::KeyValueError>),
+        #[serde(skip)]
+        Saved,
+        #[serde(skip)]
+        Elapsed(TimeResponse),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub count: u32,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = u32;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut u32, caps: &Capabilities) {
+            match event {
+                Event::Load => caps.key_value.get("count".to_string(), Event::Loaded),
+                Event::Loaded(Ok(Some(bytes))) => {
+                    *model = String::from_utf8(bytes).unwrap().parse().unwrap();
+                    caps.render.render();
+                }
+                Event::Loaded(_) | Event::Saved => {}
+                Event::Increment | Event::Elapsed(TimeResponse::DurationElapsed { .. }) => {
+                    *model += 1;
+                    let count = model.to_string().into_bytes();
+                    caps.key_value
+                        .set("count".to_string(), count, |_| Event::Saved);
+                    caps.render.render();
+                }
+                Event::Elapsed(_) => {}
+                Event::IncrementLater { millis } => {
+                    let duration = Duration::from_millis(millis).unwrap();
+                    caps.time.notify_after(duration, Event::Elapsed);
+                }
+            }
+        }
+
+        fn view(&self, model: &u32) -> ViewModel {
+            ViewModel { count: *model }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub key_value: KeyValue<Event>,
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::bridge::Request;
+    use crux_shell_headless::{kv::FileKv, time::SystemClock, HeadlessShell, Reply};
+
+    use crate::app::{App, Effect, EffectFfi};
+
+    fn run(kv: &FileKv, input: &str) -> String {
+        let clock = SystemClock::new();
+        let mut shell = HeadlessShell::<Effect, App, _>::new({
+            let kv = kv.clone();
+            let clock = clock.clone();
+            move |request: &Request<EffectFfi>| match &request.effect {
+                EffectFfi::KeyValue(operation) => kv.handle(request.id.0, operation),
+                EffectFfi::Time(operation) => clock.handle(request.id.0, operation),
+                EffectFfi::Render(_) => Reply::Done,
+            }
+        })
+        .with_clock(clock);
+
+        let mut output = vec![];
+        shell.run(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn prints_the_view_after_each_event() {
+        let output = run(
+            &FileKv::in_memory(),
+            "\"Increment\"\n\n\"Increment\"\n:view\n\"Decrement\"\n:launch\n",
+        );
+
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(
+            lines[..3],
+            [r#"{"count":1}"#, r#"{"count":2}"#, r#"{"count":2}"#]
+        );
+        assert!(lines[3].starts_with("error: not an event"));
+        assert_eq!(lines[4], "error: unknown command `:launch`, try :help");
+    }
+
+    #[test]
+    fn timers_fire_while_waiting() {
+        let output = run(
+            &FileKv::in_memory(),
+            "{\"IncrementLater\":{\"millis\":20}}\n:wait 0.1\n:quit\n\"Increment\"\n",
+        );
+
+        assert_eq!(output, "{\"count\":0}\n{\"count\":1}\n");
+    }
+
+    #[test]
+    fn the_store_is_kept_in_a_file() {
+        let path = std::env::temp_dir().join(format!("crux_headless_{}.json", std::process::id()));
+
+        run(
+            &FileKv::open(&path).unwrap(),
+            "\"Increment\"\n\"Increment\"\n",
+        );
+
+        let kv = FileKv::open(&path).unwrap();
+        assert_eq!(kv.get("count"), Some(b"2".to_vec()));
+        assert_eq!(run(&kv, "\"Load\"\n"), "{\"count\":2}\n");
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,31 @@
+//! The shell side of the [`Http`](crux_http::Http) capability.
+
+use crux_core::{capability::Introspect, App, Effect, WithContext};
+use crux_http::protocol::{HttpRequest, HttpResult};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Driver, Responder};
+
+/// Makes the app's HTTP requests.
+///
+/// Responses with error statuses (4xx and 5xx) should be passed to the app like any other
+/// response, as a browser or a platform HTTP client would, and only failures to get a
+/// response at all as an [`HttpError`](crux_http::HttpError).
+pub trait HttpShell {
+    fn http(&mut self, request: HttpRequest, responder: Responder<HttpResult>);
+}
+
+impl<Eff, A> Driver<Eff, A>
+where
+    Eff: Effect + Send + 'static,
+    Eff::Ffi: DeserializeOwned,
+    A: App,
+    A::Capabilities: WithContext<A::Event, Eff> + Introspect,
+    A::Event: Serialize + DeserializeOwned,
+{
+    /// Route the app's HTTP requests to `shell`.
+    #[must_use]
+    pub fn with_http(self, mut shell: impl HttpShell + 'static) -> Self {
+        self.with_handler(move |request, responder| shell.http(request, responder))
+    }
+}
@@ -0,0 +1,31 @@
+//! The shell side of the [`KeyValue`](crux_kv::KeyValue) capability.
+
+use crux_core::{capability::Introspect, App, Effect, WithContext};
+use crux_kv::{KeyValueOperation, KeyValueResult};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Driver, Responder};
+
+/// A key-value store for the app.
+///
+/// The responder of a [`KeyValueOperation::Watch`] should be kept, and responded to each
+/// time the watched keys change, until the watch is removed with
+/// [`KeyValueOperation::Unwatch`].
+pub trait KvShell {
+    fn key_value(&mut self, operation: KeyValueOperation, responder: Responder<KeyValueResult>);
+}
+
+impl<Eff, A> Driver<Eff, A>
+where
+    Eff: Effect + Send + 'static,
+    Eff::Ffi: DeserializeOwned,
+    A: App,
+    A::Capabilities: WithContext<A::Event, Eff> + Introspect,
+    A::Event: Serialize + DeserializeOwned,
+{
+    /// Route the app's key-value operations to `shell`.
+    #[must_use]
+    pub fn with_key_value(self, mut shell: impl KvShell + 'static) -> Self {
+        self.with_handler(move |operation, responder| shell.key_value(operation, responder))
+    }
+}
@@ -0,0 +1,311 @@
+//! Traits and a driver for writing the shell of a Crux app in Rust.
+//!
+//! A shell carries out the app's requests, and sends the outputs back to the core. Written
+//! in Rust, e.g. for a desktop or terminal UI, it could match on the app's `Effect` type,
+//! but each app has its own, and each shell would need its own way to respond to requests
+//! later. Instead, each built-in capability has a trait for its shell side:
+//! [`HttpShell`](http::HttpShell) (feature `http`), [`KvShell`](kv::KvShell) (feature `kv`)
+//! and [`TimeShell`](time::TimeShell) (feature `time`). The [`Driver`] runs the app through
+//! the serialized bridge, like a shell in any other language does, and routes each request
+//! to the implementation for its capability, found by the type of its operation.
+//!
+//! ```rust,ignore
+//! let mut driver = Driver::<Effect, App>::new()
+//!     .with_http(NetworkHttp::default())
+//!     .with_key_value(FileStore::open("store.json")?)
+//!     .with_time(ThreadTimers::default())
+//!     .with_render(|| redraw());
+//!
+//! driver.send(&Event::Load)?;
+//! ```
+//!
+//! Each request comes with a [`Responder`], to respond with the output straight away, or
+//! from another thread, e.g. when a timer fires. Responses sent later are processed by the
+//! next call to [`Driver::send`] or [`Driver::run_pending`].
+
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "kv")]
+pub mod kv;
+#[cfg(feature = "time")]
+pub mod time;
+
+use std::{
+    any::type_name,
+    collections::BTreeMap,
+    marker::PhantomData,
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use bincode::{DefaultOptions, Options};
+use crux_core::{
+    bridge::{Bridge, Request},
+    capability::{Introspect, Operation},
+    render::RenderOperation,
+    App, Core, Effect, WithContext,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+/// The serialized outputs waiting to be sent to the core, with the ids of their requests
+type Responses = Arc<Mutex<Vec<(u32, Vec<u8>)>>>;
+
+/// A handler for the requests of one operation type, called with the operation and the id
+/// of the request
+type Handler = Box<dyn FnMut(Value, u32) -> Result<(), serde_json::Error>>;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("no handler for the {effect} effect, with operation {operation}")]
+    Unhandled {
+        effect: String,
+        operation: &'static str,
+    },
+    #[error("the {effect} effect is not one of the app's capabilities")]
+    UnknownEffect { effect: String },
+    #[error("the operation of the {effect} effect could not be read, {source}")]
+    Operation {
+        effect: String,
+        source: serde_json::Error,
+    },
+}
+
+/// Responds to a request with its output, straight away or later, from any thread. A
+/// request for a subscription, e.g. to animation frames, can be responded to several times.
+/// Clones respond to the same request.
+pub struct Responder<T> {
+    id: u32,
+    responses: Responses,
+    output: PhantomData<fn(&T)>,
+}
+
+impl<T> Clone for Responder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            responses: self.responses.clone(),
+            output: PhantomData,
+        }
+    }
+}
+
+impl<T: Serialize> Responder<T> {
+    /// Respond to the request with `output`. The core processes it on the next call to
+    /// [`Driver::send`] or [`Driver::run_pending`], or straight away when responding while
+    /// the request is being handled.
+    ///
+    /// # Panics
+    ///
+    /// If the output can't be serialized.
+    pub fn respond(&self, output: &T) {
+        self.responses
+            .lock()
+            .expect("Responses lock was poisoned.")
+            .push((self.id, serialize(output)));
+    }
+
+    /// The id of the request, as the core knows it
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+/// Runs the app `A` with effect type `Eff`, and routes its requests to the shell's
+/// implementations of its capabilities.
+pub struct Driver<Eff, A>
+where
+    Eff: Effect,
+    A: App,
+{
+    bridge: Bridge<Eff, A>,
+    /// The type name of the operation of each of the app's effects
+    operations: BTreeMap<&'static str, &'static str>,
+    /// The handlers, by the type name of their operation
+    handlers: BTreeMap<&'static str, Handler>,
+    responses: Responses,
+}
+
+impl<Eff, A> Default for Driver<Eff, A>
+where
+    Eff: Effect + Send + 'static,
+    Eff::Ffi: DeserializeOwned,
+    A: App,
+    A::Capabilities: WithContext<A::Event, Eff> + Introspect,
+    A::Event: Serialize + DeserializeOwned,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Eff, A> Driver<Eff, A>
+where
+    Eff: Effect + Send + 'static,
+    Eff::Ffi: DeserializeOwned,
+    A: App,
+    A::Capabilities: WithContext<A::Event, Eff> + Introspect,
+    A::Event: Serialize + DeserializeOwned,
+{
+    /// Start a new instance of the app, without any handlers.
+    pub fn new() -> Self {
+        let operations = A::Capabilities::capabilities()
+            .into_iter()
+            .map(|info| (info.effect, info.operation))
+            .collect();
+
+        Self {
+            bridge: Bridge::new(Core::new()),
+            operations,
+            handlers: BTreeMap::new(),
+            responses: Responses::default(),
+        }
+    }
+
+    /// Handle the requests with operation type `Op`, e.g. of a capability without a trait
+    /// in this crate, with `handler`, which replaces any earlier one for the type.
+    #[must_use]
+    pub fn with_handler<Op, F>(mut self, mut handler: F) -> Self
+    where
+        Op: Operation + DeserializeOwned,
+        F: FnMut(Op, Responder<Op::Output>) + 'static,
+    {
+        let responses = self.responses.clone();
+        self.handlers.insert(
+            type_name::<Op>(),
+            Box::new(move |operation, id| {
+                let operation = serde_json::from_value(operation)?;
+                handler(
+                    operation,
+                    Responder {
+                        id,
+                        responses: responses.clone(),
+                        output: PhantomData,
+                    },
+                );
+                Ok(())
+            }),
+        );
+        self
+    }
+
+    /// Call `render` for every render request, after which [`Driver::view`] returns the
+    /// new view model.
+    #[must_use]
+    pub fn with_render<F>(self, mut render: F) -> Self
+    where
+        F: FnMut() + 'static,
+    {
+        self.with_handler(move |_: RenderOperation, _| render())
+    }
+
+    /// Send an event to the app, e.g. when the user taps a button.
+    ///
+    /// Returns once the resulting requests have been routed to their handlers, and the
+    /// responses they sent straight away, as well as any sent later since the last call,
+    /// have been processed.
+    ///
+    /// # Errors
+    ///
+    /// If there is no handler for one of the requests, or its operation couldn't be read.
+    /// The other requests are still handled.
+    pub fn send(&mut self, event: &A::Event) -> Result<(), Error> {
+        let requests = self.bridge.process_event(&serialize(event));
+        let routed = self.route(&requests);
+
+        routed.and(self.run_pending())
+    }
+
+    /// Process the responses sent since the last call, e.g. from another thread when a
+    /// timer fired, and route the requests which result from them.
+    ///
+    /// # Errors
+    ///
+    /// If there is no handler for one of the requests, or its operation couldn't be read.
+    /// The other requests are still handled.
+    pub fn run_pending(&mut self) -> Result<(), Error> {
+        let mut result = Ok(());
+        loop {
+            let responses =
+                mem::take(&mut *self.responses.lock().expect("Responses lock was poisoned."));
+            if responses.is_empty() {
+                return result;
+            }
+
+            for (id, output) in responses {
+                let requests = self.bridge.handle_response(id, &output);
+                result = result.and(self.route(&requests));
+            }
+        }
+    }
+
+    /// The current view model.
+    pub fn view(&self) -> A::ViewModel
+    where
+        A::ViewModel: DeserializeOwned,
+    {
+        deserialize(&self.bridge.view())
+    }
+
+    /// Pass each of the serialized `requests` to the handler for its operation, returning
+    /// the first error
+    fn route(&mut self, requests: &[u8]) -> Result<(), Error> {
+        let requests: Vec<Request<Eff::Ffi>> = deserialize(requests);
+
+        let mut result = Ok(());
+        for request in requests {
+            result = result.and(self.dispatch(request));
+        }
+        result
+    }
+
+    fn dispatch(&mut self, request: Request<Eff::Ffi>) -> Result<(), Error> {
+        // the effect is an enum with a variant for each capability, holding its operation,
+        // so it is serialized as a map from the variant's name to the operation
+        let effect = serde_json::to_value(&request.effect).map_err(|source| Error::Operation {
+            effect: type_name::<Eff::Ffi>().to_string(),
+            source,
+        })?;
+        let Value::Object(effect) = effect else {
+            return Err(Error::UnknownEffect {
+                effect: effect.to_string(),
+            });
+        };
+        let Some((effect, operation)) = effect.into_iter().next() else {
+            return Err(Error::UnknownEffect {
+                effect: String::new(),
+            });
+        };
+
+        let Some(&operation_type) = self.operations.get(effect.as_str()) else {
+            return Err(Error::UnknownEffect { effect });
+        };
+        let Some(handler) = self.handlers.get_mut(operation_type) else {
+            return Err(Error::Unhandled {
+                effect,
+                operation: operation_type,
+            });
+        };
+
+        handler(operation, request.id.0).map_err(|source| Error::Operation { effect, source })
+    }
+}
+
+fn options() -> impl Options + Copy {
+    DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+}
+
+fn serialize<T: Serialize + ?Sized>(value: &T) -> Vec<u8> {
+    options()
+        .serialize(value)
+        .expect("Shell SDK serialization failed.")
+}
+
+fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> T {
+    options()
+        .deserialize(bytes)
+        .expect("Shell SDK deserialization failed.")
+}
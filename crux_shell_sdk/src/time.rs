@@ -0,0 +1,32 @@
+//! The shell side of the [`Time`](crux_time::Time) capability.
+
+use crux_core::{capability::Introspect, App, Effect, WithContext};
+use crux_time::{TimeRequest, TimeResponse};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Driver, Responder};
+
+/// The clock, timers and formatting of dates and times for the app.
+///
+/// Timers are responded to when they fire, usually from another thread, which the driver
+/// picks up on its next [`run_pending`](Driver::run_pending). The responder of a
+/// [`TimeRequest::AnimationFrames`] subscription is responded to for every frame, until it
+/// is cleared.
+pub trait TimeShell {
+    fn time(&mut self, request: TimeRequest, responder: Responder<TimeResponse>);
+}
+
+impl<Eff, A> Driver<Eff, A>
+where
+    Eff: Effect + Send + 'static,
+    Eff::Ffi: DeserializeOwned,
+    A: App,
+    A::Capabilities: WithContext<A::Event, Eff> + Introspect,
+    A::Event: Serialize + DeserializeOwned,
+{
+    /// Route the app's time requests to `shell`.
+    #[must_use]
+    pub fn with_time(self, mut shell: impl TimeShell + 'static) -> Self {
+        self.with_handler(move |request, responder| shell.time(request, responder))
+    }
+}
@@ -0,0 +1,218 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_http::Http;
+    use crux_kv::KeyValue;
+    use crux_time::{Duration, Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Load,
+        Fetch,
+        IncrementLater,
+
+        #[serde(skip)]
+        Loaded(Result<Option<Vec<u8>>, crux_kv::error::KeyValueError>),
+        #[serde(skip)]
+        Fetched(crux_http::Result<crux_http::Response<Vec<u8>>>),
+        #[serde(skip)]
+        Saved,
+        #[serde(skip)]
+        Elapsed(TimeResponse),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub count: u32,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = u32;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut u32, caps: &Capabilities) {
+            match event {
+                Event::Load => caps.key_value.get("count".to_string(), Event::Loaded),
+                Event::Fetch => caps
+                    .http
+                    .get("https://example.com/count")
+                    .send(Event::Fetched),
+                Event::IncrementLater => {
+                    caps.time
+                        .notify_after(Duration::from_millis(10).unwrap(), Event::Elapsed);
+                }
+                Event::Loaded(Ok(Some(bytes))) => {
+                    *model = String::from_utf8(bytes).unwrap().parse().unwrap();
+                    caps.render.render();
+                }
+                Event::Fetched(Ok(mut response)) => {
+                    let body = response.take_body().unwrap();
+                    *model = String::from_utf8(body).unwrap().parse().unwrap();
+                    caps.render.render();
+                }
+                Event::Elapsed(TimeResponse::DurationElapsed { .. }) => {
+                    *model += 1;
+                    let count = model.to_string().into_bytes();
+                    caps.key_value
+                        .set("count".to_string(), count, |_| Event::Saved);
+                    caps.render.render();
+                }
+                Event::Loaded(_) | Event::Fetched(_) | Event::Saved | Event::Elapsed(_) => {}
+            }
+        }
+
+        fn view(&self, model: &u32) -> ViewModel {
+            ViewModel { count: *model }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub http: Http<Event>,
+        pub key_value: KeyValue<Event>,
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod shells {
+    use std::{
+        cell::RefCell,
+        collections::BTreeMap,
+        rc::Rc,
+        sync::{Arc, Mutex},
+    };
+
+    use crux_http::protocol::{HttpRequest, HttpResponse, HttpResult};
+    use crux_kv::{value::Value, KeyValueOperation, KeyValueResponse, KeyValueResult};
+    use crux_shell_sdk::{http::HttpShell, kv::KvShell, time::TimeShell, Responder};
+    use crux_time::{TimeRequest, TimeResponse};
+
+    pub struct FixedHttp(pub &'static str);
+
+    impl HttpShell for FixedHttp {
+        fn http(&mut self, _request: HttpRequest, responder: Responder<HttpResult>) {
+            let response = HttpResponse::ok().body(self.0.as_bytes().to_vec()).build();
+            responder.respond(&HttpResult::Ok(response));
+        }
+    }
+
+    #[derive(Clone, Default)]
+    pub struct MapKv(pub Rc<RefCell<BTreeMap<String, Vec<u8>>>>);
+
+    impl KvShell for MapKv {
+        fn key_value(
+            &mut self,
+            operation: KeyValueOperation,
+            responder: Responder<KeyValueResult>,
+        ) {
+            let mut store = self.0.borrow_mut();
+            let response = match operation {
+                KeyValueOperation::Get { key } => KeyValueResponse::Get {
+                    value: store.get(&key).cloned().map_or(Value::None, Value::Bytes),
+                },
+                KeyValueOperation::Set { key, value } => KeyValueResponse::Set {
+                    previous: store.insert(key, value).map_or(Value::None, Value::Bytes),
+                },
+                operation => panic!("unexpected {operation:?}"),
+            };
+            responder.respond(&KeyValueResult::Ok { response });
+        }
+    }
+
+    type Timer = (Responder<TimeResponse>, TimeResponse);
+
+    /// Timers which the test fires, as a real shell would from another thread
+    #[derive(Clone, Default)]
+    pub struct ManualTimers(pub Arc<Mutex<Vec<Timer>>>);
+
+    impl ManualTimers {
+        pub fn fire_all(&self) {
+            for (responder, response) in self.0.lock().unwrap().drain(..) {
+                responder.respond(&response);
+            }
+        }
+    }
+
+    impl TimeShell for ManualTimers {
+        fn time(&mut self, request: TimeRequest, responder: Responder<TimeResponse>) {
+            let TimeRequest::NotifyAfter { id, .. } = request else {
+                panic!("unexpected {request:?}");
+            };
+            let response = TimeResponse::DurationElapsed { id };
+            self.0.lock().unwrap().push((responder, response));
+        }
+    }
+}
+
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use crux_shell_sdk::{Driver, Error};
+
+    use crate::{
+        app::{App, Effect, Event, ViewModel},
+        shells::{FixedHttp, ManualTimers, MapKv},
+    };
+
+    #[test]
+    fn routes_requests_to_the_shells() {
+        let kv = MapKv::default();
+        kv.0.borrow_mut().insert("count".to_string(), b"5".to_vec());
+        let renders = Rc::new(Cell::new(0));
+
+        let mut driver = Driver::<Effect, App>::new()
+            .with_http(FixedHttp("7"))
+            .with_key_value(kv.clone())
+            .with_time(ManualTimers::default())
+            .with_render({
+                let renders = renders.clone();
+                move || renders.set(renders.get() + 1)
+            });
+
+        driver.send(&Event::Load).unwrap();
+        assert_eq!(driver.view(), ViewModel { count: 5 });
+
+        driver.send(&Event::Fetch).unwrap();
+        assert_eq!(driver.view(), ViewModel { count: 7 });
+        assert_eq!(renders.get(), 2);
+    }
+
+    #[test]
+    fn responses_sent_later_are_processed_when_pending_work_runs() {
+        let kv = MapKv::default();
+        let timers = ManualTimers::default();
+
+        let mut driver = Driver::<Effect, App>::new()
+            .with_key_value(kv.clone())
+            .with_time(timers.clone())
+            .with_render(|| {});
+
+        driver.send(&Event::IncrementLater).unwrap();
+        assert_eq!(driver.view(), ViewModel { count: 0 });
+
+        let fire = std::thread::spawn(move || timers.fire_all());
+        fire.join().unwrap();
+        driver.run_pending().unwrap();
+
+        assert_eq!(driver.view(), ViewModel { count: 1 });
+        assert_eq!(kv.0.borrow().get("count"), Some(&b"1".to_vec()));
+    }
+
+    #[test]
+    fn requests_without_a_handler_are_an_error() {
+        let mut driver = Driver::<Effect, App>::new().with_key_value(MapKv::default());
+
+        let error = driver.send(&Event::Fetch).unwrap_err();
+        assert!(
+            matches!(&error, Error::Unhandled { effect, .. } if effect == "Http"),
+            "{error}"
+        );
+    }
+}
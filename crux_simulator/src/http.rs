@@ -0,0 +1,64 @@
+//! The shell side of the [`Http`](crux_http::Http) capability, with canned responses.
+
+use std::sync::{Arc, Mutex};
+
+use crux_http::protocol::{HttpRequest, HttpResponse, HttpResult};
+
+use crate::Reply;
+
+/// Responds to HTTP requests with canned responses, matched by method and URL.
+///
+/// Requests which don't match any of the responses get a 404 response. Clones
+/// share the same responses.
+#[derive(Clone, Default)]
+pub struct HttpStub {
+    routes: Arc<Mutex<Vec<Route>>>,
+}
+
+struct Route {
+    method: String,
+    url: String,
+    result: HttpResult,
+}
+
+impl HttpStub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Respond to requests with `method` (e.g. "GET") for `url` with `response`.
+    /// Later responses for the same method and URL replace earlier ones.
+    #[must_use]
+    pub fn on(self, method: &str, url: &str, response: HttpResponse) -> Self {
+        self.respond_with(method, url, HttpResult::Ok(response));
+        self
+    }
+
+    /// Respond to requests with `method` for `url` with `result`, which can also be
+    /// an error, e.g. a timeout.
+    pub fn respond_with(&self, method: &str, url: &str, result: HttpResult) {
+        let mut routes = self.routes.lock().expect("Routes Mutex poisoned.");
+
+        routes.retain(|route| !(route.method == method && route.url == url));
+        routes.push(Route {
+            method: method.to_string(),
+            url: url.to_string(),
+            result,
+        });
+    }
+
+    /// Handle an HTTP request from the app.
+    pub fn handle(&self, request: &HttpRequest) -> Reply {
+        let routes = self.routes.lock().expect("Routes Mutex poisoned.");
+
+        let result = routes
+            .iter()
+            .find(|route| route.method == request.method && route.url == request.url)
+            .map_or_else(
+                || HttpResult::Ok(HttpResponse::status(404).build()),
+                |route| route.result.clone(),
+            );
+
+        Reply::respond(&result)
+    }
+}
@@ -0,0 +1,185 @@
+//! The shell side of the [`KeyValue`](crux_kv::KeyValue) capability, with an in-memory store.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use crux_kv::{
+    error::KeyValueError, value::Value, KeyValueOperation, KeyValueResponse, KeyValueResult,
+    WatchId, Write,
+};
+
+use crate::{Reply, Response};
+
+/// An in-memory key-value store. Clones share the same store, so a test can keep a
+/// clone to check what the app stored.
+///
+/// Listing keys returns all the matching keys at once, so the only valid cursor is 0.
+/// Watches are told about the changes made with [`MemoryKv::change`], standing in for
+/// another process writing to the store.
+#[derive(Clone, Default)]
+pub struct MemoryKv {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    entries: BTreeMap<String, Vec<u8>>,
+    watches: Vec<Watch>,
+    /// responses confirming stopped watches, sent with the next change
+    unwatched: Vec<Response>,
+}
+
+struct Watch {
+    request: u32,
+    id: WatchId,
+    prefix: String,
+}
+
+impl MemoryKv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value stored under `key`, if any.
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.lock().entries.get(key).cloned()
+    }
+
+    /// Store `value` under `key`, e.g. to set up the state an app starts with. Watches
+    /// aren't told about it, see [`MemoryKv::change`].
+    pub fn insert(&self, key: &str, value: impl Into<Vec<u8>>) {
+        self.lock().entries.insert(key.to_string(), value.into());
+    }
+
+    /// All the stored keys and their values, e.g. to save them.
+    pub fn entries(&self) -> BTreeMap<String, Vec<u8>> {
+        self.lock().entries.clone()
+    }
+
+    /// Store `value` under `key`, or remove the key if it's `None`, as if another process
+    /// did, returning the responses telling the app's watches of the key about it, to pass
+    /// to [`Simulator::respond_all`](crate::Simulator::respond_all). They follow the
+    /// responses confirming the watches stopped since the last change.
+    pub fn change(&self, key: &str, value: Option<Vec<u8>>) -> Vec<Response> {
+        let mut state = self.lock();
+        match &value {
+            Some(value) => state.entries.insert(key.to_string(), value.clone()),
+            None => state.entries.remove(key),
+        };
+
+        let mut responses = std::mem::take(&mut state.unwatched);
+        let changed = KeyValueResult::Ok {
+            response: KeyValueResponse::Changed {
+                key: key.to_string(),
+                value: value.into(),
+            },
+        };
+        responses.extend(
+            state
+                .watches
+                .iter()
+                .filter(|watch| key.starts_with(watch.prefix.as_str()))
+                .map(|watch| Response::new(watch.request, &changed)),
+        );
+        responses
+    }
+
+    /// Handle a key-value operation from the app, with the `id` of the request it came in.
+    pub fn handle(&self, id: u32, operation: &KeyValueOperation) -> Reply {
+        let mut state = self.lock();
+        let State {
+            entries: store,
+            watches,
+            unwatched,
+        } = &mut *state;
+
+        let response = match operation {
+            KeyValueOperation::Get { key } => KeyValueResponse::Get {
+                value: to_value(store.get(key).cloned()),
+            },
+            KeyValueOperation::Set { key, value } => KeyValueResponse::Set {
+                previous: to_value(store.insert(key.clone(), value.clone())),
+            },
+            KeyValueOperation::Delete { key } => KeyValueResponse::Delete {
+                previous: to_value(store.remove(key)),
+            },
+            KeyValueOperation::Exists { key } => KeyValueResponse::Exists {
+                is_present: store.contains_key(key),
+            },
+            KeyValueOperation::ListKeys { cursor, .. } if *cursor != 0 => {
+                return Reply::respond(&KeyValueResult::Err {
+                    error: KeyValueError::CursorNotFound,
+                });
+            }
+            KeyValueOperation::ListKeys { prefix, .. } => KeyValueResponse::ListKeys {
+                keys: store
+                    .keys()
+                    .filter(|key| key.starts_with(prefix.as_str()))
+                    .cloned()
+                    .collect(),
+                next_cursor: 0,
+            },
+            KeyValueOperation::Batch { writes } => {
+                // writes to a map can't fail, so applying them in order is atomic
+                for write in writes {
+                    match write {
+                        Write::Set { key, value } => {
+                            store.insert(key.clone(), value.clone());
+                        }
+                        Write::Delete { key } => {
+                            store.remove(key);
+                        }
+                    }
+                }
+                KeyValueResponse::Batch
+            }
+            KeyValueOperation::CompareAndSwap { key, expected, new } => {
+                let current = to_value(store.get(key).cloned());
+                if current != *expected {
+                    return Reply::respond(&KeyValueResult::Err {
+                        error: KeyValueError::Conflict { current },
+                    });
+                }
+                match Option::<Vec<u8>>::from(new.clone()) {
+                    Some(value) => store.insert(key.clone(), value),
+                    None => store.remove(key),
+                };
+                KeyValueResponse::CompareAndSwap
+            }
+            KeyValueOperation::Watch { id: watch, prefix } => {
+                watches.push(Watch {
+                    request: id,
+                    id: *watch,
+                    prefix: prefix.clone(),
+                });
+                return Reply::Later;
+            }
+            KeyValueOperation::Unwatch { id: watch } => {
+                let (stopped, watching): (Vec<_>, Vec<_>) =
+                    watches.drain(..).partition(|pending| pending.id == *watch);
+                *watches = watching;
+                unwatched.extend(stopped.into_iter().map(|watch| {
+                    Response::new(
+                        watch.request,
+                        &KeyValueResult::Ok {
+                            response: KeyValueResponse::Unwatched,
+                        },
+                    )
+                }));
+                return Reply::Done;
+            }
+        };
+
+        Reply::respond(&KeyValueResult::Ok { response })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Store Mutex poisoned.")
+    }
+}
+
+fn to_value(bytes: Option<Vec<u8>>) -> Value {
+    bytes.map_or(Value::None, Value::Bytes)
+}
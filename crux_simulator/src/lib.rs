@@ -0,0 +1,196 @@
+//! A headless, in-process shell for end-to-end tests of Crux apps.
+//!
+//! The [`Simulator`] drives the core exactly like a real shell does, through the
+//! serialized [`Bridge`] interface: events go in as bytes, requests come out as bytes,
+//! and responses go back in as bytes. Unlike tests written with
+//! [`AppTester`](crux_core::testing::AppTester), this exercises the serialization of
+//! events, effects and capability outputs, catching incompatibilities between the
+//! core and the types shared with the shells.
+//!
+//! The requests are handled by a function given to [`Simulator::new`], which can use
+//! the scripted stand-ins for the shell side of the built-in capabilities:
+//! [`HttpStub`](http::HttpStub), [`MemoryKv`](kv::MemoryKv) and
+//! [`VirtualClock`](time::VirtualClock).
+//!
+//! ```rust,ignore
+//! let kv = MemoryKv::new();
+//! let http = HttpStub::new().on("GET", "https://example.com/count", HttpResponse::ok().body("3").build());
+//!
+//! let mut simulator = Simulator::<Effect, App, _>::new({
+//!     let kv = kv.clone();
+//!     move |request: &Request<EffectFfi>| match &request.effect {
+//!         EffectFfi::Http(operation) => http.handle(operation),
+//!         EffectFfi::KeyValue(operation) => kv.handle(request.id.0, operation),
+//!         EffectFfi::Render(_) => Reply::Done,
+//!     }
+//! });
+//!
+//! simulator.send(&Event::Load);
+//!
+//! assert_eq!(simulator.view().count, 3);
+//! assert_eq!(kv.get("count"), Some(b"3".to_vec()));
+//! ```
+
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "kv")]
+pub mod kv;
+#[cfg(feature = "time")]
+pub mod time;
+
+use std::collections::VecDeque;
+
+use bincode::{DefaultOptions, Options};
+use crux_core::{
+    bridge::{Bridge, LocalReply, Request},
+    App, Core, Effect, WithContext,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// How the simulated shell handles a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// The request doesn't expect a response, e.g. a render
+    Done,
+    /// Respond to the request straight away with the serialized output
+    Respond(Vec<u8>),
+    /// The request will be responded to later, with [`Simulator::respond`]
+    Later,
+}
+
+impl Reply {
+    /// Respond to the request with the `output`, serialized the way a shell would.
+    pub fn respond<T: Serialize>(output: &T) -> Self {
+        Reply::Respond(serialize(output))
+    }
+}
+
+/// The stand-ins can also back some of the capabilities of a core running against a real
+/// shell, with [`Bridge::with_fakes`]. A request replied to [`Reply::Later`] is responded
+/// to by passing the [`Response`] to [`Bridge::handle_response`].
+impl From<Reply> for LocalReply {
+    fn from(reply: Reply) -> Self {
+        match reply {
+            Reply::Done | Reply::Later => LocalReply::Done,
+            Reply::Respond(output) => LocalReply::Respond(output),
+        }
+    }
+}
+
+/// A response to a request which was replied to with [`Reply::Later`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The id of the request
+    pub id: u32,
+    /// The serialized output
+    pub output: Vec<u8>,
+}
+
+impl Response {
+    /// A response to the request with `id`, with the `output` serialized the way a shell would.
+    pub fn new<T: Serialize>(id: u32, output: &T) -> Self {
+        Self {
+            id,
+            output: serialize(output),
+        }
+    }
+}
+
+/// A simulated shell, running the app `A` with effect type `Eff`, and handling its
+/// requests with `H`.
+pub struct Simulator<Eff, A, H>
+where
+    Eff: Effect,
+    A: App,
+{
+    bridge: Bridge<Eff, A>,
+    handler: H,
+    requests: Vec<Request<Eff::Ffi>>,
+}
+
+impl<Eff, A, H> Simulator<Eff, A, H>
+where
+    Eff: Effect + Send + 'static,
+    Eff::Ffi: DeserializeOwned,
+    A: App,
+    A::Capabilities: WithContext<A::Event, Eff>,
+    A::Event: Serialize + DeserializeOwned,
+    H: FnMut(&Request<Eff::Ffi>) -> Reply,
+{
+    /// Start a new instance of the app, with requests handled by `handler`.
+    pub fn new(handler: H) -> Self {
+        Self {
+            bridge: Bridge::new(Core::new()),
+            handler,
+            requests: Vec::new(),
+        }
+    }
+
+    /// Send an event to the app, as the shell would, e.g. when the user taps a button.
+    ///
+    /// Returns once all the resulting requests have been handled, and the responses
+    /// which were available straight away have been processed.
+    pub fn send(&mut self, event: &A::Event) {
+        let requests = self.bridge.process_event(&serialize(event));
+
+        self.handle(&requests);
+    }
+
+    /// Respond to a request which was replied to with [`Reply::Later`].
+    pub fn respond(&mut self, response: Response) {
+        let requests = self.bridge.handle_response(response.id, &response.output);
+
+        self.handle(&requests);
+    }
+
+    /// Respond to several requests, in order.
+    pub fn respond_all(&mut self, responses: impl IntoIterator<Item = Response>) {
+        for response in responses {
+            self.respond(response);
+        }
+    }
+
+    /// The current view model, deserialized as the shell would.
+    pub fn view(&self) -> A::ViewModel
+    where
+        A::ViewModel: DeserializeOwned,
+    {
+        deserialize(&self.bridge.view())
+    }
+
+    /// All the requests the app has made so far, in the order they were handled.
+    pub fn requests(&self) -> &[Request<Eff::Ffi>] {
+        &self.requests
+    }
+
+    fn handle(&mut self, requests: &[u8]) {
+        let mut pending: VecDeque<Request<Eff::Ffi>> = deserialize(requests);
+
+        while let Some(request) = pending.pop_front() {
+            if let Reply::Respond(output) = (self.handler)(&request) {
+                let requests = self.bridge.handle_response(request.id.0, &output);
+                pending.extend(deserialize::<Vec<_>>(&requests));
+            }
+
+            self.requests.push(request);
+        }
+    }
+}
+
+fn options() -> impl Options + Copy {
+    DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+}
+
+fn serialize<T: Serialize + ?Sized>(value: &T) -> Vec<u8> {
+    options()
+        .serialize(value)
+        .expect("Simulator serialization failed.")
+}
+
+fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> T {
+    options()
+        .deserialize(bytes)
+        .expect("Simulator deserialization failed.")
+}
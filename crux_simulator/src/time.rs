@@ -0,0 +1,328 @@
+//! The shell side of the [`Time`](crux_time::Time) capability, with a virtual clock.
+
+use std::sync::{Arc, Mutex};
+
+use crux_time::{Duration, FormatStyle, Instant, MissedTicks, TimeRequest, TimeResponse, TimerId};
+
+use crate::{Reply, Response};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// A clock which only moves when told to, with [`VirtualClock::advance`].
+///
+/// Timers fire when the clock is advanced past them, returning the responses to pass to
+/// [`Simulator::respond_all`](crate::Simulator::respond_all). Cleared timers never fire.
+/// Animation frame subscriptions get one frame every time the clock is advanced, unless the
+/// app is in the background, see [`VirtualClock::set_background`]. Paused timers and
+/// animations are held back until they are resumed, and a timer which fell due in the
+/// meantime fires with the next advance. Clones share the same clock.
+///
+/// There is no locale, so instants are formatted the same way on every machine: relative
+/// to the clock in whole seconds (e.g. "90 seconds ago"), and as seconds since the Unix
+/// epoch in the other styles (e.g. "ShortDate 1700000000"). Parsing takes the same formats
+/// back, whatever the locale asked for.
+#[derive(Clone)]
+pub struct VirtualClock {
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    /// nanoseconds since the Unix epoch
+    now: u128,
+    time_zone: String,
+    /// offsets from UTC in seconds, by time zone name
+    utc_offsets: Vec<(String, i32)>,
+    timers: Vec<Timer>,
+    animations: Vec<Animation>,
+    background: bool,
+    /// responses confirming cleared animations, sent with the next advance
+    cleared: Vec<Response>,
+}
+
+struct Timer {
+    request: u32,
+    id: TimerId,
+    due: u128,
+    response: TimeResponse,
+    /// paused timers don't fire, even when they are due
+    paused: bool,
+}
+
+struct Animation {
+    request: u32,
+    id: TimerId,
+    paused: bool,
+}
+
+impl VirtualClock {
+    /// A clock showing `start`, in the UTC time zone.
+    pub fn new(start: Instant) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                now: nanos(start),
+                time_zone: "UTC".to_string(),
+                utc_offsets: vec![("UTC".to_string(), 0)],
+                timers: Vec::new(),
+                animations: Vec::new(),
+                background: false,
+                cleared: Vec::new(),
+            })),
+        }
+    }
+
+    /// Set the IANA name of the time zone the shell reports, e.g. "Europe/London".
+    pub fn set_time_zone(&self, name: &str) {
+        self.lock().time_zone = name.to_string();
+    }
+
+    /// Set the offset from UTC, in seconds, the shell reports for the time zone `name`. The
+    /// offset doesn't change with daylight saving time, and zones other than "UTC" are unknown
+    /// until they are set.
+    pub fn set_utc_offset(&self, name: &str, seconds: i32) {
+        let mut state = self.lock();
+        state.utc_offsets.retain(|(zone, _)| zone != name);
+        state.utc_offsets.push((name.to_string(), seconds));
+    }
+
+    /// The time the clock shows.
+    pub fn now(&self) -> Instant {
+        instant(self.lock().now)
+    }
+
+    /// Move the clock forward by `duration`, returning the responses to the timers which
+    /// fired, in the order they were due, followed by a frame for each animation.
+    pub fn advance(&self, duration: Duration) -> Vec<Response> {
+        let mut state = self.lock();
+        state.now += u128::from(duration.as_nanos());
+
+        let now = state.now;
+        let (mut fired, pending): (Vec<_>, Vec<_>) = state
+            .timers
+            .drain(..)
+            .partition(|timer| timer.due <= now && !timer.paused);
+        state.timers = pending;
+
+        fired.sort_by_key(|timer| timer.due);
+        let mut responses = std::mem::take(&mut state.cleared);
+        responses.extend(
+            fired
+                .into_iter()
+                .map(|timer| Response::new(timer.request, &timer.response)),
+        );
+
+        if !state.background {
+            let timestamp = Duration::new(u64::try_from(now).expect("Virtual clock overflowed."));
+            responses.extend(
+                state
+                    .animations
+                    .iter()
+                    .filter(|animation| !animation.paused)
+                    .map(|animation| {
+                        Response::new(
+                            animation.request,
+                            &TimeResponse::AnimationFrame {
+                                id: animation.id,
+                                timestamp,
+                            },
+                        )
+                    }),
+            );
+        }
+        responses
+    }
+
+    /// Move the app to the background, or back to the foreground, returning the responses
+    /// telling the animations they are paused or resumed.
+    pub fn set_background(&self, background: bool) -> Vec<Response> {
+        let mut state = self.lock();
+        if state.background == background {
+            return Vec::new();
+        }
+        state.background = background;
+
+        state
+            .animations
+            .iter()
+            .map(|animation| {
+                let id = animation.id;
+                let response = if background {
+                    TimeResponse::AnimationPaused { id }
+                } else {
+                    TimeResponse::AnimationResumed { id }
+                };
+                Response::new(animation.request, &response)
+            })
+            .collect()
+    }
+
+    /// Handle a time request from the app, with the `id` of the request it came in.
+    pub fn handle(&self, id: u32, request: &TimeRequest) -> Reply {
+        let mut state = self.lock();
+
+        match request {
+            TimeRequest::Now => Reply::respond(&TimeResponse::Now(instant(state.now))),
+            TimeRequest::NowQuantized { resolution } => Reply::respond(&TimeResponse::Now(
+                instant(state.now).quantized(*resolution),
+            )),
+            TimeRequest::TimeZone => Reply::respond(&TimeResponse::TimeZone {
+                name: state.time_zone.clone(),
+            }),
+            TimeRequest::NotifyAt { id: timer, instant } => {
+                state.timers.push(Timer {
+                    request: id,
+                    id: *timer,
+                    due: nanos(*instant),
+                    response: TimeResponse::InstantArrived { id: *timer },
+                    paused: false,
+                });
+                Reply::Later
+            }
+            TimeRequest::NotifyAfter {
+                id: timer,
+                duration,
+            } => {
+                let due = state.now + u128::from(duration.as_nanos());
+                state.timers.push(Timer {
+                    request: id,
+                    id: *timer,
+                    due,
+                    response: TimeResponse::DurationElapsed { id: *timer },
+                    paused: false,
+                });
+                Reply::Later
+            }
+            TimeRequest::Clear { id: timer } => {
+                state.timers.retain(|pending| pending.id != *timer);
+
+                let (cleared, animations): (Vec<_>, Vec<_>) = state
+                    .animations
+                    .drain(..)
+                    .partition(|animation| animation.id == *timer);
+                state.animations = animations;
+                state.cleared.extend(cleared.into_iter().map(|animation| {
+                    Response::new(animation.request, &TimeResponse::Cleared { id: *timer })
+                }));
+                Reply::Done
+            }
+            TimeRequest::Pause { id: timer } => {
+                state.pause(*timer, true);
+                Reply::Done
+            }
+            TimeRequest::Resume { id: timer, missed } => {
+                state.pause(*timer, false);
+                if *missed == MissedTicks::Skip {
+                    let now = state.now;
+                    for pending in state.timers.iter_mut() {
+                        if pending.id == *timer && pending.due <= now {
+                            pending.response = TimeResponse::Missed { id: *timer };
+                        }
+                    }
+                }
+                Reply::Done
+            }
+            TimeRequest::AnimationFrames { id: timer } => {
+                state.animations.push(Animation {
+                    request: id,
+                    id: *timer,
+                    paused: false,
+                });
+                Reply::Later
+            }
+            TimeRequest::Format { instant, style } => Reply::respond(&TimeResponse::Formatted {
+                text: format(state.now, nanos(*instant), *style),
+            }),
+            TimeRequest::Parse { text, style, .. } => {
+                Reply::respond(&match parse(state.now, text, *style) {
+                    Ok(parsed) => TimeResponse::Parsed {
+                        instant: instant(parsed),
+                    },
+                    Err(reason) => TimeResponse::ParseFailed { reason },
+                })
+            }
+            TimeRequest::UtcOffset { zone, .. } => {
+                let offset = state.utc_offsets.iter().find(|(name, _)| name == zone);
+                Reply::respond(&offset.map_or_else(
+                    || TimeResponse::UnknownTimeZone { name: zone.clone() },
+                    |(_, seconds)| TimeResponse::UtcOffset { seconds: *seconds },
+                ))
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Clock Mutex poisoned.")
+    }
+}
+
+impl State {
+    /// Pause or resume the timers and animations with the `id`
+    fn pause(&mut self, id: TimerId, paused: bool) {
+        for timer in self.timers.iter_mut().filter(|timer| timer.id == id) {
+            timer.paused = paused;
+        }
+        for animation in self
+            .animations
+            .iter_mut()
+            .filter(|animation| animation.id == id)
+        {
+            animation.paused = paused;
+        }
+    }
+}
+
+fn format(now: u128, instant: u128, style: FormatStyle) -> String {
+    match style {
+        FormatStyle::Relative if instant > now => {
+            format!("in {} seconds", (instant - now) / NANOS_PER_SEC)
+        }
+        FormatStyle::Relative => format!("{} seconds ago", (now - instant) / NANOS_PER_SEC),
+        style => format!("{style:?} {}", instant / NANOS_PER_SEC),
+    }
+}
+
+/// Parse `text` formatted by [`format`] in the `style`, back into nanoseconds since the epoch
+fn parse(now: u128, text: &str, style: FormatStyle) -> Result<u128, String> {
+    let seconds = |digits: &str| {
+        digits
+            .parse::<u64>()
+            .map(|seconds| u128::from(seconds) * NANOS_PER_SEC)
+            .map_err(|_| format!("{text:?} is not a {style:?} time"))
+    };
+
+    let parsed = match style {
+        FormatStyle::Relative => {
+            if let Some(ahead) = text
+                .strip_prefix("in ")
+                .and_then(|text| text.strip_suffix(" seconds"))
+            {
+                Ok(now + seconds(ahead)?)
+            } else if let Some(ago) = text.strip_suffix(" seconds ago") {
+                now.checked_sub(seconds(ago)?)
+                    .ok_or_else(|| format!("{text:?} is before the Unix epoch"))
+            } else {
+                Err(format!("{text:?} is not a Relative time"))
+            }
+        }
+        style => match text.strip_prefix(&format!("{style:?} ")) {
+            Some(digits) => seconds(digits),
+            None => Err(format!("{text:?} is not a {style:?} time")),
+        },
+    }?;
+
+    if parsed / NANOS_PER_SEC > u128::from(u64::MAX) {
+        return Err(format!("{text:?} is too far in the future"));
+    }
+    Ok(parsed)
+}
+
+fn nanos(instant: Instant) -> u128 {
+    u128::from(instant.seconds) * NANOS_PER_SEC + u128::from(instant.nanos)
+}
+
+fn instant(nanos: u128) -> Instant {
+    let seconds = u64::try_from(nanos / NANOS_PER_SEC).expect("Virtual clock overflowed.");
+    #[allow(clippy::cast_possible_truncation)]
+    let nanos = (nanos % NANOS_PER_SEC) as u32;
+
+    Instant::new(seconds, nanos).expect("Virtual clock overflowed.")
+}
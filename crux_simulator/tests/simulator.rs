@@ -0,0 +1,405 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_core::render::Render;
+    use crux_http::Http;
+    use crux_kv::KeyValue;
+    use crux_time::{Duration, Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    const COUNT_URL: &str = "https://example.com/count";
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Event {
+        Load,
+        StartTimer,
+        StopTimer,
+        Animate,
+        StopAnimation,
+        Watch,
+        Unwatch,
+
+        #[serde(skip)]
+        Loaded(crux_http::Result<crux_http::Response<String>>),
+        #[serde(skip)]
+        Stored(Result<Option<Vec<u8>>, crux_kv::error::KeyValueError>),
+        #[serde(skip)]
+        Tick(TimeResponse),
+        #[serde(skip)]
+        Frame(TimeResponse),
+        #[serde(skip)]
+        Changed(Result<(String, Option<Vec<u8>>), crux_kv::error::KeyValueError>),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        count: u32,
+        ticks: u32,
+        timer: Option<crux_time::TimerId>,
+        frames: u32,
+        animation: Option<crux_time::TimerId>,
+        watch: Option<crux_kv::WatchId>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    pub struct ViewModel {
+        pub count: u32,
+        pub ticks: u32,
+        pub frames: u32,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ViewModel;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Load => caps.http.get(COUNT_URL).expect_string().send(Event::Loaded),
+                Event::Loaded(Ok(mut response)) => {
+                    let body = response.take_body().unwrap();
+                    model.count = body.parse().unwrap();
+                    caps.key_value
+                        .set("count".to_string(), body.into_bytes(), Event::Stored);
+                }
+                Event::Loaded(Err(_)) => caps.render.render(),
+                Event::Stored(result) => {
+                    result.expect("count should be stored");
+                    caps.render.render();
+                }
+                Event::StartTimer => {
+                    let duration = Duration::from_secs(1).unwrap();
+                    model.timer = Some(caps.time.notify_after(duration, Event::Tick));
+                }
+                Event::StopTimer => {
+                    if let Some(timer) = model.timer.take() {
+                        caps.time.clear(timer);
+                    }
+                }
+                Event::Tick(TimeResponse::DurationElapsed { .. }) => {
+                    model.ticks += 1;
+                    let duration = Duration::from_secs(1).unwrap();
+                    model.timer = Some(caps.time.notify_after(duration, Event::Tick));
+                    caps.render.render();
+                }
+                Event::Tick(_) => {}
+                Event::Animate => {
+                    model.animation = Some(caps.time.request_animation_frames(Event::Frame));
+                }
+                Event::StopAnimation => {
+                    if let Some(animation) = model.animation {
+                        caps.time.clear(animation);
+                    }
+                }
+                Event::Frame(TimeResponse::AnimationFrame { .. }) => {
+                    model.frames += 1;
+                    caps.render.render();
+                }
+                Event::Frame(TimeResponse::Cleared { .. }) => model.animation = None,
+                Event::Frame(_) => {}
+                Event::Watch => {
+                    model.watch = Some(caps.key_value.watch("count".to_string(), Event::Changed));
+                }
+                Event::Unwatch => {
+                    if let Some(watch) = model.watch.take() {
+                        caps.key_value.unwatch(watch);
+                    }
+                }
+                Event::Changed(Ok((_, value))) => {
+                    let value = value.unwrap_or_else(|| b"0".to_vec());
+                    model.count = String::from_utf8(value).unwrap().parse().unwrap();
+                    caps.render.render();
+                }
+                Event::Changed(Err(_)) => model.watch = None,
+            }
+        }
+
+        fn view(&self, model: &Model) -> ViewModel {
+            ViewModel {
+                count: model.count,
+                ticks: model.ticks,
+                frames: model.frames,
+            }
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub http: Http<Event>,
+        pub key_value: KeyValue<Event>,
+        pub time: Time<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::{
+        bridge::{Bridge, LocalReply, Request},
+        Core,
+    };
+    use crux_http::protocol::{HttpResponse, HttpResult};
+    use crux_simulator::{
+        http::HttpStub, kv::MemoryKv, time::VirtualClock, Reply, Response, Simulator,
+    };
+    use crux_time::{
+        Duration, FormatStyle, Instant, MissedTicks, TimeRequest, TimeResponse, TimerId,
+    };
+
+    use crate::app::{App, Effect, EffectFfi, Event, ViewModel};
+
+    struct Shell {
+        http: HttpStub,
+        kv: MemoryKv,
+        clock: VirtualClock,
+    }
+
+    impl Shell {
+        fn new() -> Self {
+            Self {
+                http: HttpStub::new(),
+                kv: MemoryKv::new(),
+                clock: VirtualClock::new(Instant::new(0, 0).unwrap()),
+            }
+        }
+
+        fn simulator(&self) -> Simulator<Effect, App, impl FnMut(&Request<EffectFfi>) -> Reply> {
+            let http = self.http.clone();
+            let kv = self.kv.clone();
+            let clock = self.clock.clone();
+
+            Simulator::new(move |request: &Request<EffectFfi>| match &request.effect {
+                EffectFfi::Http(operation) => http.handle(operation),
+                EffectFfi::KeyValue(operation) => kv.handle(request.id.0, operation),
+                EffectFfi::Time(operation) => clock.handle(request.id.0, operation),
+                EffectFfi::Render(_) => Reply::Done,
+            })
+        }
+    }
+
+    #[test]
+    fn loads_and_stores_the_count() {
+        let shell = Shell::new();
+        shell.http.respond_with(
+            "GET",
+            "https://example.com/count",
+            crux_http::protocol::HttpResult::Ok(HttpResponse::ok().body("3").build()),
+        );
+        let mut simulator = shell.simulator();
+
+        simulator.send(&Event::Load);
+
+        assert_eq!(
+            simulator.view(),
+            ViewModel {
+                count: 3,
+                ticks: 0,
+                frames: 0
+            }
+        );
+        assert_eq!(shell.kv.get("count"), Some(b"3".to_vec()));
+        assert!(matches!(
+            simulator.requests().last().unwrap().effect,
+            EffectFfi::Render(_)
+        ));
+    }
+
+    #[test]
+    fn unknown_urls_are_not_found() {
+        let shell = Shell::new();
+        let mut simulator = shell.simulator();
+
+        simulator.send(&Event::Load);
+
+        assert_eq!(
+            simulator.view(),
+            ViewModel {
+                count: 0,
+                ticks: 0,
+                frames: 0
+            }
+        );
+        assert_eq!(shell.kv.get("count"), None);
+    }
+
+    #[test]
+    fn timers_fire_when_the_clock_advances() {
+        let shell = Shell::new();
+        let mut simulator = shell.simulator();
+
+        simulator.send(&Event::StartTimer);
+
+        let fired = shell.clock.advance(Duration::from_millis(500).unwrap());
+        assert!(fired.is_empty());
+
+        let fired = shell.clock.advance(Duration::from_millis(500).unwrap());
+        simulator.respond_all(fired);
+        assert_eq!(simulator.view().ticks, 1);
+
+        let fired = shell.clock.advance(Duration::from_secs(1).unwrap());
+        simulator.respond_all(fired);
+        assert_eq!(simulator.view().ticks, 2);
+
+        simulator.send(&Event::StopTimer);
+
+        let fired = shell.clock.advance(Duration::from_secs(10).unwrap());
+        assert!(fired.is_empty());
+        assert_eq!(shell.clock.now(), Instant::new(12, 0).unwrap());
+    }
+
+    #[test]
+    fn paused_timers_fire_once_resumed() {
+        let clock = VirtualClock::new(Instant::new(0, 0).unwrap());
+        let second = Duration::from_secs(1).unwrap();
+        let (late, skipped) = (TimerId(1), TimerId(2));
+
+        for (request, id) in [(10, late), (20, skipped)] {
+            let notify = TimeRequest::NotifyAfter {
+                id,
+                duration: second,
+            };
+            assert_eq!(clock.handle(request, &notify), Reply::Later);
+            assert_eq!(clock.handle(0, &TimeRequest::Pause { id }), Reply::Done);
+        }
+        assert!(clock.advance(second).is_empty());
+
+        let resume = |id, missed| clock.handle(0, &TimeRequest::Resume { id, missed });
+        assert_eq!(resume(late, MissedTicks::CatchUp), Reply::Done);
+        assert_eq!(resume(skipped, MissedTicks::Skip), Reply::Done);
+
+        assert_eq!(
+            clock.advance(Duration::new(0)),
+            [
+                Response::new(10, &TimeResponse::DurationElapsed { id: late }),
+                Response::new(20, &TimeResponse::Missed { id: skipped }),
+            ]
+        );
+    }
+
+    #[test]
+    fn parsing_takes_back_what_the_clock_formats() {
+        let shell = Shell::new();
+        shell.clock.advance(Duration::from_secs(100).unwrap());
+
+        let parse = |text: &str, style| {
+            shell.clock.handle(
+                0,
+                &TimeRequest::Parse {
+                    text: text.to_string(),
+                    locale: Some("fr-FR".to_string()),
+                    style,
+                },
+            )
+        };
+
+        assert_eq!(
+            parse("ShortDate 1700000000", FormatStyle::ShortDate),
+            Reply::respond(&TimeResponse::Parsed {
+                instant: Instant::new(1_700_000_000, 0).unwrap()
+            })
+        );
+        assert_eq!(
+            parse("90 seconds ago", FormatStyle::Relative),
+            Reply::respond(&TimeResponse::Parsed {
+                instant: Instant::new(10, 0).unwrap()
+            })
+        );
+        assert_eq!(
+            parse("9 janvier 2024", FormatStyle::LongDate),
+            Reply::respond(&TimeResponse::ParseFailed {
+                reason: r#""9 janvier 2024" is not a LongDate time"#.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn animation_frames_stop_in_the_background() {
+        let shell = Shell::new();
+        let mut simulator = shell.simulator();
+        let frame = Duration::from_millis(16).unwrap();
+
+        simulator.send(&Event::Animate);
+
+        simulator.respond_all(shell.clock.advance(frame));
+        simulator.respond_all(shell.clock.advance(frame));
+        assert_eq!(simulator.view().frames, 2);
+
+        simulator.respond_all(shell.clock.set_background(true));
+        assert!(shell.clock.advance(frame).is_empty());
+
+        simulator.respond_all(shell.clock.set_background(false));
+        simulator.respond_all(shell.clock.advance(frame));
+        assert_eq!(simulator.view().frames, 3);
+
+        simulator.send(&Event::StopAnimation);
+        simulator.respond_all(shell.clock.advance(frame));
+        assert!(shell.clock.advance(frame).is_empty());
+        assert_eq!(simulator.view().frames, 3);
+    }
+
+    #[test]
+    fn watches_see_changes_made_outside_the_app() {
+        let shell = Shell::new();
+        let mut simulator = shell.simulator();
+
+        simulator.send(&Event::Watch);
+
+        simulator.respond_all(shell.kv.change("count", Some(b"5".to_vec())));
+        assert_eq!(simulator.view().count, 5);
+
+        // other keys aren't watched
+        assert!(shell.kv.change("other", Some(b"6".to_vec())).is_empty());
+
+        simulator.respond_all(shell.kv.change("count", None));
+        assert_eq!(simulator.view().count, 0);
+
+        // the next change confirms the watch has stopped, without reaching the app
+        simulator.send(&Event::Unwatch);
+        simulator.respond_all(shell.kv.change("count", Some(b"7".to_vec())));
+        assert_eq!(simulator.view().count, 0);
+        assert!(shell.kv.change("count", Some(b"8".to_vec())).is_empty());
+    }
+
+    #[test]
+    fn stand_ins_can_back_some_capabilities_of_a_real_shell() {
+        let kv = MemoryKv::new();
+        let bridge = Bridge::<Effect, App>::new(Core::new()).with_fakes({
+            let kv = kv.clone();
+            move |request: &Request<EffectFfi>| match &request.effect {
+                EffectFfi::KeyValue(operation) => kv.handle(request.id.0, operation).into(),
+                _ => LocalReply::ToShell,
+            }
+        });
+
+        // the "real" shell gets the HTTP request
+        let requests: Vec<Request<EffectFfi>> =
+            bincode::deserialize(&bridge.process_event(&bincode::serialize(&Event::Load).unwrap()))
+                .unwrap();
+        let [Request {
+            id,
+            effect: EffectFfi::Http(_),
+            ..
+        }] = &requests[..]
+        else {
+            panic!("Expected an HTTP request");
+        };
+
+        // and the render, after the count is stored in the fake store
+        let response = HttpResult::Ok(HttpResponse::ok().body("3").build());
+        let requests: Vec<Request<EffectFfi>> = bincode::deserialize(
+            &bridge.handle_response(id.0, &bincode::serialize(&response).unwrap()),
+        )
+        .unwrap();
+        let [Request {
+            effect: EffectFfi::Render(_),
+            ..
+        }] = &requests[..]
+        else {
+            panic!("Expected a render");
+        };
+
+        assert_eq!(kv.get("count"), Some(b"3".to_vec()));
+    }
+}
@@ -0,0 +1,131 @@
+//! Issuing time requests and resolving them through the capability, the way an app ticking
+//! at the display's refresh rate does, either with a timer it sets again on every tick or
+//! with a subscription to the Shell's animation frames.
+//!
+//! Run with `cargo bench -p crux_time --bench time`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use crux_core::{Core, Request};
+use crux_time::{Duration, Instant, TimeRequest, TimeResponse, TimerId};
+
+mod app {
+    use crux_core::macros::Effect;
+    use crux_time::{Duration, Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    /// A tick at 60Hz, in nanoseconds
+    pub const TICK: u64 = 16_666_667;
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize)]
+    pub enum Event {
+        Now,
+        StartTimer,
+        StartFrames,
+
+        #[serde(skip)]
+        Time(TimeResponse),
+        #[serde(skip)]
+        Tick(TimeResponse),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        last: Option<TimeResponse>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            let tick = Duration::new(TICK);
+            match event {
+                Event::Now => caps.time.now(Event::Time),
+                Event::StartTimer => {
+                    caps.time.notify_after(tick, Event::Tick);
+                }
+                Event::StartFrames => {
+                    caps.time.request_animation_frames(Event::Time);
+                }
+                Event::Time(response) => model.last = Some(response),
+                Event::Tick(response) => {
+                    if let TimeResponse::DurationElapsed { .. } = response {
+                        caps.time.notify_after(tick, Event::Tick);
+                    }
+                }
+            }
+        }
+
+        fn view(&self, _model: &Model) {}
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+    }
+}
+
+use app::{App, Effect, Event};
+
+fn time_request(mut effects: Vec<Effect>) -> Request<TimeRequest> {
+    match effects.pop() {
+        Some(Effect::Time(request)) => request,
+        _ => panic!("expected a time request"),
+    }
+}
+
+fn time(c: &mut Criterion) {
+    let mut group = c.benchmark_group("time");
+
+    group.bench_function("now", |b| {
+        let core = Core::<Effect, App>::new();
+        let now = TimeResponse::Now(Instant::new(1_700_000_000, 0).unwrap());
+        b.iter_batched(
+            || time_request(core.process_event(Event::Now)),
+            |mut request| core.resolve(&mut request, now.clone()),
+            BatchSize::SmallInput,
+        );
+    });
+
+    // every tick resolves the timer, and the app sets a new one
+    group.bench_function("timer_tick", |b| {
+        let core = Core::<Effect, App>::new();
+        let mut request = time_request(core.process_event(Event::StartTimer));
+        b.iter(|| {
+            let TimeRequest::NotifyAfter { id, .. } = request.operation else {
+                panic!("expected a timer");
+            };
+            let effects = core.resolve(&mut request, TimeResponse::DurationElapsed { id });
+            request = time_request(effects);
+        });
+    });
+
+    // every tick resolves the same subscription again
+    group.bench_function("animation_frame", |b| {
+        let core = Core::<Effect, App>::new();
+        let mut request = time_request(core.process_event(Event::StartFrames));
+        let mut frame = 0;
+        b.iter(|| {
+            frame += 1;
+            let timestamp = Duration::new(frame * app::TICK);
+            core.resolve(
+                &mut request,
+                TimeResponse::AnimationFrame {
+                    id: TimerId(1),
+                    timestamp,
+                },
+            )
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, time);
+criterion_main!(benches);
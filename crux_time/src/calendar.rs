@@ -0,0 +1,192 @@
+//! Calendar arithmetic in a time zone
+//!
+//! Adding 24 hours to an [`Instant`] doesn't give "the same time tomorrow" on days when
+//! daylight saving time starts or ends, and midnight isn't always `seconds % 86400` away.
+//! The functions in this module do the arithmetic on the wall-clock time in the given
+//! time zone instead, and convert the result back to an [`Instant`], which can be passed
+//! to [`Time::notify_at`](crate::Time::notify_at).
+//!
+//! Wall-clock times which don't exist in the time zone (because the clocks go forward over them)
+//! are moved forward by the length of the gap, e.g. 02:30 becomes 03:30. Wall-clock times which
+//! occur twice (because the clocks go back over them) resolve to the earlier of the two.
+//!
+//! The time zone of the Shell can be obtained with [`Time::time_zone`](crate::Time::time_zone).
+
+use chrono::{DateTime, Days, LocalResult, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+pub use chrono_tz::Tz;
+
+use crate::{error::TimeResult, Instant, TimeError};
+
+/// Look up a time zone by its IANA name, e.g. "Europe/London".
+///
+/// Errors with [`TimeError::InvalidTimeZone`] if the name isn't known.
+pub fn time_zone(name: &str) -> TimeResult<Tz> {
+    name.parse().map_err(|_| TimeError::InvalidTimeZone)
+}
+
+/// The offset from UTC of `time_zone` at `instant`, in seconds, e.g. for a Shell to answer
+/// [`TimeRequest::UtcOffset`](crate::TimeRequest::UtcOffset).
+pub fn utc_offset(instant: Instant, time_zone: Tz) -> TimeResult<i32> {
+    let utc: DateTime<Utc> = instant.try_into()?;
+
+    Ok(utc
+        .with_timezone(&time_zone)
+        .offset()
+        .fix()
+        .local_minus_utc())
+}
+
+/// The instant at which the wall clock in `time_zone` next shows the same time as it does at `instant`.
+pub fn same_time_tomorrow(instant: Instant, time_zone: Tz) -> TimeResult<Instant> {
+    add_days(instant, 1, time_zone)
+}
+
+/// The instant at which the wall clock in `time_zone` shows the same time as it does at `instant`,
+/// `days` days later (or earlier, if `days` is negative).
+pub fn add_days(instant: Instant, days: i64, time_zone: Tz) -> TimeResult<Instant> {
+    let local = local_date_time(instant, time_zone)?;
+    let date = if days < 0 {
+        local
+            .date()
+            .checked_sub_days(Days::new(days.unsigned_abs()))
+    } else {
+        local
+            .date()
+            .checked_add_days(Days::new(days.unsigned_abs()))
+    };
+    let date = date.ok_or(TimeError::InvalidInstant)?;
+
+    resolve(date.and_time(local.time()), time_zone)
+}
+
+/// The first instant of the day which contains `instant`, in `time_zone`.
+pub fn start_of_day(instant: Instant, time_zone: Tz) -> TimeResult<Instant> {
+    let local = local_date_time(instant, time_zone)?;
+
+    resolve(local.date().and_time(NaiveTime::MIN), time_zone)
+}
+
+pub(crate) fn local_date_time(instant: Instant, time_zone: Tz) -> TimeResult<NaiveDateTime> {
+    let utc: DateTime<Utc> = instant.try_into()?;
+
+    Ok(utc.with_timezone(&time_zone).naive_local())
+}
+
+pub(crate) fn resolve(local: NaiveDateTime, time_zone: Tz) -> TimeResult<Instant> {
+    let date_time = match time_zone.from_local_datetime(&local) {
+        LocalResult::Single(date_time) | LocalResult::Ambiguous(date_time, _) => {
+            date_time.with_timezone(&Utc)
+        }
+        LocalResult::None => {
+            // the wall-clock time falls into a gap, interpret it with the offset in effect
+            // before the gap, which moves it forward by the gap's length
+            let before = local
+                .checked_sub_days(Days::new(1))
+                .ok_or(TimeError::InvalidInstant)?;
+            let offset = time_zone.offset_from_utc_datetime(&before).fix();
+            (local - offset).and_utc()
+        }
+    };
+
+    date_time.try_into()
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, TimeZone, Utc};
+    use chrono_tz::{America::Havana, Europe::London};
+
+    use super::*;
+
+    fn instant(year: i32, month: u32, day: u32, hour: u32, min: u32) -> Instant {
+        Utc.with_ymd_and_hms(year, month, day, hour, min, 0)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    fn utc(instant: Instant) -> DateTime<Utc> {
+        instant.try_into().unwrap()
+    }
+
+    #[test]
+    fn same_time_tomorrow_across_spring_forward() {
+        // 09:00 GMT, the clocks go forward overnight
+        let tomorrow = same_time_tomorrow(instant(2024, 3, 30, 9, 0), London).unwrap();
+
+        // 09:00 BST
+        assert_eq!(utc(tomorrow), utc(instant(2024, 3, 31, 8, 0)));
+    }
+
+    #[test]
+    fn same_time_tomorrow_across_fall_back() {
+        // 09:00 BST, the clocks go back overnight
+        let tomorrow = same_time_tomorrow(instant(2024, 10, 26, 8, 0), London).unwrap();
+
+        // 09:00 GMT
+        assert_eq!(utc(tomorrow), utc(instant(2024, 10, 27, 9, 0)));
+    }
+
+    #[test]
+    fn same_time_tomorrow_in_gap() {
+        // 01:30 GMT, which doesn't exist the next day
+        let tomorrow = same_time_tomorrow(instant(2024, 3, 30, 1, 30), London).unwrap();
+
+        // 02:30 BST
+        assert_eq!(utc(tomorrow), utc(instant(2024, 3, 31, 1, 30)));
+    }
+
+    #[test]
+    fn same_time_tomorrow_when_ambiguous() {
+        // 01:30 BST, which happens twice the next day
+        let tomorrow = same_time_tomorrow(instant(2024, 10, 26, 0, 30), London).unwrap();
+
+        // the first 01:30, still in BST
+        assert_eq!(utc(tomorrow), utc(instant(2024, 10, 27, 0, 30)));
+    }
+
+    #[test]
+    fn add_negative_days() {
+        // 09:00 BST
+        let earlier = add_days(instant(2024, 4, 2, 8, 0), -3, London).unwrap();
+
+        // 09:00 GMT
+        assert_eq!(utc(earlier), utc(instant(2024, 3, 30, 9, 0)));
+    }
+
+    #[test]
+    fn start_of_day_in_summer_time() {
+        let start = start_of_day(instant(2024, 7, 1, 12, 0), London).unwrap();
+
+        // midnight BST
+        assert_eq!(utc(start), utc(instant(2024, 6, 30, 23, 0)));
+    }
+
+    #[test]
+    fn start_of_day_when_midnight_is_skipped() {
+        // in Cuba, the clocks go forward from midnight to 01:00
+        let start = start_of_day(instant(2024, 3, 10, 16, 0), Havana).unwrap();
+
+        // 01:00 CDT
+        assert_eq!(utc(start), utc(instant(2024, 3, 10, 5, 0)));
+    }
+
+    #[test]
+    fn utc_offset_changes_with_summer_time() {
+        assert_eq!(utc_offset(instant(2024, 1, 15, 12, 0), London), Ok(0));
+        assert_eq!(utc_offset(instant(2024, 7, 15, 12, 0), London), Ok(3600));
+        assert_eq!(
+            utc_offset(instant(2024, 7, 15, 12, 0), Havana),
+            Ok(-4 * 3600)
+        );
+    }
+
+    #[test]
+    fn unknown_time_zone() {
+        assert_eq!(time_zone("Europe/London").unwrap(), London);
+        assert_eq!(
+            time_zone("Europe/Nowhere").unwrap_err(),
+            TimeError::InvalidTimeZone
+        );
+    }
+}
@@ -20,6 +20,11 @@ impl Duration {
         Self { nanos }
     }
 
+    /// The number of nanoseconds in the duration.
+    pub fn as_nanos(&self) -> u64 {
+        self.nanos
+    }
+
     /// Create a new `Duration` from the given number of milliseconds.
     ///
     /// Errors with [`TimeError::InvalidDuration`] if the number of milliseconds
@@ -13,4 +13,6 @@ pub enum TimeError {
     InvalidDuration,
     #[error("invalid Instant")]
     InvalidInstant,
+    #[error("invalid time zone")]
+    InvalidTimeZone,
 }
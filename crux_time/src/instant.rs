@@ -1,11 +1,17 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{duration::NANOS_PER_SEC, error::TimeResult, TimeError};
+use crate::{duration::NANOS_PER_SEC, error::TimeResult, Duration, TimeError};
 
 /// Represents a point in time (UTC):
 ///
 /// - seconds: number of seconds since the Unix epoch (1970-01-01T00:00:00Z)
 /// - nanos: number of nanoseconds since the last second
+///
+/// Like Unix time, the seconds don't count leap seconds: every day is exactly 86 400
+/// seconds long, and an instant can't represent 23:59:60. The Shell reports its platform's
+/// clock, which either repeats a second during a leap second, or smears it over the hours
+/// around it, so instants within 12 hours of a leap second may be up to a second off
+/// the true UTC time. Use [`Instant::to_tai`] where the count of elapsed SI seconds matters.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Instant {
@@ -27,8 +33,108 @@ impl Instant {
         }
         Ok(Self { seconds, nanos })
     }
+
+    /// This instant on the International Atomic Time (TAI) scale, counting every elapsed
+    /// second since the Unix epoch, leap seconds included. It's the instant's seconds plus
+    /// TAI − UTC at the time, from 10 seconds in 1972 to 37 seconds since 2017.
+    ///
+    /// Errors with [`TimeError::InvalidInstant`] for instants before 1972, when UTC was
+    /// not offset from TAI by whole seconds.
+    pub fn to_tai(&self) -> TimeResult<Instant> {
+        let offset = tai_offset(|start, _| start <= self.seconds)?;
+        let seconds = self
+            .seconds
+            .checked_add(offset)
+            .ok_or(TimeError::InvalidInstant)?;
+
+        Instant::new(seconds, self.nanos)
+    }
+
+    /// The UTC instant of an `instant` on the TAI scale, as returned by [`Instant::to_tai`].
+    /// An instant during a leap second maps to the second before it, which a Unix clock
+    /// repeats.
+    ///
+    /// Errors with [`TimeError::InvalidInstant`] for instants before 1972.
+    pub fn from_tai(instant: Instant) -> TimeResult<Instant> {
+        let offset = tai_offset(|start, offset| start + offset <= instant.seconds)?;
+        let mut seconds = instant.seconds - offset;
+
+        // during a leap second, the next offset hasn't taken effect yet, which would
+        // otherwise put the instant at the start of the next day
+        if LEAP_SECONDS
+            .iter()
+            .any(|&(start, next)| start == seconds && next == offset + 1)
+        {
+            seconds -= 1;
+        }
+
+        Instant::new(seconds, instant.nanos)
+    }
+
+    /// This instant rounded down to a whole multiple of `resolution` since the Unix epoch,
+    /// e.g. to the start of the minute for a resolution of 60 seconds. A zero resolution
+    /// leaves the instant as it is.
+    pub fn quantized(&self, resolution: Duration) -> Instant {
+        let resolution = u128::from(resolution.as_nanos());
+        if resolution == 0 {
+            return *self;
+        }
+
+        let nanos_per_sec = u128::from(NANOS_PER_SEC);
+        let nanos = u128::from(self.seconds) * nanos_per_sec + u128::from(self.nanos);
+        let quantized = nanos - nanos % resolution;
+
+        // rounding down can't overflow the seconds, and the remainder is below a second
+        Instant {
+            seconds: (quantized / nanos_per_sec) as u64,
+            nanos: (quantized % nanos_per_sec) as u32,
+        }
+    }
+}
+
+/// TAI − UTC, from the last entry of [`LEAP_SECONDS`] which has taken effect
+fn tai_offset(taken_effect: impl Fn(u64, u64) -> bool) -> TimeResult<u64> {
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|&&(start, offset)| taken_effect(start, offset))
+        .map(|&(_, offset)| offset)
+        .ok_or(TimeError::InvalidInstant)
 }
 
+/// The Unix time from which TAI − UTC has each value, in seconds, from the IERS
+/// leap second list. No leap second has been scheduled since the one at the end of 2016.
+const LEAP_SECONDS: [(u64, u64); 28] = [
+    (63_072_000, 10),    // 1972-01-01
+    (78_796_800, 11),    // 1972-07-01
+    (94_694_400, 12),    // 1973-01-01
+    (126_230_400, 13),   // 1974-01-01
+    (157_766_400, 14),   // 1975-01-01
+    (189_302_400, 15),   // 1976-01-01
+    (220_924_800, 16),   // 1977-01-01
+    (252_460_800, 17),   // 1978-01-01
+    (283_996_800, 18),   // 1979-01-01
+    (315_532_800, 19),   // 1980-01-01
+    (362_793_600, 20),   // 1981-07-01
+    (394_329_600, 21),   // 1982-07-01
+    (425_865_600, 22),   // 1983-07-01
+    (489_024_000, 23),   // 1985-07-01
+    (567_993_600, 24),   // 1988-01-01
+    (631_152_000, 25),   // 1990-01-01
+    (662_688_000, 26),   // 1991-01-01
+    (709_948_800, 27),   // 1992-07-01
+    (741_484_800, 28),   // 1993-07-01
+    (773_020_800, 29),   // 1994-07-01
+    (820_454_400, 30),   // 1996-01-01
+    (867_715_200, 31),   // 1997-07-01
+    (915_148_800, 32),   // 1999-01-01
+    (1_136_073_600, 33), // 2006-01-01
+    (1_230_768_000, 34), // 2009-01-01
+    (1_341_100_800, 35), // 2012-07-01
+    (1_435_708_800, 36), // 2015-07-01
+    (1_483_228_800, 37), // 2017-01-01
+];
+
 #[cfg(feature = "chrono")]
 impl TryFrom<Instant> for chrono::DateTime<chrono::Utc> {
     type Error = TimeError;
@@ -70,6 +176,63 @@ mod test {
         let instant = Instant::new(1_000_000_000, 1_000_000_000);
         assert_eq!(instant.unwrap_err(), TimeError::InvalidInstant);
     }
+
+    #[test]
+    fn quantized_instant() {
+        let instant = Instant::new(1_000_000_119, 999_999_999).unwrap();
+
+        let minute = Duration::from_secs(60).unwrap();
+        assert_eq!(
+            instant.quantized(minute),
+            Instant::new(1_000_000_080, 0).unwrap()
+        );
+
+        let quarter_second = Duration::from_millis(250).unwrap();
+        assert_eq!(
+            instant.quantized(quarter_second),
+            Instant::new(1_000_000_119, 750_000_000).unwrap()
+        );
+
+        assert_eq!(instant.quantized(Duration::new(0)), instant);
+    }
+
+    #[test]
+    fn tai_counts_leap_seconds() {
+        // 2016-12-31T23:59:59Z and 2017-01-01T00:00:00Z, either side of the last leap second
+        let before = Instant::new(1_483_228_799, 500).unwrap();
+        let after = Instant::new(1_483_228_800, 500).unwrap();
+
+        let before_tai = before.to_tai().unwrap();
+        let after_tai = after.to_tai().unwrap();
+        assert_eq!(before_tai, Instant::new(1_483_228_799 + 36, 500).unwrap());
+        assert_eq!(after_tai.seconds - before_tai.seconds, 2);
+
+        assert_eq!(Instant::from_tai(before_tai).unwrap(), before);
+        assert_eq!(Instant::from_tai(after_tai).unwrap(), after);
+
+        // 23:59:60 is the second 23:59:59 again
+        let leap_second = Instant::new(before_tai.seconds + 1, 0).unwrap();
+        assert_eq!(
+            Instant::from_tai(leap_second).unwrap(),
+            Instant::new(1_483_228_799, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn tai_is_undefined_before_1972() {
+        let instant = Instant::new(63_071_999, 0).unwrap();
+        assert_eq!(instant.to_tai().unwrap_err(), TimeError::InvalidInstant);
+        assert_eq!(
+            Instant::from_tai(Instant::new(63_072_009, 0).unwrap()).unwrap_err(),
+            TimeError::InvalidInstant
+        );
+
+        let instant = Instant::new(63_072_000, 0).unwrap();
+        assert_eq!(
+            Instant::from_tai(instant.to_tai().unwrap()).unwrap(),
+            instant
+        );
+    }
 }
 
 #[cfg(feature = "chrono")]
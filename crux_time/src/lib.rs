@@ -3,10 +3,18 @@
 //! Current time (on a wall clock) is considered a side-effect (although if we were to get pedantic, it's
 //! more of a side-cause) by Crux, and has to be obtained externally. This capability provides a simple
 //! interface to do so.
+//!
+//! With the `calendar` feature enabled, the [`calendar`] module provides calendar arithmetic in
+//! the Shell's time zone, which is resilient to daylight saving time changes, and the [`schedule`]
+//! module provides recurring schedules, e.g. every weekday at 09:00.
 
+#[cfg(feature = "calendar")]
+pub mod calendar;
 pub mod duration;
 pub mod error;
 pub mod instant;
+#[cfg(feature = "calendar")]
+pub mod schedule;
 
 pub use duration::Duration;
 pub use error::TimeError;
@@ -15,32 +23,170 @@ pub use instant::Instant;
 use serde::{Deserialize, Serialize};
 
 use crux_core::capability::{CapabilityContext, Operation};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use futures::{Stream, StreamExt};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TimeRequest {
     Now,
-    NotifyAt { id: TimerId, instant: Instant },
-    NotifyAfter { id: TimerId, duration: Duration },
-    Clear { id: TimerId },
+    NotifyAt {
+        id: TimerId,
+        instant: Instant,
+    },
+    NotifyAfter {
+        id: TimerId,
+        duration: Duration,
+    },
+    Clear {
+        id: TimerId,
+    },
+    TimeZone,
+    Format {
+        instant: Instant,
+        style: FormatStyle,
+    },
+    /// Deliver a [`TimeResponse::AnimationFrame`] for every frame the Shell draws, driven by
+    /// the display's refresh (e.g. `CADisplayLink`, `Choreographer` or `requestAnimationFrame`),
+    /// until the subscription is cleared with [`TimeRequest::Clear`], which the Shell confirms
+    /// with [`TimeResponse::Cleared`].
+    ///
+    /// While the app is in the background the Shell stops delivering frames, and sends
+    /// [`TimeResponse::AnimationPaused`] when it goes to the background and
+    /// [`TimeResponse::AnimationResumed`] when it comes back.
+    AnimationFrames {
+        id: TimerId,
+    },
+    /// Look up the offset from UTC of the time zone with the IANA name `zone` at `instant`,
+    /// e.g. of another user's time zone, in the Shell's time zone database.
+    UtcOffset {
+        zone: String,
+        instant: Instant,
+    },
+    /// The current time, like [`TimeRequest::Now`], but rounded down to a whole multiple of
+    /// `resolution` (see [`Instant::quantized`]), for apps which don't need a precise clock.
+    /// The Shell answers with a [`TimeResponse::Now`], and may round to a coarser resolution
+    /// than requested, but never to a finer one.
+    NowQuantized {
+        resolution: Duration,
+    },
+    /// Parse a date or time the user entered as `text`, e.g. "9 janvier 2024", with the
+    /// Shell's platform facilities, as it would be formatted in `style` in the `locale`, a
+    /// BCP 47 language tag like "fr-FR", or the user's locale if there's none. A date or time
+    /// without a time zone is in the Shell's time zone. The Shell answers with a
+    /// [`TimeResponse::Parsed`], or a [`TimeResponse::ParseFailed`] if the text doesn't
+    /// match, or it can't parse the style, e.g. [`FormatStyle::Relative`].
+    Parse {
+        text: String,
+        locale: Option<String>,
+        style: FormatStyle,
+    },
+    /// Hold back the responses of the timer or subscription `id`, e.g. while the app is in the
+    /// background, without clearing it. Timers keep running while paused, only their
+    /// notifications are held back, and the Shell stops delivering animation frames.
+    Pause {
+        id: TimerId,
+    },
+    /// Deliver the responses of the timer or subscription `id` again, after a
+    /// [`TimeRequest::Pause`]. What happens to a notification which fell due while it was
+    /// paused is up to the `missed` policy. Animation frames are never caught up.
+    Resume {
+        id: TimerId,
+        missed: MissedTicks,
+    },
 }
 
+/// What the Shell does with a notification which fell due while its timer was paused, when
+/// the timer is resumed with [`TimeRequest::Resume`]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct TimerId(pub usize);
+#[serde(rename_all = "camelCase")]
+pub enum MissedTicks {
+    /// Answer the timer with [`TimeResponse::Missed`], so the app can tell the tick is late
+    /// and move on to the next one
+    Skip,
+    /// Deliver the notification as soon as the timer is resumed, as if it had just fallen due
+    CatchUp,
+}
 
-fn get_timer_id() -> TimerId {
-    static COUNTER: AtomicUsize = AtomicUsize::new(1);
-    TimerId(COUNTER.fetch_add(1, Ordering::Relaxed))
+/// How the Shell should format an [`Instant`] for display, following the rules of the
+/// user's locale, e.g. "2 hours ago" or "Tuesday, 9 January 2024".
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FormatStyle {
+    /// Relative to the current time, e.g. "2 hours ago" or "in 3 days"
+    Relative,
+    /// A date with a numeric month, e.g. "09/01/2024"
+    ShortDate,
+    /// A date with the month spelled out, e.g. "9 January 2024"
+    LongDate,
+    /// A time of day, e.g. "09:30"
+    ShortTime,
+    /// A short date and time, e.g. "09/01/2024, 09:30"
+    ShortDateTime,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimerId(pub u64);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TimeResponse {
     Now(Instant),
-    InstantArrived { id: TimerId },
-    DurationElapsed { id: TimerId },
-    Cleared { id: TimerId },
+    InstantArrived {
+        id: TimerId,
+    },
+    DurationElapsed {
+        id: TimerId,
+    },
+    Cleared {
+        id: TimerId,
+    },
+    /// The IANA name of the Shell's time zone, e.g. "Europe/London"
+    TimeZone {
+        name: String,
+    },
+    /// An instant formatted by the Shell, as requested with [`TimeRequest::Format`]
+    Formatted {
+        text: String,
+    },
+    /// A frame of a [`TimeRequest::AnimationFrames`] subscription
+    AnimationFrame {
+        id: TimerId,
+        /// When the frame is displayed, on a monotonic clock with an arbitrary origin, so
+        /// only the difference between frames is meaningful
+        timestamp: Duration,
+    },
+    /// The app went to the background, and no frames will be delivered until it is resumed
+    AnimationPaused {
+        id: TimerId,
+    },
+    /// The app came back from the background, and frames will be delivered again
+    AnimationResumed {
+        id: TimerId,
+    },
+    /// The offset from UTC of a time zone requested with [`TimeRequest::UtcOffset`], which is
+    /// added to UTC to get the wall-clock time, e.g. 3600 for "Europe/Paris" in winter
+    UtcOffset {
+        seconds: i32,
+    },
+    /// The time zone requested with [`TimeRequest::UtcOffset`] isn't in the Shell's time zone
+    /// database
+    UnknownTimeZone {
+        name: String,
+    },
+    /// The instant of a date or time parsed by the Shell, as requested with
+    /// [`TimeRequest::Parse`]
+    Parsed {
+        instant: Instant,
+    },
+    /// The text of a [`TimeRequest::Parse`] couldn't be parsed as a date or time, with the
+    /// Shell's explanation, e.g. for showing next to the input field
+    ParseFailed {
+        reason: String,
+    },
+    /// The timer fell due while it was paused, and was resumed with [`MissedTicks::Skip`]
+    Missed {
+        id: TimerId,
+    },
 }
 
 impl Operation for TimeRequest {
@@ -53,6 +199,7 @@ impl Operation for TimeRequest {
 /// notifications when a specific instant has arrived or a duration has elapsed.
 pub struct Time<Ev> {
     context: CapabilityContext<TimeRequest, Ev>,
+    resolution: Option<Duration>,
 }
 
 impl<Ev> crux_core::Capability<Ev> for Time<Ev> {
@@ -65,7 +212,10 @@ impl<Ev> crux_core::Capability<Ev> for Time<Ev> {
         Ev: 'static,
         NewEv: 'static + Send,
     {
-        Time::new(self.context.map_event(f))
+        Time {
+            context: self.context.map_event(f),
+            resolution: self.resolution,
+        }
     }
 
     #[cfg(feature = "typegen")]
@@ -82,6 +232,7 @@ impl<Ev> Clone for Time<Ev> {
     fn clone(&self) -> Self {
         Self {
             context: self.context.clone(),
+            resolution: self.resolution,
         }
     }
 }
@@ -91,7 +242,25 @@ where
     Ev: 'static,
 {
     pub fn new(context: CapabilityContext<TimeRequest, Ev>) -> Self {
-        Self { context }
+        Self {
+            context,
+            resolution: None,
+        }
+    }
+
+    /// A new timer id, from the core's id generator
+    fn timer_id(&self) -> TimerId {
+        TimerId(self.context.next_id().to_u64())
+    }
+
+    /// A copy of this capability which asks the Shell for the current time rounded down to
+    /// a whole multiple of `resolution`, e.g. to the minute, in [`Time::now`] and
+    /// [`Time::now_async`], so the app never sees a more precise clock than it needs.
+    pub fn with_resolution(&self, resolution: Duration) -> Self {
+        Self {
+            context: self.context.clone(),
+            resolution: Some(resolution),
+        }
     }
 
     /// Request current time, which will be passed to the app as a [`TimeResponse`] containing an [`Instant`]
@@ -113,7 +282,12 @@ where
     /// Request current time, which will be passed to the app as a [`TimeResponse`] containing an [`Instant`]
     /// This is an async call to use with [`crux_core::compose::Compose`].
     pub async fn now_async(&self) -> TimeResponse {
-        self.context.request_from_shell(TimeRequest::Now).await
+        let request = match self.resolution {
+            Some(resolution) => TimeRequest::NowQuantized { resolution },
+            None => TimeRequest::Now,
+        };
+
+        self.context.request_from_shell(request).await
     }
 
     /// Ask to receive a notification when the specified [`Instant`] has arrived.
@@ -121,7 +295,7 @@ where
     where
         F: FnOnce(TimeResponse) -> Ev + Send + Sync + 'static,
     {
-        let tid = get_timer_id();
+        let tid = self.timer_id();
         self.context.spawn({
             let context = self.context.clone();
             let this = self.clone();
@@ -147,7 +321,7 @@ where
     where
         F: FnOnce(TimeResponse) -> Ev + Send + Sync + 'static,
     {
-        let tid = get_timer_id();
+        let tid = self.timer_id();
         self.context.spawn({
             let context = self.context.clone();
             let this = self.clone();
@@ -168,6 +342,291 @@ where
             .await
     }
 
+    /// Wait for `duration` to elapse, with a new timer, e.g. to delay the next step of a task.
+    /// This is an async call to use with [`crux_core::compose::Compose`].
+    pub async fn sleep_async(&self, duration: Duration) -> TimeResponse {
+        self.notify_after_async(self.timer_id(), duration).await
+    }
+
+    /// Request the Shell's time zone, which will be passed to the app as a [`TimeResponse`]
+    /// containing its IANA name, wrapped in the event produced by the `callback`.
+    pub fn time_zone<F>(&self, callback: F)
+    where
+        F: FnOnce(TimeResponse) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            let this = self.clone();
+
+            async move {
+                context.update_app(callback(this.time_zone_async().await));
+            }
+        });
+    }
+
+    /// Request the Shell's time zone, which will be passed to the app as a [`TimeResponse`]
+    /// containing its IANA name.
+    /// This is an async call to use with [`crux_core::compose::Compose`].
+    pub async fn time_zone_async(&self) -> TimeResponse {
+        self.context.request_from_shell(TimeRequest::TimeZone).await
+    }
+
+    /// Ask the Shell for the offset from UTC of the time zone with the IANA name `zone`, e.g.
+    /// "America/New_York", at `instant`, so that times in other users' time zones can be
+    /// computed without bundling the time zone database. The result is passed to the app as a
+    /// [`TimeResponse::UtcOffset`], or [`TimeResponse::UnknownTimeZone`] if the Shell doesn't
+    /// know the zone, wrapped in the event produced by the `callback`.
+    ///
+    /// The offset only holds around `instant`, as it changes with daylight saving time.
+    pub fn utc_offset<F>(&self, zone: impl Into<String>, instant: Instant, callback: F)
+    where
+        F: FnOnce(TimeResponse) -> Ev + Send + Sync + 'static,
+    {
+        let zone = zone.into();
+        self.context.spawn({
+            let context = self.context.clone();
+            let this = self.clone();
+
+            async move {
+                context.update_app(callback(this.utc_offset_async(zone, instant).await));
+            }
+        });
+    }
+
+    /// Ask the Shell for the offset from UTC of the time zone with the IANA name `zone` at
+    /// `instant`, see [`Time::utc_offset`].
+    /// This is an async call to use with [`crux_core::compose::Compose`].
+    pub async fn utc_offset_async(
+        &self,
+        zone: impl Into<String>,
+        instant: Instant,
+    ) -> TimeResponse {
+        let zone = zone.into();
+        self.context
+            .request_from_shell(TimeRequest::UtcOffset { zone, instant })
+            .await
+    }
+
+    /// Ask the Shell to format `instant` in the given `style` with the rules of the user's
+    /// locale, e.g. to show "2 hours ago" in a view model. The result is passed to the app as a
+    /// [`TimeResponse::Formatted`] wrapped in the event produced by the `callback`.
+    pub fn format<F>(&self, instant: Instant, style: FormatStyle, callback: F)
+    where
+        F: FnOnce(TimeResponse) -> Ev + Send + Sync + 'static,
+    {
+        self.context.spawn({
+            let context = self.context.clone();
+            let this = self.clone();
+
+            async move {
+                context.update_app(callback(this.format_async(instant, style).await));
+            }
+        });
+    }
+
+    /// Ask the Shell to format `instant` in the given `style` with the rules of the user's locale.
+    /// This is an async call to use with [`crux_core::compose::Compose`].
+    pub async fn format_async(&self, instant: Instant, style: FormatStyle) -> TimeResponse {
+        self.context
+            .request_from_shell(TimeRequest::Format { instant, style })
+            .await
+    }
+
+    /// Ask the Shell to parse a date or time the user entered as `text`, as it would be
+    /// formatted in `style` in the `locale` (e.g. "fr-FR"), or the user's locale if it's `None`,
+    /// since locale-aware parsing needs data which is impractical to bundle with the core. The
+    /// result is passed to the app as a [`TimeResponse::Parsed`], or
+    /// [`TimeResponse::ParseFailed`] if the Shell couldn't parse it, wrapped in the event
+    /// produced by the `callback`.
+    pub fn parse<F>(
+        &self,
+        text: impl Into<String>,
+        locale: Option<String>,
+        style: FormatStyle,
+        callback: F,
+    ) where
+        F: FnOnce(TimeResponse) -> Ev + Send + Sync + 'static,
+    {
+        let text = text.into();
+        self.context.spawn({
+            let context = self.context.clone();
+            let this = self.clone();
+
+            async move {
+                context.update_app(callback(this.parse_async(text, locale, style).await));
+            }
+        });
+    }
+
+    /// Ask the Shell to parse a date or time the user entered as `text`, see [`Time::parse`].
+    /// This is an async call to use with [`crux_core::compose::Compose`].
+    pub async fn parse_async(
+        &self,
+        text: impl Into<String>,
+        locale: Option<String>,
+        style: FormatStyle,
+    ) -> TimeResponse {
+        let text = text.into();
+        self.context
+            .request_from_shell(TimeRequest::Parse {
+                text,
+                locale,
+                style,
+            })
+            .await
+    }
+
+    /// Compute the instant at which the wall clock in the Shell's time zone next shows the
+    /// same time as it does at `instant`, e.g. to schedule a daily reminder with [`Time::notify_at`].
+    /// This is an async call to use with [`crux_core::compose::Compose`].
+    #[cfg(feature = "calendar")]
+    pub async fn same_time_tomorrow_async(&self, instant: Instant) -> error::TimeResult<Instant> {
+        let time_zone = self.calendar_time_zone().await?;
+        calendar::same_time_tomorrow(instant, time_zone)
+    }
+
+    /// Compute the first instant of the day which contains `instant`, in the Shell's time zone.
+    /// This is an async call to use with [`crux_core::compose::Compose`].
+    #[cfg(feature = "calendar")]
+    pub async fn start_of_day_async(&self, instant: Instant) -> error::TimeResult<Instant> {
+        let time_zone = self.calendar_time_zone().await?;
+        calendar::start_of_day(instant, time_zone)
+    }
+
+    /// Ask to receive a notification at every occurrence of the `schedule`. Only the next
+    /// occurrence is requested from the Shell at a time, and the following one is computed
+    /// once it has arrived. The returned [`TimerId`] is the same for every occurrence, so
+    /// passing it to [`Time::clear`] stops the schedule, and to [`Time::pause`] holds it back.
+    /// Occurrences skipped on [`Time::resume`] are not passed to the app.
+    ///
+    /// The occurrences are computed from the precise current time, even with
+    /// [`Time::with_resolution`], so the first one is never in the past. The schedule ends
+    /// if the Shell answers the request for the current time with anything but a
+    /// [`TimeResponse::Now`], or the next occurrence can't be represented.
+    #[cfg(feature = "calendar")]
+    pub fn notify_on_schedule<F>(&self, schedule: schedule::Schedule, callback: F) -> TimerId
+    where
+        F: Fn(TimeResponse) -> Ev + Send + Sync + 'static,
+    {
+        let tid = self.timer_id();
+        self.context.spawn({
+            let context = self.context.clone();
+            let this = self.clone();
+
+            async move {
+                let Some(mut after) = this.precise_now().await else {
+                    return;
+                };
+
+                while let Ok(next) = schedule.next_after(after) {
+                    let response = this.notify_at_async(tid, next).await;
+
+                    // occurrences missed while paused are skipped, carrying on from now
+                    if let TimeResponse::Missed { .. } = response {
+                        let Some(now) = this.precise_now().await else {
+                            break;
+                        };
+                        after = now;
+                        continue;
+                    }
+
+                    let arrived = matches!(response, TimeResponse::InstantArrived { .. });
+
+                    context.update_app(callback(response));
+                    if !arrived {
+                        break;
+                    }
+                    after = next;
+                }
+            }
+        });
+
+        tid
+    }
+
+    /// The current time, whatever the resolution, or `None` if the Shell answers with
+    /// anything but a [`TimeResponse::Now`]
+    #[cfg(feature = "calendar")]
+    async fn precise_now(&self) -> Option<Instant> {
+        match self.context.request_from_shell(TimeRequest::Now).await {
+            TimeResponse::Now(now) => Some(now),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "calendar")]
+    async fn calendar_time_zone(&self) -> error::TimeResult<calendar::Tz> {
+        match self.time_zone_async().await {
+            TimeResponse::TimeZone { name } => calendar::time_zone(&name),
+            _ => panic!("attempt to convert TimeResponse other than TimeZone to a time zone"),
+        }
+    }
+
+    /// Subscribe to the Shell's animation frames, e.g. to drive an animation in the core. Every
+    /// [`TimeResponse::AnimationFrame`], as well as [`TimeResponse::AnimationPaused`] and
+    /// [`TimeResponse::AnimationResumed`] when the app goes to the background and comes back,
+    /// is passed to the app wrapped in the event produced by the `callback`. Passing the
+    /// returned [`TimerId`] to [`Time::clear`] ends the subscription, after a final
+    /// [`TimeResponse::Cleared`].
+    pub fn request_animation_frames<F>(&self, callback: F) -> TimerId
+    where
+        F: Fn(TimeResponse) -> Ev + Send + Sync + 'static,
+    {
+        let tid = self.timer_id();
+        self.context.spawn({
+            let context = self.context.clone();
+            let mut frames = self.animation_frames_async(tid);
+
+            async move {
+                while let Some(response) = frames.next().await {
+                    let cleared = matches!(response, TimeResponse::Cleared { .. });
+
+                    context.update_app(callback(response));
+                    if cleared {
+                        break;
+                    }
+                }
+            }
+        });
+
+        tid
+    }
+
+    /// Subscribe to the Shell's animation frames, see [`Time::request_animation_frames`].
+    /// This is an async call to use with [`crux_core::compose::Compose`].
+    pub fn animation_frames_async(&self, id: TimerId) -> impl Stream<Item = TimeResponse> {
+        self.context
+            .stream_from_shell(TimeRequest::AnimationFrames { id })
+    }
+
+    /// Ask the Shell to hold back the notifications of the timer or subscription `id`, e.g.
+    /// when the app goes to the background, as reported by the `crux_lifecycle` capability,
+    /// without clearing it. See [`TimeRequest::Pause`].
+    pub fn pause(&self, id: TimerId) {
+        self.context.spawn({
+            let context = self.context.clone();
+
+            async move {
+                context.notify_shell(TimeRequest::Pause { id }).await;
+            }
+        });
+    }
+
+    /// Ask the Shell to deliver the notifications of a paused timer or subscription `id`
+    /// again, with the `missed` policy for a notification which fell due in the meantime.
+    /// See [`TimeRequest::Resume`].
+    pub fn resume(&self, id: TimerId, missed: MissedTicks) {
+        self.context.spawn({
+            let context = self.context.clone();
+
+            async move {
+                context
+                    .notify_shell(TimeRequest::Resume { id, missed })
+                    .await;
+            }
+        });
+    }
+
     pub fn clear(&self, id: TimerId) {
         self.context.spawn({
             let context = self.context.clone();
@@ -220,6 +679,20 @@ mod test {
 
         let deserialized: TimeRequest = serde_json::from_str(&serialized).unwrap();
         assert_eq!(now, deserialized);
+
+        let format = TimeRequest::Format {
+            instant: Instant::new(1, 2).expect("valid instant"),
+            style: FormatStyle::ShortDateTime,
+        };
+
+        let serialized = serde_json::to_string(&format).unwrap();
+        assert_eq!(
+            &serialized,
+            r#"{"format":{"instant":{"seconds":1,"nanos":2},"style":"shortDateTime"}}"#
+        );
+
+        let deserialized: TimeRequest = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(format, deserialized);
     }
 
     #[test]
@@ -247,5 +720,15 @@ mod test {
 
         let deserialized: TimeResponse = serde_json::from_str(&serialized).unwrap();
         assert_eq!(now, deserialized);
+
+        let formatted = TimeResponse::Formatted {
+            text: "2 hours ago".to_string(),
+        };
+
+        let serialized = serde_json::to_string(&formatted).unwrap();
+        assert_eq!(&serialized, r#"{"formatted":{"text":"2 hours ago"}}"#);
+
+        let deserialized: TimeResponse = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(formatted, deserialized);
     }
 }
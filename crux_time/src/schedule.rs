@@ -0,0 +1,177 @@
+//! Recurring schedules, e.g. "every weekday at 09:00 in Europe/London"
+//!
+//! The next occurrence of a [`Schedule`] is computed in the core, with the same rules for
+//! daylight saving time changes as the [`calendar`](crate::calendar) module. Use
+//! [`Time::notify_on_schedule`](crate::Time::notify_on_schedule) to be notified of each
+//! occurrence, which only asks the Shell for a notification of the next one at a time.
+
+pub use chrono::Weekday;
+use chrono::{Datelike, Days, NaiveTime};
+
+use crate::{
+    calendar::{local_date_time, resolve, Tz},
+    error::TimeResult,
+    Instant, TimeError,
+};
+
+/// A wall-clock time on some days of the week, in a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    /// bit n is set for the day n days from Monday
+    days: u8,
+    time: NaiveTime,
+    time_zone: Tz,
+}
+
+impl Schedule {
+    /// Every day at `hour`:`minute`.
+    ///
+    /// Errors with [`TimeError::InvalidTime`] if the hour or minute is out of range.
+    pub fn daily(hour: u32, minute: u32, time_zone: Tz) -> TimeResult<Self> {
+        use Weekday::{Fri, Mon, Sat, Sun, Thu, Tue, Wed};
+
+        Self::on_days(
+            &[Mon, Tue, Wed, Thu, Fri, Sat, Sun],
+            hour,
+            minute,
+            time_zone,
+        )
+    }
+
+    /// Monday to Friday at `hour`:`minute`.
+    ///
+    /// Errors with [`TimeError::InvalidTime`] if the hour or minute is out of range.
+    pub fn weekdays(hour: u32, minute: u32, time_zone: Tz) -> TimeResult<Self> {
+        use Weekday::{Fri, Mon, Thu, Tue, Wed};
+
+        Self::on_days(&[Mon, Tue, Wed, Thu, Fri], hour, minute, time_zone)
+    }
+
+    /// On each of the `days` at `hour`:`minute`.
+    ///
+    /// Errors with [`TimeError::InvalidTime`] if the hour or minute is out of range,
+    /// or no days are given.
+    pub fn on_days(days: &[Weekday], hour: u32, minute: u32, time_zone: Tz) -> TimeResult<Self> {
+        let time = NaiveTime::from_hms_opt(hour, minute, 0).ok_or(TimeError::InvalidTime)?;
+        if days.is_empty() {
+            return Err(TimeError::InvalidTime);
+        }
+
+        Ok(Self {
+            days: days
+                .iter()
+                .fold(0, |days, day| days | 1 << day.num_days_from_monday()),
+            time,
+            time_zone,
+        })
+    }
+
+    /// Whether the schedule occurs on `day`.
+    pub fn includes(&self, day: Weekday) -> bool {
+        self.days & 1 << day.num_days_from_monday() != 0
+    }
+
+    /// The first occurrence of the schedule strictly after `instant`.
+    pub fn next_after(&self, instant: Instant) -> TimeResult<Instant> {
+        let local = local_date_time(instant, self.time_zone)?;
+
+        // the same day of the next week is the latest the next occurrence can be
+        for offset in 0..=7 {
+            let date = local
+                .date()
+                .checked_add_days(Days::new(offset))
+                .ok_or(TimeError::InvalidInstant)?;
+            if !self.includes(date.weekday()) {
+                continue;
+            }
+
+            let occurrence = resolve(date.and_time(self.time), self.time_zone)?;
+            if (occurrence.seconds, occurrence.nanos) > (instant.seconds, instant.nanos) {
+                return Ok(occurrence);
+            }
+        }
+
+        unreachable!("a schedule with at least one day occurs within a week")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, TimeZone, Utc};
+    use chrono_tz::Europe::London;
+
+    use super::*;
+
+    fn instant(year: i32, month: u32, day: u32, hour: u32, min: u32) -> Instant {
+        Utc.with_ymd_and_hms(year, month, day, hour, min, 0)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    fn utc(instant: Instant) -> DateTime<Utc> {
+        instant.try_into().unwrap()
+    }
+
+    #[test]
+    fn weekdays_skip_the_weekend() {
+        let schedule = Schedule::weekdays(9, 0, London).unwrap();
+
+        // Friday 10:00 GMT
+        let next = schedule.next_after(instant(2024, 1, 5, 10, 0)).unwrap();
+
+        // Monday 09:00 GMT
+        assert_eq!(utc(next), utc(instant(2024, 1, 8, 9, 0)));
+    }
+
+    #[test]
+    fn later_the_same_day() {
+        let schedule = Schedule::daily(9, 0, London).unwrap();
+
+        let next = schedule.next_after(instant(2024, 1, 5, 8, 59)).unwrap();
+
+        assert_eq!(utc(next), utc(instant(2024, 1, 5, 9, 0)));
+    }
+
+    #[test]
+    fn strictly_after() {
+        let schedule = Schedule::daily(9, 0, London).unwrap();
+
+        let next = schedule.next_after(instant(2024, 1, 5, 9, 0)).unwrap();
+
+        assert_eq!(utc(next), utc(instant(2024, 1, 6, 9, 0)));
+    }
+
+    #[test]
+    fn across_daylight_saving_time() {
+        let schedule = Schedule::daily(9, 0, London).unwrap();
+
+        // 09:00 GMT, the clocks go forward overnight
+        let next = schedule.next_after(instant(2024, 3, 30, 9, 0)).unwrap();
+
+        // 09:00 BST
+        assert_eq!(utc(next), utc(instant(2024, 3, 31, 8, 0)));
+    }
+
+    #[test]
+    fn once_a_week() {
+        let schedule = Schedule::on_days(&[Weekday::Wed], 18, 30, London).unwrap();
+
+        // Wednesday 19:00 GMT
+        let next = schedule.next_after(instant(2024, 1, 3, 19, 0)).unwrap();
+
+        assert_eq!(utc(next), utc(instant(2024, 1, 10, 18, 30)));
+    }
+
+    #[test]
+    fn invalid_schedules() {
+        assert_eq!(
+            Schedule::daily(24, 0, London).unwrap_err(),
+            TimeError::InvalidTime
+        );
+        assert_eq!(
+            Schedule::on_days(&[], 9, 0, London).unwrap_err(),
+            TimeError::InvalidTime
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! The number of allocations it takes to tick at the display's refresh rate, counted by a
+//! global allocator, on the test's own thread only.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // the count may already be gone while the thread is exiting
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// The average number of allocations for each of `ticks` calls to `tick`
+fn allocations_per_tick(ticks: usize, mut tick: impl FnMut()) -> f64 {
+    let before = ALLOCATIONS.with(Cell::get);
+    for _ in 0..ticks {
+        tick();
+    }
+    let after = ALLOCATIONS.with(Cell::get);
+    (after - before) as f64 / ticks as f64
+}
+
+mod app {
+    use crux_core::macros::Effect;
+    use crux_time::{Duration, Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize)]
+    pub enum Event {
+        StartTimer,
+        StartFrames,
+        Tick(TimeResponse),
+        Frame(TimeResponse),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub frames: usize,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::StartTimer | Event::Tick(_) => {
+                    caps.time
+                        .notify_after(Duration::new(16_666_667), Event::Tick);
+                }
+                Event::StartFrames => {
+                    caps.time.request_animation_frames(Event::Frame);
+                }
+                Event::Frame(_) => model.frames += 1,
+            }
+        }
+
+        fn view(&self, _model: &Model) {}
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::{Core, Request};
+    use crux_time::{Duration, TimeRequest, TimeResponse, TimerId};
+
+    use crate::{
+        allocations_per_tick,
+        app::{App, Effect, Event},
+    };
+
+    fn time_request(mut effects: Vec<Effect>) -> Request<TimeRequest> {
+        let Some(Effect::Time(request)) = effects.pop() else {
+            panic!("expected a time request");
+        };
+        request
+    }
+
+    #[test]
+    fn timer_set_again_on_every_tick() {
+        let core = Core::<Effect, App>::new();
+        let mut request = Some(time_request(core.process_event(Event::StartTimer)));
+        let mut tick = || {
+            let mut current = request.take().unwrap();
+            let effects = core.resolve(
+                &mut current,
+                TimeResponse::DurationElapsed { id: TimerId(1) },
+            );
+            request = Some(time_request(effects));
+        };
+        // let the channels and the executor grow to their working size
+        allocations_per_tick(100, &mut tick);
+
+        // the state the new timer shares with its request, and the list of effects returned
+        // to the shell. The timer's task reuses the slot of the one before it
+        let allocations = allocations_per_tick(1000, &mut tick);
+        assert!(allocations < 2.5, "{allocations} allocations per tick");
+    }
+
+    #[test]
+    fn animation_frames() {
+        let core = Core::<Effect, App>::new();
+        let mut request = time_request(core.process_event(Event::StartFrames));
+        let mut frame = 0;
+        let mut tick = || {
+            frame += 1;
+            let frame = TimeResponse::AnimationFrame {
+                id: TimerId(1),
+                timestamp: Duration::new(frame * 16_666_667),
+            };
+            assert!(core.resolve(&mut request, frame).is_empty());
+        };
+        allocations_per_tick(100, &mut tick);
+
+        // only the channels' occasional new blocks
+        let allocations = allocations_per_tick(1000, &mut tick);
+        assert!(allocations < 0.5, "{allocations} allocations per tick");
+    }
+}
@@ -0,0 +1,165 @@
+mod app {
+    use crux_core::macros::Effect;
+    use crux_time::{Duration, MissedTicks, Time, TimeResponse, TimerId};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize)]
+    pub enum Event {
+        Start,
+        Stop,
+        Background,
+        Foreground,
+        Frame(TimeResponse),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub frames: Option<TimerId>,
+        pub started_at: Option<Duration>,
+        pub elapsed_millis: u64,
+        pub paused: bool,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Start => {
+                    model.frames = Some(caps.time.request_animation_frames(Event::Frame));
+                }
+                Event::Stop => {
+                    if let Some(frames) = model.frames {
+                        caps.time.clear(frames);
+                    }
+                }
+                Event::Background => {
+                    if let Some(frames) = model.frames {
+                        caps.time.pause(frames);
+                    }
+                }
+                Event::Foreground => {
+                    if let Some(frames) = model.frames {
+                        caps.time.resume(frames, MissedTicks::Skip);
+                    }
+                }
+                Event::Frame(TimeResponse::AnimationFrame { timestamp, .. }) => {
+                    let started_at = *model.started_at.get_or_insert(timestamp);
+                    model.elapsed_millis =
+                        (timestamp.as_nanos() - started_at.as_nanos()) / 1_000_000;
+                }
+                Event::Frame(TimeResponse::AnimationPaused { .. }) => model.paused = true,
+                Event::Frame(TimeResponse::AnimationResumed { .. }) => model.paused = false,
+                Event::Frame(TimeResponse::Cleared { .. }) => model.frames = None,
+                Event::Frame(_) => {}
+            }
+        }
+
+        fn view(&self, _model: &Model) {}
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+    }
+}
+
+mod tests {
+    use crux_core::testing::AppTester;
+    use crux_time::{Duration, MissedTicks, TimeRequest, TimeResponse};
+
+    use crate::app::{App, Effect, Event, Model};
+
+    fn frame(id: crux_time::TimerId, millis: u64) -> TimeResponse {
+        TimeResponse::AnimationFrame {
+            id,
+            timestamp: Duration::from_millis(millis).unwrap(),
+        }
+    }
+
+    #[test]
+    fn delivers_frames_until_cleared() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let Effect::Time(mut request) = app.update(Event::Start, &mut model).expect_one_effect();
+        let id = model.frames.unwrap();
+        assert_eq!(request.operation, TimeRequest::AnimationFrames { id });
+
+        for millis in [1000, 1016, 1033] {
+            let update = app.resolve(&mut request, frame(id, millis)).unwrap();
+            for event in update.events {
+                app.update(event, &mut model).assert_empty();
+            }
+        }
+        assert_eq!(model.elapsed_millis, 33);
+
+        let Effect::Time(clear) = app.update(Event::Stop, &mut model).expect_one_effect();
+        assert_eq!(clear.operation, TimeRequest::Clear { id });
+
+        let update = app
+            .resolve(&mut request, TimeResponse::Cleared { id })
+            .unwrap();
+        for event in update.events {
+            app.update(event, &mut model).assert_empty();
+        }
+        assert!(model.frames.is_none());
+
+        // the subscription has ended, so later frames are an error
+        assert!(app.resolve(&mut request, frame(id, 1050)).is_err());
+    }
+
+    #[test]
+    fn passes_pause_and_resume_to_the_app() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let Effect::Time(mut request) = app.update(Event::Start, &mut model).expect_one_effect();
+        let id = model.frames.unwrap();
+
+        let event = app
+            .resolve(&mut request, TimeResponse::AnimationPaused { id })
+            .unwrap()
+            .expect_one_event();
+        app.update(event, &mut model).assert_empty();
+        assert!(model.paused);
+
+        let event = app
+            .resolve(&mut request, TimeResponse::AnimationResumed { id })
+            .unwrap()
+            .expect_one_event();
+        app.update(event, &mut model).assert_empty();
+        assert!(!model.paused);
+    }
+
+    #[test]
+    fn pauses_and_resumes_the_subscription() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let _ = app.update(Event::Start, &mut model).expect_one_effect();
+        let id = model.frames.unwrap();
+
+        let Effect::Time(pause) = app
+            .update(Event::Background, &mut model)
+            .expect_one_effect();
+        assert_eq!(pause.operation, TimeRequest::Pause { id });
+
+        let Effect::Time(resume) = app
+            .update(Event::Foreground, &mut model)
+            .expect_one_effect();
+        assert_eq!(
+            resume.operation,
+            TimeRequest::Resume {
+                id,
+                missed: MissedTicks::Skip
+            }
+        );
+    }
+}
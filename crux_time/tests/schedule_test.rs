@@ -0,0 +1,215 @@
+#[cfg(feature = "calendar")]
+mod app {
+    use chrono_tz::Europe::London;
+    use crux_core::macros::Effect;
+    use crux_time::{schedule::Schedule, Duration, Time, TimeResponse, TimerId};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize)]
+    pub enum Event {
+        Start,
+        StartHourly,
+        Stop,
+        Reminder(TimeResponse),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        pub reminders: usize,
+        pub timer: Option<TimerId>,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = ();
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::Start => {
+                    let schedule = Schedule::weekdays(9, 0, London).unwrap();
+                    model.timer = Some(caps.time.notify_on_schedule(schedule, Event::Reminder));
+                }
+                Event::StartHourly => {
+                    let schedule = Schedule::weekdays(9, 15, London).unwrap();
+                    let time = caps
+                        .time
+                        .with_resolution(Duration::from_secs(3600).unwrap());
+                    model.timer = Some(time.notify_on_schedule(schedule, Event::Reminder));
+                }
+                Event::Stop => {
+                    if let Some(timer) = model.timer.take() {
+                        caps.time.clear(timer);
+                    }
+                }
+                Event::Reminder(TimeResponse::InstantArrived { .. }) => model.reminders += 1,
+                Event::Reminder(_) => {}
+            }
+        }
+
+        fn view(&self, _model: &Model) {}
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+    }
+}
+
+#[cfg(feature = "calendar")]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+    use crux_core::testing::AppTester;
+    use crux_time::{Instant, TimeRequest, TimeResponse};
+
+    use crate::app::{App, Effect, Event, Model};
+
+    fn instant(year: i32, month: u32, day: u32, hour: u32, min: u32) -> Instant {
+        Utc.with_ymd_and_hms(year, month, day, hour, min, 0)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    fn utc(instant: Instant) -> DateTime<Utc> {
+        instant.try_into().unwrap()
+    }
+
+    #[test]
+    fn notifies_of_one_occurrence_at_a_time() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let mut update = app.update(Event::Start, &mut model);
+        let Effect::Time(mut now) = update.effects.remove(0);
+        assert_eq!(now.operation, TimeRequest::Now);
+
+        // Friday 10:00 GMT
+        let update = app
+            .resolve(&mut now, TimeResponse::Now(instant(2024, 1, 5, 10, 0)))
+            .unwrap();
+        let mut effects = update.effects;
+        assert_eq!(effects.len(), 1);
+        let Effect::Time(mut request) = effects.remove(0);
+
+        let TimeRequest::NotifyAt { id, instant: at } = request.operation.clone() else {
+            panic!("expected a NotifyAt request");
+        };
+        assert_eq!(Some(id), model.timer);
+        assert_eq!(utc(at), utc(instant(2024, 1, 8, 9, 0)));
+
+        let update = app
+            .resolve(&mut request, TimeResponse::InstantArrived { id })
+            .unwrap();
+        for event in update.events {
+            let _ = app.update(event, &mut model);
+        }
+        assert_eq!(model.reminders, 1);
+
+        // re-armed for Tuesday, with the same timer
+        let mut effects = update.effects;
+        assert_eq!(effects.len(), 1);
+        let Effect::Time(mut request) = effects.remove(0);
+        assert_eq!(
+            request.operation,
+            TimeRequest::NotifyAt {
+                id,
+                instant: instant(2024, 1, 9, 9, 0)
+            }
+        );
+
+        let update = app.update(Event::Stop, &mut model);
+        let Effect::Time(clear) = &update.effects[0];
+        assert_eq!(clear.operation, TimeRequest::Clear { id });
+
+        let update = app
+            .resolve(&mut request, TimeResponse::Cleared { id })
+            .unwrap();
+        assert_eq!(update.events.len(), 1);
+        assert!(update.effects.is_empty());
+    }
+
+    #[test]
+    fn skips_occurrences_missed_while_paused() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let mut update = app.update(Event::Start, &mut model);
+        let Effect::Time(mut now) = update.effects.remove(0);
+
+        // Friday 10:00 GMT
+        let update = app
+            .resolve(&mut now, TimeResponse::Now(instant(2024, 1, 5, 10, 0)))
+            .unwrap();
+        let Effect::Time(mut request) = update.effects.into_iter().next().unwrap();
+        let id = model.timer.unwrap();
+
+        // the app was paused over Monday's occurrence, and resumed on Tuesday at 12:00
+        let mut update = app
+            .resolve(&mut request, TimeResponse::Missed { id })
+            .unwrap();
+        assert!(update.events.is_empty());
+        let Effect::Time(mut now) = update.effects.remove(0);
+        assert_eq!(now.operation, TimeRequest::Now);
+
+        let update = app
+            .resolve(&mut now, TimeResponse::Now(instant(2024, 1, 9, 12, 0)))
+            .unwrap();
+        let Effect::Time(request) = update.effects.into_iter().next().unwrap();
+        assert_eq!(
+            request.operation,
+            TimeRequest::NotifyAt {
+                id,
+                instant: instant(2024, 1, 10, 9, 0)
+            }
+        );
+        assert_eq!(model.reminders, 0);
+    }
+
+    #[test]
+    fn occurrences_follow_the_precise_time() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        // to the hour, Friday 09:30 GMT would be 09:00, before the 09:15 occurrence
+        let mut update = app.update(Event::StartHourly, &mut model);
+        let Effect::Time(mut now) = update.effects.remove(0);
+        assert_eq!(now.operation, TimeRequest::Now);
+
+        let update = app
+            .resolve(&mut now, TimeResponse::Now(instant(2024, 1, 5, 9, 30)))
+            .unwrap();
+        let Effect::Time(request) = update.effects.into_iter().next().unwrap();
+        assert_eq!(
+            request.operation,
+            TimeRequest::NotifyAt {
+                id: model.timer.unwrap(),
+                instant: instant(2024, 1, 8, 9, 15)
+            }
+        );
+    }
+
+    #[test]
+    fn ends_on_an_unexpected_response() {
+        let app = AppTester::<App, Effect>::default();
+        let mut model = Model::default();
+
+        let mut update = app.update(Event::Start, &mut model);
+        let Effect::Time(mut now) = update.effects.remove(0);
+
+        let update = app
+            .resolve(
+                &mut now,
+                TimeResponse::Cleared {
+                    id: model.timer.unwrap(),
+                },
+            )
+            .unwrap();
+        assert!(update.events.is_empty());
+        assert!(update.effects.is_empty());
+    }
+}
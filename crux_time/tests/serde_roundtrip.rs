@@ -0,0 +1,196 @@
+//! Round-trip tests for the types crossing the FFI boundary.
+//!
+//! New variants of `TimeRequest` and `TimeResponse` must be added at the end, so that
+//! messages serialized by Shells built against an older version keep deserializing.
+//! The pinned variant indexes below fail if a variant is inserted or reordered.
+
+use bincode::{DefaultOptions, Options};
+use proptest::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crux_time::{Duration, FormatStyle, Instant, MissedTicks, TimeRequest, TimeResponse, TimerId};
+
+// the same options as used by `crux_core::bridge::Bridge`
+fn options() -> impl Options + Copy {
+    DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+}
+
+fn assert_roundtrip<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let bytes = options().serialize(value).unwrap();
+    assert_eq!(&options().deserialize::<T>(&bytes).unwrap(), value);
+
+    let json = serde_json::to_string(value).unwrap();
+    assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value);
+}
+
+fn variant_index<T: Serialize>(value: &T) -> u32 {
+    let bytes = options().serialize(value).unwrap();
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+fn instant() -> impl Strategy<Value = Instant> {
+    (any::<u64>(), 0..1_000_000_000u32)
+        .prop_map(|(seconds, nanos)| Instant::new(seconds, nanos).unwrap())
+}
+
+fn duration() -> impl Strategy<Value = Duration> {
+    any::<u64>().prop_map(Duration::new)
+}
+
+fn timer_id() -> impl Strategy<Value = TimerId> {
+    any::<u64>().prop_map(TimerId)
+}
+
+fn format_style() -> impl Strategy<Value = FormatStyle> {
+    prop_oneof![
+        Just(FormatStyle::Relative),
+        Just(FormatStyle::ShortDate),
+        Just(FormatStyle::LongDate),
+        Just(FormatStyle::ShortTime),
+        Just(FormatStyle::ShortDateTime),
+    ]
+}
+
+fn missed_ticks() -> impl Strategy<Value = MissedTicks> {
+    prop_oneof![Just(MissedTicks::Skip), Just(MissedTicks::CatchUp)]
+}
+
+fn time_request() -> impl Strategy<Value = TimeRequest> {
+    prop_oneof![
+        Just(TimeRequest::Now),
+        (timer_id(), instant()).prop_map(|(id, instant)| TimeRequest::NotifyAt { id, instant }),
+        (timer_id(), duration())
+            .prop_map(|(id, duration)| TimeRequest::NotifyAfter { id, duration }),
+        timer_id().prop_map(|id| TimeRequest::Clear { id }),
+        Just(TimeRequest::TimeZone),
+        (instant(), format_style())
+            .prop_map(|(instant, style)| TimeRequest::Format { instant, style }),
+        timer_id().prop_map(|id| TimeRequest::AnimationFrames { id }),
+        (any::<String>(), instant())
+            .prop_map(|(zone, instant)| TimeRequest::UtcOffset { zone, instant }),
+        duration().prop_map(|resolution| TimeRequest::NowQuantized { resolution }),
+        (any::<String>(), any::<Option<String>>(), format_style()).prop_map(
+            |(text, locale, style)| TimeRequest::Parse {
+                text,
+                locale,
+                style
+            }
+        ),
+        timer_id().prop_map(|id| TimeRequest::Pause { id }),
+        (timer_id(), missed_ticks()).prop_map(|(id, missed)| TimeRequest::Resume { id, missed }),
+    ]
+}
+
+fn time_response() -> impl Strategy<Value = TimeResponse> {
+    prop_oneof![
+        instant().prop_map(TimeResponse::Now),
+        timer_id().prop_map(|id| TimeResponse::InstantArrived { id }),
+        timer_id().prop_map(|id| TimeResponse::DurationElapsed { id }),
+        timer_id().prop_map(|id| TimeResponse::Cleared { id }),
+        any::<String>().prop_map(|name| TimeResponse::TimeZone { name }),
+        any::<String>().prop_map(|text| TimeResponse::Formatted { text }),
+        (timer_id(), duration())
+            .prop_map(|(id, timestamp)| TimeResponse::AnimationFrame { id, timestamp }),
+        timer_id().prop_map(|id| TimeResponse::AnimationPaused { id }),
+        timer_id().prop_map(|id| TimeResponse::AnimationResumed { id }),
+        any::<i32>().prop_map(|seconds| TimeResponse::UtcOffset { seconds }),
+        any::<String>().prop_map(|name| TimeResponse::UnknownTimeZone { name }),
+        instant().prop_map(|instant| TimeResponse::Parsed { instant }),
+        any::<String>().prop_map(|reason| TimeResponse::ParseFailed { reason }),
+        timer_id().prop_map(|id| TimeResponse::Missed { id }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn time_request_roundtrip(request in time_request()) {
+        assert_roundtrip(&request);
+    }
+
+    #[test]
+    fn time_response_roundtrip(response in time_response()) {
+        assert_roundtrip(&response);
+    }
+}
+
+#[test]
+fn time_request_variant_indexes() {
+    let id = TimerId(1);
+    let instant = Instant::new(1, 0).unwrap();
+    let duration = Duration::new(1);
+
+    assert_eq!(variant_index(&TimeRequest::Now), 0);
+    assert_eq!(variant_index(&TimeRequest::NotifyAt { id, instant }), 1);
+    assert_eq!(variant_index(&TimeRequest::NotifyAfter { id, duration }), 2);
+    assert_eq!(variant_index(&TimeRequest::Clear { id }), 3);
+    assert_eq!(variant_index(&TimeRequest::TimeZone), 4);
+    assert_eq!(
+        variant_index(&TimeRequest::Format {
+            instant,
+            style: FormatStyle::Relative
+        }),
+        5
+    );
+    assert_eq!(variant_index(&TimeRequest::AnimationFrames { id }), 6);
+    let zone = "Europe/London".to_string();
+    assert_eq!(variant_index(&TimeRequest::UtcOffset { zone, instant }), 7);
+    assert_eq!(
+        variant_index(&TimeRequest::NowQuantized {
+            resolution: duration
+        }),
+        8
+    );
+    assert_eq!(
+        variant_index(&TimeRequest::Parse {
+            text: "9 January 2024".to_string(),
+            locale: Some("en-GB".to_string()),
+            style: FormatStyle::LongDate
+        }),
+        9
+    );
+    assert_eq!(variant_index(&TimeRequest::Pause { id }), 10);
+    let missed = MissedTicks::Skip;
+    assert_eq!(variant_index(&TimeRequest::Resume { id, missed }), 11);
+}
+
+#[test]
+fn time_response_variant_indexes() {
+    let id = TimerId(1);
+    let instant = Instant::new(1, 0).unwrap();
+    let name = "Europe/London".to_string();
+
+    assert_eq!(variant_index(&TimeResponse::Now(instant)), 0);
+    assert_eq!(variant_index(&TimeResponse::InstantArrived { id }), 1);
+    assert_eq!(variant_index(&TimeResponse::DurationElapsed { id }), 2);
+    assert_eq!(variant_index(&TimeResponse::Cleared { id }), 3);
+    assert_eq!(variant_index(&TimeResponse::TimeZone { name }), 4);
+    let text = "2 hours ago".to_string();
+    assert_eq!(variant_index(&TimeResponse::Formatted { text }), 5);
+    let timestamp = Duration::new(1);
+    assert_eq!(
+        variant_index(&TimeResponse::AnimationFrame { id, timestamp }),
+        6
+    );
+    assert_eq!(variant_index(&TimeResponse::AnimationPaused { id }), 7);
+    assert_eq!(variant_index(&TimeResponse::AnimationResumed { id }), 8);
+    assert_eq!(variant_index(&TimeResponse::UtcOffset { seconds: 3600 }), 9);
+    let name = "Europe/Nowhere".to_string();
+    assert_eq!(variant_index(&TimeResponse::UnknownTimeZone { name }), 10);
+    assert_eq!(variant_index(&TimeResponse::Parsed { instant }), 11);
+    let reason = "not a date".to_string();
+    assert_eq!(variant_index(&TimeResponse::ParseFailed { reason }), 12);
+    assert_eq!(variant_index(&TimeResponse::Missed { id }), 13);
+}
+
+#[test]
+fn unknown_variant_is_an_error() {
+    let bytes = options().serialize(&u32::MAX).unwrap();
+
+    assert!(options().deserialize::<TimeResponse>(&bytes).is_err());
+    assert!(serde_json::from_str::<TimeResponse>(r#""fromTheFuture""#).is_err());
+}
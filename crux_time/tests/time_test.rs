@@ -13,6 +13,7 @@ mod shared {
     pub enum Event {
         Get,
         GetAsync,
+        GetToTheMinute,
         Set(TimeResponse),
 
         StartDebounce,
@@ -64,6 +65,10 @@ mod shared {
                         ctx.update_app(Event::Set(time.now_async().await));
                     }
                 }),
+                Event::GetToTheMinute => {
+                    let minute = crux_time::Duration::from_secs(60).expect("valid duration");
+                    caps.time.with_resolution(minute).now(Event::Set)
+                }
                 Event::Set(time) => {
                     if let TimeResponse::Now(time) = time {
                         let time: DateTime<Utc> = time.try_into().unwrap();
@@ -179,7 +184,7 @@ mod tests {
     };
     use chrono::{DateTime, Utc};
     use crux_core::{testing::AppTester, Core};
-    use crux_time::TimeResponse;
+    use crux_time::{Duration, Instant, TimeRequest, TimeResponse};
 
     #[test]
     pub fn test_time() {
@@ -207,6 +212,27 @@ mod tests {
         assert_eq!(app.view(&model).time, "2022-12-01T01:47:12.746202562+00:00");
     }
 
+    #[test]
+    pub fn test_time_with_resolution() {
+        let app = AppTester::<App, _>::default();
+        let mut model = Model::default();
+
+        let request = &mut app
+            .update(Event::GetToTheMinute, &mut model)
+            .expect_one_effect()
+            .expect_time();
+
+        let resolution = Duration::from_secs(60).unwrap();
+        assert_eq!(request.operation, TimeRequest::NowQuantized { resolution });
+
+        let now: DateTime<Utc> = "2022-12-01T01:47:12.746202562+00:00".parse().unwrap();
+        let now: Instant = now.try_into().unwrap();
+        let response = TimeResponse::Now(now.quantized(resolution));
+        let _update = app.resolve_to_event_then_update(request, response, &mut model);
+
+        assert_eq!(app.view(&model).time, "2022-12-01T01:47:00+00:00");
+    }
+
     #[test]
     pub fn test_debounce_timer() {
         let app = AppTester::<App, _>::default();
@@ -272,4 +298,17 @@ mod tests {
         assert!(!model.debounce_complete);
         assert!(model.debounce_time_id.is_none());
     }
+
+    #[test]
+    pub fn timer_ids_differ_between_cores() {
+        // e.g. two instances of the app in one shell
+        let timer_id = |seed| {
+            let app = AppTester::<App, _>::default().with_seed(seed);
+            let mut model = Model::default();
+            let _ = app.update(Event::StartDebounce, &mut model);
+            model.debounce_time_id.unwrap()
+        };
+
+        assert_ne!(timer_id(1), timer_id(2));
+    }
 }
@@ -0,0 +1,206 @@
+//! Feeds arbitrary bytes from the shell to the bridge, as events and as responses to the
+//! effects the app requested, bare or in envelopes, and as the startup configuration and
+//! view slice paths. Malformed input must be reported as an error, never panic.
+//!
+//! Run with `cargo +nightly fuzz run bridge` from the `fuzz` directory.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use crux_core::{
+    bridge::{Bridge, Envelope, Request},
+    Core,
+};
+use libfuzzer_sys::fuzz_target;
+
+use app::{App, Effect, EffectFfi, Event};
+
+mod app {
+    use crux_core::{macros::Effect, render::Render};
+    use crux_http::Http;
+    use crux_kv::{error::KeyValueError, KeyValue};
+    use crux_time::{Time, TimeResponse};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Default)]
+    pub struct App;
+
+    #[derive(Serialize, Deserialize)]
+    pub enum Event {
+        GetTime,
+        Fetch(String),
+        Load(String),
+        #[serde(skip)]
+        SetTime(TimeResponse),
+        #[serde(skip)]
+        SetItems(crux_http::Result<crux_http::Response<Vec<String>>>),
+        #[serde(skip)]
+        SetValue(Result<Option<Vec<u8>>, KeyValueError>),
+    }
+
+    #[derive(Default)]
+    pub struct Model {
+        responses: usize,
+    }
+
+    impl crux_core::App for App {
+        type Event = Event;
+        type Model = Model;
+        type ViewModel = usize;
+        type Capabilities = Capabilities;
+
+        fn update(&self, event: Event, model: &mut Model, caps: &Capabilities) {
+            match event {
+                Event::GetTime => caps.time.now(Event::SetTime),
+                Event::Fetch(url) => match url.parse::<crux_http::http::Url>() {
+                    Ok(url) => caps
+                        .http
+                        .get(url)
+                        .expect_json::<Vec<String>>()
+                        .send(Event::SetItems),
+                    Err(_) => caps.render.render(),
+                },
+                Event::Load(key) => caps.key_value.get(key, Event::SetValue),
+                Event::SetTime(response) => {
+                    model.responses += usize::from(matches!(response, TimeResponse::Now(_)));
+                    caps.render.render();
+                }
+                Event::SetItems(result) => {
+                    model.responses += usize::from(result.is_ok());
+                    caps.render.render();
+                }
+                Event::SetValue(result) => {
+                    model.responses += usize::from(result.is_ok());
+                    caps.render.render();
+                }
+            }
+        }
+
+        fn view(&self, model: &Model) -> usize {
+            model.responses
+        }
+    }
+
+    #[derive(Effect)]
+    pub struct Capabilities {
+        pub time: Time<Event>,
+        pub http: Http<Event>,
+        pub key_value: KeyValue<Event>,
+        pub render: Render<Event>,
+    }
+}
+
+/// A message from the shell
+#[derive(Arbitrary, Debug)]
+enum Message {
+    /// A well formed event, so that the fuzzer reaches the effects
+    Event(ShellEvent),
+    /// Any bytes, as an event
+    Bytes(Vec<u8>),
+    /// Any bytes, as the response to one of the requests the core has sent
+    Response { request: usize, output: Vec<u8> },
+    /// Any bytes, as the response to any id
+    UnknownResponse { id: u32, output: Vec<u8> },
+    /// A well formed event, in an envelope
+    EnvelopedEvent {
+        sequence: u64,
+        trace_id: Option<String>,
+        event: ShellEvent,
+    },
+    /// Any bytes, as an enveloped event
+    EnvelopedBytes(Vec<u8>),
+    /// Any bytes, in an envelope, as the response to one of the requests the core has sent
+    EnvelopedResponse { request: usize, output: Vec<u8> },
+    /// Any bytes, as the startup configuration
+    Init(Vec<u8>),
+    /// Any bytes, as the paths of a view slice
+    ViewSlice(Vec<u8>),
+}
+
+#[derive(Arbitrary, Debug)]
+enum ShellEvent {
+    GetTime,
+    Fetch(String),
+    Load(String),
+}
+
+impl From<ShellEvent> for Event {
+    fn from(event: ShellEvent) -> Self {
+        match event {
+            ShellEvent::GetTime => Event::GetTime,
+            ShellEvent::Fetch(url) => Event::Fetch(url),
+            ShellEvent::Load(key) => Event::Load(key),
+        }
+    }
+}
+
+fn envelope(sequence: u64, trace_id: Option<String>, payload: Vec<u8>) -> Vec<u8> {
+    let envelope = Envelope {
+        sequence,
+        in_reply_to: None,
+        trace_id,
+        payload,
+        causes: vec![],
+    };
+    bincode::serialize(&envelope).expect("envelope serializes")
+}
+
+fn open(reply: Vec<u8>) -> Vec<u8> {
+    let envelope: Envelope = bincode::deserialize(&reply).expect("the core sends valid envelopes");
+    envelope.payload
+}
+
+fuzz_target!(|messages: Vec<Message>| {
+    let bridge: Bridge<Effect, App> = Bridge::new(Core::new());
+    let mut requests = vec![];
+
+    for message in messages {
+        let result = match message {
+            Message::Event(event) => {
+                let event = bincode::serialize(&Event::from(event)).expect("event serializes");
+                bridge.try_process_event(&event)
+            }
+            Message::Bytes(event) => bridge.try_process_event(&event),
+            Message::Response { request, output } if !requests.is_empty() => {
+                let id = requests[request % requests.len()];
+                bridge.try_handle_response(id, &output)
+            }
+            Message::Response { .. } => continue,
+            Message::UnknownResponse { id, output } => bridge.try_handle_response(id, &output),
+            Message::EnvelopedEvent {
+                sequence,
+                trace_id,
+                event,
+            } => {
+                let event = bincode::serialize(&Event::from(event)).expect("event serializes");
+                bridge
+                    .try_process_event_enveloped(&envelope(sequence, trace_id, event))
+                    .map(open)
+            }
+            Message::EnvelopedBytes(message) => {
+                bridge.try_process_event_enveloped(&message).map(open)
+            }
+            Message::EnvelopedResponse { request, output } if !requests.is_empty() => {
+                let id = requests[request % requests.len()];
+                bridge
+                    .try_handle_response_enveloped(id, &envelope(0, None, output))
+                    .map(open)
+            }
+            Message::EnvelopedResponse { .. } => continue,
+            Message::Init(config) => {
+                let _ = bridge.try_init(&config);
+                continue;
+            }
+            Message::ViewSlice(paths) => {
+                let _ = bridge.try_view_slice(&paths);
+                continue;
+            }
+        };
+
+        if let Ok(bytes) = result {
+            let sent: Vec<Request<EffectFfi>> =
+                bincode::deserialize(&bytes).expect("the core sends valid requests");
+            requests.extend(sent.iter().map(|request| request.id.0));
+        }
+    }
+});